@@ -9,5 +9,8 @@
 pub use glam;
 
 pub mod metaballs;
+pub mod metaballs_3d;
 pub mod raytracing;
+pub mod sdf;
+pub mod tunnel;
 pub mod utils;
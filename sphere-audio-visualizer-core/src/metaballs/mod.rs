@@ -2,7 +2,10 @@
 
 use glam::{Vec2, Vec3A};
 
-use crate::utils::math::{dot2, inverse_sqrt};
+use crate::utils::{
+    math::{dot2, inverse_sqrt},
+    random::random,
+};
 
 /// Stores the properties of a Metaball
 #[repr(C, align(16))]
@@ -24,7 +27,11 @@ impl Metaball {
 pub struct Metaballs<'a> {
     color: Vec3A,
     size: Vec2,
-    zoom: f32,
+    zoom: Vec2,
+    offset: Vec2,
+    loudness: f32,
+    frame: u32,
+    seed: u32,
     metaballs: &'a [Metaball],
 }
 
@@ -36,8 +43,16 @@ pub struct MetaballsArgs {
     pub color: Vec3A,
     /// Represents the size of the viewport in pixels
     pub size: Vec2,
-    /// Represents the zoom factor of the viewport
-    pub zoom: f32,
+    /// Represents the zoom factor of the viewport along each axis
+    pub zoom: Vec2,
+    /// Represents the offset of the viewport in pixels
+    pub offset: Vec2,
+    /// Represents the loudness by which the halo intensity is raised
+    pub loudness: f32,
+    /// Represents the index of the rendered frame
+    pub frame: u32,
+    /// Represents the seed of the rendered frame for stochastic effects
+    pub seed: u32,
 }
 
 impl<'a> Metaballs<'a> {
@@ -47,15 +62,30 @@ impl<'a> Metaballs<'a> {
             color: args.color,
             size: args.size,
             zoom: args.zoom,
+            offset: args.offset,
+            loudness: args.loudness,
+            frame: args.frame,
+            seed: args.seed,
             metaballs,
         }
     }
 
+    /// Returns the index of the rendered frame
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Returns a random number in `0.0..1.0` for the pixel at the given
+    /// position, which is reproducible for the seed of the frame
+    pub fn random(&self, sample: &Vec2, dimension: u32) -> f32 {
+        random(self.seed, sample.x as u32, sample.y as u32, dimension)
+    }
+
     /// Samples the color at the given sceen position
     pub fn sample(&self, sample: &Vec2) -> Vec3A {
         let mut value: f32 = 0.0;
 
-        let position = (*sample / self.size * 2.0 - 1.0) * self.zoom;
+        let position = ((*sample - self.offset) / self.size * 2.0 - 1.0) * self.zoom;
 
         for id in 0..self.metaballs.len() {
             let oc = position - self.metaballs[id].position;
@@ -65,7 +95,7 @@ impl<'a> Metaballs<'a> {
         }
 
         if value <= 0.75 {
-            self.color * value
+            self.color * value * (1.0 + self.loudness)
         } else {
             Vec3A::splat(1.0)
         }
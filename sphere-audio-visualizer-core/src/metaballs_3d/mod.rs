@@ -0,0 +1,286 @@
+//! Contains the definition of the 3D metaballs algorithm. Every metaball adds
+//! a smooth bump to a scalar field and the surface is the iso surface at which
+//! the field reaches a threshold, so nearby metaballs melt into each other.
+
+use glam::{vec3a, Vec2, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{
+        camera::{Camera, SceneCamera},
+        Ray,
+    },
+    utils::{
+        math::{dot, normalize, tonemap_filmic},
+        OptionPolyfill,
+    },
+};
+
+/// Defines the steepest slope of the falloff of a metaball with an influence
+/// of one, which bounds how fast the field can change along a ray
+const FALLOFF_SLOPE: f32 = 1.7173;
+
+/// Stores the properties of a 3D metaball
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+pub struct Metaball3D {
+    position: Vec3A,
+    radius: f32,
+    color: Vec3A,
+}
+
+impl Metaball3D {
+    /// Creates a new Instance
+    /// - `position` the position of the metaball
+    /// - `radius` the radius of the metaball if it stands alone
+    /// - `color` the color of the surface around the metaball
+    pub fn new(position: Vec3A, radius: f32, color: Vec3A) -> Self {
+        Self {
+            position,
+            radius,
+            color,
+        }
+    }
+
+    /// Returns the position of the metaball
+    pub fn position(&self) -> Vec3A {
+        self.position
+    }
+
+    /// Returns the radius of the metaball if it stands alone
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Returns the color of the surface around the metaball
+    pub fn color(&self) -> Vec3A {
+        self.color
+    }
+}
+
+/// Stores the properties of the 3D metaballs scene used for shader parameters
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct Metaballs3DArgs {
+    /// Represents the camera used
+    pub camera: SceneCamera,
+    /// Represents the color of the background, which also lights the scene
+    /// from all directions
+    pub background: Vec3A,
+    /// Represents the direction towards the light of the scene
+    pub light_direction: Vec3A,
+    /// Represents the color of the light of the scene
+    pub light_color: Vec3A,
+    /// Represents the distance at which a metaball stops contributing to the
+    /// field relative to its radius. Larger values melt the metaballs from
+    /// further away.
+    pub influence: f32,
+    /// Represents the field value of the surface. A metaball standing alone
+    /// reaches it at its radius.
+    pub threshold: f32,
+    /// Represents the step along a ray below which the ray hits the surface
+    /// relative to the distance travelled
+    pub epsilon: f32,
+    /// Represents the loudness by which the background brightness is raised
+    pub loudness: f32,
+    /// Represents the maximum number of marching steps per ray
+    pub steps: u32,
+    /// Represents the number of metaballs used
+    pub metaball_count: u32,
+    /// Represents whether the radiance is written without tonemapping. This
+    /// is non-zero for HDR outputs, which roll off the highlights themselves.
+    pub hdr: u32,
+}
+
+impl Metaballs3DArgs {
+    /// Returns the threshold at which a metaball standing alone has the
+    /// surface at its radius, if it stops contributing at `influence` times
+    /// its radius
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::metaballs_3d::Metaballs3DArgs;
+    ///
+    /// assert_eq!(Metaballs3DArgs::threshold(2.0), 0.421875);
+    /// assert_eq!(Metaballs3DArgs::threshold(1.0), 0.0);
+    /// ```
+    pub fn threshold(influence: f32) -> f32 {
+        let x = 1.0 - 1.0 / (influence * influence);
+
+        x * x * x
+    }
+}
+
+/// Describes the field at a point
+struct FieldSample {
+    value: f32,
+    color: Vec3A,
+}
+
+/// Implements the raymarching of the 3D metaballs field
+pub struct Metaballs3D<'a> {
+    args: Metaballs3DArgs,
+    metaballs: &'a [Metaball3D],
+}
+
+impl<'a> Metaballs3D<'a> {
+    /// Creates a new instance from shader parameters
+    pub fn from_args(args: Metaballs3DArgs, metaballs: &'a [Metaball3D]) -> Self {
+        Self { args, metaballs }
+    }
+
+    fn metaball_count(&self) -> usize {
+        (self.args.metaball_count as usize).min(self.metaballs.len())
+    }
+
+    /// Returns the contribution of a metaball at the squared distance
+    /// relative to the squared influence radius, which falls off smoothly to
+    /// zero at the influence radius
+    fn falloff(x: f32) -> f32 {
+        if x >= 1.0 {
+            return 0.0;
+        }
+
+        let y = 1.0 - x;
+
+        y * y * y
+    }
+
+    /// Returns the value of the field at the point and the colors of the
+    /// metaballs weighted by their contribution
+    fn sample_field(&self, point: &Vec3A) -> FieldSample {
+        let mut value = 0.0;
+        let mut color = vec3a(0.0, 0.0, 0.0);
+
+        for id in 0..self.metaball_count() {
+            let metaball = &self.metaballs[id];
+            let offset = *point - metaball.position;
+            let influence = metaball.radius * self.args.influence;
+            let contribution = Self::falloff(dot(&offset, &offset) / (influence * influence));
+
+            value += contribution;
+            color += metaball.color * contribution;
+        }
+
+        FieldSample { value, color }
+    }
+
+    /// Returns the value of the field at the point
+    pub fn field(&self, point: &Vec3A) -> f32 {
+        self.sample_field(point).value
+    }
+
+    /// Returns how far the ray can advance from the point without crossing
+    /// the surface. Within the influence of the metaballs the field changes
+    /// at most by their steepest slopes. The other metaballs don't contribute
+    /// before the ray enters their influence.
+    pub fn step(&self, point: &Vec3A) -> f32 {
+        let mut value = 0.0;
+        let mut slope = 0.0;
+        let mut outside = f32::INFINITY;
+
+        for id in 0..self.metaball_count() {
+            let metaball = &self.metaballs[id];
+            let offset = *point - metaball.position;
+            let influence = metaball.radius * self.args.influence;
+            let distance = dot(&offset, &offset).sqrt();
+
+            if distance < influence {
+                let x = distance / influence;
+
+                value += Self::falloff(x * x);
+                slope += FALLOFF_SLOPE / influence;
+            } else {
+                outside = outside.min(distance - influence);
+            }
+        }
+
+        if slope > 0.0 {
+            ((self.args.threshold - value) / slope).min(outside)
+        } else {
+            outside
+        }
+    }
+
+    /// Marches along the ray until it hits the surface. Returns the distance
+    /// along the ray to the hit.
+    pub fn march(&self, ray: &Ray) -> OptionPolyfill<f32> {
+        let mut t = ray.t_min();
+        let mut is_hit = false;
+
+        for _ in 0..self.args.steps {
+            let step = self.step(&ray.point_at(t));
+
+            // the steps shrink towards the surface, the precision is relaxed
+            // with the distance which saves steps where a pixel covers a
+            // large area
+            if step < self.args.epsilon * t.max(1.0) {
+                is_hit = true;
+                break;
+            }
+
+            t += step;
+
+            if t > ray.t_max() {
+                break;
+            }
+        }
+
+        OptionPolyfill::new(is_hit, t)
+    }
+
+    /// Returns the normal of the surface at the given point, which points
+    /// against the gradient of the field
+    pub fn normal(&self, point: &Vec3A) -> Vec3A {
+        let mut gradient = vec3a(0.0, 0.0, 0.0);
+
+        for id in 0..self.metaball_count() {
+            let metaball = &self.metaballs[id];
+            let offset = *point - metaball.position;
+            let influence = metaball.radius * self.args.influence;
+            let x = dot(&offset, &offset) / (influence * influence);
+
+            if x < 1.0 {
+                let y = 1.0 - x;
+
+                gradient -= offset * (6.0 * y * y / (influence * influence));
+            }
+        }
+
+        normalize(&-gradient)
+    }
+
+    /// Returns the radiance arriving along the ray
+    pub fn radiance(&self, ray: &Ray) -> Vec3A {
+        let background = self.args.background * (1.0 + self.args.loudness);
+        let hit = self.march(ray);
+
+        if hit.is_none() {
+            return background;
+        }
+
+        let position = ray.point_at(hit.unwrap());
+        let normal = self.normal(&position);
+        let field = self.sample_field(&position);
+        let albedo = field.color / field.value.max(f32::EPSILON);
+
+        let diffuse = dot(&normal, &self.args.light_direction).max(0.0);
+
+        // the rim brightens the silhouettes with the background, which keeps
+        // the blobs apart from it
+        let rim = (1.0 + dot(&ray.direction(), &normal)).powf(4.0);
+
+        albedo * (background * 0.5 + self.args.light_color * diffuse) + background * rim
+    }
+
+    /// Samples the color of a pixel at the given position
+    pub fn sample(&self, sample: &Vec2) -> Vec3A {
+        let radiance = self.radiance(&self.args.camera.prime_ray(sample));
+
+        match self.args.hdr {
+            0 => tonemap_filmic(&radiance),
+            _ => radiance,
+        }
+    }
+}
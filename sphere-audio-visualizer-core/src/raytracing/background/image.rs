@@ -0,0 +1,95 @@
+use core::f32::consts::{PI, TAU};
+
+use glam::{vec2, Vec2, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::utils::math::normalize;
+
+use super::Background;
+
+/// An environment map stores the light coming from every direction as an
+/// equirectangular image. It is implemented by the texture types of the
+/// different renderers.
+pub trait EnvironmentMap {
+    /// Samples the radiance at the texture coordinate `uv`, where the image
+    /// spans `0.0..1.0` on both axes. `lod` selects the mip level, higher
+    /// levels return blurrier results.
+    fn sample(&self, uv: Vec2, lod: f32) -> Vec3A;
+}
+
+/// Stores the parameters of an [`ImageBackground`] used for shader
+/// parameters. The image itself is passed to the shader separately.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct ImageBackgroundArgs {
+    /// Represents the color the image is multiplied with
+    pub color: Vec3A,
+    /// Represents the rotation of the image around the vertical axis in
+    /// radians
+    pub rotation: f32,
+    /// Represents the mip level sampled for the diffuse light emitted by the
+    /// background. Higher levels average over a larger area.
+    pub irradiance_lod: f32,
+}
+
+impl ImageBackgroundArgs {
+    /// Creates a new instance.
+    /// - `color` Represents the color the image is multiplied with
+    pub fn new(color: Vec3A) -> Self {
+        Self {
+            color,
+            rotation: 0.0,
+            irradiance_lod: 0.0,
+        }
+    }
+
+    /// Sets the rotation of the image around the vertical axis in radians
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the mip level sampled for the diffuse light emitted by the
+    /// background
+    pub fn with_irradiance_lod(mut self, irradiance_lod: f32) -> Self {
+        self.irradiance_lod = irradiance_lod;
+        self
+    }
+}
+
+/// A Background which emits the light stored in an equirectangular
+/// [`EnvironmentMap`], like a HDR photo of a real environment.
+pub struct ImageBackground<M: EnvironmentMap> {
+    args: ImageBackgroundArgs,
+    map: M,
+}
+
+impl<M: EnvironmentMap> ImageBackground<M> {
+    /// Creates a new instance from shader parameters
+    pub fn from_args(args: ImageBackgroundArgs, map: M) -> Self {
+        Self { args, map }
+    }
+
+    /// Returns the texture coordinate of the environment map in the given
+    /// direction. The top of the image is straight up.
+    pub fn uv(&self, direction: &Vec3A) -> Vec2 {
+        let direction = normalize(direction);
+
+        let u = (direction.x.atan2(direction.z) + self.args.rotation) / TAU;
+        let v = direction.y.max(-1.0).min(1.0).acos() / PI;
+
+        vec2(u - u.floor(), v)
+    }
+}
+
+impl<M: EnvironmentMap> Background for ImageBackground<M> {
+    fn radiance(&self, direction: &Vec3A) -> Vec3A {
+        self.map.sample(self.uv(direction), 0.0) * self.args.color
+    }
+
+    fn intensity(&self, normal: &Vec3A) -> Vec3A {
+        self.map.sample(self.uv(normal), self.args.irradiance_lod) * self.args.color
+    }
+}
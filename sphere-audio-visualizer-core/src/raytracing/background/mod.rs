@@ -3,8 +3,10 @@
 use glam::Vec3A;
 
 pub use self::constant::*;
+pub use self::image::*;
 
 mod constant;
+mod image;
 
 /// The Background defines the radiance returned by the radiance algorithm if
 /// nothing was hit.
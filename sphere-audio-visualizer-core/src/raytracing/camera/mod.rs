@@ -2,10 +2,12 @@
 
 use glam::Vec2;
 
-pub use self::perspective::*;
+pub use self::{panoramic::*, perspective::*, scene::*};
 use super::Ray;
 
+mod panoramic;
 mod perspective;
+mod scene;
 
 /// A Camera is used to generate prime rays for raytracing
 pub trait Camera {
@@ -0,0 +1,96 @@
+use core::f32::consts::{PI, TAU};
+
+use glam::{vec3a, Mat4, Vec2};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::raytracing::Ray;
+
+use super::Camera;
+
+/// Implements a panoramic camera, which captures every direction around it
+/// using an equirectangular mapping. The horizontal axis of the screen spans
+/// 360° around the vertical axis and the vertical axis 180° from straight up
+/// to straight down, as expected by 360° video players.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct PanoramicCamera {
+    transform: Mat4,
+    screen_size: Vec2,
+    screen_offset: Vec2,
+    t_min: f32,
+    t_max: f32,
+}
+
+impl PanoramicCamera {
+    /// Creates a new instance
+    /// - `transform` represents the transform of the camera in world space
+    /// - `screen_size` represents the screen size in pixels
+    /// - `t_min` represents the near plane of the camera.
+    /// - `t_max` represents the far plane of the camera.
+    pub fn new(transform: Mat4, screen_size: Vec2, t_min: f32, t_max: f32) -> Self {
+        Self {
+            transform,
+            screen_size,
+            screen_offset: Vec2::ZERO,
+            t_min,
+            t_max,
+        }
+    }
+
+    /// Sets the offset in pixels of the screen area the camera renders
+    /// into, e.g. to letterbox the image
+    pub fn with_screen_offset(mut self, screen_offset: Vec2) -> Self {
+        self.screen_offset = screen_offset;
+        self
+    }
+
+    /// Returns the transform of the camera in world space
+    pub fn transform(&self) -> Mat4 {
+        self.transform
+    }
+
+    /// Returns the screen size in pixels
+    pub fn screen_size(&self) -> Vec2 {
+        self.screen_size
+    }
+
+    /// Returns the offset of the screen area in pixels
+    pub fn screen_offset(&self) -> Vec2 {
+        self.screen_offset
+    }
+
+    /// Returns the distance of the near plane
+    pub fn t_min(&self) -> f32 {
+        self.t_min
+    }
+
+    /// Returns the distance of the far plane
+    pub fn t_max(&self) -> f32 {
+        self.t_max
+    }
+}
+
+impl Camera for PanoramicCamera {
+    fn prime_ray(&self, sample: &Vec2) -> Ray {
+        let uv = (*sample - self.screen_offset) / self.screen_size;
+
+        // the center of the image looks along the positive z axis
+        let longitude = (uv.x - 0.5) * TAU;
+        let latitude = (0.5 - uv.y) * PI;
+
+        let ray = Ray::new(
+            vec3a(0.0, 0.0, 0.0),
+            vec3a(
+                latitude.cos() * longitude.sin(),
+                latitude.sin(),
+                latitude.cos() * longitude.cos(),
+            ),
+            self.t_min,
+            self.t_max,
+        );
+
+        ray.transform(&self.transform)
+    }
+}
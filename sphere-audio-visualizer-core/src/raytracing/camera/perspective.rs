@@ -1,4 +1,4 @@
-use glam::{vec2, vec3a, Mat4, Vec2, Vec3A};
+use glam::{vec2, vec3a, vec4, Mat4, Vec2, Vec3A};
 
 #[cfg(target_arch = "spirv")]
 use num_traits::Float;
@@ -13,6 +13,7 @@ use super::Camera;
 pub struct PerspectiveCamera {
     transform: Mat4,
     screen_size: Vec2,
+    screen_offset: Vec2,
     tan_fov: f32,
     t_min: f32,
     t_max: f32,
@@ -29,16 +30,74 @@ impl PerspectiveCamera {
         Self {
             transform,
             screen_size,
+            screen_offset: Vec2::ZERO,
             tan_fov: fov.tan(),
             t_min,
             t_max,
         }
     }
+
+    /// Sets the offset in pixels of the screen area the camera renders
+    /// into, e.g. to letterbox the image
+    pub fn with_screen_offset(mut self, screen_offset: Vec2) -> Self {
+        self.screen_offset = screen_offset;
+        self
+    }
+
+    /// Returns the transform of the camera in world space
+    pub fn transform(&self) -> Mat4 {
+        self.transform
+    }
+
+    /// Returns the field of view in radians
+    pub fn fov(&self) -> f32 {
+        self.tan_fov.atan()
+    }
+
+    /// Returns the screen size in pixels
+    pub fn screen_size(&self) -> Vec2 {
+        self.screen_size
+    }
+
+    /// Returns the offset of the screen area in pixels
+    pub fn screen_offset(&self) -> Vec2 {
+        self.screen_offset
+    }
+
+    /// Returns the distance of the near plane
+    pub fn t_min(&self) -> f32 {
+        self.t_min
+    }
+
+    /// Returns the distance of the far plane
+    pub fn t_max(&self) -> f32 {
+        self.t_max
+    }
+
+    /// Returns the matrix transforming from world space into camera space
+    pub fn view(&self) -> Mat4 {
+        self.transform.inverse()
+    }
+
+    /// Returns the matrix transforming from camera space into clip space
+    /// matching the prime rays of the camera. The depth is mapped from
+    /// `t_min` to `t_max` onto 0.0-1.0.
+    pub fn projection(&self) -> Mat4 {
+        let aspect = self.screen_size.x / self.screen_size.y;
+        let depth = self.t_max / (self.t_max - self.t_min);
+
+        Mat4::from_cols(
+            vec4(1.0 / self.tan_fov, 0.0, 0.0, 0.0),
+            vec4(0.0, aspect / self.tan_fov, 0.0, 0.0),
+            vec4(0.0, 0.0, depth, 1.0),
+            vec4(0.0, 0.0, -self.t_min * depth, 0.0),
+        )
+    }
 }
 
 impl Camera for PerspectiveCamera {
     fn prime_ray(&self, sample: &Vec2) -> Ray {
-        let sensor = (*sample / self.screen_size * 2.0 - Vec2::splat(1.0))
+        let sensor = ((*sample - self.screen_offset) / self.screen_size * 2.0 - Vec2::splat(1.0))
             * self.tan_fov
             * vec2(1.0, -(self.screen_size.y / self.screen_size.x));
 
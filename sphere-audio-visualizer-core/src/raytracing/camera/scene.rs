@@ -0,0 +1,146 @@
+use core::f32::consts::{PI, TAU};
+
+use glam::{vec2, Mat4, Vec2, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::raytracing::Ray;
+
+use super::{Camera, PanoramicCamera, PerspectiveCamera};
+
+/// Stores either a [`PerspectiveCamera`] or a [`PanoramicCamera`], so the
+/// projection can be switched without changing the shader parameters. Shaders
+/// don't support enums carrying data, so the cameras share their fields.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct SceneCamera {
+    transform: Mat4,
+    screen_size: Vec2,
+    screen_offset: Vec2,
+    tan_fov: f32,
+    t_min: f32,
+    t_max: f32,
+    panoramic: u32,
+}
+
+impl SceneCamera {
+    /// Returns if the camera uses the panoramic projection
+    pub fn is_panoramic(&self) -> bool {
+        self.panoramic != 0
+    }
+
+    /// Returns the screen size in pixels
+    pub fn screen_size(&self) -> Vec2 {
+        self.screen_size
+    }
+
+    /// Returns the offset of the screen area in pixels
+    pub fn screen_offset(&self) -> Vec2 {
+        self.screen_offset
+    }
+
+    /// Returns the distance of the near plane
+    pub fn t_min(&self) -> f32 {
+        self.t_min
+    }
+
+    /// Returns the distance of the far plane
+    pub fn t_max(&self) -> f32 {
+        self.t_max
+    }
+
+    /// Returns the matrix transforming from world space into camera space
+    pub fn view(&self) -> Mat4 {
+        self.transform.inverse()
+    }
+
+    /// Returns the camera as a [`PerspectiveCamera`]. A panoramic camera
+    /// keeps the field of view it was created with.
+    pub fn perspective(&self) -> PerspectiveCamera {
+        PerspectiveCamera::new(
+            self.transform,
+            self.screen_size,
+            self.tan_fov.atan(),
+            self.t_min,
+            self.t_max,
+        )
+        .with_screen_offset(self.screen_offset)
+    }
+
+    /// Returns the camera as a [`PanoramicCamera`]
+    pub fn panoramic(&self) -> PanoramicCamera {
+        PanoramicCamera::new(self.transform, self.screen_size, self.t_min, self.t_max)
+            .with_screen_offset(self.screen_offset)
+    }
+
+    /// Projects a point in world space onto the screen, inverting the prime
+    /// rays of the camera. Returns the position in pixels or [`None`] if the
+    /// point is not in front of the camera.
+    pub fn project(&self, point: Vec3A) -> Option<Vec2> {
+        let point = self.view().transform_point3a(point);
+
+        let uv = if self.is_panoramic() {
+            let distance = point.length();
+            if distance < self.t_min || distance > self.t_max {
+                return None;
+            }
+
+            let longitude = point.x.atan2(point.z);
+            let latitude = (point.y / distance).asin();
+
+            vec2(longitude / TAU + 0.5, 0.5 - latitude / PI)
+        } else {
+            if point.z < self.t_min || point.z > self.t_max {
+                return None;
+            }
+
+            let sensor = vec2(point.x, point.y)
+                / point.z
+                / self.tan_fov
+                / vec2(1.0, -(self.screen_size.y / self.screen_size.x));
+
+            (sensor + Vec2::splat(1.0)) * 0.5
+        };
+
+        Some(uv * self.screen_size + self.screen_offset)
+    }
+}
+
+impl From<PerspectiveCamera> for SceneCamera {
+    fn from(camera: PerspectiveCamera) -> Self {
+        Self {
+            transform: camera.transform(),
+            screen_size: camera.screen_size(),
+            screen_offset: camera.screen_offset(),
+            tan_fov: camera.fov().tan(),
+            t_min: camera.t_min(),
+            t_max: camera.t_max(),
+            panoramic: 0,
+        }
+    }
+}
+
+impl From<PanoramicCamera> for SceneCamera {
+    fn from(camera: PanoramicCamera) -> Self {
+        Self {
+            transform: camera.transform(),
+            screen_size: camera.screen_size(),
+            screen_offset: camera.screen_offset(),
+            tan_fov: 1.0,
+            t_min: camera.t_min(),
+            t_max: camera.t_max(),
+            panoramic: 1,
+        }
+    }
+}
+
+impl Camera for SceneCamera {
+    fn prime_ray(&self, sample: &Vec2) -> Ray {
+        if self.is_panoramic() {
+            self.panoramic().prime_ray(sample)
+        } else {
+            self.perspective().prime_ray(sample)
+        }
+    }
+}
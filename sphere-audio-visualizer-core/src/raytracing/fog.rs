@@ -0,0 +1,55 @@
+//! Contains the participating medium filling the scene.
+
+use glam::{vec3a, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+/// Describes a homogeneous fog around the camera. Light is scattered
+/// equally in all directions by the fog, which makes the beams of the lights
+/// and the shadows of the spheres visible as light shafts.
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fog {
+    /// The color of the light scattered by the fog
+    pub color: Vec3A,
+    /// The part of the light scattered or absorbed per scene unit. Zero
+    /// disables the fog.
+    pub density: f32,
+    /// The distance from the camera the fog reaches
+    pub distance: f32,
+    /// The number of points along a ray at which the lights are sampled
+    pub steps: u32,
+}
+
+impl Fog {
+    /// Returns if the fog affects the image
+    pub fn is_enabled(&self) -> bool {
+        self.density > 0.0 && self.steps > 0
+    }
+
+    /// Returns the part of the light passing `distance` scene units of fog
+    pub fn transmittance(&self, distance: f32) -> f32 {
+        (-self.density * distance).exp()
+    }
+
+    /// Returns the scattering of light arriving with the given intensity,
+    /// which is the radiance scattered towards the camera per scene unit
+    pub fn scattering(&self, intensity: &Vec3A) -> Vec3A {
+        // the phase function of an isotropic medium
+        const PHASE: f32 = 1.0 / (4.0 * core::f32::consts::PI);
+
+        *intensity * self.color * self.density * PHASE
+    }
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            color: vec3a(1.0, 1.0, 1.0),
+            density: 0.0,
+            distance: 32.0,
+            steps: 16,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use glam::{Vec2, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{raytracing::SurfaceProperties, utils::math::normalize};
+
+use super::{Light, LightSample};
+
+/// Implements a directional light, which lights the scene from infinitely far
+/// away like the sun
+#[repr(C, align(16))]
+pub struct DirectionalLight {
+    direction: Vec3A,
+    intensity: Vec3A,
+}
+
+impl DirectionalLight {
+    /// Creates a new instance
+    /// - `direction` Represents the direction towards the light
+    /// - `intensity` Represents the intensity and color of the light
+    pub fn new(direction: Vec3A, intensity: Vec3A) -> Self {
+        Self {
+            direction: normalize(&direction),
+            intensity,
+        }
+    }
+
+    /// Returns the normalized direction towards the light
+    pub fn direction(&self) -> Vec3A {
+        self.direction
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _surface: &SurfaceProperties, _random: Vec2) -> LightSample {
+        LightSample {
+            direction: self.direction,
+            distance: 1000.0,
+            radiance: self.intensity,
+            pdf: 0.0,
+        }
+    }
+}
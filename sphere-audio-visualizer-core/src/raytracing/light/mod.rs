@@ -1,22 +1,179 @@
 //!Contains implementations of of the supported lights.
 
-use glam::{vec3a, Vec3A};
+use core::f32::consts::PI;
 
+use glam::{vec2, vec3a, Vec2, Vec3A, Vec4};
+
+pub use self::directional::*;
 pub use self::point::*;
+pub use self::spot::*;
 
 use super::{Ray, SurfaceProperties};
+use crate::utils::math::{cosine_hemisphere, dot, power_heuristic};
 
+mod directional;
 mod point;
+mod spot;
+
+/// Describes the light arriving at a point on a surface from one direction
+#[derive(Clone, Copy)]
+pub struct LightSample {
+    /// The normalized direction towards the light
+    pub direction: Vec3A,
+    /// The distance to the sampled point of the light
+    pub distance: f32,
+    /// The light arriving along the direction, without the cosine term and
+    /// shadows
+    pub radiance: Vec3A,
+    /// The probability density of sampling the direction with respect to the
+    /// solid angle. Lights without an extent (delta lights) report zero, as
+    /// they are sampled with certainty and can't be hit by other rays. Their
+    /// radiance is the light arriving in total then.
+    pub pdf: f32,
+}
+
+impl LightSample {
+    /// Returns a sample without any light
+    pub fn none() -> Self {
+        Self {
+            direction: vec3a(0.0, 0.0, 1.0),
+            distance: 0.0,
+            radiance: vec3a(0.0, 0.0, 0.0),
+            pdf: 0.0,
+        }
+    }
+
+    /// Accounts for the light being chosen with `probability` among others
+    pub fn select(mut self, probability: f32) -> Self {
+        if self.pdf > 0.0 {
+            self.pdf *= probability;
+        } else {
+            self.radiance /= probability;
+        }
+
+        self
+    }
+
+    /// Adds the light of another sample along the same direction, which is
+    /// chosen with `probability`
+    pub fn add(&mut self, other: &LightSample, probability: f32) {
+        if other.pdf <= 0.0 {
+            return;
+        }
+
+        // the shadow ray ends at the nearest light
+        self.distance = if self.pdf > 0.0 {
+            self.distance.min(other.distance)
+        } else {
+            other.distance
+        };
+        self.radiance += other.radiance;
+        self.pdf += other.pdf * probability;
+    }
+
+    /// Returns the ray testing if the light is occluded
+    pub fn shadow_ray(&self, surface: &SurfaceProperties) -> Ray {
+        Ray::new(
+            surface.position,
+            self.direction,
+            0.0001,
+            self.distance * 0.9999,
+        )
+    }
+}
 
 /// A light is used to light diffuse surfaces
 pub trait Light: Send + Sync {
-    /// Retuns the light intesity on the given point `surface`. `intersect`
+    /// Samples the light arriving at the given point `surface`. `random`
+    /// holds two numbers in `0.0..1.0`, which lights with an extent use to
+    /// choose a point on the light.
+    fn sample(&self, surface: &SurfaceProperties, random: Vec2) -> LightSample;
+
+    /// Returns the light arriving at the given point `surface` along a
+    /// direction sampled from the BSDF. The pdf is the density [`Light::sample`]
+    /// draws the direction with. Delta lights can't be hit and return no light.
+    fn hit(&self, _surface: &SurfaceProperties, _direction: &Vec3A) -> LightSample {
+        LightSample::none()
+    }
+
+    /// Returns if the light has no extent, so it can only be sampled by
+    /// [`Light::sample`]
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    /// Retuns the light intesity on the given point `surface`. A sample of
+    /// the light and a sample of the diffuse BSDF are combined with multiple
+    /// importance sampling, which keeps the noise low for small and large
+    /// lights alike. `random` holds four numbers in `0.0..1.0`. `intersect`
     /// is used for shadow calculations.
     fn intensity(
         &self,
         surface: &SurfaceProperties,
+        random: Vec4,
         intersect: impl Fn(&Ray) -> bool + Copy,
-    ) -> Vec3A;
+    ) -> Vec3A {
+        let mut intensity = vec3a(0.0, 0.0, 0.0);
+
+        let light = self.sample(surface, vec2(random.x, random.y));
+        let cos = dot(&surface.normal, &light.direction).max(0.0);
+        let light_intensity = light.radiance * cos;
+
+        // surfaces facing away from the light are not lit, which saves the
+        // shadow ray
+        if light_intensity.max_element() > 0.0 && !(intersect)(&light.shadow_ray(surface)) {
+            intensity += if light.pdf > 0.0 {
+                light_intensity * power_heuristic(light.pdf, cos / PI) / light.pdf
+            } else {
+                light_intensity
+            };
+        }
+
+        // rays sampled from the BSDF never hit delta lights
+        if !self.is_delta() {
+            let direction = cosine_hemisphere(&surface.normal, &vec2(random.z, random.w));
+            let bsdf = self.hit(surface, &direction);
+            let bsdf_pdf = dot(&surface.normal, &direction).max(0.0) / PI;
+
+            if bsdf.pdf > 0.0
+                && bsdf_pdf > 0.0
+                && bsdf.radiance.max_element() > 0.0
+                && !(intersect)(&bsdf.shadow_ray(surface))
+            {
+                // the cosine cancels with the density of the BSDF sample
+                intensity += bsdf.radiance * PI * power_heuristic(bsdf_pdf, bsdf.pdf);
+            }
+        }
+
+        intensity
+    }
+
+    /// Returns the light arriving at the point `position` inside of a medium,
+    /// which is scattered regardless of its direction. `random` holds two
+    /// numbers in `0.0..1.0`. `intersect` is used for shadow calculations.
+    fn in_scattering(
+        &self,
+        position: &Vec3A,
+        random: Vec2,
+        intersect: impl Fn(&Ray) -> bool + Copy,
+    ) -> Vec3A {
+        // the medium has no normal, the lights only use the position
+        let surface = SurfaceProperties {
+            position: *position,
+            normal: vec3a(0.0, 0.0, 1.0),
+        };
+        let light = self.sample(&surface, random);
+
+        if light.radiance.max_element() <= 0.0 || (intersect)(&light.shadow_ray(&surface)) {
+            return vec3a(0.0, 0.0, 0.0);
+        }
+
+        if light.pdf > 0.0 {
+            light.radiance / light.pdf
+        } else {
+            light.radiance
+        }
+    }
 }
 
 /// A wrapper for a collection of multiple lights that implements the [`Light`]
@@ -24,19 +181,77 @@ pub trait Light: Send + Sync {
 pub struct LightGroup<'a, L: Light>(pub &'a [L]);
 
 impl<'a, L: Light> Light for LightGroup<'a, L> {
+    fn sample(&self, surface: &SurfaceProperties, random: Vec2) -> LightSample {
+        let count = self.0.len();
+
+        if count == 0 {
+            return LightSample::none();
+        }
+
+        // the first number chooses the light and is rescaled for its sample
+        let scaled = random.x * count as f32;
+        let id = (scaled as usize).min(count - 1);
+
+        self.0[id]
+            .sample(surface, vec2(scaled - id as f32, random.y))
+            .select(1.0 / count as f32)
+    }
+
+    fn hit(&self, surface: &SurfaceProperties, direction: &Vec3A) -> LightSample {
+        let mut hit = LightSample {
+            direction: *direction,
+            ..LightSample::none()
+        };
+
+        for id in 0..self.0.len() {
+            hit.add(
+                &self.0[id].hit(surface, direction),
+                1.0 / self.0.len() as f32,
+            );
+        }
+
+        hit
+    }
+
+    fn is_delta(&self) -> bool {
+        let mut is_delta = true;
+
+        for id in 0..self.0.len() {
+            is_delta &= self.0[id].is_delta();
+        }
+
+        is_delta
+    }
+
     fn intensity(
         &self,
         surface: &SurfaceProperties,
+        random: Vec4,
         intersect: impl Fn(&Ray) -> bool + Copy,
     ) -> Vec3A {
         let mut intensity = vec3a(0.0, 0.0, 0.0);
 
         for id in 0..self.0.len() {
-            intensity += self.0[id].intensity(surface, intersect);
+            intensity += self.0[id].intensity(surface, random, intersect);
         }
 
         intensity
     }
+
+    fn in_scattering(
+        &self,
+        position: &Vec3A,
+        random: Vec2,
+        intersect: impl Fn(&Ray) -> bool + Copy,
+    ) -> Vec3A {
+        let mut in_scattering = vec3a(0.0, 0.0, 0.0);
+
+        for id in 0..self.0.len() {
+            in_scattering += self.0[id].in_scattering(position, random, intersect);
+        }
+
+        in_scattering
+    }
 }
 
 /// Stores the light setup of a scene. Every supported light type should be
@@ -44,14 +259,107 @@ impl<'a, L: Light> Light for LightGroup<'a, L> {
 pub struct LightScene<'a> {
     /// The [`LightGroup`] for [`PointLight`]
     pub point_lights: LightGroup<'a, PointLight>,
+    /// The [`LightGroup`] for [`DirectionalLight`]
+    pub directional_lights: LightGroup<'a, DirectionalLight>,
+    /// The [`LightGroup`] for [`SpotLight`]
+    pub spot_lights: LightGroup<'a, SpotLight>,
 }
 
 impl<'a> Light for LightScene<'a> {
+    fn sample(&self, surface: &SurfaceProperties, random: Vec2) -> LightSample {
+        let point_count = self.point_lights.0.len() as f32;
+        let directional_count = self.directional_lights.0.len() as f32;
+        let count = point_count + directional_count + self.spot_lights.0.len() as f32;
+
+        if count <= 0.0 {
+            return LightSample::none();
+        }
+
+        // every light is chosen with the same probability, so the groups are
+        // chosen by the number of their lights
+        let scaled = random.x * count;
+
+        if scaled < point_count {
+            self.point_lights
+                .sample(surface, vec2(scaled / point_count, random.y))
+                .select(point_count / count)
+        } else if scaled < point_count + directional_count {
+            self.directional_lights
+                .sample(
+                    surface,
+                    vec2((scaled - point_count) / directional_count, random.y),
+                )
+                .select(directional_count / count)
+        } else {
+            let spot_count = count - point_count - directional_count;
+
+            self.spot_lights
+                .sample(
+                    surface,
+                    vec2(
+                        (scaled - point_count - directional_count) / spot_count,
+                        random.y,
+                    ),
+                )
+                .select(spot_count / count)
+        }
+    }
+
+    fn hit(&self, surface: &SurfaceProperties, direction: &Vec3A) -> LightSample {
+        let count = (self.point_lights.0.len()
+            + self.directional_lights.0.len()
+            + self.spot_lights.0.len()) as f32;
+
+        let mut hit = LightSample {
+            direction: *direction,
+            ..LightSample::none()
+        };
+
+        hit.add(
+            &self.point_lights.hit(surface, direction),
+            self.point_lights.0.len() as f32 / count,
+        );
+        hit.add(
+            &self.directional_lights.hit(surface, direction),
+            self.directional_lights.0.len() as f32 / count,
+        );
+        hit.add(
+            &self.spot_lights.hit(surface, direction),
+            self.spot_lights.0.len() as f32 / count,
+        );
+
+        hit
+    }
+
+    fn is_delta(&self) -> bool {
+        self.point_lights.is_delta()
+            && self.directional_lights.is_delta()
+            && self.spot_lights.is_delta()
+    }
+
     fn intensity(
         &self,
         surface: &SurfaceProperties,
+        random: Vec4,
+        intersect: impl Fn(&Ray) -> bool + Copy,
+    ) -> Vec3A {
+        self.point_lights.intensity(surface, random, intersect)
+            + self
+                .directional_lights
+                .intensity(surface, random, intersect)
+            + self.spot_lights.intensity(surface, random, intersect)
+    }
+
+    fn in_scattering(
+        &self,
+        position: &Vec3A,
+        random: Vec2,
         intersect: impl Fn(&Ray) -> bool + Copy,
     ) -> Vec3A {
-        self.point_lights.intensity(surface, intersect)
+        self.point_lights.in_scattering(position, random, intersect)
+            + self
+                .directional_lights
+                .in_scattering(position, random, intersect)
+            + self.spot_lights.in_scattering(position, random, intersect)
     }
 }
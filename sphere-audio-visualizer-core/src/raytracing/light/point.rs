@@ -1,14 +1,14 @@
-use glam::{vec3a, Vec3A};
+use glam::{Vec2, Vec3A};
 
 #[cfg(target_arch = "spirv")]
 use num_traits::Float;
 
 use crate::{
-    raytracing::{Ray, SurfaceProperties},
+    raytracing::SurfaceProperties,
     utils::math::{dot, inverse_sqrt},
 };
 
-use super::Light;
+use super::{Light, LightSample};
 
 /// Implements a point light
 #[repr(C, align(16))]
@@ -27,20 +27,25 @@ impl PointLight {
             intensity,
         }
     }
+
+    /// Returns the position of the point light
+    pub fn position(&self) -> Vec3A {
+        self.position
+    }
 }
 
 impl Light for PointLight {
-    fn intensity(&self, surface: &SurfaceProperties, intersect: impl Fn(&Ray) -> bool) -> Vec3A {
+    fn sample(&self, surface: &SurfaceProperties, _random: Vec2) -> LightSample {
         let dir = self.position - surface.position;
 
-        let shadow_ray = Ray::new(surface.position, dir, 0.0001, 0.9999);
+        let mag2 = dot(&dir, &dir);
+        let inverse_mag = inverse_sqrt(mag2);
 
-        if (intersect)(&shadow_ray) {
-            vec3a(0.0, 0.0, 0.0)
-        } else {
-            let mag2 = dot(&dir, &dir);
-            let dir_normalized = dir * inverse_sqrt(mag2);
-            (self.intensity / mag2) * dot(&surface.normal, &dir_normalized).max(0.0)
+        LightSample {
+            direction: dir * inverse_mag,
+            distance: mag2 * inverse_mag,
+            radiance: self.intensity / mag2,
+            pdf: 0.0,
         }
     }
 }
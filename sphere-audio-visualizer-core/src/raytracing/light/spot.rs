@@ -0,0 +1,99 @@
+use glam::{Vec2, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::SurfaceProperties,
+    utils::math::{dot, inverse_sqrt, normalize},
+};
+
+use super::{Light, LightSample};
+
+/// Implements a spot light, which is a point light that only lights a cone
+/// around its direction. The light fades out between the inner and the outer
+/// cone angle.
+#[repr(C, align(16))]
+pub struct SpotLight {
+    position: Vec3A,
+    direction: Vec3A,
+    intensity: Vec3A,
+    cos_inner_angle: f32,
+    cos_outer_angle: f32,
+}
+
+impl SpotLight {
+    /// Creates a new instance
+    /// - `position` Represents the position of the spot light
+    /// - `direction` Represents the direction the spot light points in
+    /// - `inner_angle` Represents the angle in radians between the direction
+    /// and the edge of the fully lit cone
+    /// - `outer_angle` Represents the angle in radians between the direction
+    /// and the edge of the lit cone
+    /// - `intensity` Represents the intensity and color of the spot light
+    pub fn new(
+        position: Vec3A,
+        direction: Vec3A,
+        inner_angle: f32,
+        outer_angle: f32,
+        intensity: Vec3A,
+    ) -> Self {
+        let outer_angle = outer_angle.max(inner_angle);
+
+        Self {
+            position,
+            direction: normalize(&direction),
+            intensity,
+            cos_inner_angle: inner_angle.cos(),
+            cos_outer_angle: outer_angle.cos(),
+        }
+    }
+
+    /// Returns the position of the spot light
+    pub fn position(&self) -> Vec3A {
+        self.position
+    }
+
+    /// Returns the normalized direction the spot light points in
+    pub fn direction(&self) -> Vec3A {
+        self.direction
+    }
+
+    fn falloff(&self, cos_angle: f32) -> f32 {
+        // a hard edged cone has no range to fade out in
+        if self.cos_inner_angle <= self.cos_outer_angle {
+            return if cos_angle >= self.cos_outer_angle {
+                1.0
+            } else {
+                0.0
+            };
+        }
+
+        let x = ((cos_angle - self.cos_outer_angle)
+            / (self.cos_inner_angle - self.cos_outer_angle))
+            .max(0.0)
+            .min(1.0);
+
+        x * x * (3.0 - 2.0 * x)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, surface: &SurfaceProperties, _random: Vec2) -> LightSample {
+        let dir = self.position - surface.position;
+
+        let mag2 = dot(&dir, &dir);
+        let inverse_mag = inverse_sqrt(mag2);
+        let dir_normalized = dir * inverse_mag;
+
+        // surfaces outside of the cone receive no light, which saves the
+        // shadow ray
+        LightSample {
+            direction: dir_normalized,
+            distance: mag2 * inverse_mag,
+            radiance: (self.intensity / mag2)
+                * self.falloff(-dot(&dir_normalized, &self.direction)),
+            pdf: 0.0,
+        }
+    }
+}
@@ -1,15 +1,17 @@
 //! Contains the implementation of the raytracing algorithm
 
-use glam::{vec3a, Mat4, Vec2, Vec3A, Vec4};
+use glam::{vec2, vec3a, Mat4, Vec2, Vec3A, Vec4};
 
 use crate::utils::{
-    math::{tonemap_filmic, transform_point3a, transform_vector3a},
+    math::{transform_point3a, transform_vector3a, ColorGrading, Tonemapper},
+    random::{hash, random, random_state, unit},
     OptionPolyfill,
 };
 
 use self::{
-    background::{Background, ConstantBackground},
-    camera::{Camera, PerspectiveCamera},
+    background::{Background, ImageBackgroundArgs},
+    camera::{Camera, SceneCamera},
+    fog::Fog,
     light::Light,
     shape::{Reflection, SceneArgs, Shading, ShapeGroup},
 };
@@ -19,13 +21,68 @@ use num_traits::Float;
 
 pub mod background;
 pub mod camera;
+pub mod fog;
 pub mod light;
 pub mod shape;
+#[cfg(feature = "statistics")]
+pub mod statistics;
+
+/// Stores the data carried along a ray and its successors through the scene
+#[derive(Clone, Copy)]
+pub struct RayPayload {
+    /// The index of refraction of the medium the ray travels through
+    pub ior: f32,
+    /// Whether the ray travels inside of an object
+    pub inside: bool,
+    /// The distance travelled by the predecessors of the ray
+    pub distance: f32,
+    /// The number of bounces preceding the ray
+    pub depth: u32,
+    /// The state of the random numbers drawn along the path of the ray
+    pub seed: u32,
+    /// The point in time the path of the ray is traced at, relative to the
+    /// shutter interval in `-0.5..0.5`. Moving shapes are placed by it.
+    pub time: f32,
+}
+
+impl RayPayload {
+    /// Returns the payload of a successor ray emitted after travelling
+    /// `distance` along the current ray
+    pub fn advance(&self, distance: f32) -> Self {
+        Self {
+            distance: self.distance + distance,
+            depth: self.depth + 1,
+            seed: hash(self.seed),
+            ..*self
+        }
+    }
+
+    /// Returns a random number in `0.0..1.0` drawn for the ray. Every
+    /// successor draws another number.
+    pub fn random(&self) -> f32 {
+        unit(self.seed)
+    }
+}
+
+impl Default for RayPayload {
+    fn default() -> Self {
+        Self {
+            ior: 1.0,
+            inside: false,
+            distance: 0.0,
+            depth: 0,
+            seed: 0,
+            time: 0.0,
+        }
+    }
+}
 
 /// Implements a Ray
+#[derive(Clone, Copy)]
 pub struct Ray {
     origin: Vec4,
     direction: Vec4,
+    payload: RayPayload,
 }
 
 impl Ray {
@@ -38,9 +95,28 @@ impl Ray {
         Self {
             origin: origin.extend(t_min),
             direction: direction.extend(t_max),
+            payload: RayPayload::default(),
         }
     }
 
+    /// Sets the [`RayPayload`] of the ray
+    pub fn with_payload(mut self, payload: RayPayload) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Gets the [`RayPayload`] of the ray
+    pub fn payload(&self) -> &RayPayload {
+        &self.payload
+    }
+
+    /// Sets the point in time of the ray. Shadow rays are traced at the time
+    /// of the path they are cast for, so they see the same shapes.
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.payload.time = time;
+        self
+    }
+
     /// Gets the origin of the ray
     pub fn origin(&self) -> Vec3A {
         self.origin.truncate().into()
@@ -69,6 +145,7 @@ impl Ray {
             self.t_min(),
             self.t_max(),
         )
+        .with_payload(self.payload)
     }
 
     /// Checks weather a point is on this ray.
@@ -97,6 +174,16 @@ pub struct Raytracer<C: Camera, S: ShapeGroup, B: Background, L: Light> {
     background: B,
     light: L,
     bounces: u32,
+    loudness: f32,
+    frame: u32,
+    seed: u32,
+    supersampling: u32,
+    jitter: u32,
+    roulette_depth: u32,
+    tonemapper: Tonemapper,
+    hdr: u32,
+    color_grading: ColorGrading,
+    fog: Fog,
 }
 
 impl<C: Camera, S: ShapeGroup, B: Background, L: Light> Raytracer<C, S, B, L> {
@@ -108,14 +195,82 @@ impl<C: Camera, S: ShapeGroup, B: Background, L: Light> Raytracer<C, S, B, L> {
             background: args.background,
             light,
             bounces: args.bounces,
+            loudness: args.loudness,
+            frame: args.frame,
+            seed: args.seed,
+            supersampling: args.supersampling,
+            jitter: args.jitter,
+            roulette_depth: args.roulette_depth,
+            tonemapper: args.tonemapper,
+            hdr: args.hdr,
+            color_grading: args.color_grading,
+            fog: args.fog,
         }
     }
 
-    /// Samples the color of a pixel at the given position
+    /// Returns the index of the rendered frame
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Returns a random number in `0.0..1.0` for the pixel at the given
+    /// position, which is reproducible for the seed of the frame
+    pub fn random(&self, sample: &Vec2, dimension: u32) -> f32 {
+        random(self.seed, sample.x as u32, sample.y as u32, dimension)
+    }
+
+    /// Samples the color of a pixel at the given position, which is the
+    /// center of the pixel. With supersampling the pixel is split into a grid
+    /// and the tonemapped colors at the centers of its cells are averaged
+    /// before they are graded. With jitter the cells are sampled at random
+    /// positions instead, which differ from frame to frame.
     pub fn sample(&self, sample: &Vec2) -> Vec3A {
-        let prime_ray = self.camera.prime_ray(sample);
+        let supersampling = self.supersampling.max(1);
+        let mut color = vec3a(0.0, 0.0, 0.0);
+
+        for y in 0..supersampling {
+            for x in 0..supersampling {
+                let cell = match self.jitter {
+                    0 => vec2(0.5, 0.5),
+                    _ => {
+                        let dimension = (y * supersampling + x) * 2;
+
+                        vec2(
+                            self.random(sample, dimension),
+                            self.random(sample, dimension + 1),
+                        )
+                    }
+                };
+                let offset = (vec2(x as f32, y as f32) + cell) / supersampling as f32 - 0.5;
+
+                // the dimensions following the jitter seed the path
+                let seed = random_state(
+                    self.seed,
+                    sample.x as u32,
+                    sample.y as u32,
+                    supersampling * supersampling * 2 + y * supersampling + x,
+                );
+
+                let prime_ray = self.camera.prime_ray(&(*sample + offset));
+                // the seed is salted like the one of the lights
+                let payload = RayPayload {
+                    seed,
+                    time: unit(hash(seed ^ 0xc2b2ae35)) - 0.5,
+                    ..*prime_ray.payload()
+                };
+                let prime_ray = prime_ray.with_payload(payload);
+
+                let radiance = self.color_grading.expose(&self.radiance(prime_ray));
+
+                color += match self.hdr {
+                    0 => self.tonemapper.tonemap(&radiance),
+                    _ => radiance,
+                };
+            }
+        }
 
-        tonemap_filmic(&self.radiance(prime_ray))
+        self.color_grading
+            .grade(&(color / (supersampling * supersampling) as f32))
     }
 
     /// Querries the radiance of the scene using a ray
@@ -126,28 +281,57 @@ impl<C: Camera, S: ShapeGroup, B: Background, L: Light> Raytracer<C, S, B, L> {
             color: vec3a(1.0, 1.0, 1.0),
         };
 
-        for _ in 0..self.bounces {
+        for bounce in 0..self.bounces {
             let hit = self.intersect(&reflection.ray);
+            let mut distance = reflection.ray.t_max();
 
             let shading = if hit.is_some() {
-                self.shape_shade(&reflection.ray, unsafe { hit.unwrap() })
-                // Safety: checked for some before
+                let hit = hit.unwrap();
+                distance = self.shape.hit_distance(&hit);
+
+                self.shape_shade(&reflection.ray, hit)
             } else {
                 Shading {
-                    emission: self.background.radiance(&reflection.ray.direction()),
+                    emission: self.background.radiance(&reflection.ray.direction())
+                        * (1.0 + self.loudness),
                     reflection: OptionPolyfill::none(),
                 }
             };
 
+            // only the primary ray is marched through the fog, the light of
+            // the hit surface is dimmed by the fog in front of it
+            if bounce == 0 && self.fog.is_enabled() {
+                let distance = distance.min(self.fog.distance);
+                let time = reflection.ray.payload().time;
+
+                radiance += self.in_scattering(&reflection.ray, distance, |ray| {
+                    self.intersect(&ray.with_time(time)).is_some()
+                });
+                reflection.color *= self.fog.transmittance(distance - reflection.ray.t_min());
+            }
+
             radiance += reflection.color * shading.emission;
 
             if shading.reflection.is_some() {
-                let Reflection { ray, color } = unsafe { shading.reflection.unwrap() };
-                // Safety: checked for some before
+                let Reflection { ray, color } = shading.reflection.unwrap();
 
                 reflection = Reflection {
                     ray,
                     color: reflection.color * color,
+                };
+
+                // dark paths contribute little, so they are terminated
+                // randomly. The surviving paths are brightened accordingly,
+                // which keeps the average radiance unchanged.
+                if self.roulette_depth != 0 && bounce + 1 >= self.roulette_depth {
+                    let color = reflection.color;
+                    let survival = color.x.max(color.y).max(color.z).min(1.0);
+
+                    if unit(hash(!reflection.ray.payload().seed)) >= survival {
+                        break;
+                    }
+
+                    reflection.color /= survival;
                 }
             } else {
                 break;
@@ -160,7 +344,7 @@ impl<C: Camera, S: ShapeGroup, B: Background, L: Light> Raytracer<C, S, B, L> {
     /// Returns the shading of a hit surface
     pub fn shape_shade(&self, ray: &Ray, hit: S::Hit) -> Shading {
         self.shape
-            .shade(ray, hit, |surface| self.intensity(surface))
+            .shade(ray, hit, |surface| self.intensity(surface, ray.payload()))
     }
 
     /// Returns the hit if the scene intersected with the given ray
@@ -188,30 +372,131 @@ impl<C: Camera, S: ShapeGroup, B: Background, L: Light> Raytracer<C, S, B, L> {
         occlusion.max(0.0)
     }
 
-    /// returns the light instensity of a point on a surface
-    pub fn intensity(&self, surface: &SurfaceProperties) -> Vec3A {
-        self.background.intensity(&surface.normal) * self.ambient_occlusion(surface)
+    /// Returns the light scattered towards the origin of the ray by the fog
+    /// up to `distance` along the ray. The lights are sampled at evenly
+    /// spaced points, which are offset randomly to turn banding into noise.
+    /// `intersect` is used for shadow calculations.
+    pub fn in_scattering(
+        &self,
+        ray: &Ray,
+        distance: f32,
+        intersect: impl Fn(&Ray) -> bool + Copy,
+    ) -> Vec3A {
+        let mut in_scattering = vec3a(0.0, 0.0, 0.0);
+        let length = (distance - ray.t_min()).max(0.0);
+        let step = length / self.fog.steps as f32;
+
+        // the seed is salted like the one of the lights
+        let seed = hash(ray.payload().seed ^ 0x85ebca6b);
+        let offset = unit(seed);
+
+        for id in 0..self.fog.steps {
+            let t = (id as f32 + offset) * step;
+            let random = Self::light_random(seed ^ id);
+
+            let intensity = self.light.in_scattering(
+                &ray.point_at(ray.t_min() + t),
+                vec2(random.x, random.y),
+                intersect,
+            );
+
+            in_scattering += self.fog.scattering(&intensity) * self.fog.transmittance(t) * step;
+        }
+
+        in_scattering
+    }
+
+    /// Returns the random numbers sampling the lights for a path with the
+    /// given seed
+    fn light_random(seed: u32) -> Vec4 {
+        // the seed is salted, so the numbers differ from the ones of the
+        // reflection and the russian roulette
+        let x = hash(seed ^ 0x9e3779b9);
+        let y = hash(x);
+        let z = hash(y);
+        let w = hash(z);
+
+        Vec4::new(unit(x), unit(y), unit(z), unit(w))
+    }
+
+    /// returns the light instensity of a point on a surface. `payload` is the
+    /// payload of the path the lights are sampled for.
+    pub fn intensity(&self, surface: &SurfaceProperties, payload: &RayPayload) -> Vec3A {
+        self.background.intensity(&surface.normal)
+            * (1.0 + self.loudness)
+            * self.ambient_occlusion(surface)
             + self
                 .light
-                .intensity(surface, |ray| self.intersect(ray).is_some())
+                .intensity(surface, Self::light_random(payload.seed), |ray| {
+                    self.intersect(&ray.with_time(payload.time)).is_some()
+                })
     }
 }
 
 /// Stores the arguments of a raytracer used for shader parameters
 #[repr(C, align(16))]
 #[derive(Clone)]
-pub struct RaytracerArgs<C: Camera, B: Background> {
+pub struct RaytracerArgs<C: Camera, B> {
     /// Represents the camera used
     pub camera: C,
     /// Represents the backgtound used
     pub background: B,
     /// Represents the amount of ray bounces that should be simulated
     pub bounces: u32,
+    /// Represents the loudness by which the background brightness is raised
+    pub loudness: f32,
+    /// Represents the index of the rendered frame
+    pub frame: u32,
+    /// Represents the seed of the rendered frame for stochastic effects
+    pub seed: u32,
+    /// Represents the number of samples per pixel along each axis. The
+    /// samples are stratified on a grid, so a pixel is sampled
+    /// `supersampling²` times.
+    pub supersampling: u32,
+    /// Represents whether the samples are jittered within their cells. This
+    /// is non-zero while frames are accumulated, so every frame samples other
+    /// positions.
+    pub jitter: u32,
+    /// Represents the number of bounces after which paths are terminated
+    /// randomly depending on their throughput (russian roulette). Zero
+    /// disables the termination.
+    pub roulette_depth: u32,
+    /// Represents the operator mapping the radiance to displayable colors
+    pub tonemapper: Tonemapper,
+    /// Represents whether the radiance is written without tonemapping. This
+    /// is non-zero for HDR outputs, which roll off the highlights themselves.
+    pub hdr: u32,
+    /// Represents the exposure and the color grading of the output
+    pub color_grading: ColorGrading,
+    /// Represents the fog the primary rays are marched through
+    pub fog: Fog,
+}
+
+impl<C: Camera, B> RaytracerArgs<C, B> {
+    /// Replaces the background. This is used to build a [`Background`] from
+    /// shader parameters and resources, which are bound separately.
+    pub fn with_background<T>(self, background: T) -> RaytracerArgs<C, T> {
+        RaytracerArgs {
+            camera: self.camera,
+            background,
+            bounces: self.bounces,
+            loudness: self.loudness,
+            frame: self.frame,
+            seed: self.seed,
+            supersampling: self.supersampling,
+            jitter: self.jitter,
+            roulette_depth: self.roulette_depth,
+            tonemapper: self.tonemapper,
+            hdr: self.hdr,
+            color_grading: self.color_grading,
+            fog: self.fog,
+        }
+    }
 }
 
 /// Stores the arguments for raytracing used for shader parameters
 #[repr(C, align(16))]
-pub struct RaytracingArgsBundle<C: Camera, B: Background> {
+pub struct RaytracingArgsBundle<C: Camera, B> {
     /// Represents the arguments of the raytracer
     pub raytracer_args: RaytracerArgs<C, B>,
     /// Represents the arguments of the scene
@@ -219,4 +504,4 @@ pub struct RaytracingArgsBundle<C: Camera, B: Background> {
 }
 
 /// Defines a basic type configuration for raytracing
-pub type BasicRaytracingArgsBundle = RaytracingArgsBundle<PerspectiveCamera, ConstantBackground>;
+pub type BasicRaytracingArgsBundle = RaytracingArgsBundle<SceneCamera, ImageBackgroundArgs>;
@@ -0,0 +1,189 @@
+use glam::Vec3A;
+
+use crate::{
+    raytracing::{Ray, SurfaceProperties},
+    utils::OptionPolyfill,
+};
+
+use super::{BoundingBoxGroup, GroupHit, Shading, Shape, ShapeGroup, Sphere, SurfaceTexture, AABB};
+
+/// A node of a flattened bounding volume hierarchy. The nodes are stored in
+/// depth first order, so the first child of an inner node directly follows
+/// it. Instead of the second child, every node stores the index of the node
+/// to continue with when the node is missed or done, which allows to traverse
+/// the hierarchy without a stack.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct BvhNode {
+    bounding_box: AABB,
+    skip: u32,
+    first: u32,
+    count: u32,
+}
+
+impl BvhNode {
+    /// Creates an inner node:
+    /// - `bounding_box` Represents the bounding box of all shapes below
+    /// - `skip` Represents the index of the node following the subtree
+    pub fn inner(bounding_box: AABB, skip: u32) -> Self {
+        Self {
+            bounding_box,
+            skip,
+            first: 0,
+            count: 0,
+        }
+    }
+
+    /// Creates a leaf node:
+    /// - `bounding_box` Represents the bounding box of the shapes of the leaf
+    /// - `skip` Represents the index of the node following the leaf
+    /// - `first`, `count` Represent the range of shapes in the leaf
+    pub fn leaf(bounding_box: AABB, skip: u32, first: u32, count: u32) -> Self {
+        Self {
+            bounding_box,
+            skip,
+            first,
+            count,
+        }
+    }
+
+    /// Returns the bounding box of the node
+    pub fn bounding_box(&self) -> &AABB {
+        &self.bounding_box
+    }
+
+    /// Returns the index of the node following the subtree of this node
+    pub fn skip(&self) -> usize {
+        self.skip as usize
+    }
+
+    /// Returns the index of the first shape of a leaf
+    pub fn first(&self) -> usize {
+        self.first as usize
+    }
+
+    /// Returns the number of shapes of a leaf. Inner nodes contain no shapes.
+    pub fn count(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Returns weather the node is a leaf
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+/// A [`BoundingBoxGroup`] accelerated by a flattened bounding volume
+/// hierarchy. The shapes have to be ordered like the leaves of the hierarchy.
+/// Without nodes every shape is tested like in a [`BoundingBoxGroup`].
+pub struct BvhGroup<'a, S: Shape> {
+    nodes: &'a [BvhNode],
+    node_count: usize,
+    group: BoundingBoxGroup<'a, S>,
+}
+
+impl<'a> BvhGroup<'a, Sphere> {
+    /// Returns the shading of a hit like [`ShapeGroup::shade`], but normal
+    /// mapped spheres are perturbed by the normal map
+    pub fn shade_normal_mapped(
+        &self,
+        ray: &Ray,
+        hit: GroupHit,
+        normal_map: &impl SurfaceTexture,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        self.group
+            .shade_normal_mapped(ray, hit, normal_map, intensity)
+    }
+}
+
+impl<'a, S: Shape> BvhGroup<'a, S> {
+    /// Creates a new instance. Only the first nodes up to the count are part
+    /// of the hierarchy.
+    pub fn new(group: BoundingBoxGroup<'a, S>, nodes: &'a [BvhNode], node_count: usize) -> Self {
+        Self {
+            nodes,
+            node_count,
+            group,
+        }
+    }
+
+    /// Returns the number of nodes in the hierarchy
+    pub fn node_count(&self) -> usize {
+        self.node_count.min(self.nodes.len())
+    }
+}
+
+impl<'a, S: Shape> ShapeGroup for BvhGroup<'a, S> {
+    type Hit = GroupHit;
+
+    fn intersect(&self, ray: &Ray) -> OptionPolyfill<Self::Hit> {
+        let node_count = self.node_count();
+
+        if node_count == 0 {
+            return self.group.intersect(ray);
+        }
+
+        let shape_count = self.group.group.len();
+
+        let mut is_hit = false;
+        let mut nearest_hit = GroupHit {
+            hit: ray.t_max(),
+            id: 0,
+        };
+
+        let mut index = 0;
+
+        while index < node_count {
+            let node = &self.nodes[index];
+            let intersection = node.bounding_box.aabb_intersection(ray);
+
+            // nodes behind the nearest hit can't contain a nearer one
+            if intersection.intersect(ray) && intersection.tmin < nearest_hit.hit {
+                if node.is_leaf() {
+                    let end = (node.first() + node.count()).min(shape_count);
+
+                    for id in node.first()..end {
+                        let hit = self.group.group.0[id].intersect(ray);
+
+                        unsafe {
+                            let hit_is_some = hit.is_some();
+                            let hit = hit.unwrap_unchecked();
+
+                            is_hit = is_hit || hit_is_some;
+
+                            if hit_is_some && nearest_hit.hit > hit {
+                                nearest_hit = GroupHit { hit, id };
+                            }
+                        }
+                    }
+
+                    index = node.skip();
+                } else {
+                    index += 1;
+                }
+            } else {
+                index = node.skip();
+            }
+        }
+
+        OptionPolyfill::new(is_hit, nearest_hit)
+    }
+
+    fn hit_distance(&self, hit: &Self::Hit) -> f32 {
+        hit.hit
+    }
+
+    fn distance(&self, point: &Vec3A) -> f32 {
+        self.group.distance(point)
+    }
+
+    fn shade(
+        &self,
+        ray: &Ray,
+        hit: Self::Hit,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        self.group.shade(ray, hit, intensity)
+    }
+}
@@ -0,0 +1,160 @@
+use glam::Vec3A;
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{Ray, SurfaceProperties},
+    utils::{
+        math::{dot, normalize},
+        OptionPolyfill,
+    },
+};
+
+use super::{Material, Shading, Shape, AABB};
+
+/// Implements a capsule. The capsule consists of all
+/// points within a radius around a line segment, so it is a cylinder with
+/// hemispherical caps, e.g. a tube connecting two spheres.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct Capsule {
+    a: Vec3A,
+    b: Vec3A,
+    radius: f32,
+    material: u32,
+}
+
+impl Capsule {
+    /// Creates a new instance:
+    /// - `a`, `b` Represent the end points of the line segment in world space
+    /// - `radius` Represents the radius of the capsule
+    /// - `material` Represents the index of the material of the capsule
+    pub fn new(a: Vec3A, b: Vec3A, radius: f32, material: u32) -> Self {
+        Self {
+            a,
+            b,
+            radius,
+            material,
+        }
+    }
+
+    /// Returns the end points of the line segment in world space
+    pub fn end_points(&self) -> [Vec3A; 2] {
+        [self.a, self.b]
+    }
+
+    /// Returns the radius of the capsule
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Intersects the ray with the sphere of a cap around the passed end point
+    fn cap_intersect(&self, ray: &Ray, center: Vec3A) -> OptionPolyfill<f32> {
+        let direction = ray.direction();
+        let oc = ray.origin() - center;
+
+        let rdrd = dot(&direction, &direction);
+        let b = dot(&direction, &oc);
+        let c = dot(&oc, &oc) - self.radius * self.radius;
+        let discriminant = b * b - rdrd * c;
+
+        let t = (-b - discriminant.max(0.0).sqrt()) / rdrd;
+
+        OptionPolyfill::new(discriminant >= 0.0 && ray.valid_t(t), t)
+    }
+
+    /// Returns the point on the line segment nearest to the passed point
+    fn nearest_segment_point(&self, point: &Vec3A) -> Vec3A {
+        let ba = self.b - self.a;
+        let pa = *point - self.a;
+
+        let h = (dot(&pa, &ba) / dot(&ba, &ba)).max(0.0).min(1.0);
+
+        self.a + ba * h
+    }
+}
+
+impl Shape for Capsule {
+    fn intersect(&self, ray: &Ray) -> OptionPolyfill<f32> {
+        let direction = ray.direction();
+        let ba = self.b - self.a;
+        let oa = ray.origin() - self.a;
+
+        let baba = dot(&ba, &ba);
+        let bard = dot(&ba, &direction);
+        let baoa = dot(&ba, &oa);
+        let rdoa = dot(&direction, &oa);
+        let oaoa = dot(&oa, &oa);
+        let rdrd = dot(&direction, &direction);
+
+        // the infinite cylinder around the segment, only the part between the
+        // end points belongs to the capsule
+        let a = baba * rdrd - bard * bard;
+        let b = baba * rdoa - baoa * bard;
+        let c = baba * oaoa - baoa * baoa - self.radius * self.radius * baba;
+        let discriminant = b * b - a * c;
+
+        let t = (-b - discriminant.max(0.0).sqrt()) / a;
+        let y = baoa + t * bard;
+
+        let mut is_hit = discriminant >= 0.0 && y > 0.0 && y < baba && ray.valid_t(t);
+        let mut nearest_hit = if is_hit { t } else { ray.t_max() };
+
+        // the caps are spheres around the end points
+        let cap_a_hit = self.cap_intersect(ray, self.a);
+
+        unsafe {
+            let cap_a_hit_is_some = cap_a_hit.is_some();
+            let cap_a_hit = cap_a_hit.unwrap_unchecked();
+
+            if cap_a_hit_is_some && cap_a_hit < nearest_hit {
+                is_hit = true;
+                nearest_hit = cap_a_hit;
+            }
+        }
+
+        let cap_b_hit = self.cap_intersect(ray, self.b);
+
+        unsafe {
+            let cap_b_hit_is_some = cap_b_hit.is_some();
+            let cap_b_hit = cap_b_hit.unwrap_unchecked();
+
+            if cap_b_hit_is_some && cap_b_hit < nearest_hit {
+                is_hit = true;
+                nearest_hit = cap_b_hit;
+            }
+        }
+
+        OptionPolyfill::new(is_hit, nearest_hit)
+    }
+
+    fn distance(&self, point: &Vec3A) -> f32 {
+        let offset = *point - self.nearest_segment_point(point);
+
+        dot(&offset, &offset).sqrt() - self.radius
+    }
+
+    fn material(&self) -> usize {
+        self.material as usize
+    }
+
+    fn shade(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        material: &Material,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let position = ray.point_at(hit);
+        let normal = normalize(&(position - self.nearest_segment_point(&position)));
+
+        material.shade(ray, hit, &SurfaceProperties { position, normal }, intensity)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        AABB::empty()
+            .with_point(self.a.min(self.b) - Vec3A::splat(self.radius))
+            .with_point(self.a.max(self.b) + Vec3A::splat(self.radius))
+    }
+}
@@ -0,0 +1,115 @@
+use glam::Vec3A;
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{Ray, SurfaceProperties},
+    utils::{
+        math::{dot, face_forward, normalize},
+        OptionPolyfill,
+    },
+};
+
+use super::{plane::PARALLEL_EPSILON, Material, Shading, Shape, AABB};
+
+/// Implements a circular disc. Both sides of the disc
+/// are visible.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct Disc {
+    position: Vec3A,
+    normal: Vec3A,
+    radius: f32,
+    material: u32,
+}
+
+impl Disc {
+    /// Creates a new instance:
+    /// - `position` Represents the center of the disc in world space
+    /// - `normal` Represents the normal of the disc, it gets normalized
+    /// - `radius` Represents the radius of the disc
+    /// - `material` Represents the index of the material of the disc
+    pub fn new(position: Vec3A, normal: Vec3A, radius: f32, material: u32) -> Self {
+        Self {
+            position,
+            normal: normalize(&normal),
+            radius,
+            material,
+        }
+    }
+
+    /// Returns the center of the disc in world space
+    pub fn position(&self) -> Vec3A {
+        self.position
+    }
+
+    /// Returns the normal of the disc
+    pub fn normal(&self) -> Vec3A {
+        self.normal
+    }
+
+    /// Returns the radius of the disc
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+impl Shape for Disc {
+    fn intersect(&self, ray: &Ray) -> OptionPolyfill<f32> {
+        let cosine = dot(&self.normal, &ray.direction());
+        let t = dot(&(self.position - ray.origin()), &self.normal) / cosine;
+
+        let offset = ray.point_at(t) - self.position;
+
+        OptionPolyfill::new(
+            cosine.abs() > PARALLEL_EPSILON
+                && dot(&offset, &offset) <= self.radius * self.radius
+                && ray.valid_t(t),
+            t,
+        )
+    }
+
+    fn distance(&self, point: &Vec3A) -> f32 {
+        let offset = *point - self.position;
+        let height = dot(&offset, &self.normal);
+
+        // the distance to the rim is measured inside the plane of the disc
+        let radial = offset - self.normal * height;
+        let rim = (dot(&radial, &radial).sqrt() - self.radius).max(0.0);
+
+        (height * height + rim * rim).sqrt()
+    }
+
+    fn material(&self) -> usize {
+        self.material as usize
+    }
+
+    fn shade(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        material: &Material,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let position = ray.point_at(hit);
+
+        // the normal always faces the incoming ray
+        let normal = face_forward(&self.normal, &ray.direction());
+
+        material.shade(ray, hit, &SurfaceProperties { position, normal }, intensity)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        // the extent of the disc along every axis shrinks with the component
+        // of the normal along it
+        let extent = (Vec3A::ONE - self.normal * self.normal)
+            .max(Vec3A::ZERO)
+            .powf(0.5)
+            * self.radius;
+
+        AABB::empty()
+            .with_point(self.position - extent)
+            .with_point(self.position + extent)
+    }
+}
@@ -0,0 +1,169 @@
+use glam::{vec3a, Mat4, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{Ray, SurfaceProperties},
+    utils::{
+        math::{dot, face_forward, transform_normal3a, transform_point3a},
+        OptionPolyfill,
+    },
+};
+
+use super::{Material, Rect, Shading, Shape, Sphere, AABB};
+
+/// Identifies the canonical shape placed by an [`Instance`]
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InstanceShape {
+    /// A sphere around the origin with a radius of 1.0
+    Sphere,
+    /// A rectangle around the origin with a normal pointing into positive
+    /// y-axis direction and a side length of 1.0, like a [`Rect`]
+    Rect,
+}
+
+/// Implements an instance of a canonical shape, which is placed by a
+/// transform. Only the transform is stored per instance, so the transform can
+/// also scale the shape non-uniformly, e.g. to stretch a rectangle or to turn
+/// a sphere into an ellipsoid.
+#[repr(C, align(16))]
+pub struct Instance {
+    transform: Mat4,
+    shape: InstanceShape,
+    material: u32,
+}
+
+impl Instance {
+    /// Creates a new instance:
+    /// - `shape` Represents the canonical shape
+    /// - `transform` Represents the transform of the shape in world space,
+    ///   which maps world space into the space of the canonical shape like
+    ///   the transform of a [`Rect`]
+    /// - `material` Represents the index of the material of the instance
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::{
+    ///     glam::{vec3, Mat4},
+    ///     raytracing::shape::{Instance, InstanceShape, Shape},
+    /// };
+    ///
+    /// // a rect stretched to twice its width
+    /// let transform = Mat4::from_scale(vec3(2.0, 1.0, 1.0));
+    /// let instance = Instance::new(InstanceShape::Rect, transform.inverse(), 0);
+    ///
+    /// assert_eq!(instance.bounding_box().max().x, 1.0);
+    /// assert_eq!(instance.bounding_box().max().z, 0.5);
+    /// ```
+    pub fn new(shape: InstanceShape, transform: Mat4, material: u32) -> Self {
+        Self {
+            transform,
+            shape,
+            material,
+        }
+    }
+
+    /// Returns the canonical shape of the instance
+    pub fn shape(&self) -> InstanceShape {
+        self.shape
+    }
+
+    /// Returns the transform of the instance in world space
+    pub fn transform(&self) -> Mat4 {
+        self.transform
+    }
+
+    /// Returns the half extents of the box around the canonical shape
+    fn extent(&self) -> Vec3A {
+        match self.shape {
+            InstanceShape::Sphere => vec3a(1.0, 1.0, 1.0),
+            InstanceShape::Rect => vec3a(0.5, 0.0, 0.5),
+        }
+    }
+}
+
+impl Shape for Instance {
+    fn intersect(&self, ray: &Ray) -> OptionPolyfill<f32> {
+        // the direction is not normalized, so the distance along the ray is
+        // the same in both spaces
+        let ray = ray.transform(&self.transform);
+
+        match self.shape {
+            InstanceShape::Sphere => Sphere::new(Vec3A::ZERO, 1.0, self.material).intersect(&ray),
+            InstanceShape::Rect => Rect::new(Mat4::IDENTITY, self.material).intersect(&ray),
+        }
+    }
+
+    fn distance(&self, point: &Vec3A) -> f32 {
+        match self.shape {
+            InstanceShape::Sphere => {
+                let local = transform_point3a(&self.transform, point);
+
+                // the distance is scaled back by the largest scale, which is
+                // exact for uniform scales and underestimates it otherwise
+                let scale = self
+                    .transform
+                    .x_axis
+                    .truncate()
+                    .length()
+                    .max(self.transform.y_axis.truncate().length())
+                    .max(self.transform.z_axis.truncate().length());
+
+                (dot(&local, &local).sqrt() - 1.0) / scale
+            }
+            InstanceShape::Rect => f32::INFINITY,
+        }
+    }
+
+    fn material(&self) -> usize {
+        self.material as usize
+    }
+
+    fn shade(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        material: &Material,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let normal = match self.shape {
+            InstanceShape::Sphere => ray.transform(&self.transform).point_at(hit),
+            InstanceShape::Rect => vec3a(0.0, 1.0, 0.0),
+        };
+
+        let normal = transform_normal3a(&self.transform, &normal);
+
+        // rectangles have two sides, their normal always faces the incoming
+        // ray
+        let normal = match self.shape {
+            InstanceShape::Rect => face_forward(&normal, &ray.direction()),
+            _ => normal,
+        };
+
+        let surface = SurfaceProperties {
+            position: ray.point_at(hit),
+            normal,
+        };
+
+        material.shade(ray, hit, &surface, intensity)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        let transform = self.transform.inverse();
+        let extent = self.extent();
+        let mut bounding_box = AABB::empty();
+
+        for id in 0..8u32 {
+            let corner = vec3a(
+                if id & 1 == 0 { -extent.x } else { extent.x },
+                if id & 2 == 0 { -extent.y } else { extent.y },
+                if id & 4 == 0 { -extent.z } else { extent.z },
+            );
+
+            bounding_box.add_point(transform_point3a(&transform, &corner));
+        }
+
+        bounding_box
+    }
+}
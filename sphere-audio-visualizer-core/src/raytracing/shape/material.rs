@@ -0,0 +1,262 @@
+use glam::Vec3A;
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{Ray, RayPayload, SurfaceProperties},
+    utils::{
+        math::{dot, reflect, shlick},
+        OptionPolyfill,
+    },
+};
+
+use super::{Reflection, Shading};
+
+/// Defines the default index of refraction of a [`Material`]
+pub const MATERIAL_IOR: f32 = 1.45;
+
+/// Describes the look of a surface. Shapes reference their material by its
+/// index in the material buffer of the scene, so many shapes can share one
+/// material.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct Material {
+    albedo: Vec3A,
+    emission: Vec3A,
+    roughness: f32,
+    metallic: f32,
+    ior: f32,
+    transmission: f32,
+}
+
+impl Material {
+    /// Creates a new diffuse material with the passed color. The material
+    /// can be made glossy by lowering its roughness.
+    pub fn new(albedo: Vec3A) -> Self {
+        Self {
+            albedo,
+            emission: Vec3A::ZERO,
+            roughness: 1.0,
+            metallic: 0.0,
+            ior: MATERIAL_IOR,
+            transmission: 0.0,
+        }
+    }
+
+    /// Sets the color of the material
+    pub fn with_albedo(mut self, albedo: Vec3A) -> Self {
+        self.albedo = albedo;
+        self
+    }
+
+    /// Sets the light emitted by the material
+    pub fn with_emission(mut self, emission: Vec3A) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    /// Sets the roughness of the material between 0.0-1.0. A rough material
+    /// reflects less and is lit more diffusely.
+    pub fn with_roughness(mut self, roughness: f32) -> Self {
+        self.roughness = roughness;
+        self
+    }
+
+    /// Sets how metallic the material is between 0.0-1.0. Metals reflect in
+    /// their own color and are not lit diffusely.
+    pub fn with_metallic(mut self, metallic: f32) -> Self {
+        self.metallic = metallic;
+        self
+    }
+
+    /// Sets the index of refraction of the material
+    pub fn with_ior(mut self, ior: f32) -> Self {
+        self.ior = ior;
+        self
+    }
+
+    /// Sets how much light passes through the material between 0.0-1.0.
+    /// Transmissive materials refract the light passing through them like
+    /// glass, tinted by their color.
+    pub fn with_transmission(mut self, transmission: f32) -> Self {
+        self.transmission = transmission;
+        self
+    }
+
+    /// Returns the color of the material
+    pub fn albedo(&self) -> Vec3A {
+        self.albedo
+    }
+
+    /// Returns the light emitted by the material
+    pub fn emission(&self) -> Vec3A {
+        self.emission
+    }
+
+    /// Returns the roughness of the material
+    pub fn roughness(&self) -> f32 {
+        self.roughness
+    }
+
+    /// Returns how metallic the material is
+    pub fn metallic(&self) -> f32 {
+        self.metallic
+    }
+
+    /// Returns the index of refraction of the material
+    pub fn ior(&self) -> f32 {
+        self.ior
+    }
+
+    /// Returns how much light passes through the material
+    pub fn transmission(&self) -> f32 {
+        self.transmission
+    }
+
+    /// Returns the shading of the material at a surface point hit by the ray.
+    /// `intensity` is used for diffuse lighting.
+    pub fn shade(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        surface: &SurfaceProperties,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let transmission = self.transmission * (1.0 - self.metallic);
+
+        if transmission > 0.0 {
+            return self.shade_transmissive(ray, hit, surface, intensity, transmission);
+        }
+
+        let ray_direction = ray.direction();
+        let payload = ray.payload();
+
+        let gloss = 1.0 - self.roughness;
+        let fresnel = shlick(&ray_direction, &surface.normal, payload.ior, self.ior) * gloss;
+
+        // dielectrics reflect white by the fresnel term, metals in their color
+        let reflectance = Vec3A::splat(fresnel).lerp(self.albedo * gloss, self.metallic);
+        let diffuse = self.albedo * (1.0 - self.metallic) * (1.0 - fresnel);
+
+        // black surfaces are not lit, which saves the shadow rays
+        let emission = if diffuse.max_element() > 0.0 {
+            (intensity)(surface) * diffuse + self.emission
+        } else {
+            self.emission
+        };
+
+        let reflection_ray = Ray::new(
+            surface.position,
+            reflect(&ray_direction, &surface.normal),
+            0.0001,
+            1000.0,
+        )
+        .with_payload(payload.advance(hit));
+
+        Shading {
+            emission,
+            reflection: OptionPolyfill::new(
+                gloss > 0.0,
+                Reflection {
+                    ray: reflection_ray,
+                    color: reflectance,
+                },
+            ),
+        }
+    }
+
+    /// Returns the shading of a transmissive material. The ray is either
+    /// reflected or refracted, chosen randomly by the fresnel term, so the
+    /// average over many paths contains both.
+    fn shade_transmissive(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        surface: &SurfaceProperties,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+        transmission: f32,
+    ) -> Shading {
+        let ray_direction = ray.direction();
+        let payload = ray.payload();
+
+        // rays inside of the material hit the surface from behind and leave
+        // into the air
+        let normal = if payload.inside {
+            -surface.normal
+        } else {
+            surface.normal
+        };
+        let n1 = if payload.inside {
+            self.ior
+        } else {
+            payload.ior
+        };
+        let n2 = if payload.inside { 1.0 } else { self.ior };
+
+        let eta = n1 / n2;
+        let cos_i = -dot(&ray_direction, &normal);
+        let k = 1.0 - eta * eta * (1.0 - cos_i * cos_i);
+
+        // beyond the critical angle all light is reflected, otherwise the
+        // fresnel term uses the angle on the optically thinner side
+        let fresnel = if k < 0.0 {
+            1.0
+        } else if n1 > n2 {
+            let r = (n1 - n2) / (n1 + n2);
+            let r2 = r * r;
+            r2 + (1.0 - r2) * (1.0 - k.sqrt()).powf(5.0)
+        } else {
+            shlick(&ray_direction, &normal, n1, n2)
+        };
+
+        let gloss = 1.0 - self.roughness;
+        let diffuse = self.albedo * (1.0 - self.metallic) * (1.0 - fresnel) * (1.0 - transmission);
+
+        // the inside of a surface is not lit
+        let emission = if !payload.inside && diffuse.max_element() > 0.0 {
+            (intensity)(surface) * diffuse + self.emission
+        } else {
+            self.emission
+        };
+
+        // the colors are divided by the probability of their branch
+        let reflectance = Vec3A::splat(fresnel * gloss).lerp(self.albedo * gloss, self.metallic);
+        let reflected = payload.random() < fresnel;
+
+        let reflection = if reflected {
+            Reflection {
+                ray: Ray::new(
+                    surface.position,
+                    reflect(&ray_direction, &normal),
+                    0.0001,
+                    1000.0,
+                )
+                .with_payload(payload.advance(hit)),
+                color: reflectance / fresnel,
+            }
+        } else {
+            let refraction_payload = RayPayload {
+                ior: n2,
+                inside: !payload.inside,
+                ..payload.advance(hit)
+            };
+
+            Reflection {
+                ray: Ray::new(
+                    surface.position,
+                    ray_direction * eta + normal * (eta * cos_i - k.max(0.0).sqrt()),
+                    0.0001,
+                    1000.0,
+                )
+                .with_payload(refraction_payload),
+                color: self.albedo * transmission,
+            }
+        };
+
+        Shading {
+            emission,
+            reflection: OptionPolyfill::new(true, reflection),
+        }
+    }
+}
@@ -4,12 +4,23 @@ use glam::{vec3a, vec4, Vec3A};
 
 use crate::utils::{OptionPolyfill, Uninit};
 
-pub use self::{rect::*, sphere::*};
-
-use super::{Ray, SurfaceProperties};
-
+pub use self::{
+    bvh::*, capsule::*, disc::*, instance::*, material::*, plane::*, rect::*, sphere::*,
+    texture::*, triangle::*,
+};
+
+use super::{Ray, RayPayload, SurfaceProperties};
+
+mod bvh;
+mod capsule;
+mod disc;
+mod instance;
+mod material;
+mod plane;
 mod rect;
 mod sphere;
+mod texture;
+mod triangle;
 
 /// Stores the shading of a surface
 pub struct Shading {
@@ -33,6 +44,7 @@ impl Uninit for Reflection {
             ray: Ray {
                 origin: vec4(0.0, 0.0, 0.0, 0.0),
                 direction: vec4(0.0, 0.0, 0.0, 0.0),
+                payload: RayPayload::default(),
             },
             color: vec3a(0.0, 0.0, 0.0),
         }
@@ -59,8 +71,8 @@ impl Uninit for Diffusion {
     }
 }
 
-/// A Shape can be intersected by rays. It also already contains the material
-/// for shading.
+/// A Shape can be intersected by rays. It references the [`Material`] used
+/// for shading by its index.
 pub trait Shape: Send + Sync {
     /// Returns the intersection point of this shape with a ray if they
     /// intersect.
@@ -70,12 +82,16 @@ pub trait Shape: Send + Sync {
     /// this shape
     fn distance(&self, point: &Vec3A) -> f32;
 
-    /// Returns the shading of a hit event. `intensity` is used for diffuse
-    /// lighting
+    /// Returns the index of the [`Material`] of the shape
+    fn material(&self) -> usize;
+
+    /// Returns the shading of a hit event with the material of the shape.
+    /// `intensity` is used for diffuse lighting
     fn shade(
         &self,
         ray: &Ray,
         hit: f32,
+        material: &Material,
         intensity: impl Fn(&SurfaceProperties) -> Vec3A,
     ) -> Shading;
 
@@ -94,6 +110,9 @@ pub trait ShapeGroup {
     /// returns information about the intersected shape.
     fn intersect(&self, ray: &Ray) -> OptionPolyfill<Self::Hit>;
 
+    /// Returns the distance along the intersected ray to a hit
+    fn hit_distance(&self, hit: &Self::Hit) -> f32;
+
     /// Returns the shortest distance from the passed point to the surface of
     /// the shapes in the group
     fn distance(&self, point: &Vec3A) -> f32;
@@ -108,8 +127,21 @@ pub trait ShapeGroup {
     ) -> Shading;
 }
 
-/// A Shapegroup of shapes with the same type
-pub struct Group<'a, S: Shape>(&'a [S]);
+/// A Shapegroup of shapes with the same type. Only the first shapes up to the
+/// count are part of the group. The shapes index into the materials.
+pub struct Group<'a, S: Shape>(&'a [S], usize, &'a [Material]);
+
+impl<'a, S: Shape> Group<'a, S> {
+    /// Returns the number of shapes in the group
+    pub fn len(&self) -> usize {
+        self.1.min(self.0.len())
+    }
+
+    /// Returns weather the group contains no shapes
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
 
 /// A hit on a [`Group`]
 pub struct GroupHit {
@@ -118,6 +150,16 @@ pub struct GroupHit {
 }
 
 impl GroupHit {
+    /// Returns the distance of the hit along the ray
+    pub fn hit(&self) -> f32 {
+        self.hit
+    }
+
+    /// Returns the index of the hit shape in the group
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
     /// Return the smaller of two [`GroupHit`]s
     pub fn min(self, other: Self) -> Self {
         if self.hit > other.hit {
@@ -147,12 +189,12 @@ impl<'a, S: Shape> ShapeGroup for Group<'a, S> {
             id: 0,
         };
 
-        for id in 0..self.0.len() {
+        for id in 0..self.len() {
             let hit = self.0[id].intersect(ray);
 
             unsafe {
                 let hit_is_some = hit.is_some();
-                let hit = hit.unwrap();
+                let hit = hit.unwrap_unchecked();
 
                 is_hit = is_hit || hit_is_some;
 
@@ -165,10 +207,14 @@ impl<'a, S: Shape> ShapeGroup for Group<'a, S> {
         OptionPolyfill::new(is_hit, nearest_hit)
     }
 
+    fn hit_distance(&self, hit: &Self::Hit) -> f32 {
+        hit.hit
+    }
+
     fn distance(&self, point: &Vec3A) -> f32 {
         let mut distance = f32::INFINITY;
 
-        for id in 0..self.0.len() {
+        for id in 0..self.len() {
             distance = distance.min(self.0[id].distance(point))
         }
 
@@ -181,7 +227,9 @@ impl<'a, S: Shape> ShapeGroup for Group<'a, S> {
         hit: Self::Hit,
         intensity: impl Fn(&SurfaceProperties) -> Vec3A,
     ) -> Shading {
-        self.0[hit.id].shade(ray, hit.hit, intensity)
+        let shape = &self.0[hit.id];
+
+        shape.shade(ray, hit.hit, &self.2[shape.material()], intensity)
     }
 }
 
@@ -210,6 +258,21 @@ impl AABB {
         }
     }
 
+    /// Returns the minimum corner of the bounding box
+    pub fn min(&self) -> Vec3A {
+        self.min
+    }
+
+    /// Returns the maximum corner of the bounding box
+    pub fn max(&self) -> Vec3A {
+        self.max
+    }
+
+    /// Returns weather the bounding box contains no point
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
     fn aabb_intersection(&self, ray: &Ray) -> AABBIntersection {
         let di = 1.0 / ray.direction();
 
@@ -291,6 +354,17 @@ pub struct BoundingBoxGroup<'a, S: Shape> {
     group: Group<'a, S>,
 }
 
+impl<'a, S: Shape> BoundingBoxGroup<'a, S> {
+    /// Creates a new instance from the shapes, the materials they index into
+    /// and their combined bounding box
+    pub fn new(shapes: &'a [S], materials: &'a [Material], bounding_box: AABB) -> Self {
+        Self {
+            bounding_box,
+            group: Group(shapes, shapes.len(), materials),
+        }
+    }
+}
+
 impl<'a, S: Shape> ShapeGroup for BoundingBoxGroup<'a, S> {
     type Hit = GroupHit;
 
@@ -302,6 +376,10 @@ impl<'a, S: Shape> ShapeGroup for BoundingBoxGroup<'a, S> {
         }
     }
 
+    fn hit_distance(&self, hit: &Self::Hit) -> f32 {
+        hit.hit
+    }
+
     fn distance(&self, point: &Vec3A) -> f32 {
         self.group.distance(point)
     }
@@ -316,13 +394,76 @@ impl<'a, S: Shape> ShapeGroup for BoundingBoxGroup<'a, S> {
     }
 }
 
+impl<'a> BoundingBoxGroup<'a, Rect> {
+    /// Returns the shading of a hit like [`ShapeGroup::shade`], but textured
+    /// rectangles are colored by the texture and normal mapped rectangles
+    /// are perturbed by the normal map
+    pub fn shade_textured(
+        &self,
+        ray: &Ray,
+        hit: GroupHit,
+        texture: &impl SurfaceTexture,
+        normal_map: &impl SurfaceTexture,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let rect = &self.group.0[hit.id];
+
+        rect.shade_textured(
+            ray,
+            hit.hit,
+            &self.group.2[rect.material()],
+            texture,
+            normal_map,
+            intensity,
+        )
+    }
+}
+
+impl<'a> BoundingBoxGroup<'a, Sphere> {
+    /// Returns the shading of a hit like [`ShapeGroup::shade`], but normal
+    /// mapped spheres are perturbed by the normal map
+    pub fn shade_normal_mapped(
+        &self,
+        ray: &Ray,
+        hit: GroupHit,
+        normal_map: &impl SurfaceTexture,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let sphere = &self.group.0[hit.id];
+
+        sphere.shade_normal_mapped(
+            ray,
+            hit.hit,
+            &self.group.2[sphere.material()],
+            normal_map,
+            intensity,
+        )
+    }
+}
+
 /// Represents the geometry of an scene. All supported shapes should be
-/// represented by a [`BoundingBoxGroup`] Field in this struct.
-pub struct Scene<'a, 'b> {
-    /// The [`BoundingBoxGroup`] for [`Sphere`]
-    pub spheres: BoundingBoxGroup<'a, Sphere>,
+/// represented by a [`BoundingBoxGroup`] or [`BvhGroup`] Field in this struct.
+/// Unbounded shapes like [`Plane`]s are represented by a plain [`Group`].
+pub struct Scene<'a, 'b, 'c, 'd, 'e, 'f, 'g, T: SurfaceTexture, N: SurfaceTexture> {
+    /// The [`BvhGroup`] for [`Sphere`]
+    pub spheres: BvhGroup<'a, Sphere>,
     /// The [`BoundingBoxGroup`] for [`Rect`]
     pub rects: BoundingBoxGroup<'b, Rect>,
+    /// The [`TriangleMesh`] for [`Triangle`]
+    pub triangles: TriangleMesh<'c>,
+    /// The [`Group`] for [`Plane`]
+    pub planes: Group<'d, Plane>,
+    /// The [`BoundingBoxGroup`] for [`Disc`]
+    pub discs: BoundingBoxGroup<'e, Disc>,
+    /// The [`BoundingBoxGroup`] for [`Capsule`]
+    pub capsules: BoundingBoxGroup<'f, Capsule>,
+    /// The [`BoundingBoxGroup`] for [`Instance`]
+    pub instances: BoundingBoxGroup<'g, Instance>,
+    /// The [`SurfaceTexture`] of textured [`Rect`]s
+    pub texture: T,
+    /// The tangent space normal map of normal mapped [`Rect`]s and
+    /// [`Sphere`]s
+    pub normal_map: N,
 }
 
 /// Indentifies the different Shape types we support
@@ -331,6 +472,16 @@ pub enum ShapeType {
     Sphere,
     /// Represents a [`Rect`]
     Rect,
+    /// Represents a [`Triangle`]
+    Triangle,
+    /// Represents a [`Plane`]
+    Plane,
+    /// Represents a [`Disc`]
+    Disc,
+    /// Represents a [`Capsule`]
+    Capsule,
+    /// Represents an [`Instance`]
+    Instance,
 }
 
 /// A hit on a [`Scene`]
@@ -359,23 +510,69 @@ impl Uninit for SceneHit {
     }
 }
 
-impl<'a, 'b> Scene<'a, 'b> {
-    /// Creates a scene from shader inputs.
-    pub fn from_args(args: SceneArgs, spheres: &'a [Sphere], rects: &'b [Rect]) -> Self {
+impl<'a, 'b, 'c, 'd, 'e, 'f, 'g, T: SurfaceTexture, N: SurfaceTexture>
+    Scene<'a, 'b, 'c, 'd, 'e, 'f, 'g, T, N>
+{
+    /// Creates a scene from shader inputs. The shapes index into the
+    /// materials, textured and normal mapped shapes sample the textures.
+    pub fn from_args<'m: 'a + 'b + 'c + 'd + 'e + 'f + 'g>(
+        args: SceneArgs,
+        materials: &'m [Material],
+        spheres: &'a [Sphere],
+        sphere_nodes: &'a [BvhNode],
+        rects: &'b [Rect],
+        triangles: &'c [Triangle],
+        triangle_nodes: &'c [BvhNode],
+        planes: &'d [Plane],
+        discs: &'e [Disc],
+        capsules: &'f [Capsule],
+        instances: &'g [Instance],
+        texture: T,
+        normal_map: N,
+    ) -> Self {
         Self {
-            spheres: BoundingBoxGroup {
-                group: Group(spheres),
-                bounding_box: args.spheres_bounding_box.clone(),
-            },
+            spheres: BvhGroup::new(
+                BoundingBoxGroup {
+                    group: Group(spheres, args.sphere_count as usize, materials),
+                    bounding_box: args.spheres_bounding_box.clone(),
+                },
+                sphere_nodes,
+                args.sphere_node_count as usize,
+            ),
             rects: BoundingBoxGroup {
-                group: Group(rects),
+                group: Group(rects, rects.len(), materials),
                 bounding_box: args.rects_bounding_box.clone(),
             },
+            triangles: BvhGroup::new(
+                BoundingBoxGroup {
+                    group: Group(triangles, triangles.len(), materials),
+                    bounding_box: args.triangles_bounding_box.clone(),
+                },
+                triangle_nodes,
+                args.triangle_node_count as usize,
+            ),
+            planes: Group(planes, args.plane_count as usize, materials),
+            discs: BoundingBoxGroup {
+                group: Group(discs, args.disc_count as usize, materials),
+                bounding_box: args.discs_bounding_box.clone(),
+            },
+            capsules: BoundingBoxGroup {
+                group: Group(capsules, args.capsule_count as usize, materials),
+                bounding_box: args.capsules_bounding_box.clone(),
+            },
+            instances: BoundingBoxGroup {
+                group: Group(instances, args.instance_count as usize, materials),
+                bounding_box: args.instances_bounding_box.clone(),
+            },
+            texture,
+            normal_map,
         }
     }
 }
 
-impl<'a, 'b> ShapeGroup for Scene<'a, 'b> {
+impl<'a, 'b, 'c, 'd, 'e, 'f, 'g, T: SurfaceTexture, N: SurfaceTexture> ShapeGroup
+    for Scene<'a, 'b, 'c, 'd, 'e, 'f, 'g, T, N>
+{
     type Hit = SceneHit;
 
     fn intersect(&self, ray: &Ray) -> OptionPolyfill<Self::Hit> {
@@ -392,7 +589,7 @@ impl<'a, 'b> ShapeGroup for Scene<'a, 'b> {
 
         unsafe {
             let sphere_is_hit = sphere_hit.is_some();
-            let sphere_hit = sphere_hit.unwrap();
+            let sphere_hit = sphere_hit.unwrap_unchecked();
 
             is_hit = is_hit || sphere_is_hit;
             if sphere_is_hit && hit.hit.hit > sphere_hit.hit {
@@ -407,7 +604,7 @@ impl<'a, 'b> ShapeGroup for Scene<'a, 'b> {
 
         unsafe {
             let rect_is_hit = rect_hit.is_some();
-            let rect_hit = rect_hit.unwrap();
+            let rect_hit = rect_hit.unwrap_unchecked();
 
             is_hit = is_hit || rect_is_hit;
             if rect_is_hit && hit.hit.hit > rect_hit.hit {
@@ -418,11 +615,97 @@ impl<'a, 'b> ShapeGroup for Scene<'a, 'b> {
             }
         }
 
+        let triangle_hit = self.triangles.intersect(ray);
+
+        unsafe {
+            let triangle_is_hit = triangle_hit.is_some();
+            let triangle_hit = triangle_hit.unwrap_unchecked();
+
+            is_hit = is_hit || triangle_is_hit;
+            if triangle_is_hit && hit.hit.hit > triangle_hit.hit {
+                hit = SceneHit {
+                    hit: triangle_hit,
+                    shape_type: ShapeType::Triangle,
+                };
+            }
+        }
+
+        let plane_hit = self.planes.intersect(ray);
+
+        unsafe {
+            let plane_is_hit = plane_hit.is_some();
+            let plane_hit = plane_hit.unwrap_unchecked();
+
+            is_hit = is_hit || plane_is_hit;
+            if plane_is_hit && hit.hit.hit > plane_hit.hit {
+                hit = SceneHit {
+                    hit: plane_hit,
+                    shape_type: ShapeType::Plane,
+                };
+            }
+        }
+
+        let disc_hit = self.discs.intersect(ray);
+
+        unsafe {
+            let disc_is_hit = disc_hit.is_some();
+            let disc_hit = disc_hit.unwrap_unchecked();
+
+            is_hit = is_hit || disc_is_hit;
+            if disc_is_hit && hit.hit.hit > disc_hit.hit {
+                hit = SceneHit {
+                    hit: disc_hit,
+                    shape_type: ShapeType::Disc,
+                };
+            }
+        }
+
+        let capsule_hit = self.capsules.intersect(ray);
+
+        unsafe {
+            let capsule_is_hit = capsule_hit.is_some();
+            let capsule_hit = capsule_hit.unwrap_unchecked();
+
+            is_hit = is_hit || capsule_is_hit;
+            if capsule_is_hit && hit.hit.hit > capsule_hit.hit {
+                hit = SceneHit {
+                    hit: capsule_hit,
+                    shape_type: ShapeType::Capsule,
+                };
+            }
+        }
+
+        let instance_hit = self.instances.intersect(ray);
+
+        unsafe {
+            let instance_is_hit = instance_hit.is_some();
+            let instance_hit = instance_hit.unwrap_unchecked();
+
+            is_hit = is_hit || instance_is_hit;
+            if instance_is_hit && hit.hit.hit > instance_hit.hit {
+                hit = SceneHit {
+                    hit: instance_hit,
+                    shape_type: ShapeType::Instance,
+                };
+            }
+        }
+
         OptionPolyfill::new(is_hit, hit)
     }
 
+    fn hit_distance(&self, hit: &Self::Hit) -> f32 {
+        hit.hit.hit
+    }
+
     fn distance(&self, point: &Vec3A) -> f32 {
-        self.spheres.distance(point).min(self.rects.distance(point))
+        self.spheres
+            .distance(point)
+            .min(self.rects.distance(point))
+            .min(self.triangles.distance(point))
+            .min(self.planes.distance(point))
+            .min(self.discs.distance(point))
+            .min(self.capsules.distance(point))
+            .min(self.instances.distance(point))
     }
 
     fn shade(
@@ -432,8 +715,19 @@ impl<'a, 'b> ShapeGroup for Scene<'a, 'b> {
         intensity: impl Fn(&SurfaceProperties) -> Vec3A,
     ) -> Shading {
         match hit.shape_type {
-            ShapeType::Sphere => self.spheres.shade(ray, hit.hit, intensity),
-            ShapeType::Rect => self.rects.shade(ray, hit.hit, intensity),
+            ShapeType::Sphere => {
+                self.spheres
+                    .shade_normal_mapped(ray, hit.hit, &self.normal_map, intensity)
+            }
+            ShapeType::Rect => {
+                self.rects
+                    .shade_textured(ray, hit.hit, &self.texture, &self.normal_map, intensity)
+            }
+            ShapeType::Triangle => self.triangles.shade(ray, hit.hit, intensity),
+            ShapeType::Plane => self.planes.shade(ray, hit.hit, intensity),
+            ShapeType::Disc => self.discs.shade(ray, hit.hit, intensity),
+            ShapeType::Capsule => self.capsules.shade(ray, hit.hit, intensity),
+            ShapeType::Instance => self.instances.shade(ray, hit.hit, intensity),
         }
     }
 }
@@ -446,4 +740,33 @@ pub struct SceneArgs {
     pub rects_bounding_box: AABB,
     /// bounding box from the [Sphere] [Group]
     pub spheres_bounding_box: AABB,
+    /// bounding box from the [Triangle] [Group]
+    pub triangles_bounding_box: AABB,
+    /// bounding box from the [Disc] [Group]
+    pub discs_bounding_box: AABB,
+    /// bounding box from the [Capsule] [Group]
+    pub capsules_bounding_box: AABB,
+    /// bounding box from the [Instance] [Group]
+    pub instances_bounding_box: AABB,
+    /// the number of spheres in the [Sphere] [Group]. Spheres in the buffer
+    /// past this count are ignored.
+    pub sphere_count: u32,
+    /// the number of nodes in the [BvhNode] hierarchy of the [Sphere]s. The
+    /// spheres are tested one by one without nodes.
+    pub sphere_node_count: u32,
+    /// the number of nodes in the [BvhNode] hierarchy of the [Triangle]s. The
+    /// triangles are tested one by one without nodes.
+    pub triangle_node_count: u32,
+    /// the number of planes in the [Plane] [Group]. Planes in the buffer past
+    /// this count are ignored.
+    pub plane_count: u32,
+    /// the number of discs in the [Disc] [Group]. Discs in the buffer past
+    /// this count are ignored.
+    pub disc_count: u32,
+    /// the number of capsules in the [Capsule] [Group]. Capsules in the
+    /// buffer past this count are ignored.
+    pub capsule_count: u32,
+    /// the number of instances in the [Instance] [Group]. Instances in the
+    /// buffer past this count are ignored.
+    pub instance_count: u32,
 }
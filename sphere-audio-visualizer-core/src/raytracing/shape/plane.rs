@@ -0,0 +1,88 @@
+use glam::Vec3A;
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{Ray, SurfaceProperties},
+    utils::{
+        math::{dot, face_forward, normalize},
+        OptionPolyfill,
+    },
+};
+
+use super::{Material, Shading, Shape, AABB};
+
+/// The smallest cosine between a ray and a plane for which the ray is not
+/// considered parallel to the plane
+pub(crate) const PARALLEL_EPSILON: f32 = 1e-8;
+
+/// Implements an infinite plane, e.g. a floor under the
+/// spheres. Both sides of the plane are visible.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct Plane {
+    position: Vec3A,
+    normal: Vec3A,
+    material: u32,
+}
+
+impl Plane {
+    /// Creates a new instance:
+    /// - `position` Represents any point on the plane in world space
+    /// - `normal` Represents the normal of the plane, it gets normalized
+    /// - `material` Represents the index of the material of the plane
+    pub fn new(position: Vec3A, normal: Vec3A, material: u32) -> Self {
+        Self {
+            position,
+            normal: normalize(&normal),
+            material,
+        }
+    }
+
+    /// Returns the point the plane was created from in world space
+    pub fn position(&self) -> Vec3A {
+        self.position
+    }
+
+    /// Returns the normal of the plane
+    pub fn normal(&self) -> Vec3A {
+        self.normal
+    }
+}
+
+impl Shape for Plane {
+    fn intersect(&self, ray: &Ray) -> OptionPolyfill<f32> {
+        let cosine = dot(&self.normal, &ray.direction());
+        let t = dot(&(self.position - ray.origin()), &self.normal) / cosine;
+
+        OptionPolyfill::new(cosine.abs() > PARALLEL_EPSILON && ray.valid_t(t), t)
+    }
+
+    fn distance(&self, point: &Vec3A) -> f32 {
+        dot(&(*point - self.position), &self.normal).abs()
+    }
+
+    fn material(&self) -> usize {
+        self.material as usize
+    }
+
+    fn shade(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        material: &Material,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let position = ray.point_at(hit);
+
+        // the normal always faces the incoming ray
+        let normal = face_forward(&self.normal, &ray.direction());
+
+        material.shade(ray, hit, &SurfaceProperties { position, normal }, intensity)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        AABB::all()
+    }
+}
@@ -1,26 +1,182 @@
-use glam::{vec3a, Mat4, Vec3A};
+use glam::{vec2, vec3a, Mat4, Vec2, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
 
 use crate::{
     raytracing::{Ray, SurfaceProperties},
-    utils::{math::transform_point3a, OptionPolyfill},
+    utils::{
+        math::{dot, face_forward, normalize, transform_normal3a, transform_point3a},
+        OptionPolyfill,
+    },
 };
 
-use super::{Shading, Shape, AABB};
+use super::{
+    map_normal, ConstantTexture, Material, Shading, Shape, SurfaceTexture, AABB, FLAT_NORMAL,
+};
 
 /// Implements a rectangle shape with a normal pointing into positive y-axis
-/// direction and a side length of 1.0 and emissive material
+/// direction and a side length of 1.0. The rectangle can be colored and
+/// normal mapped by [`SurfaceTexture`]s.
 #[repr(C, align(16))]
 pub struct Rect {
     transform: Mat4,
-    color: Vec3A,
+    material: u32,
+    ripple_amplitude: f32,
+    ripple_frequency: f32,
+    ripple_phase: f32,
+    texture_scale: f32,
+    normal_strength: f32,
 }
 
 impl Rect {
     /// Creates a new instance:
     /// - `transform` Represents the transform of the rectangle in world space
-    /// - `color` Represents the color of the rectangle
-    pub fn new(transform: Mat4, color: Vec3A) -> Self {
-        Self { transform, color }
+    /// - `material` Represents the index of the material of the rectangle
+    pub fn new(transform: Mat4, material: u32) -> Self {
+        Self {
+            transform,
+            material,
+            ripple_amplitude: 0.0,
+            ripple_frequency: 0.0,
+            ripple_phase: 0.0,
+            texture_scale: 0.0,
+            normal_strength: 0.0,
+        }
+    }
+
+    /// Adds circular ripples around the center of the rectangle, which
+    /// perturb the normal used for shading:
+    /// - `amplitude` Represents the height of the ripples
+    /// - `frequency` Represents the angular frequency of the ripples along
+    ///   the radius
+    /// - `phase` Represents the phase by which the ripples travel outwards
+    pub fn with_ripple(mut self, amplitude: f32, frequency: f32, phase: f32) -> Self {
+        self.ripple_amplitude = amplitude;
+        self.ripple_frequency = frequency;
+        self.ripple_phase = phase;
+        self
+    }
+
+    /// Colors the rectangle by the texture of the scene. `scale` Represents
+    /// how often the texture repeats along each side of the rectangle.
+    pub fn with_texture(mut self, scale: f32) -> Self {
+        self.texture_scale = scale;
+        self
+    }
+
+    /// Returns how often the texture repeats along each side of the
+    /// rectangle. Rectangles with a scale of 0.0 are not textured.
+    pub fn texture_scale(&self) -> f32 {
+        self.texture_scale
+    }
+
+    /// Perturbs the normal of the rectangle by the normal map of the scene,
+    /// which uses the texture coordinates of the texture. `strength`
+    /// Represents the scale of the tilt of the normal map.
+    pub fn with_normal_map(mut self, strength: f32) -> Self {
+        self.normal_strength = strength;
+        self
+    }
+
+    /// Returns the scale of the tilt of the normal map. Rectangles with a
+    /// strength of 0.0 are not normal mapped.
+    pub fn normal_strength(&self) -> f32 {
+        self.normal_strength
+    }
+
+    /// Returns the texture coordinate of a point on the rectangle in world
+    /// space. The texture spans the rectangle from `0.0` to the texture scale
+    /// along the x-axis and the z-axis of the rectangle.
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::{
+    ///     glam::{vec2, vec3a, Mat4},
+    ///     raytracing::shape::Rect,
+    /// };
+    ///
+    /// let rect = Rect::new(Mat4::IDENTITY, 0).with_texture(2.0);
+    ///
+    /// assert_eq!(rect.uv(&vec3a(0.25, 0.0, -0.5)), vec2(1.5, 0.0));
+    /// ```
+    pub fn uv(&self, point: &Vec3A) -> Vec2 {
+        self.local_uv(&transform_point3a(&self.transform, point))
+    }
+
+    fn local_uv(&self, position: &Vec3A) -> Vec2 {
+        vec2(position.x + 0.5, position.z + 0.5) * self.texture_scale
+    }
+
+    /// Returns the shading like [`Shape::shade`], but the color and the
+    /// emission of the material are multiplied by the texture if the
+    /// rectangle is textured, and the normal is perturbed by the normal map
+    /// if the rectangle is normal mapped
+    pub fn shade_textured(
+        &self,
+        ray: &Ray,
+        t: f32,
+        material: &Material,
+        texture: &impl SurfaceTexture,
+        normal_map: &impl SurfaceTexture,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let local_ray = ray.transform(&self.transform);
+        let position = local_ray.point_at(t);
+        let view = normalize(&-local_ray.direction());
+
+        let radius = (position.x * position.x + position.z * position.z)
+            .sqrt()
+            .max(0.0001);
+        let slope = self.ripple_amplitude
+            * self.ripple_frequency
+            * (self.ripple_frequency * radius - self.ripple_phase).cos();
+        let normal = normalize(&vec3a(
+            -slope * position.x / radius,
+            1.0,
+            -slope * position.z / radius,
+        ));
+
+        let normal = if self.normal_strength > 0.0 {
+            map_normal(
+                &normal,
+                &vec3a(1.0, 0.0, 0.0),
+                normal_map.sample(self.local_uv(&position)),
+                self.normal_strength,
+            )
+        } else {
+            normal
+        };
+
+        // the brightness follows the tilt of the rippled and normal mapped
+        // surface towards the viewer relative to the flat surface
+        let brightness = (dot(&normal, &view).abs() / view.y.abs().max(0.001)).min(4.0);
+
+        let normal = transform_normal3a(&self.transform, &normal);
+
+        // the normal always faces the incoming ray
+        let normal = face_forward(&normal, &ray.direction());
+
+        let surface = SurfaceProperties {
+            position: ray.point_at(t),
+            normal,
+        };
+
+        let shading = if self.texture_scale > 0.0 {
+            let color = texture.sample(self.local_uv(&position));
+
+            material
+                .clone()
+                .with_albedo(material.albedo() * color)
+                .with_emission(material.emission() * color)
+                .shade(ray, t, &surface, intensity)
+        } else {
+            material.shade(ray, t, &surface, intensity)
+        };
+
+        Shading {
+            emission: shading.emission * brightness,
+            reflection: shading.reflection,
+        }
     }
 }
 
@@ -49,16 +205,25 @@ impl Shape for Rect {
         f32::INFINITY
     }
 
+    fn material(&self) -> usize {
+        self.material as usize
+    }
+
     fn shade(
         &self,
-        _ray: &Ray,
-        _t: f32,
-        _intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+        ray: &Ray,
+        t: f32,
+        material: &Material,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
     ) -> Shading {
-        Shading {
-            emission: self.color,
-            reflection: OptionPolyfill::none(),
-        }
+        self.shade_textured(
+            ray,
+            t,
+            material,
+            &ConstantTexture(Vec3A::ONE),
+            &ConstantTexture(FLAT_NORMAL),
+            intensity,
+        )
     }
 
     fn bounding_box(&self) -> AABB {
@@ -1,4 +1,6 @@
-use glam::Vec3A;
+use core::f32::consts::{PI, TAU};
+
+use glam::{vec2, vec3a, Vec2, Vec3A};
 
 #[cfg(target_arch = "spirv")]
 use core::arch::asm;
@@ -8,41 +10,163 @@ use num_traits::Float;
 use crate::{
     raytracing::{Ray, SurfaceProperties},
     utils::{
-        math::{distance, dot, normalize, reflect, shlick},
+        math::{distance, dot, normalize},
         {OptionPolyfill, Uninit},
     },
 };
 
-use super::{Reflection, Shading, Shape, AABB};
+use super::{
+    map_normal, ConstantTexture, Material, Shading, Shape, SurfaceTexture, AABB, FLAT_NORMAL,
+};
 
-/// Implements a sphere shape with glossy material.
+/// Implements a sphere shape. The sphere can be normal mapped by a
+/// [`SurfaceTexture`] and glow in the color of its material.
 #[repr(C, align(16))]
 pub struct Sphere {
     position: Vec3A,
-    color: Vec3A,
     radius: f32,
-    n: f32,
+    material: u32,
+    normal_strength: f32,
+    emission: f32,
+    motion: Vec3A,
 }
 
 impl Sphere {
     /// Creates a new Sphere shape
     /// - `position` Represents the position of the sphere in world space
-    /// - `color` Represents the color of the sphere
     /// - `radius` Represents the radius of the sphere
-    /// - `n` refractive factor of the sphere material
-    pub fn new(position: Vec3A, color: Vec3A, radius: f32, n: f32) -> Self {
+    /// - `material` Represents the index of the material of the sphere
+    pub fn new(position: Vec3A, radius: f32, material: u32) -> Self {
         Self {
             position,
-            color,
             radius,
-            n,
+            material,
+            normal_strength: 0.0,
+            emission: 0.0,
+            motion: Vec3A::ZERO,
+        }
+    }
+
+    /// Sets the distance the sphere travels while the shutter is open. The
+    /// sphere is centered on its position halfway through.
+    pub fn with_motion(mut self, motion: Vec3A) -> Self {
+        self.motion = motion;
+        self
+    }
+
+    /// Perturbs the normal of the sphere by the normal map of the scene.
+    /// `strength` Represents the scale of the tilt of the normal map.
+    pub fn with_normal_map(mut self, strength: f32) -> Self {
+        self.normal_strength = strength;
+        self
+    }
+
+    /// Returns the scale of the tilt of the normal map. Spheres with a
+    /// strength of 0.0 are not normal mapped.
+    pub fn normal_strength(&self) -> f32 {
+        self.normal_strength
+    }
+
+    /// Lets the sphere glow in the color of its material. `emission`
+    /// Represents the brightness of the glow, which is added to the emission
+    /// of the material, so the sphere lights up the shapes around it.
+    pub fn with_emission(mut self, emission: f32) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    /// Returns the brightness of the glow of the sphere
+    pub fn emission(&self) -> f32 {
+        self.emission
+    }
+
+    /// Returns the texture coordinate of a normal of the sphere. The texture
+    /// wraps around the sphere once, with the top of the texture at the top
+    /// of the sphere.
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::{
+    ///     glam::{vec2, vec3a},
+    ///     raytracing::shape::Sphere,
+    /// };
+    ///
+    /// let sphere = Sphere::new(vec3a(0.0, 0.0, 0.0), 1.0, 0);
+    ///
+    /// let uv = sphere.uv(&vec3a(0.0, 0.0, 1.0));
+    ///
+    /// assert!((uv - vec2(0.0, 0.5)).length() < 0.0001);
+    /// ```
+    pub fn uv(&self, normal: &Vec3A) -> Vec2 {
+        let u = normal.x.atan2(normal.z) / TAU;
+        let v = normal.y.max(-1.0).min(1.0).acos() / PI;
+
+        vec2(u - u.floor(), v)
+    }
+
+    /// Returns the shading like [`Shape::shade`], but the normal is perturbed
+    /// by the normal map if the sphere is normal mapped
+    pub fn shade_normal_mapped(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        material: &Material,
+        normal_map: &impl SurfaceTexture,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let position = ray.point_at(hit);
+        let normal = normalize(&(position - self.center(ray)));
+
+        let normal = if self.normal_strength > 0.0 {
+            // the tangent follows the texture coordinate around the vertical
+            // axis, which is undefined at the poles
+            let tangent = if normal.x.abs() + normal.z.abs() > 0.0001 {
+                vec3a(normal.z, 0.0, -normal.x)
+            } else {
+                vec3a(1.0, 0.0, 0.0)
+            };
+
+            map_normal(
+                &normal,
+                &tangent,
+                normal_map.sample(self.uv(&normal)),
+                self.normal_strength,
+            )
+        } else {
+            normal
+        };
+
+        let shading = material.shade(ray, hit, &SurfaceProperties { position, normal }, intensity);
+
+        Shading {
+            emission: shading.emission + material.albedo() * self.emission,
+            reflection: shading.reflection,
         }
     }
+
+    /// Returns the position of the sphere in world space
+    pub fn position(&self) -> Vec3A {
+        self.position
+    }
+
+    /// Returns the radius of the sphere
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Returns the distance the sphere travels while the shutter is open
+    pub fn motion(&self) -> Vec3A {
+        self.motion
+    }
+
+    /// Returns the position of the sphere at the point in time of the ray
+    fn center(&self, ray: &Ray) -> Vec3A {
+        self.position + self.motion * ray.payload().time
+    }
 }
 
 impl Sphere {
     fn sphere_hit(&self, ray: &Ray) -> OptionPolyfill<SphereHit> {
-        let oc = ray.origin() - self.position;
+        let oc = ray.origin() - self.center(ray);
         let direction = ray.direction();
 
         let a = dot(&direction, &direction);
@@ -59,7 +183,7 @@ impl Shape for Sphere {
         let sphere_hit = self.sphere_hit(ray);
 
         if sphere_hit.is_some() {
-            unsafe { sphere_hit.unwrap() }.hit(ray)
+            sphere_hit.unwrap().hit(ray)
         } else {
             OptionPolyfill::none()
         }
@@ -69,35 +193,28 @@ impl Shape for Sphere {
         distance(&self.position, point) - self.radius
     }
 
+    fn material(&self) -> usize {
+        self.material as usize
+    }
+
     fn shade(
         &self,
         ray: &Ray,
         hit: f32,
+        material: &Material,
         intensity: impl Fn(&SurfaceProperties) -> Vec3A,
     ) -> Shading {
-        let position = ray.point_at(hit);
-        let normal = normalize(&(position - self.position));
-        let ray_direction = ray.direction();
-
-        let reflection_ray = Ray::new(position, reflect(&ray_direction, &normal), 0.0001, 1000.0);
-
-        let surface = SurfaceProperties { position, normal };
-
-        let shlick = shlick(&ray_direction, &normal, 1.0, self.n);
-
-        Shading {
-            emission: (intensity)(&surface) * self.color * (1.0 - shlick),
-            reflection: OptionPolyfill::some(Reflection {
-                ray: reflection_ray,
-                color: Vec3A::splat(shlick),
-            }),
-        }
+        self.shade_normal_mapped(ray, hit, material, &ConstantTexture(FLAT_NORMAL), intensity)
     }
 
     fn bounding_box(&self) -> AABB {
+        // the box covers the whole path of the sphere
+        let start = self.position - self.motion * 0.5;
+        let end = self.position + self.motion * 0.5;
+
         AABB {
-            min: self.position - self.radius,
-            max: self.position + self.radius,
+            min: start.min(end) - self.radius,
+            max: start.max(end) + self.radius,
         }
     }
 }
@@ -121,7 +238,12 @@ impl Uninit for SphereHit {
 
 impl SphereHit {
     fn hit(&self, ray: &Ray) -> OptionPolyfill<f32> {
-        let t = (-self.b - self.discriminant.sqrt()) / (2.0 * self.a);
+        let root = self.discriminant.sqrt();
+        let near = (-self.b - root) / (2.0 * self.a);
+        let far = (-self.b + root) / (2.0 * self.a);
+
+        // rays travelling inside of the sphere hit it from behind
+        let t = if ray.valid_t(near) { near } else { far };
 
         OptionPolyfill::new(ray.valid_t(t), t)
     }
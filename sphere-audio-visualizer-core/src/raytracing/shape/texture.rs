@@ -0,0 +1,54 @@
+use glam::{Vec2, Vec3A};
+
+use crate::utils::math::{dot, normalize};
+
+/// Defines the color of a flat normal map, which keeps the normals
+pub const FLAT_NORMAL: Vec3A = Vec3A::new(0.5, 0.5, 1.0);
+
+/// A surface texture stores the color of a surface as an image, which is
+/// mapped onto shapes by their texture coordinates. It is implemented by the
+/// texture types of the different renderers.
+pub trait SurfaceTexture {
+    /// Samples the color at the texture coordinate `uv`, where the image
+    /// spans `0.0..1.0` on both axes and repeats outside of it
+    fn sample(&self, uv: Vec2) -> Vec3A;
+}
+
+/// A [`SurfaceTexture`] with the same color everywhere. It is used where no
+/// texture is bound, e.g. on the CPU.
+pub struct ConstantTexture(pub Vec3A);
+
+impl SurfaceTexture for ConstantTexture {
+    fn sample(&self, _uv: Vec2) -> Vec3A {
+        self.0
+    }
+}
+
+/// Returns the normal perturbed by a sample of a tangent space normal map,
+/// whose red and green channels point along the tangent and the bitangent.
+/// The bitangent is the cross product of the normal and the tangent, which
+/// follows the convention of OpenGL normal maps. `strength` scales the tilt.
+///
+/// ```
+/// use sphere_audio_visualizer_core::{glam::vec3a, raytracing::shape::map_normal};
+///
+/// let normal = vec3a(0.0, 1.0, 0.0);
+/// let tangent = vec3a(1.0, 0.0, 0.0);
+///
+/// // a flat sample keeps the normal
+/// assert_eq!(map_normal(&normal, &tangent, vec3a(0.5, 0.5, 1.0), 1.0), normal);
+///
+/// let tilted = map_normal(&normal, &tangent, vec3a(1.0, 0.5, 1.0), 1.0);
+/// assert!(tilted.x > 0.7 && tilted.y > 0.7);
+/// ```
+pub fn map_normal(normal: &Vec3A, tangent: &Vec3A, sample: Vec3A, strength: f32) -> Vec3A {
+    // the tangent is made orthogonal to the normal first
+    let tangent = normalize(&(*tangent - *normal * dot(normal, tangent)));
+    let bitangent = normal.cross(tangent);
+
+    let local = sample * 2.0 - Vec3A::ONE;
+
+    normalize(
+        &(tangent * (local.x * strength) + bitangent * (local.y * strength) + *normal * local.z),
+    )
+}
@@ -0,0 +1,124 @@
+use glam::{Mat4, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{Ray, SurfaceProperties},
+    utils::{
+        math::{dot, face_forward, normalize, transform_point3a},
+        OptionPolyfill,
+    },
+};
+
+use super::{BvhGroup, Material, Shading, Shape, AABB};
+
+/// The smallest determinant for which a ray is not considered parallel to a
+/// triangle
+const PARALLEL_EPSILON: f32 = 1e-8;
+
+/// Implements a triangle shape. Both sides of the
+/// triangle are visible.
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct Triangle {
+    a: Vec3A,
+    b: Vec3A,
+    c: Vec3A,
+    material: u32,
+}
+
+/// A mesh of [`Triangle`]s, which is only tested against rays hitting its
+/// bounding volume hierarchy
+pub type TriangleMesh<'a> = BvhGroup<'a, Triangle>;
+
+impl Triangle {
+    /// Creates a new instance:
+    /// - `a`, `b`, `c` Represent the corners of the triangle in world space
+    /// - `material` Represents the index of the material of the triangle
+    pub fn new(a: Vec3A, b: Vec3A, c: Vec3A, material: u32) -> Self {
+        Self { a, b, c, material }
+    }
+
+    /// Returns the corners of the triangle in world space
+    pub fn corners(&self) -> [Vec3A; 3] {
+        [self.a, self.b, self.c]
+    }
+
+    /// Sets the index of the material of the triangle
+    pub fn with_material(mut self, material: u32) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Returns the triangle with its corners transformed by `transform`
+    pub fn transformed(&self, transform: &Mat4) -> Self {
+        Self {
+            a: transform_point3a(transform, &self.a),
+            b: transform_point3a(transform, &self.b),
+            c: transform_point3a(transform, &self.c),
+            material: self.material,
+        }
+    }
+}
+
+impl Shape for Triangle {
+    fn intersect(&self, ray: &Ray) -> OptionPolyfill<f32> {
+        // Möller–Trumbore intersection
+        let edge1 = self.b - self.a;
+        let edge2 = self.c - self.a;
+        let direction = ray.direction();
+
+        let p = direction.cross(edge2);
+        let determinant = dot(&edge1, &p);
+        let inverse_determinant = 1.0 / determinant;
+
+        let s = ray.origin() - self.a;
+        let u = dot(&s, &p) * inverse_determinant;
+
+        let q = s.cross(edge1);
+        let v = dot(&direction, &q) * inverse_determinant;
+
+        let t = dot(&edge2, &q) * inverse_determinant;
+
+        OptionPolyfill::new(
+            determinant.abs() > PARALLEL_EPSILON
+                && u >= 0.0
+                && v >= 0.0
+                && u + v <= 1.0
+                && ray.valid_t(t),
+            t,
+        )
+    }
+
+    fn distance(&self, _point: &Vec3A) -> f32 {
+        f32::INFINITY
+    }
+
+    fn material(&self) -> usize {
+        self.material as usize
+    }
+
+    fn shade(
+        &self,
+        ray: &Ray,
+        hit: f32,
+        material: &Material,
+        intensity: impl Fn(&SurfaceProperties) -> Vec3A,
+    ) -> Shading {
+        let position = ray.point_at(hit);
+        let normal = normalize(&(self.b - self.a).cross(self.c - self.a));
+
+        // the normal always faces the incoming ray
+        let normal = face_forward(&normal, &ray.direction());
+
+        material.shade(ray, hit, &SurfaceProperties { position, normal }, intensity)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        AABB::empty()
+            .with_point(self.a)
+            .with_point(self.b)
+            .with_point(self.c)
+    }
+}
@@ -0,0 +1,39 @@
+//! Contains the counters used to measure the work done by the raytracer.
+//! Only available with the `statistics` feature. The counters are only
+//! filled by the WGSL raytracer shader, the Rust implementation and the CPU
+//! raytracer don't count anything.
+
+use core::ops::{Add, AddAssign};
+
+/// Stores the amount of work done while raytracing one or multiple samples
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct RaytracerStatistics {
+    /// The number of rays cast into the scene including shadow rays
+    pub rays_cast: u32,
+    /// The number of ray bounding box intersection tests
+    pub aabb_tests: u32,
+    /// The number of ray shape intersection tests
+    pub shape_tests: u32,
+    /// The number of samples the counters are summed up over
+    pub samples: u32,
+}
+
+impl Add for RaytracerStatistics {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            rays_cast: self.rays_cast + other.rays_cast,
+            aabb_tests: self.aabb_tests + other.aabb_tests,
+            shape_tests: self.shape_tests + other.shape_tests,
+            samples: self.samples + other.samples,
+        }
+    }
+}
+
+impl AddAssign for RaytracerStatistics {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
@@ -0,0 +1,249 @@
+//! Contains the sphere tracer, which renders the shapes of a raytracer scene
+//! by marching along their signed distance functions instead of intersecting
+//! them. The spheres are blended into each other with a smooth union, which
+//! turns them into 3D metaballs.
+
+use glam::{vec3a, Vec2, Vec3A, Vec4};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::{
+    raytracing::{
+        camera::{Camera, SceneCamera},
+        light::Light,
+        shape::{Material, Plane, Shape, Sphere},
+        Ray, SurfaceProperties,
+    },
+    utils::{
+        math::{normalize, smooth_min, tonemap_filmic},
+        OptionPolyfill,
+    },
+};
+
+/// Defines the offset of the points sampled to estimate the normal
+const NORMAL_OFFSET: f32 = 0.001;
+
+/// Defines the distance above the surface the shadow rays start at, so they
+/// don't hit the surface they start on
+const SURFACE_OFFSET: f32 = 0.01;
+
+/// Stores the arguments of the sphere tracer used for shader parameters
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct SdfTracerArgs {
+    /// Represents the camera used
+    pub camera: SceneCamera,
+    /// Represents the color of the background, which also lights the scene
+    /// from all directions
+    pub background: Vec3A,
+    /// Represents the distance over which the spheres are blended into each
+    /// other. Zero keeps them apart.
+    pub smoothness: f32,
+    /// Represents the distance to a surface relative to the distance
+    /// travelled below which a ray hits the surface
+    pub epsilon: f32,
+    /// Represents the loudness by which the background brightness is raised
+    pub loudness: f32,
+    /// Represents the maximum number of marching steps per ray
+    pub steps: u32,
+    /// Represents the number of spheres used
+    pub sphere_count: u32,
+    /// Represents the number of planes used
+    pub plane_count: u32,
+    /// Represents whether the radiance is written without tonemapping. This
+    /// is non-zero for HDR outputs, which roll off the highlights themselves.
+    pub hdr: u32,
+}
+
+/// Describes the blended surface nearest to a point
+struct SdfSample {
+    distance: f32,
+    albedo: Vec3A,
+    emission: Vec3A,
+}
+
+/// Implements the sphere tracing algorithm. Only spheres and planes are
+/// supported, as the other shapes have no exact distance function.
+pub struct SdfTracer<'a, L: Light> {
+    args: SdfTracerArgs,
+    spheres: &'a [Sphere],
+    planes: &'a [Plane],
+    materials: &'a [Material],
+    light: L,
+}
+
+impl<'a, L: Light> SdfTracer<'a, L> {
+    /// Creates a new instance from shader parameters. The shapes index into
+    /// the materials.
+    pub fn from_args(
+        args: SdfTracerArgs,
+        spheres: &'a [Sphere],
+        planes: &'a [Plane],
+        materials: &'a [Material],
+        light: L,
+    ) -> Self {
+        Self {
+            args,
+            spheres,
+            planes,
+            materials,
+            light,
+        }
+    }
+
+    /// Returns the blended surface nearest to the point
+    fn nearest(&self, point: &Vec3A) -> SdfSample {
+        let mut nearest = SdfSample {
+            distance: f32::INFINITY,
+            albedo: vec3a(0.0, 0.0, 0.0),
+            emission: vec3a(0.0, 0.0, 0.0),
+        };
+
+        let sphere_count = (self.args.sphere_count as usize).min(self.spheres.len());
+
+        for id in 0..sphere_count {
+            let sphere = &self.spheres[id];
+            let material = &self.materials[sphere.material()];
+            let distance = sphere.distance(point);
+            let emission = material.emission() + material.albedo() * sphere.emission();
+
+            if id == 0 {
+                nearest = SdfSample {
+                    distance,
+                    albedo: material.albedo(),
+                    emission,
+                };
+            } else {
+                // the materials are blended like the distances
+                let (distance, weight) =
+                    smooth_min(distance, nearest.distance, self.args.smoothness);
+
+                nearest = SdfSample {
+                    distance,
+                    albedo: nearest.albedo.lerp(material.albedo(), weight),
+                    emission: nearest.emission.lerp(emission, weight),
+                };
+            }
+        }
+
+        let plane_count = (self.args.plane_count as usize).min(self.planes.len());
+
+        for id in 0..plane_count {
+            let plane = &self.planes[id];
+            let distance = plane.distance(point);
+
+            if distance < nearest.distance {
+                let material = &self.materials[plane.material()];
+
+                nearest = SdfSample {
+                    distance,
+                    albedo: material.albedo(),
+                    emission: material.emission(),
+                };
+            }
+        }
+
+        nearest
+    }
+
+    /// Returns the shortest distance of the given point to a surface of the
+    /// scene
+    pub fn distance(&self, point: &Vec3A) -> f32 {
+        self.nearest(point).distance
+    }
+
+    /// Marches along the ray until it hits a surface. Returns the distance
+    /// along the ray to the hit.
+    pub fn march(&self, ray: &Ray) -> OptionPolyfill<f32> {
+        let mut t = ray.t_min();
+        let mut is_hit = false;
+
+        for _ in 0..self.args.steps {
+            let distance = self.distance(&ray.point_at(t));
+
+            // the precision is relaxed with the distance, which saves steps
+            // where a pixel covers a large area
+            if distance < self.args.epsilon * t.max(1.0) {
+                is_hit = true;
+                break;
+            }
+
+            t += distance;
+
+            if t > ray.t_max() {
+                break;
+            }
+        }
+
+        OptionPolyfill::new(is_hit, t)
+    }
+
+    /// Returns the normal of the surface at the given point, which is the
+    /// gradient of the distance function sampled at the corners of a
+    /// tetrahedron
+    pub fn normal(&self, point: &Vec3A) -> Vec3A {
+        let a = vec3a(1.0, -1.0, -1.0);
+        let b = vec3a(-1.0, -1.0, 1.0);
+        let c = vec3a(-1.0, 1.0, -1.0);
+        let d = vec3a(1.0, 1.0, 1.0);
+
+        normalize(
+            &(a * self.distance(&(*point + a * NORMAL_OFFSET))
+                + b * self.distance(&(*point + b * NORMAL_OFFSET))
+                + c * self.distance(&(*point + c * NORMAL_OFFSET))
+                + d * self.distance(&(*point + d * NORMAL_OFFSET))),
+        )
+    }
+
+    /// Returns the ambient occlusion of a point on a surface
+    pub fn ambient_occlusion(&self, surface: &SurfaceProperties) -> f32 {
+        let mut occlusion = 1.0;
+
+        for i in 1u32..6 {
+            let sample = i as f32;
+            let offset = sample * 0.35;
+            occlusion -= (offset - self.distance(&(surface.position + surface.normal * offset)))
+                * 0.5f32.powf(sample);
+        }
+
+        occlusion.max(0.0)
+    }
+
+    /// Returns the radiance arriving along the ray
+    pub fn radiance(&self, ray: &Ray) -> Vec3A {
+        let background = self.args.background * (1.0 + self.args.loudness);
+        let hit = self.march(ray);
+
+        if hit.is_none() {
+            return background;
+        }
+
+        let position = ray.point_at(hit.unwrap());
+        let nearest = self.nearest(&position);
+        let normal = self.normal(&position);
+
+        let surface = SurfaceProperties {
+            position: position + normal * SURFACE_OFFSET,
+            normal,
+        };
+
+        // the lights are delta lights, which don't use random numbers
+        let intensity = background * self.ambient_occlusion(&surface)
+            + self
+                .light
+                .intensity(&surface, Vec4::splat(0.5), |ray| self.march(ray).is_some());
+
+        nearest.albedo * intensity + nearest.emission
+    }
+
+    /// Samples the color of a pixel at the given position
+    pub fn sample(&self, sample: &Vec2) -> Vec3A {
+        let radiance = self.radiance(&self.args.camera.prime_ray(sample));
+
+        match self.args.hdr {
+            0 => tonemap_filmic(&radiance),
+            _ => radiance,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Contains the definition of the raymarched tunnel. The wall of the tunnel
+//! is described by a signed distance function, which is displaced by the
+//! levels of the frequency bands along rings.
+
+use glam::{vec2, vec3a, Vec2, Vec3A};
+
+#[cfg(target_arch = "spirv")]
+use num_traits::Float;
+
+use crate::utils::math::normalize;
+
+/// Defines the distance from the camera to the image plane, which results in
+/// a field of view of roughly 90 degrees
+const FOCAL_LENGTH: f32 = 1.0;
+
+/// Defines the distance after which a ray is considered to have missed the
+/// wall
+const MAX_DISTANCE: f32 = 100.0;
+
+/// Defines the fraction of the distance a ray advances per step. The
+/// displacement breaks the distance bound, so the rays march conservatively.
+const STEP_FACTOR: f32 = 0.5;
+
+/// Stores properties of the tunnel used for shader parameters
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct TunnelArgs {
+    /// Represents the color of the rings
+    pub color: Vec3A,
+    /// Represents the size of the viewport in pixels
+    pub size: Vec2,
+    /// Represents the offset of the viewport in pixels
+    pub offset: Vec2,
+    /// Represents the position of the camera along the tunnel
+    pub depth: f32,
+    /// Represents the radius of the undisplaced tunnel
+    pub radius: f32,
+    /// Represents how far a ring is pushed inwards at full level
+    pub displacement: f32,
+    /// Represents the distance between two rings
+    pub ring_spacing: f32,
+    /// Represents the rotation in radians of the bands per unit of depth
+    pub twist: f32,
+    /// Represents the density of the fog hiding the far end of the tunnel
+    pub fog: f32,
+    /// Represents the loudness by which the brightness is raised
+    pub loudness: f32,
+    /// Represents the maximum number of raymarching steps per pixel
+    pub steps: u32,
+}
+
+/// Stores properties of the tunnel scene
+pub struct Tunnel<'a> {
+    args: TunnelArgs,
+    levels: &'a [f32],
+}
+
+impl<'a> Tunnel<'a> {
+    /// Creates a new instance from shader parameters
+    pub fn from_args(args: TunnelArgs, levels: &'a [f32]) -> Self {
+        Self { args, levels }
+    }
+
+    /// Returns the level of the band at the given angle around the tunnel.
+    /// The bands are mirrored, so there is no seam at the top.
+    fn level(&self, angle: f32) -> f32 {
+        if self.levels.is_empty() {
+            return 0.0;
+        }
+
+        let band = (angle.abs() / core::f32::consts::PI * self.levels.len() as f32) as usize;
+
+        self.levels[band.min(self.levels.len() - 1)]
+    }
+
+    /// Returns the profile of the rings at the given depth, which is 1.0 in
+    /// the center of a ring and falls off towards the gaps
+    fn ring(&self, depth: f32) -> f32 {
+        let x = depth / self.args.ring_spacing;
+        let x = x - x.floor() - 0.5;
+
+        (-x * x * 32.0).exp()
+    }
+
+    /// Returns the angle around the tunnel including the twist at a point
+    fn angle(&self, point: &Vec3A) -> f32 {
+        let angle = point.y.atan2(point.x) + point.z * self.args.twist;
+
+        // wraps the angle back into -PI..PI
+        let tau = core::f32::consts::TAU;
+        angle - (angle / tau + 0.5).floor() * tau
+    }
+
+    /// Returns the signed distance to the wall of the tunnel, which is
+    /// positive inside the tunnel
+    pub fn distance(&self, point: &Vec3A) -> f32 {
+        let wall = self.args.radius
+            - self.args.displacement * self.level(self.angle(point)) * self.ring(point.z);
+
+        wall - vec2(point.x, point.y).length()
+    }
+
+    /// Samples the color at the given screen position
+    pub fn sample(&self, sample: &Vec2) -> Vec3A {
+        let uv = (*sample - self.args.offset) / self.args.size * 2.0 - 1.0;
+        let aspect = self.args.size.x / self.args.size.y;
+
+        // the shorter axis spans the field of view, so vertical viewports
+        // don't crop the sides of the tunnel
+        let scale = vec2(aspect.max(1.0), aspect.recip().max(1.0));
+
+        let origin = vec3a(0.0, 0.0, self.args.depth);
+        let direction = normalize(&vec3a(uv.x * scale.x, -uv.y * scale.y, FOCAL_LENGTH));
+
+        let mut t = 0.0;
+        for _ in 0..self.args.steps {
+            let distance = self.distance(&(origin + direction * t));
+
+            if distance < 0.001 * t || t > MAX_DISTANCE {
+                break;
+            }
+
+            t = t + distance * STEP_FACTOR;
+        }
+
+        let point = origin + direction * t;
+        let ring = self.ring(point.z);
+        let level = self.level(self.angle(&point));
+
+        let brightness = (0.15 + ring * (0.5 + level)) * (-t * self.args.fog).exp();
+
+        self.args.color * brightness * (1.0 + self.args.loudness)
+    }
+}
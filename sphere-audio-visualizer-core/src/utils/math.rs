@@ -5,7 +5,7 @@
 use core::arch::asm;
 #[cfg(target_arch = "spirv")]
 use glam::Vec4;
-use glam::{Mat4, Vec2, Vec3A};
+use glam::{vec3a, Mat4, Vec2, Vec3A};
 #[cfg(target_arch = "spirv")]
 use num_traits::Float;
 
@@ -25,6 +25,108 @@ pub fn tonemap_filmic(x: &Vec3A) -> Vec3A {
     return result.powf(2.2);
 }
 
+/// Applies the ACES filmic tonemapping curve fitted by Krzysztof Narkowicz
+/// <https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/>
+pub fn tonemap_aces(x: &Vec3A) -> Vec3A {
+    let x = x.max(Vec3A::splat(0.0));
+    let result = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+    result.clamp(Vec3A::splat(0.0), Vec3A::splat(1.0))
+}
+
+/// Applies Reinhard tonemapping, which compresses every channel into
+/// `0.0..1.0` without a toe
+pub fn tonemap_reinhard(x: &Vec3A) -> Vec3A {
+    let x = x.max(Vec3A::splat(0.0));
+    x / (x + 1.0)
+}
+
+/// Selects the operator mapping the radiance to displayable colors
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tonemapper {
+    /// Applies [`tonemap_filmic`]
+    Filmic,
+    /// Applies [`tonemap_aces`]
+    Aces,
+    /// Applies [`tonemap_reinhard`]
+    Reinhard,
+    /// Passes the radiance through, so it is only clipped by the target
+    Linear,
+}
+
+impl Tonemapper {
+    /// Contains every tonemapper
+    pub const ALL: [Tonemapper; 4] = [
+        Tonemapper::Filmic,
+        Tonemapper::Aces,
+        Tonemapper::Reinhard,
+        Tonemapper::Linear,
+    ];
+
+    /// Maps the radiance to a displayable color
+    pub fn tonemap(&self, x: &Vec3A) -> Vec3A {
+        match self {
+            Tonemapper::Filmic => tonemap_filmic(x),
+            Tonemapper::Aces => tonemap_aces(x),
+            Tonemapper::Reinhard => tonemap_reinhard(x),
+            Tonemapper::Linear => *x,
+        }
+    }
+}
+
+impl Default for Tonemapper {
+    fn default() -> Self {
+        Tonemapper::Filmic
+    }
+}
+
+/// Stores the exposure and the color grading applied around the tonemapping,
+/// so renders can be matched to other footage
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorGrading {
+    /// The exposure in stops, applied to the radiance before tonemapping
+    pub exposure: f32,
+    /// The contrast around middle grey, where `1.0` keeps the colors
+    pub contrast: f32,
+    /// The saturation, where `0.0` is greyscale and `1.0` keeps the colors
+    pub saturation: f32,
+    /// The gamma, where values above `1.0` brighten the dark colors
+    pub gamma: f32,
+}
+
+impl ColorGrading {
+    /// Scales the radiance by the exposure
+    pub fn expose(&self, radiance: &Vec3A) -> Vec3A {
+        *radiance * 2.0f32.powf(self.exposure)
+    }
+
+    /// Grades a tonemapped linear color
+    pub fn grade(&self, color: &Vec3A) -> Vec3A {
+        const MIDDLE_GREY: f32 = 0.18;
+
+        let color = color.max(Vec3A::splat(0.0));
+        let color = MIDDLE_GREY * (color / MIDDLE_GREY).powf(self.contrast);
+
+        let luminance = dot(&color, &vec3a(0.2126, 0.7152, 0.0722));
+        let color = (luminance + (color - luminance) * self.saturation).max(Vec3A::splat(0.0));
+
+        color.powf(1.0 / self.gamma)
+    }
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
 /// normalizes a vector
 #[cfg(target_arch = "spirv")]
 #[inline]
@@ -182,6 +284,17 @@ pub fn dot2(a: &Vec2, b: &Vec2) -> f32 {
     a.dot(*b)
 }
 
+/// Flips `normal` so it faces against `direction`, the direction of the
+/// incoming ray
+#[inline]
+pub fn face_forward(normal: &Vec3A, direction: &Vec3A) -> Vec3A {
+    if dot(normal, direction) > 0.0 {
+        -*normal
+    } else {
+        *normal
+    }
+}
+
 /// Transforms a vector `point` with matrix `transform`
 #[cfg(target_arch = "spirv")]
 #[inline]
@@ -227,18 +340,105 @@ pub fn transform_point3a(transform: &Mat4, point: &Vec3A) -> Vec3A {
     transform.transform_point3a(*point)
 }
 
-/// Transforms a vector `point` with matrix `transform`
+/// Transforms a direction `vector` with matrix `transform` ignoring the
+/// translation
 #[cfg(target_arch = "spirv")]
 #[inline]
-pub fn transform_vector3a(transform: &Mat4, point: &Vec3A) -> Vec3A {
-    transform_vector4(transform, &point.extend(0.0))
+pub fn transform_vector3a(transform: &Mat4, vector: &Vec3A) -> Vec3A {
+    transform_vector4(transform, &vector.extend(0.0))
         .truncate()
         .into()
 }
 
-/// Transforms a vector `point` with matrix `transform`
+/// Transforms a direction `vector` with matrix `transform` ignoring the
+/// translation
 #[cfg(not(target_arch = "spirv"))]
 #[inline]
-pub fn transform_vector3a(transform: &Mat4, point: &Vec3A) -> Vec3A {
-    transform.transform_point3a(*point)
+pub fn transform_vector3a(transform: &Mat4, vector: &Vec3A) -> Vec3A {
+    transform.transform_vector3a(*vector)
+}
+
+/// Transforms a surface `normal` from the local space of a shape to world
+/// space. `inverse_transform` maps from world space to the local space, like
+/// the transforms shapes store, so the normal is transformed by its
+/// transpose and stays perpendicular to the surface under non-uniform
+/// scaling. The result is normalized.
+///
+/// ```
+/// use sphere_audio_visualizer_core::{
+///     glam::{vec3, vec3a, Mat4},
+///     utils::math::transform_normal3a,
+/// };
+///
+/// let transform = Mat4::from_scale(vec3(2.0, 1.0, 1.0));
+/// let normal = transform_normal3a(&transform.inverse(), &vec3a(1.0, 1.0, 0.0));
+///
+/// assert!((normal - vec3a(1.0, 2.0, 0.0).normalize()).length() < 1e-6);
+/// ```
+#[inline]
+pub fn transform_normal3a(inverse_transform: &Mat4, normal: &Vec3A) -> Vec3A {
+    normalize(&transform_vector3a(&inverse_transform.transpose(), normal))
+}
+
+/// Returns the weight of a sample drawn with the density `pdf` when it is
+/// combined with another sampling strategy of density `other_pdf` by multiple
+/// importance sampling. This is Veach's power heuristic with an exponent of 2.
+#[inline]
+pub fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let pdf2 = pdf * pdf;
+    let sum = pdf2 + other_pdf * other_pdf;
+
+    if sum > 0.0 {
+        pdf2 / sum
+    } else {
+        0.0
+    }
+}
+
+/// Returns a direction on the hemisphere around the normalized `normal`
+/// distributed by the cosine to the normal. Its density with respect to the
+/// solid angle is `cos / PI`. `random` holds two numbers in `0.0..1.0`.
+pub fn cosine_hemisphere(normal: &Vec3A, random: &Vec2) -> Vec3A {
+    // builds an orthonormal basis without branching on the normal
+    // <https://graphics.pixar.com/library/OrthonormalB/paper.pdf>
+    let sign = if normal.z >= 0.0 { 1.0 } else { -1.0 };
+    let a = -1.0 / (sign + normal.z);
+    let b = normal.x * normal.y * a;
+    let tangent = vec3a(
+        1.0 + sign * normal.x * normal.x * a,
+        sign * b,
+        -sign * normal.x,
+    );
+    let bitangent = vec3a(b, sign + normal.y * normal.y * a, -normal.y);
+
+    let radius = random.x.sqrt();
+    let angle = random.y * core::f32::consts::TAU;
+
+    tangent * (radius * angle.cos())
+        + bitangent * (radius * angle.sin())
+        + *normal * (1.0 - random.x).max(0.0).sqrt()
+}
+
+/// Returns the smooth minimum of `a` and `b`, which blends the values when
+/// they are closer than `k`, and the weight of `a` in the blend. This is the
+/// polynomial smooth minimum by Inigo Quilez
+/// <https://iquilezles.org/articles/smin/>. A `k` of zero returns the minimum.
+///
+/// Example:
+///
+/// ```
+/// use sphere_audio_visualizer_core::utils::math::smooth_min;
+///
+/// assert_eq!(smooth_min(1.0, 3.0, 0.5), (1.0, 1.0));
+/// assert_eq!(smooth_min(1.0, 1.0, 0.5), (0.875, 0.5));
+/// assert_eq!(smooth_min(2.0, 1.0, 0.0), (1.0, 0.0));
+/// ```
+pub fn smooth_min(a: f32, b: f32, k: f32) -> (f32, f32) {
+    if k <= 0.0 {
+        return if a < b { (a, 1.0) } else { (b, 0.0) };
+    }
+
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+
+    (b + (a - b) * h - k * h * (1.0 - h), h)
 }
@@ -2,5 +2,6 @@
 
 pub mod math;
 mod option;
+pub mod random;
 
 pub use self::option::*;
@@ -44,7 +44,7 @@ impl<T> OptionPolyfill<T> {
     ///
     /// assert!(!option.is_none());
     /// assert!(option.is_some());
-    /// assert_eq!(unsafe { option.unwrap() }, 16);
+    /// assert_eq!(option.unwrap(), 16);
     /// ```
     pub fn some(value: T) -> Self {
         Self {
@@ -63,9 +63,50 @@ impl<T> OptionPolyfill<T> {
         !self.is_some
     }
 
-    /// Gets the internal value
-    pub unsafe fn unwrap(self) -> T {
-        return self.value;
+    /// Gets the internal value. On the host it is asserted in debug builds
+    /// that the Option is some.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::utils::OptionPolyfill;
+    ///
+    /// assert_eq!(OptionPolyfill::some(16).unwrap(), 16);
+    /// ```
+    pub fn unwrap(self) -> T {
+        #[cfg(not(target_arch = "spirv"))]
+        debug_assert!(self.is_some, "called `unwrap` on a none `OptionPolyfill`");
+
+        self.value
+    }
+
+    /// Gets the internal value without checking if the Option is some. This
+    /// is used by branchless code which only uses the value if the Option
+    /// turns out to be some.
+    ///
+    /// Safety: the returned value is an uninitialized placeholder if the
+    /// Option is none and must not be relied upon.
+    pub unsafe fn unwrap_unchecked(self) -> T {
+        self.value
+    }
+
+    /// Gets the internal value if the Option is some. Otherwise `default` is
+    /// returned.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::utils::OptionPolyfill;
+    ///
+    /// assert_eq!(OptionPolyfill::<u32>::some(16).unwrap_or(8), 16);
+    /// assert_eq!(OptionPolyfill::<u32>::none().unwrap_or(8), 8);
+    /// ```
+    pub fn unwrap_or(self, default: T) -> T {
+        if self.is_some {
+            self.value
+        } else {
+            default
+        }
     }
 
     /// Applying the function `f` to the contained value.
@@ -90,6 +131,31 @@ impl<T> OptionPolyfill<T> {
         }
     }
 
+    /// Applying the function `f` to the contained value if it is some and
+    /// returns the resulting Option.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::utils::OptionPolyfill;
+    ///
+    /// let half = |x: u32| OptionPolyfill::new(x % 2 == 0, x / 2);
+    ///
+    /// assert_eq!(OptionPolyfill::some(16).and_then(half), OptionPolyfill::some(8));
+    /// assert_eq!(OptionPolyfill::some(15).and_then(half).is_none(), true);
+    /// assert_eq!(OptionPolyfill::<u32>::none().and_then(half), OptionPolyfill::none());
+    /// ```
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> OptionPolyfill<U>) -> OptionPolyfill<U>
+    where
+        U: Uninit,
+    {
+        if self.is_some {
+            (f)(self.value)
+        } else {
+            OptionPolyfill::none()
+        }
+    }
+
     /// Applying the function `some` to the contained value if it is some.
     /// Otherwise use the function `none` to generate a value.
     ///
@@ -138,6 +204,49 @@ impl<T> OptionPolyfill<T> {
     }
 }
 
+#[cfg(not(target_arch = "spirv"))]
+impl<T: Uninit> From<Option<T>> for OptionPolyfill<T> {
+    /// Converts a Rust [`Option`] into an [`OptionPolyfill`]
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::utils::OptionPolyfill;
+    ///
+    /// assert_eq!(OptionPolyfill::from(Some(16)), OptionPolyfill::some(16));
+    /// assert_eq!(OptionPolyfill::<u32>::from(None), OptionPolyfill::none());
+    /// ```
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(value) => OptionPolyfill::some(value),
+            None => OptionPolyfill::none(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "spirv"))]
+impl<T> From<OptionPolyfill<T>> for Option<T> {
+    /// Converts an [`OptionPolyfill`] into a Rust [`Option`]
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// use sphere_audio_visualizer_core::utils::OptionPolyfill;
+    ///
+    /// assert_eq!(Option::from(OptionPolyfill::some(16)), Some(16));
+    ///
+    /// let none: Option<u32> = OptionPolyfill::none().into();
+    /// assert_eq!(none, None);
+    /// ```
+    fn from(option: OptionPolyfill<T>) -> Self {
+        if option.is_some {
+            Some(option.value)
+        } else {
+            None
+        }
+    }
+}
+
 /// This trait is used to generate uninitialized values
 pub trait Uninit {
     /// generates a uninitialized value
@@ -0,0 +1,105 @@
+//! Contains a deterministic hash based random number generator. The WGSL
+//! shaders contain the same functions, so both backends draw the same numbers.
+
+use glam::{vec2, Vec2};
+
+/// Hashes a value using the PCG hash
+pub fn hash(value: u32) -> u32 {
+    let state = value.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+
+    (word >> 22) ^ word
+}
+
+/// Returns the seed of a frame derived from the base seed
+pub fn frame_seed(seed: u32, frame: u32) -> u32 {
+    hash(seed ^ hash(frame))
+}
+
+/// Returns a random number in `0.0..1.0` for a pixel. The `dimension`
+/// separates independent numbers drawn for the same pixel.
+///
+/// ```
+/// use sphere_audio_visualizer_core::utils::random::random;
+///
+/// let value = random(42, 3, 7, 0);
+///
+/// assert!(value >= 0.0 && value < 1.0);
+/// assert_eq!(value, random(42, 3, 7, 0));
+/// ```
+pub fn random(seed: u32, x: u32, y: u32, dimension: u32) -> f32 {
+    unit(random_state(seed, x, y, dimension))
+}
+
+/// Returns the hashed state [`random`] derives its number from. The state
+/// can be hashed further to draw a sequence of numbers.
+pub fn random_state(seed: u32, x: u32, y: u32, dimension: u32) -> u32 {
+    hash(seed ^ hash(x ^ hash(y ^ hash(dimension))))
+}
+
+/// Converts a hashed state into a number in `0.0..1.0`
+pub fn unit(state: u32) -> f32 {
+    // only 24 bits fit into the mantissa, so the conversion is exact
+    (state >> 8) as f32 / 16777216.0
+}
+
+/// A sequence of random numbers for a pixel of a frame, e.g. to jitter the
+/// samples of soft shadows, depth of field or anti aliasing. Every number is
+/// hashed from the previous one, so the sequence only depends on its seed.
+///
+/// ```
+/// use sphere_audio_visualizer_core::utils::random::Random;
+///
+/// let mut a = Random::new(42, 1, 3, 7);
+/// let mut b = Random::new(42, 1, 3, 7);
+///
+/// let value = a.next_f32();
+///
+/// assert!(value >= 0.0 && value < 1.0);
+/// assert_eq!(value, b.next_f32());
+/// assert_ne!(a.next_u32(), Random::new(42, 2, 3, 7).next_u32());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Random {
+    state: u32,
+}
+
+impl Random {
+    /// Creates a new instance for the pixel at `x` and `y` of the frame with
+    /// the given base seed
+    pub fn new(seed: u32, frame: u32, x: u32, y: u32) -> Self {
+        Self::from_state(random_state(frame_seed(seed, frame), x, y, 0))
+    }
+
+    /// Creates a new instance continuing from a hashed state, e.g. the seed
+    /// of a frame passed to a shader
+    pub fn from_state(state: u32) -> Self {
+        Self { state }
+    }
+
+    /// Returns the current state, which continues the sequence if it is
+    /// passed to [`Random::from_state`]
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    /// Draws the next hashed state
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = hash(self.state);
+        self.state
+    }
+
+    /// Draws the next number in `0.0..1.0`
+    pub fn next_f32(&mut self) -> f32 {
+        unit(self.next_u32())
+    }
+
+    /// Draws the next two numbers in `0.0..1.0`, e.g. to sample a disk or a
+    /// square
+    pub fn next_vec2(&mut self) -> Vec2 {
+        let x = self.next_f32();
+        let y = self.next_f32();
+
+        vec2(x, y)
+    }
+}
@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use egui::{ComboBox, Grid, Ui};
+use gstreamer::prelude::DeviceMonitorExtManual;
+use gstreamer::traits::DeviceExt;
+use gstreamer::{Device, DeviceMonitor, ElementFactory, IntRange};
+use sphere_audio_visualizer::{audio_analysis::Samples, OnlineSampleSource, SampleSourceStatus};
+
+use crate::Settings;
+
+use super::StaticSystemSampleSource;
+
+/// Defines the buffer sizes in samples offered by the [`ASIOSampleSource`].
+/// Zero keeps the buffer size preferred by the driver.
+pub const ASIO_BUFFER_SIZES: [u32; 7] = [0, 32, 64, 128, 256, 512, 1024];
+
+/// Defines the amount of channels assumed for drivers which don't report it
+const ASIO_DEFAULT_CHANNELS: u32 = 2;
+
+/// A [`OnlineSampleSource`] based on the `asiosrc` of GStreamer, which
+/// captures professional audio interfaces with a low latency on Windows. The
+/// selected input channels are mixed down to mono. The source only lists
+/// devices if the ASIO plugin of GStreamer and an ASIO driver are installed.
+pub struct ASIOSampleSource {
+    settings: Arc<Settings>,
+    device_monitor: DeviceMonitor,
+    device: Option<Device>,
+    channels: Vec<u32>,
+    buffer_size: u32,
+    inner: Option<StaticSystemSampleSource>,
+}
+
+impl ASIOSampleSource {
+    /// Creates a new instance with the first two channels of the first ASIO
+    /// device
+    pub fn new(settings: Arc<Settings>) -> Self {
+        let device_monitor = DeviceMonitor::new();
+
+        device_monitor.add_filter(Some("Audio/Source"), None);
+
+        let device = asio_devices(&device_monitor).into_iter().next();
+
+        Self {
+            settings,
+            device_monitor,
+            device,
+            channels: vec![0, 1],
+            buffer_size: 0,
+            inner: None,
+        }
+    }
+
+    /// Builder method for setting the buffer size in samples. Zero keeps the
+    /// buffer size preferred by the driver.
+    pub fn with_buffer_size(mut self, buffer_size: u32) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Gets the buffer size in samples
+    pub fn buffer_size(&self) -> u32 {
+        self.buffer_size
+    }
+
+    /// Gets the captured input channels, starting at zero
+    pub fn channels(&self) -> &[u32] {
+        &self.channels
+    }
+
+    fn update(&mut self) {
+        self.inner = self.recreate_inner();
+    }
+
+    fn recreate_inner(&self) -> Option<StaticSystemSampleSource> {
+        let device = self.device.as_ref()?;
+        let clsid = device.properties()?.get::<String>("device.clsid").ok()?;
+
+        // channels beyond the device are rejected by the driver
+        let channel_count = channel_count(device);
+        let input_channels = self
+            .channels
+            .iter()
+            .filter(|channel| **channel < channel_count)
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let element = ElementFactory::make("asiosrc")
+            .property("device-clsid", clsid)
+            .property("input-channels", input_channels)
+            .property("buffer-size", self.buffer_size)
+            .build();
+
+        match element {
+            Ok(element) => Some(StaticSystemSampleSource::new(
+                &element,
+                self.settings.sample_rates[self.settings.default_sample_rate],
+            )),
+            Err(error) => {
+                eprintln!("Failed to create ASIO source: {}", error);
+                None
+            }
+        }
+    }
+}
+
+/// Returns the devices of the monitor provided by the ASIO plugin
+fn asio_devices(device_monitor: &DeviceMonitor) -> Vec<Device> {
+    device_monitor
+        .devices()
+        .into_iter()
+        .filter(|device| {
+            device
+                .properties()
+                .and_then(|properties| properties.get::<String>("device.api").ok())
+                .as_deref()
+                == Some("asio")
+        })
+        .collect()
+}
+
+/// Returns the amount of input channels of the device
+fn channel_count(device: &Device) -> u32 {
+    let channels = device.caps().and_then(|caps| {
+        let structure = caps.structure(0)?;
+
+        structure
+            .get::<i32>("channels")
+            .or_else(|_| {
+                structure
+                    .get::<IntRange<i32>>("channels")
+                    .map(|range| range.max())
+            })
+            .ok()
+    });
+
+    channels.map_or(ASIO_DEFAULT_CHANNELS, |channels| channels.max(1) as u32)
+}
+
+fn buffer_size_name(buffer_size: u32) -> String {
+    match buffer_size {
+        0 => "Driver Default".to_string(),
+        buffer_size => format!("{} samples", buffer_size),
+    }
+}
+
+impl OnlineSampleSource for ASIOSampleSource {
+    fn samples(&mut self) -> Samples {
+        match &mut self.inner {
+            Some(inner) => inner.samples(),
+            None => Samples {
+                sample_rate: 44100.0,
+                samples: &[],
+            },
+        }
+    }
+
+    fn unfocus(&mut self) {
+        self.inner = None;
+    }
+
+    fn focus(&mut self) {
+        self.update();
+    }
+
+    fn status(&self) -> Option<SampleSourceStatus> {
+        self.inner.as_ref()?.status()
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        Grid::new("ASIO Sample Source Settings")
+            .num_columns(2)
+            .striped(true)
+            .min_col_width(72.0)
+            .show(ui, |ui| {
+                let devices = asio_devices(&self.device_monitor);
+
+                if devices.is_empty() {
+                    ui.label("Device:");
+                    ui.label("No ASIO driver found");
+                    ui.end_row();
+                    return;
+                }
+
+                let device_name = self
+                    .device
+                    .as_ref()
+                    .map(|device| device.display_name().to_string())
+                    .unwrap_or("".to_string());
+
+                let old_device = self.device.clone();
+                let old_channels = self.channels.clone();
+                let old_buffer_size = self.buffer_size;
+
+                ui.label("Device:");
+                ComboBox::from_id_source("ASIO Audio Device")
+                    .selected_text(&device_name[..device_name.len().min(22)])
+                    .width(168.0)
+                    .show_ui(ui, |ui| {
+                        for device in devices {
+                            let name = device.display_name().to_string();
+                            ui.selectable_value(&mut self.device, Some(device), name);
+                        }
+                    });
+                ui.end_row();
+
+                // the channels of another device may not exist, so the
+                // selection starts over
+                if old_device != self.device {
+                    self.channels = vec![0, 1];
+                }
+
+                let channel_count = self.device.as_ref().map_or(0, channel_count);
+                self.channels.retain(|channel| *channel < channel_count);
+
+                if self.channels.is_empty() {
+                    self.channels.push(0);
+                }
+
+                let selected_text = self
+                    .channels
+                    .iter()
+                    .map(|channel| (channel + 1).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                ui.label("Channels:");
+                ComboBox::from_id_source("ASIO Audio Channels")
+                    .selected_text(selected_text)
+                    .width(168.0)
+                    .show_ui(ui, |ui| {
+                        for channel in 0..channel_count {
+                            let selected = self.channels.contains(&channel);
+
+                            // at least one channel stays selected
+                            if ui
+                                .selectable_label(selected, format!("Channel {}", channel + 1))
+                                .clicked()
+                            {
+                                if !selected {
+                                    self.channels.push(channel);
+                                    self.channels.sort_unstable();
+                                } else if self.channels.len() > 1 {
+                                    self.channels.retain(|other| *other != channel);
+                                }
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Buffer Size:");
+                ComboBox::from_id_source("ASIO Audio Buffer Size")
+                    .selected_text(buffer_size_name(self.buffer_size))
+                    .width(168.0)
+                    .show_ui(ui, |ui| {
+                        for buffer_size in ASIO_BUFFER_SIZES {
+                            ui.selectable_value(
+                                &mut self.buffer_size,
+                                buffer_size,
+                                buffer_size_name(buffer_size),
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                if old_device != self.device
+                    || old_channels != self.channels
+                    || old_buffer_size != self.buffer_size
+                {
+                    self.update()
+                }
+            });
+    }
+}
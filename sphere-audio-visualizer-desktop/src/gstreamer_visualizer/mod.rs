@@ -1,23 +1,41 @@
 //! Contains the implementation to harness the power of GStreamer for the
 //! Sphere Audio Visualizer.
 
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
 
-pub use self::{system::*, uri::*, visualizer::*};
+#[cfg(windows)]
+pub use self::asio::*;
+pub use self::{profile::*, system::*, uri::*, visualizer::*};
 use gstreamer::{
-    glib::clone::Downgrade, prelude::ElementExtManual, traits::PadExt, FlowSuccess, Sample,
+    glib::clone::Downgrade, prelude::ElementExtManual, traits::PadExt, Caps, FlowSuccess, Sample,
 };
 use gstreamer_app::{AppSink, AppSinkCallbacks};
 use gstreamer_audio::{AudioCapsBuilder, AUDIO_FORMAT_F32};
+use gstreamer_video::VideoFormat;
 use serde::{Deserialize, Serialize};
-use sphere_audio_visualizer::audio_analysis::Samples;
+use sphere_audio_visualizer::{
+    audio_analysis::Samples, rendering::wgpu::OutputFormat, SampleSourceStatus, UnderrunCounter,
+};
 
+#[cfg(windows)]
+mod asio;
+mod profile;
 mod system;
 mod uri;
 mod visualizer;
 
+/// Defines the maximum amount of seconds a [`GStreamerSampleSource`] buffers.
+/// Older samples are dropped, e.g. while the window is minimized.
+pub const MAX_BUFFER_DURATION: f64 = 1.0;
+
+/// Defines the range of the width and height of an export in pixels
+pub const RESULUTION_RANGE: (u32, u32) = (16, 8192);
+
 /// Stores resulution settings
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct Resulution {
     /// Represents the width in pixels
     pub width: u32,
@@ -25,6 +43,20 @@ pub struct Resulution {
     pub height: u32,
 }
 
+impl Resulution {
+    /// Returns the resulution clamped to [`RESULUTION_RANGE`] and rounded to
+    /// even dimensions, as encoders subsampling the chroma reject odd ones
+    pub fn validated(&self) -> Self {
+        let (min, max) = RESULUTION_RANGE;
+        let even = |value: u32| (value.clamp(min, max) + 1) & !1;
+
+        Self {
+            width: even(self.width),
+            height: even(self.height),
+        }
+    }
+}
+
 /// Stores encoding settings
 #[derive(Serialize, Deserialize, Clone)]
 pub struct EncodingSettings {
@@ -38,6 +70,46 @@ pub struct EncodingSettings {
     pub video_caps: String,
     /// Represents the extension of the file
     pub extension: String,
+    /// Represents the raw video format handed to the encoder
+    #[serde(default)]
+    pub video_format: RawVideoFormat,
+}
+
+/// Specifies the raw video format the visualizer produces for the encoder. The
+/// conversion from RGBA is done on the GPU.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub enum RawVideoFormat {
+    /// 8-Bit Red Green Blue Alpha Color
+    RGBA,
+    /// 8-Bit Blue Green Red Alpha Color
+    BGRA,
+    /// Planar 8-Bit YUV 4:2:0
+    #[default]
+    I420,
+    /// 8-Bit YUV 4:2:0 with interleaved UV plane
+    NV12,
+}
+
+impl From<RawVideoFormat> for OutputFormat {
+    fn from(format: RawVideoFormat) -> Self {
+        match format {
+            RawVideoFormat::RGBA => OutputFormat::RGBA8,
+            RawVideoFormat::BGRA => OutputFormat::BGRA8,
+            RawVideoFormat::I420 => OutputFormat::I420,
+            RawVideoFormat::NV12 => OutputFormat::NV12,
+        }
+    }
+}
+
+impl From<RawVideoFormat> for VideoFormat {
+    fn from(format: RawVideoFormat) -> Self {
+        match format {
+            RawVideoFormat::RGBA => VideoFormat::Rgba,
+            RawVideoFormat::BGRA => VideoFormat::Bgra,
+            RawVideoFormat::I420 => VideoFormat::I420,
+            RawVideoFormat::NV12 => VideoFormat::Nv12,
+        }
+    }
 }
 
 /// Stores multible samples but content is mutable
@@ -62,6 +134,8 @@ pub struct GStreamerSampleSource {
     app_sink: AppSink,
     samples: Vec<f32>,
     sample_buffer: Arc<Mutex<Vec<f32>>>,
+    overruns: Arc<AtomicU64>,
+    underruns: UnderrunCounter,
 }
 
 impl GStreamerSampleSource {
@@ -76,18 +150,34 @@ impl GStreamerSampleSource {
             sink_caps_builder = sink_caps_builder.rate_range(1..max_sample_rate as i32);
         }
 
-        let sink_caps = sink_caps_builder.build();
+        Self::from_caps(&sink_caps_builder.build())
+    }
 
+    /// Creates a new instance, which only accepts the given sample rate, e.g.
+    /// so the samples of multiple sources can be mixed
+    pub fn with_sample_rate(sample_rate: u64) -> Self {
+        let sink_caps = AudioCapsBuilder::new()
+            .format(AUDIO_FORMAT_F32)
+            .channels(1i32)
+            .rate(sample_rate as i32)
+            .build();
+
+        Self::from_caps(&sink_caps)
+    }
+
+    fn from_caps(sink_caps: &Caps) -> Self {
         let app_sink = AppSink::builder()
-            .caps(&sink_caps)
+            .caps(sink_caps)
             .max_buffers(8)
             .drop(true)
             .build();
 
         let sample_buffer = Arc::new(Mutex::new(Vec::<f32>::new()));
+        let overruns = Arc::new(AtomicU64::new(0));
 
         {
             let sample_buffer = sample_buffer.downgrade();
+            let overruns = overruns.clone();
 
             app_sink.set_callbacks(
                 AppSinkCallbacks::builder()
@@ -96,6 +186,7 @@ impl GStreamerSampleSource {
                             Self::extend_samples(
                                 &mut sample_buffer.lock().unwrap(),
                                 app_sink.pull_sample().unwrap(),
+                                &overruns,
                             );
                         }
 
@@ -109,10 +200,17 @@ impl GStreamerSampleSource {
             app_sink,
             sample_buffer,
             samples: vec![],
+            overruns,
+            underruns: UnderrunCounter::new(),
         }
     }
 
-    fn extend_samples(sample_buffer: &mut Vec<f32>, gst_sample: Sample) {
+    fn extend_samples(sample_buffer: &mut Vec<f32>, gst_sample: Sample, overruns: &AtomicU64) {
+        let sample_rate = gst_sample
+            .caps()
+            .and_then(|caps| caps.structure(0)?.get::<i32>("rate").ok())
+            .unwrap_or(44100);
+
         let gst_buffer = gst_sample.buffer().unwrap();
 
         let gst_mapped_buffer = gst_buffer.map_readable().unwrap();
@@ -123,6 +221,15 @@ impl GStreamerSampleSource {
         let silce = unsafe { &*std::ptr::slice_from_raw_parts(ptr, samples) };
 
         sample_buffer.extend(silce);
+
+        // the oldest samples are dropped if they are not requested fast enough
+        let max_len = (sample_rate as f64 * MAX_BUFFER_DURATION) as usize;
+
+        if sample_buffer.len() > max_len {
+            let dropped = sample_buffer.len() - max_len;
+            sample_buffer.drain(..dropped);
+            overruns.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     /// Gets the collected sample also clears the internal buffer.
@@ -131,12 +238,31 @@ impl GStreamerSampleSource {
 
         std::mem::swap(&mut self.samples, &mut self.sample_buffer.lock().unwrap());
 
+        self.underruns.record(self.samples.len());
+
         SamplesMut {
             sample_rate: self.sample_rate().unwrap_or(44100.0),
             samples: &mut self.samples,
         }
     }
 
+    /// Forgets the last samples for the underrun detection, e.g. because the
+    /// pipeline was paused
+    pub fn pause(&mut self) {
+        self.underruns.pause();
+    }
+
+    /// Returns the underruns, the overruns and the sample rate of the sink.
+    /// Lost devices are reported by the pipeline.
+    pub fn status(&self) -> SampleSourceStatus {
+        SampleSourceStatus {
+            underruns: self.underruns.underruns(),
+            overruns: self.overruns.load(Ordering::Relaxed),
+            sample_rate: self.sample_rate(),
+            device_lost: false,
+        }
+    }
+
     fn sample_rate(&self) -> Option<f64> {
         Some(
             self.app_sink
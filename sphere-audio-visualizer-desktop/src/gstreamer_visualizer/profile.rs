@@ -0,0 +1,369 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use egui::{Button, ComboBox, DragValue, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::Settings;
+
+use super::{EncodingSettings, Resulution, RESULUTION_RANGE};
+
+/// Stores a named combination of the resulution, frame rate and encoding of
+/// an export, e.g. the settings a video platform recommends
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportProfile {
+    /// Represents the name that is shown in the UI
+    pub name: String,
+    /// Represents the resulution of the video
+    pub resulution: Resulution,
+    /// Represents the frame rate of the video
+    pub frame_rate: u64,
+    /// Represents the encoding of the video
+    pub encoding: EncodingSettings,
+}
+
+/// Stores the export profiles saved by the user in a YAML file. The store is
+/// shared by the sample sources, so a profile saved in one of them is offered
+/// by all of them.
+pub struct ExportProfileStore {
+    path: PathBuf,
+    profiles: Mutex<Vec<ExportProfile>>,
+}
+
+impl ExportProfileStore {
+    /// Creates a new instance with the profiles of the file at `path`. A
+    /// missing file is created by the first saved profile.
+    pub fn load(path: impl Into<PathBuf>) -> Arc<Self> {
+        let path = path.into();
+
+        let profiles = match File::open(&path) {
+            Ok(file) => serde_yaml::from_reader(BufReader::new(file)).unwrap_or_else(|error| {
+                eprintln!("Failed to load export profiles {:?}: {}", path, error);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Arc::new(Self {
+            path,
+            profiles: Mutex::new(profiles),
+        })
+    }
+
+    /// Returns the saved profiles
+    pub fn profiles(&self) -> Vec<ExportProfile> {
+        self.profiles.lock().unwrap().clone()
+    }
+
+    /// Saves a profile, which replaces a saved profile of the same name
+    pub fn save(&self, profile: ExportProfile) {
+        let mut profiles = self.profiles.lock().unwrap();
+
+        match profiles.iter_mut().find(|saved| saved.name == profile.name) {
+            Some(saved) => *saved = profile,
+            None => profiles.push(profile),
+        }
+
+        self.write(&profiles);
+    }
+
+    /// Removes the saved profile with the given name
+    pub fn remove(&self, name: &str) {
+        let mut profiles = self.profiles.lock().unwrap();
+
+        profiles.retain(|saved| saved.name != name);
+
+        self.write(&profiles);
+    }
+
+    fn write(&self, profiles: &[ExportProfile]) {
+        let result = File::create(&self.path)
+            .map_err(|error| error.to_string())
+            .and_then(|file| {
+                serde_yaml::to_writer(BufWriter::new(file), profiles)
+                    .map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            eprintln!("Failed to save export profiles {:?}: {}", self.path, error);
+        }
+    }
+}
+
+/// Selects the resulution, frame rate and encoding of an export by a profile.
+/// The custom profile picks them from the presets of the [`Settings`] and can
+/// be saved under a name, if a [`ExportProfileStore`] is set.
+pub(crate) struct ExportProfileSelector {
+    settings: Arc<Settings>,
+    store: Option<Arc<ExportProfileStore>>,
+    profile: Option<ExportProfile>,
+    frame_rate_id: usize,
+    resulution: Resulution,
+    encoding_id: usize,
+    profile_name: String,
+}
+
+impl ExportProfileSelector {
+    /// Creates a new instance with the default profile of the settings. The
+    /// custom profile starts with the default presets.
+    pub(crate) fn new(settings: Arc<Settings>) -> Self {
+        let profile = settings
+            .default_export_profile
+            .and_then(|id| settings.export_profiles.get(id))
+            .cloned();
+
+        Self {
+            frame_rate_id: settings.default_frame_rate,
+            resulution: settings.resulutions[settings.default_resulution].clone(),
+            encoding_id: settings.default_encoding,
+            settings,
+            store: None,
+            profile,
+            profile_name: String::new(),
+        }
+    }
+
+    /// Sets the store the custom profiles are saved to
+    pub(crate) fn set_store(&mut self, store: Arc<ExportProfileStore>) {
+        self.store = Some(store);
+    }
+
+    pub(crate) fn frame_rate(&self) -> u64 {
+        match &self.profile {
+            Some(profile) => profile.frame_rate,
+            None => self.settings.frame_rates[self.frame_rate_id],
+        }
+    }
+
+    pub(crate) fn resulution(&self) -> &Resulution {
+        match &self.profile {
+            Some(profile) => &profile.resulution,
+            None => &self.resulution,
+        }
+    }
+
+    pub(crate) fn encoding(&self) -> &EncodingSettings {
+        match &self.profile {
+            Some(profile) => &profile.encoding,
+            None => &self.settings.encodings[self.encoding_id],
+        }
+    }
+
+    /// Returns the selected profile or the custom one
+    pub(crate) fn profile(&self) -> ExportProfile {
+        match &self.profile {
+            Some(profile) => profile.clone(),
+            None => self.custom_profile(),
+        }
+    }
+
+    /// Switches to the custom profile, which starts with the resulution and
+    /// the presets of the selected profile where they are available
+    fn customize(&mut self) {
+        if let Some(profile) = self.profile.take() {
+            let settings = &self.settings;
+
+            if let Some(id) = settings
+                .frame_rates
+                .iter()
+                .position(|frame_rate| *frame_rate == profile.frame_rate)
+            {
+                self.frame_rate_id = id;
+            }
+
+            if let Some(id) = settings
+                .encodings
+                .iter()
+                .position(|encoding| encoding.name == profile.encoding.name)
+            {
+                self.encoding_id = id;
+            }
+
+            self.resulution = profile.resulution;
+            self.profile_name = profile.name;
+        }
+    }
+
+    fn custom_profile(&self) -> ExportProfile {
+        ExportProfile {
+            name: self.profile_name.trim().to_string(),
+            resulution: self.resulution().clone(),
+            frame_rate: self.frame_rate(),
+            encoding: self.encoding().clone(),
+        }
+    }
+
+    /// Draws the rows of the selector into a grid. `id_source` distinguishes
+    /// the combo boxes of different sample sources.
+    pub(crate) fn ui(&mut self, id_source: &str, ui: &mut Ui) {
+        let saved_profiles = self
+            .store
+            .as_ref()
+            .map(|store| store.profiles())
+            .unwrap_or_default();
+
+        let selected_name = self.profile.as_ref().map(|profile| profile.name.clone());
+        let is_saved = selected_name
+            .as_ref()
+            .map(|name| saved_profiles.iter().any(|saved| &saved.name == name))
+            .unwrap_or(false);
+
+        let mut selected = None;
+        let mut custom = false;
+        let mut remove = false;
+
+        ui.label("Profile:");
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source(format!("{} Export Profile", id_source))
+                .selected_text(selected_name.as_deref().unwrap_or("Custom"))
+                .width(132.0)
+                .show_ui(ui, |ui| {
+                    custom = ui
+                        .selectable_label(selected_name.is_none(), "Custom")
+                        .clicked();
+
+                    for profile in self.settings.export_profiles.iter().chain(&saved_profiles) {
+                        if ui
+                            .selectable_label(
+                                selected_name.as_ref() == Some(&profile.name),
+                                &profile.name,
+                            )
+                            .clicked()
+                        {
+                            selected = Some(profile.clone());
+                        }
+                    }
+                });
+
+            // only the profiles saved by the user can be removed
+            remove = ui
+                .add_enabled(is_saved, Button::new("✖"))
+                .on_hover_text("Remove the saved profile")
+                .clicked();
+        });
+        ui.end_row();
+
+        if custom {
+            self.customize();
+        } else if let Some(profile) = selected {
+            self.profile = Some(profile);
+        }
+
+        if remove {
+            if let (Some(store), Some(name)) = (&self.store, &selected_name) {
+                store.remove(name);
+            }
+
+            self.customize();
+        }
+
+        if self.profile.is_some() {
+            return;
+        }
+
+        ui.label("Resulution:");
+        let selected_text = match self.settings.resulutions.contains(&self.resulution) {
+            true => format!("{}x{}", self.resulution.width, self.resulution.height),
+            false => "Custom".to_string(),
+        };
+        ComboBox::from_id_source(format!("{} Video Resulution", id_source))
+            .selected_text(selected_text)
+            .width(168.0)
+            .show_ui(ui, |ui| {
+                for preset in &self.settings.resulutions {
+                    ui.selectable_value(
+                        &mut self.resulution,
+                        preset.clone(),
+                        format!("{}x{}", preset.width, preset.height),
+                    );
+                }
+            });
+        ui.end_row();
+
+        // encoders subsampling the chroma reject odd dimensions, so the
+        // entered size is rounded to even numbers
+        let (min, max) = RESULUTION_RANGE;
+        let mut changed = false;
+
+        ui.label("Size:");
+        ui.horizontal(|ui| {
+            changed |= ui
+                .add_sized(
+                    [76.0, 20.0],
+                    DragValue::new(&mut self.resulution.width)
+                        .speed(2.0)
+                        .clamp_range(min..=max)
+                        .suffix(" px"),
+                )
+                .changed();
+            ui.label("x");
+            changed |= ui
+                .add_sized(
+                    [76.0, 20.0],
+                    DragValue::new(&mut self.resulution.height)
+                        .speed(2.0)
+                        .clamp_range(min..=max)
+                        .suffix(" px"),
+                )
+                .changed();
+        });
+        ui.end_row();
+
+        if changed {
+            self.resulution = self.resulution.validated();
+        }
+
+        ui.label("Frame Rate:");
+        ComboBox::from_id_source(format!("{} Video Frame Rate", id_source))
+            .selected_text(format!("{} hz", self.frame_rate()))
+            .width(168.0)
+            .show_ui(ui, |ui| {
+                for (id, preset) in self.settings.frame_rates.iter().enumerate() {
+                    ui.selectable_value(&mut self.frame_rate_id, id, format!("{} hz", preset));
+                }
+            });
+        ui.end_row();
+
+        ui.label("Encoding:");
+        ComboBox::from_id_source(format!("{} Video Encoding", id_source))
+            .selected_text(&self.encoding().name)
+            .width(168.0)
+            .show_ui(ui, |ui| {
+                for (id, preset) in self.settings.encodings.iter().enumerate() {
+                    ui.selectable_value(&mut self.encoding_id, id, &preset.name);
+                }
+            });
+        ui.end_row();
+
+        if self.store.is_some() {
+            let mut save = false;
+
+            ui.label("Save As:");
+            ui.horizontal(|ui| {
+                ui.add_sized(
+                    [124.0, 20.0],
+                    TextEdit::singleline(&mut self.profile_name).hint_text("Profile name"),
+                );
+
+                save = ui
+                    .add_enabled(!self.profile_name.trim().is_empty(), Button::new("Save"))
+                    .clicked();
+            });
+            ui.end_row();
+
+            if save {
+                let profile = self.custom_profile();
+
+                if let Some(store) = &self.store {
+                    store.save(profile.clone());
+                }
+
+                self.profile = Some(profile);
+            }
+        }
+    }
+}
@@ -1,22 +1,47 @@
-use std::sync::Arc;
-
-use egui::{ComboBox, Grid, Ui};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use egui::{Button, ComboBox, Grid, Ui};
 use gstreamer::prelude::{DeviceMonitorExtManual, ElementExtManual};
 use gstreamer::traits::{DeviceExt, ElementExt, GstBinExt};
-use gstreamer::{Device, DeviceMonitor, Element, ElementFactory, Pipeline, State};
-use sphere_audio_visualizer::{audio_analysis::Samples, OnlineSampleSource};
+use gstreamer::{
+    Bus, Device, DeviceMonitor, Element, ElementFactory, MessageType, MessageView, Pipeline, State,
+};
+use rfd::FileDialog;
+use sphere_audio_visualizer::{
+    audio_analysis::Samples, rendering::wgpu::OutputFormat, ExportProcess, Exporter,
+    OfflineVisualizer, OnlineSampleSource, SampleSourceStatus,
+};
 
 use crate::Settings;
 
-use super::GStreamerSampleSource;
+use super::{
+    EncodingSettings, ExportProfileSelector, ExportProfileStore, ExportTarget,
+    GStreamerSampleSource, TimecodeMode, URIExport, URI_EXPORT_AV_OFFSET, URI_EXPORT_WARM_UP,
+};
+
+/// Defines the default maximum amount of seconds kept in the session buffer of
+/// the [`SystemSampleSource`]
+pub const SYSTEM_SESSION_MAX_DURATION: f64 = 600.0;
 
 /// A [`OnlineSampleSource`] based on a GStreamer
-/// [`DeviceMonitor`] inputs
+/// [`DeviceMonitor`] inputs. The samples can be recorded into a session buffer,
+/// which is exported like a file by the [`Exporter`] implementation.
 pub struct SystemSampleSource {
     settings: Arc<Settings>,
     device_monitor: DeviceMonitor,
     device: Option<Device>,
     sample_rate_id: usize,
+    export_profile: ExportProfileSelector,
+    recording: bool,
+    session_sample_rate: f64,
+    session: Vec<f32>,
+    max_session_duration: f64,
     inner: Option<StaticSystemSampleSource>,
 }
 
@@ -30,16 +55,63 @@ impl SystemSampleSource {
         let device = device_monitor.devices().pop_front();
 
         let sample_rate_id = settings.default_sample_rate;
+        let export_profile = ExportProfileSelector::new(settings.clone());
 
         Self {
             settings,
             device_monitor,
             device,
             sample_rate_id,
+            export_profile,
+            recording: false,
+            session_sample_rate: 44100.0,
+            session: Vec::new(),
+            max_session_duration: SYSTEM_SESSION_MAX_DURATION,
             inner: None,
         }
     }
 
+    /// Builder method for setting the maximum amount of seconds kept in the
+    /// session buffer
+    pub fn with_max_session_duration(mut self, max_session_duration: f64) -> Self {
+        self.max_session_duration = max_session_duration;
+        self
+    }
+
+    /// Builder method for setting the store the export profiles are saved
+    /// to. Saving profiles is only offered in the UI if a store is set.
+    pub fn with_export_profile_store(mut self, store: Arc<ExportProfileStore>) -> Self {
+        self.export_profile.set_store(store);
+        self
+    }
+
+    /// Gets the maximum amount of seconds kept in the session buffer
+    pub fn max_session_duration(&self) -> f64 {
+        self.max_session_duration
+    }
+
+    /// Returns if the samples are currently recorded into the session buffer
+    pub fn recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Gets the amount of seconds in the session buffer
+    pub fn session_duration(&self) -> f64 {
+        self.session.len() as f64 / self.session_sample_rate
+    }
+
+    /// Clears the session buffer and starts recording into it
+    pub fn start_recording(&mut self) {
+        self.session.clear();
+        self.recording = true;
+    }
+
+    /// Stops recording into the session buffer. The buffer is kept for the
+    /// export.
+    pub fn stop_recording(&mut self) {
+        self.recording = false;
+    }
+
     fn update(&mut self) {
         self.inner = self.recreate_inner();
     }
@@ -56,12 +128,35 @@ impl SystemSampleSource {
     fn sample_rate(&self) -> u64 {
         self.settings.sample_rates[self.sample_rate_id]
     }
+
+    fn encoding(&self) -> &EncodingSettings {
+        self.export_profile.encoding()
+    }
 }
 
 impl OnlineSampleSource for SystemSampleSource {
     fn samples(&mut self) -> Samples {
         if let Some(inner) = &mut self.inner {
-            inner.samples()
+            let samples = inner.samples();
+
+            if self.recording {
+                // the buffer only holds samples of a single sample rate
+                if samples.sample_rate != self.session_sample_rate {
+                    self.session_sample_rate = samples.sample_rate;
+                    self.session.clear();
+                }
+
+                let max_len = (self.max_session_duration * self.session_sample_rate) as usize;
+
+                self.session.extend(samples.samples);
+
+                if self.session.len() >= max_len {
+                    self.session.truncate(max_len);
+                    self.recording = false;
+                }
+            }
+
+            samples
         } else {
             Samples {
                 sample_rate: 44100.0,
@@ -71,6 +166,7 @@ impl OnlineSampleSource for SystemSampleSource {
     }
 
     fn unfocus(&mut self) {
+        self.recording = false;
         self.inner = None;
     }
 
@@ -78,6 +174,10 @@ impl OnlineSampleSource for SystemSampleSource {
         self.update();
     }
 
+    fn status(&self) -> Option<SampleSourceStatus> {
+        self.inner.as_ref()?.status()
+    }
+
     fn ui(&mut self, ui: &mut Ui) {
         Grid::new("System Sample Source Settings")
             .num_columns(2)
@@ -121,6 +221,22 @@ impl OnlineSampleSource for SystemSampleSource {
                     });
                 ui.end_row();
 
+                ui.label("Session:");
+                let text = match self.recording {
+                    true => format!("Stop ({:.1} s)", self.session_duration()),
+                    false => "Record".to_string(),
+                };
+                ui.add_enabled_ui(self.inner.is_some(), |ui| {
+                    if ui.add_sized([176.0, 20.0], Button::new(text)).clicked() {
+                        if self.recording {
+                            self.stop_recording();
+                        } else {
+                            self.start_recording();
+                        }
+                    }
+                });
+                ui.end_row();
+
                 if old_device != self.device || old_sample_rate != self.sample_rate() {
                     self.update()
                 }
@@ -128,14 +244,140 @@ impl OnlineSampleSource for SystemSampleSource {
     }
 }
 
-struct StaticSystemSampleSource {
+impl Exporter for SystemSampleSource {
+    fn format(&self) -> OutputFormat {
+        self.encoding().video_format.into()
+    }
+
+    fn can_export(&self) -> bool {
+        !self.recording && !self.session.is_empty()
+    }
+
+    fn export(&mut self, visualizer: Box<dyn OfflineVisualizer>) -> Option<Box<dyn ExportProcess>> {
+        let encoding = self.encoding();
+
+        let save_path = FileDialog::new()
+            .add_filter(&encoding.extension, &[&encoding.extension])
+            .save_file()?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+
+        let session_path =
+            std::env::temp_dir().join(format!("sphere-audio-visualizer-{}.wav", timestamp));
+
+        if let Err(error) = write_wav(&session_path, self.session_sample_rate, &self.session) {
+            eprintln!("Failed to write session {:?}: {}", session_path, error);
+            return None;
+        }
+
+        let export = URIExport::new(
+            visualizer,
+            &[self.export_profile.profile()],
+            URI_EXPORT_WARM_UP,
+            URI_EXPORT_AV_OFFSET,
+            None,
+            TimecodeMode::None,
+            false,
+            None,
+            &session_path,
+            ExportTarget::File(save_path),
+        );
+
+        Some(Box::new(SessionExport {
+            export,
+            session_path,
+        }))
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        Grid::new("System Export Settings Table")
+            .num_columns(2)
+            .striped(true)
+            .min_col_width(72.0)
+            .show(ui, |ui| {
+                self.export_profile.ui("System", ui);
+            });
+    }
+}
+
+/// Writes mono 32-bit float samples as a WAV file, so the session buffer can
+/// be decoded by the `uridecodebin` of the [`URIExport`]
+fn write_wav(path: &Path, sample_rate: f64, samples: &[f32]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let sample_rate = sample_rate.round() as u32;
+    let data_size = (samples.len() * 4) as u32;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_size).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    // IEEE float format with a single channel
+    writer.write_all(&3u16.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * 4).to_le_bytes())?;
+    writer.write_all(&4u16.to_le_bytes())?;
+    writer.write_all(&32u16.to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+
+    writer.flush()
+}
+
+/// An [`ExportProcess`] of a session buffer. Removes the temporary file of the
+/// session once it is dropped.
+struct SessionExport {
+    export: URIExport,
+    session_path: PathBuf,
+}
+
+impl ExportProcess for SessionExport {
+    fn progress(&self) -> Option<f64> {
+        self.export.progress()
+    }
+
+    fn name(&self) -> &str {
+        self.export.name()
+    }
+
+    fn finished(&self) -> bool {
+        self.export.finished()
+    }
+
+    fn update(&mut self) {
+        self.export.update()
+    }
+}
+
+impl Drop for SessionExport {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.session_path);
+    }
+}
+
+/// Captures the samples of a source element, e.g. of an audio device, and
+/// amplifies them to full scale
+pub(super) struct StaticSystemSampleSource {
     pipeline: Pipeline,
+    bus: Bus,
     sample_source: GStreamerSampleSource,
     amplification: f32,
+    device_lost: bool,
 }
 
 impl StaticSystemSampleSource {
-    pub fn new(src: &Element, max_sample_rate: u64) -> Self {
+    pub(super) fn new(src: &Element, max_sample_rate: u64) -> Self {
         let pipeline = Pipeline::new(None);
 
         let audio_resample = ElementFactory::make("audioresample").build().unwrap();
@@ -155,16 +397,34 @@ impl StaticSystemSampleSource {
 
         pipeline.set_state(State::Playing).unwrap();
 
+        let bus = pipeline.bus().unwrap();
+
         Self {
             pipeline,
+            bus,
             sample_source,
             amplification: 256.0,
+            device_lost: false,
+        }
+    }
+
+    /// Checks the bus for errors, which are posted once the device is lost,
+    /// e.g. because it was unplugged
+    fn update_device_lost(&mut self) {
+        while let Some(message) = self.bus.pop_filtered(&[MessageType::Error]) {
+            if let MessageView::Error(error) = message.view() {
+                eprintln!("Lost audio device: {}", error.error());
+            }
+
+            self.device_lost = true;
         }
     }
 }
 
 impl OnlineSampleSource for StaticSystemSampleSource {
     fn samples(&mut self) -> Samples {
+        self.update_device_lost();
+
         let samples = self.sample_source.samples();
 
         self.amplification *= f64::powf(
@@ -196,6 +456,13 @@ impl OnlineSampleSource for StaticSystemSampleSource {
     fn focus(&mut self) {}
 
     fn ui(&mut self, _ui: &mut Ui) {}
+
+    fn status(&self) -> Option<SampleSourceStatus> {
+        Some(SampleSourceStatus {
+            device_lost: self.device_lost,
+            ..self.sample_source.status()
+        })
+    }
 }
 
 impl Drop for StaticSystemSampleSource {
@@ -1,65 +1,320 @@
 use std::{
+    collections::hash_map::DefaultHasher,
+    fs::File,
+    hash::{Hash, Hasher},
+    io::{self, BufReader, Read},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
 };
 
-use egui::{Button, ComboBox, Grid, ProgressBar, Ui};
+use egui::{Button, ComboBox, DragValue, Grid, ProgressBar, Slider, Ui};
 use gstreamer::{
-    prelude::{ElementExtManual, ObjectExt},
-    traits::{ElementExt, GstBinExt, PadExt},
-    Bus, Caps, ClockTime, ElementFactory, Fraction, MessageType, MessageView, Pipeline, SeekFlags,
-    State,
+    prelude::{Cast, ElementExtManual, ObjectExt, PadExtManual},
+    traits::{ElementExt, GstBinExt, GstObjectExt, PadExt},
+    Bus, Caps, ClockTime, Element, ElementFactory, ElementFactoryType, Fraction, MessageType,
+    MessageView, PadDirection, PadProbeData, PadProbeReturn, PadProbeType, Pipeline, Rank,
+    SeekFlags, SeekType, State,
 };
+use gstreamer_app::AppSink;
+use gstreamer_audio::{AudioCapsBuilder, AUDIO_FORMAT_F32};
 use gstreamer_pbutils::{
-    encoding_profile::EncodingProfileBuilder, EncodingAudioProfile, EncodingContainerProfile,
-    EncodingVideoProfile,
+    encoding_profile::EncodingProfileBuilder, prelude::DiscovererStreamInfoExt, Discoverer,
+    EncodingAudioProfile, EncodingContainerProfile, EncodingVideoProfile,
 };
 use gstreamer_video::VideoCapsBuilder;
 use rfd::FileDialog;
 use sphere_audio_visualizer::{
-    audio_analysis::Samples,
+    audio_analysis::{BeatGrid, Samples, BEAT_GRID_BEATS_PER_BAR, BEAT_GRID_BPM},
     rendering::wgpu::OutputFormat,
-    OfflineVisualizer, {ExportProcess, Exporter, OnlineSampleSource},
+    OfflineVisualizer, FRAME_HASH_EXTENSION,
+    {ExportEstimate, ExportProcess, Exporter, OnlineSampleSource, SampleSourceStatus},
 };
 
 use crate::Settings;
 
-use super::{visualizer::VisualizerElement, EncodingSettings, GStreamerSampleSource, Resulution};
+use super::{
+    visualizer::VisualizerElement, EncodingSettings, ExportProfile, ExportProfileSelector,
+    ExportProfileStore, GStreamerSampleSource, MAX_BUFFER_DURATION,
+};
 
 const PLAY: &'static str = "▶";
 const PAUSE: &'static str = "⏸";
 const SKIP_FORWARD: &'static str = "⏩";
 const SKIP_BACKWARD: &'static str = "⏪";
 
+/// Defines the default amount of seconds of audio the visualizer is pre-rolled
+/// with before the first exported frame
+pub const URI_EXPORT_WARM_UP: f64 = 2.0;
+
+/// Defines the default offset in milliseconds by which the video is delayed
+/// relative to the audio
+pub const URI_EXPORT_AV_OFFSET: i64 = 0;
+
+/// Defines how many seconds of the track are exported by a dry run
+pub const URI_DRY_RUN_DURATION: f64 = 5.0;
+
+/// Defines how long the audio stream of a file is probed before it is copied
+/// into an export
+pub const AUDIO_PASSTHROUGH_TIMEOUT: u64 = 5;
+
+/// Defines the default opacity of the watermark
+pub const WATERMARK_OPACITY: f64 = 0.8;
+
+/// Defines the distance in pixels between the watermark and the edges of the
+/// video
+pub const WATERMARK_MARGIN: i32 = 32;
+
+/// Defines the default position of the crossfader of the dual deck mode, where
+/// 0.0 only plays the first deck and 1.0 only the second one
+pub const URI_CROSSFADER: f64 = 0.5;
+
+/// Specifies the corner of the video the watermark is placed in
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum WatermarkPosition {
+    /// The top left corner
+    TopLeft,
+    /// The top right corner
+    TopRight,
+    /// The bottom left corner
+    BottomLeft,
+    /// The bottom right corner
+    BottomRight,
+}
+
+impl WatermarkPosition {
+    fn display_name(&self) -> &'static str {
+        match self {
+            WatermarkPosition::TopLeft => "Top Left",
+            WatermarkPosition::TopRight => "Top Right",
+            WatermarkPosition::BottomLeft => "Bottom Left",
+            WatermarkPosition::BottomRight => "Bottom Right",
+        }
+    }
+
+    /// Returns the offset of the watermark in pixels. Negative offsets are
+    /// measured from the right or bottom edge of the video.
+    fn offset(&self, margin: i32) -> (i32, i32) {
+        match self {
+            WatermarkPosition::TopLeft => (margin, margin),
+            WatermarkPosition::TopRight => (-margin, margin),
+            WatermarkPosition::BottomLeft => (margin, -margin),
+            WatermarkPosition::BottomRight => (-margin, -margin),
+        }
+    }
+}
+
+/// Specifies which time is burned into the exported frames
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TimecodeMode {
+    /// No timecode is burned in
+    None,
+    /// The time since the start of the track
+    Elapsed,
+    /// The time until the end of the track
+    Remaining,
+}
+
+impl TimecodeMode {
+    fn display_name(&self) -> &'static str {
+        match self {
+            TimecodeMode::None => "None",
+            TimecodeMode::Elapsed => "Elapsed",
+            TimecodeMode::Remaining => "Remaining",
+        }
+    }
+}
+
+/// Formats the time as `hours:minutes:seconds:frames`
+fn format_timecode(time: ClockTime, frame_rate: u64) -> String {
+    let frame_rate = frame_rate.max(1);
+    let frames = (time.nseconds() * frame_rate + 500_000_000) / 1_000_000_000;
+    let seconds = frames / frame_rate;
+
+    format!(
+        "{:02}:{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        frames % frame_rate
+    )
+}
+
+/// Converts a [`ClockTime`] into seconds
+fn clock_time_to_seconds(time: ClockTime) -> f64 {
+    time.nseconds() as f64 / 1_000_000_000.0
+}
+
+/// Converts seconds into a [`ClockTime`]. Negative times are clamped to zero.
+fn seconds_to_clock_time(seconds: f64) -> ClockTime {
+    ClockTime::from_nseconds((seconds.max(0.0) * 1_000_000_000.0) as u64)
+}
+
+/// Stores the details of the first audio stream of a file as it is decoded
+#[derive(Clone)]
+pub struct MediaInfo {
+    /// Represents the description of the codec of the stream
+    pub codec: String,
+    /// Represents the sample rate of the stream in hz
+    pub sample_rate: u32,
+    /// Represents the number of channels of the stream
+    pub channels: u32,
+    /// Represents the bits per sample of the stream. Zero if the codec does
+    /// not define it.
+    pub depth: u32,
+    /// Represents the duration of the file, if it is known
+    pub duration: Option<ClockTime>,
+}
+
+impl MediaInfo {
+    /// Queries the details of the first audio stream of the file. Returns
+    /// none, if the file can not be discovered or has no audio stream.
+    pub fn discover(path: &Path) -> Option<Self> {
+        let discoverer =
+            Discoverer::new(ClockTime::from_seconds(AUDIO_PASSTHROUGH_TIMEOUT)).ok()?;
+
+        let info = discoverer
+            .discover_uri(&format!("file://{}", path.display()))
+            .ok()?;
+
+        let audio_streams = info.audio_streams();
+        let stream = audio_streams.first()?;
+
+        let codec = stream
+            .caps()
+            .and_then(|caps| gstreamer_pbutils::pb_utils_get_codec_description(&caps).ok())
+            .map(|codec| codec.to_string())
+            .unwrap_or_else(|| String::from("Unknown"));
+
+        Some(Self {
+            codec,
+            sample_rate: stream.sample_rate(),
+            channels: stream.channels(),
+            depth: stream.depth(),
+            duration: info.duration(),
+        })
+    }
+
+    /// Draws the details into a grid with the given id
+    fn ui(&self, id: &str, analyzed_sample_rate: Option<f64>, ui: &mut Ui) {
+        Grid::new(id).num_columns(2).show(ui, |ui| {
+            ui.label("Codec:");
+            ui.label(&self.codec);
+            ui.end_row();
+
+            ui.label("Sample Rate:");
+            ui.label(format!("{} hz", self.sample_rate));
+            ui.end_row();
+
+            ui.label("Channels:");
+            ui.label(self.channels.to_string());
+            ui.end_row();
+
+            ui.label("Bit Depth:");
+            ui.label(match self.depth {
+                0 => String::from("Unknown"),
+                depth => depth.to_string(),
+            });
+            ui.end_row();
+
+            ui.label("Duration:");
+            ui.label(match self.duration {
+                Some(duration) => {
+                    let seconds = duration.seconds();
+                    format!("{}:{:02}", seconds / 60, seconds % 60)
+                }
+                None => String::from("Unknown"),
+            });
+            ui.end_row();
+
+            // the analysis always receives a resampled mono downmix
+            if let Some(sample_rate) = analyzed_sample_rate {
+                ui.label("Analyzed As:");
+                ui.label(format!("{:.0} hz, mono", sample_rate));
+                ui.end_row();
+            }
+        });
+    }
+}
+
+/// Stores the settings of an image, which is composited over the exported
+/// video
+#[derive(Clone)]
+pub struct Watermark {
+    /// Represents the path of the image
+    pub path: PathBuf,
+    /// Represents the corner the image is placed in
+    pub position: WatermarkPosition,
+    /// Represents the opacity of the image
+    pub opacity: f64,
+}
+
 /// A [`OnlineSampleSource`] and [`Exporter`] based on a GStreamer
-/// `uridecodebin`
+/// `uridecodebin`. In the dual deck mode two files are played at once and the
+/// visualizer reacts to the mix of the crossfader, e.g. to rehearse
+/// transitions. Exports always use the file of the first deck.
 pub struct URISampleSource {
     settings: Arc<Settings>,
-    file_path: Option<PathBuf>,
+    decks: [Deck; 2],
+    dual_deck: bool,
+    crossfader: f64,
+    mixer: DeckMixer,
     sample_rate_id: usize,
-    frame_rate_id: usize,
-    resulution_id: usize,
-    encoding_id: usize,
-    inner: Option<StaticURISampleSource>,
+    export_profile: ExportProfileSelector,
+    additional_outputs: Vec<ExportProfileSelector>,
+    export_profile_store: Option<Arc<ExportProfileStore>>,
+    warm_up: f64,
+    av_offset: i64,
+    watermark_path: Option<PathBuf>,
+    watermark_position: WatermarkPosition,
+    watermark_opacity: f64,
+    timecode_mode: TimecodeMode,
+    audio_passthrough: bool,
+    bpm: f64,
+    downbeat: f64,
+    beats_per_bar: u32,
+    beat_grid_snapping: bool,
+    analysis_cache_directory: Option<PathBuf>,
+    analysis_cache: bool,
+    hash_frames: bool,
+    estimate: Arc<Mutex<Option<ExportEstimate>>>,
 }
 
 impl URISampleSource {
     /// Creates a new instance.
     pub fn new(settings: Arc<Settings>) -> Self {
         let sample_rate_id = settings.default_sample_rate;
-        let frame_rate_id = settings.default_frame_rate;
-        let resulution_id = settings.default_resulution;
-        let encoding_id = settings.default_encoding;
+        let export_profile = ExportProfileSelector::new(settings.clone());
 
         let mut this = Self {
             settings,
-            file_path: None,
+            decks: Default::default(),
+            dual_deck: false,
+            crossfader: URI_CROSSFADER,
+            mixer: DeckMixer::default(),
             sample_rate_id,
-            frame_rate_id,
-            resulution_id,
-            encoding_id,
-            inner: None,
+            export_profile,
+            additional_outputs: Vec::new(),
+            export_profile_store: None,
+            warm_up: URI_EXPORT_WARM_UP,
+            av_offset: URI_EXPORT_AV_OFFSET,
+            watermark_path: None,
+            watermark_position: WatermarkPosition::BottomRight,
+            watermark_opacity: WATERMARK_OPACITY,
+            timecode_mode: TimecodeMode::None,
+            audio_passthrough: false,
+            bpm: BEAT_GRID_BPM,
+            downbeat: 0.0,
+            beats_per_bar: BEAT_GRID_BEATS_PER_BAR,
+            beat_grid_snapping: false,
+            analysis_cache_directory: None,
+            analysis_cache: true,
+            hash_frames: false,
+            estimate: Arc::new(Mutex::new(None)),
         };
 
         this.update();
@@ -67,37 +322,150 @@ impl URISampleSource {
         this
     }
 
+    /// Builder method for setting the directory the analyses of exported
+    /// files are cached in. Caching is only offered in the UI if a directory
+    /// is set.
+    pub fn with_analysis_cache_directory(
+        mut self,
+        analysis_cache_directory: impl Into<PathBuf>,
+    ) -> Self {
+        self.analysis_cache_directory = Some(analysis_cache_directory.into());
+        self
+    }
+
+    /// Builder method for setting the store the export profiles are saved
+    /// to. Saving profiles is only offered in the UI if a store is set.
+    pub fn with_export_profile_store(mut self, store: Arc<ExportProfileStore>) -> Self {
+        self.export_profile.set_store(store.clone());
+        self.export_profile_store = Some(store);
+        self
+    }
+
     fn update(&mut self) {
-        self.inner = self.recreate_inner();
+        for index in 0..self.decks.len() {
+            self.update_deck(index);
+        }
     }
 
-    fn recreate_inner(&self) -> Option<StaticURISampleSource> {
-        Some(StaticURISampleSource::new(
-            self.settings.sample_rates[self.sample_rate_id],
-            self.file_path.as_ref()?,
-        ))
+    fn update_deck(&mut self, index: usize) {
+        let sample_rate = self.sample_rate();
+
+        if index < self.deck_count() {
+            self.decks[index].update(sample_rate, self.dual_deck);
+        } else {
+            self.decks[index].inner = None;
+        }
+
+        self.mixer.pending[index].clear();
+        self.apply_crossfader();
+    }
+
+    fn deck_count(&self) -> usize {
+        if self.dual_deck {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Returns the gains of the decks for the crossfader. The equal power
+    /// curve keeps the loudness constant while fading from one deck to the
+    /// other.
+    fn gains(&self) -> [f64; 2] {
+        if !self.dual_deck {
+            return [1.0, 0.0];
+        }
+
+        let angle = self.crossfader * std::f64::consts::FRAC_PI_2;
+
+        [angle.cos(), angle.sin()]
+    }
+
+    fn apply_crossfader(&self) {
+        for (deck, gain) in self.decks.iter().zip(self.gains()) {
+            if let Some(inner) = &deck.inner {
+                inner.set_volume(gain);
+            }
+        }
     }
 
     fn sample_rate(&self) -> u64 {
         self.settings.sample_rates[self.sample_rate_id]
     }
 
-    fn frame_rate(&self) -> u64 {
-        self.settings.frame_rates[self.frame_rate_id]
+    fn encoding(&self) -> &EncodingSettings {
+        self.export_profile.encoding()
     }
 
-    fn resulution(&self) -> &Resulution {
-        &self.settings.resulutions[self.resulution_id]
+    fn watermark(&self) -> Option<Watermark> {
+        Some(Watermark {
+            path: self.watermark_path.clone()?,
+            position: self.watermark_position,
+            opacity: self.watermark_opacity,
+        })
     }
 
-    fn encoding(&self) -> &EncodingSettings {
-        &self.settings.encodings[self.encoding_id]
+    /// Creates an export of the opened file with the current settings. The
+    /// analysis is only cached by exports writing a file, as dry runs stop
+    /// before the end of the track. The additional outputs are encoded from
+    /// the same frames, so a dry run estimates all of them together. The
+    /// frame hashes are written next to the first output.
+    fn uri_export(
+        &self,
+        mut visualizer: Box<dyn OfflineVisualizer>,
+        target: ExportTarget,
+    ) -> Option<URIExport> {
+        let open_path = self.decks[0].file_path.as_ref()?;
+        let watermark = self.watermark();
+
+        let analysis_cache_directory = match &target {
+            ExportTarget::File(_) => self
+                .analysis_cache_directory
+                .as_deref()
+                .filter(|_| self.analysis_cache),
+            ExportTarget::DryRun { .. } => None,
+        };
+
+        if let (true, ExportTarget::File(save_path)) = (self.hash_frames, &target) {
+            visualizer.hash_frames(&save_path.with_extension(FRAME_HASH_EXTENSION));
+        }
+
+        let outputs = std::iter::once(&self.export_profile)
+            .chain(&self.additional_outputs)
+            .map(ExportProfileSelector::profile)
+            .collect::<Vec<_>>();
+
+        Some(URIExport::new(
+            visualizer,
+            &outputs,
+            self.warm_up,
+            self.av_offset,
+            watermark.as_ref(),
+            self.timecode_mode,
+            self.audio_passthrough,
+            analysis_cache_directory,
+            open_path,
+            target,
+        ))
+    }
+
+    fn snapping_beat_grid(&self) -> Option<BeatGrid> {
+        self.beat_grid_snapping
+            .then(|| BeatGrid::new(self.bpm, self.downbeat, self.beats_per_bar))
     }
 }
 
 impl OnlineSampleSource for URISampleSource {
     fn samples(&mut self) -> Samples {
-        if let Some(inner) = &mut self.inner {
+        if self.dual_deck {
+            let gains = self.gains();
+            let sample_rate = self.sample_rate();
+
+            Samples {
+                sample_rate: sample_rate as f64,
+                samples: self.mixer.mix(&mut self.decks, gains, sample_rate),
+            }
+        } else if let Some(inner) = &mut self.decks[0].inner {
             inner.samples()
         } else {
             Samples {
@@ -108,34 +476,47 @@ impl OnlineSampleSource for URISampleSource {
     }
 
     fn unfocus(&mut self) {
-        if let Some(inner) = &mut self.inner {
-            inner.unfocus()
+        for deck in &mut self.decks {
+            if let Some(inner) = &mut deck.inner {
+                inner.unfocus()
+            }
         }
     }
 
     fn focus(&mut self) {
-        if let Some(inner) = &mut self.inner {
-            inner.focus()
+        for deck in &mut self.decks {
+            if let Some(inner) = &mut deck.inner {
+                inner.focus()
+            }
         }
     }
 
-    fn ui(&mut self, ui: &mut Ui) {
-        let mut changed = false;
-
-        if ui.add_sized([256.0, 20.0], Button::new("Open")).clicked() {
-            if let Some(file_path) = FileDialog::new().pick_file() {
-                self.file_path = Some(file_path);
-                changed = true;
-            }
+    fn status(&self) -> Option<SampleSourceStatus> {
+        if !self.dual_deck {
+            return self.decks[0].inner.as_ref()?.status();
         }
 
-        if let Some(inner) = &mut self.inner {
-            if inner.eof() {
-                changed = true;
-            }
-        }
+        let status = self
+            .decks
+            .iter()
+            .filter_map(|deck| deck.inner.as_ref()?.status())
+            .reduce(|a, b| SampleSourceStatus {
+                underruns: a.underruns + b.underruns,
+                overruns: a.overruns + b.overruns,
+                sample_rate: a.sample_rate.or(b.sample_rate),
+                device_lost: a.device_lost || b.device_lost,
+            })?;
+
+        Some(SampleSourceStatus {
+            overruns: status.overruns + self.mixer.overruns,
+            ..status
+        })
+    }
 
+    fn ui(&mut self, ui: &mut Ui) {
         let old_sample_rate = self.sample_rate();
+        let old_dual_deck = self.dual_deck;
+        let old_crossfader = self.crossfader;
 
         Grid::new("Audio Sample Rate Grid")
             .num_columns(2)
@@ -155,8 +536,117 @@ impl OnlineSampleSource for URISampleSource {
                             );
                         }
                     });
+                ui.end_row();
+
+                ui.label("Dual Deck:");
+                ui.checkbox(&mut self.dual_deck, "");
+                ui.end_row();
+
+                if self.dual_deck {
+                    ui.label("Crossfader:");
+                    ui.add_sized(
+                        [176.0, 20.0],
+                        Slider::new(&mut self.crossfader, 0.0..=1.0).show_value(false),
+                    );
+                    ui.end_row();
+                }
+                ui.label("BPM:");
+                ui.add_sized(
+                    [176.0, 20.0],
+                    DragValue::new(&mut self.bpm)
+                        .speed(0.1)
+                        .clamp_range(20.0..=300.0),
+                );
+                ui.end_row();
+
+                ui.label("Downbeat:");
+                ui.add_sized(
+                    [176.0, 20.0],
+                    DragValue::new(&mut self.downbeat)
+                        .speed(0.01)
+                        .clamp_range(0.0..=f64::MAX)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+
+                ui.label("Beats/Bar:");
+                ui.add_sized(
+                    [176.0, 20.0],
+                    DragValue::new(&mut self.beats_per_bar)
+                        .speed(0.1)
+                        .clamp_range(1..=16),
+                );
+                ui.end_row();
+
+                ui.label("Snap to Bars:");
+                ui.checkbox(&mut self.beat_grid_snapping, "");
+                ui.end_row();
             });
 
+        let beat_grid = self.snapping_beat_grid();
+
+        for index in 0..self.deck_count() {
+            if self.dual_deck {
+                ui.label(DECK_NAMES[index]);
+            }
+
+            if self.decks[index].ui(DECK_NAMES[index], beat_grid, ui) {
+                self.update_deck(index);
+            }
+        }
+
+        if old_sample_rate != self.sample_rate() || old_dual_deck != self.dual_deck {
+            self.update()
+        } else if old_crossfader != self.crossfader {
+            self.apply_crossfader()
+        }
+    }
+}
+
+/// The names of the decks shown in the dual deck mode
+const DECK_NAMES: [&str; 2] = ["Deck A", "Deck B"];
+
+/// A file with its own transport in the [`URISampleSource`]
+#[derive(Default)]
+struct Deck {
+    file_path: Option<PathBuf>,
+    media_info: Option<MediaInfo>,
+    inner: Option<StaticURISampleSource>,
+}
+
+impl Deck {
+    /// Recreates the playback of the file. Mixed decks deliver their samples
+    /// at exactly the sample rate, as the samples are summed.
+    fn update(&mut self, sample_rate: u64, mixed: bool) {
+        self.inner = self.file_path.as_ref().map(|file_path| {
+            if mixed {
+                StaticURISampleSource::with_sample_rate(sample_rate, file_path)
+            } else {
+                StaticURISampleSource::new(sample_rate, file_path)
+            }
+        });
+    }
+
+    /// Draws the open button, the transport and the stream details of the
+    /// file. Returns true if the playback has to be recreated, because another
+    /// file was opened or the end of the file was reached.
+    fn ui(&mut self, name: &str, beat_grid: Option<BeatGrid>, ui: &mut Ui) -> bool {
+        let mut changed = false;
+
+        if ui.add_sized([256.0, 20.0], Button::new("Open")).clicked() {
+            if let Some(file_path) = FileDialog::new().pick_file() {
+                self.media_info = MediaInfo::discover(&file_path);
+                self.file_path = Some(file_path);
+                changed = true;
+            }
+        }
+
+        if let Some(inner) = &mut self.inner {
+            if inner.eof() {
+                changed = true;
+            }
+        }
+
         let position = self
             .inner
             .as_ref()
@@ -187,7 +677,19 @@ impl OnlineSampleSource for URISampleSource {
                 {
                     if let Some(inner) = &self.inner {
                         if let Some(position) = inner.position() {
-                            inner.seek(position.saturating_sub(ClockTime::from_seconds(5)))
+                            match beat_grid {
+                                // within the first beat of a bar the bar before
+                                // is used, so skipping while playing does not
+                                // get stuck at the start of the current bar
+                                Some(beat_grid) => {
+                                    inner.seek(seconds_to_clock_time(beat_grid.previous_bar(
+                                        clock_time_to_seconds(position) - beat_grid.beat_length(),
+                                    )))
+                                }
+                                None => {
+                                    inner.seek(position.saturating_sub(ClockTime::from_seconds(5)))
+                                }
+                            }
                         }
                     }
                 }
@@ -212,95 +714,302 @@ impl OnlineSampleSource for URISampleSource {
                 {
                     if let Some(inner) = &self.inner {
                         if let Some(position) = inner.position() {
-                            inner.seek(position.saturating_add(ClockTime::from_seconds(5)))
+                            match beat_grid {
+                                Some(beat_grid) => inner.seek(seconds_to_clock_time(
+                                    beat_grid.next_bar(clock_time_to_seconds(position)),
+                                )),
+                                None => {
+                                    inner.seek(position.saturating_add(ClockTime::from_seconds(5)))
+                                }
+                            }
                         }
                     }
                 }
             });
         });
 
-        if changed || old_sample_rate != self.sample_rate() {
-            self.update()
+        if self.file_path.is_some() {
+            match &self.media_info {
+                Some(media_info) => {
+                    let sample_rate = self
+                        .inner
+                        .as_ref()
+                        .and_then(StaticURISampleSource::status)
+                        .and_then(|status| status.sample_rate);
+
+                    media_info.ui(&format!("{} Media Info", name), sample_rate, ui);
+                }
+                None => {
+                    ui.label("The stream details of the file are unavailable");
+                }
+            }
         }
+
+        changed
+    }
+}
+
+/// Sums the samples of the decks of the [`URISampleSource`] weighted by the
+/// crossfader. The decks deliver their samples in batches of different sizes,
+/// so only the samples delivered by every playing deck are summed. The rest is
+/// kept for the next call.
+#[derive(Default)]
+struct DeckMixer {
+    pending: [Vec<f32>; 2],
+    mixed: Vec<f32>,
+    overruns: u64,
+}
+
+impl DeckMixer {
+    fn mix(&mut self, decks: &mut [Deck; 2], gains: [f64; 2], sample_rate: u64) -> &[f32] {
+        let max_len = (sample_rate as f64 * MAX_BUFFER_DURATION) as usize;
+        let mut playing = [false; 2];
+
+        for index in 0..decks.len() {
+            let pending = &mut self.pending[index];
+
+            let inner = match &mut decks[index].inner {
+                Some(inner) => inner,
+                None => {
+                    pending.clear();
+                    continue;
+                }
+            };
+
+            let gain = gains[index] as f32;
+            pending.extend(inner.samples().samples.iter().map(|sample| sample * gain));
+
+            // paused or finished decks would hold back the other deck
+            if !inner.is_playing() || inner.eof() {
+                pending.clear();
+                continue;
+            }
+
+            playing[index] = true;
+
+            // the oldest samples are dropped if a deck stalls for too long
+            if pending.len() > max_len {
+                let dropped = pending.len() - max_len;
+                pending.drain(..dropped);
+                self.overruns += 1;
+            }
+        }
+
+        let len = (0..decks.len())
+            .filter(|index| playing[*index])
+            .map(|index| self.pending[index].len())
+            .min()
+            .unwrap_or(0);
+
+        self.mixed.clear();
+        self.mixed.resize(len, 0.0);
+
+        for index in (0..decks.len()).filter(|index| playing[*index]) {
+            for (mixed, sample) in self.mixed.iter_mut().zip(self.pending[index].drain(..len)) {
+                *mixed += sample;
+            }
+        }
+
+        &self.mixed
     }
 }
 
 impl Exporter for URISampleSource {
     fn format(&self) -> OutputFormat {
-        OutputFormat::RGBA8
+        self.encoding().video_format.into()
     }
 
     fn can_export(&self) -> bool {
-        self.file_path.is_some()
+        self.decks[0].file_path.is_some()
     }
 
     fn export(&mut self, visualizer: Box<dyn OfflineVisualizer>) -> Option<Box<dyn ExportProcess>> {
-        let open_path = self.file_path.as_ref()?;
         let encoding = self.encoding();
 
         let save_path = FileDialog::new()
             .add_filter(&encoding.extension, &[&encoding.extension])
             .save_file()?;
 
-        let resulution = self.resulution();
-        let frame_rate = self.frame_rate();
+        let export = self.uri_export(visualizer, ExportTarget::File(save_path))?;
 
-        let export = URIExport::new(
-            visualizer, resulution, frame_rate, encoding, open_path, save_path,
-        );
+        Some(Box::new(export))
+    }
+
+    fn dry_run(
+        &mut self,
+        visualizer: Box<dyn OfflineVisualizer>,
+    ) -> Option<Box<dyn ExportProcess>> {
+        let export = self.uri_export(
+            visualizer,
+            ExportTarget::DryRun {
+                duration: URI_DRY_RUN_DURATION,
+                estimate: self.estimate.clone(),
+            },
+        )?;
 
         Some(Box::new(export))
     }
 
+    fn estimate(&self) -> Option<ExportEstimate> {
+        *self.estimate.lock().unwrap()
+    }
+
     fn ui(&mut self, ui: &mut Ui) {
         Grid::new("URI Export Settings Table")
             .num_columns(2)
             .striped(true)
             .min_col_width(72.0)
             .show(ui, |ui| {
-                ui.label("Resulution:");
-                let resulution = self.resulution();
-                ComboBox::from_id_source("URI Video Resulution")
-                    .selected_text(format!("{}x{}", resulution.width, resulution.height))
-                    .width(168.0)
-                    .show_ui(ui, |ui| {
-                        for (id, preset) in self.settings.resulutions.iter().enumerate() {
-                            ui.selectable_value(
-                                &mut self.resulution_id,
-                                id,
-                                format!("{}x{}", preset.width, preset.height),
-                            );
-                        }
-                    });
+                self.export_profile.ui("URI", ui);
+
+                let mut removed = None;
+
+                for (id, output) in self.additional_outputs.iter_mut().enumerate() {
+                    ui.label(format!("Output {}:", id + 2));
+                    if ui.add_sized([176.0, 20.0], Button::new("Remove")).clicked() {
+                        removed = Some(id);
+                    }
+                    ui.end_row();
+
+                    output.ui(&format!("URI Output {}", id + 2), ui);
+                }
+
+                if let Some(id) = removed {
+                    self.additional_outputs.remove(id);
+                }
+
+                ui.label("Outputs:");
+                if ui
+                    .add_sized([176.0, 20.0], Button::new("Add Output"))
+                    .clicked()
+                {
+                    let mut output = ExportProfileSelector::new(self.settings.clone());
+
+                    if let Some(store) = &self.export_profile_store {
+                        output.set_store(store.clone());
+                    }
+
+                    self.additional_outputs.push(output);
+                }
                 ui.end_row();
 
-                ui.label("Frame Rate:");
-                ComboBox::from_id_source("URI Video Frame Rate")
-                    .selected_text(format!("{} hz", self.frame_rate()))
-                    .width(168.0)
-                    .show_ui(ui, |ui| {
-                        for (id, preset) in self.settings.frame_rates.iter().enumerate() {
-                            ui.selectable_value(
-                                &mut self.frame_rate_id,
-                                id,
-                                format!("{} hz", preset),
-                            );
+                ui.label("Warm Up:");
+                ui.add_sized(
+                    [176.0, 20.0],
+                    DragValue::new(&mut self.warm_up)
+                        .speed(0.1)
+                        .clamp_range(0.0..=30.0)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+
+                ui.label("A/V Offset:");
+                ui.add_sized(
+                    [176.0, 20.0],
+                    DragValue::new(&mut self.av_offset)
+                        .speed(1.0)
+                        .clamp_range(-1000..=1000)
+                        .suffix(" ms"),
+                );
+                ui.end_row();
+
+                ui.label("Watermark:");
+                ui.horizontal(|ui| {
+                    let file_name = self
+                        .watermark_path
+                        .as_ref()
+                        .and_then(|path| path.file_name())
+                        .map(|file_name| file_name.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| "None".to_string());
+
+                    if ui
+                        .add_sized([140.0, 20.0], Button::new(file_name))
+                        .clicked()
+                    {
+                        if let Some(path) =
+                            FileDialog::new().add_filter("PNG", &["png"]).pick_file()
+                        {
+                            self.watermark_path = Some(path);
                         }
-                    });
+                    }
+
+                    if ui.add_sized([28.0, 20.0], Button::new("✖")).clicked() {
+                        self.watermark_path = None;
+                    }
+                });
                 ui.end_row();
 
-                ui.label("Encoding:");
-                ComboBox::from_id_source("URI Video Encoding")
-                    .selected_text(&self.encoding().name)
+                ui.add_enabled_ui(self.watermark_path.is_some(), |ui| {
+                    ui.label("Position:");
+                });
+                ui.add_enabled_ui(self.watermark_path.is_some(), |ui| {
+                    ComboBox::from_id_source("URI Watermark Position")
+                        .selected_text(self.watermark_position.display_name())
+                        .width(168.0)
+                        .show_ui(ui, |ui| {
+                            for position in [
+                                WatermarkPosition::TopLeft,
+                                WatermarkPosition::TopRight,
+                                WatermarkPosition::BottomLeft,
+                                WatermarkPosition::BottomRight,
+                            ] {
+                                ui.selectable_value(
+                                    &mut self.watermark_position,
+                                    position,
+                                    position.display_name(),
+                                );
+                            }
+                        });
+                });
+                ui.end_row();
+
+                ui.add_enabled_ui(self.watermark_path.is_some(), |ui| {
+                    ui.label("Opacity:");
+                });
+                ui.add_enabled_ui(self.watermark_path.is_some(), |ui| {
+                    ui.add_sized(
+                        [176.0, 20.0],
+                        DragValue::new(&mut self.watermark_opacity)
+                            .speed(0.01)
+                            .clamp_range(0.0..=1.0),
+                    );
+                });
+                ui.end_row();
+
+                ui.label("Timecode:");
+                ComboBox::from_id_source("URI Timecode Mode")
+                    .selected_text(self.timecode_mode.display_name())
                     .width(168.0)
                     .show_ui(ui, |ui| {
-                        for (id, preset) in self.settings.encodings.iter().enumerate() {
-                            ui.selectable_value(&mut self.encoding_id, id, &preset.name);
+                        for mode in [
+                            TimecodeMode::None,
+                            TimecodeMode::Elapsed,
+                            TimecodeMode::Remaining,
+                        ] {
+                            ui.selectable_value(&mut self.timecode_mode, mode, mode.display_name());
                         }
                     });
                 ui.end_row();
+
+                ui.label("Copy Audio:");
+                ui.checkbox(&mut self.audio_passthrough, "");
+                ui.end_row();
+
+                if self.analysis_cache_directory.is_some() {
+                    ui.label("Cache Analysis:");
+                    ui.checkbox(&mut self.analysis_cache, "");
+                    ui.end_row();
+                }
+
+                ui.label("Hash Frames:");
+                ui.checkbox(&mut self.hash_frames, "");
+                ui.end_row();
             });
     }
+
+    fn beat_grid(&self) -> Option<BeatGrid> {
+        self.snapping_beat_grid()
+    }
 }
 
 /// The inner implementation of the [URISampleSource]
@@ -308,13 +1017,25 @@ pub struct StaticURISampleSource {
     pipeline: Pipeline,
     bus: Bus,
     sample_source: GStreamerSampleSource,
+    volume: Element,
     is_playing: bool,
     eof: bool,
+    device_lost: bool,
 }
 
 impl StaticURISampleSource {
     /// Creates a new instance
     pub fn new(max_sample_rate: u64, path: impl AsRef<Path>) -> Self {
+        Self::from_sample_source(GStreamerSampleSource::new(Some(max_sample_rate)), path)
+    }
+
+    /// Creates a new instance, which delivers the samples at exactly the given
+    /// sample rate, so they can be mixed with the samples of another instance
+    pub fn with_sample_rate(sample_rate: u64, path: impl AsRef<Path>) -> Self {
+        Self::from_sample_source(GStreamerSampleSource::with_sample_rate(sample_rate), path)
+    }
+
+    fn from_sample_source(sample_source: GStreamerSampleSource, path: impl AsRef<Path>) -> Self {
         let pipeline = Pipeline::new(None);
 
         let uri_decode_bin = ElementFactory::make("uridecodebin")
@@ -328,13 +1049,15 @@ impl StaticURISampleSource {
 
         let app_audio_resample = ElementFactory::make("audioresample").build().unwrap();
         let app_audio_convert = ElementFactory::make("audioconvert").build().unwrap();
-        let sample_source = GStreamerSampleSource::new(Some(max_sample_rate));
 
         let audio_resample = ElementFactory::make("audioresample").build().unwrap();
         let audio_convert = ElementFactory::make("audioconvert").build().unwrap();
+        // only the playback is attenuated, the visualizer gets the full level
+        let volume = ElementFactory::make("volume").build().unwrap();
         let autoaudiosink = ElementFactory::make("autoaudiosink").build().unwrap();
 
         let app_sink = sample_source.app_sink.clone();
+        let playback_volume = volume.clone();
 
         pipeline.add(&uri_decode_bin).unwrap();
 
@@ -345,6 +1068,7 @@ impl StaticURISampleSource {
         pipeline.add(&app_sink).unwrap();
         pipeline.add(&audio_resample).unwrap();
         pipeline.add(&audio_convert).unwrap();
+        pipeline.add(&playback_volume).unwrap();
         pipeline.add(&autoaudiosink).unwrap();
 
         uri_decode_bin.connect_pad_added(move |uri_decode_bin, _src_pad| {
@@ -355,6 +1079,7 @@ impl StaticURISampleSource {
             app_sink.sync_state_with_parent().unwrap();
             audio_resample.sync_state_with_parent().unwrap();
             audio_convert.sync_state_with_parent().unwrap();
+            playback_volume.sync_state_with_parent().unwrap();
             autoaudiosink.sync_state_with_parent().unwrap();
 
             uri_decode_bin.link(&tee).unwrap();
@@ -364,7 +1089,8 @@ impl StaticURISampleSource {
             app_audio_convert.link(&app_sink).unwrap();
             tee.link(&audio_resample).unwrap();
             audio_resample.link(&audio_convert).unwrap();
-            audio_convert.link(&autoaudiosink).unwrap();
+            audio_convert.link(&playback_volume).unwrap();
+            playback_volume.link(&autoaudiosink).unwrap();
         });
 
         pipeline.set_state(State::Playing).unwrap();
@@ -375,11 +1101,19 @@ impl StaticURISampleSource {
             pipeline,
             bus,
             sample_source,
+            volume,
             is_playing: true,
             eof: false,
+            device_lost: false,
         }
     }
 
+    /// Sets the volume of the playback, where 1.0 keeps the level of the
+    /// file. The samples of the visualizer are not affected.
+    pub fn set_volume(&self, volume: f64) {
+        self.volume.set_property("volume", volume);
+    }
+
     /// Returns if the source is currently playing
     pub fn is_playing(&self) -> bool {
         self.is_playing
@@ -450,9 +1184,31 @@ impl StaticURISampleSource {
 
 impl OnlineSampleSource for StaticURISampleSource {
     fn samples(&mut self) -> Samples {
+        // errors are posted once the audio output is lost, e.g. because it
+        // was unplugged
+        while let Some(message) = self.bus.pop_filtered(&[MessageType::Error]) {
+            if let MessageView::Error(error) = message.view() {
+                eprintln!("Lost audio device: {}", error.error());
+            }
+
+            self.device_lost = true;
+        }
+
+        // paused or finished tracks deliver no samples on purpose
+        if !self.is_playing || self.eof {
+            self.sample_source.pause();
+        }
+
         self.sample_source.samples().into()
     }
 
+    fn status(&self) -> Option<SampleSourceStatus> {
+        Some(SampleSourceStatus {
+            device_lost: self.device_lost,
+            ..self.sample_source.status()
+        })
+    }
+
     fn unfocus(&mut self) {
         self.pause();
     }
@@ -468,30 +1224,286 @@ impl Drop for StaticURISampleSource {
     }
 }
 
-/// An [`ExportProcess`] for GStreamer `uridecodebin`
+/// Specifies where an [`URIExport`] writes the encoded video to
+pub enum ExportTarget {
+    /// The video is written to the file at the path
+    File(PathBuf),
+    /// Only the first seconds of the video are encoded and discarded. The
+    /// [`ExportEstimate`] extrapolated from them is stored once the export is
+    /// finished.
+    DryRun {
+        /// The amount of seconds of the track which are encoded
+        duration: f64,
+        /// The place the estimate is stored at
+        estimate: Arc<Mutex<Option<ExportEstimate>>>,
+    },
+}
+
+/// Measures the speed and size of a dry run
+struct DryRun {
+    duration: f64,
+    track_duration: f64,
+    frame_rate: u64,
+    started: Instant,
+    encoded_bytes: Arc<AtomicU64>,
+    estimate: Arc<Mutex<Option<ExportEstimate>>>,
+}
+
+impl DryRun {
+    /// Extrapolates the measurements of the encoded seconds to the whole track
+    fn estimate(&self) -> ExportEstimate {
+        let elapsed = self.started.elapsed().as_secs_f64().max(f64::EPSILON);
+        let scale = self.track_duration / self.duration;
+
+        ExportEstimate {
+            frames_per_second: self.duration * self.frame_rate as f64 / elapsed,
+            duration: elapsed * scale,
+            file_size: (self.encoded_bytes.load(Ordering::Relaxed) as f64 * scale) as u64,
+        }
+    }
+}
+
+/// Encodes the rendered frames into one of the outputs of an [`URIExport`]
+#[derive(Clone)]
+struct ExportBranch {
+    queue: Element,
+    video_filters: Vec<Element>,
+    encode_bin: Element,
+    sink: Element,
+    passthrough: bool,
+    position: Arc<AtomicU64>,
+}
+
+impl ExportBranch {
+    /// Creates the elements of an output
+    /// - `convert` Represents if the frames are scaled and resampled to the
+    /// resulution and frame rate of the output
+    /// - `file_audio_caps` Represents the caps of the encoded audio stream of
+    /// the file, which is copied if the container is able to store it
+    /// - `path` Represents the file the output is written to. The output is
+    /// discarded, if it is `None`.
+    /// - `encoded_bytes` Represents the counter the size of a discarded output
+    /// is added to
+    fn new(
+        output: &ExportProfile,
+        convert: bool,
+        file_audio_caps: Option<&Caps>,
+        path: Option<&Path>,
+        encoded_bytes: &Arc<AtomicU64>,
+    ) -> Self {
+        let encoding = &output.encoding;
+
+        let container_caps = Caps::from_str(&encoding.container_caps).unwrap();
+
+        let passthrough_caps = file_audio_caps
+            .filter(|audio_caps| URIExport::can_mux(&container_caps, audio_caps))
+            .cloned();
+
+        // encodebin passes a stream through, if it already matches the profile
+        let audio_caps = passthrough_caps
+            .clone()
+            .unwrap_or_else(|| Caps::from_str(&encoding.audio_caps).unwrap());
+        let video_caps = Caps::from_str(&encoding.video_caps).unwrap();
+
+        let audio_profile = EncodingAudioProfile::builder(&audio_caps)
+            .presence(0)
+            .build();
+
+        let video_profile = EncodingVideoProfile::builder(&video_caps)
+            .presence(0)
+            .build();
+
+        let container_profile = EncodingContainerProfile::builder(&container_caps)
+            .name("container")
+            .add_profile(video_profile)
+            .add_profile(audio_profile)
+            .build();
+
+        let encode_bin = ElementFactory::make("encodebin").build().unwrap();
+
+        encode_bin.set_property("profile", &container_profile);
+
+        let sink = match path {
+            Some(path) => ElementFactory::make("filesink")
+                .property("location", format!("{}", path.display()))
+                .build()
+                .unwrap(),
+            None => ElementFactory::make("fakesink").build().unwrap(),
+        };
+
+        // only a dry run measures the size of the encoded video, the sizes of
+        // all outputs are summed up
+        if path.is_none() {
+            let encoded_bytes = encoded_bytes.clone();
+
+            sink.static_pad("sink")
+                .unwrap()
+                .add_probe(PadProbeType::BUFFER, move |_pad, info| {
+                    if let Some(PadProbeData::Buffer(buffer)) = &info.data {
+                        encoded_bytes.fetch_add(buffer.size() as u64, Ordering::Relaxed);
+                    }
+
+                    PadProbeReturn::Ok
+                })
+                .unwrap();
+        }
+
+        // the tee blocks on a slow output, unless every output has its own
+        // queue
+        let queue = ElementFactory::make("queue").build().unwrap();
+
+        let position = Arc::new(AtomicU64::new(0));
+
+        {
+            let position = position.clone();
+
+            queue
+                .static_pad("src")
+                .unwrap()
+                .add_probe(PadProbeType::BUFFER, move |_pad, info| {
+                    if let Some(PadProbeData::Buffer(buffer)) = &info.data {
+                        if let Some(pts) = buffer.pts() {
+                            position.store(pts.nseconds(), Ordering::Relaxed);
+                        }
+                    }
+
+                    PadProbeReturn::Ok
+                })
+                .unwrap();
+        }
+
+        let video_filters = match convert {
+            true => {
+                let resulution = output.resulution.validated();
+
+                let output_caps = VideoCapsBuilder::new()
+                    .format(encoding.video_format.into())
+                    .width(resulution.width as i32)
+                    .height(resulution.height as i32)
+                    .framerate(Fraction::new(output.frame_rate as i32, 1))
+                    .build();
+
+                vec![
+                    ElementFactory::make("videorate").build().unwrap(),
+                    ElementFactory::make("videoscale").build().unwrap(),
+                    ElementFactory::make("videoconvert").build().unwrap(),
+                    ElementFactory::make("capsfilter")
+                        .property("caps", &output_caps)
+                        .build()
+                        .unwrap(),
+                ]
+            }
+            false => Vec::new(),
+        };
+
+        Self {
+            queue,
+            video_filters,
+            encode_bin,
+            sink,
+            passthrough: passthrough_caps.is_some(),
+            position,
+        }
+    }
+
+    /// Adds the elements to the pipeline and links the video from the queue
+    /// to the sink
+    fn add_to(&self, pipeline: &Pipeline) {
+        pipeline.add(&self.queue).unwrap();
+        pipeline.add(&self.encode_bin).unwrap();
+        pipeline.add(&self.sink).unwrap();
+
+        let mut video_src = self.queue.clone();
+
+        for video_filter in &self.video_filters {
+            pipeline.add(video_filter).unwrap();
+            video_src.link(video_filter).unwrap();
+            video_src = video_filter.clone();
+        }
+
+        video_src
+            .link_pads(Some("src"), &self.encode_bin, Some("video_%u"))
+            .unwrap();
+
+        self.encode_bin.link(&self.sink).unwrap();
+    }
+
+    /// Delays the linked streams of the encoder by the given milliseconds
+    fn set_offsets(&self, audio_offset: i64, video_offset: i64) {
+        for pad in self.encode_bin.sink_pads() {
+            let offset = match pad.name().starts_with("audio") {
+                true => audio_offset,
+                false => video_offset,
+            };
+
+            pad.set_offset(offset * 1_000_000);
+        }
+    }
+}
+
+/// An [`ExportProcess`] for GStreamer `uridecodebin`. The rendered frames are
+/// teed into an encoder per output, so multiple outputs, e.g. a master and a
+/// proxy in a lower resulution, are encoded at once.
 pub struct URIExport {
     pipeline: Pipeline,
     bus: Bus,
     name: String,
     finished: bool,
+    dry_run: Option<DryRun>,
+    positions: Vec<Arc<AtomicU64>>,
 }
 
 impl URIExport {
     /// Creates a new instance
+    /// - `outputs` Represents the resulution, frame rate and encoding of every
+    /// output. The frames are rendered for the first output and converted for
+    /// the others.
+    /// - `warm_up` Represents the amount of seconds of audio the visualizer is
+    /// pre-rolled with, so the first frames don't start from a resting state
+    /// - `av_offset` Represents the offset in milliseconds by which the video
+    /// is delayed relative to the audio. Negative values delay the audio.
+    /// - `watermark` Represents an optional image composited over the video
+    /// - `timecode_mode` Represents which time is burned into the video
+    /// - `audio_passthrough` Represents if the audio stream of the file is
+    /// copied without re-encoding. The audio is re-encoded anyway, if the
+    /// container of the encoding can not store the codec of the file.
+    /// - `analysis_cache_directory` Represents an optional directory the
+    /// analysis of the file is cached in. Exporting the file again replays the
+    /// cached analysis instead of analyzing the audio.
+    /// - `target` Represents where the encoded video of the first output is
+    /// written to. The other outputs are written next to it.
     pub fn new(
-        visualizer: Box<dyn OfflineVisualizer>,
-        resulution: &Resulution,
-        frame_rate: u64,
-        encoding: &EncodingSettings,
+        mut visualizer: Box<dyn OfflineVisualizer>,
+        outputs: &[ExportProfile],
+        warm_up: f64,
+        av_offset: i64,
+        watermark: Option<&Watermark>,
+        timecode_mode: TimecodeMode,
+        audio_passthrough: bool,
+        analysis_cache_directory: Option<&Path>,
         open_path: impl AsRef<Path>,
-        save_path: impl AsRef<Path>,
+        target: ExportTarget,
     ) -> Self {
         let open_path = open_path.as_ref();
-        let save_path = save_path.as_ref();
+
+        let rendered = &outputs[0];
+        let frame_rate = rendered.frame_rate;
+
+        if let Some(analysis_cache_directory) = analysis_cache_directory {
+            match Self::track_key(open_path, frame_rate, warm_up) {
+                Ok(track) => visualizer.cache_analysis(analysis_cache_directory, track),
+                Err(error) => eprintln!("Failed to read {:?}: {}", open_path, error),
+            }
+        }
+
+        Self::warm_up(visualizer.as_mut(), frame_rate, warm_up, open_path);
 
         let pipeline = Pipeline::new(None);
 
+        let resulution = rendered.resulution.validated();
+
         let visualizer_caps = VideoCapsBuilder::new()
+            .format(rendered.encoding.video_format.into())
             .width(resulution.width as i32)
             .height(resulution.height as i32)
             .framerate(Fraction::new(frame_rate as i32, 1))
@@ -509,38 +1521,146 @@ impl URIExport {
 
         let visualizer_element = VisualizerElement::new(visualizer);
 
-        let container_caps = Caps::from_str(&encoding.container_caps).unwrap();
-        let audio_caps = Caps::from_str(&encoding.audio_caps).unwrap();
-        let video_caps = Caps::from_str(&encoding.video_caps).unwrap();
+        let watermark_overlay = watermark.map(|watermark| {
+            let (offset_x, offset_y) = watermark.position.offset(WATERMARK_MARGIN);
 
-        let audio_profile = EncodingAudioProfile::builder(&audio_caps)
-            .presence(0)
-            .build();
+            ElementFactory::make("gdkpixbufoverlay")
+                .property("location", format!("{}", watermark.path.display()))
+                .property("offset-x", offset_x)
+                .property("offset-y", offset_y)
+                .property("alpha", watermark.opacity)
+                .build()
+                .unwrap()
+        });
 
-        let video_profile = EncodingVideoProfile::builder(&video_caps)
-            .presence(0)
-            .build();
+        let timecode_overlay = Self::timecode_overlay(timecode_mode, frame_rate);
 
-        let container_profile = EncodingContainerProfile::builder(&container_caps)
-            .name("container")
-            .add_profile(video_profile)
-            .add_profile(audio_profile)
-            .build();
+        // the caps of the visualizer are enforced in front of the filters
+        let caps_filter = ElementFactory::make("capsfilter")
+            .property("caps", &visualizer_caps)
+            .build()
+            .unwrap();
 
-        let encode_bin = ElementFactory::make("encodebin").build().unwrap();
+        let video_filters = std::iter::once(caps_filter)
+            .chain(watermark_overlay)
+            .chain(timecode_overlay)
+            .collect::<Vec<_>>();
 
-        encode_bin.set_property("profile", &container_profile);
+        let video_tee = ElementFactory::make("tee").build().unwrap();
 
-        let file_sink = ElementFactory::make("filesink")
-            .property("location", format!("{}", save_path.display()))
-            .build()
-            .unwrap();
+        let file_audio_caps = match audio_passthrough {
+            true => Self::file_audio_caps(open_path),
+            false => None,
+        };
+
+        let paths: Vec<Option<PathBuf>> = match &target {
+            ExportTarget::File(save_path) => Self::output_paths(save_path, outputs)
+                .into_iter()
+                .map(Some)
+                .collect(),
+            ExportTarget::DryRun { .. } => vec![None; outputs.len()],
+        };
+
+        let encoded_bytes = Arc::new(AtomicU64::new(0));
+
+        let branches = outputs
+            .iter()
+            .zip(&paths)
+            .enumerate()
+            .map(|(id, (output, path))| {
+                ExportBranch::new(
+                    output,
+                    id > 0,
+                    file_audio_caps.as_ref(),
+                    path.as_deref(),
+                    &encoded_bytes,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (output, branch) in outputs.iter().zip(&branches) {
+            if audio_passthrough && !branch.passthrough {
+                eprintln!(
+                    "The audio of {:?} can not be copied into {}, it is re-encoded instead",
+                    open_path, output.encoding.name
+                );
+            }
+        }
+
+        let name = match &target {
+            ExportTarget::File(save_path) => {
+                let name = format!("{}", save_path.file_name().unwrap().to_str().unwrap());
+
+                match outputs.len() {
+                    1 => name,
+                    len => format!("{} (+{})", name, len - 1),
+                }
+            }
+            ExportTarget::DryRun { .. } => format!(
+                "Dry Run ({})",
+                open_path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+        };
 
         pipeline.add(&uri_decode_bin).unwrap();
-        pipeline.add(&encode_bin).unwrap();
-        pipeline.add(&file_sink).unwrap();
+        pipeline.add(&video_tee).unwrap();
+
+        for branch in &branches {
+            branch.add_to(&pipeline);
+            video_tee.link(&branch.queue).unwrap();
+        }
+
+        // only ever delay a stream, so no timestamps become negative
+        let (audio_offset, video_offset) = match av_offset >= 0 {
+            true => (0, av_offset),
+            false => (-av_offset, 0),
+        };
+
+        // the encoded audio is parsed from the file next to the decoded one
+        if branches.iter().any(|branch| branch.passthrough) {
+            let file_src = ElementFactory::make("filesrc")
+                .property("location", format!("{}", open_path.display()))
+                .build()
+                .unwrap();
+
+            let parse_bin = ElementFactory::make("parsebin").build().unwrap();
+
+            let audio_tee = ElementFactory::make("tee").build().unwrap();
+
+            pipeline.add(&file_src).unwrap();
+            pipeline.add(&parse_bin).unwrap();
+            pipeline.add(&audio_tee).unwrap();
+
+            file_src.link(&parse_bin).unwrap();
+
+            for branch in branches.iter().filter(|branch| branch.passthrough) {
+                audio_tee
+                    .link_pads(Some("src_%u"), &branch.encode_bin, Some("audio_%u"))
+                    .unwrap();
+                branch.set_offsets(audio_offset, video_offset);
+            }
+
+            parse_bin.connect_pad_added(move |_parse_bin, src_pad| {
+                let is_audio = src_pad
+                    .current_caps()
+                    .unwrap_or_else(|| src_pad.query_caps(None))
+                    .structure(0)
+                    .map_or(false, |structure| structure.name().starts_with("audio/"));
+
+                let sink_pad = audio_tee.static_pad("sink").unwrap();
+
+                if !is_audio || sink_pad.is_linked() {
+                    return;
+                }
+
+                src_pad.link(&sink_pad).unwrap();
+            });
+        }
 
-        encode_bin.link(&file_sink).unwrap();
+        let positions = branches
+            .iter()
+            .map(|branch| branch.position.clone())
+            .collect();
 
         {
             let pipeline = pipeline.downgrade();
@@ -560,45 +1680,301 @@ impl URIExport {
                 tee.link(&audio_convert).unwrap();
                 audio_convert.link(&visualizer_element).unwrap();
 
-                tee.link_pads(Some("src_%u"), &encode_bin, Some("audio_%u"))
-                    .unwrap();
+                for branch in &branches {
+                    if !branch.passthrough {
+                        tee.link_pads(Some("src_%u"), &branch.encode_bin, Some("audio_%u"))
+                            .unwrap();
+                    }
 
-                visualizer_element
-                    .link_pads_filtered(
-                        Some("src"),
-                        &encode_bin,
-                        Some("video_%u"),
-                        &visualizer_caps,
-                    )
+                    branch.set_offsets(audio_offset, video_offset);
+                }
+
+                let mut video_src = visualizer_element.clone().upcast::<Element>();
+
+                for video_filter in &video_filters {
+                    pipeline.add(video_filter).unwrap();
+                    video_src.link(video_filter).unwrap();
+                    video_src = video_filter.clone();
+                }
+
+                video_src
+                    .link_pads(Some("src"), &video_tee, Some("sink"))
                     .unwrap();
 
                 tee.sync_state_with_parent().unwrap();
                 audio_convert.sync_state_with_parent().unwrap();
                 visualizer_element.sync_state_with_parent().unwrap();
+
+                for video_filter in &video_filters {
+                    video_filter.sync_state_with_parent().unwrap();
+                }
             });
         }
 
-        pipeline.set_state(State::Playing).unwrap();
-
         let bus = pipeline
             .bus()
             .expect("Pipeline without bus. Shouldn't happen!");
 
+        let dry_run = match target {
+            ExportTarget::File(_) => None,
+            ExportTarget::DryRun { duration, estimate } => {
+                // the end of the dry run is set by a seek, which needs the
+                // pipeline to be prerolled
+                pipeline.set_state(State::Paused).unwrap();
+                bus.timed_pop_filtered(
+                    ClockTime::NONE,
+                    &[MessageType::AsyncDone, MessageType::Error],
+                );
+
+                let track_duration = pipeline
+                    .query_duration::<ClockTime>()
+                    .map_or(duration, clock_time_to_seconds);
+                let duration = duration.min(track_duration).max(f64::EPSILON);
+
+                pipeline
+                    .seek(
+                        1.0,
+                        SeekFlags::FLUSH | SeekFlags::ACCURATE,
+                        SeekType::Set,
+                        ClockTime::ZERO,
+                        SeekType::Set,
+                        seconds_to_clock_time(duration),
+                    )
+                    .unwrap();
+
+                Some(DryRun {
+                    duration,
+                    track_duration,
+                    frame_rate,
+                    started: Instant::now(),
+                    encoded_bytes,
+                    estimate,
+                })
+            }
+        };
+
+        pipeline.set_state(State::Playing).unwrap();
+
         Self {
             pipeline,
             bus,
-            name: format!("{}", save_path.file_name().unwrap().to_str().unwrap()),
+            name,
             finished: false,
+            dry_run,
+            positions,
+        }
+    }
+
+    /// Returns the paths of the outputs. The first output is written to the
+    /// save path, the others next to it with their resulution appended to the
+    /// file name.
+    fn output_paths(save_path: &Path, outputs: &[ExportProfile]) -> Vec<PathBuf> {
+        let stem = save_path.file_stem().unwrap_or_default().to_string_lossy();
+
+        let mut paths = vec![save_path.to_path_buf()];
+
+        for output in outputs.iter().skip(1) {
+            let resulution = output.resulution.validated();
+            let name = format!("{}-{}x{}", stem, resulution.width, resulution.height);
+            let extension = &output.encoding.extension;
+
+            let mut path = save_path.with_file_name(format!("{}.{}", name, extension));
+
+            // outputs of the same resulution are numbered
+            let mut id = 2;
+            while paths.contains(&path) {
+                path = save_path.with_file_name(format!("{}-{}.{}", name, id, extension));
+                id += 1;
+            }
+
+            paths.push(path);
+        }
+
+        paths
+    }
+
+    /// Hashes the contents of the file together with everything that changes
+    /// how its samples are split into frames, so copies of a file share their
+    /// analysis cache
+    fn track_key(open_path: &Path, frame_rate: u64, warm_up: f64) -> io::Result<u64> {
+        let mut reader = BufReader::new(File::open(open_path)?);
+        let mut hasher = DefaultHasher::new();
+        let mut buffer = [0; 65536];
+
+        loop {
+            match reader.read(&mut buffer)? {
+                0 => break,
+                len => buffer[..len].hash(&mut hasher),
+            }
+        }
+
+        frame_rate.hash(&mut hasher);
+        warm_up.to_bits().hash(&mut hasher);
+
+        Ok(hasher.finish())
+    }
+
+    /// Returns the caps of the encoded audio stream of the file
+    fn file_audio_caps(open_path: &Path) -> Option<Caps> {
+        let discoverer =
+            Discoverer::new(ClockTime::from_seconds(AUDIO_PASSTHROUGH_TIMEOUT)).ok()?;
+
+        let info = discoverer
+            .discover_uri(&format!("file://{}", open_path.display()))
+            .ok()?;
+
+        info.audio_streams().first()?.caps()
+    }
+
+    /// Returns true, if a muxer for the container caps is able to store the
+    /// audio caps without re-encoding
+    fn can_mux(container_caps: &Caps, audio_caps: &Caps) -> bool {
+        let muxers = ElementFactory::factories_with_type(ElementFactoryType::MUXER, Rank::Marginal);
+        let muxers = ElementFactory::list_filter(&muxers, container_caps, PadDirection::Src, false);
+        let muxers = ElementFactory::list_filter(&muxers, audio_caps, PadDirection::Sink, false);
+
+        !muxers.is_empty()
+    }
+
+    /// Creates a `textoverlay`, which renders the timecode of every frame
+    fn timecode_overlay(timecode_mode: TimecodeMode, frame_rate: u64) -> Option<Element> {
+        if timecode_mode == TimecodeMode::None {
+            return None;
+        }
+
+        let text_overlay = ElementFactory::make("textoverlay")
+            .property("font-desc", "Monospace 24")
+            .property("shaded-background", true)
+            .property_from_str("halignment", "left")
+            .property_from_str("valignment", "bottom")
+            .build()
+            .unwrap();
+
+        text_overlay
+            .static_pad("video_sink")
+            .unwrap()
+            .add_probe(PadProbeType::BUFFER, move |pad, info| {
+                let position = match &info.data {
+                    Some(PadProbeData::Buffer(buffer)) => buffer.pts(),
+                    _ => None,
+                };
+
+                if let (Some(position), Some(text_overlay)) = (position, pad.parent_element()) {
+                    let time = match timecode_mode {
+                        TimecodeMode::Remaining => pad
+                            .peer_query_duration::<ClockTime>()
+                            .map(|duration| duration.saturating_sub(position))
+                            .unwrap_or(ClockTime::ZERO),
+                        _ => position,
+                    };
+
+                    text_overlay.set_property("text", format_timecode(time, frame_rate));
+                }
+
+                PadProbeReturn::Ok
+            })
+            .unwrap();
+
+        Some(text_overlay)
+    }
+
+    /// Decodes the first `duration` seconds of the file and feeds them frame by
+    /// frame to the visualizer, discarding the frames
+    fn warm_up(
+        visualizer: &mut dyn OfflineVisualizer,
+        frame_rate: u64,
+        duration: f64,
+        open_path: &Path,
+    ) {
+        if duration <= 0.0 {
+            return;
+        }
+
+        let pipeline = Pipeline::new(None);
+
+        let uri_decode_bin = ElementFactory::make("uridecodebin")
+            .property("uri", format!("file://{}", open_path.display()))
+            .property("caps", Caps::builder("audio/x-raw").build())
+            .build()
+            .unwrap();
+
+        let audio_convert = ElementFactory::make("audioconvert").build().unwrap();
+
+        // the samples are pulled as fast as they are decoded
+        let app_sink = AppSink::builder()
+            .caps(
+                &AudioCapsBuilder::new()
+                    .format(AUDIO_FORMAT_F32)
+                    .channels(1i32)
+                    .build(),
+            )
+            .build();
+        app_sink.set_property("sync", false);
+
+        pipeline.add(&uri_decode_bin).unwrap();
+        pipeline.add(&audio_convert).unwrap();
+        pipeline.add(&app_sink).unwrap();
+
+        audio_convert.link(&app_sink).unwrap();
+
+        uri_decode_bin.connect_pad_added(move |_uri_decode_bin, src_pad| {
+            src_pad
+                .link(&audio_convert.static_pad("sink").unwrap())
+                .unwrap();
+        });
+
+        pipeline.set_state(State::Playing).unwrap();
+
+        let mut samples = Vec::new();
+        let mut sample_rate = 44100.0;
+
+        // an error is returned once the end of the file is reached
+        while let Ok(sample) = app_sink.pull_sample() {
+            if let Some(rate) = sample
+                .caps()
+                .and_then(|caps| caps.structure(0)?.get::<i32>("rate").ok())
+            {
+                sample_rate = rate as f64;
+            }
+
+            GStreamerSampleSource::extend_samples(&mut samples, sample);
+
+            if samples.len() as f64 >= duration * sample_rate {
+                break;
+            }
+        }
+
+        pipeline.set_state(State::Null).unwrap();
+
+        samples.truncate((duration * sample_rate) as usize);
+
+        let frame_length = (sample_rate / frame_rate as f64).ceil().max(1.0) as usize;
+
+        for samples in samples.chunks(frame_length) {
+            visualizer.warm_up(Samples {
+                sample_rate,
+                samples,
+            });
         }
     }
 }
 
 impl ExportProcess for URIExport {
     fn progress(&self) -> Option<f64> {
-        Some(
-            self.pipeline.query_position::<ClockTime>()?.nseconds() as f64
-                / self.pipeline.query_duration::<ClockTime>()?.nseconds() as f64,
-        )
+        // the export is only as far as its slowest output
+        let position = self
+            .positions
+            .iter()
+            .map(|position| position.load(Ordering::Relaxed))
+            .min()?;
+        let position = clock_time_to_seconds(ClockTime::from_nseconds(position));
+
+        match &self.dry_run {
+            Some(dry_run) => Some((position / dry_run.duration).min(1.0)),
+            None => {
+                Some(position / clock_time_to_seconds(self.pipeline.query_duration::<ClockTime>()?))
+            }
+        }
     }
 
     fn name(&self) -> &str {
@@ -613,6 +1989,10 @@ impl ExportProcess for URIExport {
         for msg in self.bus.iter() {
             match msg.view() {
                 MessageView::Eos(..) => {
+                    if let Some(dry_run) = &self.dry_run {
+                        *dry_run.estimate.lock().unwrap() = Some(dry_run.estimate());
+                    }
+
                     self.finished = true;
                     break;
                 }
@@ -2,12 +2,14 @@
 
 use gstreamer::{
     glib::{self, object_subclass, types::Pointee, wrapper, ParamSpec, ParamSpecPointer, Value},
-    prelude::ElementExtManual,
+    prelude::{Cast, ElementExtManual},
     subclass::prelude::{
-        ElementImpl, GstObjectImpl, ObjectImpl, ObjectSubclass, ObjectSubclassExt,
+        ElementImpl, GstObjectImpl, ObjectImpl, ObjectImplExt, ObjectSubclass, ObjectSubclassExt,
+        ObjectSubclassIsExt,
     },
-    traits::PadExt,
-    Element, Object, PadDirection, PadPresence, PadTemplate,
+    traits::{ElementExt, PadExt},
+    Element, EventType, Object, PadDirection, PadPresence, PadProbeData, PadProbeReturn,
+    PadProbeType, PadTemplate,
 };
 use gstreamer_audio::{AudioCapsBuilder, AUDIO_FORMAT_F32};
 use gstreamer_pbutils::{subclass::prelude::AudioVisualizerImpl, AudioVisualizer};
@@ -48,6 +50,35 @@ impl ObjectSubclass for VisualizerElementImpl {
 }
 
 impl ObjectImpl for VisualizerElementImpl {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        // the end of the track is signaled to the visualizer, once every
+        // buffer in front of the event was rendered
+        self.obj()
+            .static_pad("sink")
+            .unwrap()
+            .add_probe(PadProbeType::EVENT_DOWNSTREAM, |pad, info| {
+                let is_eos = match &info.data {
+                    Some(PadProbeData::Event(event)) => event.type_() == EventType::Eos,
+                    _ => false,
+                };
+
+                let element = pad
+                    .parent_element()
+                    .and_then(|element| element.downcast::<VisualizerElement>().ok());
+
+                if let (true, Some(element)) = (is_eos, element) {
+                    if let Some(visualizer) = element.imp().0.lock().unwrap().as_mut() {
+                        visualizer.end_of_track();
+                    }
+                }
+
+                PadProbeReturn::Ok
+            })
+            .unwrap();
+    }
+
     fn properties() -> &'static [ParamSpec] {
         lazy_static::lazy_static! {
             static ref PROPERTIES: [ParamSpec; 1] =
@@ -96,7 +127,12 @@ impl ElementImpl for VisualizerElementImpl {
                     PadDirection::Src,
                     PadPresence::Always,
                     &VideoCapsBuilder::new()
-                        .format(VideoFormat::Rgba)
+                        .format_list([
+                            VideoFormat::Rgba,
+                            VideoFormat::Bgra,
+                            VideoFormat::I420,
+                            VideoFormat::Nv12,
+                        ])
                         .build()
                     )
                     .unwrap()
@@ -131,10 +167,16 @@ impl AudioVisualizerImpl for VisualizerElementImpl {
 
             let output = visualizer.visualize(samples, width, height);
 
-            video_frame
-                .plane_data_mut(0)
-                .unwrap()
-                .copy_from_slice(&output.data);
+            // the strides of the video frame might differ from the output
+            for plane in 0..output.planes.len() {
+                let stride = video_frame.plane_stride()[plane] as usize;
+                let plane_data = video_frame.plane_data_mut(plane as u32).unwrap();
+
+                for (dst, src) in plane_data.chunks_mut(stride).zip(output.rows(plane)) {
+                    let len = dst.len().min(src.len());
+                    dst[..len].copy_from_slice(&src[..len]);
+                }
+            }
         }
 
         Ok(())
@@ -7,19 +7,20 @@
 use std::{fs::File, io::BufReader, path::PathBuf, sync::Arc};
 
 use crate::gstreamer_visualizer::{
-    EncodingSettings, Resulution, SystemSampleSource, URISampleSource,
+    EncodingSettings, ExportProfile, ExportProfileStore, Resulution, SystemSampleSource,
+    URISampleSource,
 };
 use serde::{Deserialize, Serialize};
 use sphere_audio_visualizer::{
-    rendering::{
-        wgpu::{Metaballs, Raytracer},
-        {MetaballsSceneConverter, RaytracerSceneConverter},
-    },
-    simulation::{Simulation2D, Simulation3D},
-    Application, WGPUVisualizerFactory,
+    load_visualizer_descriptions, rendering::wgpu::AdapterSelection, Application, FrameServer,
+    SessionSampleSource, TestSignalSampleSource, VisualizerDescription, VisualizerRegistry,
+    FRAME_SERVER_ADDRESS,
 };
 use winit::window::WindowBuilder;
 
+#[cfg(windows)]
+use crate::gstreamer_visualizer::ASIOSampleSource;
+
 pub mod gstreamer_visualizer;
 
 /// Stores the settings of the application
@@ -41,6 +42,15 @@ pub struct Settings {
     pub encodings: Vec<EncodingSettings>,
     /// Represents the index of the default selected encoding. Should be between `0..encodings.len()`
     pub default_encoding: usize,
+    /// Represents the built-in export profiles, which bundle a resulution, a
+    /// frame rate and an encoding
+    #[serde(default)]
+    pub export_profiles: Vec<ExportProfile>,
+    /// Represents the index of the default selected export profile. Should be
+    /// between `0..export_profiles.len()`. The custom profile is selected if
+    /// left out.
+    #[serde(default)]
+    pub default_export_profile: Option<usize>,
 }
 
 fn executable_dir() -> Option<PathBuf> {
@@ -67,20 +77,121 @@ fn load_settings() -> Option<Arc<Settings>> {
         .next()
 }
 
+/// Loads the visualizer configurations from the first `visualizers.yaml`
+/// found in the settings directories. The built-in configurations of the
+/// registry are used if there is no such file or it can't be loaded.
+fn load_visualizer_configurations(registry: &VisualizerRegistry) -> Vec<VisualizerDescription> {
+    let path = match settings_dirs()
+        .into_iter()
+        .map(|dir| dir.join("visualizers.yaml"))
+        .find(|path| path.exists())
+    {
+        Some(path) => path,
+        None => return registry.descriptions(),
+    };
+
+    match load_visualizer_descriptions(&path) {
+        Ok(descriptions) => descriptions,
+        Err(error) => {
+            eprintln!("Failed to load visualizers {:?}: {}", path, error);
+            registry.descriptions()
+        }
+    }
+}
+
+/// Defines the directory live sessions are recorded to
+const SESSION_DIRECTORY: &str = "sessions";
+
+/// Defines the directory the analyses of exported files are cached in
+const ANALYSIS_CACHE_DIRECTORY: &str = "analysis-cache";
+
+/// Defines the file the export profiles saved by the user are stored in
+const EXPORT_PROFILES_PATH: &str = "export-profiles.yaml";
+
+/// Defines the command line flag, which runs the frame server instead of the
+/// application. The flag can be followed by the address to listen on, which
+/// defaults to the loopback interface.
+const FRAME_SERVER_FLAG: &str = "--frame-server";
+
+/// Defines the environment variable selecting the adapter used for rendering,
+/// which is one of `auto`, `hardware` and `software`. Applies to the
+/// application, its exports and the frame server.
+const ADAPTER_VARIABLE: &str = "SPHERE_AUDIO_VISUALIZER_ADAPTER";
+
+fn adapter_selection() -> AdapterSelection {
+    match std::env::var(ADAPTER_VARIABLE) {
+        Ok(adapter) => match adapter.as_str() {
+            "auto" => AdapterSelection::Auto,
+            "hardware" => AdapterSelection::Hardware,
+            "software" => AdapterSelection::Software,
+            _ => {
+                eprintln!("Unknown adapter {}, selecting one automatically", adapter);
+                AdapterSelection::Auto
+            }
+        },
+        Err(_) => AdapterSelection::Auto,
+    }
+}
+
+fn serve_frames(address: &str) {
+    eprintln!("Serving frames on {}", address);
+
+    let registry = VisualizerRegistry::default();
+
+    let result = FrameServer::new()
+        .with_adapter_selection(adapter_selection())
+        .with_visualizer_descriptions(&registry, &load_visualizer_configurations(&registry))
+        .serve(address);
+
+    if let Err(error) = result {
+        eprintln!("Failed to serve frames on {}: {}", address, error);
+    }
+}
+
 fn main() {
+    let mut args = std::env::args().skip(1);
+
+    if args.next().as_deref() == Some(FRAME_SERVER_FLAG) {
+        let address = args.next();
+        serve_frames(address.as_deref().unwrap_or(FRAME_SERVER_ADDRESS));
+        return;
+    }
+
     gstreamer::init().unwrap();
 
     let settings: Arc<Settings> = load_settings().expect("Failed to load settings");
 
-    let system_sample_source = SystemSampleSource::new(settings.clone());
-    let uri_sample_source = URISampleSource::new(settings);
+    let export_profile_store = ExportProfileStore::load(EXPORT_PROFILES_PATH);
+
+    let system_sample_source = SystemSampleSource::new(settings.clone())
+        .with_export_profile_store(export_profile_store.clone());
+    #[cfg(windows)]
+    let asio_sample_source = ASIOSampleSource::new(settings.clone());
+    let uri_sample_source = URISampleSource::new(settings)
+        .with_analysis_cache_directory(ANALYSIS_CACHE_DIRECTORY)
+        .with_export_profile_store(export_profile_store);
+    let session_sample_source = SessionSampleSource::new(SESSION_DIRECTORY);
 
     let window_builder = WindowBuilder::new();
 
-    Application::new(window_builder)
+    let application = Application::new(window_builder)
         .with_sample_source(uri_sample_source, "File")
-        .with_online_only_sample_source(system_sample_source, "System")
-        .with_visualizer_configuration::<WGPUVisualizerFactory<Simulation3D, RaytracerSceneConverter, Raytracer>, _>("Raytracer")
-        .with_visualizer_configuration::<WGPUVisualizerFactory<Simulation2D, MetaballsSceneConverter, Metaballs>, _>("Metaballs")
-        .run();
+        .with_sample_source(system_sample_source, "System");
+
+    // ASIO drivers only exist on Windows
+    #[cfg(windows)]
+    let application = application.with_online_only_sample_source(asio_sample_source, "ASIO");
+
+    let application = application
+        .with_online_only_sample_source(session_sample_source, "Session")
+        .with_online_only_sample_source(TestSignalSampleSource::default(), "Test Signal")
+        .with_session_directory(SESSION_DIRECTORY)
+        .with_adapter_selection(adapter_selection());
+
+    let registry = VisualizerRegistry::default();
+
+    let application = application
+        .with_visualizer_descriptions(&registry, &load_visualizer_configurations(&registry));
+
+    application.run();
 }
@@ -7,14 +7,47 @@
 
 use sphere_audio_visualizer_core::{
     metaballs::{Metaball, Metaballs, MetaballsArgs},
+    metaballs_3d::{Metaball3D, Metaballs3D, Metaballs3DArgs},
     raytracing::{
-        light::{LightGroup, LightScene, PointLight},
-        shape::{Rect, Scene, Sphere},
+        background::{EnvironmentMap, ImageBackground},
+        light::{DirectionalLight, LightGroup, LightScene, PointLight, SpotLight},
+        shape::{
+            BvhNode, Capsule, Disc, Instance, Material, Plane, Rect, Scene, Sphere, SurfaceTexture,
+            Triangle,
+        },
         BasicRaytracingArgsBundle, Raytracer,
     },
 };
-use spirv_std::glam::{vec4, Vec4, Vec4Swizzles};
-use spirv_std::spirv;
+use spirv_std::glam::{vec4, Vec2, Vec3A, Vec4, Vec4Swizzles};
+use spirv_std::{image::Image2d, spirv, Sampler};
+
+/// Samples the environment map of the raytracer from a texture
+struct SampledEnvironmentMap<'a> {
+    image: &'a Image2d,
+    sampler: &'a Sampler,
+}
+
+impl<'a> EnvironmentMap for SampledEnvironmentMap<'a> {
+    fn sample(&self, uv: Vec2, lod: f32) -> Vec3A {
+        let color: Vec4 = self.image.sample_by_lod(*self.sampler, uv, lod);
+
+        Vec3A::from(color.truncate())
+    }
+}
+
+/// Samples the texture or the normal map of the scene from a texture
+struct SampledSurfaceTexture<'a> {
+    image: &'a Image2d,
+    sampler: &'a Sampler,
+}
+
+impl<'a> SurfaceTexture for SampledSurfaceTexture<'a> {
+    fn sample(&self, uv: Vec2) -> Vec3A {
+        let color: Vec4 = self.image.sample_by_lod(*self.sampler, uv, 0.0);
+
+        Vec3A::from(color.truncate())
+    }
+}
 
 /// This function contains the fragment shader implemntation for the metaballs
 /// renderer.
@@ -43,6 +76,33 @@ pub fn metaballs_vs(
     *position = vec4(x, y, 0.0, 1.0);
 }
 
+/// This function contains the fragment shader implemntation for the 3D
+/// metaballs renderer.
+#[spirv(fragment)]
+pub fn metaballs_3d_fs(
+    #[spirv(frag_coord)] position: Vec4,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] args: &Metaballs3DArgs,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] metaballs: &[Metaball3D],
+    output: &mut Vec4,
+) {
+    let metaballs = Metaballs3D::from_args(args.clone(), metaballs);
+
+    *output = metaballs.sample(&position.xy()).extend(1.0);
+}
+
+/// This function contains the vertex shader implemntation for the 3D
+/// metaballs renderer.
+#[spirv(vertex)]
+pub fn metaballs_3d_vs(
+    #[spirv(vertex_index)] vertex_index: u32,
+    #[spirv(position, invariant)] position: &mut Vec4,
+) {
+    let x = (vertex_index & 1) as f32 * 2.0 - 1.0;
+    let y = (vertex_index & 2) as f32 - 1.0;
+
+    *position = vec4(x, y, 0.0, 1.0);
+}
+
 /// This function contains the fragment shader implemntation for the raytracing
 /// renderer.
 #[spirv(fragment)]
@@ -52,15 +112,66 @@ pub fn raytracing_fs(
     #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] spheres: &[Sphere],
     #[spirv(storage_buffer, descriptor_set = 0, binding = 2)] rects: &[Rect],
     #[spirv(storage_buffer, descriptor_set = 0, binding = 3)] point_lights: &[PointLight],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 4)] triangles: &[Triangle],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 5)] sphere_nodes: &[BvhNode],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 6)] triangle_nodes: &[BvhNode],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 7)] planes: &[Plane],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 8)] discs: &[Disc],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 9)] capsules: &[Capsule],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 10)] materials: &[Material],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 11)]
+    directional_lights: &[DirectionalLight],
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 12)] spot_lights: &[SpotLight],
+    #[spirv(descriptor_set = 0, binding = 13)] environment_map: &Image2d,
+    #[spirv(descriptor_set = 0, binding = 14)] environment_sampler: &Sampler,
+    #[spirv(storage_buffer, descriptor_set = 0, binding = 15)] instances: &[Instance],
+    #[spirv(descriptor_set = 0, binding = 16)] surface_texture: &Image2d,
+    #[spirv(descriptor_set = 0, binding = 17)] surface_sampler: &Sampler,
+    #[spirv(descriptor_set = 0, binding = 18)] normal_map: &Image2d,
+    #[spirv(descriptor_set = 0, binding = 19)] normal_sampler: &Sampler,
     output: &mut Vec4,
 ) {
-    let scene = Scene::from_args(args.scene_args.clone(), spheres, rects);
+    let scene = Scene::from_args(
+        args.scene_args.clone(),
+        materials,
+        spheres,
+        sphere_nodes,
+        rects,
+        triangles,
+        triangle_nodes,
+        planes,
+        discs,
+        capsules,
+        instances,
+        SampledSurfaceTexture {
+            image: surface_texture,
+            sampler: surface_sampler,
+        },
+        SampledSurfaceTexture {
+            image: normal_map,
+            sampler: normal_sampler,
+        },
+    );
 
     let light_scene = LightScene {
         point_lights: LightGroup(point_lights),
+        directional_lights: LightGroup(directional_lights),
+        spot_lights: LightGroup(spot_lights),
     };
 
-    let raytracer = Raytracer::from_args(args.raytracer_args.clone(), scene, light_scene);
+    let background = ImageBackground::from_args(
+        args.raytracer_args.background.clone(),
+        SampledEnvironmentMap {
+            image: environment_map,
+            sampler: environment_sampler,
+        },
+    );
+
+    let raytracer = Raytracer::from_args(
+        args.raytracer_args.clone().with_background(background),
+        scene,
+        light_scene,
+    );
 
     *output = raytracer.sample(&position.xy()).extend(1.0);
 }
@@ -0,0 +1,95 @@
+/// Defines the default tempo of a [`BeatGrid`] in beats per minute
+pub const BEAT_GRID_BPM: f64 = 120.0;
+
+/// Defines the default number of beats in a bar of a [`BeatGrid`]
+pub const BEAT_GRID_BEATS_PER_BAR: u32 = 4;
+
+/// Describes the bars of a track with a constant tempo. The grid is anchored at
+/// a downbeat, so times can be snapped to the start of bars.
+///
+/// ```
+/// use sphere_audio_visualizer::audio_analysis::BeatGrid;
+///
+/// // 120 bpm in 4/4 with the first downbeat after half a second
+/// let beat_grid = BeatGrid::new(120.0, 0.5, 4);
+///
+/// assert_eq!(beat_grid.bar_length(), 2.0);
+/// assert_eq!(beat_grid.nearest_bar(3.2), 2.5);
+/// assert_eq!(beat_grid.previous_bar(2.5), 0.5);
+/// assert_eq!(beat_grid.next_bar(2.5), 4.5);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BeatGrid {
+    bpm: f64,
+    downbeat: f64,
+    beats_per_bar: u32,
+}
+
+impl BeatGrid {
+    /// Creates a new instance:
+    /// - `bpm` Represents the tempo in beats per minute
+    /// - `downbeat` Represents the time of any downbeat in seconds
+    /// - `beats_per_bar` Represents the number of beats in a bar
+    pub fn new(bpm: f64, downbeat: f64, beats_per_bar: u32) -> Self {
+        Self {
+            bpm,
+            downbeat,
+            beats_per_bar,
+        }
+    }
+
+    /// Returns the tempo in beats per minute
+    pub fn bpm(&self) -> f64 {
+        self.bpm
+    }
+
+    /// Returns the time of the downbeat the grid is anchored at in seconds
+    pub fn downbeat(&self) -> f64 {
+        self.downbeat
+    }
+
+    /// Returns the number of beats in a bar
+    pub fn beats_per_bar(&self) -> u32 {
+        self.beats_per_bar
+    }
+
+    /// Returns the length of a beat in seconds
+    pub fn beat_length(&self) -> f64 {
+        60.0 / self.bpm
+    }
+
+    /// Returns the length of a bar in seconds
+    pub fn bar_length(&self) -> f64 {
+        self.beat_length() * self.beats_per_bar.max(1) as f64
+    }
+
+    /// Returns the start of the bar nearest to the passed time in seconds
+    pub fn nearest_bar(&self, time: f64) -> f64 {
+        self.bar_start(self.bars(time).round())
+    }
+
+    /// Returns the start of the last bar before the passed time in seconds
+    pub fn previous_bar(&self, time: f64) -> f64 {
+        self.bar_start(self.bars(time).ceil() - 1.0)
+    }
+
+    /// Returns the start of the first bar after the passed time in seconds
+    pub fn next_bar(&self, time: f64) -> f64 {
+        self.bar_start(self.bars(time).floor() + 1.0)
+    }
+
+    /// Returns the number of bars between the downbeat and the passed time
+    fn bars(&self, time: f64) -> f64 {
+        (time - self.downbeat) / self.bar_length()
+    }
+
+    fn bar_start(&self, bars: f64) -> f64 {
+        self.downbeat + bars * self.bar_length()
+    }
+}
+
+impl Default for BeatGrid {
+    fn default() -> Self {
+        Self::new(BEAT_GRID_BPM, 0.0, BEAT_GRID_BEATS_PER_BAR)
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// Defines the file extension of cached analyses
+pub const ANALYSIS_CACHE_EXTENSION: &str = "sava";
+
+const ANALYSIS_CACHE_MAGIC: &[u8; 4] = b"SAVA";
+const ANALYSIS_CACHE_VERSION: u32 = 1;
+
+/// Caches the band levels of every analysis step of a track on disk. The
+/// first pass over a track records the levels, later passes with the same key
+/// replay them instead of analyzing the samples again. The key has to cover
+/// everything the levels depend on, like the track, the analysis settings and
+/// how the samples are split into steps.
+pub struct AnalysisCache {
+    path: PathBuf,
+    levels: Vec<Vec<f32>>,
+    position: usize,
+    replaying: bool,
+    complete: bool,
+}
+
+impl AnalysisCache {
+    /// Opens the cache with the given key in the directory. If there is no
+    /// cache for the key yet, the levels are recorded.
+    pub fn open(directory: impl AsRef<Path>, key: u64) -> Self {
+        let path = directory
+            .as_ref()
+            .join(format!("{:016x}.{}", key, ANALYSIS_CACHE_EXTENSION));
+
+        let levels = match read_levels(&path) {
+            Ok(levels) => Some(levels),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => {
+                eprintln!("Failed to read analysis cache {:?}: {}", path, error);
+                None
+            }
+        };
+
+        Self {
+            path,
+            replaying: levels.is_some(),
+            levels: levels.unwrap_or_default(),
+            position: 0,
+            complete: false,
+        }
+    }
+
+    /// Gets the path of the cache file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether the levels are replayed from an existing cache
+    pub fn is_replaying(&self) -> bool {
+        self.replaying
+    }
+
+    /// Returns the levels of the next analysis step. While recording the
+    /// levels are computed by `analyze`, which is also used as a fallback if
+    /// the track is longer than the replayed cache.
+    pub fn levels(&mut self, analyze: impl FnOnce() -> Vec<f32>) -> Vec<f32> {
+        if self.replaying {
+            if let Some(levels) = self.levels.get(self.position) {
+                self.position += 1;
+                return levels.clone();
+            }
+
+            return analyze();
+        }
+
+        let levels = analyze();
+        self.levels.push(levels.clone());
+        levels
+    }
+
+    /// Marks the recording as complete. Only complete recordings are stored
+    /// when the cache is dropped, so aborted passes leave no partial cache
+    /// behind.
+    pub fn complete(&mut self) {
+        self.complete = true;
+    }
+
+    fn store(&self) -> io::Result<()> {
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+
+        writer.write_all(ANALYSIS_CACHE_MAGIC)?;
+        writer.write_all(&ANALYSIS_CACHE_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.levels.len() as u32).to_le_bytes())?;
+
+        for levels in &self.levels {
+            writer.write_all(&(levels.len() as u32).to_le_bytes())?;
+
+            for level in levels {
+                writer.write_all(&level.to_le_bytes())?;
+            }
+        }
+
+        writer.flush()
+    }
+}
+
+impl Drop for AnalysisCache {
+    fn drop(&mut self) {
+        if self.replaying || !self.complete {
+            return;
+        }
+
+        if let Err(error) = self.store() {
+            eprintln!("Failed to store analysis cache {:?}: {}", self.path, error);
+        }
+    }
+}
+
+fn read_levels(path: &Path) -> io::Result<Vec<Vec<f32>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+
+    if &magic != ANALYSIS_CACHE_MAGIC || read_u32(&mut reader)? != ANALYSIS_CACHE_VERSION {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "not a supported analysis cache",
+        ));
+    }
+
+    let step_count = read_u32(&mut reader)? as usize;
+    let mut steps = Vec::with_capacity(step_count);
+
+    for _ in 0..step_count {
+        let len = read_u32(&mut reader)? as usize;
+        let mut bytes = vec![0; len * 4];
+        reader.read_exact(&mut bytes)?;
+
+        steps.push(
+            bytes
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+                .collect(),
+        );
+    }
+
+    Ok(steps)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
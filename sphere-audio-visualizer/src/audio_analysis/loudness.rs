@@ -0,0 +1,110 @@
+use super::Samples;
+use crate::module::Module;
+
+/// Defines the default envelope attack of the loudness
+const LOUDNESS_ATTACK: f32 = 0.01;
+
+/// Defines the default envelope release of the loudness
+const LOUDNESS_RELEASE: f32 = 0.3;
+
+/// Defines the default envelope threshold of the loudness
+const LOUDNESS_THRESHOLD: f32 = 0.1;
+
+/// Stores the settings of the loudness module
+#[derive(Clone, PartialEq)]
+pub struct LoudnessSettings {
+    /// The envelope threshhold
+    pub threshold: f32,
+    /// The envelope attack
+    pub attack: f32,
+    /// The envelope release
+    pub release: f32,
+}
+
+impl Default for LoudnessSettings {
+    fn default() -> Self {
+        Self {
+            threshold: LOUDNESS_THRESHOLD,
+            attack: LOUDNESS_ATTACK,
+            release: LOUDNESS_RELEASE,
+        }
+    }
+}
+
+/// Follows the overall loudness of the audio signal across all frequencies
+pub struct Loudness {
+    settings: LoudnessSettings,
+    attack: f32,
+    release: f32,
+    sample_rate: f64,
+    level: f32,
+}
+
+impl Loudness {
+    /// Processes multiple samples at once and returns the loudness after
+    /// processing the last sample
+    pub fn tick(&mut self, samples: Samples) -> f32 {
+        let old_sample_rate = self.sample_rate;
+        self.sample_rate = samples.sample_rate;
+
+        if self.sample_rate != old_sample_rate {
+            self.update_envelope();
+        }
+
+        for sample in samples.samples {
+            let sample = sample.abs();
+
+            let factor = if self.level < sample {
+                self.attack
+            } else {
+                self.release
+            };
+
+            self.level = factor * (self.level - sample) + sample;
+        }
+
+        self.level()
+    }
+
+    /// Returns the loudness after the last processed sample
+    pub fn level(&self) -> f32 {
+        self.level * 2.0
+    }
+
+    fn update_envelope(&mut self) {
+        let samples_per_attack = self.settings.attack * self.sample_rate as f32;
+        let samples_per_release = self.settings.release * self.sample_rate as f32;
+
+        self.attack = self.settings.threshold.powf(1f32 / samples_per_attack);
+        self.release = self.settings.threshold.powf(1f32 / samples_per_release);
+    }
+}
+
+impl Default for Loudness {
+    fn default() -> Self {
+        Self {
+            settings: LoudnessSettings::default(),
+            attack: 0.0,
+            release: 0.0,
+            sample_rate: 0.0,
+            level: 0.0,
+        }
+    }
+}
+
+impl Module for Loudness {
+    type Settings = LoudnessSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        if self.settings != settings {
+            self.settings = settings;
+            self.update_envelope();
+        }
+
+        self
+    }
+
+    fn settings(&self) -> Self::Settings {
+        self.settings.clone()
+    }
+}
@@ -1,8 +1,11 @@
 //! Contains the algorithim used for audio analysis
 
-pub use self::{filter::*, spectrum::*};
+pub use self::{beat_grid::*, cache::*, filter::*, loudness::*, spectrum::*};
 
+mod beat_grid;
+mod cache;
 mod filter;
+mod loudness;
 mod spectrum;
 pub mod utils;
 
@@ -14,3 +17,34 @@ pub struct Samples<'a> {
     /// The samples
     pub samples: &'a [f32],
 }
+
+impl<'a> Samples<'a> {
+    /// Returns the duration of the samples in seconds. Batches without a
+    /// valid sample rate last no time.
+    ///
+    /// ```
+    /// use sphere_audio_visualizer::audio_analysis::Samples;
+    ///
+    /// let samples = [0.0; 441];
+    ///
+    /// let batch = |sample_rate, samples| Samples {
+    ///     sample_rate,
+    ///     samples,
+    /// };
+    ///
+    /// assert_eq!(batch(44100.0, &samples).duration(), 0.01);
+    /// assert_eq!(batch(44100.0, &samples[..0]).duration(), 0.0);
+    /// assert_eq!(batch(0.0, &samples).duration(), 0.0);
+    /// ```
+    pub fn duration(&self) -> f64 {
+        match self.has_valid_sample_rate() {
+            true => self.samples.len() as f64 / self.sample_rate,
+            false => 0.0,
+        }
+    }
+
+    /// Returns if the sample rate is positive and finite
+    pub fn has_valid_sample_rate(&self) -> bool {
+        self.sample_rate.is_finite() && self.sample_rate > 0.0
+    }
+}
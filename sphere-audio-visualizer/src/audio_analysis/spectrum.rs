@@ -1,6 +1,9 @@
 use std::ops::Range;
 
-use rayon::prelude::{IntoParallelRefMutIterator, ParallelIterator};
+use rayon::{
+    prelude::{IntoParallelRefMutIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
 
 use super::Samples;
 use crate::{audio_analysis::filter::IIRFilter, module::Module};
@@ -23,6 +26,16 @@ const SPECTRUM_RELEASE: f32 = 0.4;
 /// Defines the default envelope threshold for the audio analysis
 const SPECTRUM_THRESHOLD: f32 = 0.1;
 
+/// Specifies how the frequency bands of the [`Spectrum`] are processed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisMode {
+    /// The bands are processed one after another on the calling thread. This
+    /// avoids the overhead of the thread pool on small CPUs or short buffers.
+    Serial,
+    /// The bands are processed in parallel on the rayon thread pool
+    Parallel,
+}
+
 /// Stores the settings of audio analysis module
 #[derive(Clone, PartialEq)]
 pub struct SpectrumSettings {
@@ -38,6 +51,11 @@ pub struct SpectrumSettings {
     pub attack: f32,
     /// The envelope release
     pub release: f32,
+    /// How the frequency bands are processed
+    pub mode: AnalysisMode,
+    /// The maximum amount of threads used by [`Spectrum::tick_par`]. 0 uses
+    /// the global rayon thread pool.
+    pub threads: usize,
 }
 
 impl Default for SpectrumSettings {
@@ -49,10 +67,30 @@ impl Default for SpectrumSettings {
             threshold: SPECTRUM_THRESHOLD,
             attack: SPECTRUM_ATTACK,
             release: SPECTRUM_RELEASE,
+            mode: AnalysisMode::Parallel,
+            threads: 0,
         }
     }
 }
 
+impl SpectrumSettings {
+    /// Returns the frequency range of a band. The bands are spaced
+    /// logarithmically between the lowest and highest frequency.
+    pub fn band_range(&self, band: usize) -> Range<f32> {
+        let exponent = (self.high / self.low).powf(1.0 / self.count as f32);
+
+        self.low * exponent.powf(band as f32)..self.low * exponent.powf((band + 1) as f32)
+    }
+
+    /// Returns the center frequency of a band, which is the geometric mean of
+    /// its range
+    pub fn center_frequency(&self, band: usize) -> f32 {
+        let range = self.band_range(band);
+
+        (range.start * range.end).sqrt()
+    }
+}
+
 /// The audio analysis module
 pub struct Spectrum {
     envelope_bands: Vec<FrequencyBand>,
@@ -60,6 +98,7 @@ pub struct Spectrum {
     attack: f32,
     release: f32,
     sample_rate: f64,
+    thread_pool: Option<ThreadPool>,
 }
 
 /// Implements the audio anaysis functionalities for one band of the analysis.
@@ -137,16 +176,36 @@ impl Spectrum {
 
         let attack = self.attack;
         let release = self.release;
+        let envelope_bands = &mut self.envelope_bands;
 
-        self.envelope_bands.par_iter_mut().for_each(move |band| {
-            for sample in samples.samples {
-                band.tick(*sample, attack, release)
+        match &self.thread_pool {
+            Some(thread_pool) => {
+                thread_pool.install(|| tick_bands_par(envelope_bands, samples, attack, release))
             }
-        });
+            None => tick_bands_par(envelope_bands, samples, attack, release),
+        }
 
         self.envelope_bands.iter().map(|band| band.level * 2.0)
     }
 
+    /// Processes multiple samples at once using the configured
+    /// [`AnalysisMode`] and collects the levels after processing the last
+    /// sample of the different bands.
+    pub fn analyze(&mut self, samples: Samples) -> Vec<f32> {
+        match self.settings.mode {
+            AnalysisMode::Serial => self.tick(samples).collect(),
+            AnalysisMode::Parallel => self.tick_par(samples).collect(),
+        }
+    }
+
+    fn update_thread_pool(&mut self) {
+        // if the pool can't be created the global thread pool is used
+        self.thread_pool = match self.settings.threads {
+            0 => None,
+            threads => ThreadPoolBuilder::new().num_threads(threads).build().ok(),
+        };
+    }
+
     fn update_envelope(&mut self) {
         let samples_per_attack = self.settings.attack * self.sample_rate as f32;
         let samples_per_release = self.settings.release * self.sample_rate as f32;
@@ -158,19 +217,21 @@ impl Spectrum {
     fn update_bands(&mut self) {
         self.envelope_bands.clear();
 
-        let exponent =
-            (self.settings.high / self.settings.low).powf(1.0 / self.settings.count as f32);
-
         for i in 0..self.settings.count {
-            let low_cutoff = self.settings.low * exponent.powf(i as f32);
-            let high_cutoff = self.settings.low * exponent.powf((i + 1) as f32);
-
             self.envelope_bands
-                .push(FrequencyBand::new(low_cutoff..high_cutoff, 44100.0));
+                .push(FrequencyBand::new(self.settings.band_range(i), 44100.0));
         }
     }
 }
 
+fn tick_bands_par(bands: &mut [FrequencyBand], samples: Samples, attack: f32, release: f32) {
+    bands.par_iter_mut().for_each(move |band| {
+        for sample in samples.samples {
+            band.tick(*sample, attack, release)
+        }
+    });
+}
+
 impl Default for Spectrum {
     fn default() -> Self {
         Self {
@@ -182,10 +243,13 @@ impl Default for Spectrum {
                 threshold: 0.0,
                 attack: 0.0,
                 release: 0.0,
+                mode: AnalysisMode::Parallel,
+                threads: 0,
             },
             attack: 0.0,
             release: 0.0,
             sample_rate: 0.0,
+            thread_pool: None,
         }
     }
 }
@@ -210,6 +274,10 @@ impl Module for Spectrum {
             self.update_envelope();
         }
 
+        if self.settings.threads != settings.threads {
+            self.update_thread_pool();
+        }
+
         self
     }
 
@@ -1,6 +1,10 @@
-use std::ops::Add;
+use std::{
+    ops::Add,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use egui::{Button, ComboBox, Context, FullOutput, Grid, ProgressBar, RawInput, Ui};
+use egui::{Button, ComboBox, Context, DragValue, FullOutput, Grid, ProgressBar, RawInput, Ui};
 use egui_wgpu_backend::ScreenDescriptor;
 use egui_winit::State;
 use winit::{
@@ -9,16 +13,33 @@ use winit::{
     window::{Window, WindowBuilder},
 };
 
-use super::{drawer::UiDrawer, ExportProcess, Exporter, OnlineSampleSource, Samples};
+#[cfg(feature = "gamepad")]
+use super::GamepadCamera;
+use super::{
+    drawer::UiDrawer, Crossfader, ExportProcess, Exporter, LoudnessMatcher, MouseCamera,
+    OnlineSampleSource, SampleSourceStatus, Samples, SessionRecorder, StatusIndicator,
+    SESSION_EXTENSION,
+};
+#[cfg(feature = "config")]
+use super::{ModuleSettingsDescription, SettingsCodec, VisualizerDescription, VisualizerRegistry};
 use crate::{
-    rendering::wgpu::EGUIScene,
-    visualizer::{DynamicVisualizer, OnlineVisualizer, VisualizerFactory},
+    audio_analysis::BeatGrid,
+    rendering::{
+        wgpu::{AdapterSelection, EGUIScene, EGUIUserTextures},
+        CameraControl,
+    },
+    visualizer::{
+        DynamicVisualizer, OfflineVisualizer, OnlineVisualizer, Section, SectionedVisualizer,
+        Visualizer, VisualizerFactory, VisualizerPreset,
+    },
 };
 
 struct VisualizerConfiguration {
     name: String,
     change_visualizer: fn(&mut DynamicVisualizer, &Window),
     settings_drawer: fn(&mut DynamicVisualizer, &mut Ui),
+    #[cfg(feature = "config")]
+    settings_codec: Option<SettingsCodec>,
 }
 
 struct SampleSourceConfiguration {
@@ -73,6 +94,18 @@ impl OnlineSampleSource for SampleSourceConfiguration {
     fn focus(&mut self) {
         self.online_sample_source.focus()
     }
+
+    fn requested_visualizer(&mut self) -> Option<String> {
+        self.online_sample_source.requested_visualizer()
+    }
+
+    fn requested_settings(&mut self) -> Option<String> {
+        self.online_sample_source.requested_settings()
+    }
+
+    fn status(&self) -> Option<SampleSourceStatus> {
+        self.online_sample_source.status()
+    }
 }
 
 /// This is the central struct of the sphere audio visualizer. It manages the
@@ -88,8 +121,19 @@ pub struct Application {
     visualizer_configurations: Vec<VisualizerConfiguration>,
     selected_sample_source_id: usize,
     sample_source_configurations: Vec<SampleSourceConfiguration>,
+    status_indicator: StatusIndicator,
+    crossfader: Crossfader,
+    loudness_matcher: LoudnessMatcher,
     export_progresses: Vec<Box<dyn ExportProcess>>,
     show_individual_progress: bool,
+    user_textures: EGUIUserTextures,
+    session_directory: Option<PathBuf>,
+    recorder: Option<SessionRecorder>,
+    presets: Vec<VisualizerPreset>,
+    sections: Vec<Section>,
+    mouse_camera: MouseCamera,
+    #[cfg(feature = "gamepad")]
+    gamepad_camera: Option<GamepadCamera>,
 }
 
 impl Application {
@@ -101,6 +145,11 @@ impl Application {
 
         let visualizer = DynamicVisualizer::new();
 
+        #[cfg(feature = "gamepad")]
+        let gamepad_camera = GamepadCamera::new()
+            .map_err(|error| eprintln!("Failed to access gamepads: {}", error))
+            .ok();
+
         Self {
             visualizer,
             window,
@@ -111,15 +160,60 @@ impl Application {
             visualizer_configurations: Vec::new(),
             selected_sample_source_id: 0,
             sample_source_configurations: Vec::new(),
+            status_indicator: StatusIndicator::default(),
+            crossfader: Crossfader::default(),
+            loudness_matcher: LoudnessMatcher::default(),
             export_progresses: Vec::new(),
             show_individual_progress: false,
+            user_textures: EGUIUserTextures::default(),
+            session_directory: None,
+            recorder: None,
+            presets: Vec::new(),
+            sections: Vec::new(),
+            mouse_camera: MouseCamera::default(),
+            #[cfg(feature = "gamepad")]
+            gamepad_camera,
         }
     }
 
+    /// Returns the user textures, which can be drawn in the overlay by their
+    /// [`egui::TextureId`]
+    pub fn user_textures(&mut self) -> &mut EGUIUserTextures {
+        &mut self.user_textures
+    }
+
+    /// Builder method for setting the directory recorded sessions are written
+    /// to. Recording is only offered in the UI if a directory is set.
+    pub fn with_session_directory(mut self, session_directory: impl Into<PathBuf>) -> Self {
+        self.session_directory = Some(session_directory.into());
+        self
+    }
+
+    /// Builder method for setting the time in seconds the samples of two
+    /// sample sources are crossfaded when switching between them
+    pub fn with_crossfade_time(mut self, crossfade_time: f64) -> Self {
+        self.crossfader.set_fade_time(crossfade_time);
+        self
+    }
+
+    /// Builder method for setting if the loudness of a newly selected sample
+    /// source is matched to the loudness of the previously selected one
+    pub fn with_loudness_matching(mut self, loudness_matching: bool) -> Self {
+        self.loudness_matcher.set_enabled(loudness_matching);
+        self
+    }
+
+    /// Builder method for selecting the adapter used for rendering, which
+    /// also applies to exports
+    pub fn with_adapter_selection(mut self, adapter_selection: AdapterSelection) -> Self {
+        self.visualizer.settings_bin_mut().insert(adapter_selection);
+        self
+    }
+
     /// adds a new visualizer configuration. The name is displayed in the UI.
     pub fn with_visualizer_configuration<F, S>(mut self, name: S) -> Self
     where
-        F: VisualizerFactory,
+        F: VisualizerFactory + 'static,
         F::OnlineVisualizer: UiDrawer,
         S: ToString,
     {
@@ -138,11 +232,53 @@ impl Application {
                         online_visualizer.ui(ui);
                     }
                 },
+                #[cfg(feature = "config")]
+                settings_codec: None,
             });
 
         self
     }
 
+    /// Adds the visualizer configurations of the descriptions using the
+    /// modules of the registry. The settings of all descriptions are applied
+    /// before the first configuration is added. Since modules share their
+    /// settings across visualizers, later descriptions override the settings
+    /// of earlier descriptions using the same module. Invalid descriptions
+    /// are reported and skipped.
+    #[cfg(feature = "config")]
+    pub fn with_visualizer_descriptions(
+        mut self,
+        registry: &VisualizerRegistry,
+        descriptions: &[VisualizerDescription],
+    ) -> Self {
+        let registrations = descriptions
+            .iter()
+            .filter_map(|description| {
+                match registry.apply(description, self.visualizer.settings_bin_mut()) {
+                    Ok(registration) => Some((
+                        registration.register,
+                        registration.settings_codec,
+                        description.name.clone(),
+                    )),
+                    Err(error) => {
+                        eprintln!("Failed to add visualizer {}: {}", description.name, error);
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for (register, settings_codec, name) in registrations {
+            self = register(self, name);
+
+            if let Some(visualizer_configuration) = self.visualizer_configurations.last_mut() {
+                visualizer_configuration.settings_codec = Some(settings_codec);
+            }
+        }
+
+        self
+    }
+
     /// addss a new online only sample source (without [`Exporter`]).
     /// The name is displayed in the UI.
     pub fn with_online_only_sample_source(
@@ -190,7 +326,8 @@ impl Application {
                     Event::RedrawEventsCleared => self.window.request_redraw(),
                     Event::WindowEvent { event, window_id } => {
                         if self.window.id() == window_id {
-                            self.state.on_event(&self.context, &event);
+                            let consumed = self.state.on_event(&self.context, &event);
+                            self.mouse_camera.on_event(&event, consumed);
 
                             match event {
                                 WindowEvent::CloseRequested => {
@@ -233,16 +370,213 @@ impl Application {
             scale_factor: self.state.pixels_per_point(),
         };
 
-        let egui_scene = EGUIScene::new(&self.context, textures_delta, shapes, scene_descriptor);
+        let egui_scene = EGUIScene::new(&self.context, textures_delta, shapes, scene_descriptor)
+            .with_user_textures(&mut self.user_textures);
+
+        let sample_source = &mut self.sample_source_configurations[self.selected_sample_source_id];
+
+        if let Some(name) = sample_source.requested_visualizer() {
+            if let Some(id) = self
+                .visualizer_configurations
+                .iter()
+                .position(|visualizer_configuration| visualizer_configuration.name == name)
+            {
+                self.select_visualizer(id);
+            }
+        }
+
+        #[cfg(feature = "config")]
+        if let Some(settings) =
+            self.sample_source_configurations[self.selected_sample_source_id].requested_settings()
+        {
+            self.apply_settings(&settings);
+        }
+
+        #[cfg(feature = "config")]
+        self.record_settings();
+
+        // the faded out source keeps running until its fade finished, so
+        // it is only unfocused afterwards
+        if let Some(id) = self.crossfader.take_finished() {
+            if id != self.selected_sample_source_id {
+                self.sample_source_configurations[id].unfocus();
+            }
+        }
+
+        let bpm = self.sample_source_configurations[self.selected_sample_source_id]
+            .exporter()
+            .and_then(|exporter| exporter.beat_grid())
+            .map(|beat_grid| beat_grid.bpm() as f32);
+
+        self.visualizer.set_bpm(bpm);
+
+        let samples = match self.crossfader.fading_source_id() {
+            Some(fading_id) => {
+                let (fading, selected) = pair_mut(
+                    &mut self.sample_source_configurations,
+                    fading_id,
+                    self.selected_sample_source_id,
+                );
+
+                let selected = selected.samples();
+                self.loudness_matcher.measure(&selected);
+
+                self.crossfader.mix(fading.samples(), selected)
+            }
+            None => {
+                let selected =
+                    self.sample_source_configurations[self.selected_sample_source_id].samples();
+                self.loudness_matcher.measure(&selected);
+
+                selected
+            }
+        };
+
+        let samples = self.loudness_matcher.apply(samples);
 
-        let samples = self.sample_source_configurations[self.selected_sample_source_id].samples();
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(error) = recorder.record_samples(&samples) {
+                eprintln!("Failed to record session: {}", error);
+                self.recorder = None;
+            }
+        }
+
+        let camera_control = CameraControl::default();
+
+        #[cfg(feature = "gamepad")]
+        let camera_control = match &mut self.gamepad_camera {
+            Some(gamepad_camera) => gamepad_camera.update(),
+            None => camera_control,
+        };
+
+        self.visualizer
+            .set_camera_control(self.mouse_camera.apply(camera_control));
+
+        self.visualizer.set_monitor(
+            self.window
+                .current_monitor()
+                .and_then(|monitor| monitor.name()),
+        );
 
         self.visualizer
             .visualize(samples, size.width, size.height, egui_scene);
     }
 
+    fn select_visualizer(&mut self, id: usize) {
+        if self.selected_visualizer_id == id {
+            return;
+        }
+
+        self.selected_visualizer_id = id;
+
+        (self.visualizer_configurations[id].change_visualizer)(&mut self.visualizer, &self.window);
+
+        self.record_visualizer();
+    }
+
+    fn record_visualizer(&mut self) {
+        if let Some(recorder) = &mut self.recorder {
+            let name = &self.visualizer_configurations[self.selected_visualizer_id].name;
+
+            if let Err(error) = recorder.record_visualizer(name) {
+                eprintln!("Failed to record session: {}", error);
+                self.recorder = None;
+            }
+        }
+    }
+
+    /// Records the settings of the selected visualizer, if they changed and
+    /// can be described
+    #[cfg(feature = "config")]
+    fn record_settings(&mut self) {
+        let recorder = match &mut self.recorder {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        let settings_codec =
+            match self.visualizer_configurations[self.selected_visualizer_id].settings_codec {
+                Some(settings_codec) => settings_codec,
+                None => return,
+            };
+
+        let settings = match self.visualizer.current_settings() {
+            Some(settings_bin) => (settings_codec.capture)(&settings_bin)
+                .and_then(|settings| serde_yaml::to_string(&settings)),
+            None => return,
+        };
+
+        let result = match settings {
+            Ok(settings) => recorder.record_settings(&settings),
+            Err(error) => {
+                eprintln!("Failed to record visualizer settings: {}", error);
+                return;
+            }
+        };
+
+        if let Err(error) = result {
+            eprintln!("Failed to record session: {}", error);
+            self.recorder = None;
+        }
+    }
+
+    /// Applies recorded settings to the selected visualizer
+    #[cfg(feature = "config")]
+    fn apply_settings(&mut self, settings: &str) {
+        let settings_codec =
+            match self.visualizer_configurations[self.selected_visualizer_id].settings_codec {
+                Some(settings_codec) => settings_codec,
+                None => return,
+            };
+
+        let result =
+            serde_yaml::from_str::<ModuleSettingsDescription>(settings).and_then(|settings| {
+                self.visualizer
+                    .update_settings(|settings_bin| (settings_codec.apply)(&settings, settings_bin))
+            });
+
+        if let Err(error) = result {
+            eprintln!("Failed to apply recorded settings: {}", error);
+        }
+    }
+
+    fn start_recording(&mut self) {
+        let session_directory = match &self.session_directory {
+            Some(session_directory) => session_directory,
+            None => return,
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let path = session_directory.join(format!("session-{}.{}", timestamp, SESSION_EXTENSION));
+
+        match std::fs::create_dir_all(session_directory)
+            .and_then(|_| SessionRecorder::create(&path))
+        {
+            Ok(recorder) => {
+                self.recorder = Some(recorder);
+                self.record_visualizer();
+            }
+            Err(error) => eprintln!("Failed to create session {:?}: {}", path, error),
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            if let Err(error) = recorder.finish() {
+                eprintln!("Failed to record session: {}", error);
+            }
+        }
+    }
+
     fn show(&mut self, new_input: RawInput) -> FullOutput {
-        self.context.run(new_input, |ctx| {
+        let old_selected_visualizer_id = self.selected_visualizer_id;
+        let mut toggle_recording = false;
+
+        let full_output = self.context.run(new_input, |ctx| {
             egui::Window::new("Settings").show(ctx, |ui| {
                 ui.heading("Audio:");
 
@@ -254,27 +588,82 @@ impl Application {
                         let old_selected_sample_source_id = self.selected_sample_source_id;
                         let audio_source_name =
                             &self.sample_source_configurations[self.selected_sample_source_id].name;
-                        ComboBox::from_id_source("Audio Source Selector")
-                            .selected_text(audio_source_name)
-                            .width(168.0)
-                            .show_ui(ui, |ui| {
-                                for (id, sample_source_configuration) in
-                                    self.sample_source_configurations.iter().enumerate()
-                                {
-                                    ui.selectable_value(
-                                        &mut self.selected_sample_source_id,
-                                        id,
-                                        &sample_source_configuration.name,
-                                    );
-                                }
-                            });
+                        let status = self.sample_source_configurations
+                            [self.selected_sample_source_id]
+                            .status();
+                        ui.horizontal(|ui| {
+                            ComboBox::from_id_source("Audio Source Selector")
+                                .selected_text(audio_source_name)
+                                .width(112.0)
+                                .show_ui(ui, |ui| {
+                                    for (id, sample_source_configuration) in
+                                        self.sample_source_configurations.iter().enumerate()
+                                    {
+                                        ui.selectable_value(
+                                            &mut self.selected_sample_source_id,
+                                            id,
+                                            &sample_source_configuration.name,
+                                        );
+                                    }
+                                });
+
+                            self.status_indicator.ui(status, ui);
+                        });
                         ui.end_row();
 
                         if old_selected_sample_source_id != self.selected_sample_source_id {
-                            self.sample_source_configurations[old_selected_sample_source_id]
-                                .unfocus();
-                            self.sample_source_configurations[self.selected_sample_source_id]
-                                .focus();
+                            self.status_indicator = StatusIndicator::default();
+                            self.loudness_matcher.start();
+
+                            // a source which was still fading out is focused
+                            // already, so it is only taken back
+                            match self.crossfader.start(old_selected_sample_source_id) {
+                                Some(id) if id == self.selected_sample_source_id => {}
+                                Some(id) => {
+                                    self.sample_source_configurations[id].unfocus();
+                                    self.sample_source_configurations
+                                        [self.selected_sample_source_id]
+                                        .focus();
+                                }
+                                None => self.sample_source_configurations
+                                    [self.selected_sample_source_id]
+                                    .focus(),
+                            }
+                        }
+
+                        ui.label("Crossfade:");
+                        let mut fade_time = self.crossfader.fade_time();
+                        if ui
+                            .add_sized(
+                                [176.0, 20.0],
+                                DragValue::new(&mut fade_time)
+                                    .speed(0.01)
+                                    .clamp_range(0.0..=10.0)
+                                    .suffix(" s"),
+                            )
+                            .changed()
+                        {
+                            self.crossfader.set_fade_time(fade_time);
+                        }
+                        ui.end_row();
+
+                        ui.label("Match Loudness:");
+                        let mut loudness_matching = self.loudness_matcher.enabled();
+                        if ui.checkbox(&mut loudness_matching, "").changed() {
+                            self.loudness_matcher.set_enabled(loudness_matching);
+                        }
+                        ui.end_row();
+
+                        if self.session_directory.is_some() {
+                            ui.label("Record:");
+                            let text = match &self.recorder {
+                                Some(recorder) => format!("Stop ({:.1} s)", recorder.duration()),
+                                None => "Start".to_string(),
+                            };
+                            if ui.add_sized([176.0, 20.0], Button::new(text)).clicked() {
+                                toggle_recording = true;
+                            }
+                            ui.end_row();
                         }
                     });
 
@@ -326,18 +715,163 @@ impl Application {
 
                     exporter.ui(ui);
 
+                    // sections render the track with different presets, e.g.
+                    // to change the look at the drop
+                    let mut removed_section = None;
+                    let beat_grid = exporter.beat_grid();
+
+                    Grid::new("Export Sections Grid")
+                        .num_columns(2)
+                        .striped(true)
+                        .min_col_width(72.0)
+                        .show(ui, |ui| {
+                            ui.label("Presets:");
+                            if ui
+                                .add_sized([176.0, 20.0], Button::new("Save Current"))
+                                .clicked()
+                            {
+                                let name = format!(
+                                    "{} {}",
+                                    self.visualizer_configurations[self.selected_visualizer_id]
+                                        .name,
+                                    self.presets.len() + 1
+                                );
+
+                                self.presets.extend(self.visualizer.preset(name));
+                            }
+                            ui.end_row();
+
+                            for (id, section) in self.sections.iter_mut().enumerate() {
+                                ui.label(format!("Section {}:", id + 1));
+                                ui.horizontal(|ui| {
+                                    let response = ui.add_sized(
+                                        [64.0, 20.0],
+                                        DragValue::new(&mut section.start)
+                                            .speed(0.1)
+                                            .clamp_range(0.0..=f64::MAX)
+                                            .suffix(" s"),
+                                    );
+
+                                    // snapping while dragging would swallow
+                                    // the small steps, so the marker is only
+                                    // placed on a downbeat once it is released
+                                    if let Some(beat_grid) = beat_grid {
+                                        if response.drag_released() || response.lost_focus() {
+                                            section.start =
+                                                snap_to_downbeat(&beat_grid, section.start);
+                                        }
+                                    }
+
+                                    let preset_name = self
+                                        .presets
+                                        .get(section.preset)
+                                        .map(VisualizerPreset::name)
+                                        .unwrap_or_default();
+
+                                    ComboBox::from_id_source(("Export Section Preset", id))
+                                        .selected_text(preset_name)
+                                        .width(60.0)
+                                        .show_ui(ui, |ui| {
+                                            for (preset_id, preset) in
+                                                self.presets.iter().enumerate()
+                                            {
+                                                ui.selectable_value(
+                                                    &mut section.preset,
+                                                    preset_id,
+                                                    preset.name(),
+                                                );
+                                            }
+                                        });
+
+                                    if ui.add_sized([28.0, 20.0], Button::new("✖")).clicked() {
+                                        removed_section = Some(id);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+
+                    if let Some(id) = removed_section {
+                        self.sections.remove(id);
+                    }
+
+                    ui.add_enabled_ui(!self.presets.is_empty(), |ui| {
+                        if ui
+                            .add_sized([256.0, 20.0], Button::new("Add Section"))
+                            .clicked()
+                        {
+                            let start = self
+                                .sections
+                                .last()
+                                .map(|section| section.start)
+                                .unwrap_or_default();
+
+                            let start = match beat_grid {
+                                Some(beat_grid) => snap_to_downbeat(&beat_grid, start),
+                                None => start,
+                            };
+
+                            self.sections.push(Section {
+                                start,
+                                preset: self.presets.len() - 1,
+                            });
+                        }
+                    });
+
                     ui.add_enabled_ui(exporter.can_export(), |ui| {
                         if ui.add_sized([256.0, 20.0], Button::new("Export")).clicked() {
-                            if let Some(visualizer) =
-                                self.visualizer.offline_visualizer(exporter.format())
-                            {
+                            if let Some(visualizer) = export_visualizer(
+                                &mut self.visualizer,
+                                &mut self.presets,
+                                &self.sections,
+                                exporter,
+                            ) {
                                 if let Some(process) = exporter.export(visualizer) {
                                     self.export_progresses.push(process)
                                 }
                             }
                         }
+
+                        if ui
+                            .add_sized([256.0, 20.0], Button::new("Dry Run"))
+                            .clicked()
+                        {
+                            if let Some(visualizer) = export_visualizer(
+                                &mut self.visualizer,
+                                &mut self.presets,
+                                &self.sections,
+                                exporter,
+                            ) {
+                                if let Some(process) = exporter.dry_run(visualizer) {
+                                    self.export_progresses.push(process)
+                                }
+                            }
+                        }
                     });
 
+                    if let Some(estimate) = exporter.estimate() {
+                        Grid::new("Export Estimate Grid")
+                            .num_columns(2)
+                            .striped(true)
+                            .min_col_width(72.0)
+                            .show(ui, |ui| {
+                                ui.label("Speed:");
+                                ui.label(format!("{:.1} fps", estimate.frames_per_second));
+                                ui.end_row();
+
+                                ui.label("Est. Time:");
+                                ui.label(format_duration(estimate.duration));
+                                ui.end_row();
+
+                                ui.label("Est. Size:");
+                                ui.label(format!(
+                                    "{:.1} MB",
+                                    estimate.file_size as f64 / 1_000_000.0
+                                ));
+                                ui.end_row();
+                            });
+                    }
+
                     if let Some(progress) = self
                         .export_progresses
                         .iter()
@@ -404,6 +938,79 @@ impl Application {
                             });
                         })
                 });
-        })
+        });
+
+        if old_selected_visualizer_id != self.selected_visualizer_id {
+            self.record_visualizer();
+        }
+
+        if toggle_recording {
+            if self.recorder.is_some() {
+                self.stop_recording();
+            } else {
+                self.start_recording();
+            }
+        }
+
+        full_output
+    }
+}
+
+/// Creates the offline visualizer an export is rendered with. If sections are
+/// defined, the presets are switched at their boundaries.
+fn export_visualizer(
+    visualizer: &mut DynamicVisualizer,
+    presets: &mut [VisualizerPreset],
+    sections: &[Section],
+    exporter: &dyn Exporter,
+) -> Option<Box<dyn OfflineVisualizer>> {
+    let mut visualizer = match sections.is_empty() {
+        true => visualizer.offline_visualizer(exporter.format())?,
+        false => Box::new(SectionedVisualizer::from_presets(
+            presets,
+            sections,
+            exporter.format(),
+        )?),
+    };
+
+    visualizer.set_bpm(exporter.beat_grid().map(|beat_grid| beat_grid.bpm() as f32));
+
+    Some(visualizer)
+}
+
+/// Formats the duration in seconds as `hours:minutes:seconds`
+fn format_duration(duration: f64) -> String {
+    let seconds = duration.max(0.0).round() as u64;
+
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Snaps the time to the nearest downbeat, which is not before the start of
+/// the track
+fn snap_to_downbeat(beat_grid: &BeatGrid, time: f64) -> f64 {
+    let downbeat = beat_grid.nearest_bar(time);
+
+    if downbeat < 0.0 {
+        beat_grid.next_bar(downbeat)
+    } else {
+        downbeat
+    }
+}
+
+/// Borrows two different elements of a slice mutably
+fn pair_mut<T>(slice: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    assert_ne!(a, b);
+
+    if a < b {
+        let (left, right) = slice.split_at_mut(b);
+        (&mut left[a], &mut right[0])
+    } else {
+        let (left, right) = slice.split_at_mut(a);
+        (&mut right[0], &mut left[b])
     }
 }
@@ -0,0 +1,123 @@
+use crate::audio_analysis::Samples;
+
+/// Defines the default time in seconds a crossfade between two sample sources
+/// lasts
+pub const CROSSFADE_TIME: f64 = 0.5;
+
+struct Fade {
+    source_id: usize,
+    elapsed: f64,
+}
+
+/// Mixes the samples of the previously selected sample source into the
+/// samples of the newly selected one, so switching sources fades the old
+/// source out instead of cutting it off. The fade advances with the mixed
+/// samples, so it lasts the same time independent of the frame rate.
+pub struct Crossfader {
+    fade_time: f64,
+    fade: Option<Fade>,
+    finished: Option<usize>,
+    buffer: Vec<f32>,
+}
+
+impl Crossfader {
+    /// Returns the time in seconds a crossfade lasts
+    pub fn fade_time(&self) -> f64 {
+        self.fade_time
+    }
+
+    /// Sets the time in seconds a crossfade lasts. A time of 0.0 switches
+    /// sources without fading.
+    pub fn set_fade_time(&mut self, fade_time: f64) -> &mut Self {
+        self.fade_time = fade_time.max(0.0);
+        self
+    }
+
+    /// Sets the time in seconds a crossfade lasts
+    pub fn with_fade_time(mut self, fade_time: f64) -> Self {
+        self.set_fade_time(fade_time);
+        self
+    }
+
+    /// Starts fading out the sample source with the given id. Returns the id
+    /// of the source which was faded out before, since it is not mixed
+    /// anymore.
+    pub fn start(&mut self, source_id: usize) -> Option<usize> {
+        let previous = self.fade.take().map(|fade| fade.source_id);
+
+        if self.fade_time > 0.0 {
+            self.fade = Some(Fade {
+                source_id,
+                elapsed: 0.0,
+            });
+        } else {
+            self.finished = Some(source_id);
+        }
+
+        previous
+    }
+
+    /// Returns the id of the sample source which is currently faded out
+    pub fn fading_source_id(&self) -> Option<usize> {
+        self.fade.as_ref().map(|fade| fade.source_id)
+    }
+
+    /// Returns the id of the sample source whose fade finished since the last
+    /// call, so it can be unfocused
+    pub fn take_finished(&mut self) -> Option<usize> {
+        self.finished.take()
+    }
+
+    /// Mixes the samples of the fading source into the samples of the
+    /// selected source. The result uses the sample rate of the selected
+    /// source, the fading samples are resampled if the rates differ.
+    pub fn mix<'a>(&'a mut self, fading: Samples, selected: Samples<'a>) -> Samples<'a> {
+        let fade = match &mut self.fade {
+            Some(fade) => fade,
+            None => return selected,
+        };
+
+        let fade_time = self.fade_time;
+        let elapsed = fade.elapsed;
+        let ratio = fading.sample_rate / selected.sample_rate;
+        let fading_len = (fading.samples.len() as f64 / ratio) as usize;
+        let len = selected.samples.len().max(fading_len);
+
+        self.buffer.clear();
+        self.buffer.extend((0..len).map(|i| {
+            let gain = ((elapsed + i as f64 / selected.sample_rate) / fade_time).min(1.0) as f32;
+            let old = fading
+                .samples
+                .get((i as f64 * ratio) as usize)
+                .copied()
+                .unwrap_or_default();
+            let new = selected.samples.get(i).copied().unwrap_or_default();
+
+            old * (1.0 - gain) + new * gain
+        }));
+
+        fade.elapsed += len as f64 / selected.sample_rate;
+
+        // without any samples there is nothing left to fade
+        if fade.elapsed >= fade_time || len == 0 {
+            self.finished = Some(fade.source_id);
+            self.fade = None;
+        }
+
+        Samples {
+            sample_rate: selected.sample_rate,
+            samples: &self.buffer,
+        }
+    }
+}
+
+impl Default for Crossfader {
+    fn default() -> Self {
+        Self {
+            fade_time: CROSSFADE_TIME,
+            fade: None,
+            finished: None,
+            buffer: Vec::new(),
+        }
+    }
+}
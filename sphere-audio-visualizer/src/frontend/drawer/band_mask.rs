@@ -0,0 +1,42 @@
+use egui::{DragValue, SelectableLabel, Ui};
+
+use crate::{simulation::BandMaskSettings, UiDrawer};
+
+impl UiDrawer for BandMaskSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Band Groups: ");
+        if ui
+            .add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.groups).clamp_range(1..=16),
+            )
+            .changed()
+        {
+            self.solo.resize(self.groups, false);
+            self.mute.resize(self.groups, false);
+        }
+        ui.end_row();
+
+        // the groups are numbered from bass to treble
+        for (label, toggles) in [
+            ("Solo Bands: ", &mut self.solo),
+            ("Mute Bands: ", &mut self.mute),
+        ] {
+            ui.label(label);
+            ui.horizontal_wrapped(|ui| {
+                ui.set_max_width(124.0);
+                ui.spacing_mut().item_spacing.x = 2.0;
+
+                for (group, toggle) in toggles.iter_mut().enumerate() {
+                    if ui
+                        .add(SelectableLabel::new(*toggle, format!("{}", group + 1)))
+                        .clicked()
+                    {
+                        *toggle = !*toggle;
+                    }
+                }
+            });
+            ui.end_row();
+        }
+    }
+}
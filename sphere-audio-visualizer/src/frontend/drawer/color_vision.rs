@@ -0,0 +1,35 @@
+use egui::{containers::ComboBox, Ui};
+
+use crate::{rendering::wgpu::ColorVisionFilterSettings, utils::ColorVision};
+
+use super::UiDrawer;
+
+impl ColorVision {
+    fn display_name(&self) -> &'static str {
+        match self {
+            ColorVision::Normal => "Normal",
+            ColorVision::Protanopia => "Protanopia",
+            ColorVision::Deuteranopia => "Deuteranopia",
+            ColorVision::Tritanopia => "Tritanopia",
+        }
+    }
+}
+
+impl UiDrawer for ColorVisionFilterSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Color Vision Preview: ");
+        ComboBox::from_id_source("Color Vision Preview")
+            .selected_text(self.color_vision.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for color_vision in ColorVision::ALL {
+                    ui.selectable_value(
+                        &mut self.color_vision,
+                        color_vision,
+                        color_vision.display_name(),
+                    );
+                }
+            });
+        ui.end_row();
+    }
+}
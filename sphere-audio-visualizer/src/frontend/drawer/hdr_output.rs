@@ -0,0 +1,56 @@
+use egui::{Checkbox, DragValue, Ui};
+
+use crate::rendering::wgpu::HdrOutputSettings;
+
+use super::UiDrawer;
+
+impl UiDrawer for HdrOutputSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        // HDR is toggled for the monitor the window is currently on
+        let mut enabled = self
+            .monitor
+            .as_ref()
+            .map_or(false, |monitor| self.monitors.contains(monitor));
+
+        ui.label("HDR: ");
+        if ui
+            .add_enabled(
+                self.supported && self.monitor.is_some(),
+                Checkbox::new(&mut enabled, self.monitor.as_deref().unwrap_or("")),
+            )
+            .changed()
+        {
+            if let Some(monitor) = &self.monitor {
+                match enabled {
+                    true => self.monitors.push(monitor.clone()),
+                    false => self.monitors.retain(|other| other != monitor),
+                }
+            }
+        }
+        ui.end_row();
+
+        if !self.supported {
+            ui.label("Note: ");
+            ui.label("HDR output requires the DX12 backend");
+            ui.end_row();
+        }
+
+        ui.label("Paper White: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.paper_white)
+                .clamp_range(80.0..=1000.0)
+                .suffix(" nits"),
+        );
+        ui.end_row();
+
+        ui.label("Peak Brightness: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.peak_brightness)
+                .clamp_range(self.paper_white..=10000.0)
+                .suffix(" nits"),
+        );
+        ui.end_row();
+    }
+}
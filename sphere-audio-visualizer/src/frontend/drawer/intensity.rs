@@ -0,0 +1,13 @@
+use egui::{Slider, Ui};
+
+use crate::visualizer::IntensitySettings;
+
+use super::UiDrawer;
+
+impl UiDrawer for IntensitySettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Intensity: ");
+        ui.add_sized([124.0, 20.0], Slider::new(&mut self.intensity, 0.0..=2.0));
+        ui.end_row();
+    }
+}
@@ -0,0 +1,21 @@
+use egui::{DragValue, Ui};
+
+use crate::audio_analysis::LoudnessSettings;
+
+use super::UiDrawer;
+
+impl UiDrawer for LoudnessSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Loudness Threshold: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.threshold));
+        ui.end_row();
+
+        ui.label("Loudness Attack: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.attack));
+        ui.end_row();
+
+        ui.label("Loudness Release: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.release));
+        ui.end_row();
+    }
+}
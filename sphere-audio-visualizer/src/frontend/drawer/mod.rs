@@ -1,11 +1,20 @@
 use egui::Ui;
 
+mod band_mask;
+mod color_vision;
+mod hdr_output;
+mod intensity;
+mod loudness;
 mod module;
+mod publisher;
+mod radius_smoother;
 mod rendering;
 mod scene_converter;
 mod simulation_resampler;
 mod simulator;
 mod spectrum;
+mod sphere_labels;
+mod transport;
 mod visualizer;
 
 pub use self::module::*;
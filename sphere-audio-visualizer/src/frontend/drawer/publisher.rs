@@ -0,0 +1,44 @@
+use egui::{DragValue, TextEdit, Ui};
+
+use crate::visualizer::DataPublisherSettings;
+
+use super::UiDrawer;
+
+impl UiDrawer for DataPublisherSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Publish Data: ");
+        ui.checkbox(&mut self.enabled, "");
+        ui.end_row();
+
+        if self.enabled {
+            // the typed address is only applied once the text field loses
+            // focus, so partially typed addresses are not resolved
+            ui.label("Publish Address: ");
+            let address_id = ui.make_persistent_id("Data Publisher Address");
+            let mut address = ui
+                .data()
+                .get_temp::<String>(address_id)
+                .unwrap_or_else(|| self.address.clone());
+            let response = ui.add_sized(
+                [124.0, 20.0],
+                TextEdit::singleline(&mut address).hint_text("host:port"),
+            );
+            if response.lost_focus() {
+                self.address = address;
+                ui.data().remove::<String>(address_id);
+            } else if response.changed() {
+                ui.data().insert_temp(address_id, address);
+            }
+            ui.end_row();
+
+            ui.label("Beat Threshold: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.beat_threshold)
+                    .speed(0.01)
+                    .clamp_range(1.0..=4.0),
+            );
+            ui.end_row();
+        }
+    }
+}
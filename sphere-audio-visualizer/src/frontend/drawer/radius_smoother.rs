@@ -0,0 +1,25 @@
+use egui::{DragValue, Ui};
+
+use crate::{simulation::RadiusSmootherSettings, UiDrawer};
+
+impl UiDrawer for RadiusSmootherSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Radius Attack: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.attack)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Radius Decay: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.decay)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+    }
+}
@@ -1,7 +1,13 @@
-use egui::containers::ComboBox;
+use egui::{containers::ComboBox, Checkbox, Color32, DragValue, Ui};
+use sphere_audio_visualizer_core::{glam::Vec3A, utils::math::Tonemapper};
 
 use crate::rendering::wgpu::{
-    ShadingLanguage, {MetaballsSettings, RaytracerSettings},
+    ParityReport, ShaderValidation, ShadingLanguage,
+    {
+        BarsSettings, CpuRaytracerSettings, DebugViewSettings, Metaballs3DSettings,
+        MetaballsSettings, MetaballsVariant, OscilloscopeSettings, RaytracerSettings,
+        SdfTracerSettings, SphereCulling, TunnelSettings,
+    },
 };
 
 use super::UiDrawer;
@@ -15,46 +21,580 @@ impl ShadingLanguage {
     }
 }
 
-impl UiDrawer for RaytracerSettings {
-    fn ui(&mut self, ui: &mut egui::Ui) {
-        ui.label("Shading Language: ");
-        ComboBox::from_id_source("Raytracer Shading Language")
-            .selected_text(self.shading_language.display_name())
-            .width(116.0)
+impl MetaballsVariant {
+    fn display_name(&self) -> &'static str {
+        match self {
+            MetaballsVariant::Fragment => "Fragment",
+            MetaballsVariant::TiledCompute => "Tiled Compute",
+        }
+    }
+}
+
+// the tonemapper is defined in the core crate, so it can't get an inherent
+// display name
+fn tonemapper_name(tonemapper: Tonemapper) -> &'static str {
+    match tonemapper {
+        Tonemapper::Filmic => "Filmic",
+        Tonemapper::Aces => "ACES",
+        Tonemapper::Reinhard => "Reinhard",
+        Tonemapper::Linear => "Linear",
+    }
+}
+
+impl SphereCulling {
+    fn display_name(&self) -> &'static str {
+        match self {
+            SphereCulling::Disabled => "Disabled",
+            SphereCulling::Frustum => "Frustum",
+            SphereCulling::LevelOfDetail => "Level of Detail",
+        }
+    }
+}
+
+fn draw_shading_language(
+    shading_language: &mut ShadingLanguage,
+    shader_validation: &ShaderValidation,
+    id_source: &str,
+    ui: &mut Ui,
+) {
+    ui.label("Shading Language: ");
+    ui.horizontal(|ui| {
+        ComboBox::from_id_source(id_source)
+            .selected_text(shading_language.display_name())
+            .width(96.0)
             .show_ui(ui, |ui| {
+                ui.add_enabled_ui(shader_validation.passed(), |ui| {
+                    ui.selectable_value(
+                        shading_language,
+                        ShadingLanguage::Rust,
+                        ShadingLanguage::Rust.display_name(),
+                    );
+                });
                 ui.selectable_value(
-                    &mut self.shading_language,
-                    ShadingLanguage::Rust,
-                    ShadingLanguage::Rust.display_name(),
-                );
-                ui.selectable_value(
-                    &mut self.shading_language,
+                    shading_language,
                     ShadingLanguage::WGSL,
                     ShadingLanguage::WGSL.display_name(),
                 );
             });
+
+        // mirrors the status indicator of the sample sources
+        let (color, state) = match shader_validation {
+            ShaderValidation::Passed => (Color32::GREEN, "Rust shader validated".to_string()),
+            ShaderValidation::Failed(reason) => (
+                Color32::RED,
+                format!("Rust shader rejected, using WGSL\n{}", reason),
+            ),
+        };
+
+        ui.colored_label(color, "⏺").on_hover_text(state);
+    });
+    ui.end_row();
+}
+
+fn draw_parity(
+    parity: &mut bool,
+    parity_report: Option<ParityReport>,
+    rust_supported: bool,
+    ui: &mut Ui,
+) {
+    ui.label("Parity Check: ");
+    ui.add_enabled(rust_supported, Checkbox::new(parity, ""));
+    ui.end_row();
+
+    if let Some(parity_report) = parity_report {
+        ui.label("Mean Difference: ");
+        ui.label(format!("{:.4}", parity_report.mean_difference));
+        ui.end_row();
+
+        ui.label("Max Difference: ");
+        ui.label(format!("{:.4}", parity_report.max_difference));
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for RaytracerSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        draw_shading_language(
+            &mut self.shading_language,
+            &self.shader_validation,
+            "Raytracer Shading Language",
+            ui,
+        );
+
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Supersampling: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.supersampling)
+                .clamp_range(1..=4)
+                .suffix(" per axis"),
+        );
+        ui.end_row();
+
+        ui.label("Roulette Depth: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.roulette_depth)
+                .clamp_range(0..=64)
+                .suffix(" bounces"),
+        );
+        ui.end_row();
+
+        ui.label("Tonemapper: ");
+        ComboBox::from_id_source("Raytracer Tonemapper")
+            .selected_text(tonemapper_name(self.tonemapper))
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for tonemapper in Tonemapper::ALL {
+                    ui.selectable_value(
+                        &mut self.tonemapper,
+                        tonemapper,
+                        tonemapper_name(tonemapper),
+                    );
+                }
+            });
+        ui.end_row();
+
+        ui.label("Exposure: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.exposure)
+                .speed(0.05)
+                .clamp_range(-8.0..=8.0)
+                .suffix(" EV"),
+        );
+        ui.end_row();
+
+        ui.label("Contrast: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.contrast)
+                .speed(0.01)
+                .clamp_range(0.0..=4.0),
+        );
+        ui.end_row();
+
+        ui.label("Saturation: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.saturation)
+                .speed(0.01)
+                .clamp_range(0.0..=4.0),
+        );
+        ui.end_row();
+
+        ui.label("Gamma: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.gamma)
+                .speed(0.01)
+                .clamp_range(0.1..=4.0),
+        );
+        ui.end_row();
+
+        ui.label("Fog Density: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.fog_density)
+                .speed(0.001)
+                .clamp_range(0.0..=1.0),
+        );
+        ui.end_row();
+
+        if self.fog_density > 0.0 {
+            ui.label("Fog Color: ");
+            let mut fog_color = self.fog_color.to_array();
+            ui.color_edit_button_rgb(&mut fog_color);
+            self.fog_color = Vec3A::from(fog_color);
+            ui.end_row();
+
+            ui.label("Fog Distance: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.fog_distance)
+                    .speed(0.1)
+                    .clamp_range(0.0..=1000.0),
+            );
+            ui.end_row();
+
+            ui.label("Fog Steps: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.fog_steps).clamp_range(1..=128),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Accumulation: ");
+        ui.checkbox(&mut self.accumulation, "");
+        ui.end_row();
+
+        if self.accumulation {
+            ui.label("Accumulated Frames: ");
+            ui.label(self.accumulated_frames.to_string());
+            ui.end_row();
+        }
+
+        ui.label("Seed: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.seed).speed(1.0));
+        ui.end_row();
+
+        ui.label("Culling: ");
+        ComboBox::from_id_source("Raytracer Culling")
+            .selected_text(self.culling.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for culling in [
+                    SphereCulling::Disabled,
+                    SphereCulling::Frustum,
+                    SphereCulling::LevelOfDetail,
+                ] {
+                    ui.selectable_value(&mut self.culling, culling, culling.display_name());
+                }
+            });
         ui.end_row();
+
+        if self.culling == SphereCulling::LevelOfDetail {
+            ui.label("Cull Threshold: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.cull_threshold)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f32::MAX),
+            );
+            ui.end_row();
+        }
+
+        draw_parity(
+            &mut self.parity,
+            self.parity_report,
+            self.shader_validation.passed(),
+            ui,
+        );
+
+        #[cfg(feature = "statistics")]
+        {
+            // the statistics are only counted by the WGSL implementation
+            let samples = self.statistics.samples.max(1) as f32;
+
+            ui.label("Rays per Sample: ");
+            ui.label(format!("{:.2}", self.statistics.rays_cast as f32 / samples));
+            ui.end_row();
+
+            ui.label("AABB Tests per Sample: ");
+            ui.label(format!(
+                "{:.2}",
+                self.statistics.aabb_tests as f32 / samples
+            ));
+            ui.end_row();
+
+            ui.label("Shape Tests per Sample: ");
+            ui.label(format!(
+                "{:.2}",
+                self.statistics.shape_tests as f32 / samples
+            ));
+            ui.end_row();
+        }
     }
 }
 
 impl UiDrawer for MetaballsSettings {
-    fn ui(&mut self, ui: &mut egui::Ui) {
-        ui.label("Shading Language: ");
-        ComboBox::from_id_source("Metaballs Shading Language")
-            .selected_text(self.shading_language.display_name())
+    fn ui(&mut self, ui: &mut Ui) {
+        draw_shading_language(
+            &mut self.shading_language,
+            &self.shader_validation,
+            "Metaballs Shading Language",
+            ui,
+        );
+
+        ui.label("Variant: ");
+        ComboBox::from_id_source("Metaballs Variant")
+            .selected_text(self.variant.display_name())
             .width(116.0)
             .show_ui(ui, |ui| {
                 ui.selectable_value(
-                    &mut self.shading_language,
-                    ShadingLanguage::Rust,
-                    ShadingLanguage::Rust.display_name(),
+                    &mut self.variant,
+                    MetaballsVariant::Fragment,
+                    MetaballsVariant::Fragment.display_name(),
                 );
                 ui.selectable_value(
-                    &mut self.shading_language,
-                    ShadingLanguage::WGSL,
-                    ShadingLanguage::WGSL.display_name(),
+                    &mut self.variant,
+                    MetaballsVariant::TiledCompute,
+                    MetaballsVariant::TiledCompute.display_name(),
                 );
             });
         ui.end_row();
+
+        if self.variant == MetaballsVariant::TiledCompute {
+            ui.label("Cull Threshold: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.cull_threshold).speed(0.0001),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Seed: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.seed).speed(1.0));
+        ui.end_row();
+
+        if self.variant == MetaballsVariant::Fragment {
+            draw_parity(
+                &mut self.parity,
+                self.parity_report,
+                self.shader_validation.passed(),
+                ui,
+            );
+        }
+    }
+}
+
+impl UiDrawer for Metaballs3DSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        draw_shading_language(
+            &mut self.shading_language,
+            &self.shader_validation,
+            "Metaballs 3D Shading Language",
+            ui,
+        );
+
+        ui.label("Influence: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.influence)
+                .speed(0.01)
+                .clamp_range(1.1..=8.0)
+                .suffix("x"),
+        );
+        ui.end_row();
+
+        ui.label("Steps: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.steps).clamp_range(1..=512),
+        );
+        ui.end_row();
+
+        ui.label("Epsilon: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.epsilon)
+                .speed(0.0001)
+                .clamp_range(0.0001..=0.1),
+        );
+        ui.end_row();
+
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for DebugViewSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Show Spheres: ");
+        ui.checkbox(&mut self.show_spheres, "");
+        ui.end_row();
+
+        ui.label("Show Bounding Boxes: ");
+        ui.checkbox(&mut self.show_bounding_boxes, "");
+        ui.end_row();
+
+        ui.label("Show Lights: ");
+        ui.checkbox(&mut self.show_lights, "");
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for CpuRaytracerSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Supersampling: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.supersampling)
+                .clamp_range(1..=4)
+                .suffix(" per axis"),
+        );
+        ui.end_row();
+
+        ui.label("Roulette Depth: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.roulette_depth)
+                .clamp_range(0..=64)
+                .suffix(" bounces"),
+        );
+        ui.end_row();
+
+        ui.label("Tonemapper: ");
+        ComboBox::from_id_source("CPU Raytracer Tonemapper")
+            .selected_text(tonemapper_name(self.tonemapper))
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for tonemapper in Tonemapper::ALL {
+                    ui.selectable_value(
+                        &mut self.tonemapper,
+                        tonemapper,
+                        tonemapper_name(tonemapper),
+                    );
+                }
+            });
+        ui.end_row();
+
+        ui.label("Exposure: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.exposure)
+                .speed(0.05)
+                .clamp_range(-8.0..=8.0)
+                .suffix(" EV"),
+        );
+        ui.end_row();
+
+        ui.label("Seed: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.seed).speed(1.0));
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for SdfTracerSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Smoothness: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.smoothness)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Steps: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.steps).clamp_range(1..=512),
+        );
+        ui.end_row();
+
+        ui.label("Epsilon: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.epsilon)
+                .speed(0.0001)
+                .clamp_range(0.0001..=0.1),
+        );
+        ui.end_row();
+
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for BarsSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Peak Height: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.peak_height)
+                .speed(0.001)
+                .clamp_range(0.0..=1.0),
+        );
+        ui.end_row();
+
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for OscilloscopeSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Line Width: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.line_width)
+                .speed(0.1)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Glow: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.glow)
+                .speed(0.1)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for TunnelSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Steps: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.steps).clamp_range(1..=512),
+        );
+        ui.end_row();
+
+        ui.label("Pulse: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.pulse)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
     }
 }
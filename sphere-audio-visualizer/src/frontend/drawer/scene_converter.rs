@@ -1,13 +1,876 @@
-use egui::Ui;
+use std::path::PathBuf;
 
-use crate::rendering::{MetaballsSceneConverterSettings, RaytracerSceneConverterSettings};
+use egui::{containers::ComboBox, Color32, DragValue, TextEdit, Ui};
+
+use sphere_audio_visualizer_core::glam::{Vec3, Vec3A};
+
+use crate::{
+    rendering::{
+        AspectRatio, BarChannels, BarsSceneConverterSettings, FloorTexture, LevelOfDetail,
+        Metaballs3DSceneConverterSettings, MetaballsSceneConverterSettings, OscilloscopeMode,
+        OscilloscopeSceneConverterSettings, Projection, RaytracerSceneConverterSettings,
+        SphereColoring, SphereMaterial, TunnelSceneConverterSettings, ZoomModulation,
+    },
+    utils::Palette,
+};
 
 use super::UiDrawer;
 
+impl FloorTexture {
+    fn display_name(&self) -> &'static str {
+        match self {
+            FloorTexture::None => "None",
+            FloorTexture::Checkerboard => "Checkerboard",
+            FloorTexture::Spectrogram => "Spectrogram",
+            FloorTexture::Image => "Image",
+        }
+    }
+}
+
+impl LevelOfDetail {
+    fn display_name(&self) -> &'static str {
+        match self {
+            LevelOfDetail::Disabled => "Disabled",
+            LevelOfDetail::Drop => "Drop",
+            LevelOfDetail::Merge => "Merge",
+        }
+    }
+}
+
+impl Projection {
+    fn display_name(&self) -> &'static str {
+        match self {
+            Projection::Perspective => "Perspective",
+            Projection::Panoramic => "Panoramic (360°)",
+        }
+    }
+}
+
+impl Palette {
+    fn display_name(&self) -> &'static str {
+        match self {
+            Palette::Classic => "Classic",
+            Palette::Viridis => "Viridis",
+            Palette::Cividis => "Cividis",
+            Palette::Magma => "Magma",
+            Palette::Inferno => "Inferno",
+        }
+    }
+}
+
+impl SphereColoring {
+    fn display_name(&self) -> &'static str {
+        match self {
+            SphereColoring::Material => "Material",
+            SphereColoring::PeakRecency => "Peak Recency",
+        }
+    }
+}
+
+impl BarChannels {
+    fn display_name(&self) -> &'static str {
+        match self {
+            BarChannels::Mono => "Mono",
+            BarChannels::Stereo => "Stereo",
+        }
+    }
+}
+
+impl ZoomModulation {
+    fn display_name(&self) -> &'static str {
+        match self {
+            ZoomModulation::Static => "Static",
+            ZoomModulation::Level => "Level",
+            ZoomModulation::Bass => "Bass",
+        }
+    }
+}
+
+impl OscilloscopeMode {
+    fn display_name(&self) -> &'static str {
+        match self {
+            OscilloscopeMode::Waveform => "Waveform",
+            OscilloscopeMode::Lissajous => "Lissajous",
+        }
+    }
+}
+
+/// Defines the locked aspect ratios offered in the UI, including the
+/// vertical and square formats of social media
+const ASPECT_RATIO_PRESETS: [AspectRatio; 5] = [
+    AspectRatio::Locked(16, 9),
+    AspectRatio::Locked(21, 9),
+    AspectRatio::Locked(1, 1),
+    AspectRatio::Locked(4, 5),
+    AspectRatio::Locked(9, 16),
+];
+
+impl AspectRatio {
+    fn display_name(&self) -> &'static str {
+        match self {
+            AspectRatio::Free => "Free",
+            AspectRatio::Locked(16, 9) => "16:9",
+            AspectRatio::Locked(21, 9) => "21:9",
+            AspectRatio::Locked(1, 1) => "1:1",
+            AspectRatio::Locked(4, 5) => "4:5 (Portrait)",
+            AspectRatio::Locked(9, 16) => "9:16 (Vertical)",
+            AspectRatio::Locked(_, _) => "Custom",
+        }
+    }
+}
+
+fn aspect_ratio_ui(ui: &mut Ui, id_source: &str, aspect_ratio: &mut AspectRatio) {
+    ui.label("Aspect Ratio: ");
+    ComboBox::from_id_source(id_source)
+        .selected_text(aspect_ratio.display_name())
+        .width(116.0)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(aspect_ratio, AspectRatio::Free, "Free");
+
+            // other ratios are entered below once the ratio is locked
+            for preset in ASPECT_RATIO_PRESETS {
+                ui.selectable_value(aspect_ratio, preset, preset.display_name());
+            }
+        });
+    ui.end_row();
+
+    if let AspectRatio::Locked(width, height) = aspect_ratio {
+        ui.label("Aspect Width: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(width).clamp_range(1..=u32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Aspect Height: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(height).clamp_range(1..=u32::MAX),
+        );
+        ui.end_row();
+    }
+}
+
 impl UiDrawer for MetaballsSceneConverterSettings {
-    fn ui(&mut self, _ui: &mut Ui) {}
+    fn ui(&mut self, ui: &mut Ui) {
+        aspect_ratio_ui(ui, "Metaballs Aspect Ratio", &mut self.aspect_ratio);
+
+        ui.label("Zoom Modulation: ");
+        ComboBox::from_id_source("Metaballs Zoom Modulation")
+            .selected_text(self.zoom_modulation.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for zoom_modulation in [
+                    ZoomModulation::Static,
+                    ZoomModulation::Level,
+                    ZoomModulation::Bass,
+                ] {
+                    ui.selectable_value(
+                        &mut self.zoom_modulation,
+                        zoom_modulation,
+                        zoom_modulation.display_name(),
+                    );
+                }
+            });
+        ui.end_row();
+
+        if self.zoom_modulation != ZoomModulation::Static {
+            ui.label("Zoom Range: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.zoom_range)
+                    .speed(0.01)
+                    .clamp_range(-1.0..=0.9),
+            );
+            ui.end_row();
+
+            ui.label("Zoom Smoothing: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.zoom_smoothing)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f32::MAX),
+            );
+            ui.end_row();
+        }
+    }
+}
+
+impl UiDrawer for Metaballs3DSceneConverterSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        aspect_ratio_ui(ui, "Metaballs 3D Aspect Ratio", &mut self.aspect_ratio);
+
+        ui.label("Palette: ");
+        ComboBox::from_id_source("Metaballs 3D Palette")
+            .selected_text(self.palette.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for palette in Palette::ALL {
+                    ui.selectable_value(&mut self.palette, palette, palette.display_name());
+                }
+            });
+        ui.end_row();
+
+        ui.label("Background Intensity: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.background_intensity)
+                .speed(0.01)
+                .clamp_range(0.0..=100.0),
+        );
+        ui.end_row();
+    }
 }
 
 impl UiDrawer for RaytracerSceneConverterSettings {
-    fn ui(&mut self, _ui: &mut Ui) {}
+    fn ui(&mut self, ui: &mut Ui) {
+        aspect_ratio_ui(ui, "Raytracer Aspect Ratio", &mut self.aspect_ratio);
+
+        ui.label("Bounces: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.bounces).clamp_range(1..=64),
+        );
+        ui.end_row();
+
+        ui.label("Projection: ");
+        ComboBox::from_id_source("Raytracer Projection")
+            .selected_text(self.projection.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for projection in [Projection::Perspective, Projection::Panoramic] {
+                    ui.selectable_value(
+                        &mut self.projection,
+                        projection,
+                        projection.display_name(),
+                    );
+                }
+            });
+        ui.end_row();
+
+        ui.label("Near Clip: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.t_min)
+                .speed(0.0001)
+                .clamp_range(0.0..=self.t_max),
+        );
+        ui.end_row();
+
+        ui.label("Far Clip: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.t_max).clamp_range(self.t_min..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Level of Detail: ");
+        ComboBox::from_id_source("Raytracer Level of Detail")
+            .selected_text(self.level_of_detail.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for level_of_detail in [
+                    LevelOfDetail::Disabled,
+                    LevelOfDetail::Drop,
+                    LevelOfDetail::Merge,
+                ] {
+                    ui.selectable_value(
+                        &mut self.level_of_detail,
+                        level_of_detail,
+                        level_of_detail.display_name(),
+                    );
+                }
+            });
+        ui.end_row();
+
+        if self.level_of_detail != LevelOfDetail::Disabled {
+            ui.label("LOD Threshold: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.lod_threshold)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f32::MAX),
+            );
+            ui.end_row();
+        }
+
+        ui.label("BVH: ");
+        ui.checkbox(&mut self.bvh, "");
+        ui.end_row();
+
+        ui.label("Light Count: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.light_count).clamp_range(1..=16),
+        );
+        ui.end_row();
+
+        ui.label("Cycle Light Colors: ");
+        ui.checkbox(&mut self.cycle_lights, "");
+        ui.end_row();
+
+        if self.cycle_lights {
+            ui.label("Light Cycle Speed: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.light_cycle_speed).speed(0.001),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Sun Intensity: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.sun_intensity)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        if self.sun_intensity > 0.0 {
+            ui.label("Sun Elevation: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.sun_elevation)
+                    .clamp_range(-90.0..=90.0)
+                    .suffix("°"),
+            );
+            ui.end_row();
+
+            ui.label("Sun Azimuth: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.sun_azimuth)
+                    .clamp_range(-180.0..=180.0)
+                    .suffix("°"),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Palette: ");
+        ComboBox::from_id_source("Raytracer Palette")
+            .selected_text(self.palette.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for palette in Palette::ALL {
+                    ui.selectable_value(&mut self.palette, palette, palette.display_name());
+                }
+            });
+        ui.end_row();
+
+        ui.label("Color-Blind Safe: ");
+        if self.palette.gradient().is_color_blind_safe() {
+            ui.label("Yes");
+        } else {
+            ui.colored_label(Color32::YELLOW, "No");
+        }
+        ui.end_row();
+
+        ui.label("Sphere Coloring: ");
+        ComboBox::from_id_source("Raytracer Sphere Coloring")
+            .selected_text(self.sphere_coloring.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for sphere_coloring in [SphereColoring::Material, SphereColoring::PeakRecency] {
+                    ui.selectable_value(
+                        &mut self.sphere_coloring,
+                        sphere_coloring,
+                        sphere_coloring.display_name(),
+                    );
+                }
+            });
+        ui.end_row();
+
+        if self.sphere_coloring == SphereColoring::PeakRecency {
+            ui.label("Peak Cooldown: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.peak_cooldown)
+                    .speed(0.01)
+                    .clamp_range(0.001..=f32::MAX),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Level Emission: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.level_emission)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        if self.level_emission > 0.0 {
+            ui.label("Level Emission Gamma: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.level_emission_gamma).speed(0.01),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Ripple Amplitude: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.ripple_amplitude).speed(0.001),
+        );
+        ui.end_row();
+
+        if self.ripple_amplitude != 0.0 {
+            ui.label("Ripple Frequency: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.ripple_frequency).speed(0.1),
+            );
+            ui.end_row();
+
+            ui.label("Ripple Speed: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.ripple_speed).speed(0.1),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Particles: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.particles).clamp_range(0..=1024),
+        );
+        ui.end_row();
+
+        if self.particles > 0 {
+            ui.label("Particle Size: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.particle_size)
+                    .speed(0.001)
+                    .clamp_range(0.0..=1.0),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Shutter: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.shutter)
+                .speed(0.001)
+                .clamp_range(0.0..=0.1)
+                .suffix(" s"),
+        );
+        ui.end_row();
+
+        // the typed path is kept in the egui memory and only applied once
+        // the text field loses focus, so the file is not loaded on every key
+        ui.label("Mesh: ");
+        let mesh_path_id = ui.make_persistent_id("Raytracer Mesh Path");
+        let mut mesh_path = ui
+            .data()
+            .get_temp::<String>(mesh_path_id)
+            .unwrap_or_else(|| {
+                self.mesh_path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default()
+            });
+        let response = ui.add_sized(
+            [124.0, 20.0],
+            TextEdit::singleline(&mut mesh_path).hint_text("OBJ file"),
+        );
+        if response.lost_focus() {
+            self.mesh_path = (!mesh_path.is_empty()).then(|| PathBuf::from(&mesh_path));
+            ui.data().remove::<String>(mesh_path_id);
+        } else if response.changed() {
+            ui.data().insert_temp(mesh_path_id, mesh_path);
+        }
+        ui.end_row();
+
+        if self.mesh_path.is_some() {
+            ui.label("Mesh Scale: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.mesh_scale)
+                    .speed(0.01)
+                    .clamp_range(0.0..=100.0),
+            );
+            ui.end_row();
+
+            ui.label("Mesh Distance: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.mesh_distance)
+                    .speed(0.1)
+                    .clamp_range(-100.0..=100.0),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Environment Map: ");
+        let environment_map_path_id = ui.make_persistent_id("Raytracer Environment Map Path");
+        let mut environment_map_path = ui
+            .data()
+            .get_temp::<String>(environment_map_path_id)
+            .unwrap_or_else(|| {
+                self.environment_map_path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default()
+            });
+        let response = ui.add_sized(
+            [124.0, 20.0],
+            TextEdit::singleline(&mut environment_map_path).hint_text("HDR file"),
+        );
+        if response.lost_focus() {
+            self.environment_map_path =
+                (!environment_map_path.is_empty()).then(|| PathBuf::from(&environment_map_path));
+            ui.data().remove::<String>(environment_map_path_id);
+        } else if response.changed() {
+            ui.data()
+                .insert_temp(environment_map_path_id, environment_map_path);
+        }
+        ui.end_row();
+
+        ui.label("Background Intensity: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.background_intensity)
+                .speed(0.01)
+                .clamp_range(0.0..=100.0),
+        );
+        ui.end_row();
+
+        if self.environment_map_path.is_some() {
+            ui.label("Background Rotation: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.background_rotation)
+                    .speed(1.0)
+                    .clamp_range(-180.0..=180.0)
+                    .suffix("°"),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Floor Texture: ");
+        ComboBox::from_id_source("Raytracer Floor Texture")
+            .selected_text(self.floor_texture.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for floor_texture in [
+                    FloorTexture::None,
+                    FloorTexture::Checkerboard,
+                    FloorTexture::Spectrogram,
+                    FloorTexture::Image,
+                ] {
+                    ui.selectable_value(
+                        &mut self.floor_texture,
+                        floor_texture,
+                        floor_texture.display_name(),
+                    );
+                }
+            });
+        ui.end_row();
+
+        if self.floor_texture == FloorTexture::Image {
+            ui.label("Floor Image: ");
+            let floor_texture_path_id = ui.make_persistent_id("Raytracer Floor Texture Path");
+            let mut floor_texture_path = ui
+                .data()
+                .get_temp::<String>(floor_texture_path_id)
+                .unwrap_or_else(|| {
+                    self.floor_texture_path
+                        .as_ref()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_default()
+                });
+            let response = ui.add_sized(
+                [124.0, 20.0],
+                TextEdit::singleline(&mut floor_texture_path).hint_text("HDR file"),
+            );
+            if response.lost_focus() {
+                self.floor_texture_path =
+                    (!floor_texture_path.is_empty()).then(|| PathBuf::from(&floor_texture_path));
+                ui.data().remove::<String>(floor_texture_path_id);
+            } else if response.changed() {
+                ui.data()
+                    .insert_temp(floor_texture_path_id, floor_texture_path);
+            }
+            ui.end_row();
+        }
+
+        ui.label("Normal Map: ");
+        let normal_map_path_id = ui.make_persistent_id("Raytracer Normal Map Path");
+        let mut normal_map_path = ui
+            .data()
+            .get_temp::<String>(normal_map_path_id)
+            .unwrap_or_else(|| {
+                self.normal_map_path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default()
+            });
+        let response = ui.add_sized(
+            [124.0, 20.0],
+            TextEdit::singleline(&mut normal_map_path).hint_text("HDR file or tiles"),
+        );
+        if response.lost_focus() {
+            self.normal_map_path =
+                (!normal_map_path.is_empty()).then(|| PathBuf::from(&normal_map_path));
+            ui.data().remove::<String>(normal_map_path_id);
+        } else if response.changed() {
+            ui.data().insert_temp(normal_map_path_id, normal_map_path);
+        }
+        ui.end_row();
+
+        ui.label("Floor Normal Strength: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.floor_normal_strength)
+                .speed(0.01)
+                .clamp_range(0.0..=10.0),
+        );
+        ui.end_row();
+
+        ui.label("Sphere Normal Strength: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.sphere_normal_strength)
+                .speed(0.01)
+                .clamp_range(0.0..=10.0),
+        );
+        ui.end_row();
+
+        // the floor normal map uses the texture coordinates of the texture
+        if self.floor_texture != FloorTexture::None || self.floor_normal_strength > 0.0 {
+            ui.label("Texture Scale: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.floor_texture_scale)
+                    .speed(0.01)
+                    .clamp_range(0.0..=100.0),
+            );
+            ui.end_row();
+        }
+
+        let mut removed = None;
+        for (index, material) in self.materials.iter_mut().enumerate() {
+            ui.label(format!("Material {}: ", index + 1));
+            if ui.button("x").clicked() {
+                removed = Some(index);
+            }
+            ui.end_row();
+
+            ui.label("Color: ");
+            let mut color = material.color.to_array();
+            ui.color_edit_button_rgb(&mut color);
+            material.color = Vec3::from(color);
+            ui.end_row();
+
+            ui.label("Emission: ");
+            let mut emission = material.emission.to_array();
+            ui.color_edit_button_rgb(&mut emission);
+            material.emission = Vec3::from(emission);
+            ui.end_row();
+
+            ui.label("IOR: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut material.ior)
+                    .speed(0.01)
+                    .clamp_range(1.0..=f32::MAX),
+            );
+            ui.end_row();
+
+            ui.label("Roughness: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut material.roughness)
+                    .speed(0.01)
+                    .clamp_range(0.0..=1.0),
+            );
+            ui.end_row();
+
+            ui.label("Metallic: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut material.metallic)
+                    .speed(0.01)
+                    .clamp_range(0.0..=1.0),
+            );
+            ui.end_row();
+
+            ui.label("Transmission: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut material.transmission)
+                    .speed(0.01)
+                    .clamp_range(0.0..=1.0),
+            );
+            ui.end_row();
+        }
+
+        if let Some(index) = removed {
+            self.materials.remove(index);
+        }
+
+        ui.label("");
+        if ui.button("Add Material").clicked() {
+            self.materials.push(SphereMaterial::default());
+        }
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for BarsSceneConverterSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        aspect_ratio_ui(ui, "Bars Aspect Ratio", &mut self.aspect_ratio);
+
+        ui.label("Channels: ");
+        ComboBox::from_id_source("Bars Channels")
+            .selected_text(self.channels.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for channels in [BarChannels::Mono, BarChannels::Stereo] {
+                    ui.selectable_value(&mut self.channels, channels, channels.display_name());
+                }
+            });
+        ui.end_row();
+
+        ui.label("Gap: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.gap)
+                .speed(0.01)
+                .clamp_range(0.0..=1.0),
+        );
+        ui.end_row();
+
+        ui.label("Low Color: ");
+        let mut low_color = self.low_color.to_array();
+        ui.color_edit_button_rgb(&mut low_color);
+        self.low_color = Vec3A::from(low_color);
+        ui.end_row();
+
+        ui.label("High Color: ");
+        let mut high_color = self.high_color.to_array();
+        ui.color_edit_button_rgb(&mut high_color);
+        self.high_color = Vec3A::from(high_color);
+        ui.end_row();
+
+        ui.label("Peak Hold: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.peak_hold)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Peak Fall: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.peak_fall)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for OscilloscopeSceneConverterSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        aspect_ratio_ui(ui, "Oscilloscope Aspect Ratio", &mut self.aspect_ratio);
+
+        ui.label("Mode: ");
+        ComboBox::from_id_source("Oscilloscope Mode")
+            .selected_text(self.mode.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for mode in [OscilloscopeMode::Waveform, OscilloscopeMode::Lissajous] {
+                    ui.selectable_value(&mut self.mode, mode, mode.display_name());
+                }
+            });
+        ui.end_row();
+
+        if self.mode == OscilloscopeMode::Lissajous {
+            ui.label("Lissajous Delay: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.lissajous_delay)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f32::MAX),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Gain: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.gain).speed(0.01));
+        ui.end_row();
+
+        ui.label("Color: ");
+        let mut color = self.color.to_array();
+        ui.color_edit_button_rgb(&mut color);
+        self.color = Vec3A::from(color);
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for TunnelSceneConverterSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        aspect_ratio_ui(ui, "Tunnel Aspect Ratio", &mut self.aspect_ratio);
+
+        ui.label("Speed: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.speed)
+                .speed(0.1)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Radius: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.radius)
+                .speed(0.01)
+                .clamp_range(0.01..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Displacement: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.displacement).speed(0.01),
+        );
+        ui.end_row();
+
+        ui.label("Ring Spacing: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.ring_spacing)
+                .speed(0.01)
+                .clamp_range(0.01..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Twist: ");
+        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.twist).speed(0.001));
+        ui.end_row();
+
+        ui.label("Fog: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.fog)
+                .speed(0.001)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
+
+        ui.label("Color: ");
+        let mut color = self.color.to_array();
+        ui.color_edit_button_rgb(&mut color);
+        self.color = Vec3A::from(color);
+        ui.end_row();
+    }
 }
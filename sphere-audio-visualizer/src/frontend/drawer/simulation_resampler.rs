@@ -1,11 +1,83 @@
-use egui::{DragValue, Ui};
+use egui::{ComboBox, DragValue, Ui};
 
-use crate::{simulation::SimulationResamplerSettings, UiDrawer};
+use crate::{
+    simulation::{IngestionPolicy, SimulationResamplerSettings},
+    UiDrawer,
+};
+
+impl IngestionPolicy {
+    fn display_name(&self) -> &'static str {
+        match self {
+            IngestionPolicy::ProcessAll => "Process All",
+            IngestionPolicy::DropOldest => "Drop Oldest",
+        }
+    }
+}
 
 impl UiDrawer for SimulationResamplerSettings {
     fn ui(&mut self, ui: &mut Ui) {
+        // previews and exports share the framerate, so the motion looks the
+        // same in both
         ui.label("Simulator Frame Rate: ");
-        ui.add_sized([124.0, 20.0], DragValue::new(&mut self.simulator_framerate));
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.simulator_framerate)
+                .clamp_range(30.0..=1000.0)
+                .suffix(" Hz"),
+        );
+        ui.end_row();
+
+        ui.label("Max Frame Time: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.max_frame_time)
+                .speed(0.01)
+                .clamp_range(0.0..=1.0)
+                .suffix(" s"),
+        );
+        ui.end_row();
+
+        ui.label("Max Steps Per Frame: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.max_steps_per_frame).clamp_range(1..=1000),
+        );
+        ui.end_row();
+
+        ui.label("Frame Time Smoothing: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.frame_time_smoothing)
+                .speed(0.01)
+                .clamp_range(0.0..=0.99),
+        );
         ui.end_row();
+
+        ui.label("Ingestion Policy: ");
+        ComboBox::from_id_source("Simulation Resampler Ingestion Policy")
+            .selected_text(self.ingestion_policy.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for ingestion_policy in [IngestionPolicy::ProcessAll, IngestionPolicy::DropOldest] {
+                    ui.selectable_value(
+                        &mut self.ingestion_policy,
+                        ingestion_policy,
+                        ingestion_policy.display_name(),
+                    );
+                }
+            });
+        ui.end_row();
+
+        if self.ingestion_policy == IngestionPolicy::DropOldest {
+            ui.label("Max Batch Time: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.max_batch_time)
+                    .speed(0.01)
+                    .clamp_range(0.0..=10.0)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        }
     }
 }
@@ -1,13 +1,136 @@
-use egui::widgets::DragValue;
+use egui::{containers::ComboBox, widgets::DragValue};
 
-use crate::simulation::SimulationSettings;
+use crate::simulation::{
+    BandOrder, Layout, PassthroughSettings, SimulationSettings, WaveformSimulationSettings,
+};
 
 use super::UiDrawer;
 
+impl BandOrder {
+    fn display_name(&self) -> &'static str {
+        match self {
+            BandOrder::LeftToRight => "Left to Right",
+            BandOrder::BassCenter => "Bass Center",
+            BandOrder::TrebleCenter => "Treble Center",
+        }
+    }
+}
+
+impl Layout {
+    fn display_name(&self) -> &'static str {
+        match self {
+            Layout::Line => "Line",
+            Layout::Orbit => "Orbit",
+        }
+    }
+}
+
 impl UiDrawer for SimulationSettings {
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.label("Min Radius: ");
         ui.add_sized([124.0, 20.0], DragValue::new(&mut self.min_radius));
         ui.end_row();
+
+        ui.label("Layout: ");
+        ComboBox::from_id_source("Simulation Layout")
+            .selected_text(self.layout.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for layout in [Layout::Line, Layout::Orbit] {
+                    ui.selectable_value(&mut self.layout, layout, layout.display_name());
+                }
+            });
+        ui.end_row();
+
+        if self.layout == Layout::Line {
+            ui.label("Band Order: ");
+            ComboBox::from_id_source("Simulation Band Order")
+                .selected_text(self.band_order.display_name())
+                .width(116.0)
+                .show_ui(ui, |ui| {
+                    for band_order in [
+                        BandOrder::LeftToRight,
+                        BandOrder::BassCenter,
+                        BandOrder::TrebleCenter,
+                    ] {
+                        ui.selectable_value(
+                            &mut self.band_order,
+                            band_order,
+                            band_order.display_name(),
+                        );
+                    }
+                });
+            ui.end_row();
+        }
+
+        if self.layout == Layout::Orbit {
+            ui.label("Orbit Speed: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.orbit_speed).speed(0.01),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Wobble Amplitude: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.wobble_amplitude).speed(0.01),
+        );
+        ui.end_row();
+
+        ui.label("Wobble Frequency: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.wobble_frequency).speed(0.01),
+        );
+        ui.end_row();
+
+        ui.label("Turbulence Strength: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.turbulence_strength).speed(0.1),
+        );
+        ui.end_row();
+
+        ui.label("Turbulence Scale: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.turbulence_scale).speed(0.01),
+        );
+        ui.end_row();
+
+        ui.label("Turbulence Speed: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.turbulence_speed).speed(0.01),
+        );
+        ui.end_row();
+    }
+}
+
+impl UiDrawer for PassthroughSettings {
+    fn ui(&mut self, _ui: &mut egui::Ui) {}
+}
+
+impl UiDrawer for WaveformSimulationSettings {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Window: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.window)
+                .speed(0.001)
+                .clamp_range(0.001..=1.0),
+        );
+        ui.end_row();
+
+        ui.label("Persistence: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.persistence)
+                .speed(0.01)
+                .clamp_range(0.0..=f32::MAX),
+        );
+        ui.end_row();
     }
 }
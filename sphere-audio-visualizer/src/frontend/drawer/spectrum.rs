@@ -1,9 +1,18 @@
-use egui::{DragValue, Ui};
+use egui::{containers::ComboBox, DragValue, Ui};
 
-use crate::audio_analysis::SpectrumSettings;
+use crate::audio_analysis::{AnalysisMode, SpectrumSettings};
 
 use super::UiDrawer;
 
+impl AnalysisMode {
+    fn display_name(&self) -> &'static str {
+        match self {
+            AnalysisMode::Serial => "Serial",
+            AnalysisMode::Parallel => "Parallel",
+        }
+    }
+}
+
 impl UiDrawer for SpectrumSettings {
     fn ui(&mut self, ui: &mut Ui) {
         ui.label("Count: ");
@@ -29,5 +38,25 @@ impl UiDrawer for SpectrumSettings {
         ui.label("Release: ");
         ui.add_sized([124.0, 20.0], DragValue::new(&mut self.release));
         ui.end_row();
+
+        ui.label("Analysis Mode: ");
+        ComboBox::from_id_source("Spectrum Analysis Mode")
+            .selected_text(self.mode.display_name())
+            .width(116.0)
+            .show_ui(ui, |ui| {
+                for mode in [AnalysisMode::Serial, AnalysisMode::Parallel] {
+                    ui.selectable_value(&mut self.mode, mode, mode.display_name());
+                }
+            });
+        ui.end_row();
+
+        if self.mode == AnalysisMode::Parallel {
+            ui.label("Analysis Threads: ");
+            ui.add_sized(
+                [124.0, 20.0],
+                DragValue::new(&mut self.threads).clamp_range(0..=256),
+            );
+            ui.end_row();
+        }
     }
 }
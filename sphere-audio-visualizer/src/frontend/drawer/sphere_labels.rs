@@ -0,0 +1,45 @@
+use egui::{vec2, Align2, Color32, FontId, Id, LayerId, Order, Pos2, Ui};
+
+use crate::visualizer::{SphereLabels, SphereLabelsSettings};
+
+use super::UiDrawer;
+
+impl UiDrawer for SphereLabelsSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Sphere Labels: ");
+        ui.checkbox(&mut self.enabled, "");
+        ui.end_row();
+    }
+}
+
+fn format_frequency(frequency: f32) -> String {
+    match frequency >= 1000.0 {
+        true => format!("{:.1} kHz", frequency / 1000.0),
+        false => format!("{:.0} Hz", frequency),
+    }
+}
+
+/// Paints the labels of the spheres behind every window. The labels are
+/// projected in pixels, while egui paints in points.
+pub(super) fn paint_sphere_labels(sphere_labels: &SphereLabels, ui: &Ui) {
+    if !sphere_labels.is_enabled() {
+        return;
+    }
+
+    let pixels_per_point = ui.ctx().pixels_per_point();
+    let painter = ui
+        .ctx()
+        .layer_painter(LayerId::new(Order::Background, Id::new("Sphere Labels")));
+
+    for label in sphere_labels.labels() {
+        let position = label.position / pixels_per_point;
+
+        painter.text(
+            Pos2::new(position.x, position.y) + vec2(4.0, -4.0),
+            Align2::LEFT_BOTTOM,
+            format!("{}\n{:.2}", format_frequency(label.frequency), label.level),
+            FontId::monospace(12.0),
+            Color32::WHITE,
+        );
+    }
+}
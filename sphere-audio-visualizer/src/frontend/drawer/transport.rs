@@ -0,0 +1,32 @@
+use egui::{Button, DragValue, Ui};
+
+use crate::visualizer::TransportSettings;
+
+use super::UiDrawer;
+
+impl UiDrawer for TransportSettings {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Paused: ");
+        ui.checkbox(&mut self.paused, "");
+        ui.end_row();
+
+        ui.label("Step: ");
+        if ui
+            .add_enabled(self.paused, Button::new("Next Frame"))
+            .clicked()
+        {
+            self.steps += 1;
+        }
+        ui.end_row();
+
+        ui.label("Speed: ");
+        ui.add_sized(
+            [124.0, 20.0],
+            DragValue::new(&mut self.speed)
+                .speed(0.01)
+                .clamp_range(0.0..=4.0)
+                .suffix("x"),
+        );
+        ui.end_row();
+    }
+}
@@ -1,4 +1,4 @@
-use egui::Ui;
+use egui::{Color32, ProgressBar, Ui};
 
 use crate::{
     module::Module,
@@ -7,10 +7,23 @@ use crate::{
         SceneConverter,
     },
     simulation::Simulator,
-    visualizer::WGPUVisualizer,
+    visualizer::{ProfileStage, WGPUVisualizer},
 };
 
-use super::{module::draw_module, UiDrawer};
+use super::{module::draw_module, sphere_labels::paint_sphere_labels, UiDrawer};
+
+impl ProfileStage {
+    fn display_name(&self) -> &'static str {
+        match self {
+            ProfileStage::Spectrum => "Spectrum",
+            ProfileStage::Resampler => "Resampler",
+            ProfileStage::Simulator => "Simulator",
+            ProfileStage::Converter => "Converter",
+            ProfileStage::Render => "Render",
+            ProfileStage::Present => "Present",
+        }
+    }
+}
 
 impl<S, SC, P, T> UiDrawer for WGPUVisualizer<S, SC, P, T>
 where
@@ -23,9 +36,60 @@ where
     <P as Module>::Settings: UiDrawer,
 {
     fn ui(&mut self, ui: &mut Ui) {
+        draw_module(&mut self.intensity, ui);
         draw_module(&mut self.spectrum, ui);
+        draw_module(&mut self.loudness, ui);
+        draw_module(&mut self.band_mask, ui);
+        draw_module(&mut self.radius_smoother, ui);
+        draw_module(&mut self.simulation_resampler, ui);
         draw_module(&mut self.simulator, ui);
         draw_module(&mut self.scene_converter, ui);
         draw_module(&mut self.pipeline, ui);
+        draw_module(&mut self.color_vision_filter, ui);
+        draw_module(&mut self.hdr_output, ui);
+        draw_module(&mut self.sphere_labels, ui);
+        draw_module(&mut self.transport, ui);
+        draw_module(&mut self.data_publisher, ui);
+
+        paint_sphere_labels(&self.sphere_labels, ui);
+
+        let memory_tracker = self.renderer.memory_tracker();
+
+        ui.label("GPU Memory: ");
+        ui.label(format!(
+            "{:.1} / {:.1} MiB",
+            memory_tracker.usage().total() as f64 / (1 << 20) as f64,
+            memory_tracker.budget() as f64 / (1 << 20) as f64,
+        ));
+        ui.end_row();
+
+        for warning in memory_tracker.warnings() {
+            ui.label("Warning: ");
+            ui.colored_label(Color32::YELLOW, warning);
+            ui.end_row();
+        }
+
+        let total_time = self.profiler.total_time();
+
+        ui.label("Frame Time: ");
+        ui.label(format!("{:.2} ms", total_time * 1000.0));
+        ui.end_row();
+
+        // the bars show the share of each stage, so the most expensive
+        // settings are easy to spot
+        for stage in ProfileStage::ALL {
+            let time = self.profiler.time(stage);
+            let share = match total_time > 0.0 {
+                true => (time / total_time) as f32,
+                false => 0.0,
+            };
+
+            ui.label(format!("{}: ", stage.display_name()));
+            ui.add_sized(
+                [124.0, 20.0],
+                ProgressBar::new(share).text(format!("{:.2} ms", time * 1000.0)),
+            );
+            ui.end_row();
+        }
     }
 }
@@ -0,0 +1,174 @@
+use std::time::Instant;
+
+use gilrs::{Axis, Button, Gilrs};
+use sphere_audio_visualizer_core::glam::{vec2, vec3, Mat3, Vec2};
+
+use crate::rendering::CameraControl;
+
+/// Defines the stick deflection below which the sticks are considered
+/// released
+const GAMEPAD_DEADZONE: f32 = 0.15;
+
+/// Defines the speed of the camera in scene units per second
+const GAMEPAD_MOVE_SPEED: f32 = 4.0;
+
+/// Defines the rotation speed of the camera in radians per second
+const GAMEPAD_LOOK_SPEED: f32 = 1.5;
+
+/// Defines the factor per second the zoom changes by
+const GAMEPAD_ZOOM_SPEED: f32 = 2.0;
+
+/// Defines the maximal pitch of the camera in radians
+const GAMEPAD_MAX_PITCH: f32 = 1.5;
+
+/// Defines the time in seconds without input after which the camera drops
+/// back to the camera of the scene converter
+const GAMEPAD_RELEASE_DELAY: f32 = 2.0;
+
+/// Defines the time in seconds the camera takes to drop back to the camera
+/// of the scene converter
+const GAMEPAD_RETURN_TIME: f32 = 0.5;
+
+/// The state of the inputs of all connected gamepads
+struct GamepadInput {
+    movement: Vec2,
+    look: Vec2,
+    zoom: f32,
+    reset: bool,
+}
+
+impl GamepadInput {
+    fn is_active(&self) -> bool {
+        self.movement != Vec2::ZERO || self.look != Vec2::ZERO || self.zoom != 0.0
+    }
+}
+
+fn deadzone(value: f32) -> f32 {
+    match value.abs() > GAMEPAD_DEADZONE {
+        true => value,
+        false => 0.0,
+    }
+}
+
+/// Flies the camera of the visualizer with a gamepad during a live
+/// performance. The left stick moves the camera or pans 2D scenes, the right
+/// stick looks around and the triggers zoom. The camera drops back smoothly
+/// once the gamepad is released, while the south button resets it
+/// immediately.
+pub struct GamepadCamera {
+    gilrs: Gilrs,
+    camera_control: CameraControl,
+    last_update: Instant,
+    last_input: Option<Instant>,
+}
+
+impl GamepadCamera {
+    /// Creates a new instance. Fails if the gamepads of the platform can't be
+    /// accessed.
+    pub fn new() -> Result<Self, gilrs::Error> {
+        Ok(Self {
+            gilrs: Gilrs::new()?,
+            camera_control: CameraControl::default(),
+            last_update: Instant::now(),
+            last_input: None,
+        })
+    }
+
+    /// Returns if the camera is controlled by the gamepad
+    pub fn is_active(&self) -> bool {
+        self.last_input.is_some()
+    }
+
+    fn input(&mut self) -> GamepadInput {
+        // the state of the gamepads is only updated by handling the events
+        while self.gilrs.next_event().is_some() {}
+
+        let mut input = GamepadInput {
+            movement: Vec2::ZERO,
+            look: Vec2::ZERO,
+            zoom: 0.0,
+            reset: false,
+        };
+
+        for (_, gamepad) in self.gilrs.gamepads() {
+            let trigger = |button| {
+                gamepad
+                    .button_data(button)
+                    .map_or(0.0, |button_data| button_data.value())
+            };
+
+            input.movement += vec2(
+                deadzone(gamepad.value(Axis::LeftStickX)),
+                deadzone(gamepad.value(Axis::LeftStickY)),
+            );
+            input.look += vec2(
+                deadzone(gamepad.value(Axis::RightStickX)),
+                deadzone(gamepad.value(Axis::RightStickY)),
+            );
+            input.zoom += deadzone(trigger(Button::RightTrigger2) - trigger(Button::LeftTrigger2));
+            input.reset |= gamepad.is_pressed(Button::South);
+        }
+
+        input
+    }
+
+    /// Applies the input of the gamepads since the last update and returns
+    /// the resulting camera control
+    pub fn update(&mut self) -> CameraControl {
+        let now = Instant::now();
+        let delta_time = (now - self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        let input = self.input();
+
+        if input.reset {
+            self.camera_control = CameraControl::default();
+            self.last_input = None;
+            return self.camera_control;
+        }
+
+        let camera_control = &mut self.camera_control;
+
+        if input.is_active() {
+            self.last_input = Some(now);
+
+            camera_control.yaw += input.look.x * GAMEPAD_LOOK_SPEED * delta_time;
+            camera_control.pitch = (camera_control.pitch
+                + input.look.y * GAMEPAD_LOOK_SPEED * delta_time)
+                .clamp(-GAMEPAD_MAX_PITCH, GAMEPAD_MAX_PITCH);
+
+            // the camera flies in the direction it looks
+            let rotation = Mat3::from_rotation_y(camera_control.yaw)
+                * Mat3::from_rotation_x(-camera_control.pitch);
+            camera_control.translation += rotation
+                * vec3(input.movement.x, 0.0, input.movement.y)
+                * GAMEPAD_MOVE_SPEED
+                * delta_time;
+
+            camera_control.pan +=
+                input.movement * GAMEPAD_MOVE_SPEED * delta_time / camera_control.zoom;
+            camera_control.zoom *= GAMEPAD_ZOOM_SPEED.powf(input.zoom * delta_time);
+        } else if let Some(last_input) = self.last_input {
+            let released = (now - last_input).as_secs_f32() - GAMEPAD_RELEASE_DELAY;
+
+            if released > 0.0 {
+                let factor = (-delta_time / GAMEPAD_RETURN_TIME).exp();
+
+                camera_control.translation *= factor;
+                camera_control.yaw *= factor;
+                camera_control.pitch *= factor;
+                camera_control.pan *= factor;
+                camera_control.zoom = camera_control.zoom.powf(factor);
+
+                // less than a thousandth of the offset is left after eight
+                // return times
+                if released > GAMEPAD_RETURN_TIME * 8.0 {
+                    *camera_control = CameraControl::default();
+                    self.last_input = None;
+                }
+            }
+        }
+
+        self.camera_control
+    }
+}
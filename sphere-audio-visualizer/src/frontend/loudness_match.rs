@@ -0,0 +1,247 @@
+use crate::audio_analysis::Samples;
+
+/// Defines the default time in seconds over which the loudness is measured,
+/// which is the window of the short-term loudness
+pub const LOUDNESS_MATCH_TIME: f64 = 3.0;
+
+/// Defines the default largest factor by which a sample source is amplified or
+/// attenuated to match the loudness of the previously selected one
+pub const LOUDNESS_MATCH_MAX_GAIN: f32 = 4.0;
+
+/// Defines the mean square below which samples are treated as silence, which
+/// is about -60 dB
+const SILENCE: f64 = 1e-6;
+
+struct Measurement {
+    reference: f64,
+    start_gain: f32,
+    mean_square: f64,
+    duration: f64,
+}
+
+/// Returns the mean square of the samples or 0.0 if there are none
+fn mean_square(samples: &[f32]) -> f64 {
+    match samples.len() {
+        0 => 0.0,
+        len => samples.iter().map(|&x| (x * x) as f64).sum::<f64>() / len as f64,
+    }
+}
+
+/// Matches the loudness of a newly selected sample source to the loudness of
+/// the previously selected one, so the intensity of the visualization does not
+/// jump when switching sources. The loudness is approximated by the mean
+/// square of the samples. While the new source is measured the gain moves
+/// towards the matching gain and is kept afterwards, so the dynamics within a
+/// source are preserved.
+///
+/// ```
+/// use sphere_audio_visualizer::{audio_analysis::Samples, LoudnessMatcher};
+///
+/// let loud = [0.5; 4410];
+/// let quiet = [0.25; 4410];
+///
+/// let batch = |samples| Samples {
+///     sample_rate: 44100.0,
+///     samples,
+/// };
+///
+/// let mut loudness_matcher = LoudnessMatcher::default().with_match_time(0.1);
+///
+/// loudness_matcher.apply(batch(&loud));
+/// loudness_matcher.start();
+/// loudness_matcher.measure(&batch(&quiet));
+///
+/// let matched = loudness_matcher.apply(batch(&quiet));
+///
+/// assert_eq!(matched.samples.last(), Some(&0.5));
+/// assert_eq!(loudness_matcher.gain(), 2.0);
+/// ```
+pub struct LoudnessMatcher {
+    enabled: bool,
+    match_time: f64,
+    max_gain: f32,
+    gain: f32,
+    level: f64,
+    measurement: Option<Measurement>,
+    buffer: Vec<f32>,
+}
+
+impl LoudnessMatcher {
+    /// Returns if the loudness of sample sources is matched
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Sets if the loudness of sample sources is matched. Disabling the
+    /// matching resets the gain.
+    pub fn set_enabled(&mut self, enabled: bool) -> &mut Self {
+        if self.enabled != enabled {
+            self.enabled = enabled;
+            self.gain = 1.0;
+            self.level = 0.0;
+            self.measurement = None;
+        }
+
+        self
+    }
+
+    /// Sets if the loudness of sample sources is matched
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.set_enabled(enabled);
+        self
+    }
+
+    /// Returns the time in seconds over which the loudness is measured
+    pub fn match_time(&self) -> f64 {
+        self.match_time
+    }
+
+    /// Sets the time in seconds over which the loudness is measured
+    pub fn set_match_time(&mut self, match_time: f64) -> &mut Self {
+        self.match_time = match_time.max(f64::EPSILON);
+        self
+    }
+
+    /// Sets the time in seconds over which the loudness is measured
+    pub fn with_match_time(mut self, match_time: f64) -> Self {
+        self.set_match_time(match_time);
+        self
+    }
+
+    /// Returns the largest factor by which a sample source is amplified or
+    /// attenuated
+    pub fn max_gain(&self) -> f32 {
+        self.max_gain
+    }
+
+    /// Sets the largest factor by which a sample source is amplified or
+    /// attenuated
+    pub fn set_max_gain(&mut self, max_gain: f32) -> &mut Self {
+        self.max_gain = max_gain.max(1.0);
+        self
+    }
+
+    /// Sets the largest factor by which a sample source is amplified or
+    /// attenuated
+    pub fn with_max_gain(mut self, max_gain: f32) -> Self {
+        self.set_max_gain(max_gain);
+        self
+    }
+
+    /// Returns the factor the samples are currently multiplied with
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Starts matching a newly selected sample source to the loudness of the
+    /// samples applied so far. Nothing is matched after silence.
+    pub fn start(&mut self) {
+        self.measurement = match self.enabled && self.level > SILENCE {
+            true => Some(Measurement {
+                reference: self.level,
+                start_gain: self.gain,
+                mean_square: 0.0,
+                duration: 0.0,
+            }),
+            false => None,
+        };
+    }
+
+    /// Measures the loudness of the samples of the newly selected sample
+    /// source. The samples have to be measured before they are mixed with a
+    /// fading source.
+    pub fn measure(&mut self, samples: &Samples) {
+        let measurement = match &mut self.measurement {
+            Some(measurement) => measurement,
+            None => return,
+        };
+
+        let duration = samples.duration();
+        let mean_square = mean_square(samples.samples);
+
+        // silence, e.g. of a paused file, would make the source seem quieter
+        if duration == 0.0 || mean_square < SILENCE {
+            return;
+        }
+
+        measurement.duration += duration;
+        measurement.mean_square +=
+            (mean_square - measurement.mean_square) * duration / measurement.duration;
+    }
+
+    fn target_gain(&self) -> f32 {
+        match &self.measurement {
+            Some(measurement) if measurement.duration > 0.0 => {
+                let matching = ((measurement.reference / measurement.mean_square).sqrt() as f32)
+                    .clamp(1.0 / self.max_gain, self.max_gain);
+                let progress = (measurement.duration / self.match_time).min(1.0) as f32;
+
+                measurement.start_gain + (matching - measurement.start_gain) * progress
+            }
+            _ => self.gain,
+        }
+    }
+
+    /// Multiplies the samples with the gain and measures the loudness of the
+    /// result, which the next selected sample source is matched to
+    pub fn apply<'a>(&'a mut self, samples: Samples<'a>) -> Samples<'a> {
+        if !self.enabled {
+            return samples;
+        }
+
+        let old_gain = self.gain;
+        let gain = self.target_gain();
+
+        self.gain = gain;
+
+        if let Some(measurement) = &self.measurement {
+            if measurement.duration >= self.match_time {
+                self.measurement = None;
+            }
+        }
+
+        // the gain is interpolated over the batch, so changes don't click
+        let len = samples.samples.len() as f32;
+        self.buffer.clear();
+        self.buffer.extend(
+            samples
+                .samples
+                .iter()
+                .enumerate()
+                .map(|(i, sample)| sample * (old_gain + (gain - old_gain) * (i + 1) as f32 / len)),
+        );
+
+        let duration = samples.duration();
+        let mean_square = mean_square(&self.buffer);
+
+        if duration > 0.0 && mean_square > SILENCE {
+            self.level = match self.level {
+                level if level > 0.0 => {
+                    // an exponential average approximates the window
+                    let alpha = 1.0 - (-duration / self.match_time).exp();
+                    level + (mean_square - level) * alpha
+                }
+                _ => mean_square,
+            };
+        }
+
+        Samples {
+            sample_rate: samples.sample_rate,
+            samples: &self.buffer,
+        }
+    }
+}
+
+impl Default for LoudnessMatcher {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            match_time: LOUDNESS_MATCH_TIME,
+            max_gain: LOUDNESS_MATCH_MAX_GAIN,
+            gain: 1.0,
+            level: 0.0,
+            measurement: None,
+            buffer: Vec::new(),
+        }
+    }
+}
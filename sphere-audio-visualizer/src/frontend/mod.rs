@@ -4,13 +4,33 @@ use std::any::Any;
 
 use egui::Ui;
 
-pub use self::{app::*, drawer::*};
+#[cfg(feature = "gamepad")]
+pub use self::gamepad::*;
+#[cfg(feature = "config")]
+pub use self::registry::*;
+pub use self::{
+    app::*, crossfade::*, drawer::*, loudness_match::*, mouse::*, server::*, session::*, status::*,
+    test_signal::*,
+};
 use crate::{
-    audio_analysis::Samples, rendering::wgpu::OutputFormat, visualizer::OfflineVisualizer,
+    audio_analysis::{BeatGrid, Samples},
+    rendering::wgpu::OutputFormat,
+    visualizer::OfflineVisualizer,
 };
 
 mod app;
+mod crossfade;
 mod drawer;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod loudness_match;
+mod mouse;
+#[cfg(feature = "config")]
+mod registry;
+mod server;
+mod session;
+mod status;
+mod test_signal;
 
 /// An [`OnlineSampleSource`] is used by an [`Application`] get the current
 /// samples for analysis from a sample source which creates new samples while
@@ -30,6 +50,28 @@ pub trait OnlineSampleSource: Any {
     /// Is invoked to draw some aditional UI with egui to configure the
     /// [`OnlineSampleSource`].
     fn ui(&mut self, ui: &mut Ui);
+
+    /// Returns the name of a visualizer the [`Application`] should switch to
+    /// before the next batch of samples is requested. This is used to replay
+    /// recorded sessions.
+    fn requested_visualizer(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Returns the settings in the YAML format of a
+    /// `ModuleSettingsDescription` the [`Application`] should apply to the
+    /// selected visualizer before the next batch of samples is requested.
+    /// This is used to replay recorded sessions.
+    fn requested_settings(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Returns the health of the sample source, which is shown next to the
+    /// sample source selector. Returns None if the sample source doesn't
+    /// report its status.
+    fn status(&self) -> Option<SampleSourceStatus> {
+        None
+    }
 }
 
 /// The [`Exporter`] is used by the [`Application`] request [`ExportProcess`]es.
@@ -47,6 +89,40 @@ pub trait Exporter {
     /// Is invoked to draw some aditional UI with egui to configure the
     /// [`Exporter`].
     fn ui(&mut self, ui: &mut Ui);
+
+    /// Returns the beat grid of the exported track if snapping is enabled.
+    /// Section markers are placed on its downbeats.
+    fn beat_grid(&self) -> Option<BeatGrid> {
+        None
+    }
+
+    /// Creates an export process, which only renders and encodes a short
+    /// sample of the track with the export settings to measure an
+    /// [`ExportEstimate`]. No file is written. Returns None if dry runs are
+    /// not supported.
+    fn dry_run(
+        &mut self,
+        _visualizer: Box<dyn OfflineVisualizer>,
+    ) -> Option<Box<dyn ExportProcess>> {
+        None
+    }
+
+    /// Returns the estimate measured by the last finished dry run
+    fn estimate(&self) -> Option<ExportEstimate> {
+        None
+    }
+}
+
+/// The expected cost of an export, extrapolated from a dry run of an
+/// [`Exporter`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ExportEstimate {
+    /// The number of frames rendered and encoded per second
+    pub frames_per_second: f64,
+    /// The time the export takes in seconds
+    pub duration: f64,
+    /// The size of the exported file in bytes
+    pub file_size: u64,
 }
 
 /// Defines the interface that a export process has to support. export
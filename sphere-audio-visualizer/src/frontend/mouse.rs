@@ -0,0 +1,91 @@
+use sphere_audio_visualizer_core::glam::{vec2, Vec2};
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+
+use crate::rendering::CameraControl;
+
+/// Defines the rotation of the orbit in radians per dragged pixel
+const MOUSE_ORBIT_SPEED: f32 = 0.005;
+
+/// Defines the maximal elevation of the orbit in radians
+const MOUSE_MAX_ELEVATION: f32 = 1.5;
+
+/// Defines the factor the zoom changes by per scrolled line
+const MOUSE_ZOOM_STEP: f32 = 1.1;
+
+/// Defines the number of pixels of touchpads scrolling one line
+const MOUSE_PIXELS_PER_LINE: f32 = 40.0;
+
+/// Defines the range of the zoom
+const MOUSE_ZOOM_RANGE: (f32, f32) = (0.25, 4.0);
+
+/// Orbits the camera of the visualizer with the mouse to inspect the scene
+/// from different angles. Dragging with the left button orbits, the wheel
+/// zooms and the middle button resets the camera. Events used by egui are
+/// ignored, so the UI stays usable.
+#[derive(Default)]
+pub struct MouseCamera {
+    orbit: Vec2,
+    zoom_steps: f32,
+    dragging: bool,
+    cursor: Option<Vec2>,
+}
+
+impl MouseCamera {
+    /// Handles a window event. `consumed` tells if egui uses the event.
+    pub fn on_event(&mut self, event: &WindowEvent, consumed: bool) {
+        match event {
+            // the button is released even over the UI, so the drag ends
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => self.dragging = *state == ElementState::Pressed && !consumed,
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Middle,
+                ..
+            } if !consumed => {
+                self.orbit = Vec2::ZERO;
+                self.zoom_steps = 0.0;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let cursor = vec2(position.x as f32, position.y as f32);
+
+                if let (true, Some(last_cursor)) = (self.dragging, self.cursor) {
+                    let delta = (cursor - last_cursor) * MOUSE_ORBIT_SPEED;
+
+                    self.orbit.x += delta.x;
+                    self.orbit.y =
+                        (self.orbit.y + delta.y).clamp(-MOUSE_MAX_ELEVATION, MOUSE_MAX_ELEVATION);
+                }
+
+                self.cursor = Some(cursor);
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseWheel { delta, .. } if !consumed => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => {
+                        position.y as f32 / MOUSE_PIXELS_PER_LINE
+                    }
+                };
+
+                let (min, max) = MOUSE_ZOOM_RANGE;
+                let max_steps = max.ln() / MOUSE_ZOOM_STEP.ln();
+                let min_steps = min.ln() / MOUSE_ZOOM_STEP.ln();
+
+                self.zoom_steps = (self.zoom_steps + lines).clamp(min_steps, max_steps);
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies the orbit and zoom to a camera control
+    pub fn apply(&self, camera_control: CameraControl) -> CameraControl {
+        CameraControl {
+            orbit: camera_control.orbit + self.orbit,
+            zoom: camera_control.zoom * MOUSE_ZOOM_STEP.powf(self.zoom_steps),
+            ..camera_control
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::{fs::File, io, io::BufReader, path::Path};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_yaml::Value;
+use thiserror::Error;
+
+use super::{drawer::UiDrawer, Application, FrameServer};
+use crate::{
+    module::Module,
+    rendering::{
+        wgpu::{
+            Bars, CpuRaytracer, DebugView, Metaballs, Metaballs3D, Oscilloscope, Pipeline,
+            Raytracer, SdfTracer, SurfaceTarget, Tunnel,
+        },
+        BarsSceneConverter, Metaballs3DSceneConverter, MetaballsSceneConverter,
+        OscilloscopeSceneConverter, RaytracerSceneConverter, SceneConverter, TunnelSceneConverter,
+    },
+    simulation::{Passthrough, Simulation2D, Simulation3D, Simulator, WaveformSimulation},
+    utils::TypeMap,
+    visualizer::{WGPUVisualizer, WGPUVisualizerFactory},
+};
+
+/// Represents the errors which could happen when loading or applying a
+/// [`VisualizerDescription`]
+#[derive(Error, Debug)]
+pub enum VisualizerDescriptionError {
+    /// This error happens when the description file could not be read.
+    #[error("failed to read the file: {0}")]
+    Io(#[from] io::Error),
+    /// This error happens when the description or the settings of a module
+    /// do not match the expected format.
+    #[error("invalid description: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    /// This error happens when no visualizer with the named modules is
+    /// registered, e.g. because of a typo or because the modules can not be
+    /// combined.
+    #[error("unknown module combination {simulator} / {scene_converter} / {pipeline}")]
+    UnknownModules {
+        /// The name of the simulator
+        simulator: String,
+        /// The name of the scene converter
+        scene_converter: String,
+        /// The name of the pipeline
+        pipeline: String,
+    },
+}
+
+/// Stores the settings of the modules of a [`VisualizerDescription`]. Every
+/// entry is deserialized into the settings of the respective module. Fields
+/// missing in an entry use their default values, modules without an entry
+/// keep their current settings.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ModuleSettingsDescription {
+    /// The settings of the simulator
+    pub simulator: Option<Value>,
+    /// The settings of the scene converter
+    pub scene_converter: Option<Value>,
+    /// The settings of the pipeline
+    pub pipeline: Option<Value>,
+}
+
+/// Describes a visualizer configuration by the names of its modules, which
+/// are the names of their types, e.g. `Simulation3D`,
+/// `RaytracerSceneConverter` and `Raytracer`.
+///
+/// ```
+/// use sphere_audio_visualizer::VisualizerDescription;
+///
+/// let descriptions: Vec<VisualizerDescription> = serde_yaml::from_str(
+///     "
+/// - name: Magma
+///   simulator: Simulation3D
+///   scene_converter: RaytracerSceneConverter
+///   pipeline: Raytracer
+///   settings:
+///     scene_converter:
+///       palette: Magma
+/// ",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(descriptions[0].pipeline, "Raytracer");
+/// assert!(descriptions[0].settings.scene_converter.is_some());
+/// ```
+#[derive(Deserialize, Clone)]
+pub struct VisualizerDescription {
+    /// The name displayed in the UI
+    pub name: String,
+    /// The name of the simulator
+    pub simulator: String,
+    /// The name of the scene converter
+    pub scene_converter: String,
+    /// The name of the pipeline
+    pub pipeline: String,
+    /// The initial settings of the modules
+    #[serde(default)]
+    pub settings: ModuleSettingsDescription,
+}
+
+/// Loads a YAML file containing a list of [`VisualizerDescription`]s
+pub fn load_visualizer_descriptions(
+    path: impl AsRef<Path>,
+) -> Result<Vec<VisualizerDescription>, VisualizerDescriptionError> {
+    let reader = BufReader::new(File::open(path)?);
+
+    Ok(serde_yaml::from_reader(reader)?)
+}
+
+/// Converts the settings of the modules of a registered visualizer between a
+/// settings bin and a [`ModuleSettingsDescription`]. This is used to record
+/// and replay the settings in sessions.
+#[derive(Clone, Copy)]
+pub(crate) struct SettingsCodec {
+    pub(crate) capture: fn(&TypeMap) -> Result<ModuleSettingsDescription, serde_yaml::Error>,
+    pub(crate) apply: fn(&ModuleSettingsDescription, &mut TypeMap) -> Result<(), serde_yaml::Error>,
+}
+
+/// Stores the functions adding a registered visualizer to an [`Application`]
+/// or a [`FrameServer`] together with the codec of its settings
+pub(crate) struct VisualizerRegistration {
+    name: String,
+    simulator: &'static str,
+    scene_converter: &'static str,
+    pipeline: &'static str,
+    pub(crate) settings_codec: SettingsCodec,
+    pub(crate) register: fn(Application, String) -> Application,
+    pub(crate) register_frame_server: fn(FrameServer, String) -> FrameServer,
+}
+
+/// Maps the names of modules to the visualizers they can be combined to, so
+/// visualizer configurations can be described in a config file instead of
+/// code. The default registry contains every combination of the built-in
+/// modules and is the single source of the built-in visualizers.
+///
+/// ```
+/// use sphere_audio_visualizer::VisualizerRegistry;
+///
+/// let descriptions = VisualizerRegistry::default().descriptions();
+///
+/// assert_eq!(descriptions[0].name, "Raytracer");
+/// assert_eq!(descriptions[0].pipeline, "Raytracer");
+/// ```
+pub struct VisualizerRegistry {
+    registrations: Vec<VisualizerRegistration>,
+}
+
+impl VisualizerRegistry {
+    /// Creates a new instance without any visualizer
+    pub fn new() -> Self {
+        Self {
+            registrations: Vec::new(),
+        }
+    }
+
+    /// Registers the combination of the modules under the names of their
+    /// types. The name is used by the description of the visualizer.
+    pub fn with_visualizer<S, SC, P>(mut self, name: impl ToString) -> Self
+    where
+        S: Simulator + Module + 'static,
+        SC: SceneConverter<S::Scene> + Module + 'static,
+        P: Pipeline<SC::Scene> + Module + 'static,
+        S::Settings: Serialize + DeserializeOwned + 'static,
+        <SC as Module>::Settings: Serialize + DeserializeOwned + 'static,
+        <P as Module>::Settings: Serialize + DeserializeOwned + 'static,
+        WGPUVisualizer<S, SC, P, SurfaceTarget>: UiDrawer,
+    {
+        self.registrations.push(VisualizerRegistration {
+            name: name.to_string(),
+            simulator: short_type_name::<S>(),
+            scene_converter: short_type_name::<SC>(),
+            pipeline: short_type_name::<P>(),
+            settings_codec: SettingsCodec {
+                capture: |settings_bin| {
+                    Ok(ModuleSettingsDescription {
+                        simulator: Some(serialize_settings::<S>(settings_bin)?),
+                        scene_converter: Some(serialize_settings::<SC>(settings_bin)?),
+                        pipeline: Some(serialize_settings::<P>(settings_bin)?),
+                    })
+                },
+                apply: |settings, settings_bin| {
+                    // everything is deserialized before anything is applied,
+                    // so invalid descriptions leave the settings untouched
+                    let simulator = deserialize_settings::<S>(&settings.simulator, settings_bin)?;
+                    let scene_converter =
+                        deserialize_settings::<SC>(&settings.scene_converter, settings_bin)?;
+                    let pipeline = deserialize_settings::<P>(&settings.pipeline, settings_bin)?;
+
+                    settings_bin.insert(simulator);
+                    settings_bin.insert(scene_converter);
+                    settings_bin.insert(pipeline);
+
+                    Ok(())
+                },
+            },
+            register: |application, name| {
+                application
+                    .with_visualizer_configuration::<WGPUVisualizerFactory<S, SC, P>, _>(name)
+            },
+            register_frame_server: |frame_server, name| {
+                frame_server
+                    .with_visualizer_configuration::<WGPUVisualizerFactory<S, SC, P>, _>(name)
+            },
+        });
+        self
+    }
+
+    /// Returns a description of every registered visualizer, which keeps the
+    /// settings of its modules
+    pub fn descriptions(&self) -> Vec<VisualizerDescription> {
+        self.registrations
+            .iter()
+            .map(|registration| VisualizerDescription {
+                name: registration.name.clone(),
+                simulator: registration.simulator.to_string(),
+                scene_converter: registration.scene_converter.to_string(),
+                pipeline: registration.pipeline.to_string(),
+                settings: ModuleSettingsDescription::default(),
+            })
+            .collect()
+    }
+
+    /// Applies the settings of the description to the settings bin and
+    /// returns the registration of the described visualizer
+    pub(crate) fn apply(
+        &self,
+        description: &VisualizerDescription,
+        settings_bin: &mut TypeMap,
+    ) -> Result<&VisualizerRegistration, VisualizerDescriptionError> {
+        let registration = self
+            .registrations
+            .iter()
+            .find(|registration| {
+                registration.simulator == description.simulator
+                    && registration.scene_converter == description.scene_converter
+                    && registration.pipeline == description.pipeline
+            })
+            .ok_or_else(|| VisualizerDescriptionError::UnknownModules {
+                simulator: description.simulator.clone(),
+                scene_converter: description.scene_converter.clone(),
+                pipeline: description.pipeline.clone(),
+            })?;
+
+        (registration.settings_codec.apply)(&description.settings, settings_bin)?;
+
+        Ok(registration)
+    }
+}
+
+impl Default for VisualizerRegistry {
+    fn default() -> Self {
+        Self::new()
+            .with_visualizer::<Simulation3D, RaytracerSceneConverter, Raytracer>("Raytracer")
+            .with_visualizer::<Simulation2D, MetaballsSceneConverter, Metaballs>("Metaballs")
+            .with_visualizer::<Simulation3D, RaytracerSceneConverter, DebugView>("Debug View")
+            .with_visualizer::<Simulation3D, RaytracerSceneConverter, SdfTracer>("SDF Tracer")
+            .with_visualizer::<Simulation3D, RaytracerSceneConverter, CpuRaytracer>("CPU Raytracer")
+            .with_visualizer::<Simulation3D, Metaballs3DSceneConverter, Metaballs3D>("3D Metaballs")
+            .with_visualizer::<Passthrough, BarsSceneConverter, Bars>("Bars")
+            .with_visualizer::<WaveformSimulation, OscilloscopeSceneConverter, Oscilloscope>(
+                "Oscilloscope",
+            )
+            .with_visualizer::<Passthrough, TunnelSceneConverter, Tunnel>("Tunnel")
+    }
+}
+
+/// Deserializes the settings of a module. Modules without settings in the
+/// description keep the settings currently stored in the bin.
+fn deserialize_settings<M: Module>(
+    value: &Option<Value>,
+    settings_bin: &TypeMap,
+) -> Result<M::Settings, serde_yaml::Error>
+where
+    M::Settings: DeserializeOwned + 'static,
+{
+    let value = match value {
+        Some(value) => value.clone(),
+        None => {
+            return Ok(settings_bin
+                .get::<M::Settings>()
+                .cloned()
+                .unwrap_or_default())
+        }
+    };
+
+    serde_yaml::from_value(value)
+}
+
+/// Serializes the settings of a module stored in the bin. Modules without
+/// settings in the bin use their default settings.
+fn serialize_settings<M: Module>(settings_bin: &TypeMap) -> Result<Value, serde_yaml::Error>
+where
+    M::Settings: Serialize + 'static,
+{
+    serde_yaml::to_value(
+        settings_bin
+            .get::<M::Settings>()
+            .cloned()
+            .unwrap_or_default(),
+    )
+}
+
+/// Returns the name of a type without its path and generic parameters
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+
+    name.rsplit("::").next().unwrap_or(name)
+}
@@ -0,0 +1,442 @@
+use std::{
+    any::TypeId,
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+#[cfg(feature = "config")]
+use super::{ModuleSettingsDescription, SettingsCodec, VisualizerDescription, VisualizerRegistry};
+use crate::{
+    audio_analysis::Samples,
+    module::ModuleManager,
+    rendering::wgpu::{AdapterSelection, OffscreenTargetOutput, OutputFormat},
+    utils::TypeMap,
+    visualizer::{OfflineVisualizer, VisualizerFactory},
+};
+
+/// Defines the default address a [`FrameServer`] listens on. Only local
+/// clients can connect to it, render farms have to pass the address of the
+/// network interface explicitly.
+pub const FRAME_SERVER_ADDRESS: &str = "127.0.0.1:7727";
+
+/// Defines the largest number of samples in a batch a [`FrameServer`] accepts
+pub const FRAME_SERVER_MAX_SAMPLES: u32 = 1 << 22;
+
+/// Defines the largest width and height of a frame a [`FrameServer`] renders
+pub const FRAME_SERVER_MAX_SIZE: u32 = 16384;
+
+/// Defines the largest length in bytes of the name and the settings of a job
+pub const FRAME_SERVER_MAX_TEXT_LEN: u32 = 1 << 20;
+
+const FRAME_SERVER_MAGIC: &[u8; 4] = b"SAVF";
+const FRAME_SERVER_VERSION: u32 = 2;
+
+const WARM_UP_TAG: u8 = 0;
+const FRAME_TAG: u8 = 1;
+const END_OF_TRACK_TAG: u8 = 2;
+
+const ACCEPTED: u8 = 0;
+const REJECTED: u8 = 1;
+
+struct FrameServerConfiguration {
+    name: String,
+    new_offline: fn(OutputFormat, &mut TypeMap) -> Box<dyn OfflineVisualizer>,
+    #[cfg(feature = "config")]
+    settings_codec: Option<SettingsCodec>,
+    #[cfg(feature = "config")]
+    settings: ModuleSettingsDescription,
+}
+
+/// Runs offline visualizers without a window and renders frames requested
+/// over the network, so a render farm can split a long export across
+/// machines. Every connection renders one job with a fresh visualizer using
+/// the settings sent by the client. Connections are served one after
+/// another. The server is not authenticated, so it should only listen on
+/// trusted networks.
+///
+/// All values are little endian and texts are sent as `u32` length followed
+/// by UTF-8 bytes. A client opens a job with the magic `SAVF`, the protocol
+/// version as `u32`, the output format as `u8` (0 RGBA8, 1 BGRA8, 2 I420,
+/// 3 NV12), the name of the visualizer and its settings as YAML text in the
+/// format of the settings of a `VisualizerDescription`. Modules without
+/// settings use the settings the visualizer was added with, so an empty text
+/// renders with those. Settings require the `config` feature. The server
+/// answers with a `u8` status, which is 0 if the job was accepted. Otherwise
+/// the status is followed by an error message and the job ends. Afterwards
+/// the client sends messages starting with a `u8` tag:
+/// - 0 warms up the visualizer with a batch of samples, which is sent as the
+/// sample rate as `f64` followed by the `u32` sample count and `f32` samples.
+/// Used to pre-roll the simulation before the first frame of a job. Batches
+/// with more than [`FRAME_SERVER_MAX_SAMPLES`] samples end the job.
+/// - 1 renders a frame. The tag is followed by the width and height as `u32`
+/// and a batch of samples like above. The server answers with a status like
+/// above. Accepted frames follow with the `u32` plane count, the offset,
+/// stride and rows of every plane as `u32` and the `u32` length of the frame
+/// data followed by the data. Frames larger than [`FRAME_SERVER_MAX_SIZE`] or
+/// with too many samples are rejected with an error message, but the job
+/// goes on.
+/// - 2 signals that every sample of the track was sent.
+///
+/// The job ends when the client closes the connection.
+pub struct FrameServer {
+    configurations: Vec<FrameServerConfiguration>,
+    adapter_selection: AdapterSelection,
+}
+
+impl FrameServer {
+    /// Creates a new instance without any visualizer
+    pub fn new() -> Self {
+        Self {
+            configurations: Vec::new(),
+            adapter_selection: AdapterSelection::default(),
+        }
+    }
+
+    /// Returns the adapter the frames are rendered with
+    pub fn adapter_selection(&self) -> AdapterSelection {
+        self.adapter_selection
+    }
+
+    /// Sets the adapter the frames are rendered with
+    pub fn set_adapter_selection(&mut self, adapter_selection: AdapterSelection) -> &mut Self {
+        self.adapter_selection = adapter_selection;
+        self
+    }
+
+    /// Sets the adapter the frames are rendered with
+    pub fn with_adapter_selection(mut self, adapter_selection: AdapterSelection) -> Self {
+        self.set_adapter_selection(adapter_selection);
+        self
+    }
+
+    /// Adds a new visualizer configuration. Clients select the visualizer by
+    /// its name.
+    pub fn with_visualizer_configuration<F, S>(mut self, name: S) -> Self
+    where
+        F: VisualizerFactory + 'static,
+        S: ToString,
+    {
+        self.configurations.push(FrameServerConfiguration {
+            name: name.to_string(),
+            new_offline: |format, settings_bin| -> Box<dyn OfflineVisualizer> {
+                Box::new(F::new_offline(
+                    format,
+                    ModuleManager::new(settings_bin).with_scope(Some(TypeId::of::<F>())),
+                ))
+            },
+            #[cfg(feature = "config")]
+            settings_codec: None,
+            #[cfg(feature = "config")]
+            settings: ModuleSettingsDescription::default(),
+        });
+        self
+    }
+
+    /// Adds the visualizer configurations of the descriptions using the
+    /// modules of the registry. Every job starts with the settings of its
+    /// description. Invalid descriptions are reported and skipped.
+    #[cfg(feature = "config")]
+    pub fn with_visualizer_descriptions(
+        mut self,
+        registry: &VisualizerRegistry,
+        descriptions: &[VisualizerDescription],
+    ) -> Self {
+        for description in descriptions {
+            // the settings are only validated here and applied to every job
+            let registration = match registry.apply(description, &mut TypeMap::new()) {
+                Ok(registration) => registration,
+                Err(error) => {
+                    eprintln!("Failed to add visualizer {}: {}", description.name, error);
+                    continue;
+                }
+            };
+
+            self = (registration.register_frame_server)(self, description.name.clone());
+
+            if let Some(configuration) = self.configurations.last_mut() {
+                configuration.settings_codec = Some(registration.settings_codec);
+                configuration.settings = description.settings.clone();
+            }
+        }
+
+        self
+    }
+
+    /// Listens on the address and serves incoming jobs until listening
+    /// fails. Failed jobs are reported and do not stop the server.
+    pub fn serve(&self, address: impl ToSocketAddrs) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let peer = stream.peer_addr()?;
+
+            if let Err(error) = self.serve_job(stream) {
+                eprintln!("Failed to serve frames to {}: {}", peer, error);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn serve_job(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != FRAME_SERVER_MAGIC || read_u32(&mut reader)? != FRAME_SERVER_VERSION {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "not a supported frame server client",
+            ));
+        }
+
+        let format = output_format(read_u8(&mut reader)?);
+        let name = read_text(&mut reader)?;
+        let settings = read_text(&mut reader)?;
+
+        let job = match (format, name, settings) {
+            (Some(format), Some(name), Some(settings)) => self
+                .configurations
+                .iter()
+                .find(|configuration| configuration.name.as_bytes() == name)
+                .ok_or_else(|| "unknown visualizer".to_string())
+                .and_then(|configuration| {
+                    let settings_bin = load_settings(configuration, &settings)?;
+                    Ok((format, configuration, settings_bin))
+                }),
+            (None, _, _) => Err("unknown output format".to_string()),
+            _ => Err("name or settings too long".to_string()),
+        };
+
+        let (format, configuration, mut settings_bin) = match job {
+            Ok(job) => job,
+            Err(message) => {
+                write_rejection(&mut writer, &message)?;
+                writer.flush()?;
+
+                return Err(io::Error::new(ErrorKind::InvalidData, message));
+            }
+        };
+
+        writer.write_all(&[ACCEPTED])?;
+        writer.flush()?;
+
+        settings_bin.insert(self.adapter_selection);
+
+        let mut visualizer = (configuration.new_offline)(format, &mut settings_bin);
+
+        loop {
+            let mut tag = [0];
+
+            match reader.read_exact(&mut tag) {
+                Ok(()) => {}
+                Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+                Err(error) => return Err(error),
+            }
+
+            match tag[0] {
+                WARM_UP_TAG => {
+                    let (sample_rate, samples) = match read_samples(&mut reader)? {
+                        Some(samples) => samples,
+                        None => {
+                            return Err(io::Error::new(
+                                ErrorKind::InvalidData,
+                                "too many samples to warm up",
+                            ))
+                        }
+                    };
+
+                    visualizer.warm_up(Samples {
+                        sample_rate,
+                        samples: &samples,
+                    });
+                }
+                FRAME_TAG => {
+                    let width = read_u32(&mut reader)?;
+                    let height = read_u32(&mut reader)?;
+
+                    let (sample_rate, samples) = match read_samples(&mut reader)? {
+                        Some(samples) => samples,
+                        None => {
+                            write_rejection(&mut writer, "too many samples")?;
+                            writer.flush()?;
+                            continue;
+                        }
+                    };
+
+                    if !(1..=FRAME_SERVER_MAX_SIZE).contains(&width)
+                        || !(1..=FRAME_SERVER_MAX_SIZE).contains(&height)
+                    {
+                        write_rejection(&mut writer, "unsupported frame size")?;
+                        writer.flush()?;
+                        continue;
+                    }
+
+                    let output = visualizer.visualize(
+                        Samples {
+                            sample_rate,
+                            samples: &samples,
+                        },
+                        width,
+                        height,
+                    );
+
+                    writer.write_all(&[ACCEPTED])?;
+                    write_output(&mut writer, &output)?;
+                    writer.flush()?;
+                }
+                END_OF_TRACK_TAG => visualizer.end_of_track(),
+                _ => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "unknown frame server message",
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl Default for FrameServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn output_format(format: u8) -> Option<OutputFormat> {
+    match format {
+        0 => Some(OutputFormat::RGBA8),
+        1 => Some(OutputFormat::BGRA8),
+        2 => Some(OutputFormat::I420),
+        3 => Some(OutputFormat::NV12),
+        _ => None,
+    }
+}
+
+/// Applies the settings of a job on top of the settings of the configuration
+#[cfg(feature = "config")]
+fn load_settings(
+    configuration: &FrameServerConfiguration,
+    settings: &[u8],
+) -> Result<TypeMap, String> {
+    let mut settings_bin = TypeMap::new();
+
+    let settings_codec = match configuration.settings_codec {
+        Some(settings_codec) => settings_codec,
+        None if settings.is_empty() => return Ok(settings_bin),
+        None => return Err("the visualizer has no described settings".to_string()),
+    };
+
+    (settings_codec.apply)(&configuration.settings, &mut settings_bin)
+        .map_err(|error| error.to_string())?;
+
+    if !settings.is_empty() {
+        let settings = std::str::from_utf8(settings)
+            .map_err(|error| error.to_string())
+            .and_then(|settings| {
+                serde_yaml::from_str::<ModuleSettingsDescription>(settings)
+                    .map_err(|error| error.to_string())
+            })?;
+
+        (settings_codec.apply)(&settings, &mut settings_bin).map_err(|error| error.to_string())?;
+    }
+
+    Ok(settings_bin)
+}
+
+/// Only accepts empty settings, as settings can't be deserialized without
+/// the `config` feature
+#[cfg(not(feature = "config"))]
+fn load_settings(
+    _configuration: &FrameServerConfiguration,
+    settings: &[u8],
+) -> Result<TypeMap, String> {
+    match settings.is_empty() {
+        true => Ok(TypeMap::new()),
+        false => Err("settings are not supported".to_string()),
+    }
+}
+
+fn write_rejection(writer: &mut impl Write, message: &str) -> io::Result<()> {
+    writer.write_all(&[REJECTED])?;
+    writer.write_all(&(message.len() as u32).to_le_bytes())?;
+    writer.write_all(message.as_bytes())
+}
+
+fn write_output(writer: &mut impl Write, output: &OffscreenTargetOutput) -> io::Result<()> {
+    writer.write_all(&(output.planes.len() as u32).to_le_bytes())?;
+
+    for plane in &output.planes {
+        writer.write_all(&(plane.offset as u32).to_le_bytes())?;
+        writer.write_all(&(plane.stride as u32).to_le_bytes())?;
+        writer.write_all(&(plane.rows as u32).to_le_bytes())?;
+    }
+
+    writer.write_all(&(output.data.len() as u32).to_le_bytes())?;
+    writer.write_all(&output.data)
+}
+
+/// Reads a batch of samples. Returns None if the batch is too long, in which
+/// case the samples are skipped without storing them.
+fn read_samples(reader: &mut impl Read) -> io::Result<Option<(f64, Vec<f32>)>> {
+    let mut sample_rate = [0; 8];
+    reader.read_exact(&mut sample_rate)?;
+
+    let len = read_u32(reader)?;
+
+    if len > FRAME_SERVER_MAX_SAMPLES {
+        skip(reader, len as u64 * 4)?;
+        return Ok(None);
+    }
+
+    let mut bytes = vec![0; len as usize * 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(Some((
+        f64::from_le_bytes(sample_rate),
+        bytes
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect(),
+    )))
+}
+
+/// Reads a text as bytes. Returns None if the text is too long, in which case
+/// the text is skipped without storing it.
+fn read_text(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let len = read_u32(reader)?;
+
+    if len > FRAME_SERVER_MAX_TEXT_LEN {
+        skip(reader, len as u64)?;
+        return Ok(None);
+    }
+
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(Some(bytes))
+}
+
+fn skip(reader: &mut impl Read, len: u64) -> io::Result<()> {
+    match io::copy(&mut reader.take(len), &mut io::sink())? {
+        skipped if skipped == len => Ok(()),
+        _ => Err(ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
@@ -0,0 +1,423 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use egui::{ComboBox, Grid, Ui};
+
+use super::{OnlineSampleSource, SampleSourceStatus};
+use crate::audio_analysis::Samples;
+
+/// Defines the file extension of recorded sessions
+pub const SESSION_EXTENSION: &str = "savs";
+
+const SESSION_MAGIC: &[u8; 4] = b"SAVS";
+const SESSION_VERSION: u32 = 2;
+
+const SAMPLES_TAG: u8 = 0;
+const VISUALIZER_TAG: u8 = 1;
+const SETTINGS_TAG: u8 = 2;
+
+/// A single event of a recorded session
+#[derive(Clone, PartialEq, Debug)]
+pub enum SessionEvent {
+    /// A batch of samples as returned by an [`OnlineSampleSource`]
+    Samples {
+        /// The sample rate
+        sample_rate: f64,
+        /// The samples
+        samples: Vec<f32>,
+    },
+    /// The user selected the visualizer with the given name
+    Visualizer(String),
+    /// The settings of the selected visualizer changed to the given YAML
+    /// module settings description
+    Settings(String),
+}
+
+/// Records the samples of an [`OnlineSampleSource`], the selected visualizers
+/// and the changes of their settings to a session file. Every batch of
+/// samples is stored as it was analysed, so replaying the session reproduces
+/// the frames exactly. The settings of the simulator, scene converter and
+/// pipeline are stored in the YAML format of the visualizer descriptions,
+/// so they are only recorded and replayed with the `config` feature.
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    duration: f64,
+    settings: Option<String>,
+}
+
+impl SessionRecorder {
+    /// Creates a new session file at the given path
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        writer.write_all(SESSION_MAGIC)?;
+        writer.write_all(&SESSION_VERSION.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            path,
+            duration: 0.0,
+            settings: None,
+        })
+    }
+
+    /// Gets the path of the session file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the amount of recorded audio in seconds
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Appends a batch of samples to the session
+    pub fn record_samples(&mut self, samples: &Samples) -> io::Result<()> {
+        self.writer.write_all(&[SAMPLES_TAG])?;
+        self.writer.write_all(&samples.sample_rate.to_le_bytes())?;
+        self.writer
+            .write_all(&(samples.samples.len() as u32).to_le_bytes())?;
+
+        for sample in samples.samples {
+            self.writer.write_all(&sample.to_le_bytes())?;
+        }
+
+        if samples.sample_rate > 0.0 {
+            self.duration += samples.samples.len() as f64 / samples.sample_rate;
+        }
+
+        Ok(())
+    }
+
+    /// Appends the selection of a visualizer to the session
+    pub fn record_visualizer(&mut self, name: &str) -> io::Result<()> {
+        // the settings of the newly selected visualizer are always recorded
+        self.settings = None;
+
+        self.writer.write_all(&[VISUALIZER_TAG])?;
+        self.writer.write_all(&(name.len() as u32).to_le_bytes())?;
+        self.writer.write_all(name.as_bytes())
+    }
+
+    /// Appends the settings of the selected visualizer to the session, if
+    /// they changed since they were recorded last
+    pub fn record_settings(&mut self, settings: &str) -> io::Result<()> {
+        if self.settings.as_deref() == Some(settings) {
+            return Ok(());
+        }
+
+        self.writer.write_all(&[SETTINGS_TAG])?;
+        self.writer
+            .write_all(&(settings.len() as u32).to_le_bytes())?;
+        self.writer.write_all(settings.as_bytes())?;
+
+        self.settings = Some(settings.to_string());
+
+        Ok(())
+    }
+
+    /// Writes the remaining buffered events to the session file
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Stores the events of a recorded session in the order they were recorded
+pub struct Session {
+    events: Vec<SessionEvent>,
+}
+
+impl Session {
+    /// Reads a session file recorded by a [`SessionRecorder`]
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let mut reader = bytes.as_slice();
+
+        let magic = read_bytes(&mut reader, SESSION_MAGIC.len())?;
+        let version = read_u32(&mut reader)?;
+
+        if magic != SESSION_MAGIC || version == 0 || version > SESSION_VERSION {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "not a supported session file",
+            ));
+        }
+
+        let mut events = Vec::new();
+
+        while let Some((&tag, rest)) = reader.split_first() {
+            reader = rest;
+
+            events.push(match tag {
+                SAMPLES_TAG => {
+                    let sample_rate = read_bytes(&mut reader, 8)?;
+                    let len = read_u32(&mut reader)? as usize;
+                    let bytes = read_bytes(&mut reader, len.saturating_mul(4))?;
+
+                    SessionEvent::Samples {
+                        sample_rate: f64::from_le_bytes(sample_rate.try_into().unwrap()),
+                        samples: bytes
+                            .chunks_exact(4)
+                            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+                            .collect(),
+                    }
+                }
+                VISUALIZER_TAG => SessionEvent::Visualizer(read_string(&mut reader)?),
+                SETTINGS_TAG => SessionEvent::Settings(read_string(&mut reader)?),
+                _ => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "unknown session event",
+                    ))
+                }
+            });
+        }
+
+        Ok(Self { events })
+    }
+
+    /// Gets the recorded events
+    pub fn events(&self) -> &[SessionEvent] {
+        &self.events
+    }
+
+    /// Gets the amount of recorded audio in seconds
+    pub fn duration(&self) -> f64 {
+        self.events
+            .iter()
+            .map(|event| match event {
+                SessionEvent::Samples {
+                    sample_rate,
+                    samples,
+                } if *sample_rate > 0.0 => samples.len() as f64 / sample_rate,
+                _ => 0.0,
+            })
+            .sum()
+    }
+}
+
+/// Splits the given amount of bytes off the reader. Lengths beyond the end of
+/// the file are rejected before anything is allocated, so corrupt files can't
+/// exhaust the memory.
+fn read_bytes<'a>(reader: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if len > reader.len() {
+        return Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "truncated session file",
+        ));
+    }
+
+    let (bytes, rest) = reader.split_at(len);
+    *reader = rest;
+
+    Ok(bytes)
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(
+        read_bytes(reader, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn read_string(reader: &mut &[u8]) -> io::Result<String> {
+    let len = read_u32(reader)? as usize;
+
+    String::from_utf8(read_bytes(reader, len)?.to_vec())
+        .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+}
+
+/// A [`OnlineSampleSource`] which replays the sessions recorded into a
+/// directory. One recorded batch of samples is returned per frame, so the
+/// analysis sees exactly the same input as while recording. The session is
+/// looped when its end is reached.
+pub struct SessionSampleSource {
+    directory: PathBuf,
+    session_paths: Vec<PathBuf>,
+    session_path: Option<PathBuf>,
+    session: Option<Session>,
+    position: usize,
+    sample_rate: Option<f64>,
+    requested_settings: Option<String>,
+}
+
+impl SessionSampleSource {
+    /// Creates a new instance listing the sessions in the given directory
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            session_paths: Vec::new(),
+            session_path: None,
+            session: None,
+            position: 0,
+            sample_rate: None,
+            requested_settings: None,
+        }
+    }
+
+    /// Gets the directory the sessions are listed from
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Gets the currently replayed session
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    fn update_session_paths(&mut self) {
+        self.session_paths = fs::read_dir(&self.directory)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |ext| ext == SESSION_EXTENSION)
+            })
+            .collect();
+
+        self.session_paths.sort();
+    }
+
+    fn update_session(&mut self) {
+        self.position = 0;
+        self.sample_rate = None;
+        self.requested_settings = None;
+        self.session = self.session_path.as_ref().and_then(|path| {
+            Session::open(path)
+                .map_err(|error| eprintln!("Failed to open session {:?}: {}", path, error))
+                .ok()
+        });
+    }
+}
+
+impl OnlineSampleSource for SessionSampleSource {
+    fn samples(&mut self) -> Samples {
+        if let Some(session) = &self.session {
+            let events = session.events();
+
+            for _ in 0..events.len() {
+                if self.position >= events.len() {
+                    self.position = 0;
+                }
+
+                let event = &events[self.position];
+                self.position += 1;
+
+                if let SessionEvent::Samples {
+                    sample_rate,
+                    samples,
+                } = event
+                {
+                    self.sample_rate = Some(*sample_rate);
+
+                    return Samples {
+                        sample_rate: *sample_rate,
+                        samples,
+                    };
+                }
+            }
+        }
+
+        Samples {
+            sample_rate: 44100.0,
+            samples: &[],
+        }
+    }
+
+    fn requested_visualizer(&mut self) -> Option<String> {
+        let events = self.session.as_ref()?.events();
+
+        if self.position >= events.len() {
+            self.position = 0;
+        }
+
+        let mut requested = None;
+
+        loop {
+            match events.get(self.position) {
+                Some(SessionEvent::Visualizer(name)) => {
+                    requested = Some(name.clone());
+                    self.requested_settings = None;
+                }
+                Some(SessionEvent::Settings(settings)) => {
+                    self.requested_settings = Some(settings.clone());
+                }
+                _ => break,
+            }
+
+            self.position += 1;
+        }
+
+        requested
+    }
+
+    fn requested_settings(&mut self) -> Option<String> {
+        self.requested_settings.take()
+    }
+
+    fn unfocus(&mut self) {
+        self.session = None;
+    }
+
+    fn status(&self) -> Option<SampleSourceStatus> {
+        // the recorded batches are replayed as they are, so gaps of the
+        // recording are no underruns of the replay
+        Some(SampleSourceStatus {
+            sample_rate: self.sample_rate,
+            ..Default::default()
+        })
+    }
+
+    fn focus(&mut self) {
+        self.update_session_paths();
+        self.update_session();
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        Grid::new("Session Sample Source Settings")
+            .num_columns(2)
+            .striped(true)
+            .min_col_width(72.0)
+            .show(ui, |ui| {
+                let session_name = self
+                    .session_path
+                    .as_ref()
+                    .and_then(|path| path.file_stem())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                let old_session_path = self.session_path.clone();
+
+                ui.label("Session:");
+                ComboBox::from_id_source("Session Selector")
+                    .selected_text(session_name)
+                    .width(168.0)
+                    .show_ui(ui, |ui| {
+                        for path in &self.session_paths {
+                            let name = path
+                                .file_stem()
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            ui.selectable_value(&mut self.session_path, Some(path.clone()), name);
+                        }
+                    });
+                ui.end_row();
+
+                if let Some(session) = &self.session {
+                    ui.label("Length:");
+                    ui.label(format!("{:.1} s", session.duration()));
+                    ui.end_row();
+                }
+
+                if old_session_path != self.session_path {
+                    self.update_session()
+                }
+            });
+    }
+}
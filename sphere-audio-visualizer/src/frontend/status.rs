@@ -0,0 +1,144 @@
+use std::time::Instant;
+
+use egui::{Color32, Ui};
+
+/// Defines the default time in seconds without samples after which an
+/// [`UnderrunCounter`] counts a buffer underrun
+pub const UNDERRUN_TIME: f64 = 0.25;
+
+/// The time in seconds the indicator stays highlighted after an underrun or
+/// an overrun
+const WARNING_TIME: f64 = 2.0;
+
+/// Describes the health of an [`OnlineSampleSource`], which is shown next to
+/// the sample source selector. It helps to find out why the spheres froze.
+///
+/// [`OnlineSampleSource`]: super::OnlineSampleSource
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SampleSourceStatus {
+    /// Represents how often the sample source delivered no samples for a
+    /// while, e.g. because the device stalled
+    pub underruns: u64,
+    /// Represents how often samples were dropped, because they were not
+    /// requested fast enough
+    pub overruns: u64,
+    /// Represents the sample rate of the delivered samples, if there are any
+    pub sample_rate: Option<f64>,
+    /// Represents if the device of the sample source got lost, e.g. because
+    /// it was unplugged
+    pub device_lost: bool,
+}
+
+/// Counts the buffer underruns of a sample source from the sizes of the
+/// batches it delivers. An underrun is counted once per gap in which no
+/// samples were delivered for longer than the underrun time.
+pub struct UnderrunCounter {
+    underrun_time: f64,
+    last_samples: Option<Instant>,
+    stalled: bool,
+    underruns: u64,
+}
+
+impl UnderrunCounter {
+    /// Creates a new instance with the default underrun time
+    pub fn new() -> Self {
+        Self {
+            underrun_time: UNDERRUN_TIME,
+            last_samples: None,
+            stalled: false,
+            underruns: 0,
+        }
+    }
+
+    /// Builder method for setting the time in seconds without samples after
+    /// which an underrun is counted
+    pub fn with_underrun_time(mut self, underrun_time: f64) -> Self {
+        self.underrun_time = underrun_time;
+        self
+    }
+
+    /// Records a batch of samples. Gaps before the first batch are not
+    /// counted, as devices take a while to start.
+    pub fn record(&mut self, sample_count: usize) {
+        let now = Instant::now();
+
+        if sample_count > 0 {
+            self.last_samples = Some(now);
+            self.stalled = false;
+        } else if let Some(last_samples) = self.last_samples {
+            if !self.stalled && (now - last_samples).as_secs_f64() > self.underrun_time {
+                self.stalled = true;
+                self.underruns += 1;
+            }
+        }
+    }
+
+    /// Forgets the last batch, e.g. because the sample source was paused.
+    /// The counted underruns are kept.
+    pub fn pause(&mut self) {
+        self.last_samples = None;
+        self.stalled = false;
+    }
+
+    /// Returns the number of counted underruns
+    pub fn underruns(&self) -> u64 {
+        self.underruns
+    }
+}
+
+impl Default for UnderrunCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Draws the [`SampleSourceStatus`] as small indicators. The indicators are
+/// highlighted for a while once the underruns or overruns increased. It is
+/// reset when another sample source is selected, so their counters are not
+/// compared.
+#[derive(Default)]
+pub(crate) struct StatusIndicator {
+    last_status: Option<SampleSourceStatus>,
+    last_warning: Option<Instant>,
+}
+
+impl StatusIndicator {
+    pub(crate) fn ui(&mut self, status: Option<SampleSourceStatus>, ui: &mut Ui) {
+        let status = match status {
+            Some(status) => status,
+            None => {
+                self.last_status = None;
+                return;
+            }
+        };
+
+        // sample sources without a status before start without a warning
+        if let Some(last_status) = self.last_status {
+            if status.underruns > last_status.underruns || status.overruns > last_status.overruns {
+                self.last_warning = Some(Instant::now());
+            }
+        }
+        self.last_status = Some(status);
+
+        let warning = self.last_warning.map_or(false, |last_warning| {
+            last_warning.elapsed().as_secs_f64() < WARNING_TIME
+        });
+
+        let (color, state) = match (status.device_lost, warning) {
+            (true, _) => (Color32::RED, "Device lost"),
+            (false, true) => (Color32::YELLOW, "Dropouts"),
+            (false, false) => (Color32::GREEN, "Healthy"),
+        };
+
+        let sample_rate = match status.sample_rate {
+            Some(sample_rate) => format!("{:.1} kHz", sample_rate / 1000.0),
+            None => "No signal".to_string(),
+        };
+
+        ui.colored_label(color, "⏺").on_hover_text(format!(
+            "{}\nUnderruns: {}\nOverruns: {}",
+            state, status.underruns, status.overruns
+        ));
+        ui.weak(sample_rate);
+    }
+}
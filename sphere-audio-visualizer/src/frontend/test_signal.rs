@@ -0,0 +1,409 @@
+use std::{f64::consts::TAU, time::Instant};
+
+use egui::{ComboBox, DragValue, Grid, Ui};
+use sphere_audio_visualizer_core::utils::random::random;
+
+use super::{OnlineSampleSource, SampleSourceStatus};
+use crate::audio_analysis::Samples;
+
+/// Defines the sample rate of generated test signals
+pub const TEST_SIGNAL_SAMPLE_RATE: f64 = 44100.0;
+
+/// Defines the default peak amplitude of generated test signals
+pub const TEST_SIGNAL_LEVEL: f32 = 0.5;
+
+/// Defines the default lowest frequency of the sine sweep in Hz
+pub const SWEEP_LOW: f64 = 20.0;
+
+/// Defines the default highest frequency of the sine sweep in Hz
+pub const SWEEP_HIGH: f64 = 20000.0;
+
+/// Defines the default time in seconds the sine sweep takes from the lowest
+/// to the highest frequency
+pub const SWEEP_DURATION: f64 = 10.0;
+
+/// Defines the default tempo of the metronome in beats per minute
+pub const METRONOME_BPM: f64 = 120.0;
+
+/// Defines the time in seconds a metronome click takes to decay
+const CLICK_DECAY: f64 = 0.01;
+
+/// Defines the number of beats per bar of the metronome. The first beat of
+/// every bar is accented with a higher click.
+const BEATS_PER_BAR: u64 = 4;
+
+/// The longest time in seconds generated at once, so the source does not
+/// catch up on the whole time it was stalled
+const MAX_BATCH_DURATION: f64 = 0.1;
+
+/// Specifies the waveform of a [`TestSignal`]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TestSignalKind {
+    /// A sine rising exponentially in frequency, so every octave takes the
+    /// same time. The sweep restarts after reaching the highest frequency.
+    SineSweep,
+    /// Noise with equal energy per octave
+    PinkNoise,
+    /// Short clicks on every beat with an accented first beat per bar
+    Metronome,
+}
+
+impl TestSignalKind {
+    fn display_name(&self) -> &'static str {
+        match self {
+            TestSignalKind::SineSweep => "Sine Sweep",
+            TestSignalKind::PinkNoise => "Pink Noise",
+            TestSignalKind::Metronome => "Metronome",
+        }
+    }
+}
+
+/// Generates synthetic audio, which allows to tune the analysis and the
+/// visualizers without external audio. The generated samples only depend on
+/// the settings and the number of generated samples, so runs are
+/// reproducible.
+///
+/// ```
+/// use sphere_audio_visualizer::{TestSignal, TestSignalKind};
+///
+/// let mut a = [0.0; 256];
+/// let mut b = [0.0; 256];
+///
+/// TestSignal::new(TestSignalKind::PinkNoise).generate(&mut a);
+/// TestSignal::new(TestSignalKind::PinkNoise).generate(&mut b);
+///
+/// assert_eq!(a, b);
+/// ```
+pub struct TestSignal {
+    kind: TestSignalKind,
+    level: f32,
+    sweep_low: f64,
+    sweep_high: f64,
+    sweep_duration: f64,
+    bpm: f64,
+    position: u64,
+    phase: f64,
+    pink: [f32; 7],
+}
+
+impl TestSignal {
+    /// Creates a new instance generating the given waveform
+    pub fn new(kind: TestSignalKind) -> Self {
+        Self {
+            kind,
+            level: TEST_SIGNAL_LEVEL,
+            sweep_low: SWEEP_LOW,
+            sweep_high: SWEEP_HIGH,
+            sweep_duration: SWEEP_DURATION,
+            bpm: METRONOME_BPM,
+            position: 0,
+            phase: 0.0,
+            pink: [0.0; 7],
+        }
+    }
+
+    /// Returns the generated waveform
+    pub fn kind(&self) -> TestSignalKind {
+        self.kind
+    }
+
+    /// Sets the generated waveform and restarts the signal
+    pub fn set_kind(&mut self, kind: TestSignalKind) -> &mut Self {
+        self.kind = kind;
+        self.reset()
+    }
+
+    /// Sets the generated waveform and restarts the signal
+    pub fn with_kind(mut self, kind: TestSignalKind) -> Self {
+        self.set_kind(kind);
+        self
+    }
+
+    /// Returns the peak amplitude
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Sets the peak amplitude
+    pub fn set_level(&mut self, level: f32) -> &mut Self {
+        self.level = level.max(0.0).min(1.0);
+        self
+    }
+
+    /// Sets the peak amplitude
+    pub fn with_level(mut self, level: f32) -> Self {
+        self.set_level(level);
+        self
+    }
+
+    /// Returns the lowest and highest frequency of the sine sweep in Hz
+    pub fn sweep_range(&self) -> (f64, f64) {
+        (self.sweep_low, self.sweep_high)
+    }
+
+    /// Sets the lowest and highest frequency of the sine sweep in Hz
+    pub fn set_sweep_range(&mut self, low: f64, high: f64) -> &mut Self {
+        self.sweep_low = low.max(1.0);
+        self.sweep_high = high.max(self.sweep_low);
+        self
+    }
+
+    /// Sets the lowest and highest frequency of the sine sweep in Hz
+    pub fn with_sweep_range(mut self, low: f64, high: f64) -> Self {
+        self.set_sweep_range(low, high);
+        self
+    }
+
+    /// Returns the time in seconds the sine sweep takes from the lowest to
+    /// the highest frequency
+    pub fn sweep_duration(&self) -> f64 {
+        self.sweep_duration
+    }
+
+    /// Sets the time in seconds the sine sweep takes from the lowest to the
+    /// highest frequency
+    pub fn set_sweep_duration(&mut self, sweep_duration: f64) -> &mut Self {
+        self.sweep_duration = sweep_duration.max(0.1);
+        self
+    }
+
+    /// Sets the time in seconds the sine sweep takes from the lowest to the
+    /// highest frequency
+    pub fn with_sweep_duration(mut self, sweep_duration: f64) -> Self {
+        self.set_sweep_duration(sweep_duration);
+        self
+    }
+
+    /// Returns the tempo of the metronome in beats per minute
+    pub fn bpm(&self) -> f64 {
+        self.bpm
+    }
+
+    /// Sets the tempo of the metronome in beats per minute
+    pub fn set_bpm(&mut self, bpm: f64) -> &mut Self {
+        self.bpm = bpm.max(1.0);
+        self
+    }
+
+    /// Sets the tempo of the metronome in beats per minute
+    pub fn with_bpm(mut self, bpm: f64) -> Self {
+        self.set_bpm(bpm);
+        self
+    }
+
+    /// Restarts the signal from the beginning
+    pub fn reset(&mut self) -> &mut Self {
+        self.position = 0;
+        self.phase = 0.0;
+        self.pink = [0.0; 7];
+        self
+    }
+
+    /// Fills the buffer with the next samples of the signal at
+    /// [`TEST_SIGNAL_SAMPLE_RATE`]
+    pub fn generate(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            *sample = self.next_sample() * self.level;
+            self.position += 1;
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let time = self.position as f64 / TEST_SIGNAL_SAMPLE_RATE;
+
+        match self.kind {
+            TestSignalKind::SineSweep => {
+                let progress = time % self.sweep_duration / self.sweep_duration;
+                let frequency = self.sweep_low * (self.sweep_high / self.sweep_low).powf(progress);
+
+                let sample = self.phase.sin();
+                self.phase = (self.phase + TAU * frequency / TEST_SIGNAL_SAMPLE_RATE) % TAU;
+
+                sample as f32
+            }
+            TestSignalKind::PinkNoise => {
+                let white =
+                    random(0, self.position as u32, (self.position >> 32) as u32, 0) * 2.0 - 1.0;
+
+                // filters white noise into pink noise, see
+                // https://www.firstpr.com.au/dsp/pink-noise/
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.1538520;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+
+                (pink * 0.11).max(-1.0).min(1.0)
+            }
+            TestSignalKind::Metronome => {
+                let beat_duration = 60.0 / self.bpm;
+                let beat = (time / beat_duration) as u64;
+                let since_beat = time - beat as f64 * beat_duration;
+
+                let frequency = match beat % BEATS_PER_BAR {
+                    0 => 2000.0,
+                    _ => 1000.0,
+                };
+
+                ((TAU * frequency * since_beat).sin() * (-since_beat / CLICK_DECAY).exp()) as f32
+            }
+        }
+    }
+}
+
+/// An [`OnlineSampleSource`] playing a [`TestSignal`] in real time
+pub struct TestSignalSampleSource {
+    signal: TestSignal,
+    samples: Vec<f32>,
+    last_batch: Option<Instant>,
+}
+
+impl TestSignalSampleSource {
+    /// Creates a new instance playing the given signal
+    pub fn new(signal: TestSignal) -> Self {
+        Self {
+            signal,
+            samples: Vec::new(),
+            last_batch: None,
+        }
+    }
+
+    /// Returns the played signal
+    pub fn signal(&mut self) -> &mut TestSignal {
+        &mut self.signal
+    }
+}
+
+impl Default for TestSignalSampleSource {
+    fn default() -> Self {
+        Self::new(TestSignal::new(TestSignalKind::SineSweep))
+    }
+}
+
+impl OnlineSampleSource for TestSignalSampleSource {
+    fn samples(&mut self) -> Samples<'_> {
+        let now = Instant::now();
+        let duration = self
+            .last_batch
+            .map(|last_batch| (now - last_batch).as_secs_f64())
+            .unwrap_or_default()
+            .min(MAX_BATCH_DURATION);
+        self.last_batch = Some(now);
+
+        self.samples
+            .resize((duration * TEST_SIGNAL_SAMPLE_RATE) as usize, 0.0);
+        self.signal.generate(&mut self.samples);
+
+        Samples {
+            sample_rate: TEST_SIGNAL_SAMPLE_RATE,
+            samples: &self.samples,
+        }
+    }
+
+    fn focus(&mut self) {
+        self.last_batch = None;
+    }
+
+    fn unfocus(&mut self) {
+        self.last_batch = None;
+    }
+
+    fn status(&self) -> Option<SampleSourceStatus> {
+        // the signal is generated on demand, so it never runs dry
+        Some(SampleSourceStatus {
+            sample_rate: Some(TEST_SIGNAL_SAMPLE_RATE),
+            ..Default::default()
+        })
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        Grid::new("Test Signal Sample Source Settings")
+            .num_columns(2)
+            .striped(true)
+            .min_col_width(72.0)
+            .show(ui, |ui| {
+                let mut kind = self.signal.kind();
+
+                ui.label("Signal:");
+                ComboBox::from_id_source("Test Signal Selector")
+                    .selected_text(kind.display_name())
+                    .width(168.0)
+                    .show_ui(ui, |ui| {
+                        for option in [
+                            TestSignalKind::SineSweep,
+                            TestSignalKind::PinkNoise,
+                            TestSignalKind::Metronome,
+                        ] {
+                            ui.selectable_value(&mut kind, option, option.display_name());
+                        }
+                    });
+                ui.end_row();
+
+                if kind != self.signal.kind() {
+                    self.signal.set_kind(kind);
+                }
+
+                let mut level = self.signal.level();
+                ui.label("Level:");
+                ui.add_sized(
+                    [176.0, 20.0],
+                    DragValue::new(&mut level)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0),
+                );
+                ui.end_row();
+                self.signal.set_level(level);
+
+                match kind {
+                    TestSignalKind::SineSweep => {
+                        let (mut low, mut high) = self.signal.sweep_range();
+                        ui.label("From:");
+                        ui.add_sized(
+                            [176.0, 20.0],
+                            DragValue::new(&mut low)
+                                .clamp_range(1.0..=high)
+                                .suffix(" Hz"),
+                        );
+                        ui.end_row();
+
+                        ui.label("To:");
+                        ui.add_sized(
+                            [176.0, 20.0],
+                            DragValue::new(&mut high)
+                                .clamp_range(low..=TEST_SIGNAL_SAMPLE_RATE / 2.0)
+                                .suffix(" Hz"),
+                        );
+                        ui.end_row();
+                        self.signal.set_sweep_range(low, high);
+
+                        let mut sweep_duration = self.signal.sweep_duration();
+                        ui.label("Duration:");
+                        ui.add_sized(
+                            [176.0, 20.0],
+                            DragValue::new(&mut sweep_duration)
+                                .speed(0.1)
+                                .clamp_range(0.1..=f64::MAX)
+                                .suffix(" s"),
+                        );
+                        ui.end_row();
+                        self.signal.set_sweep_duration(sweep_duration);
+                    }
+                    TestSignalKind::PinkNoise => {}
+                    TestSignalKind::Metronome => {
+                        let mut bpm = self.signal.bpm();
+                        ui.label("BPM:");
+                        ui.add_sized(
+                            [176.0, 20.0],
+                            DragValue::new(&mut bpm).clamp_range(1.0..=400.0),
+                        );
+                        ui.end_row();
+                        self.signal.set_bpm(bpm);
+                    }
+                }
+            });
+    }
+}
@@ -1,3 +1,5 @@
+use std::{any::TypeId, collections::HashMap};
+
 use crate::utils::TypeMap;
 
 /// The [`Module`] trait is used by different visualizer pipelines as pipline
@@ -25,10 +27,19 @@ pub trait Module: Default + Send + Sync {
     fn settings(&self) -> Self::Settings;
 }
 
+/// Stores the settings bins of the individual scopes inside the shared
+/// settings bin
+#[derive(Default)]
+struct ScopedSettingsBins(HashMap<TypeId, TypeMap>);
+
 /// Stores module settings and modules for recycling.
+/// Settings of modules inserted with [`ModuleManager::insert_scoped`] are
+/// additionally stored per scope, e.g. per visualizer configuration, so
+/// every scope can keep its own copy.
 pub struct ModuleManager<'a> {
     module_bin: TypeMap,
     settings_bin: &'a mut TypeMap,
+    scope: Option<TypeId>,
 }
 
 impl<'a> ModuleManager<'a> {
@@ -37,18 +48,96 @@ impl<'a> ModuleManager<'a> {
         Self {
             module_bin: TypeMap::new(),
             settings_bin,
+            scope: None,
         }
     }
 
+    /// Sets the scope used by [`ModuleManager::insert_scoped`] and
+    /// [`ModuleManager::extract_scoped`]
+    pub fn set_scope(&mut self, scope: Option<TypeId>) -> &mut Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Sets the scope used by [`ModuleManager::insert_scoped`] and
+    /// [`ModuleManager::extract_scoped`]
+    pub fn with_scope(mut self, scope: Option<TypeId>) -> Self {
+        self.set_scope(scope);
+        self
+    }
+
+    fn scoped_settings_bin(&mut self) -> Option<&mut TypeMap> {
+        let scope = self.scope?;
+
+        Some(
+            self.settings_bin
+                .entry::<ScopedSettingsBins>()
+                .or_default()
+                .0
+                .entry(scope)
+                .or_insert_with(TypeMap::new),
+        )
+    }
+
     /// Insterts a module
     pub fn insert<M: Module + 'static>(&mut self, module: M)
     where
         <M as Module>::Settings: 'static,
     {
-        self.settings_bin.insert(module.settings());
+        self.insert_settings(&module);
         self.module_bin.insert(module);
     }
 
+    /// Inserts a module and additionally stores its settings in the current
+    /// scope
+    pub fn insert_scoped<M: Module + 'static>(&mut self, module: M)
+    where
+        <M as Module>::Settings: 'static,
+    {
+        self.insert_settings_scoped(&module);
+        self.module_bin.insert(module);
+    }
+
+    /// Stores the settings of a module without recycling the module itself
+    pub fn insert_settings<M: Module + 'static>(&mut self, module: &M)
+    where
+        <M as Module>::Settings: 'static,
+    {
+        self.settings_bin.insert(module.settings());
+    }
+
+    /// Stores the settings of a module like
+    /// [`ModuleManager::insert_settings`] and additionally in the current
+    /// scope
+    pub fn insert_settings_scoped<M: Module + 'static>(&mut self, module: &M)
+    where
+        <M as Module>::Settings: 'static,
+    {
+        if let Some(scoped_settings_bin) = self.scoped_settings_bin() {
+            scoped_settings_bin.insert(module.settings());
+        }
+
+        self.insert_settings(module);
+    }
+
+    /// Sets the settings stored for a module. Modules without stored settings
+    /// keep their current settings.
+    pub fn load_settings<M: Module + 'static>(&self, module: &mut M)
+    where
+        <M as Module>::Settings: 'static,
+    {
+        if let Some(settings) = self.settings_bin.get::<M::Settings>() {
+            module.set_settings(settings.clone());
+        }
+    }
+
+    /// Returns a copy of a value stored alongside the module settings, e.g.
+    /// the [`AdapterSelection`](crate::rendering::wgpu::AdapterSelection)
+    /// new renderers are created with
+    pub fn stored<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.settings_bin.get::<T>().cloned()
+    }
+
     /// Inserts a object without settings it still gets recycled but the
     /// settings are lost.
     pub fn insert_lossy<M: Send + Sync + 'static>(&mut self, module: M) {
@@ -71,6 +160,23 @@ impl<'a> ModuleManager<'a> {
         self.extract_or_default::<M>().with_settings(settings)
     }
 
+    /// Extracts a module like [`ModuleManager::extract`], but prefers the
+    /// settings stored in the current scope. A scope without settings yet
+    /// starts from the settings used last by any scope.
+    pub fn extract_scoped<M: Module + 'static>(&mut self) -> M
+    where
+        <M as Module>::Settings: 'static,
+    {
+        let settings = self
+            .scoped_settings_bin()
+            .and_then(|scoped_settings_bin| scoped_settings_bin.get::<M::Settings>().cloned());
+
+        match settings {
+            Some(settings) => self.extract_or_default::<M>().with_settings(settings),
+            None => self.extract::<M>(),
+        }
+    }
+
     /// Extracts a object. Only returns Some if the object could be recycled.
     pub fn extract_optional<M: Send + Sync + 'static>(&mut self) -> Option<M> {
         self.module_bin.remove::<M>()
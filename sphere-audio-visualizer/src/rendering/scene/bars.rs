@@ -0,0 +1,375 @@
+use std::{sync::Mutex, time::Duration};
+
+use sphere_audio_visualizer_core::glam::Vec3A;
+
+use crate::module::Module;
+
+use super::{AspectRatio, SceneConverter, Viewport};
+
+/// Defines the default fraction of the width of a bar left empty as a gap to
+/// its neighbours
+pub const BAR_GAP: f32 = 0.2;
+
+/// Defines the default color at the bottom of the bars
+pub const BAR_LOW_COLOR: Vec3A = Vec3A::new(0.0, 0.8, 0.2);
+
+/// Defines the default color at the top of the bars
+pub const BAR_HIGH_COLOR: Vec3A = Vec3A::new(1.0, 0.1, 0.0);
+
+/// Defines the default time in seconds a peak marker is held before it falls
+pub const BAR_PEAK_HOLD: f32 = 0.5;
+
+/// Defines the default speed in levels per second at which a peak marker
+/// falls
+pub const BAR_PEAK_FALL: f32 = 0.5;
+
+/// Specifies how the channels of the audio are laid out
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum BarChannels {
+    /// A single set of bars goes from bass on the left to treble on the right
+    Mono,
+    /// The channels are split into a left and a right set of bars, which are
+    /// mirrored around the center with the bass in the middle like a stereo
+    /// VU meter. The audio signal is mono, so both channels show the same
+    /// levels.
+    Stereo,
+}
+
+/// Represents a single bar of the spectrum
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Bar {
+    /// The current level of the frequency band
+    pub level: f32,
+    /// The level of the peak marker of the frequency band
+    pub peak: f32,
+}
+
+/// Stores the scene definition for the bars renderer
+pub struct BarsScene {
+    pub(crate) viewport: Viewport,
+    pub(crate) gap: f32,
+    pub(crate) low_color: Vec3A,
+    pub(crate) high_color: Vec3A,
+    pub(crate) bars: Vec<Bar>,
+}
+
+impl BarsScene {
+    /// Creates a new instance.
+    /// - `viewport` defines the area of the target the scene is rendered into
+    /// - `gap` defines the fraction of the width of a bar left empty
+    /// - `low_color` and `high_color` define the gradient along the bars
+    pub fn new(viewport: Viewport, gap: f32, low_color: Vec3A, high_color: Vec3A) -> Self {
+        Self {
+            viewport,
+            gap,
+            low_color,
+            high_color,
+            bars: Vec::new(),
+        }
+    }
+
+    /// Adds a bar to the scene
+    pub fn add_bar(&mut self, bar: Bar) -> &mut Self {
+        self.bars.push(bar);
+        self
+    }
+
+    /// Adds a bar to the scene
+    pub fn with_bar(mut self, bar: Bar) -> Self {
+        self.add_bar(bar);
+        self
+    }
+}
+
+/// Converts the levels of the spectrum to the bars renderer scene format and
+/// keeps track of the peak markers
+pub struct BarsSceneConverter {
+    aspect_ratio: AspectRatio,
+    channels: BarChannels,
+    gap: f32,
+    low_color: Vec3A,
+    high_color: Vec3A,
+    peak_hold: f32,
+    peak_fall: f32,
+    peaks: Mutex<PeakMarkers>,
+}
+
+/// The peak markers of the frequency bands, which are updated on every
+/// conversion by the simulation time advanced since the previous one
+#[derive(Default)]
+struct PeakMarkers {
+    peaks: Vec<f32>,
+    ages: Vec<f32>,
+    delta_time: f32,
+}
+
+impl BarsSceneConverter {
+    /// Returns the aspect ratio of the area the scene is rendered into
+    pub fn aspect_ratio(&self) -> AspectRatio {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: AspectRatio) -> &mut Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    /// Returns how the channels of the audio are laid out
+    pub fn channels(&self) -> BarChannels {
+        self.channels
+    }
+
+    /// Sets how the channels of the audio are laid out
+    pub fn set_channels(&mut self, channels: BarChannels) -> &mut Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Sets how the channels of the audio are laid out
+    pub fn with_channels(mut self, channels: BarChannels) -> Self {
+        self.set_channels(channels);
+        self
+    }
+
+    /// Returns the fraction of the width of a bar left empty as a gap
+    pub fn gap(&self) -> f32 {
+        self.gap
+    }
+
+    /// Sets the fraction of the width of a bar left empty as a gap
+    pub fn set_gap(&mut self, gap: f32) -> &mut Self {
+        self.gap = gap.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the fraction of the width of a bar left empty as a gap
+    pub fn with_gap(mut self, gap: f32) -> Self {
+        self.set_gap(gap);
+        self
+    }
+
+    /// Returns the color at the bottom of the bars
+    pub fn low_color(&self) -> Vec3A {
+        self.low_color
+    }
+
+    /// Sets the color at the bottom of the bars
+    pub fn set_low_color(&mut self, low_color: Vec3A) -> &mut Self {
+        self.low_color = low_color;
+        self
+    }
+
+    /// Sets the color at the bottom of the bars
+    pub fn with_low_color(mut self, low_color: Vec3A) -> Self {
+        self.set_low_color(low_color);
+        self
+    }
+
+    /// Returns the color at the top of the bars
+    pub fn high_color(&self) -> Vec3A {
+        self.high_color
+    }
+
+    /// Sets the color at the top of the bars
+    pub fn set_high_color(&mut self, high_color: Vec3A) -> &mut Self {
+        self.high_color = high_color;
+        self
+    }
+
+    /// Sets the color at the top of the bars
+    pub fn with_high_color(mut self, high_color: Vec3A) -> Self {
+        self.set_high_color(high_color);
+        self
+    }
+
+    /// Returns the time in seconds a peak marker is held before it falls
+    pub fn peak_hold(&self) -> f32 {
+        self.peak_hold
+    }
+
+    /// Sets the time in seconds a peak marker is held before it falls
+    pub fn set_peak_hold(&mut self, peak_hold: f32) -> &mut Self {
+        self.peak_hold = peak_hold.max(0.0);
+        self
+    }
+
+    /// Sets the time in seconds a peak marker is held before it falls
+    pub fn with_peak_hold(mut self, peak_hold: f32) -> Self {
+        self.set_peak_hold(peak_hold);
+        self
+    }
+
+    /// Returns the speed in levels per second at which a peak marker falls
+    pub fn peak_fall(&self) -> f32 {
+        self.peak_fall
+    }
+
+    /// Sets the speed in levels per second at which a peak marker falls
+    pub fn set_peak_fall(&mut self, peak_fall: f32) -> &mut Self {
+        self.peak_fall = peak_fall.max(0.0);
+        self
+    }
+
+    /// Sets the speed in levels per second at which a peak marker falls
+    pub fn with_peak_fall(mut self, peak_fall: f32) -> Self {
+        self.set_peak_fall(peak_fall);
+        self
+    }
+
+    /// Returns the bars of the given levels after updating their peak
+    /// markers
+    fn bars(&self, levels: impl IntoIterator<Item = f32>) -> Vec<Bar> {
+        let mut peak_markers = self.peaks.lock().unwrap();
+        let PeakMarkers {
+            peaks,
+            ages,
+            delta_time,
+        } = &mut *peak_markers;
+        let delta_time = std::mem::take(delta_time);
+
+        let levels = levels
+            .into_iter()
+            .map(|level| level.clamp(0.0, 1.0))
+            .collect::<Vec<_>>();
+
+        peaks.resize(levels.len(), 0.0);
+        ages.resize(levels.len(), 0.0);
+
+        levels
+            .into_iter()
+            .zip(peaks.iter_mut().zip(ages.iter_mut()))
+            .map(|(level, (peak, age))| {
+                if level >= *peak {
+                    *peak = level;
+                    *age = 0.0;
+                } else {
+                    *age += delta_time;
+
+                    if *age > self.peak_hold {
+                        *peak = (*peak - self.peak_fall * delta_time).max(level);
+                    }
+                }
+
+                Bar { level, peak: *peak }
+            })
+            .collect()
+    }
+}
+
+impl Default for BarsSceneConverter {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            channels: BarChannels::Stereo,
+            gap: BAR_GAP,
+            low_color: BAR_LOW_COLOR,
+            high_color: BAR_HIGH_COLOR,
+            peak_hold: BAR_PEAK_HOLD,
+            peak_fall: BAR_PEAK_FALL,
+            peaks: Mutex::new(PeakMarkers::default()),
+        }
+    }
+}
+
+impl<S: IntoIterator<Item = f32>> SceneConverter<S> for BarsSceneConverter {
+    type Scene = BarsScene;
+
+    fn convert(&self, levels: S, width: f32, height: f32) -> Self::Scene {
+        let viewport = Viewport::new(width, height, self.aspect_ratio);
+
+        let mut scene = BarsScene::new(viewport, self.gap, self.low_color, self.high_color);
+
+        let bars = self.bars(levels);
+
+        // the left channel is mirrored, so the bass of both channels meets in
+        // the center
+        if self.channels == BarChannels::Stereo {
+            for bar in bars.iter().rev() {
+                scene.add_bar(*bar);
+            }
+        }
+
+        for bar in bars {
+            scene.add_bar(bar);
+        }
+
+        scene
+    }
+
+    fn advance(&mut self, delta_time: Duration) {
+        self.peaks.get_mut().unwrap().delta_time += delta_time.as_secs_f32();
+    }
+}
+
+impl Module for BarsSceneConverter {
+    type Settings = BarsSceneConverterSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_aspect_ratio(settings.aspect_ratio)
+            .set_channels(settings.channels)
+            .set_gap(settings.gap)
+            .set_low_color(settings.low_color)
+            .set_high_color(settings.high_color)
+            .set_peak_hold(settings.peak_hold)
+            .set_peak_fall(settings.peak_fall)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        BarsSceneConverterSettings {
+            aspect_ratio: self.aspect_ratio(),
+            channels: self.channels(),
+            gap: self.gap(),
+            low_color: self.low_color(),
+            high_color: self.high_color(),
+            peak_hold: self.peak_hold(),
+            peak_fall: self.peak_fall(),
+        }
+    }
+}
+
+/// Stores the settings of the [`BarsSceneConverter`]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct BarsSceneConverterSettings {
+    /// The aspect ratio of the area the scene is rendered into
+    pub aspect_ratio: AspectRatio,
+    /// How the channels of the audio are laid out
+    pub channels: BarChannels,
+    /// The fraction of the width of a bar left empty as a gap
+    pub gap: f32,
+    /// The color at the bottom of the bars
+    pub low_color: Vec3A,
+    /// The color at the top of the bars
+    pub high_color: Vec3A,
+    /// The time in seconds a peak marker is held before it falls
+    pub peak_hold: f32,
+    /// The speed in levels per second at which a peak marker falls
+    pub peak_fall: f32,
+}
+
+impl Default for BarsSceneConverterSettings {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            channels: BarChannels::Stereo,
+            gap: BAR_GAP,
+            low_color: BAR_LOW_COLOR,
+            high_color: BAR_HIGH_COLOR,
+            peak_hold: BAR_PEAK_HOLD,
+            peak_fall: BAR_PEAK_FALL,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use sphere_audio_visualizer_core::{
+    glam::Vec3A,
+    raytracing::shape::{BvhNode, Shape, AABB},
+};
+
+/// Defines the maximum number of shapes in a leaf of the bounding volume
+/// hierarchy
+pub const BVH_LEAF_SIZE: usize = 4;
+
+/// Builds a flattened bounding volume hierarchy over the shapes. The shapes
+/// are reordered, so every leaf covers a consecutive range of them. Inner
+/// nodes split their shapes at the median along the longest axis of the
+/// shape centers.
+///
+/// ```
+/// use sphere_audio_visualizer::rendering::build_bvh;
+/// use sphere_audio_visualizer_core::{glam::vec3a, raytracing::shape::Sphere};
+///
+/// let mut spheres = (0..16)
+///     .map(|i| Sphere::new(vec3a(i as f32, 0.0, 0.0), 0.5, 0))
+///     .collect::<Vec<_>>();
+///
+/// let nodes = build_bvh(&mut spheres);
+///
+/// assert_eq!(nodes[0].skip(), nodes.len());
+/// assert_eq!(nodes.iter().map(|node| node.count()).sum::<usize>(), 16);
+/// ```
+///
+/// The hierarchy finds the same hits as testing every shape, which is done
+/// when the node count is zero:
+///
+/// ```
+/// use sphere_audio_visualizer::rendering::build_bvh;
+/// use sphere_audio_visualizer_core::{
+///     glam::vec3a,
+///     raytracing::{
+///         shape::{BoundingBoxGroup, BvhGroup, Material, Shape, ShapeGroup, Sphere, AABB},
+///         Ray,
+///     },
+/// };
+///
+/// let mut spheres = (0..64)
+///     .map(|i| {
+///         let position = vec3a((i % 8) as f32, (i / 8) as f32, (i * 7 % 5) as f32);
+///         Sphere::new(position, 0.2 + (i % 3) as f32 * 0.15, 0)
+///     })
+///     .collect::<Vec<_>>();
+///
+/// let nodes = build_bvh(&mut spheres);
+///
+/// let materials = [Material::new(vec3a(1.0, 1.0, 1.0))];
+/// let bounding_box = spheres.iter().fold(AABB::empty(), |bounding_box, sphere| {
+///     bounding_box.with_aabb(&sphere.bounding_box())
+/// });
+///
+/// let group = || BoundingBoxGroup::new(&spheres, &materials, bounding_box.clone());
+/// let hierarchy = BvhGroup::new(group(), &nodes, nodes.len());
+/// let linear = BvhGroup::new(group(), &nodes, 0);
+///
+/// for x in 0..32 {
+///     for y in 0..32 {
+///         let direction = vec3a(x as f32 - 15.5, y as f32 - 15.5, 40.0).normalize();
+///         let ray = Ray::new(vec3a(3.5, 3.5, -10.0), direction, 0.0, f32::INFINITY);
+///
+///         let hit = |group: &BvhGroup<Sphere>| {
+///             group
+///                 .intersect(&ray)
+///                 .map_or_else(|hit| Some((hit.id(), hit.hit())), || None)
+///         };
+///
+///         assert_eq!(hit(&hierarchy), hit(&linear));
+///     }
+/// }
+/// ```
+pub fn build_bvh<S: Shape>(shapes: &mut [S]) -> Vec<BvhNode> {
+    let mut nodes = Vec::new();
+
+    if !shapes.is_empty() {
+        build_node(shapes, 0, &mut nodes);
+    }
+
+    nodes
+}
+
+fn build_node<S: Shape>(shapes: &mut [S], first: usize, nodes: &mut Vec<BvhNode>) {
+    let index = nodes.len();
+
+    let bounding_box = shapes.iter().fold(AABB::empty(), |bounding_box, shape| {
+        bounding_box.with_aabb(&shape.bounding_box())
+    });
+
+    if shapes.len() <= BVH_LEAF_SIZE {
+        nodes.push(BvhNode::leaf(
+            bounding_box,
+            index as u32 + 1,
+            first as u32,
+            shapes.len() as u32,
+        ));
+        return;
+    }
+
+    let centers = shapes.iter().fold(AABB::empty(), |centers, shape| {
+        centers.with_point(center(shape))
+    });
+
+    let extent = centers.max() - centers.min();
+    let axis = if extent.x >= extent.y && extent.x >= extent.z {
+        0
+    } else if extent.y >= extent.z {
+        1
+    } else {
+        2
+    };
+
+    let middle = shapes.len() / 2;
+
+    shapes.select_nth_unstable_by(middle, |a, b| center(a)[axis].total_cmp(&center(b)[axis]));
+
+    // the skip index is only known once the subtree is built
+    nodes.push(BvhNode::inner(bounding_box.clone(), 0));
+
+    let (left, right) = shapes.split_at_mut(middle);
+
+    build_node(left, first, nodes);
+    build_node(right, first + middle, nodes);
+
+    nodes[index] = BvhNode::inner(bounding_box, nodes.len() as u32);
+}
+
+fn center<S: Shape>(shape: &S) -> Vec3A {
+    let bounding_box = shape.bounding_box();
+
+    (bounding_box.min() + bounding_box.max()) * 0.5
+}
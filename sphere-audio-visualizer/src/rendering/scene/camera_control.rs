@@ -0,0 +1,51 @@
+use sphere_audio_visualizer_core::glam::{Mat4, Vec2, Vec3};
+
+/// Describes how a manually controlled camera, e.g. flown with a gamepad or
+/// orbited with the mouse, deviates from the camera of a scene converter. The
+/// default leaves the camera of the converter unchanged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraControl {
+    /// The translation of the camera in camera space
+    pub translation: Vec3,
+    /// The rotation around the vertical axis in radians. Positive values
+    /// look to the right.
+    pub yaw: f32,
+    /// The rotation around the horizontal axis in radians. Positive values
+    /// look up.
+    pub pitch: f32,
+    /// The rotation of the camera around the origin of the scene in radians.
+    /// Positive values move the camera to the left and up.
+    pub orbit: Vec2,
+    /// The factor the view is magnified by. 3D cameras move towards the
+    /// origin of the scene instead.
+    pub zoom: f32,
+    /// The offset of the view of 2D scenes in scene units
+    pub pan: Vec2,
+}
+
+impl CameraControl {
+    /// Applies the control to the transform of a camera in world space
+    pub fn apply(&self, transform: Mat4) -> Mat4 {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let orbit = Mat4::from_rotation_y(self.orbit.x) * Mat4::from_rotation_x(self.orbit.y);
+
+        orbit
+            * Mat4::from_scale_rotation_translation(scale, rotation, translation / self.zoom)
+            * Mat4::from_translation(self.translation)
+            * Mat4::from_rotation_y(self.yaw)
+            * Mat4::from_rotation_x(-self.pitch)
+    }
+}
+
+impl Default for CameraControl {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            orbit: Vec2::ZERO,
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::{fs, io, path::Path};
+
+use sphere_audio_visualizer_core::glam::{vec3a, Vec3A};
+use thiserror::Error;
+
+/// Represents the errors which could happen when loading a Radiance HDR file
+#[derive(Error, Debug)]
+pub enum HdrError {
+    /// This error happens when the file could not be read.
+    #[error("failed to read the file: {0}")]
+    Io(#[from] io::Error),
+    /// This error happens when the file does not start with the Radiance
+    /// signature or the pixel format is not RGBE.
+    #[error("not a supported Radiance HDR file")]
+    UnsupportedFormat,
+    /// This error happens when the resolution line is missing or uses an
+    /// orientation other than `-Y height +X width`.
+    #[error("unsupported resolution")]
+    UnsupportedResolution,
+    /// This error happens when the pixel data ends early or a run length
+    /// encoded scanline is malformed.
+    #[error("invalid scanline {scanline}")]
+    InvalidScanline {
+        /// The scanline starting at 0 from the top
+        scanline: usize,
+    },
+}
+
+/// Stores an image with linear high dynamic range colors. The pixels are
+/// stored row by row from the top left.
+#[derive(Clone)]
+pub struct HdrImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3A>,
+}
+
+impl HdrImage {
+    /// Creates a new instance. The length of `pixels` has to be
+    /// `width * height`.
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3A>) -> Self {
+        assert_eq!(pixels.len(), width * height);
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the width in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixels row by row from the top left
+    pub fn pixels(&self) -> &[Vec3A] {
+        &self.pixels
+    }
+
+    /// Returns the image with half the size on both axes, where every pixel
+    /// is the average of the pixels it covers. Used to build mip levels.
+    pub fn downsampled(&self) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+
+        let pixel = |x: usize, y: usize| {
+            self.pixels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+        };
+
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                (pixel(x * 2, y * 2)
+                    + pixel(x * 2 + 1, y * 2)
+                    + pixel(x * 2, y * 2 + 1)
+                    + pixel(x * 2 + 1, y * 2 + 1))
+                    * 0.25
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Returns the amount of mip levels down to a single pixel
+    pub fn mip_level_count(&self) -> u32 {
+        usize::BITS - self.width.max(self.height).max(1).leading_zeros()
+    }
+}
+
+/// Parses a Radiance HDR file with RGBE pixels. Both flat and run length
+/// encoded scanlines are supported.
+///
+/// ```
+/// use sphere_audio_visualizer::rendering::parse_hdr;
+///
+/// let mut source = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n".to_vec();
+/// source.extend([128, 64, 0, 129, 0, 0, 0, 0]);
+///
+/// let image = parse_hdr(&source).unwrap();
+///
+/// assert_eq!((image.width(), image.height()), (2, 1));
+/// assert_eq!(image.pixels()[0].x.round(), 1.0);
+/// assert_eq!(image.pixels()[1].x, 0.0);
+/// ```
+pub fn parse_hdr(source: &[u8]) -> Result<HdrImage, HdrError> {
+    let mut lines = source.split(|byte| *byte == b'\n');
+    let mut offset = 0;
+
+    let mut next_line = || {
+        let line = lines.next()?;
+        offset += line.len() + 1;
+        Some(String::from_utf8_lossy(line).into_owned())
+    };
+
+    match next_line() {
+        Some(signature) if signature == "#?RADIANCE" || signature == "#?RGBE" => {}
+        _ => return Err(HdrError::UnsupportedFormat),
+    }
+
+    // the header ends with an empty line
+    loop {
+        match next_line() {
+            Some(line) if line.is_empty() => break,
+            Some(line) => {
+                if let Some(format) = line.strip_prefix("FORMAT=") {
+                    if format != "32-bit_rle_rgbe" {
+                        return Err(HdrError::UnsupportedFormat);
+                    }
+                }
+            }
+            None => return Err(HdrError::UnsupportedFormat),
+        }
+    }
+
+    let resolution = next_line().ok_or(HdrError::UnsupportedResolution)?;
+
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => (
+            height
+                .parse::<usize>()
+                .map_err(|_| HdrError::UnsupportedResolution)?,
+            width
+                .parse::<usize>()
+                .map_err(|_| HdrError::UnsupportedResolution)?,
+        ),
+        _ => return Err(HdrError::UnsupportedResolution),
+    };
+
+    let mut data = source.get(offset..).unwrap_or(&[]);
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut scanline = vec![[0u8; 4]; width];
+
+    for y in 0..height {
+        data =
+            read_scanline(data, &mut scanline).ok_or(HdrError::InvalidScanline { scanline: y })?;
+
+        pixels.extend(scanline.iter().map(rgbe_to_rgb));
+    }
+
+    Ok(HdrImage::new(width, height, pixels))
+}
+
+/// Loads a Radiance HDR file. See [`parse_hdr`] for the supported subset.
+pub fn load_hdr(path: impl AsRef<Path>) -> Result<HdrImage, HdrError> {
+    parse_hdr(&fs::read(path)?)
+}
+
+/// Reads one scanline into `scanline` and returns the remaining data
+fn read_scanline<'a>(data: &'a [u8], scanline: &mut [[u8; 4]]) -> Option<&'a [u8]> {
+    let width = scanline.len();
+
+    // run length encoded scanlines start with 2, 2 and the width
+    let encoded = (8..0x8000).contains(&width)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && ((data[2] as usize) << 8 | data[3] as usize) == width;
+
+    if !encoded {
+        let bytes = data.get(..width * 4)?;
+
+        for (pixel, bytes) in scanline.iter_mut().zip(bytes.chunks_exact(4)) {
+            pixel.copy_from_slice(bytes);
+        }
+
+        return Some(&data[width * 4..]);
+    }
+
+    let mut data = &data[4..];
+
+    // the channels are encoded one after another
+    for channel in 0..4 {
+        let mut x = 0;
+
+        while x < width {
+            let (&count, rest) = data.split_first()?;
+
+            if count > 128 {
+                let count = count as usize - 128;
+                let (&value, rest) = rest.split_first()?;
+
+                for pixel in scanline.get_mut(x..x + count)? {
+                    pixel[channel] = value;
+                }
+
+                x += count;
+                data = rest;
+            } else {
+                let count = count as usize;
+
+                if count == 0 {
+                    return None;
+                }
+
+                let values = rest.get(..count)?;
+
+                for (pixel, value) in scanline.get_mut(x..x + count)?.iter_mut().zip(values) {
+                    pixel[channel] = *value;
+                }
+
+                x += count;
+                data = &rest[count..];
+            }
+        }
+    }
+
+    Some(data)
+}
+
+fn rgbe_to_rgb(rgbe: &[u8; 4]) -> Vec3A {
+    if rgbe[3] == 0 {
+        return Vec3A::ZERO;
+    }
+
+    let scale = 2.0f32.powi(rgbe[3] as i32 - 136);
+
+    vec3a(
+        (rgbe[0] as f32 + 0.5) * scale,
+        (rgbe[1] as f32 + 0.5) * scale,
+        (rgbe[2] as f32 + 0.5) * scale,
+    )
+}
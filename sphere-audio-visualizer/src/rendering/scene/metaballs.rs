@@ -1,4 +1,7 @@
-use std::time::Instant;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use sphere_audio_visualizer_core::{
     glam::{vec2, vec3a, Vec2, Vec3A},
@@ -7,7 +10,16 @@ use sphere_audio_visualizer_core::{
 
 use crate::{module::Module, simulation::Sphere2D};
 
-use super::SceneConverter;
+use super::{AspectRatio, CameraControl, SceneConverter, Viewport};
+
+/// Defines the zoom factor along the shorter viewport axis
+const ZOOM: f32 = 10.0;
+
+/// Defines the default fraction by which the audio pushes the zoom in
+pub const ZOOM_RANGE: f32 = 0.2;
+
+/// Defines the default time in seconds the zoom takes to follow the audio
+pub const ZOOM_SMOOTHING: f32 = 0.1;
 
 fn hue_to_rgb(hue: f32) -> Vec3A {
     const THIRD_PI: f32 = std::f32::consts::PI / 3.0;
@@ -35,20 +47,20 @@ fn hue_to_rgb(hue: f32) -> Vec3A {
 /// Stores the scene definition for the metaballs renderer
 pub struct MetaballsScene {
     pub(crate) color: Vec3A,
-    pub(crate) size: Vec2,
-    pub(crate) zoom: f32,
+    pub(crate) viewport: Viewport,
+    pub(crate) zoom: Vec2,
     pub(crate) metaballs: Vec<Metaball>,
 }
 
 impl MetaballsScene {
     /// Creates a new instance.
     /// - `color` defines the hallo color
-    /// - `size` defines the size of the viewport
-    /// - `zoom` defines the zoom factor of the camera
-    pub fn new(color: Vec3A, size: Vec2, zoom: f32) -> Self {
+    /// - `viewport` defines the area of the target the scene is rendered into
+    /// - `zoom` defines the zoom factor of the camera along each axis
+    pub fn new(color: Vec3A, viewport: Viewport, zoom: Vec2) -> Self {
         Self {
             color,
-            size,
+            viewport,
             zoom,
             metaballs: Vec::new(),
         }
@@ -67,16 +79,150 @@ impl MetaballsScene {
     }
 }
 
+/// Specifies what drives the zoom of the [`MetaballsSceneConverter`]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum ZoomModulation {
+    /// The zoom is static
+    Static,
+    /// The overall level of all frequency bands drives the zoom
+    Level,
+    /// The level of the bass frequency bands drives the zoom
+    Bass,
+}
+
 /// Converts the 2D physics simultion result to the metaballs renderer scene
 /// format
 pub struct MetaballsSceneConverter {
     start: Instant,
+    aspect_ratio: AspectRatio,
+    zoom_modulation: ZoomModulation,
+    zoom_range: f32,
+    zoom_smoothing: f32,
+    zoom_energy: Mutex<ZoomEnergy>,
+    camera_control: CameraControl,
+}
+
+/// The smoothed energy driving the zoom, which is updated on every
+/// conversion by the simulation time advanced since the previous one
+struct ZoomEnergy {
+    energy: f32,
+    delta_time: f32,
+}
+
+impl MetaballsSceneConverter {
+    /// Returns the aspect ratio of the area the scene is rendered into
+    pub fn aspect_ratio(&self) -> AspectRatio {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: AspectRatio) -> &mut Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    /// Returns what drives the zoom
+    pub fn zoom_modulation(&self) -> ZoomModulation {
+        self.zoom_modulation
+    }
+
+    /// Sets what drives the zoom
+    pub fn set_zoom_modulation(&mut self, zoom_modulation: ZoomModulation) -> &mut Self {
+        self.zoom_modulation = zoom_modulation;
+        self
+    }
+
+    /// Sets what drives the zoom
+    pub fn with_zoom_modulation(mut self, zoom_modulation: ZoomModulation) -> Self {
+        self.set_zoom_modulation(zoom_modulation);
+        self
+    }
+
+    /// Returns the fraction by which the audio pushes the zoom in. Negative
+    /// values push the zoom out.
+    pub fn zoom_range(&self) -> f32 {
+        self.zoom_range
+    }
+
+    /// Sets the fraction by which the audio pushes the zoom in. Negative
+    /// values push the zoom out.
+    pub fn set_zoom_range(&mut self, zoom_range: f32) -> &mut Self {
+        self.zoom_range = zoom_range;
+        self
+    }
+
+    /// Sets the fraction by which the audio pushes the zoom in
+    pub fn with_zoom_range(mut self, zoom_range: f32) -> Self {
+        self.set_zoom_range(zoom_range);
+        self
+    }
+
+    /// Returns the time in seconds the zoom takes to follow the audio
+    pub fn zoom_smoothing(&self) -> f32 {
+        self.zoom_smoothing
+    }
+
+    /// Sets the time in seconds the zoom takes to follow the audio
+    pub fn set_zoom_smoothing(&mut self, zoom_smoothing: f32) -> &mut Self {
+        self.zoom_smoothing = zoom_smoothing.max(0.0);
+        self
+    }
+
+    /// Sets the time in seconds the zoom takes to follow the audio
+    pub fn with_zoom_smoothing(mut self, zoom_smoothing: f32) -> Self {
+        self.set_zoom_smoothing(zoom_smoothing);
+        self
+    }
+
+    /// Returns the zoom factor after following the energy of the given
+    /// spheres
+    fn zoom(&self, spheres: &[Sphere2D]) -> f32 {
+        let mut zoom_energy = self.zoom_energy.lock().unwrap();
+
+        let delta_time = std::mem::take(&mut zoom_energy.delta_time);
+
+        let spheres = match self.zoom_modulation {
+            ZoomModulation::Static => return ZOOM,
+            ZoomModulation::Level => spheres,
+            ZoomModulation::Bass => &spheres[..(spheres.len() + 2) / 3],
+        };
+
+        let target = match spheres.len() {
+            0 => 0.0,
+            len => (spheres.iter().map(|sphere| sphere.radius).sum::<f32>() / len as f32).min(1.0),
+        };
+
+        let factor = match self.zoom_smoothing > 0.0 {
+            true => (-delta_time / self.zoom_smoothing).exp(),
+            false => 0.0,
+        };
+
+        zoom_energy.energy = factor * (zoom_energy.energy - target) + target;
+
+        ZOOM * (1.0 - self.zoom_range * zoom_energy.energy)
+    }
 }
 
 impl Default for MetaballsSceneConverter {
     fn default() -> Self {
         Self {
             start: Instant::now(),
+            aspect_ratio: AspectRatio::Free,
+            zoom_modulation: ZoomModulation::Static,
+            zoom_range: ZOOM_RANGE,
+            zoom_smoothing: ZOOM_SMOOTHING,
+            zoom_energy: Mutex::new(ZoomEnergy {
+                energy: 0.0,
+                delta_time: 0.0,
+            }),
+            camera_control: CameraControl::default(),
         }
     }
 }
@@ -87,31 +233,82 @@ impl<S: IntoIterator<Item = Sphere2D>> SceneConverter<S> for MetaballsSceneConve
     fn convert(&self, spheres: S, width: f32, height: f32) -> Self::Scene {
         let hue = self.start.elapsed().as_secs_f32();
 
-        let mut scene = MetaballsScene::new(hue_to_rgb(hue % 6.0), vec2(width, height), 10.0);
+        let viewport = Viewport::new(width, height, self.aspect_ratio);
+
+        let spheres = spheres.into_iter().collect::<Vec<_>>();
+        let zoom = self.zoom(&spheres);
+
+        let mut scene = MetaballsScene::new(
+            hue_to_rgb(hue % 6.0),
+            viewport,
+            viewport.aspect_scale() * zoom / self.camera_control.zoom,
+        );
 
         for sphere in spheres {
             scene.add_metaball(Metaball::new(
-                vec2(sphere.position.x, sphere.position.y),
+                vec2(sphere.position.x, sphere.position.y) - self.camera_control.pan,
                 sphere.radius,
             ));
         }
 
         scene
     }
+
+    fn set_camera_control(&mut self, camera_control: CameraControl) {
+        self.camera_control = camera_control;
+    }
+
+    fn advance(&mut self, delta_time: Duration) {
+        self.zoom_energy.get_mut().unwrap().delta_time += delta_time.as_secs_f32();
+    }
 }
 
 impl Module for MetaballsSceneConverter {
     type Settings = MetaballsSceneConverterSettings;
 
-    fn set_settings(&mut self, _settings: Self::Settings) -> &mut Self {
-        self
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_aspect_ratio(settings.aspect_ratio)
+            .set_zoom_modulation(settings.zoom_modulation)
+            .set_zoom_range(settings.zoom_range)
+            .set_zoom_smoothing(settings.zoom_smoothing)
     }
 
     fn settings(&self) -> Self::Settings {
-        MetaballsSceneConverterSettings
+        MetaballsSceneConverterSettings {
+            aspect_ratio: self.aspect_ratio(),
+            zoom_modulation: self.zoom_modulation(),
+            zoom_range: self.zoom_range(),
+            zoom_smoothing: self.zoom_smoothing(),
+        }
     }
 }
 
 /// Stores the settings of the [`MetaballsSceneConverter`]
-#[derive(Clone, Default)]
-pub struct MetaballsSceneConverterSettings;
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct MetaballsSceneConverterSettings {
+    /// The aspect ratio of the area the scene is rendered into
+    pub aspect_ratio: AspectRatio,
+    /// What drives the zoom
+    pub zoom_modulation: ZoomModulation,
+    /// The fraction by which the audio pushes the zoom in. Negative values
+    /// push the zoom out.
+    pub zoom_range: f32,
+    /// The time in seconds the zoom takes to follow the audio
+    pub zoom_smoothing: f32,
+}
+
+impl Default for MetaballsSceneConverterSettings {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            zoom_modulation: ZoomModulation::Static,
+            zoom_range: ZOOM_RANGE,
+            zoom_smoothing: ZOOM_SMOOTHING,
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use sphere_audio_visualizer_core::{
+    glam::{vec3, vec3a, Mat4, Vec3A},
+    metaballs_3d::Metaball3D,
+    raytracing::camera::{PerspectiveCamera, SceneCamera},
+};
+
+use crate::{
+    module::Module,
+    simulation::Sphere3D,
+    utils::{Gradient, Palette},
+};
+
+use super::{AspectRatio, CameraControl, ProjectedSphere, SceneConverter, Viewport, T_MAX, T_MIN};
+
+/// Defines the field of view along the shorter viewport axis
+const FOV: f32 = std::f32::consts::PI / 4.0;
+
+/// Defines the default brightness of the background, which also lights the
+/// metaballs from all directions
+pub const METABALLS_3D_BACKGROUND_INTENSITY: f32 = 0.2;
+
+/// Stores the scene definition for the 3D metaballs renderer
+pub struct Metaballs3DScene {
+    pub(crate) camera: SceneCamera,
+    pub(crate) background: Vec3A,
+    pub(crate) light_direction: Vec3A,
+    pub(crate) metaballs: Vec<Metaball3D>,
+}
+
+impl Metaballs3DScene {
+    /// Creates a new instance.
+    /// - `camera` the camera used
+    /// - `background` the color of the background
+    /// - `light_direction` the direction towards the light of the scene
+    pub fn new(camera: SceneCamera, background: Vec3A, light_direction: Vec3A) -> Self {
+        Self {
+            camera,
+            background,
+            light_direction,
+            metaballs: Vec::new(),
+        }
+    }
+
+    /// Adds a metaball to the scene
+    pub fn add_metaball(&mut self, metaball: Metaball3D) -> &mut Self {
+        self.metaballs.push(metaball);
+        self
+    }
+
+    /// Adds a metaball to the scene
+    pub fn with_metaball(mut self, metaball: Metaball3D) -> Self {
+        self.add_metaball(metaball);
+        self
+    }
+}
+
+/// Converts the 3D physics simulation result to the 3D metaballs renderer
+/// scene format. The metaballs are colored by their frequency band.
+pub struct Metaballs3DSceneConverter {
+    aspect_ratio: AspectRatio,
+    palette: Palette,
+    color_ramp: Gradient,
+    background_intensity: f32,
+    camera_control: CameraControl,
+}
+
+impl Metaballs3DSceneConverter {
+    /// Returns the aspect ratio of the area the scene is rendered into
+    pub fn aspect_ratio(&self) -> AspectRatio {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: AspectRatio) -> &mut Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    /// Returns the palette the frequency bands are colored with
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    /// Sets the palette the frequency bands are colored with
+    pub fn set_palette(&mut self, palette: Palette) -> &mut Self {
+        if self.palette != palette {
+            self.palette = palette;
+            self.color_ramp = palette.gradient();
+        }
+
+        self
+    }
+
+    /// Sets the palette the frequency bands are colored with
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.set_palette(palette);
+        self
+    }
+
+    /// Returns the brightness of the background
+    pub fn background_intensity(&self) -> f32 {
+        self.background_intensity
+    }
+
+    /// Sets the brightness of the background
+    pub fn set_background_intensity(&mut self, background_intensity: f32) -> &mut Self {
+        self.background_intensity = background_intensity.max(0.0);
+        self
+    }
+
+    /// Sets the brightness of the background
+    pub fn with_background_intensity(mut self, background_intensity: f32) -> Self {
+        self.set_background_intensity(background_intensity);
+        self
+    }
+
+    fn camera(&self, viewport: &Viewport) -> SceneCamera {
+        // the field of view is widened on wide viewports so it always
+        // applies to the shorter axis
+        let fov = (FOV.tan() * viewport.aspect_scale().x).atan();
+
+        let transform = self
+            .camera_control
+            .apply(Mat4::from_translation(vec3(0.0f32, 0.0f32, -10.0f32)));
+
+        SceneCamera::from(
+            PerspectiveCamera::new(transform, viewport.size, fov, T_MIN, T_MAX)
+                .with_screen_offset(viewport.offset),
+        )
+    }
+
+    fn color(&self, sphere: &Sphere3D, band_count: usize) -> Vec3A {
+        if sphere.emissive {
+            return Vec3A::ONE;
+        }
+
+        let t = match band_count {
+            0 | 1 => 0.5,
+            band_count => sphere.band as f32 / (band_count - 1) as f32,
+        };
+
+        self.color_ramp.interpolate(t).into()
+    }
+}
+
+impl Default for Metaballs3DSceneConverter {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            palette: Palette::Viridis,
+            color_ramp: Palette::Viridis.gradient(),
+            background_intensity: METABALLS_3D_BACKGROUND_INTENSITY,
+            camera_control: CameraControl::default(),
+        }
+    }
+}
+
+impl<S: IntoIterator<Item = Sphere3D>> SceneConverter<S> for Metaballs3DSceneConverter {
+    type Scene = Metaballs3DScene;
+
+    fn convert(&self, spheres: S, width: f32, height: f32) -> Self::Scene {
+        let viewport = Viewport::new(width, height, self.aspect_ratio);
+
+        // the light comes from the upper left behind the camera
+        let mut scene = Metaballs3DScene::new(
+            self.camera(&viewport),
+            Vec3A::splat(self.background_intensity),
+            vec3a(-1.0, 1.0, -1.0).normalize(),
+        );
+
+        let spheres = spheres.into_iter().collect::<Vec<_>>();
+
+        let band_count = spheres
+            .iter()
+            .filter(|sphere| !sphere.emissive)
+            .map(|sphere| sphere.band + 1)
+            .max()
+            .unwrap_or(0);
+
+        for sphere in &spheres {
+            scene.add_metaball(Metaball3D::new(
+                vec3a(sphere.position.x, sphere.position.y, sphere.position.z),
+                sphere.radius,
+                self.color(sphere, band_count),
+            ));
+        }
+
+        scene
+    }
+
+    fn project(&self, spheres: S, width: f32, height: f32) -> Vec<ProjectedSphere> {
+        let viewport = Viewport::new(width, height, self.aspect_ratio);
+        let camera = self.camera(&viewport);
+
+        spheres
+            .into_iter()
+            .filter(|sphere| !sphere.emissive)
+            .filter_map(|sphere| {
+                let position = vec3a(sphere.position.x, sphere.position.y, sphere.position.z);
+
+                camera.project(position).map(|position| ProjectedSphere {
+                    position,
+                    band: sphere.band,
+                    level: sphere.level,
+                })
+            })
+            .collect()
+    }
+
+    fn set_camera_control(&mut self, camera_control: CameraControl) {
+        self.camera_control = camera_control;
+    }
+}
+
+impl Module for Metaballs3DSceneConverter {
+    type Settings = Metaballs3DSceneConverterSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_aspect_ratio(settings.aspect_ratio)
+            .set_palette(settings.palette)
+            .set_background_intensity(settings.background_intensity)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        Metaballs3DSceneConverterSettings {
+            aspect_ratio: self.aspect_ratio(),
+            palette: self.palette(),
+            background_intensity: self.background_intensity(),
+        }
+    }
+}
+
+/// Stores the settings of the [`Metaballs3DSceneConverter`]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Metaballs3DSceneConverterSettings {
+    /// The aspect ratio of the area the scene is rendered into
+    pub aspect_ratio: AspectRatio,
+    /// The palette the frequency bands are colored with
+    pub palette: Palette,
+    /// The brightness of the background
+    pub background_intensity: f32,
+}
+
+impl Default for Metaballs3DSceneConverterSettings {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            palette: Palette::Viridis,
+            background_intensity: METABALLS_3D_BACKGROUND_INTENSITY,
+        }
+    }
+}
@@ -1,7 +1,34 @@
+mod bars;
+mod bvh;
+mod camera_control;
+mod hdr;
 mod metaballs;
+mod metaballs_3d;
+mod obj;
+mod oscilloscope;
 mod raytracing;
+mod tunnel;
+mod viewport;
 
-pub use self::{metaballs::*, raytracing::*};
+use std::time::Duration;
+
+use sphere_audio_visualizer_core::glam::Vec2;
+
+pub use self::{
+    bars::*, bvh::*, camera_control::*, hdr::*, metaballs::*, metaballs_3d::*, obj::*,
+    oscilloscope::*, raytracing::*, tunnel::*, viewport::*,
+};
+
+/// A sphere of a frequency band projected onto the screen
+#[derive(Clone, Debug)]
+pub struct ProjectedSphere {
+    /// The position of the sphere center on the screen in pixels
+    pub position: Vec2,
+    /// The index of the frequency band the sphere represents
+    pub band: usize,
+    /// The current level of the frequency band
+    pub level: f32,
+}
 
 /// A [`SceneConverter`] is used to convert one scene definition to a renderer
 /// specific scene definition.
@@ -14,4 +41,23 @@ pub trait SceneConverter<S> {
 
     /// Converts a scene to the renderer specific format
     fn convert(&self, scene: S, width: f32, height: f32) -> Self::Scene;
+
+    /// Projects the spheres of a scene onto the screen using the camera of
+    /// the converter. Converters without a 3D camera return no spheres.
+    fn project(&self, _scene: S, _width: f32, _height: f32) -> Vec<ProjectedSphere> {
+        Vec::new()
+    }
+
+    /// Overrides the camera of the converter with a manually controlled
+    /// camera. Converters without a camera ignore the control.
+    fn set_camera_control(&mut self, _camera_control: CameraControl) {}
+
+    /// Advances the animations of the converter by a step of the simulation,
+    /// so they follow the audio instead of the wall clock. Converters without
+    /// animations ignore the step.
+    fn advance(&mut self, _delta_time: Duration) {}
+
+    /// Tells the converter the tempo of the audio in beats per minute if it
+    /// is known. Converters without beat synced animations ignore it.
+    fn set_bpm(&mut self, _bpm: Option<f32>) {}
 }
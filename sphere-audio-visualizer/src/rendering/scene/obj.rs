@@ -0,0 +1,102 @@
+use std::{io, path::Path};
+
+use sphere_audio_visualizer_core::{glam::vec3a, raytracing::shape::Triangle};
+use thiserror::Error;
+
+/// Represents the errors which could happen when loading an OBJ file
+#[derive(Error, Debug)]
+pub enum ObjError {
+    /// This error happens when the file could not be read.
+    #[error("failed to read the file: {0}")]
+    Io(#[from] io::Error),
+    /// This error happens when a vertex does not consist of three numbers.
+    #[error("invalid vertex in line {line}")]
+    InvalidVertex {
+        /// The line of the vertex starting at 1
+        line: usize,
+    },
+    /// This error happens when a face has less than three vertices or
+    /// references a vertex which is not defined.
+    #[error("invalid face in line {line}")]
+    InvalidFace {
+        /// The line of the face starting at 1
+        line: usize,
+    },
+}
+
+/// Parses the geometry of an OBJ file into [`Triangle`]s with the given
+/// material index.
+/// Only vertices and faces are supported, everything else is ignored. Faces
+/// with more than three vertices are split into a triangle fan.
+///
+/// ```
+/// use sphere_audio_visualizer::rendering::parse_obj;
+///
+/// let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n";
+///
+/// let triangles = parse_obj(source, 0).unwrap();
+///
+/// assert_eq!(triangles.len(), 2);
+/// ```
+pub fn parse_obj(source: &str, material: u32) -> Result<Vec<Triangle>, ObjError> {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let coordinates = tokens
+                    .take(3)
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| ObjError::InvalidVertex { line: line_number })?;
+
+                match coordinates[..] {
+                    [x, y, z] => vertices.push(vec3a(x, y, z)),
+                    _ => return Err(ObjError::InvalidVertex { line: line_number }),
+                }
+            }
+            Some("f") => {
+                // only the position index of `v/vt/vn` is used and negative
+                // indices are relative to the end of the vertex list
+                let corners = tokens
+                    .map(|token| {
+                        let index = token.split('/').next()?.parse::<isize>().ok()?;
+
+                        let index = match index {
+                            index if index > 0 => index as usize - 1,
+                            index if index < 0 => vertices.len().checked_sub(-index as usize)?,
+                            _ => return None,
+                        };
+
+                        vertices.get(index).copied()
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|corners| corners.len() >= 3)
+                    .ok_or(ObjError::InvalidFace { line: line_number })?;
+
+                for window in 1..corners.len() - 1 {
+                    triangles.push(Triangle::new(
+                        corners[0],
+                        corners[window],
+                        corners[window + 1],
+                        material,
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+/// Loads the geometry of an OBJ file into [`Triangle`]s with the given
+/// material index.
+/// See [`parse_obj`] for the supported subset of the format.
+pub fn load_obj(path: impl AsRef<Path>, material: u32) -> Result<Vec<Triangle>, ObjError> {
+    parse_obj(&std::fs::read_to_string(path)?, material)
+}
@@ -0,0 +1,293 @@
+use sphere_audio_visualizer_core::glam::{vec2, Vec2, Vec3A};
+
+use crate::{module::Module, simulation::Trace};
+
+use super::{AspectRatio, SceneConverter, Viewport};
+
+/// Defines the default factor the samples are scaled by
+pub const OSCILLOSCOPE_GAIN: f32 = 1.0;
+
+/// Defines the default delay in milliseconds between the two axes of the
+/// [`OscilloscopeMode::Lissajous`] mode
+pub const LISSAJOUS_DELAY: f32 = 1.0;
+
+/// Defines the default color of the traces
+pub const OSCILLOSCOPE_COLOR: Vec3A = Vec3A::new(0.2, 1.0, 0.4);
+
+/// Defines the maximum number of points per trace, longer traces are
+/// decimated
+const MAX_TRACE_POINTS: usize = 2048;
+
+/// Specifies how the waveform is plotted
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum OscilloscopeMode {
+    /// The samples are plotted over time from left to right
+    Waveform,
+    /// The samples are plotted against a delayed copy of themselves. The
+    /// audio signal is mono, so the delay stands in for the second channel
+    /// of a stereo X-Y plot.
+    Lissajous,
+}
+
+/// Stores a single trace of the oscilloscope scene
+pub struct OscilloscopeTrace {
+    pub(crate) points: Vec<Vec2>,
+    pub(crate) intensity: f32,
+}
+
+/// Stores the scene definition for the oscilloscope renderer
+pub struct OscilloscopeScene {
+    pub(crate) color: Vec3A,
+    pub(crate) viewport: Viewport,
+    pub(crate) traces: Vec<OscilloscopeTrace>,
+}
+
+impl OscilloscopeScene {
+    /// Creates a new instance.
+    /// - `color` defines the color of the traces
+    /// - `viewport` defines the area of the target the scene is rendered into
+    pub fn new(color: Vec3A, viewport: Viewport) -> Self {
+        Self {
+            color,
+            viewport,
+            traces: Vec::new(),
+        }
+    }
+
+    /// Adds a trace with the given points in clip space and brightness to
+    /// the scene
+    pub fn add_trace(&mut self, points: Vec<Vec2>, intensity: f32) -> &mut Self {
+        self.traces.push(OscilloscopeTrace { points, intensity });
+        self
+    }
+
+    /// Adds a trace with the given points in clip space and brightness to
+    /// the scene
+    pub fn with_trace(mut self, points: Vec<Vec2>, intensity: f32) -> Self {
+        self.add_trace(points, intensity);
+        self
+    }
+}
+
+/// Converts the traces of the [`crate::simulation::WaveformSimulation`] to
+/// the oscilloscope renderer scene format
+pub struct OscilloscopeSceneConverter {
+    aspect_ratio: AspectRatio,
+    mode: OscilloscopeMode,
+    gain: f32,
+    lissajous_delay: f32,
+    color: Vec3A,
+}
+
+impl OscilloscopeSceneConverter {
+    /// Returns the aspect ratio of the area the scene is rendered into
+    pub fn aspect_ratio(&self) -> AspectRatio {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: AspectRatio) -> &mut Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    /// Returns how the waveform is plotted
+    pub fn mode(&self) -> OscilloscopeMode {
+        self.mode
+    }
+
+    /// Sets how the waveform is plotted
+    pub fn set_mode(&mut self, mode: OscilloscopeMode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets how the waveform is plotted
+    pub fn with_mode(mut self, mode: OscilloscopeMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Returns the factor the samples are scaled by
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Sets the factor the samples are scaled by
+    pub fn set_gain(&mut self, gain: f32) -> &mut Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Sets the factor the samples are scaled by
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.set_gain(gain);
+        self
+    }
+
+    /// Returns the delay in milliseconds between the two axes of the
+    /// [`OscilloscopeMode::Lissajous`] mode
+    pub fn lissajous_delay(&self) -> f32 {
+        self.lissajous_delay
+    }
+
+    /// Sets the delay in milliseconds between the two axes of the
+    /// [`OscilloscopeMode::Lissajous`] mode
+    pub fn set_lissajous_delay(&mut self, lissajous_delay: f32) -> &mut Self {
+        self.lissajous_delay = lissajous_delay.max(0.0);
+        self
+    }
+
+    /// Sets the delay in milliseconds between the two axes of the
+    /// [`OscilloscopeMode::Lissajous`] mode
+    pub fn with_lissajous_delay(mut self, lissajous_delay: f32) -> Self {
+        self.set_lissajous_delay(lissajous_delay);
+        self
+    }
+
+    /// Returns the color of the traces
+    pub fn color(&self) -> Vec3A {
+        self.color
+    }
+
+    /// Sets the color of the traces
+    pub fn set_color(&mut self, color: Vec3A) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the color of the traces
+    pub fn with_color(mut self, color: Vec3A) -> Self {
+        self.set_color(color);
+        self
+    }
+
+    /// Returns the points in clip space of a trace, where the delay is
+    /// given in samples
+    fn points(&self, samples: &[f32], delay: usize, viewport: &Viewport) -> Vec<Vec2> {
+        match self.mode {
+            OscilloscopeMode::Waveform => {
+                let step = (samples.len() / MAX_TRACE_POINTS).max(1);
+                let count = samples.len() / step;
+                let scale = 2.0 / count.saturating_sub(1).max(1) as f32;
+
+                samples
+                    .iter()
+                    .step_by(step)
+                    .take(count)
+                    .enumerate()
+                    .map(|(i, sample)| vec2(i as f32 * scale - 1.0, sample * self.gain))
+                    .collect()
+            }
+            OscilloscopeMode::Lissajous => {
+                let delay = delay.min(samples.len());
+                let step = ((samples.len() - delay) / MAX_TRACE_POINTS).max(1);
+
+                // keeps the figure from being stretched by the viewport
+                let scale = self.gain / viewport.aspect_scale();
+
+                samples[delay..]
+                    .iter()
+                    .zip(samples)
+                    .step_by(step)
+                    .map(|(x, y)| vec2(*x, *y) * scale)
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Default for OscilloscopeSceneConverter {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            mode: OscilloscopeMode::Waveform,
+            gain: OSCILLOSCOPE_GAIN,
+            lissajous_delay: LISSAJOUS_DELAY,
+            color: OSCILLOSCOPE_COLOR,
+        }
+    }
+}
+
+impl<S: IntoIterator<Item = Trace>> SceneConverter<S> for OscilloscopeSceneConverter {
+    type Scene = OscilloscopeScene;
+
+    fn convert(&self, traces: S, width: f32, height: f32) -> Self::Scene {
+        let viewport = Viewport::new(width, height, self.aspect_ratio);
+
+        let mut scene = OscilloscopeScene::new(self.color, viewport);
+
+        for trace in traces {
+            let delay = (self.lissajous_delay as f64 * 0.001 * trace.sample_rate) as usize;
+
+            scene.add_trace(
+                self.points(&trace.samples, delay, &viewport),
+                trace.intensity,
+            );
+        }
+
+        scene
+    }
+}
+
+impl Module for OscilloscopeSceneConverter {
+    type Settings = OscilloscopeSceneConverterSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_aspect_ratio(settings.aspect_ratio)
+            .set_mode(settings.mode)
+            .set_gain(settings.gain)
+            .set_lissajous_delay(settings.lissajous_delay)
+            .set_color(settings.color)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        OscilloscopeSceneConverterSettings {
+            aspect_ratio: self.aspect_ratio(),
+            mode: self.mode(),
+            gain: self.gain(),
+            lissajous_delay: self.lissajous_delay(),
+            color: self.color(),
+        }
+    }
+}
+
+/// Stores the settings of the [`OscilloscopeSceneConverter`]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct OscilloscopeSceneConverterSettings {
+    /// The aspect ratio of the area the scene is rendered into
+    pub aspect_ratio: AspectRatio,
+    /// How the waveform is plotted
+    pub mode: OscilloscopeMode,
+    /// The factor the samples are scaled by
+    pub gain: f32,
+    /// The delay in milliseconds between the two axes of the
+    /// [`OscilloscopeMode::Lissajous`] mode
+    pub lissajous_delay: f32,
+    /// The color of the traces
+    pub color: Vec3A,
+}
+
+impl Default for OscilloscopeSceneConverterSettings {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            mode: OscilloscopeMode::Waveform,
+            gain: OSCILLOSCOPE_GAIN,
+            lissajous_delay: LISSAJOUS_DELAY,
+            color: OSCILLOSCOPE_COLOR,
+        }
+    }
+}
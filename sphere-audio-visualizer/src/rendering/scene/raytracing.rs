@@ -1,37 +1,187 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
 use sphere_audio_visualizer_core::{
     glam::{vec2, vec3, vec3a, Mat4, Vec3, Vec3A},
     raytracing::{
-        background::{Background, ConstantBackground},
-        camera::{Camera, PerspectiveCamera},
-        light::{Light, PointLight},
-        shape::{Rect, Shape, Sphere, AABB},
+        background::ImageBackgroundArgs,
+        camera::{Camera, PanoramicCamera, PerspectiveCamera, SceneCamera},
+        light::{DirectionalLight, Light, PointLight},
+        shape::{BvhNode, Material, Rect, Shape, Sphere, Triangle, AABB},
     },
+    utils::random::random,
 };
 
 use crate::{
     module::Module,
     simulation::Sphere3D,
-    utils::{Gradient, TypeMap},
+    utils::{Gradient, Palette, TypeMap},
 };
 
-use super::SceneConverter;
+use super::{
+    build_bvh, load_hdr, load_obj, AspectRatio, CameraControl, HdrImage, ProjectedSphere,
+    SceneConverter, Viewport,
+};
 
 const SPHERE_N: f32 = 1.45;
 
+/// Defines the field of view along the shorter viewport axis
+const FOV: f32 = std::f32::consts::PI / 4.0;
+
+/// Defines the default number of ray bounces
+pub const BOUNCES: u32 = 5;
+
+/// Defines the default distance of the camera near clip plane
+pub const T_MIN: f32 = 0.0001;
+
+/// Defines the default distance of the camera far clip plane
+pub const T_MAX: f32 = 1000.0;
+
+/// Defines the default projected radius in pixels below which spheres are
+/// reduced by the level of detail
+pub const LOD_THRESHOLD: f32 = 0.5;
+
+/// The size in pixels of the screen cells in which reduced spheres are merged
+const LOD_CELL_SIZE: f32 = 8.0;
+
+/// Defines the default number of point lights arranged around the scene
+pub const LIGHT_COUNT: usize = 1;
+
+/// Defines the default speed in gradient cycles per second or per beat with
+/// which the light colors march through the color ramp
+pub const LIGHT_CYCLE_SPEED: f32 = 0.05;
+
+/// Defines the default intensity of the sun. The sun is disabled at zero.
+pub const SUN_INTENSITY: f32 = 0.0;
+
+/// Defines the default angle in degrees of the sun above the horizon
+pub const SUN_ELEVATION: f32 = 45.0;
+
+/// Defines the default angle in degrees of the sun around the vertical axis,
+/// measured from straight behind the camera towards the left
+pub const SUN_AZIMUTH: f32 = 45.0;
+
+/// Defines the default time in seconds a sphere takes to cool down after a
+/// peak when colored by peak recency
+pub const PEAK_COOLDOWN: f32 = 1.0;
+
+/// Defines the default scale of the light spheres emit proportional to their
+/// band level
+pub const LEVEL_EMISSION: f32 = 0.0;
+
+/// Defines the default gamma applied to the band level before it is turned
+/// into emitted light
+pub const LEVEL_EMISSION_GAMMA: f32 = 2.0;
+
+/// Defines the default height of the floor ripples at full bass level
+pub const RIPPLE_AMPLITUDE: f32 = 0.0;
+
+/// Defines the default angular frequency of the floor ripples along the
+/// radius of the floor
+pub const RIPPLE_FREQUENCY: f32 = 40.0;
+
+/// Defines the default speed in radians per second with which the floor
+/// ripples travel outwards
+pub const RIPPLE_SPEED: f32 = 8.0;
+
+/// Defines the default number of particles emitted around every sphere
+pub const PARTICLES: usize = 0;
+
+/// Defines the default radius of the particles relative to their sphere at
+/// full band level
+pub const PARTICLE_SIZE: f32 = 0.1;
+
+/// Defines the default time in seconds the shutter is open for, over which
+/// moving spheres are blurred. Zero disables the motion blur.
+pub const SHUTTER: f32 = 0.0;
+
+/// The distance in sphere radii over which the particles are spread around
+/// the surface of their sphere
+const PARTICLE_SPREAD: f32 = 3.0;
+
+/// The speed in radians per second with which the particles orbit their
+/// sphere
+const PARTICLE_SPEED: f32 = 0.5;
+
+/// Defines the default scale of the background mesh
+pub const MESH_SCALE: f32 = 1.0;
+
+/// Defines the default distance of the background mesh behind the center of
+/// the scene
+pub const MESH_DISTANCE: f32 = 10.0;
+
+/// The color of the background mesh
+const MESH_COLOR: f32 = 0.8;
+
+/// Defines the default brightness of the background
+pub const BACKGROUND_INTENSITY: f32 = 1.0;
+
+/// Defines the default angle in degrees the environment map is rotated by
+/// around the vertical axis
+pub const BACKGROUND_ROTATION: f32 = 0.0;
+
+/// The width in pixels of the mip level of the environment map, which is
+/// sampled for the diffuse light of the background
+const IRRADIANCE_WIDTH: f32 = 8.0;
+
+/// Defines the default number of times the floor texture repeats along each
+/// side of the floor
+pub const FLOOR_TEXTURE_SCALE: f32 = 1.0;
+
+/// The number of squares along each side of the checkerboard texture
+const CHECKERBOARD_SQUARES: usize = 8;
+
+/// The size in pixels of a square of the checkerboard texture
+const CHECKERBOARD_SQUARE_SIZE: usize = 8;
+
+/// The brightness of the dark squares of the checkerboard texture
+const CHECKERBOARD_DARK: f32 = 0.1;
+
+/// The number of frames shown by the spectrogram texture
+const SPECTROGRAM_HISTORY: usize = 128;
+
+/// Defines the default scale of the tilt of the normal map of the floor
+pub const FLOOR_NORMAL_STRENGTH: f32 = 0.0;
+
+/// Defines the default scale of the tilt of the normal map of the spheres
+pub const SPHERE_NORMAL_STRENGTH: f32 = 0.0;
+
+/// The width in pixels of the bevel at the edges of the tiles of the tile
+/// normal map
+const TILE_BEVEL: f32 = 2.0;
+
+/// The light emitted by emissive spheres like the center of the orbit layout
+const SPHERE_EMISSION: f32 = 2.0;
+
+/// The total intensity shared by the point lights
+const LIGHT_INTENSITY: f32 = 400.0;
+
+/// The distance of the point lights to the camera axis
+const LIGHT_DISTANCE: f32 = 10.0 * std::f32::consts::SQRT_2;
+
 /// Stores the scene definition for the raytracer renderer. Not every camera,
 /// background, shape or lights combination might be supported by the target
 /// renderer.
-pub struct RaytracerScene<C: Camera, B: Background> {
+pub struct RaytracerScene<C: Camera, B> {
     pub(crate) camera: C,
     pub(crate) shapes: TypeMap,
     pub(crate) background: B,
+    pub(crate) environment_map: Option<Arc<HdrImage>>,
+    pub(crate) surface_texture: Option<Arc<HdrImage>>,
+    pub(crate) normal_map: Option<Arc<HdrImage>>,
     pub(crate) lights: TypeMap,
+    pub(crate) materials: Vec<Material>,
     pub(crate) bounces: u32,
 }
 
 pub(crate) struct ShapeCollection<S: Shape> {
     pub(crate) bounding_box: AABB,
     pub(crate) shapes: Vec<S>,
+    pub(crate) nodes: Vec<BvhNode>,
 }
 
 impl<S: Shape> ShapeCollection<S> {
@@ -39,12 +189,20 @@ impl<S: Shape> ShapeCollection<S> {
         Self {
             bounding_box: AABB::empty(),
             shapes: Vec::new(),
+            nodes: Vec::new(),
         }
     }
 
     fn add(&mut self, shape: S) -> &mut Self {
         self.bounding_box.add_aabb(&shape.bounding_box());
         self.shapes.push(shape);
+        // the hierarchy doesn't cover the new shape
+        self.nodes.clear();
+        self
+    }
+
+    fn build_bvh(&mut self) -> &mut Self {
+        self.nodes = build_bvh(&mut self.shapes);
         self
     }
 
@@ -55,9 +213,13 @@ impl<S: Shape> ShapeCollection<S> {
     pub(crate) fn bounding_box(&self) -> &AABB {
         &self.bounding_box
     }
+
+    pub(crate) fn nodes(&self) -> &[BvhNode] {
+        &self.nodes
+    }
 }
 
-impl<C: Camera, B: Background> RaytracerScene<C, B> {
+impl<C: Camera, B> RaytracerScene<C, B> {
     /// Create a new instance
     /// - `camera` the camera used
     /// - `background` the background used
@@ -67,11 +229,78 @@ impl<C: Camera, B: Background> RaytracerScene<C, B> {
             camera,
             shapes: TypeMap::new(),
             background,
+            environment_map: None,
+            surface_texture: None,
+            normal_map: None,
             lights: TypeMap::new(),
+            materials: Vec::new(),
             bounces,
         }
     }
 
+    /// Sets the image sampled by an image background. Without an image the
+    /// background is uniformly white.
+    pub fn set_environment_map(&mut self, environment_map: Option<Arc<HdrImage>>) -> &mut Self {
+        self.environment_map = environment_map;
+        self
+    }
+
+    /// Sets the image sampled by an image background
+    pub fn with_environment_map(mut self, environment_map: Option<Arc<HdrImage>>) -> Self {
+        self.set_environment_map(environment_map);
+        self
+    }
+
+    pub(crate) fn environment_map(&self) -> Option<&Arc<HdrImage>> {
+        self.environment_map.as_ref()
+    }
+
+    /// Sets the image sampled by textured rectangles. Without an image the
+    /// rectangles keep the color of their material.
+    pub fn set_surface_texture(&mut self, surface_texture: Option<Arc<HdrImage>>) -> &mut Self {
+        self.surface_texture = surface_texture;
+        self
+    }
+
+    /// Sets the image sampled by textured rectangles
+    pub fn with_surface_texture(mut self, surface_texture: Option<Arc<HdrImage>>) -> Self {
+        self.set_surface_texture(surface_texture);
+        self
+    }
+
+    pub(crate) fn surface_texture(&self) -> Option<&Arc<HdrImage>> {
+        self.surface_texture.as_ref()
+    }
+
+    /// Sets the tangent space normal map sampled by normal mapped rectangles
+    /// and spheres. Without an image the normals stay flat.
+    pub fn set_normal_map(&mut self, normal_map: Option<Arc<HdrImage>>) -> &mut Self {
+        self.normal_map = normal_map;
+        self
+    }
+
+    /// Sets the tangent space normal map sampled by normal mapped rectangles
+    /// and spheres
+    pub fn with_normal_map(mut self, normal_map: Option<Arc<HdrImage>>) -> Self {
+        self.set_normal_map(normal_map);
+        self
+    }
+
+    pub(crate) fn normal_map(&self) -> Option<&Arc<HdrImage>> {
+        self.normal_map.as_ref()
+    }
+
+    /// Adds a material to the scene. Returns the index shapes use to
+    /// reference it.
+    pub fn add_material(&mut self, material: Material) -> u32 {
+        self.materials.push(material);
+        self.materials.len() as u32 - 1
+    }
+
+    pub(crate) fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
     /// Adds a shape to the scene
     pub fn add_shape<S: Shape + 'static>(&mut self, shape: S) -> &mut Self {
         self.shapes
@@ -87,10 +316,19 @@ impl<C: Camera, B: Background> RaytracerScene<C, B> {
         self
     }
 
-    pub(crate) fn shapes<S: Shape + 'static>(&mut self) -> Option<&ShapeCollection<S>> {
+    pub(crate) fn shapes<S: Shape + 'static>(&self) -> Option<&ShapeCollection<S>> {
         self.shapes.get()
     }
 
+    /// Builds a bounding volume hierarchy over the shapes of a type, which
+    /// reorders them. Adding another shape of the type removes the hierarchy.
+    pub fn build_bvh<S: Shape + 'static>(&mut self) -> &mut Self {
+        if let Some(shapes) = self.shapes.get_mut::<ShapeCollection<S>>() {
+            shapes.build_bvh();
+        }
+        self
+    }
+
     /// Adds a light to the scene
     pub fn add_ligth<L: Light + 'static>(&mut self, light: L) -> &mut Self {
         self.lights.entry().or_insert_with(Vec::new).push(light);
@@ -103,95 +341,1822 @@ impl<C: Camera, B: Background> RaytracerScene<C, B> {
         self
     }
 
-    pub(crate) fn lights_mut<L: Light + 'static>(&mut self) -> Option<&Vec<L>> {
+    pub(crate) fn lights<L: Light + 'static>(&self) -> Option<&Vec<L>> {
         self.lights.get()
     }
 }
 
 /// Defines the raytracer scene type that is supported by the basic raytracer
 /// implementation.
-pub type BasicRaytracerScene = RaytracerScene<PerspectiveCamera, ConstantBackground>;
+pub type BasicRaytracerScene = RaytracerScene<SceneCamera, ImageBackgroundArgs>;
 
 /// Converts the 3D physics simultion result to the raytracer renderer scene
 /// format
 pub struct RaytracerSceneConverter {
     color_ramp: Gradient,
     n: f32,
+    level_of_detail: LevelOfDetail,
+    lod_threshold: f32,
+    bounces: u32,
+    projection: Projection,
+    t_min: f32,
+    t_max: f32,
+    materials: Vec<SphereMaterial>,
+    aspect_ratio: AspectRatio,
+    light_count: usize,
+    cycle_lights: bool,
+    light_cycle_speed: f32,
+    sun_intensity: f32,
+    sun_elevation: f32,
+    sun_azimuth: f32,
+    bpm: Option<f32>,
+    time: f32,
+    light_phase: f32,
+    palette: Palette,
+    sphere_coloring: SphereColoring,
+    peak_ramp: Gradient,
+    peak_cooldown: f32,
+    level_emission: f32,
+    level_emission_gamma: f32,
+    ripple_amplitude: f32,
+    ripple_frequency: f32,
+    ripple_speed: f32,
+    particles: usize,
+    particle_size: f32,
+    shutter: f32,
+    mesh_path: Option<PathBuf>,
+    mesh: Arc<[Triangle]>,
+    mesh_scale: f32,
+    mesh_distance: f32,
+    environment_map_path: Option<PathBuf>,
+    environment_map: Option<Arc<HdrImage>>,
+    background_intensity: f32,
+    background_rotation: f32,
+    floor_texture: FloorTexture,
+    floor_texture_path: Option<PathBuf>,
+    floor_image: Option<Arc<HdrImage>>,
+    floor_texture_scale: f32,
+    checkerboard: Arc<HdrImage>,
+    spectrogram: Mutex<VecDeque<Vec<f32>>>,
+    normal_map_path: Option<PathBuf>,
+    normal_map: Arc<HdrImage>,
+    floor_normal_strength: f32,
+    sphere_normal_strength: f32,
+    bvh: bool,
+    camera_control: CameraControl,
 }
 
-impl Default for RaytracerSceneConverter {
-    fn default() -> Self {
-        let color_ramp = Gradient::new(vec![
-            vec3(0.0, 0.0, 0.0),
-            vec3(0.0, 0.0, 0.0),
-            vec3(0.5, 0.0, 1.0),
-            vec3(0.0, 0.0, 1.0),
-            vec3(0.0, 0.5, 1.0),
-            vec3(0.0, 0.1, 1.0),
-        ]);
+/// Describes the surface of the spheres of a range of frequency bands
+#[derive(Clone, Copy)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SphereMaterial {
+    /// The color of the sphere
+    pub color: Vec3,
+    /// The light emitted by the sphere
+    pub emission: Vec3,
+    /// The index of refraction of the sphere
+    pub ior: f32,
+    /// The roughness of the sphere between 0 (mirror) and 1 (diffuse)
+    pub roughness: f32,
+    /// How metallic the sphere is between 0 (dielectric) and 1 (metal)
+    pub metallic: f32,
+    /// How much light passes through the sphere between 0 (opaque) and 1
+    /// (glass)
+    pub transmission: f32,
+}
 
+impl Default for SphereMaterial {
+    fn default() -> Self {
         Self {
-            color_ramp,
-            n: SPHERE_N,
+            color: Vec3::splat(1.0),
+            emission: Vec3::ZERO,
+            ior: SPHERE_N,
+            roughness: 0.0,
+            metallic: 0.0,
+            transmission: 0.0,
         }
     }
 }
 
-impl<S: IntoIterator<Item = Sphere3D>> SceneConverter<S> for RaytracerSceneConverter {
-    type Scene = BasicRaytracerScene;
+/// Specifies how the [`RaytracerSceneConverter`] colors the spheres
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum SphereColoring {
+    /// The spheres are colored by the material table, or by their radius if
+    /// the table is empty
+    Material,
+    /// The spheres are colored by the time since the last peak of their
+    /// frequency band, cooling down from hot to cold
+    PeakRecency,
+}
 
-    fn convert(&self, spheres: S, width: f32, height: f32) -> Self::Scene {
-        let mut scene = BasicRaytracerScene::new(
-            PerspectiveCamera::new(
-                Mat4::from_translation(vec3(0.0f32, 0.0f32, -10.0f32)),
-                vec2(width, height),
-                std::f32::consts::PI / 4.0,
-                0.0001,
-                1000.0,
-            ),
-            ConstantBackground {
-                color: Vec3A::splat(1.0),
-            },
-            5,
-        );
+/// Specifies the texture the [`RaytracerSceneConverter`] maps onto the floor
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum FloorTexture {
+    /// The floor keeps its uniform color
+    None,
+    /// The floor shows a checkerboard
+    Checkerboard,
+    /// The floor shows the band levels of the last frames, the bands run
+    /// along one side and the frames along the other
+    Spectrogram,
+    /// The floor shows the Radiance HDR file at the floor texture path, e.g.
+    /// the album art
+    Image,
+}
+
+/// Specifies how spheres with a projected radius below the level of detail
+/// threshold are handled by the [`RaytracerSceneConverter`]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum LevelOfDetail {
+    /// Every sphere is passed to the renderer
+    Disabled,
+    /// Spheres below the threshold are dropped
+    Drop,
+    /// Spheres below the threshold which fall into the same screen cell and
+    /// depth range are merged into a single impostor sphere with the same
+    /// volume
+    Merge,
+}
+
+/// Specifies how the camera of the [`RaytracerSceneConverter`] maps the
+/// scene onto the screen
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum Projection {
+    /// The scene is seen through a perspective camera
+    Perspective,
+    /// Every direction around the camera is mapped onto the screen using an
+    /// equirectangular mapping, which is the format of 360° videos. The
+    /// aspect ratio should be locked to 2:1.
+    Panoramic,
+}
 
-        for Sphere3D { position, radius } in spheres {
-            let color = self.color_ramp.interpolate(radius as f32);
+/// Sums the properties of the spheres merged into an [`Impostor`] weighted
+/// by a factor per sphere
+#[derive(Default)]
+struct ImpostorSum {
+    position: Vec3A,
+    albedo: Vec3A,
+    emission: Vec3A,
+    roughness: f32,
+    metallic: f32,
+    ior: f32,
+    transmission: f32,
+    weight: f32,
+}
+
+impl ImpostorSum {
+    fn add(&mut self, position: Vec3A, material: &Material, emission: f32, weight: f32) {
+        // the glow of the sphere is merged into the emission of the material
+        self.position += position * weight;
+        self.albedo += material.albedo() * weight;
+        self.emission += (material.emission() + material.albedo() * emission) * weight;
+        self.roughness += material.roughness() * weight;
+        self.metallic += material.metallic() * weight;
+        self.ior += material.ior() * weight;
+        self.transmission += material.transmission() * weight;
+        self.weight += weight;
+    }
+
+    fn position(&self) -> Vec3A {
+        self.position / self.weight
+    }
+
+    fn material(&self) -> Material {
+        Material::new(self.albedo / self.weight)
+            .with_emission(self.emission / self.weight)
+            .with_roughness(self.roughness / self.weight)
+            .with_metallic(self.metallic / self.weight)
+            .with_ior(self.ior / self.weight)
+            .with_transmission(self.transmission / self.weight)
+    }
+}
 
-            scene.add_shape(Sphere::new(
-                vec3a(position.x, position.y, position.z),
-                vec3a(color.x, color.y, color.z),
-                radius,
-                self.n,
-            ));
+#[derive(Default)]
+struct Impostor {
+    by_volume: ImpostorSum,
+    by_count: ImpostorSum,
+}
+
+impl Impostor {
+    fn add(&mut self, position: Vec3A, radius: f32, material: &Material, emission: f32) {
+        let volume = radius * radius * radius;
+
+        self.by_volume.add(position, material, emission, volume);
+        self.by_count.add(position, material, emission, 1.0);
+    }
+
+    /// Returns the average weighted by the volume of the spheres. Spheres
+    /// without volume, e.g. on silent input, are averaged unweighted.
+    fn average(&self) -> &ImpostorSum {
+        match self.by_volume.weight > 0.0 {
+            true => &self.by_volume,
+            false => &self.by_count,
         }
+    }
 
-        let rect_transform = Mat4::from_translation(vec3(-10.0, 10.0, -10.0))
-            * Mat4::from_scale(Vec3::splat(10.0))
-            * Mat4::from_rotation_y(std::f32::consts::PI * 1.25)
-            * Mat4::from_rotation_x(std::f32::consts::PI * 0.25);
+    fn material(&self) -> Material {
+        self.average().material()
+    }
 
-        scene
-            .with_shape(Rect::new(rect_transform.inverse(), Vec3A::splat(10.0)))
-            .with_light(PointLight::new(
-                vec3a(-10.0, 10.0, -10.0),
-                Vec3A::splat(400.0),
-            ))
+    fn sphere(&self, material: u32) -> Sphere {
+        Sphere::new(
+            self.average().position(),
+            self.by_volume.weight.cbrt(),
+            material,
+        )
     }
 }
 
-impl Module for RaytracerSceneConverter {
-    type Settings = RaytracerSceneConverterSettings;
+impl RaytracerSceneConverter {
+    /// Returns how spheres below the level of detail threshold are handled
+    pub fn level_of_detail(&self) -> LevelOfDetail {
+        self.level_of_detail
+    }
 
-    fn set_settings(&mut self, _settings: Self::Settings) -> &mut Self {
+    /// Sets how spheres below the level of detail threshold are handled
+    pub fn set_level_of_detail(&mut self, level_of_detail: LevelOfDetail) -> &mut Self {
+        self.level_of_detail = level_of_detail;
         self
     }
 
-    fn settings(&self) -> Self::Settings {
-        RaytracerSceneConverterSettings
+    /// Sets how spheres below the level of detail threshold are handled
+    pub fn with_level_of_detail(mut self, level_of_detail: LevelOfDetail) -> Self {
+        self.set_level_of_detail(level_of_detail);
+        self
     }
-}
 
-/// Stores the settings of the [`RaytracerSceneConverter`]
-#[derive(Default, Clone)]
-pub struct RaytracerSceneConverterSettings;
+    /// Returns the projected radius in pixels below which spheres are
+    /// reduced
+    pub fn lod_threshold(&self) -> f32 {
+        self.lod_threshold
+    }
+
+    /// Sets the projected radius in pixels below which spheres are reduced
+    pub fn set_lod_threshold(&mut self, lod_threshold: f32) -> &mut Self {
+        self.lod_threshold = lod_threshold;
+        self
+    }
+
+    /// Sets the projected radius in pixels below which spheres are reduced
+    pub fn with_lod_threshold(mut self, lod_threshold: f32) -> Self {
+        self.set_lod_threshold(lod_threshold);
+        self
+    }
+
+    /// Returns the number of ray bounces
+    pub fn bounces(&self) -> u32 {
+        self.bounces
+    }
+
+    /// Sets the number of ray bounces
+    pub fn set_bounces(&mut self, bounces: u32) -> &mut Self {
+        self.bounces = bounces;
+        self
+    }
+
+    /// Sets the number of ray bounces
+    pub fn with_bounces(mut self, bounces: u32) -> Self {
+        self.set_bounces(bounces);
+        self
+    }
+
+    /// Returns how the camera maps the scene onto the screen
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Sets how the camera maps the scene onto the screen
+    pub fn set_projection(&mut self, projection: Projection) -> &mut Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Sets how the camera maps the scene onto the screen
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.set_projection(projection);
+        self
+    }
+
+    /// Returns the distance of the camera near clip plane
+    pub fn t_min(&self) -> f32 {
+        self.t_min
+    }
+
+    /// Sets the distance of the camera near clip plane
+    pub fn set_t_min(&mut self, t_min: f32) -> &mut Self {
+        self.t_min = t_min;
+        self
+    }
+
+    /// Sets the distance of the camera near clip plane
+    pub fn with_t_min(mut self, t_min: f32) -> Self {
+        self.set_t_min(t_min);
+        self
+    }
+
+    /// Returns the distance of the camera far clip plane
+    pub fn t_max(&self) -> f32 {
+        self.t_max
+    }
+
+    /// Sets the distance of the camera far clip plane
+    pub fn set_t_max(&mut self, t_max: f32) -> &mut Self {
+        self.t_max = t_max;
+        self
+    }
+
+    /// Sets the distance of the camera far clip plane
+    pub fn with_t_max(mut self, t_max: f32) -> Self {
+        self.set_t_max(t_max);
+        self
+    }
+
+    /// Returns the material table. The frequency bands are spread evenly
+    /// over the materials. If the table is empty the spheres are colored by
+    /// their radius.
+    pub fn materials(&self) -> &[SphereMaterial] {
+        &self.materials
+    }
+
+    /// Sets the material table
+    pub fn set_materials(&mut self, materials: Vec<SphereMaterial>) -> &mut Self {
+        self.materials = materials;
+        self
+    }
+
+    /// Sets the material table
+    pub fn with_materials(mut self, materials: Vec<SphereMaterial>) -> Self {
+        self.set_materials(materials);
+        self
+    }
+
+    /// Returns the aspect ratio of the area the scene is rendered into
+    pub fn aspect_ratio(&self) -> AspectRatio {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: AspectRatio) -> &mut Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    /// Returns the number of point lights arranged around the scene
+    pub fn light_count(&self) -> usize {
+        self.light_count
+    }
+
+    /// Sets the number of point lights arranged around the scene. At least
+    /// one light is used.
+    pub fn set_light_count(&mut self, light_count: usize) -> &mut Self {
+        self.light_count = light_count.max(1);
+        self
+    }
+
+    /// Sets the number of point lights arranged around the scene
+    pub fn with_light_count(mut self, light_count: usize) -> Self {
+        self.set_light_count(light_count);
+        self
+    }
+
+    /// Returns whether the light colors march through the color ramp.
+    /// Otherwise the lights are white.
+    pub fn cycle_lights(&self) -> bool {
+        self.cycle_lights
+    }
+
+    /// Sets whether the light colors march through the color ramp
+    pub fn set_cycle_lights(&mut self, cycle_lights: bool) -> &mut Self {
+        self.cycle_lights = cycle_lights;
+        self
+    }
+
+    /// Sets whether the light colors march through the color ramp
+    pub fn with_cycle_lights(mut self, cycle_lights: bool) -> Self {
+        self.set_cycle_lights(cycle_lights);
+        self
+    }
+
+    /// Returns the speed of the light colors in gradient cycles per second,
+    /// or per beat if the tempo is known
+    pub fn light_cycle_speed(&self) -> f32 {
+        self.light_cycle_speed
+    }
+
+    /// Sets the speed of the light colors in gradient cycles per second, or
+    /// per beat if the tempo is known
+    pub fn set_light_cycle_speed(&mut self, light_cycle_speed: f32) -> &mut Self {
+        self.light_cycle_speed = light_cycle_speed;
+        self
+    }
+
+    /// Sets the speed of the light colors in gradient cycles per second, or
+    /// per beat if the tempo is known
+    pub fn with_light_cycle_speed(mut self, light_cycle_speed: f32) -> Self {
+        self.set_light_cycle_speed(light_cycle_speed);
+        self
+    }
+
+    /// Returns the tempo of the audio in beats per minute if known
+    pub fn bpm(&self) -> Option<f32> {
+        self.bpm
+    }
+
+    /// Sets the tempo of the audio in beats per minute. The light colors
+    /// advance per beat instead of per second while the tempo is known.
+    pub fn set_bpm(&mut self, bpm: Option<f32>) -> &mut Self {
+        self.bpm = bpm;
+        self
+    }
+
+    /// Returns the palette of the sphere and light colors
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    /// Sets the palette of the sphere and light colors. The peak recency
+    /// coloring uses the palette too, unless it is [`Palette::Classic`].
+    pub fn set_palette(&mut self, palette: Palette) -> &mut Self {
+        if self.palette != palette {
+            self.palette = palette;
+            self.color_ramp = palette.gradient();
+            self.peak_ramp = match palette {
+                Palette::Classic => classic_peak_ramp(),
+                _ => palette.gradient(),
+            };
+        }
+
+        self
+    }
+
+    /// Sets the palette of the sphere and light colors
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.set_palette(palette);
+        self
+    }
+
+    /// Returns how the spheres are colored
+    pub fn sphere_coloring(&self) -> SphereColoring {
+        self.sphere_coloring
+    }
+
+    /// Sets how the spheres are colored
+    pub fn set_sphere_coloring(&mut self, sphere_coloring: SphereColoring) -> &mut Self {
+        self.sphere_coloring = sphere_coloring;
+        self
+    }
+
+    /// Sets how the spheres are colored
+    pub fn with_sphere_coloring(mut self, sphere_coloring: SphereColoring) -> Self {
+        self.set_sphere_coloring(sphere_coloring);
+        self
+    }
+
+    /// Returns the time in seconds a sphere takes to cool down after a peak
+    pub fn peak_cooldown(&self) -> f32 {
+        self.peak_cooldown
+    }
+
+    /// Sets the time in seconds a sphere takes to cool down after a peak
+    pub fn set_peak_cooldown(&mut self, peak_cooldown: f32) -> &mut Self {
+        self.peak_cooldown = peak_cooldown.max(0.001);
+        self
+    }
+
+    /// Sets the time in seconds a sphere takes to cool down after a peak
+    pub fn with_peak_cooldown(mut self, peak_cooldown: f32) -> Self {
+        self.set_peak_cooldown(peak_cooldown);
+        self
+    }
+
+    /// Returns the scale of the light spheres emit proportional to their band
+    /// level
+    pub fn level_emission(&self) -> f32 {
+        self.level_emission
+    }
+
+    /// Sets the scale of the light spheres emit proportional to their band
+    /// level
+    pub fn set_level_emission(&mut self, level_emission: f32) -> &mut Self {
+        self.level_emission = level_emission.max(0.0);
+        self
+    }
+
+    /// Sets the scale of the light spheres emit proportional to their band
+    /// level
+    pub fn with_level_emission(mut self, level_emission: f32) -> Self {
+        self.set_level_emission(level_emission);
+        self
+    }
+
+    /// Returns the gamma applied to the band level before it is turned into
+    /// emitted light. Higher values let only loud spheres glow.
+    pub fn level_emission_gamma(&self) -> f32 {
+        self.level_emission_gamma
+    }
+
+    /// Sets the gamma applied to the band level before it is turned into
+    /// emitted light
+    pub fn set_level_emission_gamma(&mut self, level_emission_gamma: f32) -> &mut Self {
+        self.level_emission_gamma = level_emission_gamma;
+        self
+    }
+
+    /// Sets the gamma applied to the band level before it is turned into
+    /// emitted light
+    pub fn with_level_emission_gamma(mut self, level_emission_gamma: f32) -> Self {
+        self.set_level_emission_gamma(level_emission_gamma);
+        self
+    }
+
+    /// Returns the height of the floor ripples at full bass level
+    pub fn ripple_amplitude(&self) -> f32 {
+        self.ripple_amplitude
+    }
+
+    /// Sets the height of the floor ripples at full bass level
+    pub fn set_ripple_amplitude(&mut self, ripple_amplitude: f32) -> &mut Self {
+        self.ripple_amplitude = ripple_amplitude;
+        self
+    }
+
+    /// Sets the height of the floor ripples at full bass level
+    pub fn with_ripple_amplitude(mut self, ripple_amplitude: f32) -> Self {
+        self.set_ripple_amplitude(ripple_amplitude);
+        self
+    }
+
+    /// Returns the angular frequency of the floor ripples along the radius
+    /// of the floor
+    pub fn ripple_frequency(&self) -> f32 {
+        self.ripple_frequency
+    }
+
+    /// Sets the angular frequency of the floor ripples along the radius of
+    /// the floor
+    pub fn set_ripple_frequency(&mut self, ripple_frequency: f32) -> &mut Self {
+        self.ripple_frequency = ripple_frequency;
+        self
+    }
+
+    /// Sets the angular frequency of the floor ripples along the radius of
+    /// the floor
+    pub fn with_ripple_frequency(mut self, ripple_frequency: f32) -> Self {
+        self.set_ripple_frequency(ripple_frequency);
+        self
+    }
+
+    /// Returns the speed in radians per second with which the floor ripples
+    /// travel outwards
+    pub fn ripple_speed(&self) -> f32 {
+        self.ripple_speed
+    }
+
+    /// Sets the speed in radians per second with which the floor ripples
+    /// travel outwards
+    pub fn set_ripple_speed(&mut self, ripple_speed: f32) -> &mut Self {
+        self.ripple_speed = ripple_speed;
+        self
+    }
+
+    /// Sets the speed in radians per second with which the floor ripples
+    /// travel outwards
+    pub fn with_ripple_speed(mut self, ripple_speed: f32) -> Self {
+        self.set_ripple_speed(ripple_speed);
+        self
+    }
+
+    /// Returns the number of particles emitted around every sphere
+    pub fn particles(&self) -> usize {
+        self.particles
+    }
+
+    /// Sets the number of particles emitted around every sphere
+    pub fn set_particles(&mut self, particles: usize) -> &mut Self {
+        self.particles = particles;
+        self
+    }
+
+    /// Sets the number of particles emitted around every sphere
+    pub fn with_particles(mut self, particles: usize) -> Self {
+        self.set_particles(particles);
+        self
+    }
+
+    /// Returns the radius of the particles relative to their sphere at full
+    /// band level
+    pub fn particle_size(&self) -> f32 {
+        self.particle_size
+    }
+
+    /// Sets the radius of the particles relative to their sphere at full band
+    /// level
+    pub fn set_particle_size(&mut self, particle_size: f32) -> &mut Self {
+        self.particle_size = particle_size.max(0.0);
+        self
+    }
+
+    /// Sets the radius of the particles relative to their sphere at full band
+    /// level
+    pub fn with_particle_size(mut self, particle_size: f32) -> Self {
+        self.set_particle_size(particle_size);
+        self
+    }
+
+    /// Returns the time in seconds the shutter is open for, over which moving
+    /// spheres are blurred
+    pub fn shutter(&self) -> f32 {
+        self.shutter
+    }
+
+    /// Sets the time in seconds the shutter is open for, over which moving
+    /// spheres are blurred. Zero disables the motion blur.
+    pub fn set_shutter(&mut self, shutter: f32) -> &mut Self {
+        self.shutter = shutter.max(0.0);
+        self
+    }
+
+    /// Sets the time in seconds the shutter is open for, over which moving
+    /// spheres are blurred. Zero disables the motion blur.
+    pub fn with_shutter(mut self, shutter: f32) -> Self {
+        self.set_shutter(shutter);
+        self
+    }
+
+    /// Returns the path of the OBJ file the background mesh was loaded from
+    pub fn mesh_path(&self) -> Option<&PathBuf> {
+        self.mesh_path.as_ref()
+    }
+
+    /// Sets the path of the OBJ file the background mesh is loaded from. The
+    /// file is only loaded if the path changed. If it can't be loaded the
+    /// mesh is removed.
+    pub fn set_mesh_path(&mut self, mesh_path: Option<PathBuf>) -> &mut Self {
+        if self.mesh_path == mesh_path {
+            return self;
+        }
+
+        let mesh = match &mesh_path {
+            Some(path) => load_obj(path, 0).unwrap_or_else(|error| {
+                eprintln!("Failed to load mesh {:?}: {}", path, error);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+
+        self.mesh_path = mesh_path;
+        self.mesh = mesh.into();
+        self
+    }
+
+    /// Sets the path of the OBJ file the background mesh is loaded from
+    pub fn with_mesh_path(mut self, mesh_path: Option<PathBuf>) -> Self {
+        self.set_mesh_path(mesh_path);
+        self
+    }
+
+    /// Returns the triangles of the background mesh
+    pub fn mesh(&self) -> &[Triangle] {
+        &self.mesh
+    }
+
+    /// Sets the triangles of the background mesh and forgets the path it was
+    /// loaded from
+    pub fn set_mesh(&mut self, mesh: Vec<Triangle>) -> &mut Self {
+        self.mesh_path = None;
+        self.mesh = mesh.into();
+        self
+    }
+
+    /// Sets the triangles of the background mesh
+    pub fn with_mesh(mut self, mesh: Vec<Triangle>) -> Self {
+        self.set_mesh(mesh);
+        self
+    }
+
+    /// Returns the scale of the background mesh
+    pub fn mesh_scale(&self) -> f32 {
+        self.mesh_scale
+    }
+
+    /// Sets the scale of the background mesh
+    pub fn set_mesh_scale(&mut self, mesh_scale: f32) -> &mut Self {
+        self.mesh_scale = mesh_scale.max(0.0);
+        self
+    }
+
+    /// Sets the scale of the background mesh
+    pub fn with_mesh_scale(mut self, mesh_scale: f32) -> Self {
+        self.set_mesh_scale(mesh_scale);
+        self
+    }
+
+    /// Returns the distance of the background mesh behind the center of the
+    /// scene
+    pub fn mesh_distance(&self) -> f32 {
+        self.mesh_distance
+    }
+
+    /// Sets the distance of the background mesh behind the center of the
+    /// scene
+    pub fn set_mesh_distance(&mut self, mesh_distance: f32) -> &mut Self {
+        self.mesh_distance = mesh_distance;
+        self
+    }
+
+    /// Sets the distance of the background mesh behind the center of the
+    /// scene
+    pub fn with_mesh_distance(mut self, mesh_distance: f32) -> Self {
+        self.set_mesh_distance(mesh_distance);
+        self
+    }
+
+    /// Returns the path of the Radiance HDR file the environment map was
+    /// loaded from
+    pub fn environment_map_path(&self) -> Option<&PathBuf> {
+        self.environment_map_path.as_ref()
+    }
+
+    /// Sets the path of the Radiance HDR file the environment map is loaded
+    /// from. The environment map surrounds the scene and lights it. The file
+    /// is only loaded if the path changed. If it can't be loaded the
+    /// background is uniformly white.
+    pub fn set_environment_map_path(&mut self, environment_map_path: Option<PathBuf>) -> &mut Self {
+        if self.environment_map_path == environment_map_path {
+            return self;
+        }
+
+        self.environment_map = environment_map_path.as_ref().and_then(|path| {
+            load_hdr(path)
+                .map_err(|error| eprintln!("Failed to load environment map {:?}: {}", path, error))
+                .ok()
+                .map(Arc::new)
+        });
+        self.environment_map_path = environment_map_path;
+        self
+    }
+
+    /// Sets the path of the Radiance HDR file the environment map is loaded
+    /// from
+    pub fn with_environment_map_path(mut self, environment_map_path: Option<PathBuf>) -> Self {
+        self.set_environment_map_path(environment_map_path);
+        self
+    }
+
+    /// Returns the brightness of the background
+    pub fn background_intensity(&self) -> f32 {
+        self.background_intensity
+    }
+
+    /// Sets the brightness of the background
+    pub fn set_background_intensity(&mut self, background_intensity: f32) -> &mut Self {
+        self.background_intensity = background_intensity.max(0.0);
+        self
+    }
+
+    /// Sets the brightness of the background
+    pub fn with_background_intensity(mut self, background_intensity: f32) -> Self {
+        self.set_background_intensity(background_intensity);
+        self
+    }
+
+    /// Returns the angle in degrees the environment map is rotated by around
+    /// the vertical axis
+    pub fn background_rotation(&self) -> f32 {
+        self.background_rotation
+    }
+
+    /// Sets the angle in degrees the environment map is rotated by around the
+    /// vertical axis
+    pub fn set_background_rotation(&mut self, background_rotation: f32) -> &mut Self {
+        self.background_rotation = background_rotation;
+        self
+    }
+
+    /// Sets the angle in degrees the environment map is rotated by around the
+    /// vertical axis
+    pub fn with_background_rotation(mut self, background_rotation: f32) -> Self {
+        self.set_background_rotation(background_rotation);
+        self
+    }
+
+    fn background(&self) -> ImageBackgroundArgs {
+        let irradiance_lod = self
+            .environment_map
+            .as_ref()
+            .map_or(0.0, |environment_map| {
+                (environment_map.width() as f32 / IRRADIANCE_WIDTH)
+                    .log2()
+                    .max(0.0)
+            });
+
+        ImageBackgroundArgs::new(Vec3A::splat(self.background_intensity))
+            .with_rotation(self.background_rotation.to_radians())
+            .with_irradiance_lod(irradiance_lod)
+    }
+
+    /// Returns the texture mapped onto the floor
+    pub fn floor_texture(&self) -> FloorTexture {
+        self.floor_texture
+    }
+
+    /// Sets the texture mapped onto the floor
+    pub fn set_floor_texture(&mut self, floor_texture: FloorTexture) -> &mut Self {
+        self.floor_texture = floor_texture;
+        self
+    }
+
+    /// Sets the texture mapped onto the floor
+    pub fn with_floor_texture(mut self, floor_texture: FloorTexture) -> Self {
+        self.set_floor_texture(floor_texture);
+        self
+    }
+
+    /// Returns the path of the Radiance HDR file the image floor texture was
+    /// loaded from
+    pub fn floor_texture_path(&self) -> Option<&PathBuf> {
+        self.floor_texture_path.as_ref()
+    }
+
+    /// Sets the path of the Radiance HDR file the image floor texture is
+    /// loaded from. The file is only loaded if the path changed. If it can't
+    /// be loaded the floor keeps its uniform color.
+    pub fn set_floor_texture_path(&mut self, floor_texture_path: Option<PathBuf>) -> &mut Self {
+        if self.floor_texture_path == floor_texture_path {
+            return self;
+        }
+
+        self.floor_image = floor_texture_path.as_ref().and_then(|path| {
+            load_hdr(path)
+                .map_err(|error| eprintln!("Failed to load floor texture {:?}: {}", path, error))
+                .ok()
+                .map(Arc::new)
+        });
+        self.floor_texture_path = floor_texture_path;
+        self
+    }
+
+    /// Sets the path of the Radiance HDR file the image floor texture is
+    /// loaded from
+    pub fn with_floor_texture_path(mut self, floor_texture_path: Option<PathBuf>) -> Self {
+        self.set_floor_texture_path(floor_texture_path);
+        self
+    }
+
+    /// Returns how often the texture repeats along each side of the floor
+    pub fn floor_texture_scale(&self) -> f32 {
+        self.floor_texture_scale
+    }
+
+    /// Sets how often the texture repeats along each side of the floor
+    pub fn set_floor_texture_scale(&mut self, floor_texture_scale: f32) -> &mut Self {
+        self.floor_texture_scale = floor_texture_scale.max(0.0);
+        self
+    }
+
+    /// Sets how often the texture repeats along each side of the floor
+    pub fn with_floor_texture_scale(mut self, floor_texture_scale: f32) -> Self {
+        self.set_floor_texture_scale(floor_texture_scale);
+        self
+    }
+
+    /// Returns the path of the Radiance HDR file the normal map was loaded
+    /// from
+    pub fn normal_map_path(&self) -> Option<&PathBuf> {
+        self.normal_map_path.as_ref()
+    }
+
+    /// Sets the path of the Radiance HDR file the tangent space normal map of
+    /// the floor and the spheres is loaded from. The file is only loaded if
+    /// the path changed. Without a path or if it can't be loaded, the edges
+    /// of the squares of the checkerboard texture are beveled.
+    pub fn set_normal_map_path(&mut self, normal_map_path: Option<PathBuf>) -> &mut Self {
+        if self.normal_map_path == normal_map_path {
+            return self;
+        }
+
+        self.normal_map = normal_map_path
+            .as_ref()
+            .and_then(|path| {
+                load_hdr(path)
+                    .map_err(|error| eprintln!("Failed to load normal map {:?}: {}", path, error))
+                    .ok()
+            })
+            .unwrap_or_else(tile_normal_map)
+            .into();
+        self.normal_map_path = normal_map_path;
+        self
+    }
+
+    /// Sets the path of the Radiance HDR file the tangent space normal map of
+    /// the floor and the spheres is loaded from
+    pub fn with_normal_map_path(mut self, normal_map_path: Option<PathBuf>) -> Self {
+        self.set_normal_map_path(normal_map_path);
+        self
+    }
+
+    /// Returns the scale of the tilt of the normal map of the floor, which
+    /// is disabled at zero
+    pub fn floor_normal_strength(&self) -> f32 {
+        self.floor_normal_strength
+    }
+
+    /// Sets the scale of the tilt of the normal map of the floor. The normal
+    /// map of the floor is disabled at zero.
+    pub fn set_floor_normal_strength(&mut self, floor_normal_strength: f32) -> &mut Self {
+        self.floor_normal_strength = floor_normal_strength.max(0.0);
+        self
+    }
+
+    /// Sets the scale of the tilt of the normal map of the floor
+    pub fn with_floor_normal_strength(mut self, floor_normal_strength: f32) -> Self {
+        self.set_floor_normal_strength(floor_normal_strength);
+        self
+    }
+
+    /// Returns the scale of the tilt of the normal map of the spheres, which
+    /// is disabled at zero
+    pub fn sphere_normal_strength(&self) -> f32 {
+        self.sphere_normal_strength
+    }
+
+    /// Sets the scale of the tilt of the normal map of the spheres. The
+    /// normal map of the spheres is disabled at zero.
+    pub fn set_sphere_normal_strength(&mut self, sphere_normal_strength: f32) -> &mut Self {
+        self.sphere_normal_strength = sphere_normal_strength.max(0.0);
+        self
+    }
+
+    /// Sets the scale of the tilt of the normal map of the spheres
+    pub fn with_sphere_normal_strength(mut self, sphere_normal_strength: f32) -> Self {
+        self.set_sphere_normal_strength(sphere_normal_strength);
+        self
+    }
+
+    fn floor_surface_texture(
+        &self,
+        bands: &[&Sphere3D],
+        band_count: usize,
+    ) -> Option<Arc<HdrImage>> {
+        match self.floor_texture {
+            FloorTexture::None => None,
+            FloorTexture::Checkerboard => Some(self.checkerboard.clone()),
+            FloorTexture::Spectrogram => Some(Arc::new(self.spectrogram(bands, band_count))),
+            FloorTexture::Image => self.floor_image.clone(),
+        }
+    }
+
+    /// Adds the band levels of the frame to the history and returns it as
+    /// an image, with the newest frame in the top row
+    fn spectrogram(&self, bands: &[&Sphere3D], band_count: usize) -> HdrImage {
+        let mut levels = vec![0.0f32; band_count];
+
+        for sphere in bands {
+            levels[sphere.band] = levels[sphere.band].max(sphere.level);
+        }
+
+        let mut history = self.spectrogram.lock().unwrap();
+
+        // the history restarts when the number of bands changed
+        if history.front().map_or(false, |row| row.len() != band_count) {
+            history.clear();
+        }
+
+        history.push_front(levels);
+        history.truncate(SPECTROGRAM_HISTORY);
+
+        let mut pixels = Vec::with_capacity(band_count * SPECTROGRAM_HISTORY);
+
+        for frame in 0..SPECTROGRAM_HISTORY {
+            for band in 0..band_count {
+                let level = history
+                    .get(frame)
+                    .map_or(0.0, |levels| levels[band].max(0.0).min(1.0));
+
+                pixels.push(Vec3A::from(self.color_ramp.interpolate(level)) * level);
+            }
+        }
+
+        HdrImage::new(band_count, SPECTROGRAM_HISTORY, pixels)
+    }
+
+    /// Returns whether a bounding volume hierarchy is built over the spheres
+    /// and the triangles of the background mesh
+    pub fn bvh(&self) -> bool {
+        self.bvh
+    }
+
+    /// Sets whether a bounding volume hierarchy is built over the spheres and
+    /// the triangles of the background mesh
+    pub fn set_bvh(&mut self, bvh: bool) -> &mut Self {
+        self.bvh = bvh;
+        self
+    }
+
+    /// Sets whether a bounding volume hierarchy is built over the spheres and
+    /// the triangles of the background mesh
+    pub fn with_bvh(mut self, bvh: bool) -> Self {
+        self.set_bvh(bvh);
+        self
+    }
+
+    /// Returns the intensity of the sun, which lights the scene from
+    /// infinitely far away. The sun is disabled at zero.
+    pub fn sun_intensity(&self) -> f32 {
+        self.sun_intensity
+    }
+
+    /// Sets the intensity of the sun. The sun is disabled at zero.
+    pub fn set_sun_intensity(&mut self, sun_intensity: f32) -> &mut Self {
+        self.sun_intensity = sun_intensity.max(0.0);
+        self
+    }
+
+    /// Sets the intensity of the sun. The sun is disabled at zero.
+    pub fn with_sun_intensity(mut self, sun_intensity: f32) -> Self {
+        self.set_sun_intensity(sun_intensity);
+        self
+    }
+
+    /// Returns the angle in degrees of the sun above the horizon
+    pub fn sun_elevation(&self) -> f32 {
+        self.sun_elevation
+    }
+
+    /// Sets the angle in degrees of the sun above the horizon
+    pub fn set_sun_elevation(&mut self, sun_elevation: f32) -> &mut Self {
+        self.sun_elevation = sun_elevation;
+        self
+    }
+
+    /// Sets the angle in degrees of the sun above the horizon
+    pub fn with_sun_elevation(mut self, sun_elevation: f32) -> Self {
+        self.set_sun_elevation(sun_elevation);
+        self
+    }
+
+    /// Returns the angle in degrees of the sun around the vertical axis,
+    /// measured from straight behind the camera towards the left
+    pub fn sun_azimuth(&self) -> f32 {
+        self.sun_azimuth
+    }
+
+    /// Sets the angle in degrees of the sun around the vertical axis,
+    /// measured from straight behind the camera towards the left
+    pub fn set_sun_azimuth(&mut self, sun_azimuth: f32) -> &mut Self {
+        self.sun_azimuth = sun_azimuth;
+        self
+    }
+
+    /// Sets the angle in degrees of the sun around the vertical axis,
+    /// measured from straight behind the camera towards the left
+    pub fn with_sun_azimuth(mut self, sun_azimuth: f32) -> Self {
+        self.set_sun_azimuth(sun_azimuth);
+        self
+    }
+
+    fn sun(&self) -> DirectionalLight {
+        let elevation = self.sun_elevation.to_radians();
+        let azimuth = self.sun_azimuth.to_radians();
+
+        // the camera looks along the positive z axis with y pointing up
+        let direction = vec3a(
+            -elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+
+        DirectionalLight::new(direction, Vec3A::splat(self.sun_intensity))
+    }
+
+    fn light(&self, index: usize, phase: f32) -> PointLight {
+        let offset = index as f32 / self.light_count as f32;
+        let angle = std::f32::consts::PI * 0.75 + offset * std::f32::consts::TAU;
+
+        let position = vec3a(
+            angle.cos() * LIGHT_DISTANCE,
+            angle.sin() * LIGHT_DISTANCE,
+            -10.0,
+        );
+
+        let color = match self.cycle_lights {
+            // the gradient is walked back and forth to avoid jumping from
+            // the last to the first stop
+            true => {
+                let t = (phase + offset).fract();
+                self.color_ramp
+                    .interpolate(1.0 - (t * 2.0 - 1.0).abs())
+                    .into()
+            }
+            false => Vec3A::ONE,
+        };
+
+        PointLight::new(position, color * LIGHT_INTENSITY / self.light_count as f32)
+    }
+
+    /// Creates the perspective camera used for culling and the camera the
+    /// scene is rendered with
+    fn cameras(&self, viewport: &Viewport) -> (PerspectiveCamera, SceneCamera) {
+        // the field of view is widened on wide viewports so it always
+        // applies to the shorter axis
+        let fov = (FOV.tan() * viewport.aspect_scale().x).atan();
+
+        let transform = self
+            .camera_control
+            .apply(Mat4::from_translation(vec3(0.0f32, 0.0f32, -10.0f32)));
+
+        let perspective =
+            PerspectiveCamera::new(transform, viewport.size, fov, self.t_min, self.t_max)
+                .with_screen_offset(viewport.offset);
+
+        let camera = match self.projection {
+            Projection::Perspective => SceneCamera::from(perspective.clone()),
+            Projection::Panoramic => SceneCamera::from(
+                PanoramicCamera::new(transform, viewport.size, self.t_min, self.t_max)
+                    .with_screen_offset(viewport.offset),
+            ),
+        };
+
+        (perspective, camera)
+    }
+
+    fn sphere_material(&self, sphere: &Sphere3D, band_count: usize) -> Material {
+        if sphere.emissive {
+            return Material::new(Vec3A::ONE)
+                .with_roughness(0.0)
+                .with_ior(self.n)
+                .with_emission(Vec3A::splat(SPHERE_EMISSION));
+        }
+
+        let peak_color = match self.sphere_coloring {
+            SphereColoring::Material => None,
+            SphereColoring::PeakRecency => {
+                let heat = (-sphere.peak_age / self.peak_cooldown).exp();
+                Some(self.peak_ramp.interpolate(heat))
+            }
+        };
+
+        if self.materials.is_empty() {
+            let color =
+                peak_color.unwrap_or_else(|| self.color_ramp.interpolate(sphere.radius as f32));
+
+            return Material::new(color.into())
+                .with_roughness(0.0)
+                .with_ior(self.n);
+        }
+
+        let material = &self.materials[sphere.band * self.materials.len() / band_count];
+        let color = peak_color.unwrap_or(material.color);
+
+        Material::new(color.into())
+            .with_roughness(material.roughness)
+            .with_metallic(material.metallic)
+            .with_ior(material.ior)
+            .with_transmission(material.transmission)
+            .with_emission(material.emission.into())
+    }
+
+    /// Returns the brightness of the glow of the sphere, so loud spheres
+    /// glow in their own color and light up their neighbours
+    fn sphere_emission(&self, sphere: &Sphere3D) -> f32 {
+        match sphere.emissive {
+            true => 0.0,
+            false => self.level_emission * sphere.level.max(0.0).powf(self.level_emission_gamma),
+        }
+    }
+
+    /// Places a particle on a shell around the sphere. The particles shrink
+    /// with the level of the band, so quiet bands can be culled by the
+    /// renderer.
+    fn particle(
+        &self,
+        sphere: &Sphere,
+        band: usize,
+        index: usize,
+        level: f32,
+        time: f32,
+    ) -> Sphere {
+        let (band, index) = (band as u32, index as u32);
+
+        let z = random(0, band, index, 0) * 2.0 - 1.0;
+        let angle = random(0, band, index, 1) * std::f32::consts::TAU + time * PARTICLE_SPEED;
+        let distance = 1.0 + random(0, band, index, 2) * PARTICLE_SPREAD;
+
+        let ring = (1.0 - z * z).sqrt();
+        let direction = vec3a(ring * angle.cos(), ring * angle.sin(), z);
+
+        Sphere::new(
+            sphere.position() + direction * sphere.radius() * distance,
+            sphere.radius() * self.particle_size * level.clamp(0.0, 1.0),
+            sphere.material() as u32,
+        )
+        .with_motion(sphere.motion())
+        .with_emission(sphere.emission())
+    }
+}
+
+/// The checkerboard floor texture, which starts with a bright square
+fn checkerboard() -> HdrImage {
+    let size = CHECKERBOARD_SQUARES * CHECKERBOARD_SQUARE_SIZE;
+
+    let pixels = (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .map(
+            |(x, y)| match (x / CHECKERBOARD_SQUARE_SIZE + y / CHECKERBOARD_SQUARE_SIZE) % 2 {
+                0 => Vec3A::ONE,
+                _ => Vec3A::splat(CHECKERBOARD_DARK),
+            },
+        )
+        .collect();
+
+    HdrImage::new(size, size, pixels)
+}
+
+/// The tile normal map, which bevels the edges of the squares of the
+/// checkerboard texture. The normals are encoded like in a tangent space
+/// normal map.
+fn tile_normal_map() -> HdrImage {
+    let size = CHECKERBOARD_SQUARES * CHECKERBOARD_SQUARE_SIZE;
+
+    // the height rises from the edges of a square over the bevel
+    let height = |x: usize, y: usize| {
+        let x = x % CHECKERBOARD_SQUARE_SIZE;
+        let y = y % CHECKERBOARD_SQUARE_SIZE;
+        let edge = x
+            .min(CHECKERBOARD_SQUARE_SIZE - 1 - x)
+            .min(y)
+            .min(CHECKERBOARD_SQUARE_SIZE - 1 - y);
+
+        (edge as f32 / TILE_BEVEL).min(1.0)
+    };
+
+    let pixels = (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let left = (x + size - 1) % size;
+            let right = (x + 1) % size;
+            let up = (y + size - 1) % size;
+            let down = (y + 1) % size;
+
+            // the rows run downwards, while the green channel points up
+            let slope_x = (height(right, y) - height(left, y)) * 0.5;
+            let slope_y = (height(x, up) - height(x, down)) * 0.5;
+
+            vec3a(-slope_x, -slope_y, 1.0).normalize() * 0.5 + 0.5
+        })
+        .collect();
+
+    HdrImage::new(size, size, pixels)
+}
+
+/// The original colors of the peak recency coloring, which cool down from
+/// white over orange and violet to dark blue
+fn classic_peak_ramp() -> Gradient {
+    Gradient::new(vec![
+        vec3(0.0, 0.05, 0.2),
+        vec3(0.0, 0.2, 0.8),
+        vec3(0.6, 0.0, 0.8),
+        vec3(1.0, 0.1, 0.0),
+        vec3(1.0, 0.6, 0.0),
+        vec3(1.0, 1.0, 0.8),
+    ])
+}
+
+impl Default for RaytracerSceneConverter {
+    fn default() -> Self {
+        Self {
+            color_ramp: Palette::Classic.gradient(),
+            n: SPHERE_N,
+            level_of_detail: LevelOfDetail::Disabled,
+            lod_threshold: LOD_THRESHOLD,
+            bounces: BOUNCES,
+            projection: Projection::Perspective,
+            t_min: T_MIN,
+            t_max: T_MAX,
+            materials: Vec::new(),
+            aspect_ratio: AspectRatio::Free,
+            light_count: LIGHT_COUNT,
+            cycle_lights: false,
+            light_cycle_speed: LIGHT_CYCLE_SPEED,
+            sun_intensity: SUN_INTENSITY,
+            sun_elevation: SUN_ELEVATION,
+            sun_azimuth: SUN_AZIMUTH,
+            bpm: None,
+            time: 0.0,
+            light_phase: 0.0,
+            palette: Palette::Classic,
+            sphere_coloring: SphereColoring::Material,
+            peak_ramp: classic_peak_ramp(),
+            peak_cooldown: PEAK_COOLDOWN,
+            level_emission: LEVEL_EMISSION,
+            level_emission_gamma: LEVEL_EMISSION_GAMMA,
+            ripple_amplitude: RIPPLE_AMPLITUDE,
+            ripple_frequency: RIPPLE_FREQUENCY,
+            ripple_speed: RIPPLE_SPEED,
+            particles: PARTICLES,
+            particle_size: PARTICLE_SIZE,
+            shutter: SHUTTER,
+            mesh_path: None,
+            mesh: Arc::new([]),
+            mesh_scale: MESH_SCALE,
+            mesh_distance: MESH_DISTANCE,
+            environment_map_path: None,
+            environment_map: None,
+            background_intensity: BACKGROUND_INTENSITY,
+            background_rotation: BACKGROUND_ROTATION,
+            floor_texture: FloorTexture::None,
+            floor_texture_path: None,
+            floor_image: None,
+            floor_texture_scale: FLOOR_TEXTURE_SCALE,
+            checkerboard: Arc::new(checkerboard()),
+            spectrogram: Mutex::new(VecDeque::new()),
+            normal_map_path: None,
+            normal_map: Arc::new(tile_normal_map()),
+            floor_normal_strength: FLOOR_NORMAL_STRENGTH,
+            sphere_normal_strength: SPHERE_NORMAL_STRENGTH,
+            bvh: true,
+            camera_control: CameraControl::default(),
+        }
+    }
+}
+
+impl<S: IntoIterator<Item = Sphere3D>> SceneConverter<S> for RaytracerSceneConverter {
+    type Scene = BasicRaytracerScene;
+
+    fn convert(&self, spheres: S, width: f32, height: f32) -> Self::Scene {
+        let viewport = Viewport::new(width, height, self.aspect_ratio);
+        let (perspective, camera) = self.cameras(&viewport);
+
+        let view = perspective.view();
+        let projection = perspective.projection();
+
+        let mut scene = BasicRaytracerScene::new(camera, self.background(), self.bounces)
+            .with_environment_map(self.environment_map.clone());
+
+        let mut impostors = BTreeMap::<(i32, i32, i32), Impostor>::new();
+
+        let spheres = spheres.into_iter().collect::<Vec<_>>();
+
+        let bands = spheres
+            .iter()
+            .filter(|sphere| !sphere.emissive)
+            .collect::<Vec<_>>();
+        let bass = &bands[..(bands.len() + 2) / 3];
+        let bass_level = match bass.len() {
+            0 => 0.0,
+            len => bass.iter().map(|sphere| sphere.level).sum::<f32>() / len as f32,
+        };
+
+        let band_count = spheres
+            .iter()
+            .filter(|sphere| !sphere.emissive)
+            .map(|sphere| sphere.band + 1)
+            .max();
+
+        let elapsed = self.time;
+
+        for sphere_3d in &spheres {
+            let position = vec3a(
+                sphere_3d.position.x,
+                sphere_3d.position.y,
+                sphere_3d.position.z,
+            );
+            let material = self.sphere_material(sphere_3d, band_count.unwrap_or(1));
+            let emission = self.sphere_emission(sphere_3d);
+
+            // spheres behind the camera are kept as they might still be
+            // visible in reflections. The panoramic camera sees every
+            // direction, so only the distance matters.
+            let view_position = view.transform_point3a(position);
+            let (depth, pixel_radius) = match self.projection {
+                Projection::Perspective => (
+                    view_position.z,
+                    sphere_3d.radius * projection.x_axis.x * viewport.size.x * 0.5
+                        / view_position.z,
+                ),
+                Projection::Panoramic => {
+                    let distance = view_position.length();
+
+                    (
+                        distance,
+                        sphere_3d.radius * viewport.size.x / (distance * std::f32::consts::TAU),
+                    )
+                }
+            };
+
+            let keep = self.level_of_detail == LevelOfDetail::Disabled
+                || depth <= 0.0
+                || pixel_radius >= self.lod_threshold;
+
+            // particles bypass the level of detail, they are meant to be
+            // culled by the renderer
+            let has_particles = !sphere_3d.emissive && self.particles > 0;
+
+            if !keep && self.level_of_detail == LevelOfDetail::Merge {
+                let screen_position = match self.projection {
+                    Projection::Perspective => {
+                        let clip_position = projection.project_point3(view_position.into());
+
+                        vec2(clip_position.x, clip_position.y) * viewport.size * 0.5
+                    }
+                    Projection::Panoramic => {
+                        vec2(
+                            view_position.x.atan2(view_position.z) / std::f32::consts::TAU,
+                            (view_position.y / depth).asin() / std::f32::consts::PI,
+                        ) * viewport.size
+                    }
+                };
+                let cell = (
+                    (screen_position.x / LOD_CELL_SIZE).floor() as i32,
+                    (screen_position.y / LOD_CELL_SIZE).floor() as i32,
+                    depth.log2().floor() as i32,
+                );
+
+                impostors.entry(cell).or_default().add(
+                    position,
+                    sphere_3d.radius,
+                    &material,
+                    emission,
+                );
+            }
+
+            // merged spheres without particles don't need their own material
+            if keep || has_particles {
+                let motion = vec3a(
+                    sphere_3d.velocity.x,
+                    sphere_3d.velocity.y,
+                    sphere_3d.velocity.z,
+                ) * self.shutter;
+                let sphere = Sphere::new(position, sphere_3d.radius, scene.add_material(material))
+                    .with_motion(motion)
+                    .with_normal_map(self.sphere_normal_strength)
+                    .with_emission(emission);
+
+                if has_particles {
+                    for index in 0..self.particles {
+                        scene.add_shape(self.particle(
+                            &sphere,
+                            sphere_3d.band,
+                            index,
+                            sphere_3d.level,
+                            elapsed,
+                        ));
+                    }
+                }
+
+                if keep {
+                    scene.add_shape(sphere);
+                }
+            }
+        }
+
+        for impostor in impostors.values() {
+            let material = scene.add_material(impostor.material());
+            scene.add_shape(impostor.sphere(material));
+        }
+
+        let rect_transform = Mat4::from_translation(vec3(-10.0, 10.0, -10.0))
+            * Mat4::from_scale(Vec3::splat(10.0))
+            * Mat4::from_rotation_y(std::f32::consts::PI * 1.25)
+            * Mat4::from_rotation_x(std::f32::consts::PI * 0.25);
+
+        let rect_material =
+            scene.add_material(Material::new(Vec3A::ZERO).with_emission(Vec3A::splat(10.0)));
+
+        let floor_image = self.floor_surface_texture(&bands, band_count.unwrap_or(1));
+
+        let rect = Rect::new(rect_transform.inverse(), rect_material).with_ripple(
+            self.ripple_amplitude * bass_level,
+            self.ripple_frequency,
+            elapsed * self.ripple_speed,
+        );
+
+        // the normal map uses the texture coordinates of the floor texture
+        let textured = floor_image.is_some() || self.floor_normal_strength > 0.0;
+
+        scene.add_shape(match textured {
+            true => rect
+                .with_texture(self.floor_texture_scale)
+                .with_normal_map(self.floor_normal_strength),
+            false => rect,
+        });
+        scene.set_surface_texture(floor_image);
+
+        if self.floor_normal_strength > 0.0 || self.sphere_normal_strength > 0.0 {
+            scene.set_normal_map(Some(self.normal_map.clone()));
+        }
+
+        let mesh_transform = Mat4::from_translation(vec3(0.0, 0.0, self.mesh_distance))
+            * Mat4::from_scale(Vec3::splat(self.mesh_scale));
+
+        let mesh_material = scene.add_material(Material::new(Vec3A::splat(MESH_COLOR)));
+
+        for triangle in self.mesh.iter() {
+            scene.add_shape(
+                triangle
+                    .transformed(&mesh_transform)
+                    .with_material(mesh_material),
+            );
+        }
+
+        for index in 0..self.light_count {
+            scene.add_ligth(self.light(index, self.light_phase));
+        }
+
+        if self.sun_intensity > 0.0 {
+            scene.add_ligth(self.sun());
+        }
+
+        if self.bvh {
+            scene.build_bvh::<Sphere>().build_bvh::<Triangle>();
+        }
+
+        scene
+    }
+
+    fn project(&self, spheres: S, width: f32, height: f32) -> Vec<ProjectedSphere> {
+        let viewport = Viewport::new(width, height, self.aspect_ratio);
+        let (_, camera) = self.cameras(&viewport);
+
+        spheres
+            .into_iter()
+            .filter(|sphere| !sphere.emissive)
+            .filter_map(|sphere| {
+                let position = vec3a(sphere.position.x, sphere.position.y, sphere.position.z);
+
+                camera.project(position).map(|position| ProjectedSphere {
+                    position,
+                    band: sphere.band,
+                    level: sphere.level,
+                })
+            })
+            .collect()
+    }
+
+    fn set_camera_control(&mut self, camera_control: CameraControl) {
+        self.camera_control = camera_control;
+    }
+
+    // the phase is advanced instead of derived from the time, so it doesn't
+    // jump when the tempo changes
+    fn advance(&mut self, delta_time: Duration) {
+        let delta_time = delta_time.as_secs_f32();
+        let cycles_per_second = match self.bpm {
+            Some(bpm) => self.light_cycle_speed * bpm / 60.0,
+            None => self.light_cycle_speed,
+        };
+
+        self.time += delta_time;
+        self.light_phase = (self.light_phase + delta_time * cycles_per_second).fract();
+    }
+
+    fn set_bpm(&mut self, bpm: Option<f32>) {
+        RaytracerSceneConverter::set_bpm(self, bpm);
+    }
+}
+
+impl Module for RaytracerSceneConverter {
+    type Settings = RaytracerSceneConverterSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_level_of_detail(settings.level_of_detail)
+            .set_lod_threshold(settings.lod_threshold)
+            .set_bounces(settings.bounces)
+            .set_projection(settings.projection)
+            .set_t_min(settings.t_min)
+            .set_t_max(settings.t_max)
+            .set_materials(settings.materials)
+            .set_aspect_ratio(settings.aspect_ratio)
+            .set_light_count(settings.light_count)
+            .set_cycle_lights(settings.cycle_lights)
+            .set_light_cycle_speed(settings.light_cycle_speed)
+            .set_sun_intensity(settings.sun_intensity)
+            .set_sun_elevation(settings.sun_elevation)
+            .set_sun_azimuth(settings.sun_azimuth)
+            .set_palette(settings.palette)
+            .set_sphere_coloring(settings.sphere_coloring)
+            .set_peak_cooldown(settings.peak_cooldown)
+            .set_level_emission(settings.level_emission)
+            .set_level_emission_gamma(settings.level_emission_gamma)
+            .set_ripple_amplitude(settings.ripple_amplitude)
+            .set_ripple_frequency(settings.ripple_frequency)
+            .set_ripple_speed(settings.ripple_speed)
+            .set_particles(settings.particles)
+            .set_particle_size(settings.particle_size)
+            .set_shutter(settings.shutter)
+            .set_mesh_path(settings.mesh_path)
+            .set_mesh_scale(settings.mesh_scale)
+            .set_mesh_distance(settings.mesh_distance)
+            .set_environment_map_path(settings.environment_map_path)
+            .set_background_intensity(settings.background_intensity)
+            .set_background_rotation(settings.background_rotation)
+            .set_floor_texture(settings.floor_texture)
+            .set_floor_texture_path(settings.floor_texture_path)
+            .set_floor_texture_scale(settings.floor_texture_scale)
+            .set_normal_map_path(settings.normal_map_path)
+            .set_floor_normal_strength(settings.floor_normal_strength)
+            .set_sphere_normal_strength(settings.sphere_normal_strength)
+            .set_bvh(settings.bvh)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        RaytracerSceneConverterSettings {
+            level_of_detail: self.level_of_detail(),
+            lod_threshold: self.lod_threshold(),
+            bounces: self.bounces(),
+            projection: self.projection(),
+            t_min: self.t_min(),
+            t_max: self.t_max(),
+            materials: self.materials().to_vec(),
+            aspect_ratio: self.aspect_ratio(),
+            light_count: self.light_count(),
+            cycle_lights: self.cycle_lights(),
+            light_cycle_speed: self.light_cycle_speed(),
+            sun_intensity: self.sun_intensity(),
+            sun_elevation: self.sun_elevation(),
+            sun_azimuth: self.sun_azimuth(),
+            palette: self.palette(),
+            sphere_coloring: self.sphere_coloring(),
+            peak_cooldown: self.peak_cooldown(),
+            level_emission: self.level_emission(),
+            level_emission_gamma: self.level_emission_gamma(),
+            ripple_amplitude: self.ripple_amplitude(),
+            ripple_frequency: self.ripple_frequency(),
+            ripple_speed: self.ripple_speed(),
+            particles: self.particles(),
+            particle_size: self.particle_size(),
+            shutter: self.shutter(),
+            mesh_path: self.mesh_path().cloned(),
+            mesh_scale: self.mesh_scale(),
+            mesh_distance: self.mesh_distance(),
+            environment_map_path: self.environment_map_path().cloned(),
+            background_intensity: self.background_intensity(),
+            background_rotation: self.background_rotation(),
+            floor_texture: self.floor_texture(),
+            floor_texture_path: self.floor_texture_path().cloned(),
+            floor_texture_scale: self.floor_texture_scale(),
+            normal_map_path: self.normal_map_path().cloned(),
+            floor_normal_strength: self.floor_normal_strength(),
+            sphere_normal_strength: self.sphere_normal_strength(),
+            bvh: self.bvh(),
+        }
+    }
+}
+
+/// Stores the settings of the [`RaytracerSceneConverter`]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RaytracerSceneConverterSettings {
+    /// Specifies how spheres below the level of detail threshold are handled
+    pub level_of_detail: LevelOfDetail,
+    /// The projected radius in pixels below which spheres are reduced
+    pub lod_threshold: f32,
+    /// The number of ray bounces
+    pub bounces: u32,
+    /// Specifies how the camera maps the scene onto the screen
+    pub projection: Projection,
+    /// The distance of the camera near clip plane
+    pub t_min: f32,
+    /// The distance of the camera far clip plane
+    pub t_max: f32,
+    /// The material table the frequency bands are spread over
+    pub materials: Vec<SphereMaterial>,
+    /// The aspect ratio of the area the scene is rendered into
+    pub aspect_ratio: AspectRatio,
+    /// The number of point lights arranged around the scene
+    pub light_count: usize,
+    /// Whether the light colors march through the color ramp
+    pub cycle_lights: bool,
+    /// The speed of the light colors in gradient cycles per second, or per
+    /// beat if the tempo is known
+    pub light_cycle_speed: f32,
+    /// The intensity of the sun, which is disabled at zero
+    pub sun_intensity: f32,
+    /// The angle in degrees of the sun above the horizon
+    pub sun_elevation: f32,
+    /// The angle in degrees of the sun around the vertical axis, measured
+    /// from straight behind the camera towards the left
+    pub sun_azimuth: f32,
+    /// The palette of the sphere and light colors
+    pub palette: Palette,
+    /// Specifies how the spheres are colored
+    pub sphere_coloring: SphereColoring,
+    /// The time in seconds a sphere takes to cool down after a peak
+    pub peak_cooldown: f32,
+    /// The scale of the light spheres emit proportional to their band level
+    pub level_emission: f32,
+    /// The gamma applied to the band level before it is turned into emitted
+    /// light
+    pub level_emission_gamma: f32,
+    /// The height of the floor ripples at full bass level
+    pub ripple_amplitude: f32,
+    /// The angular frequency of the floor ripples along the radius of the
+    /// floor
+    pub ripple_frequency: f32,
+    /// The speed in radians per second with which the floor ripples travel
+    /// outwards
+    pub ripple_speed: f32,
+    /// The number of particles emitted around every sphere
+    pub particles: usize,
+    /// The radius of the particles relative to their sphere at full band
+    /// level
+    pub particle_size: f32,
+    /// The time in seconds the shutter is open for, over which moving spheres
+    /// are blurred
+    pub shutter: f32,
+    /// The path of the OBJ file the background mesh is loaded from
+    pub mesh_path: Option<PathBuf>,
+    /// The scale of the background mesh
+    pub mesh_scale: f32,
+    /// The distance of the background mesh behind the center of the scene
+    pub mesh_distance: f32,
+    /// The path of the Radiance HDR file the environment map is loaded from
+    pub environment_map_path: Option<PathBuf>,
+    /// The brightness of the background
+    pub background_intensity: f32,
+    /// The angle in degrees the environment map is rotated by around the
+    /// vertical axis
+    pub background_rotation: f32,
+    /// The texture mapped onto the floor
+    pub floor_texture: FloorTexture,
+    /// The path of the Radiance HDR file the image floor texture is loaded
+    /// from
+    pub floor_texture_path: Option<PathBuf>,
+    /// The number of times the texture repeats along each side of the floor
+    pub floor_texture_scale: f32,
+    /// The path of the Radiance HDR file the tangent space normal map of the
+    /// floor and the spheres is loaded from
+    pub normal_map_path: Option<PathBuf>,
+    /// The scale of the tilt of the normal map of the floor, which is
+    /// disabled at zero
+    pub floor_normal_strength: f32,
+    /// The scale of the tilt of the normal map of the spheres, which is
+    /// disabled at zero
+    pub sphere_normal_strength: f32,
+    /// Whether a bounding volume hierarchy is built over the spheres and the
+    /// triangles of the background mesh
+    pub bvh: bool,
+}
+
+impl Default for RaytracerSceneConverterSettings {
+    fn default() -> Self {
+        Self {
+            level_of_detail: LevelOfDetail::Disabled,
+            lod_threshold: LOD_THRESHOLD,
+            bounces: BOUNCES,
+            projection: Projection::Perspective,
+            t_min: T_MIN,
+            t_max: T_MAX,
+            materials: Vec::new(),
+            aspect_ratio: AspectRatio::Free,
+            light_count: LIGHT_COUNT,
+            cycle_lights: false,
+            light_cycle_speed: LIGHT_CYCLE_SPEED,
+            sun_intensity: SUN_INTENSITY,
+            sun_elevation: SUN_ELEVATION,
+            sun_azimuth: SUN_AZIMUTH,
+            palette: Palette::Classic,
+            sphere_coloring: SphereColoring::Material,
+            peak_cooldown: PEAK_COOLDOWN,
+            level_emission: LEVEL_EMISSION,
+            level_emission_gamma: LEVEL_EMISSION_GAMMA,
+            ripple_amplitude: RIPPLE_AMPLITUDE,
+            ripple_frequency: RIPPLE_FREQUENCY,
+            ripple_speed: RIPPLE_SPEED,
+            particles: PARTICLES,
+            particle_size: PARTICLE_SIZE,
+            shutter: SHUTTER,
+            mesh_path: None,
+            mesh_scale: MESH_SCALE,
+            mesh_distance: MESH_DISTANCE,
+            environment_map_path: None,
+            background_intensity: BACKGROUND_INTENSITY,
+            background_rotation: BACKGROUND_ROTATION,
+            floor_texture: FloorTexture::None,
+            floor_texture_path: None,
+            floor_texture_scale: FLOOR_TEXTURE_SCALE,
+            normal_map_path: None,
+            floor_normal_strength: FLOOR_NORMAL_STRENGTH,
+            sphere_normal_strength: SPHERE_NORMAL_STRENGTH,
+            bvh: true,
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::time::Duration;
+
+use sphere_audio_visualizer_core::glam::Vec3A;
+
+use crate::module::Module;
+
+use super::{AspectRatio, SceneConverter, Viewport};
+
+/// Defines the default speed in units per second the camera flies forward
+pub const TUNNEL_SPEED: f32 = 4.0;
+
+/// Defines the default radius of the undisplaced tunnel
+pub const TUNNEL_RADIUS: f32 = 2.0;
+
+/// Defines the default distance a ring is pushed inwards at full level
+pub const TUNNEL_DISPLACEMENT: f32 = 1.0;
+
+/// Defines the default distance between two rings
+pub const TUNNEL_RING_SPACING: f32 = 1.0;
+
+/// Defines the default rotation in radians of the bands per unit of depth
+pub const TUNNEL_TWIST: f32 = 0.05;
+
+/// Defines the default density of the fog hiding the far end of the tunnel
+pub const TUNNEL_FOG: f32 = 0.1;
+
+/// Defines the default color of the rings
+pub const TUNNEL_COLOR: Vec3A = Vec3A::new(0.3, 0.6, 1.0);
+
+/// Stores the scene definition for the tunnel renderer
+pub struct TunnelScene {
+    pub(crate) color: Vec3A,
+    pub(crate) viewport: Viewport,
+    pub(crate) depth: f32,
+    pub(crate) radius: f32,
+    pub(crate) displacement: f32,
+    pub(crate) ring_spacing: f32,
+    pub(crate) twist: f32,
+    pub(crate) fog: f32,
+    pub(crate) levels: Vec<f32>,
+}
+
+/// Converts the levels of the spectrum to the tunnel renderer scene format.
+/// The levels displace the rings of the tunnel around its circumference,
+/// while the camera flies forward through it.
+pub struct TunnelSceneConverter {
+    aspect_ratio: AspectRatio,
+    speed: f32,
+    radius: f32,
+    displacement: f32,
+    ring_spacing: f32,
+    twist: f32,
+    fog: f32,
+    color: Vec3A,
+    depth: f32,
+}
+
+impl TunnelSceneConverter {
+    /// Returns the aspect ratio of the area the scene is rendered into
+    pub fn aspect_ratio(&self) -> AspectRatio {
+        self.aspect_ratio
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: AspectRatio) -> &mut Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /// Sets the aspect ratio of the area the scene is rendered into
+    pub fn with_aspect_ratio(mut self, aspect_ratio: AspectRatio) -> Self {
+        self.set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    /// Returns the speed in units per second the camera flies forward
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the speed in units per second the camera flies forward
+    pub fn set_speed(&mut self, speed: f32) -> &mut Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Sets the speed in units per second the camera flies forward
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.set_speed(speed);
+        self
+    }
+
+    /// Returns the radius of the undisplaced tunnel
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Sets the radius of the undisplaced tunnel
+    pub fn set_radius(&mut self, radius: f32) -> &mut Self {
+        self.radius = radius.max(0.01);
+        self
+    }
+
+    /// Sets the radius of the undisplaced tunnel
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.set_radius(radius);
+        self
+    }
+
+    /// Returns the distance a ring is pushed inwards at full level
+    pub fn displacement(&self) -> f32 {
+        self.displacement
+    }
+
+    /// Sets the distance a ring is pushed inwards at full level
+    pub fn set_displacement(&mut self, displacement: f32) -> &mut Self {
+        self.displacement = displacement;
+        self
+    }
+
+    /// Sets the distance a ring is pushed inwards at full level
+    pub fn with_displacement(mut self, displacement: f32) -> Self {
+        self.set_displacement(displacement);
+        self
+    }
+
+    /// Returns the distance between two rings
+    pub fn ring_spacing(&self) -> f32 {
+        self.ring_spacing
+    }
+
+    /// Sets the distance between two rings
+    pub fn set_ring_spacing(&mut self, ring_spacing: f32) -> &mut Self {
+        self.ring_spacing = ring_spacing.max(0.01);
+        self
+    }
+
+    /// Sets the distance between two rings
+    pub fn with_ring_spacing(mut self, ring_spacing: f32) -> Self {
+        self.set_ring_spacing(ring_spacing);
+        self
+    }
+
+    /// Returns the rotation in radians of the bands per unit of depth
+    pub fn twist(&self) -> f32 {
+        self.twist
+    }
+
+    /// Sets the rotation in radians of the bands per unit of depth
+    pub fn set_twist(&mut self, twist: f32) -> &mut Self {
+        self.twist = twist;
+        self
+    }
+
+    /// Sets the rotation in radians of the bands per unit of depth
+    pub fn with_twist(mut self, twist: f32) -> Self {
+        self.set_twist(twist);
+        self
+    }
+
+    /// Returns the density of the fog hiding the far end of the tunnel
+    pub fn fog(&self) -> f32 {
+        self.fog
+    }
+
+    /// Sets the density of the fog hiding the far end of the tunnel
+    pub fn set_fog(&mut self, fog: f32) -> &mut Self {
+        self.fog = fog.max(0.0);
+        self
+    }
+
+    /// Sets the density of the fog hiding the far end of the tunnel
+    pub fn with_fog(mut self, fog: f32) -> Self {
+        self.set_fog(fog);
+        self
+    }
+
+    /// Returns the color of the rings
+    pub fn color(&self) -> Vec3A {
+        self.color
+    }
+
+    /// Sets the color of the rings
+    pub fn set_color(&mut self, color: Vec3A) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the color of the rings
+    pub fn with_color(mut self, color: Vec3A) -> Self {
+        self.set_color(color);
+        self
+    }
+}
+
+impl Default for TunnelSceneConverter {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            speed: TUNNEL_SPEED,
+            radius: TUNNEL_RADIUS,
+            displacement: TUNNEL_DISPLACEMENT,
+            ring_spacing: TUNNEL_RING_SPACING,
+            twist: TUNNEL_TWIST,
+            fog: TUNNEL_FOG,
+            color: TUNNEL_COLOR,
+            depth: 0.0,
+        }
+    }
+}
+
+impl<S: IntoIterator<Item = f32>> SceneConverter<S> for TunnelSceneConverter {
+    type Scene = TunnelScene;
+
+    fn convert(&self, levels: S, width: f32, height: f32) -> Self::Scene {
+        TunnelScene {
+            color: self.color,
+            viewport: Viewport::new(width, height, self.aspect_ratio),
+            depth: self.depth,
+            radius: self.radius,
+            displacement: self.displacement,
+            ring_spacing: self.ring_spacing,
+            twist: self.twist,
+            fog: self.fog,
+            levels: levels
+                .into_iter()
+                .map(|level| level.clamp(0.0, 1.0))
+                .collect(),
+        }
+    }
+
+    // the camera flies with the simulation, so it stops while the audio is
+    // paused
+    fn advance(&mut self, delta_time: Duration) {
+        self.depth += delta_time.as_secs_f32() * self.speed;
+    }
+}
+
+impl Module for TunnelSceneConverter {
+    type Settings = TunnelSceneConverterSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_aspect_ratio(settings.aspect_ratio)
+            .set_speed(settings.speed)
+            .set_radius(settings.radius)
+            .set_displacement(settings.displacement)
+            .set_ring_spacing(settings.ring_spacing)
+            .set_twist(settings.twist)
+            .set_fog(settings.fog)
+            .set_color(settings.color)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        TunnelSceneConverterSettings {
+            aspect_ratio: self.aspect_ratio(),
+            speed: self.speed(),
+            radius: self.radius(),
+            displacement: self.displacement(),
+            ring_spacing: self.ring_spacing(),
+            twist: self.twist(),
+            fog: self.fog(),
+            color: self.color(),
+        }
+    }
+}
+
+/// Stores the settings of the [`TunnelSceneConverter`]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TunnelSceneConverterSettings {
+    /// The aspect ratio of the area the scene is rendered into
+    pub aspect_ratio: AspectRatio,
+    /// The speed in units per second the camera flies forward
+    pub speed: f32,
+    /// The radius of the undisplaced tunnel
+    pub radius: f32,
+    /// The distance a ring is pushed inwards at full level
+    pub displacement: f32,
+    /// The distance between two rings
+    pub ring_spacing: f32,
+    /// The rotation in radians of the bands per unit of depth
+    pub twist: f32,
+    /// The density of the fog hiding the far end of the tunnel
+    pub fog: f32,
+    /// The color of the rings
+    pub color: Vec3A,
+}
+
+impl Default for TunnelSceneConverterSettings {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: AspectRatio::Free,
+            speed: TUNNEL_SPEED,
+            radius: TUNNEL_RADIUS,
+            displacement: TUNNEL_DISPLACEMENT,
+            ring_spacing: TUNNEL_RING_SPACING,
+            twist: TUNNEL_TWIST,
+            fog: TUNNEL_FOG,
+            color: TUNNEL_COLOR,
+        }
+    }
+}
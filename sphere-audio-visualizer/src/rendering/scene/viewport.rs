@@ -0,0 +1,60 @@
+use sphere_audio_visualizer_core::glam::{vec2, Vec2};
+
+/// Specifies the aspect ratio of the area the scene is rendered into
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum AspectRatio {
+    /// The scene fills the whole target
+    Free,
+    /// The scene is letterboxed to the given width to height ratio, e.g. to
+    /// preview the export resolution
+    Locked(u32, u32),
+}
+
+/// Describes the area of the target in pixels the scene is rendered into
+#[derive(Clone, Copy)]
+pub struct Viewport {
+    /// The offset of the area in pixels
+    pub offset: Vec2,
+    /// The size of the area in pixels
+    pub size: Vec2,
+}
+
+impl Viewport {
+    /// Creates a new instance fitting the [`AspectRatio`] centered into a
+    /// target of the given size
+    pub fn new(width: f32, height: f32, aspect_ratio: AspectRatio) -> Self {
+        let size = match aspect_ratio {
+            AspectRatio::Free => vec2(width, height),
+            AspectRatio::Locked(aspect_width, aspect_height) => {
+                let aspect = aspect_width.max(1) as f32 / aspect_height.max(1) as f32;
+
+                if width > height * aspect {
+                    vec2(height * aspect, height)
+                } else {
+                    vec2(width, width / aspect)
+                }
+            }
+        }
+        .floor()
+        .max(Vec2::ONE);
+
+        Self {
+            offset: ((vec2(width, height) - size) * 0.5).max(Vec2::ZERO),
+            size,
+        }
+    }
+
+    /// Returns the size of the target the area is centered in
+    pub fn target_size(&self) -> Vec2 {
+        (self.size + self.offset * 2.0).round()
+    }
+
+    /// Returns the factors the shorter axis has to be scaled by to keep the
+    /// scene from being stretched
+    pub fn aspect_scale(&self) -> Vec2 {
+        let aspect = self.size.x / self.size.y;
+
+        vec2(aspect.max(1.0), aspect.recip().max(1.0))
+    }
+}
@@ -4,12 +4,12 @@ use std::path::Path;
 
 use thiserror::Error;
 use wgpu::{
-    Backends, Device, DeviceDescriptor, Instance, PowerPreference, Queue, RequestAdapterOptions,
-    RequestDeviceError, TextureFormat, TextureView,
+    Adapter, Backends, Device, DeviceDescriptor, Features, Instance, Limits, PowerPreference,
+    Queue, RequestAdapterOptions, RequestDeviceError, Surface, TextureFormat, TextureView,
 };
 use winit::window::Window;
 
-use self::utils::CommandQueue;
+use self::utils::{CommandQueue, MemoryTracker};
 pub use self::{pipeline::*, target::*};
 
 mod pipeline;
@@ -30,10 +30,33 @@ pub enum WGPURendererInitError {
     DeviceRequestFailed(#[from] RequestDeviceError),
 }
 
+/// Selects the kind of adapter a [`WGPURenderer`] is created with. Stored in
+/// the settings bin of a visualizer, it selects the adapter of the renderers
+/// the visualizer creates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdapterSelection {
+    /// Prefers a hardware adapter and falls back to a software adapter if
+    /// none is found
+    Auto,
+    /// Only uses a hardware adapter
+    Hardware,
+    /// Forces a software adapter (e.g. llvmpipe) over the GL backend, which
+    /// allows rendering in containers or on servers without a GPU
+    Software,
+}
+
+impl Default for AdapterSelection {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
 /// Contains all necessary information for rendering with WGPU
 pub struct WGPURenderer {
     device: Device,
     queue: Queue,
+    memory_tracker: MemoryTracker,
+    shader_validation: ShaderValidation,
 }
 
 impl WGPURenderer {
@@ -43,37 +66,97 @@ impl WGPURenderer {
     pub async fn new(
         window: Option<&Window>,
         trace_path: Option<&Path>,
+        adapter_selection: AdapterSelection,
     ) -> Result<(Self, Option<SurfaceTarget>), WGPURendererInitError> {
-        let instance = Instance::new(Backends::all());
+        let backends = match adapter_selection {
+            AdapterSelection::Software => Backends::GL,
+            _ => Backends::all(),
+        };
+
+        let instance = Instance::new(backends);
 
         let surface = window.map(|window| unsafe { instance.create_surface(window) });
 
-        let adapter = {
-            let request_adapter_options = RequestAdapterOptions {
-                power_preference: PowerPreference::HighPerformance,
-                compatible_surface: surface.as_ref(),
-                ..Default::default()
-            };
-
-            instance
-                .request_adapter(&request_adapter_options)
-                .await
-                .ok_or_else(|| WGPURendererInitError::NoAdapterFound)?
+        let hardware_adapter = match adapter_selection {
+            AdapterSelection::Software => None,
+            _ => request_adapter(&instance, surface.as_ref(), false).await,
+        };
+
+        let (adapter, software) = match (hardware_adapter, adapter_selection) {
+            (Some(adapter), _) => (adapter, false),
+            (None, AdapterSelection::Hardware) => {
+                return Err(WGPURendererInitError::NoAdapterFound)
+            }
+            (None, _) => (
+                request_adapter(&instance, surface.as_ref(), true)
+                    .await
+                    .ok_or(WGPURendererInitError::NoAdapterFound)?,
+                true,
+            ),
         };
 
-        let device_descriptor = DeviceDescriptor {
-            label: Some("sphere-visualizer-device"),
-            features: adapter.features(),
-            limits: adapter.limits(),
+        // software adapters only get the features and limits every backend
+        // supports, the WGSL pipelines are used in this case
+        let device_descriptor = if software {
+            eprintln!(
+                "using the software adapter \"{}\", rendering will be considerably slower",
+                adapter.get_info().name
+            );
+
+            // the raytracer binds every shape type as a separate storage
+            // buffer, which exceeds the downlevel default
+            DeviceDescriptor {
+                label: Some("sphere-visualizer-device"),
+                features: Features::empty(),
+                limits: Limits {
+                    max_storage_buffers_per_shader_stage: adapter
+                        .limits()
+                        .max_storage_buffers_per_shader_stage,
+                    ..Limits::downlevel_defaults().using_resolution(adapter.limits())
+                },
+            }
+        } else {
+            DeviceDescriptor {
+                label: Some("sphere-visualizer-device"),
+                features: adapter.features(),
+                limits: adapter.limits(),
+            }
         };
 
+        // the rust-gpu shader is validated while the device is requested
+        let validation = device_descriptor
+            .features
+            .contains(Features::SPIRV_SHADER_PASSTHROUGH)
+            .then(spawn_shader_validation);
+
         let (device, queue) = adapter
             .request_device(&device_descriptor, trace_path)
             .await?;
 
         let target = surface.map(|surface| SurfaceTarget::new(surface, &adapter));
 
-        Ok((Self { device, queue }, target))
+        let target_format = target
+            .as_ref()
+            .map(RenderTarget::target_format)
+            .unwrap_or_else(|| OutputFormat::RGBA8.into());
+
+        let shader_validation = validate_rust_pipelines(&device, target_format, validation).await;
+
+        if let ShaderValidation::Failed(reason) = &shader_validation {
+            eprintln!("using the WGSL shaders: {}", reason);
+        }
+
+        let memory_tracker = MemoryTracker::new(&device.limits());
+
+        Ok((
+            Self {
+                device,
+                queue,
+                memory_tracker,
+                shader_validation,
+            },
+            target,
+        ))
     }
 
     /// Creates a instance for onscreen rendering.
@@ -82,7 +165,18 @@ impl WGPURenderer {
         window: &Window,
         trace_path: Option<&Path>,
     ) -> Result<(Self, SurfaceTarget), WGPURendererInitError> {
-        let (this, surface) = Self::new(Some(window), trace_path).await?;
+        Self::onscreen_with_adapter(window, trace_path, AdapterSelection::default()).await
+    }
+
+    /// Creates a instance for onscreen rendering with the given
+    /// [`AdapterSelection`].
+    /// Optionally a trace path can be specified for debugging purposes.
+    pub async fn onscreen_with_adapter(
+        window: &Window,
+        trace_path: Option<&Path>,
+        adapter_selection: AdapterSelection,
+    ) -> Result<(Self, SurfaceTarget), WGPURendererInitError> {
+        let (this, surface) = Self::new(Some(window), trace_path, adapter_selection).await?;
 
         Ok((this, surface.unwrap()))
     }
@@ -90,7 +184,18 @@ impl WGPURenderer {
     /// Creates a instance for offscreen rendering
     /// Optionally a trace path can be specified for debugging purposes.
     pub async fn offscreen(trace_path: Option<&Path>) -> Result<Self, WGPURendererInitError> {
-        Ok(Self::new(None, trace_path).await?.0)
+        Self::offscreen_with_adapter(trace_path, AdapterSelection::default()).await
+    }
+
+    /// Creates a instance for offscreen rendering with the given
+    /// [`AdapterSelection`], e.g. to force a software adapter for headless
+    /// exports.
+    /// Optionally a trace path can be specified for debugging purposes.
+    pub async fn offscreen_with_adapter(
+        trace_path: Option<&Path>,
+        adapter_selection: AdapterSelection,
+    ) -> Result<Self, WGPURendererInitError> {
+        Ok(Self::new(None, trace_path, adapter_selection).await?.0)
     }
 
     /// Returns the WGPU [`Device`].
@@ -102,10 +207,50 @@ impl WGPURenderer {
     pub fn queue(&self) -> &Queue {
         &self.queue
     }
+
+    /// Returns if the precompiled rust-gpu shader was accepted by the active
+    /// adapter. This is checked once when the renderer is created.
+    pub fn rust_shaders_supported(&self) -> bool {
+        self.shader_validation.passed()
+    }
+
+    /// Returns the outcome of validating the precompiled rust-gpu shader for
+    /// the active adapter
+    pub fn shader_validation(&self) -> &ShaderValidation {
+        &self.shader_validation
+    }
+
+    /// Returns the [`MemoryTracker`] keeping track of the GPU memory
+    /// allocated while rendering.
+    pub fn memory_tracker(&self) -> &MemoryTracker {
+        &self.memory_tracker
+    }
+}
+
+async fn request_adapter(
+    instance: &Instance,
+    compatible_surface: Option<&Surface>,
+    force_fallback_adapter: bool,
+) -> Option<Adapter> {
+    let request_adapter_options = RequestAdapterOptions {
+        power_preference: PowerPreference::HighPerformance,
+        force_fallback_adapter,
+        compatible_surface,
+    };
+
+    instance.request_adapter(&request_adapter_options).await
 }
 
 /// A pipeline used for rendering.
 pub trait Pipeline<S> {
+    /// Is invoked once the [`WGPURenderer`] used for rendering is known, so
+    /// the pipeline can adapt to the capabilities of the active adapter.
+    fn prepare(&mut self, _renderer: &WGPURenderer) {}
+
+    /// Is invoked before every frame with the current overall loudness of
+    /// the audio signal.
+    fn set_loudness(&mut self, _loudness: f32) {}
+
     /// renders a new frame.
     fn render(
         &mut self,
@@ -119,6 +264,7 @@ pub trait Pipeline<S> {
 
 /// Specifies the different supported shading languages
 #[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
 pub enum ShadingLanguage {
     /// Rust using rust-gpu <https://github.com/EmbarkStudios/rust-gpu>
     Rust,
@@ -0,0 +1,309 @@
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, BufferBindingType, BufferUsages, Color,
+    ColorTargetState, ColorWrites, Device, Extent3d, FragmentState, LoadOp, Operations,
+    PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    ShaderStages, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType,
+    TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension, VertexState,
+};
+
+use crate::rendering::{
+    wgpu::utils::{CommandQueue, TypedBufferDeviceExt, TypedBufferInitDescriptor},
+    Viewport,
+};
+
+use super::set_viewport;
+
+/// Defines the format of the accumulated frames. The full precision keeps
+/// small contributions of late frames from being rounded away.
+const HISTORY_FORMAT: TextureFormat = TextureFormat::Rgba32Float;
+
+/// Defines the number of accumulated frames after which new frames keep a
+/// constant weight
+const MAX_ACCUMULATED_FRAMES: u32 = 256;
+
+#[repr(C, align(16))]
+struct AccumulationArgs {
+    weight: f32,
+}
+
+struct AccumulationPipeline(RenderPipeline, TextureFormat);
+
+impl AccumulationPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("accumulation.wgsl"));
+
+        // the history format is not filterable, which a derived layout assumes
+        let texture_entry = |binding| BindGroupLayoutEntry {
+            binding,
+            count: None,
+            ty: BindingType::Texture {
+                multisampled: false,
+                sample_type: TextureSampleType::Float { filterable: false },
+                view_dimension: TextureViewDimension::D2,
+            },
+            visibility: ShaderStages::FRAGMENT,
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                texture_entry(1),
+                texture_entry(2),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-accumulation-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[
+                    ColorTargetState {
+                        format: target_format,
+                        blend: None,
+                        write_mask: ColorWrites::COLOR,
+                    },
+                    ColorTargetState {
+                        format: HISTORY_FORMAT,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    },
+                ],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: Some(&pipeline_layout),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+fn create_texture(device: &Device, width: u32, height: u32, format: TextureFormat) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some("sphere-visualizer-accumulation"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+    })
+}
+
+struct AccumulationTextures {
+    _textures: [Texture; 3],
+    frame: TextureView,
+    history: [TextureView; 2],
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+}
+
+impl AccumulationTextures {
+    fn new(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+        let textures = [
+            create_texture(device, width, height, format),
+            create_texture(device, width, height, HISTORY_FORMAT),
+            create_texture(device, width, height, HISTORY_FORMAT),
+        ];
+
+        let [frame, history_a, history_b] = [&textures[0], &textures[1], &textures[2]]
+            .map(|texture| texture.create_view(&TextureViewDescriptor::default()));
+
+        Self {
+            _textures: textures,
+            frame,
+            history: [history_a, history_b],
+            width,
+            height,
+            format,
+        }
+    }
+}
+
+/// Averages the frames of a static scene to progressively refine it. Every
+/// frame is rendered into an intermediate texture and blended with the
+/// previously accumulated frames. Two history textures are swapped every
+/// frame, as the blend pass can't read and write the same texture.
+#[derive(Default)]
+pub(super) struct Accumulation {
+    pipeline: Option<AccumulationPipeline>,
+    textures: Option<AccumulationTextures>,
+    fingerprint: Option<u64>,
+    frames: u32,
+    current: usize,
+}
+
+impl Accumulation {
+    /// Prepares the textures for the next frame. The accumulated frames are
+    /// discarded if the fingerprint of the scene or the size of the target
+    /// changed.
+    pub(super) fn begin(
+        &mut self,
+        device: &Device,
+        command_queue: &CommandQueue,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+        fingerprint: u64,
+    ) {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let memory_tracker = command_queue.memory_tracker();
+        memory_tracker.track_texture(width, height, format);
+        memory_tracker.track_texture(width, height, HISTORY_FORMAT);
+        memory_tracker.track_texture(width, height, HISTORY_FORMAT);
+
+        let recreate = self.textures.as_ref().map_or(true, |textures| {
+            textures.width != width || textures.height != height || textures.format != format
+        });
+
+        if recreate {
+            self.textures = Some(AccumulationTextures::new(device, width, height, format));
+        }
+
+        if recreate || self.fingerprint != Some(fingerprint) {
+            self.frames = 0;
+        }
+
+        self.fingerprint = Some(fingerprint);
+    }
+
+    /// Returns the number of frames accumulated so far. The next frame
+    /// replaces the history if this is zero.
+    pub(super) fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Returns the texture the next frame has to be rendered into
+    pub(super) fn frame_texture(&self) -> &TextureView {
+        &self.textures.as_ref().unwrap().frame
+    }
+
+    /// Blends the rendered frame into the history and writes the result into
+    /// the target texture
+    pub(super) fn finish(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        target_format: TextureFormat,
+        target_texture: &TextureView,
+        viewport: &Viewport,
+    ) {
+        let textures = match &self.textures {
+            Some(textures) => textures,
+            None => return,
+        };
+
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| AccumulationPipeline::new(device, target_format));
+
+        if pipeline.1 != target_format {
+            *pipeline = AccumulationPipeline::new(device, target_format);
+        }
+
+        let args = AccumulationArgs {
+            weight: 1.0 / (self.frames + 1) as f32,
+        };
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &args,
+        });
+        command_queue
+            .memory_tracker()
+            .track_buffer(std::mem::size_of_val(&args) as u64);
+
+        let history = &textures.history[self.current];
+        let next_history = &textures.history[1 - self.current];
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[
+                args_buffer.bind_group_entry(0).unwrap(),
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&textures.frame),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureView(history),
+                },
+            ],
+            layout: &pipeline.0.get_bind_group_layout(0),
+        });
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[
+                    RenderPassColorAttachment {
+                        view: target_texture,
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(Color::BLACK),
+                            store: true,
+                        },
+                    },
+                    RenderPassColorAttachment {
+                        view: next_history,
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(Color::BLACK),
+                            store: true,
+                        },
+                    },
+                ],
+                depth_stencil_attachment: None,
+            });
+
+            set_viewport(&mut render_pass, viewport);
+            render_pass.set_pipeline(&pipeline.0);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+
+            render_pass.draw(0..4, 0..1);
+        }
+
+        self.current = 1 - self.current;
+        self.frames = (self.frames + 1).min(MAX_ACCUMULATED_FRAMES);
+    }
+}
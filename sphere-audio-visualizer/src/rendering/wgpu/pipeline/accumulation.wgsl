@@ -0,0 +1,36 @@
+struct Args {
+    weight: f32;
+};
+
+struct Output {
+    [[location(0)]] color: vec4<f32>;
+    [[location(1)]] history: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+[[group(0), binding(1)]]
+var frame: texture_2d<f32>;
+
+[[group(0), binding(2)]]
+var history: texture_2d<f32>;
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> Output {
+    let pixel = vec2<i32>(position.xy);
+
+    // the weight of the new frame shrinks with every accumulated frame, so
+    // all frames contribute equally to the average
+    let color = mix(textureLoad(history, pixel, 0), textureLoad(frame, pixel, 0), args.weight);
+
+    return Output(color, color);
+}
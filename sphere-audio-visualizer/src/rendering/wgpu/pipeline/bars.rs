@@ -0,0 +1,264 @@
+use sphere_audio_visualizer_core::glam::Vec3;
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BufferUsages, Color, ColorTargetState, ColorWrites, Device,
+    FragmentState, LoadOp, Operations, PolygonMode, PrimitiveState, PrimitiveTopology,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    TextureFormat, TextureView, VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::{
+        scene::BarsScene,
+        wgpu::{
+            utils::{
+                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+            },
+            Pipeline,
+        },
+    },
+};
+
+use super::set_viewport;
+
+/// Defines the default height of the peak markers as a fraction of the
+/// viewport height
+pub const BARS_PEAK_HEIGHT: f32 = 0.01;
+
+/// Defines the default strength with which the loudness raises the
+/// brightness of the bars
+pub const BARS_PULSE: f32 = 0.5;
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct BarsArgs {
+    low_color: Vec3,
+    gap: f32,
+    high_color: Vec3,
+    peak_height: f32,
+    loudness: f32,
+}
+
+struct BarsPipeline(RenderPipeline, TextureFormat);
+
+impl BarsPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("bars.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-bars-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+/// The pipeline module for rendering the spectrum as classic bars with peak
+/// markers. Every bar is an instanced quad, which makes this the cheapest
+/// pipeline for weak GPUs.
+pub struct Bars {
+    peak_height: f32,
+    pulse: f32,
+    loudness: f32,
+    pipeline: Option<BarsPipeline>,
+}
+
+impl Bars {
+    /// Returns the height of the peak markers as a fraction of the viewport
+    /// height
+    pub fn peak_height(&self) -> f32 {
+        self.peak_height
+    }
+
+    /// Sets the height of the peak markers as a fraction of the viewport
+    /// height
+    pub fn set_peak_height(&mut self, peak_height: f32) -> &mut Self {
+        self.peak_height = peak_height.max(0.0);
+        self
+    }
+
+    /// Sets the height of the peak markers as a fraction of the viewport
+    /// height
+    pub fn with_peak_height(mut self, peak_height: f32) -> Self {
+        self.set_peak_height(peak_height);
+        self
+    }
+
+    /// Returns the strength with which the loudness raises the brightness
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse;
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+}
+
+impl Default for Bars {
+    fn default() -> Self {
+        Self {
+            peak_height: BARS_PEAK_HEIGHT,
+            pulse: BARS_PULSE,
+            loudness: 0.0,
+            pipeline: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`Bars`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct BarsSettings {
+    /// The height of the peak markers as a fraction of the viewport height
+    pub peak_height: f32,
+    /// The strength with which the loudness raises the brightness
+    pub pulse: f32,
+}
+
+impl Default for BarsSettings {
+    fn default() -> Self {
+        Self {
+            peak_height: BARS_PEAK_HEIGHT,
+            pulse: BARS_PULSE,
+        }
+    }
+}
+
+impl Module for Bars {
+    type Settings = BarsSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_peak_height(settings.peak_height)
+            .set_pulse(settings.pulse)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        BarsSettings {
+            peak_height: self.peak_height(),
+            pulse: self.pulse(),
+        }
+    }
+}
+
+impl Pipeline<BarsScene> for Bars {
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
+    fn render(
+        &mut self,
+        scene: BarsScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| BarsPipeline::new(device, output_format));
+
+        if pipeline.1 != output_format {
+            *pipeline = BarsPipeline::new(device, output_format);
+        }
+
+        let memory_tracker = command_queue.memory_tracker();
+
+        let bars = memory_tracker.fit("Bars", scene.bars.as_slice());
+        let bar_count = bars.len() as u32;
+
+        let bind_group = (!bars.is_empty()).then(|| {
+            let args = BarsArgs {
+                low_color: scene.low_color.into(),
+                gap: scene.gap,
+                high_color: scene.high_color.into(),
+                peak_height: self.peak_height,
+                loudness: self.loudness * self.pulse,
+            };
+
+            let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: &args,
+            });
+            memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+            let bars_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: bars,
+            });
+
+            device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                entries: &[
+                    args_buffer.bind_group_entry(0).unwrap(),
+                    bars_buffer.bind_group_entry(1).unwrap(),
+                ],
+                layout: &pipeline.0.get_bind_group_layout(0),
+            })
+        });
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[RenderPassColorAttachment {
+                    view: target_texture,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            set_viewport(&mut render_pass, &scene.viewport);
+
+            if let Some(bind_group) = &bind_group {
+                render_pass.set_pipeline(&pipeline.0);
+                render_pass.set_bind_group(0, bind_group, &[]);
+
+                // the first half of the instances are the bars and the
+                // second half the peak markers
+                render_pass.draw(0..4, 0..bar_count * 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+struct Args {
+    low_color: vec3<f32>;
+    gap: f32;
+    high_color: vec3<f32>;
+    peak_height: f32;
+    loudness: f32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+struct Bar {
+    level: f32;
+    peak: f32;
+};
+
+struct Bars {
+    bars: array<Bar>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> bars: Bars;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] height: f32;
+    [[location(1)]] peak: f32;
+};
+
+// Every bar is drawn as two instances, the first half of the instances are
+// the bars and the second half the peak markers.
+[[stage(vertex)]]
+fn vertex(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    [[builtin(instance_index)]] instance_index: u32,
+) -> VertexOutput {
+    let count = arrayLength(&bars.bars);
+    let index = instance_index % count;
+    let is_peak = instance_index >= count;
+    let bar = bars.bars[index];
+
+    let width = 2.0 / f32(count);
+    let inset = width * args.gap * 0.5;
+    let left = -1.0 + f32(index) * width + inset;
+    let right = left + width - inset * 2.0;
+
+    var bottom = 0.0;
+    var top = bar.level;
+    if (is_peak) {
+        bottom = bar.peak;
+        top = bar.peak + args.peak_height;
+    }
+
+    let x = select(left, right, (vertex_index & 1u) != 0u);
+    let height = select(bottom, top, (vertex_index & 2u) != 0u);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(x, height * 2.0 - 1.0, 0.0, 1.0);
+    out.height = height;
+    out.peak = select(0.0, 1.0, is_peak);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fragment(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let color = mix(args.low_color, args.high_color, clamp(in.height, 0.0, 1.0));
+    let color = mix(color, vec3<f32>(1.0, 1.0, 1.0), in.peak);
+
+    return vec4<f32>(color * (1.0 + args.loudness), 1.0);
+}
@@ -0,0 +1,264 @@
+use sphere_audio_visualizer_core::glam::Mat3A;
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BindGroupEntry, BindingResource, BufferUsages, Color,
+    ColorTargetState, ColorWrites, Device, Extent3d, FragmentState, LoadOp, Operations,
+    PolygonMode, PrimitiveState, PrimitiveTopology, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Texture, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
+    VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::wgpu::{
+        utils::{CommandQueue, TypedBufferDeviceExt, TypedBufferInitDescriptor},
+        HDR_FORMAT,
+    },
+    utils::ColorVision,
+};
+
+#[repr(C, align(16))]
+struct ColorVisionArgs {
+    matrix: Mat3A,
+    encoded: u32,
+}
+
+struct ColorVisionPipeline(RenderPipeline, TextureFormat);
+
+impl ColorVisionPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("color_vision.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-color-vision-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+struct SceneTexture {
+    _texture: Texture,
+    view: TextureView,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+}
+
+impl SceneTexture {
+    fn new(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("sphere-visualizer-color-vision-scene"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+        });
+
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        Self {
+            _texture: texture,
+            view,
+            width,
+            height,
+            format,
+        }
+    }
+}
+
+/// Previews how the visualizer is seen with a color vision deficiency. The
+/// scene is rendered into an intermediate texture, which is filtered into
+/// the target by a post pass.
+pub struct ColorVisionFilter {
+    color_vision: ColorVision,
+    pipeline: Option<ColorVisionPipeline>,
+    scene_texture: Option<SceneTexture>,
+}
+
+impl ColorVisionFilter {
+    /// Returns the simulated color vision
+    pub fn color_vision(&self) -> ColorVision {
+        self.color_vision
+    }
+
+    /// Sets the simulated color vision. [`ColorVision::Normal`] disables the
+    /// filter.
+    pub fn set_color_vision(&mut self, color_vision: ColorVision) -> &mut Self {
+        self.color_vision = color_vision;
+
+        // the intermediate texture is only kept while it is used
+        if !self.is_enabled() {
+            self.scene_texture = None;
+        }
+
+        self
+    }
+
+    /// Sets the simulated color vision
+    pub fn with_color_vision(mut self, color_vision: ColorVision) -> Self {
+        self.set_color_vision(color_vision);
+        self
+    }
+
+    /// Returns if a color vision deficiency is simulated
+    pub fn is_enabled(&self) -> bool {
+        self.color_vision != ColorVision::Normal
+    }
+
+    /// Returns the texture the scene has to be rendered into before it is
+    /// filtered by [`ColorVisionFilter::render`]
+    pub fn scene_texture(
+        &mut self,
+        device: &Device,
+        command_queue: &CommandQueue,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> &TextureView {
+        command_queue
+            .memory_tracker()
+            .track_texture(width, height, format);
+
+        let scene_texture = match self.scene_texture.take() {
+            Some(scene_texture)
+                if scene_texture.width == width
+                    && scene_texture.height == height
+                    && scene_texture.format == format =>
+            {
+                scene_texture
+            }
+            _ => SceneTexture::new(device, width, height, format),
+        };
+
+        &self.scene_texture.insert(scene_texture).view
+    }
+
+    /// Filters the scene texture into the target texture
+    pub fn render(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        target_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let scene_texture = match &self.scene_texture {
+            Some(scene_texture) => scene_texture,
+            None => return,
+        };
+
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| ColorVisionPipeline::new(device, target_format));
+
+        if pipeline.1 != target_format {
+            *pipeline = ColorVisionPipeline::new(device, target_format);
+        }
+
+        let args = ColorVisionArgs {
+            matrix: self.color_vision.matrix().into(),
+            encoded: (!target_format.describe().srgb && target_format != HDR_FORMAT) as u32,
+        };
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &args,
+        });
+        command_queue
+            .memory_tracker()
+            .track_buffer(std::mem::size_of_val(&args) as u64);
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[
+                args_buffer.bind_group_entry(0).unwrap(),
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&scene_texture.view),
+                },
+            ],
+            layout: &pipeline.0.get_bind_group_layout(0),
+        });
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+            label: None,
+            color_attachments: &[RenderPassColorAttachment {
+                view: target_texture,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&pipeline.0);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+
+        render_pass.draw(0..4, 0..1);
+    }
+}
+
+impl Default for ColorVisionFilter {
+    fn default() -> Self {
+        Self {
+            color_vision: ColorVision::Normal,
+            pipeline: None,
+            scene_texture: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`ColorVisionFilter`] module
+#[derive(Clone, Default)]
+pub struct ColorVisionFilterSettings {
+    /// The simulated color vision
+    pub color_vision: ColorVision,
+}
+
+impl Module for ColorVisionFilter {
+    type Settings = ColorVisionFilterSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_color_vision(settings.color_vision)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        ColorVisionFilterSettings {
+            color_vision: self.color_vision(),
+        }
+    }
+}
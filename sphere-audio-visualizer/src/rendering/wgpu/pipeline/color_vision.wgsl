@@ -0,0 +1,56 @@
+struct Args {
+    matrix: mat3x3<f32>;
+    encoded: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+[[group(0), binding(1)]]
+var scene: texture_2d<f32>;
+
+fn decode(color: vec3<f32>) -> vec3<f32> {
+    return select(
+        pow((color + 0.055) / 1.055, vec3<f32>(2.4)),
+        color / 12.92,
+        color <= vec3<f32>(0.04045),
+    );
+}
+
+fn encode(color: vec3<f32>) -> vec3<f32> {
+    return select(
+        1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055,
+        color * 12.92,
+        color <= vec3<f32>(0.0031308),
+    );
+}
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    let color = textureLoad(scene, vec2<i32>(position.xy), 0);
+
+    // the deficiencies are simulated on linear colors, so targets without
+    // automatic srgb conversion are decoded first
+    var rgb = color.rgb;
+
+    if (args.encoded != 0u) {
+        rgb = decode(rgb);
+    }
+
+    // HDR colors exceed 1.0, so only negative colors are clipped
+    rgb = max(args.matrix * rgb, vec3<f32>(0.0));
+
+    if (args.encoded != 0u) {
+        rgb = encode(rgb);
+    }
+
+    return vec4<f32>(rgb, color.a);
+}
@@ -0,0 +1,702 @@
+use std::{num::NonZeroU32, sync::Arc};
+
+use rayon::{
+    iter::{IndexedParallelIterator, ParallelIterator},
+    slice::ParallelSliceMut,
+};
+use sphere_audio_visualizer_core::{
+    glam::{vec2, Vec2, Vec3A},
+    raytracing::{
+        background::{EnvironmentMap, ImageBackground},
+        fog::Fog,
+        light::{DirectionalLight, LightGroup, LightScene, PointLight, SpotLight},
+        shape::{
+            BvhNode, Capsule, Disc, Instance, Plane, Rect, Scene, SceneArgs, Shape, Sphere,
+            SurfaceTexture, Triangle, AABB, FLAT_NORMAL,
+        },
+        Raytracer as CoreRaytracer, RaytracerArgs,
+    },
+    utils::math::{ColorGrading, Tonemapper},
+};
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, ColorTargetState, ColorWrites, Device,
+    Extent3d, FragmentState, ImageCopyTexture, ImageDataLayout, Origin3d, PipelineLayoutDescriptor,
+    PolygonMode, PrimitiveState, PrimitiveTopology, RenderPipeline, RenderPipelineDescriptor,
+    ShaderStages, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension,
+    VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::{
+        scene::{BasicRaytracerScene, HdrImage, Viewport},
+        wgpu::{
+            utils::{CommandQueue, SeedStream, SEED},
+            Pipeline, HDR_FORMAT,
+        },
+    },
+};
+
+use super::{
+    draw_fullscreen, RAYTRACER_CONTRAST, RAYTRACER_EXPOSURE, RAYTRACER_FOG_COLOR,
+    RAYTRACER_FOG_DENSITY, RAYTRACER_FOG_DISTANCE, RAYTRACER_FOG_STEPS, RAYTRACER_GAMMA,
+    RAYTRACER_PULSE, RAYTRACER_ROULETTE_DEPTH, RAYTRACER_SATURATION, RAYTRACER_SUPERSAMPLING,
+    RAYTRACER_TONEMAPPER,
+};
+
+/// Defines the format of the staging texture the traced pixels are uploaded
+/// into. The full precision keeps the colors exactly as they were traced.
+const STAGING_FORMAT: TextureFormat = TextureFormat::Rgba32Float;
+
+struct CpuRaytracerPipeline(RenderPipeline, TextureFormat);
+
+impl CpuRaytracerPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("cpu_raytracer.wgsl"));
+
+        // the staging format is not filterable, which a derived layout assumes
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                count: None,
+                ty: BindingType::Texture {
+                    multisampled: false,
+                    sample_type: TextureSampleType::Float { filterable: false },
+                    view_dimension: TextureViewDimension::D2,
+                },
+                visibility: ShaderStages::FRAGMENT,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-cpu-raytracer-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: Some(&pipeline_layout),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+struct StagingTexture {
+    texture: Texture,
+    view: TextureView,
+    width: u32,
+    height: u32,
+}
+
+impl StagingTexture {
+    fn new(device: &Device, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("sphere-visualizer-cpu-raytracer"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: STAGING_FORMAT,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        });
+
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        Self {
+            texture,
+            view,
+            width,
+            height,
+        }
+    }
+}
+
+/// Stores the mip levels of an image of the scene, which are sampled like the
+/// textures of the GPU pipelines: bilinear within a level and linear between
+/// the levels. The horizontal axis always repeats.
+struct CpuImage {
+    image: Option<Arc<HdrImage>>,
+    levels: Vec<HdrImage>,
+    repeat_v: bool,
+}
+
+impl CpuImage {
+    fn new(image: Option<&Arc<HdrImage>>, fallback: Vec3A, repeat_v: bool) -> Self {
+        let mut level = match image {
+            Some(image) => image.as_ref().clone(),
+            None => HdrImage::new(1, 1, vec![fallback]),
+        };
+
+        let mut levels = Vec::new();
+        for _ in 1..level.mip_level_count() {
+            let next = level.downsampled();
+            levels.push(std::mem::replace(&mut level, next));
+        }
+        levels.push(level);
+
+        Self {
+            image: image.cloned(),
+            levels,
+            repeat_v,
+        }
+    }
+
+    /// Returns whether the levels were created from the image
+    fn matches(&self, image: Option<&Arc<HdrImage>>) -> bool {
+        match (&self.image, image) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn texel(&self, level: &HdrImage, x: i64, y: i64) -> Vec3A {
+        let width = level.width() as i64;
+        let height = level.height() as i64;
+
+        let x = x.rem_euclid(width);
+        let y = match self.repeat_v {
+            true => y.rem_euclid(height),
+            false => y.clamp(0, height - 1),
+        };
+
+        level.pixels()[(y * width + x) as usize]
+    }
+
+    fn sample_level(&self, level: usize, uv: Vec2) -> Vec3A {
+        let level = &self.levels[level];
+
+        // the texel centers lie at half coordinates
+        let position = uv * vec2(level.width() as f32, level.height() as f32) - 0.5;
+        let origin = position.floor();
+        let fraction = position - origin;
+
+        let x = origin.x as i64;
+        let y = origin.y as i64;
+
+        let top = self
+            .texel(level, x, y)
+            .lerp(self.texel(level, x + 1, y), fraction.x);
+        let bottom = self
+            .texel(level, x, y + 1)
+            .lerp(self.texel(level, x + 1, y + 1), fraction.x);
+
+        top.lerp(bottom, fraction.y)
+    }
+
+    fn sample_lod(&self, uv: Vec2, lod: f32) -> Vec3A {
+        let lod = lod.clamp(0.0, (self.levels.len() - 1) as f32);
+        let level = lod.floor() as usize;
+        let fraction = lod - level as f32;
+
+        match fraction > 0.0 {
+            true => self
+                .sample_level(level, uv)
+                .lerp(self.sample_level(level + 1, uv), fraction),
+            false => self.sample_level(level, uv),
+        }
+    }
+}
+
+/// Samples the environment map, the texture or the normal map of the scene
+/// from a [`CpuImage`]
+struct CpuSampler<'a>(&'a CpuImage);
+
+impl<'a> EnvironmentMap for CpuSampler<'a> {
+    fn sample(&self, uv: Vec2, lod: f32) -> Vec3A {
+        self.0.sample_lod(uv, lod)
+    }
+}
+
+impl<'a> SurfaceTexture for CpuSampler<'a> {
+    fn sample(&self, uv: Vec2) -> Vec3A {
+        self.0.sample_lod(uv, 0.0)
+    }
+}
+
+/// The pipeline module for rendering raytracer scenes on the CPU. The pixels
+/// are traced in parallel on the rayon thread pool by the same core
+/// [`Raytracer`](CoreRaytracer) the shaders are compiled from, uploaded into a
+/// staging texture and copied into the target. It serves as a reference for
+/// the [`Raytracer`](super::Raytracer) shaders and as a fallback for adapters
+/// which can't run them, as it doesn't need storage buffers.
+///
+/// The color grading and the fog use the defaults of the
+/// [`Raytracer`](super::Raytracer). Unlike on the GPU, the images of the
+/// scene are sampled at full precision and spheres are never culled.
+pub struct CpuRaytracer {
+    pulse: f32,
+    loudness: f32,
+    supersampling: u32,
+    roulette_depth: u32,
+    tonemapper: Tonemapper,
+    exposure: f32,
+    seed_stream: SeedStream,
+    pixels: Vec<Vec3A>,
+    environment_map: Option<CpuImage>,
+    surface_texture: Option<CpuImage>,
+    normal_map: Option<CpuImage>,
+    staging_texture: Option<StagingTexture>,
+    pipeline: Option<CpuRaytracerPipeline>,
+}
+
+impl CpuRaytracer {
+    /// Returns the strength with which the loudness raises the background
+    /// brightness
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the strength with which the loudness raises the background
+    /// brightness
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse;
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the background
+    /// brightness
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+
+    /// Returns the number of samples per pixel along each axis
+    pub fn supersampling(&self) -> u32 {
+        self.supersampling
+    }
+
+    /// Sets the number of samples per pixel along each axis. The traced rays
+    /// grow with its square, which is felt much more on the CPU.
+    pub fn set_supersampling(&mut self, supersampling: u32) -> &mut Self {
+        self.supersampling = supersampling.max(1);
+        self
+    }
+
+    /// Sets the number of samples per pixel along each axis
+    pub fn with_supersampling(mut self, supersampling: u32) -> Self {
+        self.set_supersampling(supersampling);
+        self
+    }
+
+    /// Returns the number of bounces after which dark paths are terminated
+    /// randomly
+    pub fn roulette_depth(&self) -> u32 {
+        self.roulette_depth
+    }
+
+    /// Sets the number of bounces after which dark paths are terminated
+    /// randomly. Zero disables the termination.
+    pub fn set_roulette_depth(&mut self, roulette_depth: u32) -> &mut Self {
+        self.roulette_depth = roulette_depth;
+        self
+    }
+
+    /// Sets the number of bounces after which dark paths are terminated
+    /// randomly
+    pub fn with_roulette_depth(mut self, roulette_depth: u32) -> Self {
+        self.set_roulette_depth(roulette_depth);
+        self
+    }
+
+    /// Returns the operator mapping the radiance to displayable colors
+    pub fn tonemapper(&self) -> Tonemapper {
+        self.tonemapper
+    }
+
+    /// Sets the operator mapping the radiance to displayable colors
+    pub fn set_tonemapper(&mut self, tonemapper: Tonemapper) -> &mut Self {
+        self.tonemapper = tonemapper;
+        self
+    }
+
+    /// Sets the operator mapping the radiance to displayable colors
+    pub fn with_tonemapper(mut self, tonemapper: Tonemapper) -> Self {
+        self.set_tonemapper(tonemapper);
+        self
+    }
+
+    /// Returns the exposure in stops
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Sets the exposure in stops applied before tonemapping
+    pub fn set_exposure(&mut self, exposure: f32) -> &mut Self {
+        self.exposure = exposure;
+        self
+    }
+
+    /// Sets the exposure in stops applied before tonemapping
+    pub fn with_exposure(mut self, exposure: f32) -> Self {
+        self.set_exposure(exposure);
+        self
+    }
+
+    /// Returns the base seed of stochastic effects
+    pub fn seed(&self) -> u32 {
+        self.seed_stream.seed()
+    }
+
+    /// Sets the base seed of stochastic effects. The same seed traces the
+    /// same frames as the [`Raytracer`](super::Raytracer) with that seed.
+    pub fn set_seed(&mut self, seed: u32) -> &mut Self {
+        self.seed_stream.set_seed(seed);
+        self
+    }
+
+    /// Sets the base seed of stochastic effects
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.set_seed(seed);
+        self
+    }
+}
+
+impl Default for CpuRaytracer {
+    fn default() -> Self {
+        Self {
+            pulse: RAYTRACER_PULSE,
+            loudness: 0.0,
+            supersampling: RAYTRACER_SUPERSAMPLING,
+            roulette_depth: RAYTRACER_ROULETTE_DEPTH,
+            tonemapper: RAYTRACER_TONEMAPPER,
+            exposure: RAYTRACER_EXPOSURE,
+            seed_stream: SeedStream::default(),
+            pixels: Vec::new(),
+            environment_map: None,
+            surface_texture: None,
+            normal_map: None,
+            staging_texture: None,
+            pipeline: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`CpuRaytracer`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct CpuRaytracerSettings {
+    /// The strength with which the loudness raises the background brightness
+    pub pulse: f32,
+    /// The number of samples per pixel along each axis
+    pub supersampling: u32,
+    /// The number of bounces after which dark paths are terminated randomly.
+    /// Zero disables the termination.
+    pub roulette_depth: u32,
+    /// The operator mapping the radiance to displayable colors
+    pub tonemapper: Tonemapper,
+    /// The exposure in stops applied before tonemapping
+    pub exposure: f32,
+    /// The base seed of stochastic effects
+    pub seed: u32,
+}
+
+impl Default for CpuRaytracerSettings {
+    fn default() -> Self {
+        Self {
+            pulse: RAYTRACER_PULSE,
+            supersampling: RAYTRACER_SUPERSAMPLING,
+            roulette_depth: RAYTRACER_ROULETTE_DEPTH,
+            tonemapper: RAYTRACER_TONEMAPPER,
+            exposure: RAYTRACER_EXPOSURE,
+            seed: SEED,
+        }
+    }
+}
+
+impl Module for CpuRaytracer {
+    type Settings = CpuRaytracerSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_pulse(settings.pulse)
+            .set_supersampling(settings.supersampling)
+            .set_roulette_depth(settings.roulette_depth)
+            .set_tonemapper(settings.tonemapper)
+            .set_exposure(settings.exposure)
+            .set_seed(settings.seed)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        CpuRaytracerSettings {
+            pulse: self.pulse(),
+            supersampling: self.supersampling(),
+            roulette_depth: self.roulette_depth(),
+            tonemapper: self.tonemapper(),
+            exposure: self.exposure(),
+            seed: self.seed(),
+        }
+    }
+}
+
+/// Returns the shapes of a type in the scene with their bounding box and
+/// hierarchy
+fn scene_shapes<T: Shape + 'static>(scene: &BasicRaytracerScene) -> (&[T], &[BvhNode], AABB) {
+    match scene.shapes::<T>() {
+        Some(shapes) => (
+            shapes.shapes(),
+            shapes.nodes(),
+            shapes.bounding_box().clone(),
+        ),
+        None => (&[], &[], AABB::empty()),
+    }
+}
+
+impl Pipeline<BasicRaytracerScene> for CpuRaytracer {
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
+    fn render(
+        &mut self,
+        scene: BasicRaytracerScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| CpuRaytracerPipeline::new(device, output_format));
+
+        if pipeline.1 != output_format {
+            *pipeline = CpuRaytracerPipeline::new(device, output_format);
+        }
+
+        let viewport = Viewport {
+            offset: scene.camera.screen_offset(),
+            size: scene.camera.screen_size(),
+        };
+
+        let extent = (viewport.offset + viewport.size).ceil();
+        let width = (extent.x as u32).max(1);
+        let height = (extent.y as u32).max(1);
+
+        if !matches!(&self.staging_texture, Some(texture) if texture.width == width && texture.height == height)
+        {
+            self.staging_texture = Some(StagingTexture::new(device, width, height));
+        }
+
+        let staging_texture = self.staging_texture.as_ref().unwrap();
+        command_queue
+            .memory_tracker()
+            .track_texture(width, height, STAGING_FORMAT);
+
+        // the images are only prepared again once the scene replaced them
+        let environment_map = scene.environment_map();
+        if !matches!(&self.environment_map, Some(image) if image.matches(environment_map)) {
+            self.environment_map = Some(CpuImage::new(environment_map, Vec3A::ONE, false));
+        }
+
+        let surface_texture = scene.surface_texture();
+        if !matches!(&self.surface_texture, Some(image) if image.matches(surface_texture)) {
+            self.surface_texture = Some(CpuImage::new(surface_texture, Vec3A::ONE, true));
+        }
+
+        let normal_map = scene.normal_map();
+        if !matches!(&self.normal_map, Some(image) if image.matches(normal_map)) {
+            self.normal_map = Some(CpuImage::new(normal_map, FLAT_NORMAL, true));
+        }
+
+        // the shapes stay in main memory, so nothing has to be fitted
+        let (spheres, sphere_nodes, spheres_bounding_box) = scene_shapes::<Sphere>(&scene);
+        let (rects, _, rects_bounding_box) = scene_shapes::<Rect>(&scene);
+        let (triangles, triangle_nodes, triangles_bounding_box) = scene_shapes::<Triangle>(&scene);
+        let (planes, _, _) = scene_shapes::<Plane>(&scene);
+        let (discs, _, discs_bounding_box) = scene_shapes::<Disc>(&scene);
+        let (capsules, _, capsules_bounding_box) = scene_shapes::<Capsule>(&scene);
+        let (instances, _, instances_bounding_box) = scene_shapes::<Instance>(&scene);
+
+        let point_lights = scene
+            .lights::<PointLight>()
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let directional_lights = scene
+            .lights::<DirectionalLight>()
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let spot_lights = scene
+            .lights::<SpotLight>()
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+
+        let (frame, seed) = self.seed_stream.next_frame();
+
+        let args = RaytracerArgs {
+            camera: scene.camera.clone(),
+            background: scene.background.clone(),
+            bounces: scene.bounces,
+            loudness: self.loudness * self.pulse,
+            frame,
+            seed,
+            supersampling: self.supersampling,
+            jitter: 0,
+            roulette_depth: self.roulette_depth,
+            tonemapper: self.tonemapper,
+            hdr: (output_format == HDR_FORMAT) as u32,
+            color_grading: ColorGrading {
+                exposure: self.exposure,
+                contrast: RAYTRACER_CONTRAST,
+                saturation: RAYTRACER_SATURATION,
+                gamma: RAYTRACER_GAMMA,
+            },
+            fog: Fog {
+                color: RAYTRACER_FOG_COLOR,
+                density: RAYTRACER_FOG_DENSITY,
+                distance: RAYTRACER_FOG_DISTANCE,
+                steps: RAYTRACER_FOG_STEPS,
+            },
+        };
+
+        let shapes = Scene::from_args(
+            SceneArgs {
+                spheres_bounding_box,
+                rects_bounding_box,
+                triangles_bounding_box,
+                discs_bounding_box,
+                capsules_bounding_box,
+                instances_bounding_box,
+                sphere_count: spheres.len() as u32,
+                sphere_node_count: sphere_nodes.len() as u32,
+                triangle_node_count: triangle_nodes.len() as u32,
+                plane_count: planes.len() as u32,
+                disc_count: discs.len() as u32,
+                capsule_count: capsules.len() as u32,
+                instance_count: instances.len() as u32,
+            },
+            scene.materials(),
+            spheres,
+            sphere_nodes,
+            rects,
+            triangles,
+            triangle_nodes,
+            planes,
+            discs,
+            capsules,
+            instances,
+            CpuSampler(self.surface_texture.as_ref().unwrap()),
+            CpuSampler(self.normal_map.as_ref().unwrap()),
+        );
+
+        let lights = LightScene {
+            point_lights: LightGroup(point_lights),
+            directional_lights: LightGroup(directional_lights),
+            spot_lights: LightGroup(spot_lights),
+        };
+
+        let background = ImageBackground::from_args(
+            args.background.clone(),
+            CpuSampler(self.environment_map.as_ref().unwrap()),
+        );
+
+        let raytracer = CoreRaytracer::from_args(args.with_background(background), shapes, lights);
+
+        // the pixels outside of the viewport are never drawn, so they are not
+        // traced
+        let min = viewport.offset.floor();
+        let max = extent;
+
+        self.pixels.clear();
+        self.pixels
+            .resize(width as usize * height as usize, Vec3A::ZERO);
+
+        self.pixels
+            .par_chunks_mut(width as usize)
+            .enumerate()
+            .for_each(|(y, row)| {
+                if (y as f32) < min.y || (y as f32) >= max.y {
+                    return;
+                }
+
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    if (x as f32) < min.x || (x as f32) >= max.x {
+                        continue;
+                    }
+
+                    // the sample lies at the pixel center like the fragment
+                    // coordinate of the shaders
+                    *pixel = raytracer.sample(&(vec2(x as f32, y as f32) + 0.5));
+                }
+            });
+
+        let data = self
+            .pixels
+            .iter()
+            .flat_map(|pixel| pixel.extend(1.0).to_array())
+            .flat_map(f32::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        // the upload is executed before the commands of the frame, so the
+        // copy pass reads the traced pixels
+        command_queue.queue().write_texture(
+            ImageCopyTexture {
+                texture: &staging_texture.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            &data,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(width * 16),
+                rows_per_image: None,
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&staging_texture.view),
+            }],
+            layout: &pipeline.0.get_bind_group_layout(0),
+        });
+
+        draw_fullscreen(
+            command_queue.command_encoder(device),
+            &pipeline.0,
+            &bind_group,
+            target_texture,
+            &viewport,
+        );
+    }
+}
@@ -0,0 +1,17 @@
+[[group(0), binding(0)]]
+var image: texture_2d<f32>;
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    // the image covers the target pixel by pixel, so it is loaded without a
+    // sampler
+    return textureLoad(image, vec2<i32>(position.xy), 0);
+}
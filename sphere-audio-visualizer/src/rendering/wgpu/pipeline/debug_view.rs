@@ -0,0 +1,514 @@
+use sphere_audio_visualizer_core::{
+    glam::{vec3a, Mat4, Vec3A},
+    raytracing::{
+        light::PointLight,
+        shape::{Capsule, Disc, Material, Rect, Shape, Sphere, Triangle, AABB},
+    },
+};
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BufferUsages, Color, ColorTargetState, ColorWrites,
+    CompareFunction, DepthStencilState, Device, Extent3d, FragmentState, LoadOp, Operations,
+    PolygonMode, PrimitiveState, PrimitiveTopology, RenderPassColorAttachment,
+    RenderPassDepthStencilAttachment, RenderPassDescriptor, RenderPipeline,
+    RenderPipelineDescriptor, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages, TextureView, TextureViewDescriptor, VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::{
+        scene::{BasicRaytracerScene, ShapeCollection, Viewport},
+        wgpu::{
+            utils::{
+                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+            },
+            Pipeline,
+        },
+    },
+};
+
+use super::set_viewport;
+
+/// Defines the default size of the light gizmos in world space
+pub const LIGHT_GIZMO_SIZE: f32 = 1.0;
+
+const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+const SCENE_BOUNDING_BOX_COLOR: Vec3A = Vec3A::new(0.0, 1.0, 0.0);
+const SHAPE_BOUNDING_BOX_COLOR: Vec3A = Vec3A::new(0.0, 0.5, 0.5);
+const BVH_NODE_COLOR: Vec3A = Vec3A::new(0.5, 0.0, 0.5);
+const LIGHT_GIZMO_COLOR: Vec3A = Vec3A::new(1.0, 1.0, 0.0);
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct DebugViewArgs {
+    view: Mat4,
+    projection: Mat4,
+}
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct DebugViewLineVertex {
+    position: Vec3A,
+    color: Vec3A,
+}
+
+fn add_line(vertices: &mut Vec<DebugViewLineVertex>, from: Vec3A, to: Vec3A, color: Vec3A) {
+    vertices.push(DebugViewLineVertex {
+        position: from,
+        color,
+    });
+    vertices.push(DebugViewLineVertex {
+        position: to,
+        color,
+    });
+}
+
+fn add_bounding_box(vertices: &mut Vec<DebugViewLineVertex>, aabb: &AABB, color: Vec3A) {
+    if aabb.is_empty() || !aabb.min().is_finite() || !aabb.max().is_finite() {
+        return;
+    }
+
+    let (min, max) = (aabb.min(), aabb.max());
+
+    let corner = |i: usize| {
+        vec3a(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        )
+    };
+
+    for i in 0..8 {
+        for axis in [1, 2, 4] {
+            if i & axis == 0 {
+                add_line(vertices, corner(i), corner(i | axis), color);
+            }
+        }
+    }
+}
+
+fn add_shape_bounding_boxes<S: Shape>(
+    vertices: &mut Vec<DebugViewLineVertex>,
+    shapes: Option<&ShapeCollection<S>>,
+) {
+    if let Some(shapes) = shapes {
+        add_bounding_box(vertices, shapes.bounding_box(), SCENE_BOUNDING_BOX_COLOR);
+
+        // the root node matches the bounding box of the scene
+        for node in shapes.nodes().iter().skip(1) {
+            add_bounding_box(vertices, node.bounding_box(), BVH_NODE_COLOR);
+        }
+
+        for shape in shapes.shapes() {
+            add_bounding_box(vertices, &shape.bounding_box(), SHAPE_BOUNDING_BOX_COLOR);
+        }
+    }
+}
+
+fn add_light_gizmo(vertices: &mut Vec<DebugViewLineVertex>, position: Vec3A) {
+    for axis in [Vec3A::X, Vec3A::Y, Vec3A::Z] {
+        let offset = axis * LIGHT_GIZMO_SIZE * 0.5;
+
+        add_line(
+            vertices,
+            position - offset,
+            position + offset,
+            LIGHT_GIZMO_COLOR,
+        );
+    }
+}
+
+struct DebugViewDepthTexture {
+    _texture: Texture,
+    texture_view: TextureView,
+    width: u32,
+    height: u32,
+}
+
+struct DebugViewPipeline {
+    sphere_pipeline: RenderPipeline,
+    line_pipeline: RenderPipeline,
+    target_format: TextureFormat,
+    depth_texture: Option<DebugViewDepthTexture>,
+}
+
+impl DebugViewPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("debug_view.wgsl"));
+
+        let create_pipeline = |label, entry_points: (&str, &str), topology| {
+            device.create_render_pipeline(&RenderPipelineDescriptor {
+                label: Some(label),
+                vertex: VertexState {
+                    module: &shader_module,
+                    entry_point: entry_points.0,
+                    buffers: &[],
+                },
+                fragment: Some(FragmentState {
+                    module: &shader_module,
+                    entry_point: entry_points.1,
+                    targets: &[ColorTargetState {
+                        format: target_format,
+                        blend: None,
+                        write_mask: ColorWrites::COLOR,
+                    }],
+                }),
+                depth_stencil: Some(DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: CompareFunction::Less,
+                    stencil: Default::default(),
+                    bias: Default::default(),
+                }),
+                multiview: None,
+                layout: None,
+                primitive: PrimitiveState {
+                    topology,
+                    polygon_mode: PolygonMode::Fill,
+                    ..Default::default()
+                },
+                multisample: Default::default(),
+            })
+        };
+
+        let sphere_pipeline = create_pipeline(
+            "sphere-visualizer-debug-view-sphere-pipeline",
+            ("sphere_vertex", "sphere_fragment"),
+            PrimitiveTopology::TriangleStrip,
+        );
+
+        let line_pipeline = create_pipeline(
+            "sphere-visualizer-debug-view-line-pipeline",
+            ("line_vertex", "line_fragment"),
+            PrimitiveTopology::LineList,
+        );
+
+        Self {
+            sphere_pipeline,
+            line_pipeline,
+            target_format,
+            depth_texture: None,
+        }
+    }
+
+    fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        if !matches!(&self.depth_texture, Some(texture) if texture.width == width && texture.height == height)
+        {
+            let texture = device.create_texture(&TextureDescriptor {
+                label: None,
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: DEPTH_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT,
+            });
+
+            let texture_view = texture.create_view(&TextureViewDescriptor::default());
+
+            self.depth_texture = Some(DebugViewDepthTexture {
+                _texture: texture,
+                texture_view,
+                width,
+                height,
+            });
+        }
+    }
+}
+
+/// The pipeline module for a rasterized debug view of raytracer scenes. The
+/// spheres are drawn as instanced billboards and the bounding boxes and
+/// lights as lines, which is a lot cheaper than raytracing the scene.
+pub struct DebugView {
+    show_spheres: bool,
+    show_bounding_boxes: bool,
+    show_lights: bool,
+    pipeline: Option<DebugViewPipeline>,
+}
+
+impl DebugView {
+    /// Returns if the spheres are drawn
+    pub fn show_spheres(&self) -> bool {
+        self.show_spheres
+    }
+
+    /// Sets if the spheres are drawn
+    pub fn set_show_spheres(&mut self, show_spheres: bool) -> &mut Self {
+        self.show_spheres = show_spheres;
+        self
+    }
+
+    /// Sets if the spheres are drawn
+    pub fn with_show_spheres(mut self, show_spheres: bool) -> Self {
+        self.set_show_spheres(show_spheres);
+        self
+    }
+
+    /// Returns if the bounding boxes of the shapes are drawn
+    pub fn show_bounding_boxes(&self) -> bool {
+        self.show_bounding_boxes
+    }
+
+    /// Sets if the bounding boxes of the shapes are drawn
+    pub fn set_show_bounding_boxes(&mut self, show_bounding_boxes: bool) -> &mut Self {
+        self.show_bounding_boxes = show_bounding_boxes;
+        self
+    }
+
+    /// Sets if the bounding boxes of the shapes are drawn
+    pub fn with_show_bounding_boxes(mut self, show_bounding_boxes: bool) -> Self {
+        self.set_show_bounding_boxes(show_bounding_boxes);
+        self
+    }
+
+    /// Returns if the lights are drawn as gizmos
+    pub fn show_lights(&self) -> bool {
+        self.show_lights
+    }
+
+    /// Sets if the lights are drawn as gizmos
+    pub fn set_show_lights(&mut self, show_lights: bool) -> &mut Self {
+        self.show_lights = show_lights;
+        self
+    }
+
+    /// Sets if the lights are drawn as gizmos
+    pub fn with_show_lights(mut self, show_lights: bool) -> Self {
+        self.set_show_lights(show_lights);
+        self
+    }
+}
+
+impl Default for DebugView {
+    fn default() -> Self {
+        Self {
+            show_spheres: true,
+            show_bounding_boxes: true,
+            show_lights: true,
+            pipeline: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`DebugView`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DebugViewSettings {
+    /// Whether the spheres are drawn
+    pub show_spheres: bool,
+    /// Whether the bounding boxes of the shapes are drawn
+    pub show_bounding_boxes: bool,
+    /// Whether the lights are drawn as gizmos
+    pub show_lights: bool,
+}
+
+impl Default for DebugViewSettings {
+    fn default() -> Self {
+        Self {
+            show_spheres: true,
+            show_bounding_boxes: true,
+            show_lights: true,
+        }
+    }
+}
+
+impl Module for DebugView {
+    type Settings = DebugViewSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_show_spheres(settings.show_spheres)
+            .set_show_bounding_boxes(settings.show_bounding_boxes)
+            .set_show_lights(settings.show_lights)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        DebugViewSettings {
+            show_spheres: self.show_spheres(),
+            show_bounding_boxes: self.show_bounding_boxes(),
+            show_lights: self.show_lights(),
+        }
+    }
+}
+
+impl Pipeline<BasicRaytracerScene> for DebugView {
+    fn render(
+        &mut self,
+        scene: BasicRaytracerScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| DebugViewPipeline::new(device, output_format));
+
+        if pipeline.target_format != output_format {
+            *pipeline = DebugViewPipeline::new(device, output_format);
+        }
+
+        let viewport = Viewport {
+            offset: scene.camera.screen_offset(),
+            size: scene.camera.screen_size(),
+        };
+
+        let target_size = viewport.target_size();
+        let (width, height) = (target_size.x as u32, target_size.y as u32);
+
+        pipeline.resize(device, width, height);
+
+        let memory_tracker = command_queue.memory_tracker();
+        memory_tracker.track_texture(width, height, DEPTH_FORMAT);
+
+        // the rasterized preview always uses a perspective projection
+        let args = DebugViewArgs {
+            view: scene.camera.view(),
+            projection: scene.camera.perspective().projection(),
+        };
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &args,
+        });
+        memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+        let spheres = match self.show_spheres {
+            true => memory_tracker.fit(
+                "Spheres",
+                scene
+                    .shapes::<Sphere>()
+                    .map(ShapeCollection::shapes)
+                    .unwrap_or(&[]),
+            ),
+            false => &[],
+        };
+        let sphere_count = spheres.len() as u32;
+
+        // the materials are not fitted, as every sphere relies on its material
+        let materials = scene.materials();
+
+        // the placeholder is never used as there is no sphere referencing it
+        let placeholder_material = [Material::new(Vec3A::ZERO)];
+
+        let spheres_bind_group = (!spheres.is_empty()).then(|| {
+            let spheres_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: spheres,
+            });
+
+            let materials_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: match materials.is_empty() {
+                    true => &placeholder_material[..],
+                    false => materials,
+                },
+            });
+            memory_tracker.track_buffer(std::mem::size_of_val(materials) as u64);
+
+            device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                entries: &[
+                    args_buffer.bind_group_entry(0).unwrap(),
+                    spheres_buffer.bind_group_entry(1).unwrap(),
+                    materials_buffer.bind_group_entry(3).unwrap(),
+                ],
+                layout: &pipeline.sphere_pipeline.get_bind_group_layout(0),
+            })
+        });
+
+        let mut line_vertices = Vec::new();
+
+        if self.show_bounding_boxes {
+            add_shape_bounding_boxes(&mut line_vertices, scene.shapes::<Sphere>());
+            add_shape_bounding_boxes(&mut line_vertices, scene.shapes::<Rect>());
+            add_shape_bounding_boxes(&mut line_vertices, scene.shapes::<Triangle>());
+            add_shape_bounding_boxes(&mut line_vertices, scene.shapes::<Disc>());
+            add_shape_bounding_boxes(&mut line_vertices, scene.shapes::<Capsule>());
+        }
+
+        if self.show_lights {
+            for light in scene.lights::<PointLight>().into_iter().flatten() {
+                add_light_gizmo(&mut line_vertices, light.position());
+            }
+        }
+
+        let line_vertices = memory_tracker.fit("Debug Lines", &line_vertices);
+        let line_vertices = &line_vertices[..line_vertices.len() & !1];
+
+        let lines_bind_group = (!line_vertices.is_empty()).then(|| {
+            let lines_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: line_vertices,
+            });
+
+            device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                entries: &[
+                    args_buffer.bind_group_entry(0).unwrap(),
+                    lines_buffer.bind_group_entry(2).unwrap(),
+                ],
+                layout: &pipeline.line_pipeline.get_bind_group_layout(0),
+            })
+        });
+
+        let depth_texture = pipeline.depth_texture.as_ref().unwrap();
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[RenderPassColorAttachment {
+                    view: target_texture,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color {
+                            r: 0.05,
+                            g: 0.05,
+                            b: 0.05,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: &depth_texture.texture_view,
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            set_viewport(&mut render_pass, &viewport);
+
+            if let Some(spheres_bind_group) = &spheres_bind_group {
+                render_pass.set_pipeline(&pipeline.sphere_pipeline);
+                render_pass.set_bind_group(0, spheres_bind_group, &[]);
+
+                render_pass.draw(0..4, 0..sphere_count);
+            }
+
+            if let Some(lines_bind_group) = &lines_bind_group {
+                render_pass.set_pipeline(&pipeline.line_pipeline);
+                render_pass.set_bind_group(0, lines_bind_group, &[]);
+
+                render_pass.draw(0..line_vertices.len() as u32, 0..1);
+            }
+        }
+    }
+}
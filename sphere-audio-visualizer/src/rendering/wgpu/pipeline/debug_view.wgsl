@@ -0,0 +1,122 @@
+struct Args {
+    view: mat4x4<f32>;
+    projection: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+// mirrors sphere_audio_visualizer_core::raytracing::shape::Sphere
+struct Sphere {
+    position: vec3<f32>;
+    _pad0: f32;
+    radius: f32;
+    material: u32;
+    normal_strength: f32;
+    emission: f32;
+    motion: vec3<f32>;
+};
+
+struct Spheres {
+    spheres: array<Sphere>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> spheres: Spheres;
+
+// mirrors sphere_audio_visualizer_core::raytracing::shape::Material
+struct Material {
+    albedo: vec3<f32>;
+    _pad0: f32;
+    emission: vec3<f32>;
+    _pad1: f32;
+    roughness: f32;
+    metallic: f32;
+    ior: f32;
+    transmission: f32;
+};
+
+struct Materials {
+    materials: array<Material>;
+};
+
+[[group(0), binding(3)]]
+var<storage, read> materials: Materials;
+
+struct LineVertex {
+    position: vec3<f32>;
+    color: vec3<f32>;
+};
+
+struct LineVertices {
+    vertices: array<LineVertex>;
+};
+
+[[group(0), binding(2)]]
+var<storage, read> lines: LineVertices;
+
+struct SphereOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+    [[location(1)]] color: vec3<f32>;
+};
+
+[[stage(vertex)]]
+fn sphere_vertex(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    [[builtin(instance_index)]] instance_index: u32,
+) -> SphereOutput {
+    let sphere = spheres.spheres[instance_index];
+
+    let uv = vec2<f32>(f32(vertex_index & 1u) * 2.0 - 1.0, f32(vertex_index & 2u) - 1.0);
+
+    // the billboard is placed at the front of the sphere facing the camera
+    let center = args.view * vec4<f32>(sphere.position, 1.0);
+    let position = center.xyz + vec3<f32>(uv * sphere.radius, -sphere.radius);
+
+    var output: SphereOutput;
+    output.position = args.projection * vec4<f32>(position, 1.0);
+    output.uv = uv;
+
+    // glowing spheres add their color to the emission of the material like
+    // in the raytracer
+    let material = materials.materials[sphere.material];
+    output.color = material.albedo + material.emission + material.albedo * sphere.emission;
+
+    return output;
+}
+
+[[stage(fragment)]]
+fn sphere_fragment(input: SphereOutput) -> [[location(0)]] vec4<f32> {
+    let distance = dot(input.uv, input.uv);
+
+    if (distance > 1.0) {
+        discard;
+    }
+
+    let normal = vec3<f32>(input.uv, -sqrt(1.0 - distance));
+    let shading = 0.25 + 0.75 * max(dot(normal, normalize(vec3<f32>(-0.5, 0.5, -1.0))), 0.0);
+
+    return vec4<f32>(input.color * shading, 1.0);
+}
+
+struct LineOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] color: vec3<f32>;
+};
+
+[[stage(vertex)]]
+fn line_vertex([[builtin(vertex_index)]] vertex_index: u32) -> LineOutput {
+    let vertex = lines.vertices[vertex_index];
+
+    var output: LineOutput;
+    output.position = args.projection * args.view * vec4<f32>(vertex.position, 1.0);
+    output.color = vertex.color;
+
+    return output;
+}
+
+[[stage(fragment)]]
+fn line_fragment(input: LineOutput) -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(input.color, 1.0);
+}
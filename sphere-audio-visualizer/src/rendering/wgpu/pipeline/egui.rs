@@ -1,4 +1,9 @@
-use egui::{epaint::ClippedShape, ClippedMesh, Context, TexturesDelta};
+use std::collections::HashMap;
+
+use egui::{
+    epaint::{ClippedShape, ImageDelta},
+    ClippedMesh, ColorImage, Context, ImageData, TextureId, TexturesDelta,
+};
 use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
 use wgpu::{Device, TextureFormat, TextureView};
 
@@ -22,6 +27,112 @@ impl EGUIRenderPipeline {
 #[derive(Default)]
 pub struct EGUIRenderer {
     egui_render_pipeline: Option<EGUIRenderPipeline>,
+    images: HashMap<TextureId, ImageData>,
+}
+
+impl EGUIRenderer {
+    /// Keeps a copy of every live texture, so it can be uploaded again when
+    /// the render pass has to be recreated
+    fn update_images(&mut self, textures: &TexturesDelta) {
+        for (texture_id, image_delta) in &textures.set {
+            match (image_delta.pos, self.images.get_mut(texture_id)) {
+                (Some(pos), Some(image)) => blit_image(image, &image_delta.image, pos),
+                _ => {
+                    self.images.insert(*texture_id, image_delta.image.clone());
+                }
+            }
+        }
+
+        for texture_id in &textures.free {
+            self.images.remove(texture_id);
+        }
+    }
+
+    fn full_textures(&self) -> TexturesDelta {
+        TexturesDelta {
+            set: self
+                .images
+                .iter()
+                .map(|(texture_id, image)| (*texture_id, ImageDelta::full(image.clone())))
+                .collect(),
+            free: Vec::new(),
+        }
+    }
+}
+
+fn blit_image(target: &mut ImageData, source: &ImageData, pos: [usize; 2]) {
+    match (target, source) {
+        (ImageData::Color(target), ImageData::Color(source)) => blit(
+            &mut target.pixels,
+            target.size,
+            &source.pixels,
+            source.size,
+            pos,
+        ),
+        (ImageData::Alpha(target), ImageData::Alpha(source)) => blit(
+            &mut target.pixels,
+            target.size,
+            &source.pixels,
+            source.size,
+            pos,
+        ),
+        _ => {}
+    }
+}
+
+fn blit<T: Copy>(
+    target: &mut [T],
+    target_size: [usize; 2],
+    source: &[T],
+    source_size: [usize; 2],
+    [x, y]: [usize; 2],
+) {
+    let width = source_size[0].min(target_size[0].saturating_sub(x));
+    let height = source_size[1].min(target_size[1].saturating_sub(y));
+
+    for row in 0..height {
+        let target_start = (y + row) * target_size[0] + x;
+        let source_start = row * source_size[0];
+
+        target[target_start..target_start + width]
+            .copy_from_slice(&source[source_start..source_start + width]);
+    }
+}
+
+/// Manages user textures like album art or logos, which can be drawn in the
+/// overlay by their [`TextureId`]
+#[derive(Default)]
+pub struct EGUIUserTextures {
+    next_id: u64,
+    textures: TexturesDelta,
+}
+
+impl EGUIUserTextures {
+    /// Registers a new image and returns the [`TextureId`] to draw it with
+    pub fn alloc(&mut self, image: ColorImage) -> TextureId {
+        let texture_id = TextureId::User(self.next_id);
+        self.next_id += 1;
+        self.set(texture_id, image);
+        texture_id
+    }
+
+    /// Replaces the image of an already registered texture
+    pub fn set(&mut self, texture_id: TextureId, image: ColorImage) {
+        self.textures
+            .set
+            .insert(texture_id, ImageDelta::full(image));
+    }
+
+    /// Frees a registered texture. The [`TextureId`] must not be used
+    /// afterwards.
+    pub fn free(&mut self, texture_id: TextureId) {
+        self.textures.free.push(texture_id);
+    }
+
+    /// Returns the changes since the last call
+    pub fn take_delta(&mut self) -> TexturesDelta {
+        std::mem::take(&mut self.textures)
+    }
 }
 
 /// The Scene representation for the [`EGUIRenderer`]
@@ -47,6 +158,12 @@ impl EGUIScene {
             textures: textures_delta,
         }
     }
+
+    /// Adds the pending changes of the user textures to the scene
+    pub fn with_user_textures(mut self, user_textures: &mut EGUIUserTextures) -> Self {
+        self.textures.append(user_textures.take_delta());
+        self
+    }
 }
 
 impl Pipeline<EGUIScene> for EGUIRenderer {
@@ -58,20 +175,27 @@ impl Pipeline<EGUIScene> for EGUIRenderer {
         output_format: TextureFormat,
         output_texture: &TextureView,
     ) {
-        let egui_render_pass = {
-            let egui_render_pipeline = self
-                .egui_render_pipeline
-                .get_or_insert_with(|| EGUIRenderPipeline::new(device, output_format));
+        self.update_images(&scene.textures);
 
-            if egui_render_pipeline.output_format != output_format {
-                *egui_render_pipeline = EGUIRenderPipeline::new(device, output_format);
-            }
+        let recreated = self
+            .egui_render_pipeline
+            .as_ref()
+            .map_or(true, |pipeline| pipeline.output_format != output_format);
 
-            &mut egui_render_pipeline.egui_render_pass
+        if recreated {
+            self.egui_render_pipeline = Some(EGUIRenderPipeline::new(device, output_format));
+        }
+
+        let textures = if recreated {
+            self.full_textures()
+        } else {
+            scene.textures
         };
 
+        let egui_render_pass = &mut self.egui_render_pipeline.as_mut().unwrap().egui_render_pass;
+
         egui_render_pass
-            .add_textures(device, command_queue.queue(), &scene.textures)
+            .add_textures(device, command_queue.queue(), &textures)
             .unwrap();
 
         egui_render_pass.update_buffers(
@@ -91,6 +215,10 @@ impl Pipeline<EGUIScene> for EGUIRenderer {
             )
             .unwrap();
 
-        egui_render_pass.remove_textures(scene.textures).unwrap();
+        // a recreated render pass only received the live textures, so there
+        // is nothing left to free
+        if !recreated {
+            egui_render_pass.remove_textures(textures).unwrap();
+        }
     }
 }
@@ -0,0 +1,438 @@
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BindGroupEntry, BindingResource, BufferUsages, Color,
+    ColorTargetState, ColorWrites, Device, Extent3d, FragmentState, LoadOp, Operations,
+    PolygonMode, PrimitiveState, PrimitiveTopology, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Texture, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
+    VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::wgpu::{
+        utils::{CommandQueue, TypedBufferDeviceExt, TypedBufferInitDescriptor},
+        SurfaceTarget, HDR_FORMAT,
+    },
+};
+
+/// Defines the default brightness of white in nits
+pub const HDR_OUTPUT_PAPER_WHITE: f32 = 200.0;
+
+/// Defines the default peak brightness of the display in nits
+pub const HDR_OUTPUT_PEAK_BRIGHTNESS: f32 = 1000.0;
+
+/// Defines the brightness of `1.0` in scRGB in nits
+const SCRGB_WHITE: f32 = 80.0;
+
+/// Defines the format of the texture the UI is drawn into
+const OVERLAY_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+#[repr(C, align(16))]
+struct HdrOutputArgs {
+    paper_white: f32,
+    peak_brightness: f32,
+}
+
+struct HdrOutputPipeline(RenderPipeline, TextureFormat);
+
+impl HdrOutputPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("hdr_output.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-hdr-output-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+struct OutputTexture {
+    _texture: Texture,
+    view: TextureView,
+    width: u32,
+    height: u32,
+}
+
+impl OutputTexture {
+    fn new(device: &Device, width: u32, height: u32, format: TextureFormat) -> Self {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("sphere-visualizer-hdr-output"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+        });
+
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        Self {
+            _texture: texture,
+            view,
+            width,
+            height,
+        }
+    }
+
+    /// Reuses the texture if it has the given size, otherwise a new one is
+    /// created
+    fn fit<'a>(
+        texture: &'a mut Option<Self>,
+        device: &Device,
+        command_queue: &CommandQueue,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> &'a TextureView {
+        command_queue
+            .memory_tracker()
+            .track_texture(width, height, format);
+
+        let output_texture = match texture.take() {
+            Some(output_texture)
+                if output_texture.width == width && output_texture.height == height =>
+            {
+                output_texture
+            }
+            _ => Self::new(device, width, height, format),
+        };
+
+        &texture.insert(output_texture).view
+    }
+}
+
+/// Presents the visualizer on HDR displays, so the highlights of the
+/// raytracer are not clipped. HDR is enabled per monitor, as the window may
+/// be moved between an HDR and an SDR display during a show. The scene is
+/// rendered into a linear texture and the UI into an overlay, which are
+/// combined by the output transform. It scales the scene to the paper white
+/// and rolls the highlights off towards the peak brightness of the display.
+pub struct HdrOutput {
+    monitors: Vec<String>,
+    monitor: Option<String>,
+    supported: bool,
+    paper_white: f32,
+    peak_brightness: f32,
+    pipeline: Option<HdrOutputPipeline>,
+    scene_texture: Option<OutputTexture>,
+    overlay_texture: Option<OutputTexture>,
+}
+
+impl HdrOutput {
+    /// Returns the names of the monitors HDR is enabled for
+    pub fn monitors(&self) -> &[String] {
+        &self.monitors
+    }
+
+    /// Sets the names of the monitors HDR is enabled for
+    pub fn set_monitors(&mut self, monitors: Vec<String>) -> &mut Self {
+        self.monitors = monitors;
+        self.release_textures();
+        self
+    }
+
+    /// Sets the names of the monitors HDR is enabled for
+    pub fn with_monitors(mut self, monitors: Vec<String>) -> Self {
+        self.set_monitors(monitors);
+        self
+    }
+
+    /// Returns the name of the monitor the window is on
+    pub fn monitor(&self) -> Option<&str> {
+        self.monitor.as_deref()
+    }
+
+    /// Sets the name of the monitor the window is on. This is updated by the
+    /// window every frame.
+    pub fn set_monitor(&mut self, monitor: Option<String>) -> &mut Self {
+        if self.monitor != monitor {
+            self.monitor = monitor;
+            self.release_textures();
+        }
+        self
+    }
+
+    /// Returns if the surface can present HDR colors on the active backend
+    pub fn supported(&self) -> bool {
+        self.supported
+    }
+
+    /// Returns if HDR is enabled for the monitor the window is on
+    pub fn is_enabled(&self) -> bool {
+        self.monitor
+            .as_ref()
+            .map_or(false, |monitor| self.monitors.contains(monitor))
+    }
+
+    /// Returns the brightness of white in nits
+    pub fn paper_white(&self) -> f32 {
+        self.paper_white
+    }
+
+    /// Sets the brightness of white in nits. The UI and the SDR range of the
+    /// scene are shown with this brightness.
+    pub fn set_paper_white(&mut self, paper_white: f32) -> &mut Self {
+        self.paper_white = paper_white;
+        self
+    }
+
+    /// Sets the brightness of white in nits
+    pub fn with_paper_white(mut self, paper_white: f32) -> Self {
+        self.set_paper_white(paper_white);
+        self
+    }
+
+    /// Returns the peak brightness of the display in nits
+    pub fn peak_brightness(&self) -> f32 {
+        self.peak_brightness
+    }
+
+    /// Sets the peak brightness of the display in nits. The highlights of
+    /// the scene are rolled off towards it.
+    pub fn set_peak_brightness(&mut self, peak_brightness: f32) -> &mut Self {
+        self.peak_brightness = peak_brightness;
+        self
+    }
+
+    /// Sets the peak brightness of the display in nits
+    pub fn with_peak_brightness(mut self, peak_brightness: f32) -> Self {
+        self.set_peak_brightness(peak_brightness);
+        self
+    }
+
+    /// Configures the surface for the monitor the window is on
+    pub fn configure(&mut self, target: &mut SurfaceTarget) {
+        self.supported = target.hdr_supported();
+
+        target.set_hdr(self.is_enabled());
+    }
+
+    // the intermediate textures are only kept while they are used
+    fn release_textures(&mut self) {
+        if !self.is_enabled() {
+            self.scene_texture = None;
+            self.overlay_texture = None;
+        }
+    }
+
+    /// Returns the texture the scene has to be rendered into with
+    /// [`HDR_FORMAT`] before it is transformed by [`HdrOutput::render`]
+    pub fn scene_texture(
+        &mut self,
+        device: &Device,
+        command_queue: &CommandQueue,
+        width: u32,
+        height: u32,
+    ) -> &TextureView {
+        OutputTexture::fit(
+            &mut self.scene_texture,
+            device,
+            command_queue,
+            width,
+            height,
+            HDR_FORMAT,
+        )
+    }
+
+    /// Returns the cleared texture the UI has to be drawn into before it is
+    /// combined with the scene by [`HdrOutput::render`]. Returns the format
+    /// of the texture as well.
+    pub fn overlay_texture(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        width: u32,
+        height: u32,
+    ) -> (&TextureView, TextureFormat) {
+        let view = OutputTexture::fit(
+            &mut self.overlay_texture,
+            device,
+            command_queue,
+            width,
+            height,
+            OVERLAY_FORMAT,
+        );
+
+        command_queue
+            .command_encoder(device)
+            .begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::TRANSPARENT),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+        (view, OVERLAY_FORMAT)
+    }
+
+    /// Transforms the scene texture and the overlay into the target texture
+    pub fn render(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        target_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let (scene_texture, overlay_texture) = match (&self.scene_texture, &self.overlay_texture) {
+            (Some(scene_texture), Some(overlay_texture)) => (scene_texture, overlay_texture),
+            _ => return,
+        };
+
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| HdrOutputPipeline::new(device, target_format));
+
+        if pipeline.1 != target_format {
+            *pipeline = HdrOutputPipeline::new(device, target_format);
+        }
+
+        let args = HdrOutputArgs {
+            paper_white: self.paper_white / SCRGB_WHITE,
+            peak_brightness: self.peak_brightness.max(self.paper_white) / SCRGB_WHITE,
+        };
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &args,
+        });
+        command_queue
+            .memory_tracker()
+            .track_buffer(std::mem::size_of_val(&args) as u64);
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[
+                args_buffer.bind_group_entry(0).unwrap(),
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&scene_texture.view),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureView(&overlay_texture.view),
+                },
+            ],
+            layout: &pipeline.0.get_bind_group_layout(0),
+        });
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+            label: None,
+            color_attachments: &[RenderPassColorAttachment {
+                view: target_texture,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&pipeline.0);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+
+        render_pass.draw(0..4, 0..1);
+    }
+}
+
+impl Default for HdrOutput {
+    fn default() -> Self {
+        Self {
+            monitors: Vec::new(),
+            monitor: None,
+            supported: false,
+            paper_white: HDR_OUTPUT_PAPER_WHITE,
+            peak_brightness: HDR_OUTPUT_PEAK_BRIGHTNESS,
+            pipeline: None,
+            scene_texture: None,
+            overlay_texture: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`HdrOutput`] module
+#[derive(Clone)]
+pub struct HdrOutputSettings {
+    /// The names of the monitors HDR is enabled for
+    pub monitors: Vec<String>,
+    /// The name of the monitor the window is on. This is only informational
+    /// and ignored when applying the settings.
+    pub monitor: Option<String>,
+    /// If the surface can present HDR colors on the active backend. This is
+    /// only informational and ignored when applying the settings.
+    pub supported: bool,
+    /// The brightness of white in nits
+    pub paper_white: f32,
+    /// The peak brightness of the display in nits
+    pub peak_brightness: f32,
+}
+
+impl Default for HdrOutputSettings {
+    fn default() -> Self {
+        Self {
+            monitors: Vec::new(),
+            monitor: None,
+            supported: false,
+            paper_white: HDR_OUTPUT_PAPER_WHITE,
+            peak_brightness: HDR_OUTPUT_PEAK_BRIGHTNESS,
+        }
+    }
+}
+
+impl Module for HdrOutput {
+    type Settings = HdrOutputSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_monitors(settings.monitors)
+            .set_paper_white(settings.paper_white)
+            .set_peak_brightness(settings.peak_brightness)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        HdrOutputSettings {
+            monitors: self.monitors().to_vec(),
+            monitor: self.monitor().map(str::to_owned),
+            supported: self.supported(),
+            paper_white: self.paper_white(),
+            peak_brightness: self.peak_brightness(),
+        }
+    }
+}
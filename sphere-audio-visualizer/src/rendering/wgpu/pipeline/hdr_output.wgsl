@@ -0,0 +1,44 @@
+struct Args {
+    paper_white: f32;
+    peak_brightness: f32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+[[group(0), binding(1)]]
+var scene: texture_2d<f32>;
+
+[[group(0), binding(2)]]
+var overlay: texture_2d<f32>;
+
+// colors up to the knee are kept, brighter colors approach the peak
+// brightness smoothly instead of being clipped
+fn roll_off(color: vec3<f32>) -> vec3<f32> {
+    let knee = min(args.paper_white, args.peak_brightness * 0.5);
+    let range = args.peak_brightness - knee;
+    let compressed = knee + range * (1.0 - exp((knee - color) / range));
+
+    return select(color, compressed, color > vec3<f32>(knee));
+}
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    let scene_color = max(textureLoad(scene, vec2<i32>(position.xy), 0).rgb, vec3<f32>(0.0));
+    let overlay_color = textureLoad(overlay, vec2<i32>(position.xy), 0);
+
+    // scRGB maps 1.0 to 80 nits, the brightnesses are passed in this unit.
+    // The overlay is premultiplied and shown at paper white like the scene.
+    let color = roll_off(scene_color * args.paper_white) * (1.0 - overlay_color.a)
+        + overlay_color.rgb * args.paper_white;
+
+    return vec4<f32>(color, 1.0);
+}
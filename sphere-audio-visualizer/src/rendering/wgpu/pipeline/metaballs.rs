@@ -1,26 +1,33 @@
-use sphere_audio_visualizer_core::metaballs::MetaballsArgs;
+use std::sync::Arc;
+
+use sphere_audio_visualizer_core::metaballs::{Metaball, MetaballsArgs};
 use wgpu::{
-    include_wgsl, util::make_spirv_raw, BindGroupDescriptor, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingType, BufferBindingType, BufferUsages, Color, ColorTargetState,
-    ColorWrites, Device, FragmentState, LoadOp, Operations, PipelineLayoutDescriptor, PolygonMode,
-    PrimitiveState, PrimitiveTopology, RenderPassColorAttachment, RenderPassDescriptor,
-    RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptorSpirV, ShaderStages,
-    TextureFormat, TextureView, VertexState,
+    include_wgsl, util::make_spirv_raw, BindGroupDescriptor, BindGroupEntry,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferBindingType, BufferUsages, Color, ColorTargetState, ColorWrites, ComputePassDescriptor,
+    ComputePipeline, ComputePipelineDescriptor, Device, Extent3d, FragmentState, LoadOp,
+    Operations, PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    ShaderModuleDescriptorSpirV, ShaderStages, Texture, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages, TextureView, TextureViewDescriptor, VertexState,
 };
 
 use crate::{
     module::Module,
     rendering::{
-        scene::MetaballsScene,
+        scene::{MetaballsScene, Viewport},
         wgpu::{
             utils::{
-                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+                CommandQueue, DynamicBuffer, SeedStream, Uploader, SEED,
+                {TypedBuffer, TypedBufferDeviceExt, TypedBufferInitDescriptor},
             },
-            Pipeline, ShadingLanguage, SHADER,
+            Pipeline, ShaderValidation, ShadingLanguage, WGPURenderer, SHADER,
         },
     },
 };
 
+use super::{draw_fullscreen, set_viewport, ParityCheck, ParityReport};
+
 struct MetaballsWGSLPipeline(RenderPipeline, TextureFormat);
 
 impl MetaballsWGSLPipeline {
@@ -58,10 +65,10 @@ impl MetaballsWGSLPipeline {
     }
 }
 
-struct MetaballsRustPipeline(RenderPipeline, TextureFormat);
+pub(super) struct MetaballsRustPipeline(RenderPipeline, TextureFormat);
 
 impl MetaballsRustPipeline {
-    fn new(device: &Device, target_format: TextureFormat) -> Self {
+    pub(super) fn new(device: &Device, target_format: TextureFormat) -> Self {
         let shader_module = unsafe {
             device.create_shader_module_spirv(&ShaderModuleDescriptorSpirV {
                 label: None,
@@ -132,11 +139,139 @@ impl MetaballsRustPipeline {
     }
 }
 
+const TILE_SIZE: u32 = 16;
+
+/// Defines the default contribution below which a metaball is culled from a
+/// tile
+pub const METABALLS_CULL_THRESHOLD: f32 = 0.001;
+
+/// Defines the default strength with which the loudness raises the halo
+/// intensity
+pub const METABALLS_PULSE: f32 = 0.5;
+
+#[repr(C, align(16))]
+struct MetaballsTiledArgs {
+    args: MetaballsArgs,
+    cull_threshold: f32,
+}
+
+struct MetaballsTiledTexture {
+    _texture: Texture,
+    texture_view: TextureView,
+    width: u32,
+    height: u32,
+}
+
+struct MetaballsTiledPipeline {
+    compute_pipeline: ComputePipeline,
+    blit_pipeline: RenderPipeline,
+    target_format: TextureFormat,
+    texture: Option<MetaballsTiledTexture>,
+}
+
+impl MetaballsTiledPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("metaballs_tiled.wgsl"));
+
+        let compute_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("sphere-visualizer-metaballs-tiled-pipeline"),
+            layout: None,
+            module: &shader_module,
+            entry_point: "tile",
+        });
+
+        let blit_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-metaballs-blit-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self {
+            compute_pipeline,
+            blit_pipeline,
+            target_format,
+            texture: None,
+        }
+    }
+
+    fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        if !matches!(&self.texture, Some(texture) if texture.width == width && texture.height == height)
+        {
+            let texture = device.create_texture(&TextureDescriptor {
+                label: None,
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba16Float,
+                usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+            });
+
+            let texture_view = texture.create_view(&TextureViewDescriptor::default());
+
+            self.texture = Some(MetaballsTiledTexture {
+                _texture: texture,
+                texture_view,
+                width,
+                height,
+            });
+        }
+    }
+}
+
+/// Specifies how the metaballs are evaluated
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetaballsVariant {
+    /// Evaluates every metaball for every pixel in a fragment shader
+    Fragment,
+    /// Bins the metaballs into screen tiles in a compute shader and only
+    /// evaluates the metaballs relevant for a tile. Always uses WGSL.
+    TiledCompute,
+}
+
 /// The pipeline module for rendering metaballs scenes
 pub struct Metaballs {
     implementation: ShadingLanguage,
+    variant: MetaballsVariant,
+    cull_threshold: f32,
+    pulse: f32,
+    loudness: f32,
+    seed_stream: SeedStream,
+    uploader: Uploader,
+    metaballs_buffer: DynamicBuffer,
     rust_pipeline: Option<MetaballsRustPipeline>,
     wgsl_pipeline: Option<MetaballsWGSLPipeline>,
+    tiled_pipeline: Option<MetaballsTiledPipeline>,
+    shader_validation: ShaderValidation,
+    parity: bool,
+    parity_check: ParityCheck,
+    parity_report: Option<ParityReport>,
 }
 
 impl Metaballs {
@@ -144,8 +279,7 @@ impl Metaballs {
     pub fn from_implementation(implementation: ShadingLanguage) -> Self {
         Self {
             implementation,
-            rust_pipeline: None,
-            wgsl_pipeline: None,
+            ..Default::default()
         }
     }
 
@@ -157,27 +291,176 @@ impl Metaballs {
 
     /// Sets the Shading Language that should be used going forward.
     pub fn set_implementation(&mut self, implementation: ShadingLanguage) -> &mut Self {
-        self.implementation = implementation;
+        self.implementation = match implementation {
+            ShadingLanguage::Rust if !self.shader_validation.passed() => ShadingLanguage::WGSL,
+            implementation => implementation,
+        };
         self
     }
 
+    /// Returns if the precompiled rust-gpu shader is supported by the active
+    /// adapter. If not the WGSL implementation is used instead.
+    pub fn rust_supported(&self) -> bool {
+        self.shader_validation.passed()
+    }
+
+    /// Returns the outcome of validating the precompiled rust-gpu shader
+    pub fn shader_validation(&self) -> &ShaderValidation {
+        &self.shader_validation
+    }
+
     /// Gets the currently used Shading Language.
     pub fn implementation(&self) -> ShadingLanguage {
         self.implementation.clone()
     }
+
+    /// Sets the [`MetaballsVariant`] that should be used going forward.
+    pub fn with_variant(mut self, variant: MetaballsVariant) -> Self {
+        self.set_variant(variant);
+        self
+    }
+
+    /// Sets the [`MetaballsVariant`] that should be used going forward.
+    pub fn set_variant(&mut self, variant: MetaballsVariant) -> &mut Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Gets the currently used [`MetaballsVariant`].
+    pub fn variant(&self) -> MetaballsVariant {
+        self.variant
+    }
+
+    /// Sets the contribution below which a metaball is culled from a tile.
+    /// Only used by [`MetaballsVariant::TiledCompute`].
+    pub fn with_cull_threshold(mut self, cull_threshold: f32) -> Self {
+        self.set_cull_threshold(cull_threshold);
+        self
+    }
+
+    /// Sets the contribution below which a metaball is culled from a tile.
+    /// Only used by [`MetaballsVariant::TiledCompute`].
+    pub fn set_cull_threshold(&mut self, cull_threshold: f32) -> &mut Self {
+        self.cull_threshold = cull_threshold.max(0.0);
+        self
+    }
+
+    /// Gets the contribution below which a metaball is culled from a tile.
+    pub fn cull_threshold(&self) -> f32 {
+        self.cull_threshold
+    }
+
+    /// Sets the strength with which the loudness raises the halo intensity.
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the halo intensity.
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse.max(0.0);
+        self
+    }
+
+    /// Gets the strength with which the loudness raises the halo intensity.
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the base seed of stochastic effects. Changing it restarts the
+    /// stream of frame seeds.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.set_seed(seed);
+        self
+    }
+
+    /// Sets the base seed of stochastic effects. Changing it restarts the
+    /// stream of frame seeds.
+    pub fn set_seed(&mut self, seed: u32) -> &mut Self {
+        self.seed_stream.set_seed(seed);
+        self
+    }
+
+    /// Gets the base seed of stochastic effects.
+    pub fn seed(&self) -> u32 {
+        self.seed_stream.seed()
+    }
+
+    /// Sets if every frame is additionally rendered with both shading
+    /// languages to compare them. This stalls every frame.
+    pub fn with_parity(mut self, parity: bool) -> Self {
+        self.set_parity(parity);
+        self
+    }
+
+    /// Sets if every frame is additionally rendered with both shading
+    /// languages to compare them. This stalls every frame.
+    pub fn set_parity(&mut self, parity: bool) -> &mut Self {
+        self.parity = parity;
+        if !parity {
+            self.parity_report = None;
+        }
+        self
+    }
+
+    /// Gets if every frame is additionally rendered with both shading
+    /// languages to compare them.
+    pub fn parity(&self) -> bool {
+        self.parity
+    }
+
+    /// Returns the difference between the shading languages in the last
+    /// rendered frame. Only available while the parity mode is enabled, the
+    /// rust-gpu shader is supported and the fragment variant is used.
+    pub fn parity_report(&self) -> Option<ParityReport> {
+        self.parity_report
+    }
 }
 
 /// Stores the settings of the [`Metaballs`] pipeline module
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct MetaballsSettings {
     /// The used [`ShadingLanguage`]
     pub shading_language: ShadingLanguage,
+    /// The outcome of validating the [`ShadingLanguage::Rust`]
+    /// implementation for the active adapter. This is only informational and
+    /// ignored when applying the settings.
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub shader_validation: ShaderValidation,
+    /// The used [`MetaballsVariant`]
+    pub variant: MetaballsVariant,
+    /// The contribution below which a metaball is culled from a tile
+    pub cull_threshold: f32,
+    /// The strength with which the loudness raises the halo intensity
+    pub pulse: f32,
+    /// The base seed of stochastic effects
+    pub seed: u32,
+    /// Whether every frame is additionally rendered with both shading
+    /// languages to compare them
+    pub parity: bool,
+    /// The difference between the shading languages in the last rendered
+    /// frame. This is only informational and ignored when applying the
+    /// settings.
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub parity_report: Option<ParityReport>,
 }
 
 impl Default for MetaballsSettings {
     fn default() -> Self {
         Self {
             shading_language: ShadingLanguage::Rust,
+            shader_validation: ShaderValidation::Passed,
+            variant: MetaballsVariant::Fragment,
+            cull_threshold: METABALLS_CULL_THRESHOLD,
+            pulse: METABALLS_PULSE,
+            seed: SEED,
+            parity: false,
+            parity_report: None,
         }
     }
 }
@@ -187,11 +470,23 @@ impl Module for Metaballs {
 
     fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
         self.set_implementation(settings.shading_language)
+            .set_variant(settings.variant)
+            .set_cull_threshold(settings.cull_threshold)
+            .set_pulse(settings.pulse)
+            .set_seed(settings.seed)
+            .set_parity(settings.parity)
     }
 
     fn settings(&self) -> Self::Settings {
         MetaballsSettings {
             shading_language: self.implementation(),
+            shader_validation: self.shader_validation().clone(),
+            variant: self.variant(),
+            cull_threshold: self.cull_threshold(),
+            pulse: self.pulse(),
+            seed: self.seed(),
+            parity: self.parity(),
+            parity_report: self.parity_report(),
         }
     }
 }
@@ -200,13 +495,34 @@ impl Default for Metaballs {
     fn default() -> Self {
         Self {
             implementation: ShadingLanguage::WGSL,
+            variant: MetaballsVariant::Fragment,
+            cull_threshold: METABALLS_CULL_THRESHOLD,
+            pulse: METABALLS_PULSE,
+            loudness: 0.0,
+            seed_stream: SeedStream::default(),
+            uploader: Uploader::default(),
+            metaballs_buffer: DynamicBuffer::new(Some("metaballs"), BufferUsages::STORAGE),
             rust_pipeline: None,
             wgsl_pipeline: None,
+            tiled_pipeline: None,
+            shader_validation: ShaderValidation::Passed,
+            parity: false,
+            parity_check: ParityCheck::default(),
+            parity_report: None,
         }
     }
 }
 
 impl Pipeline<MetaballsScene> for Metaballs {
+    fn prepare(&mut self, renderer: &WGPURenderer) {
+        self.shader_validation = renderer.shader_validation().clone();
+        self.set_implementation(self.implementation());
+    }
+
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
     fn render(
         &mut self,
         scene: MetaballsScene,
@@ -215,62 +531,231 @@ impl Pipeline<MetaballsScene> for Metaballs {
         output_format: TextureFormat,
         output_texture: &TextureView,
     ) {
-        let pipeline = match self.implementation {
-            ShadingLanguage::Rust => {
-                let rust_pipeline = self
-                    .rust_pipeline
-                    .get_or_insert_with(|| MetaballsRustPipeline::new(device, output_format));
+        let memory_tracker = command_queue.memory_tracker();
+
+        self.uploader.recall(device);
+
+        let metaballs_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.metaballs_buffer,
+            memory_tracker.fit("Metaballs", scene.metaballs.as_slice()),
+        );
 
-                if rust_pipeline.1 != output_format {
-                    *rust_pipeline = MetaballsRustPipeline::new(device, output_format);
-                }
+        self.uploader.finish();
 
-                &rust_pipeline.0
+        let (frame, seed) = self.seed_stream.next_frame();
+
+        let args = MetaballsArgs {
+            color: scene.color,
+            size: scene.viewport.size,
+            zoom: scene.zoom,
+            offset: scene.viewport.offset,
+            loudness: self.loudness * self.pulse,
+            frame,
+            seed,
+        };
+
+        match self.variant {
+            MetaballsVariant::Fragment => {
+                let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                    label: None,
+                    usage: BufferUsages::STORAGE,
+                    value: &args,
+                });
+                memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+                self.render_fragment(
+                    device,
+                    command_queue,
+                    output_format,
+                    output_texture,
+                    &args_buffer,
+                    &metaballs_buffer,
+                    &scene.viewport,
+                );
             }
-            ShadingLanguage::WGSL => {
-                let wgsl_pipeline = self
-                    .wgsl_pipeline
-                    .get_or_insert_with(|| MetaballsWGSLPipeline::new(device, output_format));
+            MetaballsVariant::TiledCompute => {
+                // the tiled variant only exists in WGSL
+                self.parity_report = None;
+
+                self.render_tiled(
+                    device,
+                    command_queue,
+                    output_format,
+                    output_texture,
+                    args,
+                    &metaballs_buffer,
+                    &scene.viewport,
+                );
+            }
+        }
+    }
+}
 
-                if wgsl_pipeline.1 != output_format {
-                    *wgsl_pipeline = MetaballsWGSLPipeline::new(device, output_format);
-                }
+impl Metaballs {
+    fn render_fragment(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        output_texture: &TextureView,
+        args_buffer: &TypedBuffer<Buffer, MetaballsArgs>,
+        metaballs_buffer: &TypedBuffer<Arc<Buffer>, [Metaball]>,
+        viewport: &Viewport,
+    ) {
+        let parity =
+            self.parity && self.shader_validation.passed() && ParityCheck::supports(output_format);
 
-                &wgsl_pipeline.0
+        if parity || self.implementation == ShadingLanguage::Rust {
+            let rust_pipeline = self
+                .rust_pipeline
+                .get_or_insert_with(|| MetaballsRustPipeline::new(device, output_format));
+
+            if rust_pipeline.1 != output_format {
+                *rust_pipeline = MetaballsRustPipeline::new(device, output_format);
             }
+        }
+
+        if parity || self.implementation == ShadingLanguage::WGSL {
+            let wgsl_pipeline = self
+                .wgsl_pipeline
+                .get_or_insert_with(|| MetaballsWGSLPipeline::new(device, output_format));
+
+            if wgsl_pipeline.1 != output_format {
+                *wgsl_pipeline = MetaballsWGSLPipeline::new(device, output_format);
+            }
+        }
+
+        let pipeline = match self.implementation {
+            ShadingLanguage::Rust => &self.rust_pipeline.as_ref().unwrap().0,
+            ShadingLanguage::WGSL => &self.wgsl_pipeline.as_ref().unwrap().0,
         };
 
-        let metaballs_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
-            label: None,
-            usage: BufferUsages::STORAGE,
-            value: scene.metaballs.as_slice(),
-        });
+        // bind groups of pipelines with derived layouts are not interchangeable
+        let bind_group = |pipeline: &RenderPipeline| {
+            device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                entries: &[
+                    args_buffer.bind_group_entry(0).unwrap(),
+                    metaballs_buffer.bind_group_entry(1).unwrap(),
+                ],
+                layout: &pipeline.get_bind_group_layout(0),
+            })
+        };
 
-        let args = MetaballsArgs {
-            color: scene.color,
-            size: scene.size,
-            zoom: scene.zoom,
+        draw_fullscreen(
+            command_queue.command_encoder(device),
+            pipeline,
+            &bind_group(pipeline),
+            output_texture,
+            viewport,
+        );
+
+        self.parity_report = if parity {
+            let pipelines = [
+                &self.rust_pipeline.as_ref().unwrap().0,
+                &self.wgsl_pipeline.as_ref().unwrap().0,
+            ];
+
+            let extent = (viewport.offset + viewport.size).ceil();
+            let targets =
+                self.parity_check
+                    .begin(device, output_format, extent.x as u32, extent.y as u32);
+
+            for (pipeline, target) in pipelines.into_iter().zip(&targets) {
+                draw_fullscreen(
+                    command_queue.command_encoder(device),
+                    pipeline,
+                    &bind_group(pipeline),
+                    target,
+                    viewport,
+                );
+            }
+
+            Some(self.parity_check.finish(device, command_queue))
+        } else {
+            None
+        };
+    }
+
+    fn render_tiled(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        output_texture: &TextureView,
+        args: MetaballsArgs,
+        metaballs_buffer: &TypedBuffer<Arc<Buffer>, [Metaball]>,
+        viewport: &Viewport,
+    ) {
+        let width = args.size.x.max(1.0) as u32;
+        let height = args.size.y.max(1.0) as u32;
+
+        let tiled_args = MetaballsTiledArgs {
+            args,
+            cull_threshold: self.cull_threshold,
         };
 
-        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+        let tiled_args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
             label: None,
             usage: BufferUsages::STORAGE,
-            value: &args,
+            value: &tiled_args,
         });
 
-        let layout = pipeline.get_bind_group_layout(0);
+        let memory_tracker = command_queue.memory_tracker();
+        memory_tracker.track_buffer(std::mem::size_of_val(&tiled_args) as u64);
+        memory_tracker.track_texture(width, height, TextureFormat::Rgba16Float);
+
+        let tiled_pipeline = self
+            .tiled_pipeline
+            .get_or_insert_with(|| MetaballsTiledPipeline::new(device, output_format));
 
-        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        if tiled_pipeline.target_format != output_format {
+            *tiled_pipeline = MetaballsTiledPipeline::new(device, output_format);
+        }
+
+        tiled_pipeline.resize(device, width, height);
+
+        let texture_view = &tiled_pipeline.texture.as_ref().unwrap().texture_view;
+
+        let compute_bind_group = device.create_bind_group(&BindGroupDescriptor {
             label: None,
             entries: &[
-                args_buffer.bind_group_entry(0).unwrap(),
+                tiled_args_buffer.bind_group_entry(0).unwrap(),
                 metaballs_buffer.bind_group_entry(1).unwrap(),
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::TextureView(texture_view),
+                },
             ],
-            layout: &layout,
+            layout: &tiled_pipeline.compute_pipeline.get_bind_group_layout(0),
+        });
+
+        let blit_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[
+                tiled_args_buffer.bind_group_entry(0).unwrap(),
+                BindGroupEntry {
+                    binding: 3,
+                    resource: BindingResource::TextureView(texture_view),
+                },
+            ],
+            layout: &tiled_pipeline.blit_pipeline.get_bind_group_layout(0),
         });
 
         let command_encoder = command_queue.command_encoder(device);
 
+        {
+            let mut compute_pass =
+                command_encoder.begin_compute_pass(&ComputePassDescriptor { label: None });
+
+            compute_pass.set_pipeline(&tiled_pipeline.compute_pipeline);
+            compute_pass.set_bind_group(0, &compute_bind_group, &[]);
+
+            compute_pass.dispatch(width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE), 1);
+        }
+
         {
             let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
                 label: None,
@@ -285,8 +770,9 @@ impl Pipeline<MetaballsScene> for Metaballs {
                 depth_stencil_attachment: None,
             });
 
-            render_pass.set_pipeline(&pipeline);
-            render_pass.set_bind_group(0, &bind_group, &[]);
+            set_viewport(&mut render_pass, viewport);
+            render_pass.set_pipeline(&tiled_pipeline.blit_pipeline);
+            render_pass.set_bind_group(0, &blit_bind_group, &[]);
 
             render_pass.draw(0..4, 0..1);
         }
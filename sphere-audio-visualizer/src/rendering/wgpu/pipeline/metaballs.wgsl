@@ -6,12 +6,30 @@ struct Sphere {
 struct Args { 
     color: vec3<f32>;
     size: vec2<f32>;
-    zoom: f32;
+    zoom: vec2<f32>;
+    offset: vec2<f32>;
+    loudness: f32;
+    frame: u32;
+    seed: u32;
 };
 
 [[group(0), binding(0)]]
 var<storage, read> args: Args;
 
+// mirrors sphere_audio_visualizer_core::utils::random
+fn hash(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+
+    return (word >> 22u) ^ word;
+}
+
+fn random(position: vec2<f32>, dimension: u32) -> f32 {
+    let value = hash(args.seed ^ hash(u32(position.x) ^ hash(u32(position.y) ^ hash(dimension))));
+
+    return f32(value >> 8u) / 16777216.0;
+}
+
 struct Spheres {
     spheres: array<Sphere>;
 };
@@ -33,7 +51,7 @@ fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]]
 fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
     var value = 0.0;
 
-    let position = (position.xy / args.size * 2.0 - 1.0) * args.zoom;
+    let position = ((position.xy - args.offset) / args.size * 2.0 - 1.0) * args.zoom;
 
     let count = arrayLength(&spheres.spheres);
     for(var i: u32 = 0u; i < count; i = i + 1u) {
@@ -43,5 +61,5 @@ fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f
         value = value + inverseSqrt(dot(oc, oc)) * radius * 0.05;
     }
 
-    return select(vec4<f32>(args.color * value, 1.0), vec4<f32>(1.0, 1.0, 1.0, 1.0), value >= 0.75);
+    return select(vec4<f32>(args.color * value * (1.0 + args.loudness), 1.0), vec4<f32>(1.0, 1.0, 1.0, 1.0), value >= 0.75);
 }
\ No newline at end of file
@@ -0,0 +1,467 @@
+use sphere_audio_visualizer_core::{
+    glam::Vec3A,
+    metaballs_3d::{Metaball3D, Metaballs3DArgs},
+};
+use wgpu::{
+    include_wgsl, util::make_spirv_raw, BindGroupDescriptor, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingType, BufferBindingType, BufferUsages, ColorTargetState,
+    ColorWrites, Device, FragmentState, PipelineLayoutDescriptor, PolygonMode, PrimitiveState,
+    PrimitiveTopology, RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptorSpirV,
+    ShaderStages, TextureFormat, TextureView, VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::{
+        scene::{Metaballs3DScene, Viewport},
+        wgpu::{
+            utils::{
+                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+            },
+            Pipeline, ShaderValidation, ShadingLanguage, WGPURenderer, HDR_FORMAT, SHADER,
+        },
+    },
+};
+
+use super::draw_fullscreen;
+
+/// Defines the default distance at which a metaball stops contributing to the
+/// field relative to its radius
+pub const METABALLS_3D_INFLUENCE: f32 = 2.0;
+
+/// Defines the default maximum number of marching steps per pixel
+pub const METABALLS_3D_STEPS: u32 = 128;
+
+/// Defines the default step, relative to the distance travelled, below which
+/// a ray hits the surface
+pub const METABALLS_3D_EPSILON: f32 = 0.001;
+
+/// Defines the default strength with which the loudness raises the
+/// brightness of the background
+pub const METABALLS_3D_PULSE: f32 = 0.5;
+
+struct Metaballs3DWGSLPipeline(RenderPipeline, TextureFormat);
+
+impl Metaballs3DWGSLPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("metaballs_3d.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-metaballs-3d-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+pub(super) struct Metaballs3DRustPipeline(RenderPipeline, TextureFormat);
+
+impl Metaballs3DRustPipeline {
+    pub(super) fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = unsafe {
+            device.create_shader_module_spirv(&ShaderModuleDescriptorSpirV {
+                label: None,
+                source: make_spirv_raw(SHADER),
+            })
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: None,
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "metaballs_3d_vs",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "metaballs_3d_fs",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: Some(&pipeline_layout),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+/// The pipeline module for rendering 3D metaballs scenes by raymarching the
+/// field of the metaballs. Unlike the [`SdfTracer`](super::SdfTracer), which
+/// blends the distances of the spheres, the metaballs add up, so they swell
+/// where they meet.
+pub struct Metaballs3D {
+    implementation: ShadingLanguage,
+    influence: f32,
+    steps: u32,
+    epsilon: f32,
+    pulse: f32,
+    loudness: f32,
+    rust_pipeline: Option<Metaballs3DRustPipeline>,
+    wgsl_pipeline: Option<Metaballs3DWGSLPipeline>,
+    shader_validation: ShaderValidation,
+}
+
+impl Metaballs3D {
+    /// Creates a new instance using the specified Shading Language
+    pub fn from_implementation(implementation: ShadingLanguage) -> Self {
+        Self {
+            implementation,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the Shading Language that should be used going forward.
+    pub fn with_implementation(mut self, implementation: ShadingLanguage) -> Self {
+        self.set_implementation(implementation);
+        self
+    }
+
+    /// Sets the Shading Language that should be used going forward.
+    pub fn set_implementation(&mut self, implementation: ShadingLanguage) -> &mut Self {
+        self.implementation = match implementation {
+            ShadingLanguage::Rust if !self.shader_validation.passed() => ShadingLanguage::WGSL,
+            implementation => implementation,
+        };
+        self
+    }
+
+    /// Returns if the precompiled rust-gpu shader is supported by the active
+    /// adapter. If not the WGSL implementation is used instead.
+    pub fn rust_supported(&self) -> bool {
+        self.shader_validation.passed()
+    }
+
+    /// Returns the outcome of validating the precompiled rust-gpu shader
+    pub fn shader_validation(&self) -> &ShaderValidation {
+        &self.shader_validation
+    }
+
+    /// Gets the currently used Shading Language.
+    pub fn implementation(&self) -> ShadingLanguage {
+        self.implementation.clone()
+    }
+
+    /// Returns the distance at which a metaball stops contributing to the
+    /// field relative to its radius
+    pub fn influence(&self) -> f32 {
+        self.influence
+    }
+
+    /// Sets the distance at which a metaball stops contributing to the field
+    /// relative to its radius. Larger values melt the metaballs from further
+    /// away. A metaball standing alone keeps its radius.
+    pub fn set_influence(&mut self, influence: f32) -> &mut Self {
+        self.influence = influence.max(1.1);
+        self
+    }
+
+    /// Sets the distance at which a metaball stops contributing to the field
+    pub fn with_influence(mut self, influence: f32) -> Self {
+        self.set_influence(influence);
+        self
+    }
+
+    /// Returns the maximum number of marching steps per pixel
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Sets the maximum number of marching steps per pixel. Rays running out
+    /// of steps show the background.
+    pub fn set_steps(&mut self, steps: u32) -> &mut Self {
+        self.steps = steps.max(1);
+        self
+    }
+
+    /// Sets the maximum number of marching steps per pixel
+    pub fn with_steps(mut self, steps: u32) -> Self {
+        self.set_steps(steps);
+        self
+    }
+
+    /// Returns the step, relative to the distance travelled, below which a
+    /// ray hits the surface
+    pub fn epsilon(&self) -> f32 {
+        self.epsilon
+    }
+
+    /// Sets the step, relative to the distance travelled, below which a ray
+    /// hits the surface. Larger values need fewer steps, but swell the
+    /// surface.
+    pub fn set_epsilon(&mut self, epsilon: f32) -> &mut Self {
+        self.epsilon = epsilon.max(f32::EPSILON);
+        self
+    }
+
+    /// Sets the step below which a ray hits the surface
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.set_epsilon(epsilon);
+        self
+    }
+
+    /// Returns the strength with which the loudness raises the brightness
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse.max(0.0);
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+}
+
+impl Default for Metaballs3D {
+    fn default() -> Self {
+        Self {
+            implementation: ShadingLanguage::WGSL,
+            influence: METABALLS_3D_INFLUENCE,
+            steps: METABALLS_3D_STEPS,
+            epsilon: METABALLS_3D_EPSILON,
+            pulse: METABALLS_3D_PULSE,
+            loudness: 0.0,
+            rust_pipeline: None,
+            wgsl_pipeline: None,
+            shader_validation: ShaderValidation::Passed,
+        }
+    }
+}
+
+/// Stores the settings of the [`Metaballs3D`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Metaballs3DSettings {
+    /// The used [`ShadingLanguage`]
+    pub shading_language: ShadingLanguage,
+    /// The outcome of validating the [`ShadingLanguage::Rust`]
+    /// implementation for the active adapter. This is only informational and
+    /// ignored when applying the settings.
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub shader_validation: ShaderValidation,
+    /// The distance at which a metaball stops contributing to the field
+    /// relative to its radius
+    pub influence: f32,
+    /// The maximum number of marching steps per pixel
+    pub steps: u32,
+    /// The step below which a ray hits the surface
+    pub epsilon: f32,
+    /// The strength with which the loudness raises the brightness
+    pub pulse: f32,
+}
+
+impl Default for Metaballs3DSettings {
+    fn default() -> Self {
+        Self {
+            shading_language: ShadingLanguage::Rust,
+            shader_validation: ShaderValidation::Passed,
+            influence: METABALLS_3D_INFLUENCE,
+            steps: METABALLS_3D_STEPS,
+            epsilon: METABALLS_3D_EPSILON,
+            pulse: METABALLS_3D_PULSE,
+        }
+    }
+}
+
+impl Module for Metaballs3D {
+    type Settings = Metaballs3DSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_implementation(settings.shading_language)
+            .set_influence(settings.influence)
+            .set_steps(settings.steps)
+            .set_epsilon(settings.epsilon)
+            .set_pulse(settings.pulse)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        Metaballs3DSettings {
+            shading_language: self.implementation(),
+            shader_validation: self.shader_validation().clone(),
+            influence: self.influence(),
+            steps: self.steps(),
+            epsilon: self.epsilon(),
+            pulse: self.pulse(),
+        }
+    }
+}
+
+impl Pipeline<Metaballs3DScene> for Metaballs3D {
+    fn prepare(&mut self, renderer: &WGPURenderer) {
+        self.shader_validation = renderer.shader_validation().clone();
+        self.set_implementation(self.implementation());
+    }
+
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
+    fn render(
+        &mut self,
+        scene: Metaballs3DScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let pipeline = match self.implementation {
+            ShadingLanguage::Rust => {
+                let rust_pipeline = self
+                    .rust_pipeline
+                    .get_or_insert_with(|| Metaballs3DRustPipeline::new(device, output_format));
+
+                if rust_pipeline.1 != output_format {
+                    *rust_pipeline = Metaballs3DRustPipeline::new(device, output_format);
+                }
+
+                &rust_pipeline.0
+            }
+            ShadingLanguage::WGSL => {
+                let wgsl_pipeline = self
+                    .wgsl_pipeline
+                    .get_or_insert_with(|| Metaballs3DWGSLPipeline::new(device, output_format));
+
+                if wgsl_pipeline.1 != output_format {
+                    *wgsl_pipeline = Metaballs3DWGSLPipeline::new(device, output_format);
+                }
+
+                &wgsl_pipeline.0
+            }
+        };
+
+        let memory_tracker = command_queue.memory_tracker();
+
+        let metaballs = memory_tracker.fit("Metaballs", scene.metaballs.as_slice());
+
+        let args = Metaballs3DArgs {
+            camera: scene.camera.clone(),
+            background: scene.background,
+            light_direction: scene.light_direction,
+            light_color: Vec3A::ONE,
+            influence: self.influence,
+            threshold: Metaballs3DArgs::threshold(self.influence),
+            epsilon: self.epsilon,
+            loudness: self.loudness * self.pulse,
+            steps: self.steps,
+            metaball_count: metaballs.len() as u32,
+            hdr: (output_format == HDR_FORMAT) as u32,
+        };
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &args,
+        });
+        memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+        // empty buffers can't be bound, the placeholder is never sampled as
+        // the count is zero
+        let placeholder_metaball = [Metaball3D::new(Vec3A::ZERO, 0.0, Vec3A::ZERO)];
+
+        let metaballs_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: match metaballs.is_empty() {
+                true => &placeholder_metaball[..],
+                false => metaballs,
+            },
+        });
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[
+                args_buffer.bind_group_entry(0).unwrap(),
+                metaballs_buffer.bind_group_entry(1).unwrap(),
+            ],
+            layout: &pipeline.get_bind_group_layout(0),
+        });
+
+        let viewport = Viewport {
+            offset: scene.camera.screen_offset(),
+            size: scene.camera.screen_size(),
+        };
+
+        draw_fullscreen(
+            command_queue.command_encoder(device),
+            pipeline,
+            &bind_group,
+            target_texture,
+            &viewport,
+        );
+    }
+}
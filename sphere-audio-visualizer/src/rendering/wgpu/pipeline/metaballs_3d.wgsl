@@ -0,0 +1,231 @@
+// mirrors sphere_audio_visualizer_core::metaballs_3d::Metaballs3D
+
+struct Camera {
+    transform: mat4x4<f32>;
+    screen_size: vec2<f32>;
+    screen_offset: vec2<f32>;
+    tan_fov: f32;
+    t_min: f32;
+    t_max: f32;
+    panoramic: u32;
+};
+
+struct Args {
+    camera: Camera;
+    background: vec3<f32>;
+    _pad0: f32;
+    light_direction: vec3<f32>;
+    _pad1: f32;
+    light_color: vec3<f32>;
+    _pad2: f32;
+    influence: f32;
+    threshold: f32;
+    epsilon: f32;
+    loudness: f32;
+    steps: u32;
+    metaball_count: u32;
+    hdr: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+struct Metaball {
+    position: vec3<f32>;
+    _pad0: f32;
+    radius: f32;
+    color: vec3<f32>;
+};
+
+struct Metaballs {
+    metaballs: array<Metaball>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> metaballs: Metaballs;
+
+struct Ray {
+    origin: vec3<f32>;
+    t_min: f32;
+    direction: vec3<f32>;
+    t_max: f32;
+};
+
+fn point_at(ray: Ray, t: f32) -> vec3<f32> {
+    return ray.origin + ray.direction * t;
+}
+
+fn prime_ray(camera: Camera, sample: vec2<f32>) -> Ray {
+    var ray: Ray;
+
+    if(camera.panoramic != 0u) {
+        let uv = (sample - camera.screen_offset) / camera.screen_size;
+
+        // the center of the image looks along the positive z axis
+        let longitude = (uv.x - 0.5) * 6.28318530717959;
+        let latitude = (0.5 - uv.y) * 3.14159265358979;
+
+        ray.direction = vec3<f32>(
+            cos(latitude) * sin(longitude),
+            sin(latitude),
+            cos(latitude) * cos(longitude)
+        );
+    } else {
+        let sensor = ((sample - camera.screen_offset) / camera.screen_size * 2.0 - vec2<f32>(1.0))
+                * camera.tan_fov
+                * vec2<f32>(1.0, -(camera.screen_size.y / camera.screen_size.x));
+
+        ray.direction = normalize(vec3<f32>(sensor, 1.0));
+    }
+
+    ray.origin = (camera.transform * vec4<f32>(0.0, 0.0, 0.0, 1.0)).xyz;
+    ray.direction = normalize((camera.transform * vec4<f32>(ray.direction, 0.0)).xyz);
+    ray.t_min = camera.t_min;
+    ray.t_max = camera.t_max;
+
+    return ray;
+}
+
+// the steepest slope of the falloff of a metaball with an influence of one
+let FALLOFF_SLOPE: f32 = 1.7173;
+
+fn metaball_count() -> u32 {
+    return min(arrayLength(&metaballs.metaballs), args.metaball_count);
+}
+
+fn falloff(x: f32) -> f32 {
+    let y = max(1.0 - x, 0.0);
+
+    return y * y * y;
+}
+
+// returns the colors of the metaballs weighted by their contribution in xyz
+// and the value of the field in w
+fn field(position: vec3<f32>) -> vec4<f32> {
+    var result = vec4<f32>(0.0);
+
+    for(var i: u32 = 0u; i < metaball_count(); i = i + 1u) {
+        let metaball = metaballs.metaballs[i];
+        let offset = position - metaball.position;
+        let influence = metaball.radius * args.influence;
+        let contribution = falloff(dot(offset, offset) / (influence * influence));
+
+        result = result + vec4<f32>(metaball.color, 1.0) * contribution;
+    }
+
+    return result;
+}
+
+// returns how far the ray can advance from the position without crossing
+// the surface
+fn safe_step(position: vec3<f32>) -> f32 {
+    var value = 0.0;
+    var slope = 0.0;
+    var outside = 1000000.0;
+
+    for(var i: u32 = 0u; i < metaball_count(); i = i + 1u) {
+        let metaball = metaballs.metaballs[i];
+        let influence = metaball.radius * args.influence;
+        let distance = distance(position, metaball.position);
+
+        if (distance < influence) {
+            let x = distance / influence;
+
+            value = value + falloff(x * x);
+            slope = slope + FALLOFF_SLOPE / influence;
+        } else {
+            outside = min(outside, distance - influence);
+        }
+    }
+
+    if (slope > 0.0) {
+        return min((args.threshold - value) / slope, outside);
+    }
+
+    return outside;
+}
+
+// returns the distance along the ray to the hit or a negative value if the
+// ray misses
+fn march(ray: Ray) -> f32 {
+    var t = ray.t_min;
+
+    for(var i: u32 = 0u; i < args.steps; i = i + 1u) {
+        let advance = safe_step(point_at(ray, t));
+
+        if (advance < args.epsilon * max(t, 1.0)) {
+            return t;
+        }
+
+        t = t + advance;
+
+        if (t > ray.t_max) {
+            break;
+        }
+    }
+
+    return -1.0;
+}
+
+fn normal(position: vec3<f32>) -> vec3<f32> {
+    var gradient = vec3<f32>(0.0);
+
+    for(var i: u32 = 0u; i < metaball_count(); i = i + 1u) {
+        let metaball = metaballs.metaballs[i];
+        let offset = position - metaball.position;
+        let influence = metaball.radius * args.influence;
+        let y = max(1.0 - dot(offset, offset) / (influence * influence), 0.0);
+
+        gradient = gradient - offset * (6.0 * y * y / (influence * influence));
+    }
+
+    return normalize(-gradient);
+}
+
+fn radiance(ray: Ray) -> vec3<f32> {
+    let background = args.background * (1.0 + args.loudness);
+    let t = march(ray);
+
+    if (t < 0.0) {
+        return background;
+    }
+
+    let position = point_at(ray, t);
+    let normal = normal(position);
+    let field = field(position);
+    let albedo = field.xyz / max(field.w, 1.1920929e-7);
+
+    let diffuse = max(dot(normal, args.light_direction), 0.0);
+
+    // the rim brightens the silhouettes with the background, which keeps the
+    // blobs apart from it
+    let rim = pow(max(1.0 + dot(ray.direction, normal), 0.0), 4.0);
+
+    return albedo * (background * 0.5 + args.light_color * diffuse) + background * rim;
+}
+
+fn tonemapFilmic(x: vec3<f32>) -> vec3<f32> {
+    let X: vec3<f32> = max(vec3<f32>(0.0), x - 0.004);
+    let result: vec3<f32> = (X * (6.2 * X + 0.5)) / (X * (6.2 * X + 1.7) + 0.06);
+    return pow(result, vec3<f32>(2.2));
+}
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    let radiance = radiance(prime_ray(args.camera, position.xy));
+
+    // HDR outputs roll off the highlights themselves
+    if (args.hdr != 0u) {
+        return vec4<f32>(radiance, 1.0);
+    }
+
+    return vec4<f32>(tonemapFilmic(radiance), 1.0);
+}
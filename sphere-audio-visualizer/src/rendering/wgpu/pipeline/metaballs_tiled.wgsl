@@ -0,0 +1,118 @@
+struct Sphere {
+    position: vec2<f32>;
+    radius: f32;
+};
+
+struct Args {
+    color: vec3<f32>;
+    size: vec2<f32>;
+    zoom: vec2<f32>;
+    offset: vec2<f32>;
+    loudness: f32;
+    frame: u32;
+    seed: u32;
+};
+
+struct TiledArgs {
+    args: Args;
+    cull_threshold: f32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> tiled_args: TiledArgs;
+
+struct Spheres {
+    spheres: array<Sphere>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> spheres: Spheres;
+
+[[group(0), binding(2)]]
+var output: texture_storage_2d<rgba16float, write>;
+
+[[group(0), binding(3)]]
+var input: texture_2d<f32>;
+
+let TILE_SIZE: u32 = 16u;
+let TILE_CAPACITY: u32 = 256u;
+
+var<workgroup> tile_spheres: array<u32, TILE_CAPACITY>;
+var<workgroup> tile_count: atomic<u32>;
+
+fn to_scene(position: vec2<f32>) -> vec2<f32> {
+    return (position / tiled_args.args.size * 2.0 - 1.0) * tiled_args.args.zoom;
+}
+
+[[stage(compute), workgroup_size(16, 16)]]
+fn tile(
+    [[builtin(global_invocation_id)]] global_id: vec3<u32>,
+    [[builtin(workgroup_id)]] workgroup_id: vec3<u32>,
+    [[builtin(local_invocation_index)]] local_index: u32,
+) {
+    let args = tiled_args.args;
+
+    let tile_min = to_scene(vec2<f32>(workgroup_id.xy * TILE_SIZE));
+    let tile_max = to_scene(vec2<f32>((workgroup_id.xy + 1u) * TILE_SIZE));
+
+    let position = to_scene(vec2<f32>(global_id.xy) + 0.5);
+
+    var value = 0.0;
+
+    let count = arrayLength(&spheres.spheres);
+    for(var batch: u32 = 0u; batch < count; batch = batch + TILE_CAPACITY) {
+        if (local_index == 0u) {
+            atomicStore(&tile_count, 0u);
+        }
+
+        workgroupBarrier();
+
+        // every invocation tests one sphere of the batch against the tile
+        let id = batch + local_index;
+        if (id < count) {
+            let sphere = spheres.spheres[id];
+
+            let nearest = clamp(sphere.position, min(tile_min, tile_max), max(tile_min, tile_max));
+            let distance = length(sphere.position - nearest);
+
+            if (sphere.radius * 0.05 >= tiled_args.cull_threshold * distance) {
+                tile_spheres[atomicAdd(&tile_count, 1u)] = id;
+            }
+        }
+
+        workgroupBarrier();
+
+        let tile_len = atomicLoad(&tile_count);
+        for(var i: u32 = 0u; i < tile_len; i = i + 1u) {
+            let sphere_id = tile_spheres[i];
+            let sphere = spheres.spheres[sphere_id];
+
+            let oc = position - sphere.position;
+
+            value = value + inverseSqrt(dot(oc, oc)) * sphere.radius * 0.05;
+        }
+
+        workgroupBarrier();
+    }
+
+    if (f32(global_id.x) < args.size.x && f32(global_id.y) < args.size.y) {
+        let color = select(vec4<f32>(args.color * value * (1.0 + args.loudness), 1.0), vec4<f32>(1.0, 1.0, 1.0, 1.0), value >= 0.75);
+
+        textureStore(output, vec2<i32>(global_id.xy), color);
+    }
+}
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    let position = vec4<f32>(x, y, 0.0, 1.0);
+
+    return position;
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    return textureLoad(input, vec2<i32>(position.xy - tiled_args.args.offset), 0);
+}
@@ -1,5 +1,156 @@
+mod accumulation;
+mod bars;
+mod color_vision;
+mod cpu_raytracer;
+mod debug_view;
 mod egui;
+mod hdr_output;
 mod metaballs;
+mod metaballs_3d;
+mod oscilloscope;
+mod parity;
 mod raytracing;
+mod sdf_tracer;
+mod tunnel;
 
-pub use self::{egui::*, metaballs::*, raytracing::*};
+use std::thread::{self, JoinHandle};
+
+use naga::{
+    front::spv,
+    valid::{Capabilities, ValidationFlags, Validator},
+};
+use wgpu::{
+    BindGroup, Color, CommandEncoder, Device, ErrorFilter, LoadOp, Operations, RenderPass,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, TextureFormat, TextureView,
+};
+
+use crate::rendering::Viewport;
+
+use super::SHADER;
+
+pub use self::{
+    bars::*, color_vision::*, cpu_raytracer::*, debug_view::*, egui::*, hdr_output::*,
+    metaballs::*, metaballs_3d::*, oscilloscope::*, parity::*, raytracing::*, sdf_tracer::*,
+    tunnel::*,
+};
+
+/// Describes the outcome of validating the precompiled rust-gpu shader for the
+/// active adapter
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShaderValidation {
+    /// The shader passed the validation and can be used by the pipelines
+    Passed,
+    /// The shader was rejected for the contained reason, the pipelines use
+    /// their WGSL implementation instead
+    Failed(String),
+}
+
+impl ShaderValidation {
+    /// Returns if the shader passed the validation
+    pub fn passed(&self) -> bool {
+        *self == Self::Passed
+    }
+}
+
+impl Default for ShaderValidation {
+    fn default() -> Self {
+        Self::Passed
+    }
+}
+
+/// Parses and validates the precompiled rust-gpu shader with naga on a
+/// background thread. Passthrough shaders skip the validation of wgpu, so
+/// invalid modules would only be rejected by the driver.
+pub(crate) fn spawn_shader_validation() -> JoinHandle<Result<(), String>> {
+    thread::spawn(|| {
+        let module = spv::parse_u8_slice(SHADER, &spv::Options::default())
+            .map_err(|error| format!("The rust shader could not be parsed: {}", error))?;
+
+        Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .map_err(|error| format!("The rust shader is invalid: {}", error))?;
+
+        Ok(())
+    })
+}
+
+/// Eagerly creates all pipelines using the precompiled rust-gpu shader to
+/// check if it is accepted by the active adapter. The shader is only handed to
+/// the driver once the background validation passed.
+pub(crate) async fn validate_rust_pipelines(
+    device: &Device,
+    target_format: TextureFormat,
+    validation: Option<JoinHandle<Result<(), String>>>,
+) -> ShaderValidation {
+    // the validation is only spawned for devices supporting passthrough
+    let validation = match validation {
+        Some(validation) => validation,
+        None => {
+            return ShaderValidation::Failed(
+                "SPIR-V passthrough is not supported by the adapter".to_string(),
+            )
+        }
+    };
+
+    match validation.join() {
+        Ok(Ok(())) => {}
+        Ok(Err(reason)) => return ShaderValidation::Failed(reason),
+        Err(_) => {
+            return ShaderValidation::Failed("The validation of the rust shader failed".to_string())
+        }
+    }
+
+    device.push_error_scope(ErrorFilter::Validation);
+
+    MetaballsRustPipeline::new(device, target_format);
+    Metaballs3DRustPipeline::new(device, target_format);
+    RaytracerRustPipeline::new(device, target_format);
+
+    match device.pop_error_scope().await {
+        Some(error) => ShaderValidation::Failed(error.to_string()),
+        None => ShaderValidation::Passed,
+    }
+}
+
+/// Restricts the render pass to the area of the target the scene is rendered
+/// into. Everything outside keeps the clear color.
+fn set_viewport(render_pass: &mut RenderPass, viewport: &Viewport) {
+    let Viewport { offset, size } = viewport;
+
+    render_pass.set_viewport(offset.x, offset.y, size.x, size.y, 0.0, 1.0);
+    render_pass.set_scissor_rect(
+        offset.x as u32,
+        offset.y as u32,
+        size.x as u32,
+        size.y as u32,
+    );
+}
+
+/// Clears the target and draws a fullscreen quad with the pipeline into the
+/// area of the viewport
+fn draw_fullscreen(
+    command_encoder: &mut CommandEncoder,
+    pipeline: &RenderPipeline,
+    bind_group: &BindGroup,
+    target_texture: &TextureView,
+    viewport: &Viewport,
+) {
+    let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+        label: None,
+        color_attachments: &[RenderPassColorAttachment {
+            view: target_texture,
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::BLACK),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: None,
+    });
+
+    set_viewport(&mut render_pass, viewport);
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_bind_group(0, bind_group, &[]);
+
+    render_pass.draw(0..4, 0..1);
+}
@@ -0,0 +1,318 @@
+use sphere_audio_visualizer_core::glam::{Vec2, Vec3};
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BlendComponent, BlendFactor, BlendOperation, BlendState,
+    BufferUsages, Color, ColorTargetState, ColorWrites, Device, FragmentState, LoadOp, Operations,
+    PolygonMode, PrimitiveState, PrimitiveTopology, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, TextureFormat, TextureView,
+    VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::{
+        scene::OscilloscopeScene,
+        wgpu::{
+            utils::{
+                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+            },
+            Pipeline,
+        },
+    },
+};
+
+use super::set_viewport;
+
+/// Defines the default width of the traces in pixels
+pub const OSCILLOSCOPE_LINE_WIDTH: f32 = 2.0;
+
+/// Defines the default width of the glow around the traces in pixels
+pub const OSCILLOSCOPE_GLOW: f32 = 6.0;
+
+/// Defines the default strength with which the loudness raises the
+/// brightness of the traces
+pub const OSCILLOSCOPE_PULSE: f32 = 0.5;
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct OscilloscopeArgs {
+    color: Vec3,
+    line_width: f32,
+    size: Vec2,
+    glow: f32,
+    loudness: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct OscilloscopeSegment {
+    p0: Vec2,
+    p1: Vec2,
+    intensity: f32,
+    _padding: f32,
+}
+
+const ADDITIVE_BLEND: BlendComponent = BlendComponent {
+    src_factor: BlendFactor::One,
+    dst_factor: BlendFactor::One,
+    operation: BlendOperation::Add,
+};
+
+struct OscilloscopePipeline(RenderPipeline, TextureFormat);
+
+impl OscilloscopePipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("oscilloscope.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-oscilloscope-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: Some(BlendState {
+                        color: ADDITIVE_BLEND,
+                        alpha: ADDITIVE_BLEND,
+                    }),
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+/// The pipeline module for rendering the raw waveform as glowing lines like
+/// an analog oscilloscope. Every line segment is an instanced quad, which is
+/// blended additively, so overlapping traces add up.
+pub struct Oscilloscope {
+    line_width: f32,
+    glow: f32,
+    pulse: f32,
+    loudness: f32,
+    pipeline: Option<OscilloscopePipeline>,
+}
+
+impl Oscilloscope {
+    /// Returns the width of the traces in pixels
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    /// Sets the width of the traces in pixels
+    pub fn set_line_width(&mut self, line_width: f32) -> &mut Self {
+        self.line_width = line_width.max(0.0);
+        self
+    }
+
+    /// Sets the width of the traces in pixels
+    pub fn with_line_width(mut self, line_width: f32) -> Self {
+        self.set_line_width(line_width);
+        self
+    }
+
+    /// Returns the width of the glow around the traces in pixels
+    pub fn glow(&self) -> f32 {
+        self.glow
+    }
+
+    /// Sets the width of the glow around the traces in pixels
+    pub fn set_glow(&mut self, glow: f32) -> &mut Self {
+        self.glow = glow.max(0.0);
+        self
+    }
+
+    /// Sets the width of the glow around the traces in pixels
+    pub fn with_glow(mut self, glow: f32) -> Self {
+        self.set_glow(glow);
+        self
+    }
+
+    /// Returns the strength with which the loudness raises the brightness
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse;
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+}
+
+impl Default for Oscilloscope {
+    fn default() -> Self {
+        Self {
+            line_width: OSCILLOSCOPE_LINE_WIDTH,
+            glow: OSCILLOSCOPE_GLOW,
+            pulse: OSCILLOSCOPE_PULSE,
+            loudness: 0.0,
+            pipeline: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`Oscilloscope`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct OscilloscopeSettings {
+    /// The width of the traces in pixels
+    pub line_width: f32,
+    /// The width of the glow around the traces in pixels
+    pub glow: f32,
+    /// The strength with which the loudness raises the brightness
+    pub pulse: f32,
+}
+
+impl Default for OscilloscopeSettings {
+    fn default() -> Self {
+        Self {
+            line_width: OSCILLOSCOPE_LINE_WIDTH,
+            glow: OSCILLOSCOPE_GLOW,
+            pulse: OSCILLOSCOPE_PULSE,
+        }
+    }
+}
+
+impl Module for Oscilloscope {
+    type Settings = OscilloscopeSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_line_width(settings.line_width)
+            .set_glow(settings.glow)
+            .set_pulse(settings.pulse)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        OscilloscopeSettings {
+            line_width: self.line_width(),
+            glow: self.glow(),
+            pulse: self.pulse(),
+        }
+    }
+}
+
+impl Pipeline<OscilloscopeScene> for Oscilloscope {
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
+    fn render(
+        &mut self,
+        scene: OscilloscopeScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| OscilloscopePipeline::new(device, output_format));
+
+        if pipeline.1 != output_format {
+            *pipeline = OscilloscopePipeline::new(device, output_format);
+        }
+
+        let memory_tracker = command_queue.memory_tracker();
+
+        let segments = scene
+            .traces
+            .iter()
+            .flat_map(|trace| {
+                trace.points.windows(2).map(|points| OscilloscopeSegment {
+                    p0: points[0],
+                    p1: points[1],
+                    intensity: trace.intensity,
+                    _padding: 0.0,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // the latest traces come first, so they survive the fitting
+        let segments = memory_tracker.fit("Oscilloscope Segments", &segments);
+        let segment_count = segments.len() as u32;
+
+        let bind_group = (!segments.is_empty()).then(|| {
+            let args = OscilloscopeArgs {
+                color: scene.color.into(),
+                line_width: self.line_width,
+                size: scene.viewport.size,
+                glow: self.glow,
+                loudness: self.loudness * self.pulse,
+            };
+
+            let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: &args,
+            });
+            memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+            let segments_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: segments,
+            });
+
+            device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                entries: &[
+                    args_buffer.bind_group_entry(0).unwrap(),
+                    segments_buffer.bind_group_entry(1).unwrap(),
+                ],
+                layout: &pipeline.0.get_bind_group_layout(0),
+            })
+        });
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[RenderPassColorAttachment {
+                    view: target_texture,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            set_viewport(&mut render_pass, &scene.viewport);
+
+            if let Some(bind_group) = &bind_group {
+                render_pass.set_pipeline(&pipeline.0);
+                render_pass.set_bind_group(0, bind_group, &[]);
+
+                render_pass.draw(0..4, 0..segment_count);
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+struct Args {
+    color: vec3<f32>;
+    line_width: f32;
+    size: vec2<f32>;
+    glow: f32;
+    loudness: f32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+struct Segment {
+    p0: vec2<f32>;
+    p1: vec2<f32>;
+    intensity: f32;
+};
+
+struct Segments {
+    segments: array<Segment>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> segments: Segments;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] distance: f32;
+    [[location(1)]] intensity: f32;
+};
+
+// Every segment is drawn as a quad, which is extended by the line width and
+// the glow in pixels around the segment.
+[[stage(vertex)]]
+fn vertex(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    [[builtin(instance_index)]] instance_index: u32,
+) -> VertexOutput {
+    let segment = segments.segments[instance_index];
+
+    let delta = (segment.p1 - segment.p0) * args.size * 0.5;
+    let len = length(delta);
+    let direction = select(vec2<f32>(1.0, 0.0), delta / max(len, 0.0001), len > 0.0001);
+    let normal = vec2<f32>(-direction.y, direction.x);
+
+    let along = f32(vertex_index & 1u);
+    let across = f32(vertex_index & 2u) - 1.0;
+    let extent = args.line_width * 0.5 + args.glow;
+
+    let offset = (normal * across + direction * (along * 2.0 - 1.0)) * extent;
+    let position = mix(segment.p0, segment.p1, along) + offset / args.size * 2.0;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.distance = across * extent;
+    out.intensity = segment.intensity;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fragment(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let half_width = args.line_width * 0.5;
+    let distance = abs(in.distance);
+
+    let core = clamp(half_width + 0.5 - distance, 0.0, 1.0);
+    let glow = exp(-3.0 * max(distance - half_width, 0.0) / max(args.glow, 0.0001)) * 0.5;
+    let value = max(core, glow) * in.intensity * (1.0 + args.loudness);
+
+    return vec4<f32>(args.color * value, 1.0);
+}
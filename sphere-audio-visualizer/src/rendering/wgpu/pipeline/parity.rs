@@ -0,0 +1,213 @@
+use std::num::NonZeroU32;
+
+use wgpu::{
+    Buffer, BufferDescriptor, BufferUsages, Device, Extent3d, ImageCopyBuffer, ImageDataLayout,
+    Maintain, MapMode, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    TextureView, TextureViewDescriptor, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+use crate::rendering::wgpu::utils::CommandQueue;
+
+/// Stores the difference between the images of the same scene rendered by the
+/// rust-gpu and the WGSL implementation of a pipeline
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct ParityReport {
+    /// Represents the mean absolute difference of the color channels in
+    /// `0.0..=1.0`
+    pub mean_difference: f32,
+    /// Represents the largest absolute difference of a color channel in
+    /// `0.0..=1.0`
+    pub max_difference: f32,
+}
+
+impl ParityReport {
+    /// Compares two images of the same size with four bytes per pixel. The
+    /// fourth byte of every pixel is ignored.
+    ///
+    /// ```
+    /// use sphere_audio_visualizer::rendering::wgpu::ParityReport;
+    ///
+    /// let a = [0, 0, 0, 255, 255, 0, 0, 255];
+    /// let b = [0, 0, 0, 0, 0, 0, 0, 255];
+    ///
+    /// let report = ParityReport::compare(&a, &b);
+    ///
+    /// assert_eq!(report.max_difference, 1.0);
+    /// assert!((report.mean_difference - 1.0 / 6.0).abs() < 1e-6);
+    /// ```
+    pub fn compare(a: &[u8], b: &[u8]) -> Self {
+        let mut sum = 0u64;
+        let mut max = 0u8;
+        let mut count = 0u64;
+
+        for (a, b) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+            for channel in 0..3 {
+                let difference = a[channel].abs_diff(b[channel]);
+
+                sum += difference as u64;
+                max = max.max(difference);
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            return Self::default();
+        }
+
+        Self {
+            mean_difference: sum as f32 / count as f32 / 255.0,
+            max_difference: max as f32 / 255.0,
+        }
+    }
+}
+
+struct ParityTexture {
+    texture: Texture,
+    buffer: Buffer,
+}
+
+struct ParityTextures {
+    textures: [ParityTexture; 2],
+    format: TextureFormat,
+    size: Extent3d,
+    bytes_per_row: u32,
+}
+
+impl ParityTextures {
+    fn new(device: &Device, format: TextureFormat, width: u32, height: u32) -> Self {
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let bytes_per_row =
+            (width * 4).div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let texture = || ParityTexture {
+            texture: device.create_texture(&TextureDescriptor {
+                label: None,
+                dimension: TextureDimension::D2,
+                format,
+                mip_level_count: 1,
+                sample_count: 1,
+                size,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            }),
+            buffer: device.create_buffer(&BufferDescriptor {
+                label: None,
+                mapped_at_creation: false,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                size: (bytes_per_row * height) as u64,
+            }),
+        };
+
+        Self {
+            textures: [texture(), texture()],
+            format,
+            size,
+            bytes_per_row,
+        }
+    }
+}
+
+/// Renders the same scene with the rust-gpu and the WGSL implementation of a
+/// pipeline into offscreen textures and compares the results
+#[derive(Default)]
+pub(super) struct ParityCheck {
+    textures: Option<ParityTextures>,
+}
+
+impl ParityCheck {
+    /// Returns if the images of the given format can be compared
+    pub(super) fn supports(format: TextureFormat) -> bool {
+        format.describe().block_size == 4
+    }
+
+    /// Returns the views the rust-gpu and the WGSL implementation render into
+    pub(super) fn begin(
+        &mut self,
+        device: &Device,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> [TextureView; 2] {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let recreate = self.textures.as_ref().map_or(true, |textures| {
+            textures.format != format
+                || textures.size.width != width
+                || textures.size.height != height
+        });
+
+        if recreate {
+            self.textures = Some(ParityTextures::new(device, format, width, height));
+        }
+
+        let textures = self.textures.as_ref().unwrap();
+
+        let [rust_texture, wgsl_texture] = &textures.textures;
+
+        [rust_texture, wgsl_texture].map(|texture| {
+            texture
+                .texture
+                .create_view(&TextureViewDescriptor::default())
+        })
+    }
+
+    /// Reads both images back and compares them. This flushes the
+    /// [`CommandQueue`] and waits for the device, so it stalls the frame.
+    pub(super) fn finish(&self, device: &Device, command_queue: &mut CommandQueue) -> ParityReport {
+        let textures = match &self.textures {
+            Some(textures) => textures,
+            None => return ParityReport::default(),
+        };
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        for texture in &textures.textures {
+            command_encoder.copy_texture_to_buffer(
+                texture.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &texture.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(textures.bytes_per_row),
+                        rows_per_image: NonZeroU32::new(textures.size.height),
+                    },
+                },
+                textures.size,
+            );
+        }
+
+        command_queue.flush();
+
+        let [rust_texture, wgsl_texture] = &textures.textures;
+
+        let [rust_image, wgsl_image] = [rust_texture, wgsl_texture].map(|texture| {
+            let slice = texture.buffer.slice(..);
+
+            let future = slice.map_async(MapMode::Read);
+            device.poll(Maintain::Wait);
+            pollster::block_on(future).unwrap();
+
+            let view = slice.get_mapped_range();
+
+            let mut data =
+                Vec::with_capacity((textures.size.width * textures.size.height * 4) as usize);
+
+            for y in 0..textures.size.height {
+                let offset = (y * textures.bytes_per_row) as usize;
+                data.extend(&view[offset..offset + textures.size.width as usize * 4]);
+            }
+
+            drop(view);
+            texture.buffer.unmap();
+
+            data
+        });
+
+        ParityReport::compare(&rust_image, &wgsl_image)
+    }
+}
@@ -1,35 +1,79 @@
-use sphere_audio_visualizer_core::raytracing::{
-    light::PointLight,
-    shape::{Rect, SceneArgs, Sphere, AABB},
-    BasicRaytracingArgsBundle, RaytracerArgs,
+use std::{collections::hash_map::DefaultHasher, hash::Hasher, num::NonZeroU32, sync::Arc};
+#[cfg(feature = "statistics")]
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "statistics")]
+use sphere_audio_visualizer_core::raytracing::statistics::RaytracerStatistics;
+use sphere_audio_visualizer_core::{
+    glam::{vec2, Mat4, Vec2, Vec3A},
+    raytracing::{
+        camera::SceneCamera,
+        fog::Fog,
+        light::{DirectionalLight, PointLight, SpotLight},
+        shape::{
+            BvhNode, Capsule, Disc, Instance, InstanceShape, Material, Plane, Rect, SceneArgs,
+            Sphere, Triangle, AABB, FLAT_NORMAL,
+        },
+        BasicRaytracingArgsBundle, RaytracerArgs,
+    },
+    utils::math::{ColorGrading, Tonemapper},
 };
 use wgpu::{
-    include_wgsl, util::make_spirv_raw, BindGroupDescriptor, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingType, BufferBindingType, BufferUsages, Color, ColorTargetState,
-    ColorWrites, Device, FragmentState, LoadOp, Operations, PipelineLayoutDescriptor, PolygonMode,
-    PrimitiveState, PrimitiveTopology, RenderPassColorAttachment, RenderPassDescriptor,
-    RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptorSpirV, ShaderStages,
-    TextureFormat, TextureView, VertexState,
+    include_wgsl, util::make_spirv_raw, AddressMode, BindGroupDescriptor, BindGroupEntry,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, ColorWrites,
+    ComputePassDescriptor, ComputePipeline, ComputePipelineDescriptor, Device, Extent3d,
+    FilterMode, FragmentState, ImageCopyTexture, ImageDataLayout, Origin3d,
+    PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology, Queue,
+    RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor,
+    ShaderModuleDescriptor, ShaderModuleDescriptorSpirV, ShaderSource, ShaderStages, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
+    TextureView, TextureViewDescriptor, TextureViewDimension, VertexState,
 };
+#[cfg(feature = "statistics")]
+use wgpu::{BufferAsyncError, CommandEncoder, MapMode};
 
 use crate::{
     module::Module,
     rendering::{
-        scene::{BasicRaytracerScene, ShapeCollection},
+        scene::{BasicRaytracerScene, HdrImage, ShapeCollection, Viewport},
         wgpu::{
             utils::{
-                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+                CommandQueue, DynamicBuffer, SeedStream, TypedBuffer, Uploader, SEED,
+                {TypedBufferDeviceExt, TypedBufferInitDescriptor},
             },
-            Pipeline, ShadingLanguage, SHADER,
+            Pipeline, ShaderValidation, ShadingLanguage, WGPURenderer, HDR_FORMAT, SHADER,
         },
     },
 };
 
+#[cfg(feature = "statistics")]
+use crate::rendering::wgpu::utils::noop_waker;
+
+use super::{accumulation::Accumulation, draw_fullscreen, ParityCheck, ParityReport};
+
 struct RaytracerWGSLPipeline(RenderPipeline, TextureFormat);
 
 impl RaytracerWGSLPipeline {
     fn new(device: &Device, target_format: TextureFormat) -> Self {
-        let shader_module = device.create_shader_module(&include_wgsl!("raytracing.wgsl"));
+        let source = include_str!("raytracing.wgsl");
+
+        // the counters are only compiled in with the statistics feature
+        #[cfg(feature = "statistics")]
+        let source = source.replace(
+            "let STATISTICS: bool = false;",
+            "let STATISTICS: bool = true;",
+        );
+
+        let shader_module = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("raytracing.wgsl"),
+            source: ShaderSource::Wgsl(source.into()),
+        });
 
         let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             label: Some("sphere-visualizer-raytracing-pipeline"),
@@ -62,10 +106,10 @@ impl RaytracerWGSLPipeline {
     }
 }
 
-struct RaytracerRustPipeline(RenderPipeline, TextureFormat);
+pub(super) struct RaytracerRustPipeline(RenderPipeline, TextureFormat);
 
 impl RaytracerRustPipeline {
-    fn new(device: &Device, target_format: TextureFormat) -> Self {
+    pub(super) fn new(device: &Device, target_format: TextureFormat) -> Self {
         let shader_module = unsafe {
             device.create_shader_module_spirv(&ShaderModuleDescriptorSpirV {
                 label: None,
@@ -116,6 +160,154 @@ impl RaytracerRustPipeline {
                     },
                     visibility: ShaderStages::FRAGMENT,
                 },
+                BindGroupLayoutEntry {
+                    binding: 4,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 5,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 6,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 7,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 8,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 9,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 10,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 11,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 12,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 13,
+                    count: None,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 14,
+                    count: None,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 15,
+                    count: None,
+                    ty: BindingType::Buffer {
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                        ty: BufferBindingType::Storage { read_only: true },
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 16,
+                    count: None,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 17,
+                    count: None,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 18,
+                    count: None,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    visibility: ShaderStages::FRAGMENT,
+                },
+                BindGroupLayoutEntry {
+                    binding: 19,
+                    count: None,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    visibility: ShaderStages::FRAGMENT,
+                },
             ],
         });
 
@@ -156,198 +348,1825 @@ impl RaytracerRustPipeline {
     }
 }
 
-/// The pipeline module used for raytraced rendering
-pub struct Raytracer {
-    implementation: ShadingLanguage,
-    rust_pipeline: Option<RaytracerRustPipeline>,
-    wgsl_pipeline: Option<RaytracerWGSLPipeline>,
+/// Defines the amount of spheres processed by a workgroup of the culling pass
+const CULLING_WORKGROUP_SIZE: u32 = 64;
+
+/// Stores the parameters of the sphere culling pass
+#[repr(C, align(16))]
+struct CullingArgs {
+    view: Mat4,
+    slopes: Vec2,
+    pixel_scale: f32,
+    cull_threshold: f32,
+    t_min: f32,
+    t_max: f32,
+    sphere_count: u32,
+    panoramic: u32,
 }
 
-impl Raytracer {
-    /// Creates a new instance using the specified [`ShadingLanguage`]
-    pub fn from_implementation(implementation: ShadingLanguage) -> Self {
+impl CullingArgs {
+    fn new(camera: &SceneCamera, cull_threshold: f32) -> Self {
+        let projection = camera.perspective().projection();
+
+        // the panoramic camera has no frustum and maps the angle of a sphere
+        // linearly onto the screen
+        let pixel_scale = match camera.is_panoramic() {
+            true => camera.screen_size().x / std::f32::consts::TAU,
+            false => projection.x_axis.x * camera.screen_size().x * 0.5,
+        };
+
         Self {
-            implementation,
-            rust_pipeline: None,
-            wgsl_pipeline: None,
+            view: camera.view(),
+            slopes: vec2(1.0 / projection.x_axis.x, 1.0 / projection.y_axis.y),
+            pixel_scale,
+            cull_threshold,
+            t_min: camera.t_min(),
+            t_max: camera.t_max(),
+            sphere_count: 0,
+            panoramic: camera.is_panoramic() as u32,
         }
     }
+}
 
-    /// Sets [`ShadingLanguage`] that should be used going forward
-    pub fn with_implementation(mut self, implementation: ShadingLanguage) -> Self {
-        self.set_implementation(implementation);
-        self
-    }
+struct SphereCullingPipeline(ComputePipeline);
 
-    /// Sets [`ShadingLanguage`] that should be used going forward
-    pub fn set_implementation(&mut self, implementation: ShadingLanguage) -> &mut Self {
-        self.implementation = implementation;
-        self
-    }
+impl SphereCullingPipeline {
+    fn new(device: &Device) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("sphere_culling.wgsl"));
 
-    /// Gets the used [`ShadingLanguage`]
-    pub fn implementation(&self) -> ShadingLanguage {
-        self.implementation.clone()
+        let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("sphere-visualizer-sphere-culling-pipeline"),
+            layout: None,
+            module: &shader_module,
+            entry_point: "cull",
+        });
+
+        Self(pipeline)
     }
 }
 
-/// Stores the settings of the [`Raytracer`] pipeline module
-#[derive(Clone)]
-pub struct RaytracerSettings {
-    /// The used [`ShadingLanguage`]
-    pub shading_language: ShadingLanguage,
+/// Defines the format of the image textures. The shared exponent keeps the
+/// high dynamic range in four bytes per pixel.
+const IMAGE_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgb9e5Ufloat;
+
+/// Stores an image of the scene on the GPU, like the environment map or the
+/// surface texture. Scenes without the image get a single pixel of the
+/// fallback color, e.g. white, so the colors it is multiplied with are kept.
+struct ImageTexture {
+    image: Option<Arc<HdrImage>>,
+    view: TextureView,
+    sampler: Sampler,
+    width: u32,
+    height: u32,
 }
 
-impl Default for RaytracerSettings {
-    fn default() -> Self {
+impl ImageTexture {
+    fn new(
+        device: &Device,
+        queue: &Queue,
+        image: Option<&Arc<HdrImage>>,
+        fallback: Vec3A,
+        label: &str,
+        address_mode_v: AddressMode,
+    ) -> Self {
+        let mut level = match image {
+            Some(image) => image.as_ref().clone(),
+            None => HdrImage::new(1, 1, vec![fallback]),
+        };
+
+        // images beyond the texture size limit lose their finest levels
+        let max_size = device.limits().max_texture_dimension_2d as usize;
+        while level.width().max(level.height()) > max_size {
+            level = level.downsampled();
+        }
+
+        let width = level.width() as u32;
+        let height = level.height() as u32;
+        let mip_level_count = level.mip_level_count();
+
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some(label),
+            dimension: TextureDimension::D2,
+            format: IMAGE_TEXTURE_FORMAT,
+            mip_level_count,
+            sample_count: 1,
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        });
+
+        for mip_level in 0..mip_level_count {
+            if mip_level > 0 {
+                level = level.downsampled();
+            }
+
+            let data = level
+                .pixels()
+                .iter()
+                .flat_map(|pixel| rgb9e5(pixel).to_le_bytes())
+                .collect::<Vec<_>>();
+
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                &data,
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(level.width() as u32 * 4),
+                    rows_per_image: None,
+                },
+                Extent3d {
+                    width: level.width() as u32,
+                    height: level.height() as u32,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: AddressMode::Repeat,
+            address_mode_v,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+
         Self {
-            shading_language: ShadingLanguage::Rust,
+            image: image.cloned(),
+            view: texture.create_view(&TextureViewDescriptor::default()),
+            sampler,
+            width,
+            height,
+        }
+    }
+
+    /// Returns whether the texture was created from the image
+    fn matches(&self, image: Option<&Arc<HdrImage>>) -> bool {
+        match (&self.image, image) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
         }
     }
 }
 
-impl Module for Raytracer {
-    type Settings = RaytracerSettings;
+/// Encodes a color into the shared exponent format of
+/// [`IMAGE_TEXTURE_FORMAT`]
+fn rgb9e5(color: &Vec3A) -> u32 {
+    const MANTISSA_BITS: i32 = 9;
+    const EXPONENT_BIAS: i32 = 15;
+    const MAX_VALUE: f32 = 65408.0;
 
-    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
-        self.set_implementation(settings.shading_language)
-    }
+    let color = color.max(Vec3A::ZERO).min(Vec3A::splat(MAX_VALUE));
+    let max_component = color.max_element();
 
-    fn settings(&self) -> Self::Settings {
-        RaytracerSettings {
-            shading_language: self.implementation(),
-        }
+    let mut exponent = max_component
+        .log2()
+        .floor()
+        .max(-EXPONENT_BIAS as f32 - 1.0) as i32
+        + 1
+        + EXPONENT_BIAS;
+    let mut scale = 2.0f32.powi(exponent - EXPONENT_BIAS - MANTISSA_BITS);
+
+    // rounding may overflow the mantissa, which needs one more exponent step
+    if (max_component / scale).round() as u32 == 1 << MANTISSA_BITS {
+        exponent += 1;
+        scale *= 2.0;
     }
+
+    let mantissa = (color / scale).round();
+
+    mantissa.x as u32
+        | (mantissa.y as u32) << MANTISSA_BITS
+        | (mantissa.z as u32) << (MANTISSA_BITS * 2)
+        | (exponent as u32) << (MANTISSA_BITS * 3)
 }
 
-impl Default for Raytracer {
-    fn default() -> Self {
+/// Defines the size of the counters of the WGSL shader, which are four `u32`s
+const STATISTICS_SIZE: u64 = 16;
+
+#[cfg(feature = "statistics")]
+type MapFuture = Pin<Box<dyn Future<Output = Result<(), BufferAsyncError>> + Send>>;
+
+#[cfg(feature = "statistics")]
+enum ReadbackState {
+    /// The buffer can receive the counters of the next frame
+    Idle,
+    /// The counters of a frame were copied into the buffer
+    Copied,
+    /// The buffer is mapped for reading, the future is not `Sync` but only
+    /// ever accessed mutably
+    Mapping(Mutex<MapFuture>),
+}
+
+/// Reads the statistics counted by the WGSL shader back without stalling the
+/// rendering, so they arrive a few frames late. Frames rendered while a
+/// readback is in flight are not counted.
+#[cfg(feature = "statistics")]
+struct StatisticsReadback {
+    buffer: Arc<Buffer>,
+    state: ReadbackState,
+}
+
+#[cfg(feature = "statistics")]
+impl StatisticsReadback {
+    fn new(device: &Device) -> Self {
         Self {
-            implementation: ShadingLanguage::Rust,
-            rust_pipeline: None,
-            wgsl_pipeline: None,
+            buffer: Arc::new(device.create_buffer(&BufferDescriptor {
+                label: Some("statistics-readback"),
+                size: STATISTICS_SIZE,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })),
+            state: ReadbackState::Idle,
         }
     }
-}
 
-impl Pipeline<BasicRaytracerScene> for Raytracer {
-    fn render(
-        &mut self,
-        mut scene: BasicRaytracerScene,
-        device: &Device,
-        command_queue: &mut CommandQueue,
-        output_format: TextureFormat,
-        target_texture: &TextureView,
-    ) {
-        let pipeline = match self.implementation {
-            ShadingLanguage::Rust => {
-                let rust_pipeline = self
-                    .rust_pipeline
-                    .get_or_insert_with(|| RaytracerRustPipeline::new(device, output_format));
+    /// Returns if the counters of the next frame can be copied
+    fn is_idle(&self) -> bool {
+        matches!(self.state, ReadbackState::Idle)
+    }
 
-                if rust_pipeline.1 != output_format {
-                    *rust_pipeline = RaytracerRustPipeline::new(device, output_format);
-                }
+    /// Records the copy of the counters into the readback buffer
+    fn copy(&mut self, command_encoder: &mut CommandEncoder, counters: &Buffer) {
+        command_encoder.copy_buffer_to_buffer(counters, 0, &self.buffer, 0, STATISTICS_SIZE);
+        self.state = ReadbackState::Copied;
+    }
 
-                &rust_pipeline.0
-            }
-            ShadingLanguage::WGSL => {
-                let wgsl_pipeline = self
-                    .wgsl_pipeline
-                    .get_or_insert_with(|| RaytracerWGSLPipeline::new(device, output_format));
+    /// Maps the counters copied by a previous frame and returns them once the
+    /// mapping is done. The device is polled by the [`Uploader`] every frame.
+    fn poll(&mut self) -> Option<RaytracerStatistics> {
+        match &mut self.state {
+            ReadbackState::Idle => None,
+            ReadbackState::Copied => {
+                // the copy has to be submitted before the buffer is mapped
+                let buffer = self.buffer.clone();
 
-                if wgsl_pipeline.1 != output_format {
-                    *wgsl_pipeline = RaytracerWGSLPipeline::new(device, output_format);
-                }
+                self.state = ReadbackState::Mapping(Mutex::new(Box::pin(async move {
+                    buffer.slice(..).map_async(MapMode::Read).await
+                })));
 
-                &wgsl_pipeline.0
+                None
             }
-        };
+            ReadbackState::Mapping(future) => {
+                let waker = noop_waker();
 
-        let spheres = scene.shapes::<Sphere>();
-        let spheres_bounding_box = spheres
-            .map(ShapeCollection::bounding_box)
-            .cloned()
-            .unwrap_or_else(AABB::empty);
+                let result = match future
+                    .get_mut()
+                    .unwrap()
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                {
+                    Poll::Ready(result) => result,
+                    Poll::Pending => return None,
+                };
 
-        let spheres_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
-            label: None,
-            usage: BufferUsages::STORAGE,
-            value: spheres.map(ShapeCollection::shapes).unwrap_or(&[]),
-        });
+                self.state = ReadbackState::Idle;
 
-        let rects = scene.shapes::<Rect>();
-        let rects_bounding_box = rects
-            .map(ShapeCollection::bounding_box)
-            .cloned()
-            .unwrap_or_else(AABB::empty);
+                result.ok().map(|()| {
+                    let view = self.buffer.slice(..).get_mapped_range();
+                    let statistics = unsafe {
+                        std::ptr::read_unaligned(view.as_ptr() as *const RaytracerStatistics)
+                    };
 
-        let rects_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
-            label: None,
-            usage: BufferUsages::STORAGE,
-            value: rects.map(ShapeCollection::shapes).unwrap_or(&[]),
-        });
+                    drop(view);
+                    self.buffer.unmap();
 
-        let point_lights_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
-            label: None,
-            usage: BufferUsages::STORAGE,
-            value: scene
-                .lights_mut::<PointLight>()
-                .map(Vec::as_slice)
-                .unwrap_or(&[]),
-        });
+                    statistics
+                })
+            }
+        }
+    }
+}
 
-        let args = BasicRaytracingArgsBundle {
-            raytracer_args: RaytracerArgs {
-                camera: scene.camera,
-                background: scene.background,
-                bounces: scene.bounces,
-            },
-            scene_args: SceneArgs {
-                spheres_bounding_box,
-                rects_bounding_box,
-            },
-        };
+/// Hashes the raw bytes of a value to detect changes of the scene
+fn hash_bytes<T: ?Sized>(hasher: &mut DefaultHasher, value: &T) {
+    let data = unsafe {
+        std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of_val(value))
+    };
 
-        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+    hasher.write(data);
+}
+
+/// Defines the default strength with which the loudness raises the
+/// background brightness
+pub const RAYTRACER_PULSE: f32 = 0.25;
+
+/// Defines the default number of samples per pixel along each axis
+pub const RAYTRACER_SUPERSAMPLING: u32 = 1;
+
+/// Defines the default number of bounces after which dark paths are
+/// terminated randomly
+pub const RAYTRACER_ROULETTE_DEPTH: u32 = 3;
+
+/// Defines the default operator mapping the radiance to displayable colors
+pub const RAYTRACER_TONEMAPPER: Tonemapper = Tonemapper::Filmic;
+
+/// Defines the default exposure in stops
+pub const RAYTRACER_EXPOSURE: f32 = 0.0;
+
+/// Defines the default contrast
+pub const RAYTRACER_CONTRAST: f32 = 1.0;
+
+/// Defines the default saturation
+pub const RAYTRACER_SATURATION: f32 = 1.0;
+
+/// Defines the default gamma
+pub const RAYTRACER_GAMMA: f32 = 1.0;
+
+/// Defines the default density of the fog, which is disabled by default
+pub const RAYTRACER_FOG_DENSITY: f32 = 0.0;
+
+/// Defines the default color of the fog
+pub const RAYTRACER_FOG_COLOR: Vec3A = Vec3A::ONE;
+
+/// Defines the default distance from the camera the fog reaches
+pub const RAYTRACER_FOG_DISTANCE: f32 = 32.0;
+
+/// Defines the default number of points per pixel at which the fog is lit
+pub const RAYTRACER_FOG_STEPS: u32 = 16;
+
+/// Defines the default projected radius in pixels below which spheres are
+/// culled
+pub const RAYTRACER_CULL_THRESHOLD: f32 = 0.5;
+
+/// Specifies which spheres are removed by the culling pass of the
+/// [`Raytracer`]. The pass runs in a compute shader before the spheres are
+/// rendered. Culled spheres are also missing from reflections and shadows.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum SphereCulling {
+    /// Every sphere is rendered
+    Disabled,
+    /// Spheres outside of the view frustum are culled
+    Frustum,
+    /// Spheres outside of the view frustum or with a projected radius below
+    /// the cull threshold are culled
+    LevelOfDetail,
+}
+
+/// The pipeline module used for raytraced rendering
+pub struct Raytracer {
+    implementation: ShadingLanguage,
+    pulse: f32,
+    loudness: f32,
+    supersampling: u32,
+    roulette_depth: u32,
+    tonemapper: Tonemapper,
+    exposure: f32,
+    contrast: f32,
+    saturation: f32,
+    gamma: f32,
+    fog_density: f32,
+    fog_color: Vec3A,
+    fog_distance: f32,
+    fog_steps: u32,
+    accumulation: bool,
+    accumulator: Accumulation,
+    seed_stream: SeedStream,
+    uploader: Uploader,
+    spheres_buffer: DynamicBuffer,
+    rects_buffer: DynamicBuffer,
+    point_lights_buffer: DynamicBuffer,
+    triangles_buffer: DynamicBuffer,
+    sphere_nodes_buffer: DynamicBuffer,
+    triangle_nodes_buffer: DynamicBuffer,
+    planes_buffer: DynamicBuffer,
+    discs_buffer: DynamicBuffer,
+    capsules_buffer: DynamicBuffer,
+    instances_buffer: DynamicBuffer,
+    materials_buffer: DynamicBuffer,
+    directional_lights_buffer: DynamicBuffer,
+    spot_lights_buffer: DynamicBuffer,
+    environment_texture: Option<ImageTexture>,
+    surface_texture: Option<ImageTexture>,
+    normal_map: Option<ImageTexture>,
+    culling: SphereCulling,
+    cull_threshold: f32,
+    culling_pipeline: Option<SphereCullingPipeline>,
+    culled_spheres_buffer: DynamicBuffer,
+    rust_pipeline: Option<RaytracerRustPipeline>,
+    wgsl_pipeline: Option<RaytracerWGSLPipeline>,
+    shader_validation: ShaderValidation,
+    parity: bool,
+    parity_check: ParityCheck,
+    parity_report: Option<ParityReport>,
+    statistics_buffer: Option<Buffer>,
+    #[cfg(feature = "statistics")]
+    statistics_readback: Option<StatisticsReadback>,
+    #[cfg(feature = "statistics")]
+    statistics: RaytracerStatistics,
+}
+
+impl Raytracer {
+    /// Creates a new instance using the specified [`ShadingLanguage`]
+    pub fn from_implementation(implementation: ShadingLanguage) -> Self {
+        Self {
+            implementation,
+            pulse: RAYTRACER_PULSE,
+            loudness: 0.0,
+            supersampling: RAYTRACER_SUPERSAMPLING,
+            roulette_depth: RAYTRACER_ROULETTE_DEPTH,
+            tonemapper: RAYTRACER_TONEMAPPER,
+            exposure: RAYTRACER_EXPOSURE,
+            contrast: RAYTRACER_CONTRAST,
+            saturation: RAYTRACER_SATURATION,
+            gamma: RAYTRACER_GAMMA,
+            fog_density: RAYTRACER_FOG_DENSITY,
+            fog_color: RAYTRACER_FOG_COLOR,
+            fog_distance: RAYTRACER_FOG_DISTANCE,
+            fog_steps: RAYTRACER_FOG_STEPS,
+            accumulation: false,
+            accumulator: Accumulation::default(),
+            seed_stream: SeedStream::default(),
+            uploader: Uploader::default(),
+            spheres_buffer: DynamicBuffer::new(Some("spheres"), BufferUsages::STORAGE),
+            rects_buffer: DynamicBuffer::new(Some("rects"), BufferUsages::STORAGE),
+            point_lights_buffer: DynamicBuffer::new(Some("point-lights"), BufferUsages::STORAGE),
+            triangles_buffer: DynamicBuffer::new(Some("triangles"), BufferUsages::STORAGE),
+            sphere_nodes_buffer: DynamicBuffer::new(Some("sphere-nodes"), BufferUsages::STORAGE),
+            triangle_nodes_buffer: DynamicBuffer::new(
+                Some("triangle-nodes"),
+                BufferUsages::STORAGE,
+            ),
+            planes_buffer: DynamicBuffer::new(Some("planes"), BufferUsages::STORAGE),
+            discs_buffer: DynamicBuffer::new(Some("discs"), BufferUsages::STORAGE),
+            capsules_buffer: DynamicBuffer::new(Some("capsules"), BufferUsages::STORAGE),
+            instances_buffer: DynamicBuffer::new(Some("instances"), BufferUsages::STORAGE),
+            materials_buffer: DynamicBuffer::new(Some("materials"), BufferUsages::STORAGE),
+            directional_lights_buffer: DynamicBuffer::new(
+                Some("directional-lights"),
+                BufferUsages::STORAGE,
+            ),
+            spot_lights_buffer: DynamicBuffer::new(Some("spot-lights"), BufferUsages::STORAGE),
+            environment_texture: None,
+            surface_texture: None,
+            normal_map: None,
+            culling: SphereCulling::Disabled,
+            cull_threshold: RAYTRACER_CULL_THRESHOLD,
+            culling_pipeline: None,
+            culled_spheres_buffer: DynamicBuffer::new(
+                Some("culled-spheres"),
+                BufferUsages::STORAGE,
+            ),
+            rust_pipeline: None,
+            wgsl_pipeline: None,
+            shader_validation: ShaderValidation::Passed,
+            parity: false,
+            parity_check: ParityCheck::default(),
+            parity_report: None,
+            statistics_buffer: None,
+            #[cfg(feature = "statistics")]
+            statistics_readback: None,
+            #[cfg(feature = "statistics")]
+            statistics: RaytracerStatistics::default(),
+        }
+    }
+
+    /// Sets [`ShadingLanguage`] that should be used going forward
+    pub fn with_implementation(mut self, implementation: ShadingLanguage) -> Self {
+        self.set_implementation(implementation);
+        self
+    }
+
+    /// Sets [`ShadingLanguage`] that should be used going forward
+    pub fn set_implementation(&mut self, implementation: ShadingLanguage) -> &mut Self {
+        self.implementation = match implementation {
+            ShadingLanguage::Rust if !self.shader_validation.passed() => ShadingLanguage::WGSL,
+            implementation => implementation,
+        };
+        self
+    }
+
+    /// Returns if the precompiled rust-gpu shader is supported by the active
+    /// adapter. If not the WGSL implementation is used instead.
+    pub fn rust_supported(&self) -> bool {
+        self.shader_validation.passed()
+    }
+
+    /// Returns the outcome of validating the precompiled rust-gpu shader
+    pub fn shader_validation(&self) -> &ShaderValidation {
+        &self.shader_validation
+    }
+
+    /// Gets the used [`ShadingLanguage`]
+    pub fn implementation(&self) -> ShadingLanguage {
+        self.implementation.clone()
+    }
+
+    /// Sets the strength with which the loudness raises the background
+    /// brightness
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the background
+    /// brightness
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse.max(0.0);
+        self
+    }
+
+    /// Gets the strength with which the loudness raises the background
+    /// brightness
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the number of samples per pixel along each axis. Every pixel is
+    /// sampled `supersampling²` times, which smooths the edges of the spheres
+    /// at the cost of rendering time.
+    pub fn with_supersampling(mut self, supersampling: u32) -> Self {
+        self.set_supersampling(supersampling);
+        self
+    }
+
+    /// Sets the number of samples per pixel along each axis. Every pixel is
+    /// sampled `supersampling²` times, which smooths the edges of the spheres
+    /// at the cost of rendering time.
+    pub fn set_supersampling(&mut self, supersampling: u32) -> &mut Self {
+        self.supersampling = supersampling.max(1);
+        self
+    }
+
+    /// Gets the number of samples per pixel along each axis
+    pub fn supersampling(&self) -> u32 {
+        self.supersampling
+    }
+
+    /// Sets the number of bounces after which paths are terminated randomly
+    /// depending on their throughput (russian roulette). This makes high
+    /// bounce counts affordable, as dark paths end early. Zero disables the
+    /// termination.
+    pub fn with_roulette_depth(mut self, roulette_depth: u32) -> Self {
+        self.set_roulette_depth(roulette_depth);
+        self
+    }
+
+    /// Sets the number of bounces after which paths are terminated randomly
+    /// depending on their throughput (russian roulette). This makes high
+    /// bounce counts affordable, as dark paths end early. Zero disables the
+    /// termination.
+    pub fn set_roulette_depth(&mut self, roulette_depth: u32) -> &mut Self {
+        self.roulette_depth = roulette_depth;
+        self
+    }
+
+    /// Gets the number of bounces after which paths are terminated randomly
+    pub fn roulette_depth(&self) -> u32 {
+        self.roulette_depth
+    }
+
+    /// Sets the operator mapping the radiance to displayable colors. HDR
+    /// outputs ignore it, as they roll off the highlights themselves.
+    pub fn with_tonemapper(mut self, tonemapper: Tonemapper) -> Self {
+        self.set_tonemapper(tonemapper);
+        self
+    }
+
+    /// Sets the operator mapping the radiance to displayable colors. HDR
+    /// outputs ignore it, as they roll off the highlights themselves.
+    pub fn set_tonemapper(&mut self, tonemapper: Tonemapper) -> &mut Self {
+        self.tonemapper = tonemapper;
+        self
+    }
+
+    /// Gets the operator mapping the radiance to displayable colors
+    pub fn tonemapper(&self) -> Tonemapper {
+        self.tonemapper
+    }
+
+    /// Sets the exposure in stops, which scales the radiance before
+    /// tonemapping
+    pub fn with_exposure(mut self, exposure: f32) -> Self {
+        self.set_exposure(exposure);
+        self
+    }
+
+    /// Sets the exposure in stops, which scales the radiance before
+    /// tonemapping
+    pub fn set_exposure(&mut self, exposure: f32) -> &mut Self {
+        self.exposure = exposure;
+        self
+    }
+
+    /// Gets the exposure in stops
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Sets the contrast around middle grey applied after tonemapping
+    pub fn with_contrast(mut self, contrast: f32) -> Self {
+        self.set_contrast(contrast);
+        self
+    }
+
+    /// Sets the contrast around middle grey applied after tonemapping
+    pub fn set_contrast(&mut self, contrast: f32) -> &mut Self {
+        self.contrast = contrast;
+        self
+    }
+
+    /// Gets the contrast around middle grey
+    pub fn contrast(&self) -> f32 {
+        self.contrast
+    }
+
+    /// Sets the saturation applied after tonemapping. Zero renders greyscale.
+    pub fn with_saturation(mut self, saturation: f32) -> Self {
+        self.set_saturation(saturation);
+        self
+    }
+
+    /// Sets the saturation applied after tonemapping. Zero renders greyscale.
+    pub fn set_saturation(&mut self, saturation: f32) -> &mut Self {
+        self.saturation = saturation;
+        self
+    }
+
+    /// Gets the saturation
+    pub fn saturation(&self) -> f32 {
+        self.saturation
+    }
+
+    /// Sets the gamma applied after tonemapping. Values above one brighten
+    /// the dark colors.
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.set_gamma(gamma);
+        self
+    }
+
+    /// Sets the gamma applied after tonemapping. Values above one brighten
+    /// the dark colors.
+    pub fn set_gamma(&mut self, gamma: f32) -> &mut Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Gets the gamma
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    /// Sets the density of the fog the camera looks through. The fog scatters
+    /// the light of the lights, which shows their beams as light shafts.
+    /// Zero disables the fog.
+    pub fn with_fog_density(mut self, fog_density: f32) -> Self {
+        self.set_fog_density(fog_density);
+        self
+    }
+
+    /// Sets the density of the fog the camera looks through. The fog scatters
+    /// the light of the lights, which shows their beams as light shafts.
+    /// Zero disables the fog.
+    pub fn set_fog_density(&mut self, fog_density: f32) -> &mut Self {
+        self.fog_density = fog_density;
+        self
+    }
+
+    /// Gets the density of the fog
+    pub fn fog_density(&self) -> f32 {
+        self.fog_density
+    }
+
+    /// Sets the color of the light scattered by the fog
+    pub fn with_fog_color(mut self, fog_color: Vec3A) -> Self {
+        self.set_fog_color(fog_color);
+        self
+    }
+
+    /// Sets the color of the light scattered by the fog
+    pub fn set_fog_color(&mut self, fog_color: Vec3A) -> &mut Self {
+        self.fog_color = fog_color;
+        self
+    }
+
+    /// Gets the color of the light scattered by the fog
+    pub fn fog_color(&self) -> Vec3A {
+        self.fog_color
+    }
+
+    /// Sets the distance from the camera the fog reaches
+    pub fn with_fog_distance(mut self, fog_distance: f32) -> Self {
+        self.set_fog_distance(fog_distance);
+        self
+    }
+
+    /// Sets the distance from the camera the fog reaches
+    pub fn set_fog_distance(&mut self, fog_distance: f32) -> &mut Self {
+        self.fog_distance = fog_distance;
+        self
+    }
+
+    /// Gets the distance from the camera the fog reaches
+    pub fn fog_distance(&self) -> f32 {
+        self.fog_distance
+    }
+
+    /// Sets the number of points per pixel at which the fog is lit. More
+    /// points reduce the noise of the light shafts, but every point casts a
+    /// shadow ray per light.
+    pub fn with_fog_steps(mut self, fog_steps: u32) -> Self {
+        self.set_fog_steps(fog_steps);
+        self
+    }
+
+    /// Sets the number of points per pixel at which the fog is lit. More
+    /// points reduce the noise of the light shafts, but every point casts a
+    /// shadow ray per light.
+    pub fn set_fog_steps(&mut self, fog_steps: u32) -> &mut Self {
+        self.fog_steps = fog_steps;
+        self
+    }
+
+    /// Gets the number of points per pixel at which the fog is lit
+    pub fn fog_steps(&self) -> u32 {
+        self.fog_steps
+    }
+
+    /// Sets if the frames of a static scene are accumulated. Every frame then
+    /// samples the pixels at other positions and is averaged with the
+    /// previous frames until the scene changes.
+    pub fn with_accumulation(mut self, accumulation: bool) -> Self {
+        self.set_accumulation(accumulation);
+        self
+    }
+
+    /// Sets if the frames of a static scene are accumulated. Every frame then
+    /// samples the pixels at other positions and is averaged with the
+    /// previous frames until the scene changes.
+    pub fn set_accumulation(&mut self, accumulation: bool) -> &mut Self {
+        self.accumulation = accumulation;
+
+        // the accumulated frames are only kept while they are used
+        if !accumulation {
+            self.accumulator = Accumulation::default();
+        }
+
+        self
+    }
+
+    /// Gets if the frames of a static scene are accumulated
+    pub fn accumulation(&self) -> bool {
+        self.accumulation
+    }
+
+    /// Returns the number of frames averaged in the last rendered frame
+    pub fn accumulated_frames(&self) -> u32 {
+        self.accumulator.frames()
+    }
+
+    /// Sets the base seed of stochastic effects. Changing it restarts the
+    /// stream of frame seeds.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.set_seed(seed);
+        self
+    }
+
+    /// Sets the base seed of stochastic effects. Changing it restarts the
+    /// stream of frame seeds.
+    pub fn set_seed(&mut self, seed: u32) -> &mut Self {
+        self.seed_stream.set_seed(seed);
+        self
+    }
+
+    /// Gets the base seed of stochastic effects
+    pub fn seed(&self) -> u32 {
+        self.seed_stream.seed()
+    }
+
+    /// Sets which spheres are culled before rendering
+    pub fn with_culling(mut self, culling: SphereCulling) -> Self {
+        self.set_culling(culling);
+        self
+    }
+
+    /// Sets which spheres are culled before rendering
+    pub fn set_culling(&mut self, culling: SphereCulling) -> &mut Self {
+        self.culling = culling;
+        self
+    }
+
+    /// Gets which spheres are culled before rendering
+    pub fn culling(&self) -> SphereCulling {
+        self.culling
+    }
+
+    /// Sets the projected radius in pixels below which spheres are culled by
+    /// [`SphereCulling::LevelOfDetail`]
+    pub fn with_cull_threshold(mut self, cull_threshold: f32) -> Self {
+        self.set_cull_threshold(cull_threshold);
+        self
+    }
+
+    /// Sets the projected radius in pixels below which spheres are culled by
+    /// [`SphereCulling::LevelOfDetail`]
+    pub fn set_cull_threshold(&mut self, cull_threshold: f32) -> &mut Self {
+        self.cull_threshold = cull_threshold.max(0.0);
+        self
+    }
+
+    /// Gets the projected radius in pixels below which spheres are culled by
+    /// [`SphereCulling::LevelOfDetail`]
+    pub fn cull_threshold(&self) -> f32 {
+        self.cull_threshold
+    }
+
+    /// Sets if every frame is additionally rendered with both shading
+    /// languages to compare them. This stalls every frame.
+    pub fn with_parity(mut self, parity: bool) -> Self {
+        self.set_parity(parity);
+        self
+    }
+
+    /// Sets if every frame is additionally rendered with both shading
+    /// languages to compare them. This stalls every frame.
+    pub fn set_parity(&mut self, parity: bool) -> &mut Self {
+        self.parity = parity;
+        if !parity {
+            self.parity_report = None;
+        }
+        self
+    }
+
+    /// Gets if every frame is additionally rendered with both shading
+    /// languages to compare them
+    pub fn parity(&self) -> bool {
+        self.parity
+    }
+
+    /// Returns the difference between the shading languages in the last
+    /// rendered frame. Only available while the parity mode is enabled and the
+    /// rust-gpu shader is supported.
+    pub fn parity_report(&self) -> Option<ParityReport> {
+        self.parity_report
+    }
+
+    /// Records the culling pass, which compacts the visible spheres into a
+    /// separate buffer and writes their count into the arguments of the
+    /// raytracer. Returns `None` if there are no spheres to cull.
+    fn cull_spheres(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        camera: &SceneCamera,
+        spheres_buffer: &TypedBuffer<Arc<Buffer>, [Sphere]>,
+        args_buffer: &TypedBuffer<Buffer, BasicRaytracingArgsBundle>,
+        sphere_count_offset: u64,
+    ) -> Option<TypedBuffer<Arc<Buffer>, [Sphere]>> {
+        if spheres_buffer.is_empty() {
+            return None;
+        }
+
+        let culling_pipeline = self
+            .culling_pipeline
+            .get_or_insert_with(|| SphereCullingPipeline::new(device));
+
+        let cull_threshold = match self.culling {
+            SphereCulling::LevelOfDetail => self.cull_threshold,
+            _ => 0.0,
+        };
+
+        let culling_args = CullingArgs::new(camera, cull_threshold);
+        let count_offset = (&culling_args.sphere_count as *const u32 as usize
+            - &culling_args as *const CullingArgs as usize) as u64;
+
+        let culling_args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
             label: None,
-            usage: BufferUsages::STORAGE,
-            value: &args,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            value: &culling_args,
         });
 
-        let layout = pipeline.get_bind_group_layout(0);
+        let culled_spheres_buffer = self
+            .culled_spheres_buffer
+            .allocate::<[Sphere]>(device, spheres_buffer.len());
+
+        let memory_tracker = command_queue.memory_tracker();
+        memory_tracker.track_buffer(std::mem::size_of_val(&culling_args) as u64);
+        memory_tracker.track_buffer(culled_spheres_buffer.size() as u64);
 
         let bind_group = device.create_bind_group(&BindGroupDescriptor {
             label: None,
             entries: &[
-                args_buffer.bind_group_entry(0).unwrap(),
+                culling_args_buffer.bind_group_entry(0).unwrap(),
                 spheres_buffer.bind_group_entry(1).unwrap(),
-                rects_buffer.bind_group_entry(2).unwrap(),
-                point_lights_buffer.bind_group_entry(3).unwrap(),
+                culled_spheres_buffer.bind_group_entry(2).unwrap(),
             ],
-            layout: &layout,
+            layout: &culling_pipeline.0.get_bind_group_layout(0),
         });
 
         let command_encoder = command_queue.command_encoder(device);
 
         {
-            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+            let mut compute_pass =
+                command_encoder.begin_compute_pass(&ComputePassDescriptor { label: None });
+
+            compute_pass.set_pipeline(&culling_pipeline.0);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+
+            compute_pass.dispatch(
+                (spheres_buffer.len() as u32).div_ceil(CULLING_WORKGROUP_SIZE),
+                1,
+                1,
+            );
+        }
+
+        command_encoder.copy_buffer_to_buffer(
+            &culling_args_buffer,
+            count_offset,
+            args_buffer,
+            args_buffer.offset() as u64 + sphere_count_offset,
+            std::mem::size_of::<u32>() as u64,
+        );
+
+        Some(culled_spheres_buffer)
+    }
+
+    /// Returns the statistics of the last read back frame. They are counted
+    /// by the WGSL implementation over all samples of the frame and stay
+    /// unchanged while the Rust implementation is used.
+    #[cfg(feature = "statistics")]
+    pub fn statistics(&self) -> RaytracerStatistics {
+        self.statistics
+    }
+}
+
+/// Stores the settings of the [`Raytracer`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RaytracerSettings {
+    /// The used [`ShadingLanguage`]
+    pub shading_language: ShadingLanguage,
+    /// The outcome of validating the [`ShadingLanguage::Rust`]
+    /// implementation for the active adapter. This is only informational and
+    /// ignored when applying the settings.
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub shader_validation: ShaderValidation,
+    /// The strength with which the loudness raises the background brightness
+    pub pulse: f32,
+    /// The number of samples per pixel along each axis
+    pub supersampling: u32,
+    /// The number of bounces after which dark paths are terminated randomly.
+    /// Zero disables the termination.
+    pub roulette_depth: u32,
+    /// The operator mapping the radiance to displayable colors
+    pub tonemapper: Tonemapper,
+    /// The exposure in stops applied before tonemapping
+    pub exposure: f32,
+    /// The contrast around middle grey applied after tonemapping
+    pub contrast: f32,
+    /// The saturation applied after tonemapping
+    pub saturation: f32,
+    /// The gamma applied after tonemapping
+    pub gamma: f32,
+    /// The density of the fog, where zero disables the fog
+    pub fog_density: f32,
+    /// The color of the light scattered by the fog
+    pub fog_color: Vec3A,
+    /// The distance from the camera the fog reaches
+    pub fog_distance: f32,
+    /// The number of points per pixel at which the fog is lit
+    pub fog_steps: u32,
+    /// Whether the frames of a static scene are accumulated
+    pub accumulation: bool,
+    /// The number of frames averaged in the last rendered frame. This is
+    /// only informational and ignored when applying the settings.
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub accumulated_frames: u32,
+    /// The base seed of stochastic effects
+    pub seed: u32,
+    /// Specifies which spheres are culled before rendering
+    pub culling: SphereCulling,
+    /// The projected radius in pixels below which spheres are culled by
+    /// [`SphereCulling::LevelOfDetail`]
+    pub cull_threshold: f32,
+    /// Whether every frame is additionally rendered with both shading
+    /// languages to compare them
+    pub parity: bool,
+    /// The difference between the shading languages in the last rendered
+    /// frame. This is only informational and ignored when applying the
+    /// settings.
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub parity_report: Option<ParityReport>,
+    /// The statistics of the last rendered frame. This is only informational
+    /// and ignored when applying the settings.
+    #[cfg(feature = "statistics")]
+    #[cfg_attr(feature = "config", serde(skip))]
+    pub statistics: RaytracerStatistics,
+}
+
+impl Default for RaytracerSettings {
+    fn default() -> Self {
+        Self {
+            shading_language: ShadingLanguage::Rust,
+            shader_validation: ShaderValidation::Passed,
+            pulse: RAYTRACER_PULSE,
+            supersampling: RAYTRACER_SUPERSAMPLING,
+            roulette_depth: RAYTRACER_ROULETTE_DEPTH,
+            tonemapper: RAYTRACER_TONEMAPPER,
+            exposure: RAYTRACER_EXPOSURE,
+            contrast: RAYTRACER_CONTRAST,
+            saturation: RAYTRACER_SATURATION,
+            gamma: RAYTRACER_GAMMA,
+            fog_density: RAYTRACER_FOG_DENSITY,
+            fog_color: RAYTRACER_FOG_COLOR,
+            fog_distance: RAYTRACER_FOG_DISTANCE,
+            fog_steps: RAYTRACER_FOG_STEPS,
+            accumulation: false,
+            accumulated_frames: 0,
+            seed: SEED,
+            culling: SphereCulling::Disabled,
+            cull_threshold: RAYTRACER_CULL_THRESHOLD,
+            parity: false,
+            parity_report: None,
+            #[cfg(feature = "statistics")]
+            statistics: RaytracerStatistics::default(),
+        }
+    }
+}
+
+impl Module for Raytracer {
+    type Settings = RaytracerSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_implementation(settings.shading_language)
+            .set_pulse(settings.pulse)
+            .set_supersampling(settings.supersampling)
+            .set_roulette_depth(settings.roulette_depth)
+            .set_tonemapper(settings.tonemapper)
+            .set_exposure(settings.exposure)
+            .set_contrast(settings.contrast)
+            .set_saturation(settings.saturation)
+            .set_gamma(settings.gamma)
+            .set_fog_density(settings.fog_density)
+            .set_fog_color(settings.fog_color)
+            .set_fog_distance(settings.fog_distance)
+            .set_fog_steps(settings.fog_steps)
+            .set_accumulation(settings.accumulation)
+            .set_seed(settings.seed)
+            .set_culling(settings.culling)
+            .set_cull_threshold(settings.cull_threshold)
+            .set_parity(settings.parity)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        RaytracerSettings {
+            shading_language: self.implementation(),
+            shader_validation: self.shader_validation().clone(),
+            pulse: self.pulse(),
+            supersampling: self.supersampling(),
+            roulette_depth: self.roulette_depth(),
+            tonemapper: self.tonemapper(),
+            exposure: self.exposure(),
+            contrast: self.contrast(),
+            saturation: self.saturation(),
+            gamma: self.gamma(),
+            fog_density: self.fog_density(),
+            fog_color: self.fog_color(),
+            fog_distance: self.fog_distance(),
+            fog_steps: self.fog_steps(),
+            accumulation: self.accumulation(),
+            accumulated_frames: self.accumulated_frames(),
+            seed: self.seed(),
+            culling: self.culling(),
+            cull_threshold: self.cull_threshold(),
+            parity: self.parity(),
+            parity_report: self.parity_report(),
+            #[cfg(feature = "statistics")]
+            statistics: self.statistics(),
+        }
+    }
+}
+
+impl Default for Raytracer {
+    fn default() -> Self {
+        Self {
+            implementation: ShadingLanguage::Rust,
+            pulse: RAYTRACER_PULSE,
+            loudness: 0.0,
+            supersampling: RAYTRACER_SUPERSAMPLING,
+            roulette_depth: RAYTRACER_ROULETTE_DEPTH,
+            tonemapper: RAYTRACER_TONEMAPPER,
+            exposure: RAYTRACER_EXPOSURE,
+            contrast: RAYTRACER_CONTRAST,
+            saturation: RAYTRACER_SATURATION,
+            gamma: RAYTRACER_GAMMA,
+            fog_density: RAYTRACER_FOG_DENSITY,
+            fog_color: RAYTRACER_FOG_COLOR,
+            fog_distance: RAYTRACER_FOG_DISTANCE,
+            fog_steps: RAYTRACER_FOG_STEPS,
+            accumulation: false,
+            accumulator: Accumulation::default(),
+            seed_stream: SeedStream::default(),
+            uploader: Uploader::default(),
+            spheres_buffer: DynamicBuffer::new(Some("spheres"), BufferUsages::STORAGE),
+            rects_buffer: DynamicBuffer::new(Some("rects"), BufferUsages::STORAGE),
+            point_lights_buffer: DynamicBuffer::new(Some("point-lights"), BufferUsages::STORAGE),
+            triangles_buffer: DynamicBuffer::new(Some("triangles"), BufferUsages::STORAGE),
+            sphere_nodes_buffer: DynamicBuffer::new(Some("sphere-nodes"), BufferUsages::STORAGE),
+            triangle_nodes_buffer: DynamicBuffer::new(
+                Some("triangle-nodes"),
+                BufferUsages::STORAGE,
+            ),
+            planes_buffer: DynamicBuffer::new(Some("planes"), BufferUsages::STORAGE),
+            discs_buffer: DynamicBuffer::new(Some("discs"), BufferUsages::STORAGE),
+            capsules_buffer: DynamicBuffer::new(Some("capsules"), BufferUsages::STORAGE),
+            instances_buffer: DynamicBuffer::new(Some("instances"), BufferUsages::STORAGE),
+            materials_buffer: DynamicBuffer::new(Some("materials"), BufferUsages::STORAGE),
+            directional_lights_buffer: DynamicBuffer::new(
+                Some("directional-lights"),
+                BufferUsages::STORAGE,
+            ),
+            spot_lights_buffer: DynamicBuffer::new(Some("spot-lights"), BufferUsages::STORAGE),
+            environment_texture: None,
+            surface_texture: None,
+            normal_map: None,
+            culling: SphereCulling::Disabled,
+            cull_threshold: RAYTRACER_CULL_THRESHOLD,
+            culling_pipeline: None,
+            culled_spheres_buffer: DynamicBuffer::new(
+                Some("culled-spheres"),
+                BufferUsages::STORAGE,
+            ),
+            rust_pipeline: None,
+            wgsl_pipeline: None,
+            shader_validation: ShaderValidation::Passed,
+            parity: false,
+            parity_check: ParityCheck::default(),
+            parity_report: None,
+            statistics_buffer: None,
+            #[cfg(feature = "statistics")]
+            statistics_readback: None,
+            #[cfg(feature = "statistics")]
+            statistics: RaytracerStatistics::default(),
+        }
+    }
+}
+
+impl Pipeline<BasicRaytracerScene> for Raytracer {
+    fn prepare(&mut self, renderer: &WGPURenderer) {
+        self.shader_validation = renderer.shader_validation().clone();
+        self.set_implementation(self.implementation());
+    }
+
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
+    fn render(
+        &mut self,
+        scene: BasicRaytracerScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let parity =
+            self.parity && self.shader_validation.passed() && ParityCheck::supports(output_format);
+
+        if parity || self.implementation == ShadingLanguage::Rust {
+            let rust_pipeline = self
+                .rust_pipeline
+                .get_or_insert_with(|| RaytracerRustPipeline::new(device, output_format));
+
+            if rust_pipeline.1 != output_format {
+                *rust_pipeline = RaytracerRustPipeline::new(device, output_format);
+            }
+        }
+
+        if parity || self.implementation == ShadingLanguage::WGSL {
+            let wgsl_pipeline = self
+                .wgsl_pipeline
+                .get_or_insert_with(|| RaytracerWGSLPipeline::new(device, output_format));
+
+            if wgsl_pipeline.1 != output_format {
+                *wgsl_pipeline = RaytracerWGSLPipeline::new(device, output_format);
+            }
+        }
+
+        let memory_tracker = command_queue.memory_tracker();
+
+        self.uploader.recall(device);
+
+        let spheres = scene.shapes::<Sphere>();
+        let spheres_bounding_box = spheres
+            .map(ShapeCollection::bounding_box)
+            .cloned()
+            .unwrap_or_else(AABB::empty);
+        let sphere_nodes = spheres.map(ShapeCollection::nodes).unwrap_or(&[]);
+
+        // a reduced hierarchy would miss shapes, so it is dropped instead
+        let sphere_nodes = match memory_tracker.fit("Sphere Nodes", sphere_nodes) {
+            nodes if nodes.len() == sphere_nodes.len() => nodes,
+            _ => &[],
+        };
+        let spheres = memory_tracker.fit(
+            "Spheres",
+            spheres.map(ShapeCollection::shapes).unwrap_or(&[]),
+        );
+
+        let spheres_buffer =
+            self.uploader
+                .upload(device, command_queue, &mut self.spheres_buffer, spheres);
+
+        let rects = scene.shapes::<Rect>();
+        let rects_bounding_box = rects
+            .map(ShapeCollection::bounding_box)
+            .cloned()
+            .unwrap_or_else(AABB::empty);
+        let rects = memory_tracker.fit("Rects", rects.map(ShapeCollection::shapes).unwrap_or(&[]));
+
+        let rects_buffer =
+            self.uploader
+                .upload(device, command_queue, &mut self.rects_buffer, rects);
+
+        let point_lights = memory_tracker.fit(
+            "Point Lights",
+            scene
+                .lights::<PointLight>()
+                .map(Vec::as_slice)
+                .unwrap_or(&[]),
+        );
+
+        let point_lights_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.point_lights_buffer,
+            point_lights,
+        );
+
+        let directional_lights = memory_tracker.fit(
+            "Directional Lights",
+            scene
+                .lights::<DirectionalLight>()
+                .map(Vec::as_slice)
+                .unwrap_or(&[]),
+        );
+
+        // the placeholder lights nothing as its intensity is zero
+        let placeholder_directional_light = [DirectionalLight::new(Vec3A::Y, Vec3A::ZERO)];
+
+        let directional_lights_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.directional_lights_buffer,
+            match directional_lights.is_empty() {
+                true => &placeholder_directional_light[..],
+                false => directional_lights,
+            },
+        );
+
+        let spot_lights = memory_tracker.fit(
+            "Spot Lights",
+            scene
+                .lights::<SpotLight>()
+                .map(Vec::as_slice)
+                .unwrap_or(&[]),
+        );
+
+        // the placeholder lights nothing as its intensity is zero
+        let placeholder_spot_light = [SpotLight::new(Vec3A::ZERO, Vec3A::Y, 0.0, 0.0, Vec3A::ZERO)];
+
+        let spot_lights_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.spot_lights_buffer,
+            match spot_lights.is_empty() {
+                true => &placeholder_spot_light[..],
+                false => spot_lights,
+            },
+        );
+
+        let triangles = scene.shapes::<Triangle>();
+        let triangles_bounding_box = triangles
+            .map(ShapeCollection::bounding_box)
+            .cloned()
+            .unwrap_or_else(AABB::empty);
+        let triangle_nodes = triangles.map(ShapeCollection::nodes).unwrap_or(&[]);
+        let triangle_nodes = match memory_tracker.fit("Triangle Nodes", triangle_nodes) {
+            nodes if nodes.len() == triangle_nodes.len() => nodes,
+            _ => &[],
+        };
+        let triangles = memory_tracker.fit(
+            "Triangles",
+            triangles.map(ShapeCollection::shapes).unwrap_or(&[]),
+        );
+
+        // empty buffers can't be bound, the placeholder is never hit as the
+        // bounding box of the triangles is empty
+        let placeholder_triangle = [Triangle::new(Vec3A::ZERO, Vec3A::ZERO, Vec3A::ZERO, 0)];
+
+        let triangles_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.triangles_buffer,
+            match triangles.is_empty() {
+                true => &placeholder_triangle[..],
+                false => triangles,
+            },
+        );
+
+        // the placeholder node is never visited as the node counts are zero
+        let placeholder_node = [BvhNode::inner(AABB::empty(), 1)];
+
+        // the culling pass compacts the spheres, which breaks the hierarchy
+        let sphere_nodes = match self.culling {
+            SphereCulling::Disabled => sphere_nodes,
+            _ => &[],
+        };
+
+        let sphere_nodes_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.sphere_nodes_buffer,
+            match sphere_nodes.is_empty() {
+                true => &placeholder_node[..],
+                false => sphere_nodes,
+            },
+        );
+
+        let triangle_nodes_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.triangle_nodes_buffer,
+            match triangle_nodes.is_empty() {
+                true => &placeholder_node[..],
+                false => triangle_nodes,
+            },
+        );
+
+        let planes = memory_tracker.fit(
+            "Planes",
+            scene
+                .shapes::<Plane>()
+                .map(ShapeCollection::shapes)
+                .unwrap_or(&[]),
+        );
+
+        let discs = scene.shapes::<Disc>();
+        let discs_bounding_box = discs
+            .map(ShapeCollection::bounding_box)
+            .cloned()
+            .unwrap_or_else(AABB::empty);
+        let discs = memory_tracker.fit("Discs", discs.map(ShapeCollection::shapes).unwrap_or(&[]));
+
+        // the placeholders are never hit as the plane and disc counts are zero
+        let placeholder_plane = [Plane::new(Vec3A::ZERO, Vec3A::Y, 0)];
+        let placeholder_disc = [Disc::new(Vec3A::ZERO, Vec3A::Y, 0.0, 0)];
+
+        let planes_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.planes_buffer,
+            match planes.is_empty() {
+                true => &placeholder_plane[..],
+                false => planes,
+            },
+        );
+
+        let discs_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.discs_buffer,
+            match discs.is_empty() {
+                true => &placeholder_disc[..],
+                false => discs,
+            },
+        );
+
+        let capsules = scene.shapes::<Capsule>();
+        let capsules_bounding_box = capsules
+            .map(ShapeCollection::bounding_box)
+            .cloned()
+            .unwrap_or_else(AABB::empty);
+        let capsules = memory_tracker.fit(
+            "Capsules",
+            capsules.map(ShapeCollection::shapes).unwrap_or(&[]),
+        );
+
+        // the placeholder is never hit as the capsule count is zero
+        let placeholder_capsule = [Capsule::new(Vec3A::ZERO, Vec3A::ZERO, 0.0, 0)];
+
+        let capsules_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.capsules_buffer,
+            match capsules.is_empty() {
+                true => &placeholder_capsule[..],
+                false => capsules,
+            },
+        );
+
+        let instances = scene.shapes::<Instance>();
+        let instances_bounding_box = instances
+            .map(ShapeCollection::bounding_box)
+            .cloned()
+            .unwrap_or_else(AABB::empty);
+        let instances = memory_tracker.fit(
+            "Instances",
+            instances.map(ShapeCollection::shapes).unwrap_or(&[]),
+        );
+
+        // the placeholder is never hit as the instance count is zero
+        let placeholder_instance = [Instance::new(InstanceShape::Sphere, Mat4::IDENTITY, 0)];
+
+        let instances_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.instances_buffer,
+            match instances.is_empty() {
+                true => &placeholder_instance[..],
+                false => instances,
+            },
+        );
+
+        // the materials are not fitted, as every shape relies on its material
+        let materials = scene.materials();
+
+        // the placeholder is never used as there is no shape referencing it
+        let placeholder_material = [Material::new(Vec3A::ZERO)];
+
+        let materials_buffer = self.uploader.upload(
+            device,
+            command_queue,
+            &mut self.materials_buffer,
+            match materials.is_empty() {
+                true => &placeholder_material[..],
+                false => materials,
+            },
+        );
+        memory_tracker.track_buffer(std::mem::size_of_val(materials) as u64);
+
+        self.uploader.finish();
+
+        let (frame, seed) = self.seed_stream.next_frame();
+
+        let mut args = BasicRaytracingArgsBundle {
+            raytracer_args: RaytracerArgs {
+                camera: scene.camera.clone(),
+                background: scene.background.clone(),
+                bounces: scene.bounces,
+                loudness: self.loudness * self.pulse,
+                frame,
+                seed,
+                supersampling: self.supersampling,
+                jitter: 0,
+                roulette_depth: self.roulette_depth,
+                tonemapper: self.tonemapper,
+                hdr: (output_format == HDR_FORMAT) as u32,
+                color_grading: ColorGrading {
+                    exposure: self.exposure,
+                    contrast: self.contrast,
+                    saturation: self.saturation,
+                    gamma: self.gamma,
+                },
+                fog: Fog {
+                    color: self.fog_color,
+                    density: self.fog_density,
+                    distance: self.fog_distance,
+                    steps: self.fog_steps,
+                },
+            },
+            scene_args: SceneArgs {
+                spheres_bounding_box,
+                rects_bounding_box,
+                triangles_bounding_box,
+                discs_bounding_box,
+                capsules_bounding_box,
+                instances_bounding_box,
+                sphere_count: spheres.len() as u32,
+                sphere_node_count: sphere_nodes.len() as u32,
+                triangle_node_count: triangle_nodes.len() as u32,
+                plane_count: planes.len() as u32,
+                disc_count: discs.len() as u32,
+                capsule_count: capsules.len() as u32,
+                instance_count: instances.len() as u32,
+            },
+        };
+
+        let viewport = Viewport {
+            offset: scene.camera.screen_offset(),
+            size: scene.camera.screen_size(),
+        };
+
+        if self.accumulation {
+            let mut hasher = DefaultHasher::new();
+
+            // the frame and the seed change every frame, even if the scene
+            // stays the same
+            let mut raytracer_args = args.raytracer_args.clone();
+            raytracer_args.frame = 0;
+            raytracer_args.seed = 0;
+
+            hash_bytes(&mut hasher, &raytracer_args);
+            hash_bytes(&mut hasher, &args.scene_args);
+            hash_bytes(&mut hasher, spheres);
+            hash_bytes(&mut hasher, sphere_nodes);
+            hash_bytes(&mut hasher, rects);
+            hash_bytes(&mut hasher, point_lights);
+            hash_bytes(&mut hasher, triangles);
+            hash_bytes(&mut hasher, triangle_nodes);
+            hash_bytes(&mut hasher, planes);
+            hash_bytes(&mut hasher, discs);
+            hash_bytes(&mut hasher, capsules);
+            hash_bytes(&mut hasher, instances);
+            hash_bytes(&mut hasher, materials);
+            hash_bytes(&mut hasher, directional_lights);
+            hash_bytes(&mut hasher, spot_lights);
+            hasher.write_usize(
+                scene
+                    .environment_map()
+                    .map_or(0, |image| Arc::as_ptr(image) as usize),
+            );
+            hasher.write_usize(
+                scene
+                    .surface_texture()
+                    .map_or(0, |image| Arc::as_ptr(image) as usize),
+            );
+            hasher.write_usize(
+                scene
+                    .normal_map()
+                    .map_or(0, |image| Arc::as_ptr(image) as usize),
+            );
+            hasher.write_u8(self.implementation.clone() as u8);
+            hasher.write_u8(self.culling as u8);
+            hasher.write_u32(self.cull_threshold.to_bits());
+
+            let extent = (viewport.offset + viewport.size).ceil();
+
+            self.accumulator.begin(
+                device,
+                command_queue,
+                output_format,
+                extent.x as u32,
+                extent.y as u32,
+                hasher.finish(),
+            );
+
+            // the first frame samples the cell centers like without
+            // accumulation, so the scene looks the same until it stops
+            args.raytracer_args.jitter = (self.accumulator.frames() > 0) as u32;
+        }
+
+        #[cfg(feature = "statistics")]
+        if let Some(statistics) = self
+            .statistics_readback
+            .as_mut()
+            .and_then(StatisticsReadback::poll)
+        {
+            self.statistics = statistics;
+        }
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            value: &args,
+        });
+        memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+        let culled_spheres_buffer = match self.culling {
+            SphereCulling::Disabled => None,
+            _ => {
+                let sphere_count_offset = (&args.scene_args.sphere_count as *const u32 as usize
+                    - &args as *const BasicRaytracingArgsBundle as usize)
+                    as u64;
+
+                self.cull_spheres(
+                    device,
+                    command_queue,
+                    &scene.camera,
+                    &spheres_buffer,
+                    &args_buffer,
+                    sphere_count_offset,
+                )
+            }
+        };
+        let spheres_buffer = culled_spheres_buffer.as_ref().unwrap_or(&spheres_buffer);
+
+        let environment_map = scene.environment_map();
+
+        if !matches!(&self.environment_texture, Some(texture) if texture.matches(environment_map)) {
+            self.environment_texture = Some(ImageTexture::new(
+                device,
+                command_queue.queue(),
+                environment_map,
+                Vec3A::ONE,
+                "environment-map",
+                AddressMode::ClampToEdge,
+            ));
+        }
+
+        let environment_texture = self.environment_texture.as_ref().unwrap();
+        memory_tracker.track_texture(
+            environment_texture.width,
+            environment_texture.height,
+            IMAGE_TEXTURE_FORMAT,
+        );
+
+        let surface_texture = scene.surface_texture();
+
+        // textures repeat on both axes, unlike the environment map
+        if !matches!(&self.surface_texture, Some(texture) if texture.matches(surface_texture)) {
+            self.surface_texture = Some(ImageTexture::new(
+                device,
+                command_queue.queue(),
+                surface_texture,
+                Vec3A::ONE,
+                "surface-texture",
+                AddressMode::Repeat,
+            ));
+        }
+
+        let surface_texture = self.surface_texture.as_ref().unwrap();
+        memory_tracker.track_texture(
+            surface_texture.width,
+            surface_texture.height,
+            IMAGE_TEXTURE_FORMAT,
+        );
+
+        let normal_map = scene.normal_map();
+
+        if !matches!(&self.normal_map, Some(texture) if texture.matches(normal_map)) {
+            self.normal_map = Some(ImageTexture::new(
+                device,
+                command_queue.queue(),
+                normal_map,
+                FLAT_NORMAL,
+                "normal-map",
+                AddressMode::Repeat,
+            ));
+        }
+
+        let normal_map = self.normal_map.as_ref().unwrap();
+        memory_tracker.track_texture(normal_map.width, normal_map.height, IMAGE_TEXTURE_FORMAT);
+
+        // the WGSL shader always declares the counters, they are only written
+        // with the statistics feature
+        let statistics_buffer = &*self.statistics_buffer.get_or_insert_with(|| {
+            device.create_buffer(&BufferDescriptor {
+                label: Some("statistics"),
+                size: STATISTICS_SIZE,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        // bind groups of pipelines with derived layouts are not interchangeable
+        let bind_group = |pipeline: &RenderPipeline, implementation: &ShadingLanguage| {
+            let mut entries = vec![
+                args_buffer.bind_group_entry(0).unwrap(),
+                spheres_buffer.bind_group_entry(1).unwrap(),
+                rects_buffer.bind_group_entry(2).unwrap(),
+                point_lights_buffer.bind_group_entry(3).unwrap(),
+                triangles_buffer.bind_group_entry(4).unwrap(),
+                sphere_nodes_buffer.bind_group_entry(5).unwrap(),
+                triangle_nodes_buffer.bind_group_entry(6).unwrap(),
+                planes_buffer.bind_group_entry(7).unwrap(),
+                discs_buffer.bind_group_entry(8).unwrap(),
+                capsules_buffer.bind_group_entry(9).unwrap(),
+                materials_buffer.bind_group_entry(10).unwrap(),
+                directional_lights_buffer.bind_group_entry(11).unwrap(),
+                spot_lights_buffer.bind_group_entry(12).unwrap(),
+                BindGroupEntry {
+                    binding: 13,
+                    resource: BindingResource::TextureView(&environment_texture.view),
+                },
+                BindGroupEntry {
+                    binding: 14,
+                    resource: BindingResource::Sampler(&environment_texture.sampler),
+                },
+                instances_buffer.bind_group_entry(15).unwrap(),
+                BindGroupEntry {
+                    binding: 16,
+                    resource: BindingResource::TextureView(&surface_texture.view),
+                },
+                BindGroupEntry {
+                    binding: 17,
+                    resource: BindingResource::Sampler(&surface_texture.sampler),
+                },
+                BindGroupEntry {
+                    binding: 18,
+                    resource: BindingResource::TextureView(&normal_map.view),
+                },
+                BindGroupEntry {
+                    binding: 19,
+                    resource: BindingResource::Sampler(&normal_map.sampler),
+                },
+            ];
+
+            if *implementation == ShadingLanguage::WGSL {
+                entries.push(BindGroupEntry {
+                    binding: 20,
+                    resource: statistics_buffer.as_entire_binding(),
+                });
+            }
+
+            device.create_bind_group(&BindGroupDescriptor {
                 label: None,
-                color_attachments: &[RenderPassColorAttachment {
-                    view: target_texture,
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Clear(Color::BLACK),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            });
+                entries: &entries,
+                layout: &pipeline.get_bind_group_layout(0),
+            })
+        };
 
-            render_pass.set_pipeline(&pipeline);
-            render_pass.set_bind_group(0, &bind_group, &[]);
+        let pipeline = match self.implementation {
+            ShadingLanguage::Rust => &self.rust_pipeline.as_ref().unwrap().0,
+            ShadingLanguage::WGSL => &self.wgsl_pipeline.as_ref().unwrap().0,
+        };
 
-            render_pass.draw(0..4, 0..1);
+        // only frames which are not in flight of being read back are counted
+        #[cfg(feature = "statistics")]
+        let count_statistics = self.implementation == ShadingLanguage::WGSL
+            && self
+                .statistics_readback
+                .get_or_insert_with(|| StatisticsReadback::new(device))
+                .is_idle();
+
+        #[cfg(feature = "statistics")]
+        if count_statistics {
+            command_queue
+                .command_encoder(device)
+                .clear_buffer(statistics_buffer, 0, None);
+        }
+
+        match self.accumulation {
+            true => {
+                draw_fullscreen(
+                    command_queue.command_encoder(device),
+                    pipeline,
+                    &bind_group(pipeline, &self.implementation),
+                    self.accumulator.frame_texture(),
+                    &viewport,
+                );
+
+                self.accumulator.finish(
+                    device,
+                    command_queue,
+                    output_format,
+                    target_texture,
+                    &viewport,
+                );
+            }
+            false => draw_fullscreen(
+                command_queue.command_encoder(device),
+                pipeline,
+                &bind_group(pipeline, &self.implementation),
+                target_texture,
+                &viewport,
+            ),
+        }
+
+        #[cfg(feature = "statistics")]
+        if count_statistics {
+            self.statistics_readback
+                .as_mut()
+                .unwrap()
+                .copy(command_queue.command_encoder(device), statistics_buffer);
         }
+
+        self.parity_report = if parity {
+            let pipelines = [
+                (
+                    &self.rust_pipeline.as_ref().unwrap().0,
+                    &ShadingLanguage::Rust,
+                ),
+                (
+                    &self.wgsl_pipeline.as_ref().unwrap().0,
+                    &ShadingLanguage::WGSL,
+                ),
+            ];
+
+            let extent = (viewport.offset + viewport.size).ceil();
+            let targets =
+                self.parity_check
+                    .begin(device, output_format, extent.x as u32, extent.y as u32);
+
+            for ((pipeline, implementation), target) in pipelines.into_iter().zip(&targets) {
+                draw_fullscreen(
+                    command_queue.command_encoder(device),
+                    pipeline,
+                    &bind_group(pipeline, implementation),
+                    target,
+                    &viewport,
+                );
+            }
+
+            Some(self.parity_check.finish(device, command_queue))
+        } else {
+            None
+        };
     }
 }
@@ -13,24 +13,65 @@ struct AABB {
 struct SceneArgs {
     rects_bounding_box: AABB;
     spheres_bounding_box: AABB;
+    triangles_bounding_box: AABB;
+    discs_bounding_box: AABB;
+    capsules_bounding_box: AABB;
+    instances_bounding_box: AABB;
+    sphere_count: u32;
+    sphere_node_count: u32;
+    triangle_node_count: u32;
+    plane_count: u32;
+    disc_count: u32;
+    capsule_count: u32;
+    instance_count: u32;
 };
 
 struct Camera {
     transform: mat4x4<f32>;
     screen_size: vec2<f32>;
+    screen_offset: vec2<f32>;
     tan_fov: f32;
     t_min: f32;
     t_max: f32;
+    panoramic: u32;
 };
 
 struct Background {
     color: vec3<f32>;
+    _pad0: f32;
+    rotation: f32;
+    irradiance_lod: f32;
+};
+
+struct ColorGrading {
+    exposure: f32;
+    contrast: f32;
+    saturation: f32;
+    gamma: f32;
+};
+
+struct Fog {
+    color: vec3<f32>;
+    _pad0: f32;
+    density: f32;
+    distance: f32;
+    steps: u32;
 };
 
 struct RaytracerArgs {
     camera: Camera;
     background: Background;
     bounces: u32;
+    loudness: f32;
+    frame: u32;
+    seed: u32;
+    supersampling: u32;
+    jitter: u32;
+    roulette_depth: u32;
+    tonemapper: u32;
+    hdr: u32;
+    color_grading: ColorGrading;
+    fog: Fog;
 };
 
 struct Args {
@@ -41,13 +82,63 @@ struct Args {
 [[group(0), binding(0)]]
 var<storage, read> args: Args;
 
+[[group(0), binding(13)]]
+var environment_map: texture_2d<f32>;
+
+[[group(0), binding(14)]]
+var environment_sampler: sampler;
+
+// mirrors sphere_audio_visualizer_core::raytracing::background::ImageBackground
+fn background_uv(direction: vec3<f32>) -> vec2<f32> {
+    let pi = 3.14159265358979;
+    let direction = normalize(direction);
+
+    let u = (atan2(direction.x, direction.z) + args.raytracer_args.background.rotation) / (2.0 * pi);
+    let v = acos(clamp(direction.y, -1.0, 1.0)) / pi;
+
+    return vec2<f32>(u - floor(u), v);
+}
+
+fn background_radiance(direction: vec3<f32>) -> vec3<f32> {
+    let color = textureSampleLevel(environment_map, environment_sampler, background_uv(direction), 0.0).rgb;
+
+    return color * args.raytracer_args.background.color;
+}
+
+fn background_intensity(normal: vec3<f32>) -> vec3<f32> {
+    let color = textureSampleLevel(environment_map, environment_sampler, background_uv(normal), args.raytracer_args.background.irradiance_lod).rgb;
+
+    return color * args.raytracer_args.background.color;
+}
+
+// mirrors sphere_audio_visualizer_core::utils::random
+fn hash(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+
+    return (word >> 22u) ^ word;
+}
+
+fn random_state(position: vec2<f32>, dimension: u32) -> u32 {
+    return hash(args.raytracer_args.seed ^ hash(u32(position.x) ^ hash(u32(position.y) ^ hash(dimension))));
+}
+
+fn unit(state: u32) -> f32 {
+    return f32(state >> 8u) / 16777216.0;
+}
+
+fn random(position: vec2<f32>, dimension: u32) -> f32 {
+    return unit(random_state(position, dimension));
+}
+
 struct Sphere {
     position: vec3<f32>;
     _pad0: f32;
-    color: vec3<f32>;
-    _pad1: f32;
     radius: f32;
-    n: f32;
+    material: u32;
+    normal_strength: f32;
+    emission: f32;
+    motion: vec3<f32>;
 };
 
 struct Spheres {
@@ -59,7 +150,12 @@ var<storage, read> spheres: Spheres;
 
 struct Rect {
     transform: mat4x4<f32>;
-    color: vec3<f32>;
+    material: u32;
+    ripple_amplitude: f32;
+    ripple_frequency: f32;
+    ripple_phase: f32;
+    texture_scale: f32;
+    normal_strength: f32;
 };
 
 struct Rects {
@@ -81,13 +177,228 @@ struct PointLights {
 [[group(0), binding(3)]]
 var<storage, read> point_lights: PointLights;
 
+struct DirectionalLight {
+    direction: vec3<f32>;
+    color: vec3<f32>;
+};
+
+struct DirectionalLights {
+    directional_lights: array<DirectionalLight>;
+};
+
+[[group(0), binding(11)]]
+var<storage, read> directional_lights: DirectionalLights;
+
+struct SpotLight {
+    position: vec3<f32>;
+    direction: vec3<f32>;
+    color: vec3<f32>;
+    _pad0: f32;
+    cos_inner_angle: f32;
+    cos_outer_angle: f32;
+};
+
+struct SpotLights {
+    spot_lights: array<SpotLight>;
+};
+
+[[group(0), binding(12)]]
+var<storage, read> spot_lights: SpotLights;
+
+struct Triangle {
+    a: vec3<f32>;
+    _pad0: f32;
+    b: vec3<f32>;
+    _pad1: f32;
+    c: vec3<f32>;
+    _pad2: f32;
+    material: u32;
+};
+
+struct Triangles {
+    triangles: array<Triangle>;
+};
+
+[[group(0), binding(4)]]
+var<storage, read> triangles: Triangles;
+
+// mirrors sphere_audio_visualizer_core::raytracing::shape::BvhNode
+struct BvhNode {
+    bounding_box: AABB;
+    skip: u32;
+    first: u32;
+    count: u32;
+};
+
+struct BvhNodes {
+    nodes: array<BvhNode>;
+};
+
+[[group(0), binding(5)]]
+var<storage, read> sphere_nodes: BvhNodes;
+
+[[group(0), binding(6)]]
+var<storage, read> triangle_nodes: BvhNodes;
+
+struct Plane {
+    position: vec3<f32>;
+    _pad0: f32;
+    normal: vec3<f32>;
+    _pad1: f32;
+    material: u32;
+};
+
+struct Planes {
+    planes: array<Plane>;
+};
+
+[[group(0), binding(7)]]
+var<storage, read> planes: Planes;
+
+struct Disc {
+    position: vec3<f32>;
+    _pad0: f32;
+    normal: vec3<f32>;
+    _pad1: f32;
+    radius: f32;
+    material: u32;
+};
+
+struct Discs {
+    discs: array<Disc>;
+};
+
+[[group(0), binding(8)]]
+var<storage, read> discs: Discs;
+
+struct Capsule {
+    a: vec3<f32>;
+    _pad0: f32;
+    b: vec3<f32>;
+    _pad1: f32;
+    radius: f32;
+    material: u32;
+};
+
+struct Capsules {
+    capsules: array<Capsule>;
+};
+
+[[group(0), binding(9)]]
+var<storage, read> capsules: Capsules;
+
+// mirrors sphere_audio_visualizer_core::raytracing::shape::Instance, the
+// shape is 0 for the unit sphere and 1 for the unit rect
+struct Instance {
+    transform: mat4x4<f32>;
+    shape: u32;
+    material: u32;
+};
+
+struct Instances {
+    instances: array<Instance>;
+};
+
+[[group(0), binding(15)]]
+var<storage, read> instances: Instances;
+
+[[group(0), binding(16)]]
+var surface_texture: texture_2d<f32>;
+
+[[group(0), binding(17)]]
+var surface_sampler: sampler;
+
+[[group(0), binding(18)]]
+var normal_map: texture_2d<f32>;
+
+[[group(0), binding(19)]]
+var normal_sampler: sampler;
+
+// mirrors sphere_audio_visualizer_core::raytracing::statistics::RaytracerStatistics
+struct Statistics {
+    rays_cast: atomic<u32>;
+    aabb_tests: atomic<u32>;
+    shape_tests: atomic<u32>;
+    samples: atomic<u32>;
+};
+
+[[group(0), binding(20)]]
+var<storage, read_write> statistics: Statistics;
+
+// replaced by true when the statistics feature is enabled, the branches
+// around the counters are removed by the shader compiler otherwise
+let STATISTICS: bool = false;
+
+fn count_rays_cast() {
+    if(STATISTICS) {
+        atomicAdd(&statistics.rays_cast, 1u);
+    }
+}
+
+fn count_aabb_tests() {
+    if(STATISTICS) {
+        atomicAdd(&statistics.aabb_tests, 1u);
+    }
+}
+
+fn count_shape_tests(count: u32) {
+    if(STATISTICS) {
+        atomicAdd(&statistics.shape_tests, count);
+    }
+}
+
+fn count_samples() {
+    if(STATISTICS) {
+        atomicAdd(&statistics.samples, 1u);
+    }
+}
+
+// mirrors sphere_audio_visualizer_core::raytracing::shape::map_normal
+fn map_normal(normal: vec3<f32>, tangent: vec3<f32>, uv: vec2<f32>, strength: f32) -> vec3<f32> {
+    let sample = textureSampleLevel(normal_map, normal_sampler, uv, 0.0).rgb;
+
+    // the tangent is made orthogonal to the normal first
+    let tangent = normalize(tangent - normal * dot(normal, tangent));
+    let bitangent = cross(normal, tangent);
+
+    let local = sample * 2.0 - 1.0;
+
+    return normalize(tangent * (local.x * strength) + bitangent * (local.y * strength) + normal * local.z);
+}
+
+// mirrors sphere_audio_visualizer_core::raytracing::shape::Material
+struct Material {
+    albedo: vec3<f32>;
+    _pad0: f32;
+    emission: vec3<f32>;
+    _pad1: f32;
+    roughness: f32;
+    metallic: f32;
+    ior: f32;
+    transmission: f32;
+};
+
+struct Materials {
+    materials: array<Material>;
+};
+
+[[group(0), binding(10)]]
+var<storage, read> materials: Materials;
+
 struct Ray {
     origin: vec3<f32>;
     t_min: f32;
     direction: vec3<f32>;
     t_max: f32;
+    // mirrors sphere_audio_visualizer_core::raytracing::RayPayload
+    seed: u32;
+    inside: bool;
 };
 
+// mirrors RayPayload::time, every ray of a path including the shadow rays is
+// traced at the same point in time
+var<private> shutter_time: f32;
+
 fn valid_t(ray: Ray, t: f32) -> bool {
     return ray.t_min < t && ray.t_max > t;
 }
@@ -103,6 +414,8 @@ fn transform_ray(ray: Ray, transform: mat4x4<f32>) -> Ray {
     result.direction = (transform * vec4<f32>(ray.direction, 0.0)).xyz;
     result.t_min = ray.t_min;
     result.t_max = ray.t_max;
+    result.seed = ray.seed;
+    result.inside = ray.inside;
     
     return result;
 }
@@ -113,8 +426,12 @@ struct SphereIntersection {
     discriminant: f32;
 };
 
+fn sphere_center(sphere: Sphere) -> vec3<f32> {
+    return sphere.position + sphere.motion * shutter_time;
+}
+
 fn sphere_intersect(ray: Ray, sphere: Sphere, intersection: ptr<function, f32>) -> bool {
-    let oc = ray.origin - sphere.position;
+    let oc = ray.origin - sphere_center(sphere);
     let radius = sphere.radius;
     let direction = ray.direction;
 
@@ -124,42 +441,430 @@ fn sphere_intersect(ray: Ray, sphere: Sphere, intersection: ptr<function, f32>)
     let discriminant = b*b - 4.0*a*c;
     
     if(discriminant >= 0.0) {
-        let t = (-b - sqrt(discriminant)) / (2.0*a);
+        var t = (-b - sqrt(discriminant)) / (2.0*a);
+
+        // rays travelling inside of the sphere hit it from behind
+        if(!valid_t(ray, t)) {
+            t = (-b + sqrt(discriminant)) / (2.0*a);
+        }
+
+        *intersection = t;
+
+        return valid_t(ray, t);
+    }
+
+    return false;
+}
+
+fn aabb_entry(ray: Ray, aabb: AABB, entry: ptr<function, f32>) -> bool {
+    count_aabb_tests();
+
+    let di = 1.0 / ray.direction;
+
+    let t1 = (aabb.min - ray.origin) * di;
+    let t2 = (aabb.max - ray.origin) * di;
+
+    let tmin = min(t1, t2);
+    let tmax = max(t1, t2);
+
+    let tmin = max(tmin.x, max(tmin.y, tmin.z));
+    let tmax = min(tmax.x, min(tmax.y, tmax.z));
+
+    *entry = tmin;
+
+    return tmax >= tmin && (valid_t(ray, tmin) || valid_t(ray, tmax));
+}
+
+fn aabb_intersect(ray: Ray, aabb: AABB) -> bool {
+    var entry: f32;
+
+    return aabb_entry(ray, aabb, &entry);
+}
+
+struct SpheresIntersection {
+    nearest_intersection_result: f32;
+    nearest_intersected_sphere: u32;
+};
+
+fn intersect_spheres(ray: Ray, spheres_intersection: ptr<function, SpheresIntersection>) -> bool {
+    let sphere_count = min(arrayLength(&spheres.spheres), args.scene_args.sphere_count);
+    let node_count = min(arrayLength(&sphere_nodes.nodes), args.scene_args.sphere_node_count);
+
+    var nearest_intersection_result: f32 = ray.t_max;
+    var nearest_intersected_sphere: u32 = sphere_count;
+
+    if(node_count == 0u) {
+        count_shape_tests(sphere_count);
+
+        for(var i: u32 = 0u; i < sphere_count; i = i + 1u) {
+            var t: f32;
+
+            if(sphere_intersect(ray, spheres.spheres[i], &t)) {
+                if(nearest_intersection_result > t) {
+                    nearest_intersection_result = t;
+                    nearest_intersected_sphere = i;
+                }
+            }
+        }
+    }
+
+    // stackless traversal, nodes which are missed are skipped with their
+    // subtree
+    var index: u32 = 0u;
+
+    loop {
+        if(index >= node_count) {
+            break;
+        }
+
+        let node = sphere_nodes.nodes[index];
+        var entry: f32;
+
+        if(aabb_entry(ray, node.bounding_box, &entry) && entry < nearest_intersection_result) {
+            if(node.count == 0u) {
+                index = index + 1u;
+                continue;
+            }
+
+            let end = min(node.first + node.count, sphere_count);
+
+            count_shape_tests(end - min(node.first, end));
+
+            for(var i: u32 = node.first; i < end; i = i + 1u) {
+                var t: f32;
+
+                if(sphere_intersect(ray, spheres.spheres[i], &t)) {
+                    if(nearest_intersection_result > t) {
+                        nearest_intersection_result = t;
+                        nearest_intersected_sphere = i;
+                    }
+                }
+            }
+        }
+
+        index = node.skip;
+    }
+
+    (*spheres_intersection).nearest_intersection_result = nearest_intersection_result;
+    (*spheres_intersection).nearest_intersected_sphere = nearest_intersected_sphere;
+
+    return nearest_intersected_sphere != sphere_count;
+}
+
+fn triangle_intersect(ray: Ray, triangle: Triangle, intersection: ptr<function, f32>) -> bool {
+    // Möller–Trumbore intersection
+    let edge1 = triangle.b - triangle.a;
+    let edge2 = triangle.c - triangle.a;
+
+    let p = cross(ray.direction, edge2);
+    let determinant = dot(edge1, p);
+    let inverse_determinant = 1.0 / determinant;
+
+    let s = ray.origin - triangle.a;
+    let u = dot(s, p) * inverse_determinant;
+
+    let q = cross(s, edge1);
+    let v = dot(ray.direction, q) * inverse_determinant;
+
+    let t = dot(edge2, q) * inverse_determinant;
+
+    *intersection = t;
+
+    return abs(determinant) > 1e-8 && u >= 0.0 && v >= 0.0 && u + v <= 1.0 && valid_t(ray, t);
+}
+
+struct TrianglesIntersection {
+    nearest_intersection_result: f32;
+    nearest_intersected_triangle: u32;
+};
+
+fn intersect_triangles(ray: Ray, triangles_intersection: ptr<function, TrianglesIntersection>) -> bool {
+    let triangle_count = arrayLength(&triangles.triangles);
+    let node_count = min(arrayLength(&triangle_nodes.nodes), args.scene_args.triangle_node_count);
+
+    var nearest_intersection_result: f32 = ray.t_max;
+    var nearest_intersected_triangle: u32 = triangle_count;
+
+    if(node_count == 0u && aabb_intersect(ray, args.scene_args.triangles_bounding_box)) {
+        count_shape_tests(triangle_count);
+
+        for(var i: u32 = 0u; i < triangle_count; i = i + 1u) {
+            var t: f32;
+
+            if(triangle_intersect(ray, triangles.triangles[i], &t)) {
+                if(nearest_intersection_result > t) {
+                    nearest_intersection_result = t;
+                    nearest_intersected_triangle = i;
+                }
+            }
+        }
+    }
+
+    var index: u32 = 0u;
+
+    loop {
+        if(index >= node_count) {
+            break;
+        }
+
+        let node = triangle_nodes.nodes[index];
+        var entry: f32;
+
+        if(aabb_entry(ray, node.bounding_box, &entry) && entry < nearest_intersection_result) {
+            if(node.count == 0u) {
+                index = index + 1u;
+                continue;
+            }
+
+            let end = min(node.first + node.count, triangle_count);
+
+            count_shape_tests(end - min(node.first, end));
+
+            for(var i: u32 = node.first; i < end; i = i + 1u) {
+                var t: f32;
+
+                if(triangle_intersect(ray, triangles.triangles[i], &t)) {
+                    if(nearest_intersection_result > t) {
+                        nearest_intersection_result = t;
+                        nearest_intersected_triangle = i;
+                    }
+                }
+            }
+        }
+
+        index = node.skip;
+    }
+
+    (*triangles_intersection).nearest_intersection_result = nearest_intersection_result;
+    (*triangles_intersection).nearest_intersected_triangle = nearest_intersected_triangle;
+
+    return nearest_intersected_triangle != triangle_count;
+}
+
+fn plane_intersect(ray: Ray, plane: Plane, intersection: ptr<function, f32>) -> bool {
+    let cosine = dot(plane.normal, ray.direction);
+    let t = dot(plane.position - ray.origin, plane.normal) / cosine;
+
+    *intersection = t;
+
+    return abs(cosine) > 1e-8 && valid_t(ray, t);
+}
+
+struct PlanesIntersection {
+    nearest_intersection_result: f32;
+    nearest_intersected_plane: u32;
+};
+
+fn intersect_planes(ray: Ray, planes_intersection: ptr<function, PlanesIntersection>) -> bool {
+    let plane_count = min(arrayLength(&planes.planes), args.scene_args.plane_count);
+
+    var nearest_intersection_result: f32 = ray.t_max;
+    var nearest_intersected_plane: u32 = plane_count;
+
+    count_shape_tests(plane_count);
+
+    for(var i: u32 = 0u; i < plane_count; i = i + 1u) {
+        var t: f32;
+
+        if(plane_intersect(ray, planes.planes[i], &t)) {
+            if(nearest_intersection_result > t) {
+                nearest_intersection_result = t;
+                nearest_intersected_plane = i;
+            }
+        }
+    }
+
+    (*planes_intersection).nearest_intersection_result = nearest_intersection_result;
+    (*planes_intersection).nearest_intersected_plane = nearest_intersected_plane;
+
+    return nearest_intersected_plane != plane_count;
+}
+
+fn disc_intersect(ray: Ray, disc: Disc, intersection: ptr<function, f32>) -> bool {
+    let cosine = dot(disc.normal, ray.direction);
+    let t = dot(disc.position - ray.origin, disc.normal) / cosine;
+
+    let offset = point_at(ray, t) - disc.position;
+
+    *intersection = t;
+
+    return abs(cosine) > 1e-8 && dot(offset, offset) <= disc.radius * disc.radius && valid_t(ray, t);
+}
+
+struct DiscsIntersection {
+    nearest_intersection_result: f32;
+    nearest_intersected_disc: u32;
+};
+
+fn intersect_discs(ray: Ray, discs_intersection: ptr<function, DiscsIntersection>) -> bool {
+    let disc_count = min(arrayLength(&discs.discs), args.scene_args.disc_count);
+
+    var nearest_intersection_result: f32 = ray.t_max;
+    var nearest_intersected_disc: u32 = disc_count;
+
+    if(aabb_intersect(ray, args.scene_args.discs_bounding_box)) {
+        count_shape_tests(disc_count);
+
+        for(var i: u32 = 0u; i < disc_count; i = i + 1u) {
+            var t: f32;
+
+            if(disc_intersect(ray, discs.discs[i], &t)) {
+                if(nearest_intersection_result > t) {
+                    nearest_intersection_result = t;
+                    nearest_intersected_disc = i;
+                }
+            }
+        }
+    }
+
+    (*discs_intersection).nearest_intersection_result = nearest_intersection_result;
+    (*discs_intersection).nearest_intersected_disc = nearest_intersected_disc;
+
+    return nearest_intersected_disc != disc_count;
+}
+
+fn capsule_cap_intersect(ray: Ray, center: vec3<f32>, radius: f32, intersection: ptr<function, f32>) -> bool {
+    let oc = ray.origin - center;
+
+    let rdrd = dot(ray.direction, ray.direction);
+    let b = dot(ray.direction, oc);
+    let c = dot(oc, oc) - radius * radius;
+    let discriminant = b * b - rdrd * c;
+
+    let t = (-b - sqrt(max(discriminant, 0.0))) / rdrd;
+
+    *intersection = t;
+
+    return discriminant >= 0.0 && valid_t(ray, t);
+}
+
+fn capsule_intersect(ray: Ray, capsule: Capsule, intersection: ptr<function, f32>) -> bool {
+    let ba = capsule.b - capsule.a;
+    let oa = ray.origin - capsule.a;
+
+    let baba = dot(ba, ba);
+    let bard = dot(ba, ray.direction);
+    let baoa = dot(ba, oa);
+    let rdoa = dot(ray.direction, oa);
+    let oaoa = dot(oa, oa);
+    let rdrd = dot(ray.direction, ray.direction);
+
+    // the infinite cylinder around the segment, only the part between the
+    // end points belongs to the capsule
+    let a = baba * rdrd - bard * bard;
+    let b = baba * rdoa - baoa * bard;
+    let c = baba * oaoa - baoa * baoa - capsule.radius * capsule.radius * baba;
+    let discriminant = b * b - a * c;
+
+    let t = (-b - sqrt(max(discriminant, 0.0))) / a;
+    let y = baoa + t * bard;
+
+    var is_hit = discriminant >= 0.0 && y > 0.0 && y < baba && valid_t(ray, t);
+    var nearest_intersection = select(ray.t_max, t, is_hit);
+
+    // the caps are spheres around the end points
+    var cap_intersection: f32;
+
+    if(capsule_cap_intersect(ray, capsule.a, capsule.radius, &cap_intersection) && cap_intersection < nearest_intersection) {
+        is_hit = true;
+        nearest_intersection = cap_intersection;
+    }
+
+    if(capsule_cap_intersect(ray, capsule.b, capsule.radius, &cap_intersection) && cap_intersection < nearest_intersection) {
+        is_hit = true;
+        nearest_intersection = cap_intersection;
+    }
+
+    *intersection = nearest_intersection;
+
+    return is_hit;
+}
+
+struct CapsulesIntersection {
+    nearest_intersection_result: f32;
+    nearest_intersected_capsule: u32;
+};
+
+fn intersect_capsules(ray: Ray, capsules_intersection: ptr<function, CapsulesIntersection>) -> bool {
+    let capsule_count = min(arrayLength(&capsules.capsules), args.scene_args.capsule_count);
+
+    var nearest_intersection_result: f32 = ray.t_max;
+    var nearest_intersected_capsule: u32 = capsule_count;
+
+    if(aabb_intersect(ray, args.scene_args.capsules_bounding_box)) {
+        count_shape_tests(capsule_count);
+
+        for(var i: u32 = 0u; i < capsule_count; i = i + 1u) {
+            var t: f32;
+
+            if(capsule_intersect(ray, capsules.capsules[i], &t)) {
+                if(nearest_intersection_result > t) {
+                    nearest_intersection_result = t;
+                    nearest_intersected_capsule = i;
+                }
+            }
+        }
+    }
+
+    (*capsules_intersection).nearest_intersection_result = nearest_intersection_result;
+    (*capsules_intersection).nearest_intersected_capsule = nearest_intersected_capsule;
+
+    return nearest_intersected_capsule != capsule_count;
+}
+
+fn instance_intersect(ray: Ray, instance: Instance, intersection: ptr<function, f32>) -> bool {
+    // the direction is not normalized, so the distance along the ray is the
+    // same in both spaces
+    let ray = transform_ray(ray, instance.transform);
+
+    if(instance.shape == 1u) {
+        let t = -ray.origin.y / ray.direction.y;
+        let axis_valid = abs(point_at(ray, t).xz);
 
         *intersection = t;
 
-        return valid_t(ray, t);
+        return valid_t(ray, t) && axis_valid.x < 0.5 && axis_valid.y < 0.5;
     }
 
-    return false;
+    var sphere: Sphere;
+
+    sphere.position = vec3<f32>(0.0);
+    sphere.radius = 1.0;
+    sphere.motion = vec3<f32>(0.0);
+
+    return sphere_intersect(ray, sphere, intersection);
 }
 
-struct SpheresIntersection {
+struct InstancesIntersection {
     nearest_intersection_result: f32;
-    nearest_intersected_sphere: u32;
+    nearest_intersected_instance: u32;
 };
 
-fn intersect_spheres(ray: Ray, spheres_intersection: ptr<function, SpheresIntersection>) -> bool {
-    let sphere_count = arrayLength(&spheres.spheres);
+fn intersect_instances(ray: Ray, instances_intersection: ptr<function, InstancesIntersection>) -> bool {
+    let instance_count = min(arrayLength(&instances.instances), args.scene_args.instance_count);
 
     var nearest_intersection_result: f32 = ray.t_max;
-    var nearest_intersected_sphere: u32 = sphere_count;
+    var nearest_intersected_instance: u32 = instance_count;
 
-    for(var i: u32 = 0u; i < sphere_count; i = i + 1u) {
-        var t: f32;
+    if(aabb_intersect(ray, args.scene_args.instances_bounding_box)) {
+        count_shape_tests(instance_count);
 
-        if(sphere_intersect(ray, spheres.spheres[i], &t)) {
-            if(nearest_intersection_result > t) {
-                nearest_intersection_result = t;
-                nearest_intersected_sphere = i;
+        for(var i: u32 = 0u; i < instance_count; i = i + 1u) {
+            var t: f32;
+
+            if(instance_intersect(ray, instances.instances[i], &t)) {
+                if(nearest_intersection_result > t) {
+                    nearest_intersection_result = t;
+                    nearest_intersected_instance = i;
+                }
             }
         }
     }
 
-    (*spheres_intersection).nearest_intersection_result = nearest_intersection_result;
-    (*spheres_intersection).nearest_intersected_sphere = nearest_intersected_sphere;
+    (*instances_intersection).nearest_intersection_result = nearest_intersection_result;
+    (*instances_intersection).nearest_intersected_instance = nearest_intersected_instance;
 
-    return nearest_intersected_sphere != sphere_count;
+    return nearest_intersected_instance != instance_count;
 }
 
 struct ShadingResult {
@@ -171,8 +876,20 @@ struct ShadingResult {
 
 fn shadow(ray: Ray) -> bool {
     var spheres_intersection: SpheresIntersection;
-
-    return intersect_spheres(ray, &spheres_intersection);
+    var triangles_intersection: TrianglesIntersection;
+    var planes_intersection: PlanesIntersection;
+    var discs_intersection: DiscsIntersection;
+    var capsules_intersection: CapsulesIntersection;
+    var instances_intersection: InstancesIntersection;
+
+    count_rays_cast();
+
+    return intersect_spheres(ray, &spheres_intersection)
+        || intersect_triangles(ray, &triangles_intersection)
+        || intersect_planes(ray, &planes_intersection)
+        || intersect_discs(ray, &discs_intersection)
+        || intersect_capsules(ray, &capsules_intersection)
+        || intersect_instances(ray, &instances_intersection);
 }
 
 fn lambert_point_light(point_light: PointLight, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
@@ -188,8 +905,60 @@ fn lambert_point_light(point_light: PointLight, position: vec3<f32>, normal: vec
     return select(max(dot(normalize(light_dir), normal), 0.0) / dot(light_dir, light_dir), 0.0, shadow(ray)) * point_light.color;
 }
 
+fn lambert_directional_light(directional_light: DirectionalLight, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let color = max(dot(directional_light.direction, normal), 0.0) * directional_light.color;
+
+    // surfaces facing away from the light are not lit, which saves the
+    // shadow ray
+    if(max(color.x, max(color.y, color.z)) <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    var ray: Ray;
+
+    ray.direction = directional_light.direction;
+    ray.origin = position;
+    ray.t_max = 1000.0;
+    ray.t_min = 0.001;
+
+    return select(color, vec3<f32>(0.0), shadow(ray));
+}
+
+fn lambert_spot_light(spot_light: SpotLight, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let light_dir = spot_light.position - position;
+    let light_dir_normalized = normalize(light_dir);
+
+    // a hard edged cone has no range to fade out in
+    let cos_angle = -dot(light_dir_normalized, spot_light.direction);
+    let falloff = select(
+        smoothStep(spot_light.cos_outer_angle, spot_light.cos_inner_angle, cos_angle),
+        select(0.0, 1.0, cos_angle >= spot_light.cos_outer_angle),
+        spot_light.cos_inner_angle <= spot_light.cos_outer_angle
+    );
+
+    let color = max(dot(light_dir_normalized, normal), 0.0) * falloff / dot(light_dir, light_dir) * spot_light.color;
+
+    // surfaces outside of the cone are not lit, which saves the shadow ray
+    if(max(color.x, max(color.y, color.z)) <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    var ray: Ray;
+
+    ray.direction = light_dir;
+    ray.origin = position;
+    ray.t_max = 1.0;
+    ray.t_min = 0.001;
+
+    return select(color, vec3<f32>(0.0), shadow(ray));
+}
+
+// all supported lights are delta lights, which get the full weight of the
+// multiple importance sampling, so every light is evaluated directly
 fn lambert(position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
     let point_light_count = arrayLength(&point_lights.point_lights);
+    let directional_light_count = arrayLength(&directional_lights.directional_lights);
+    let spot_light_count = arrayLength(&spot_lights.spot_lights);
 
     var result = vec3<f32>(0.0);
 
@@ -197,6 +966,14 @@ fn lambert(position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
         result = result + lambert_point_light(point_lights.point_lights[i], position, normal);
     }
 
+    for(var i: u32 = 0u; i < directional_light_count; i = i + 1u) {
+        result = result + lambert_directional_light(directional_lights.directional_lights[i], position, normal);
+    }
+
+    for(var i: u32 = 0u; i < spot_light_count; i = i + 1u) {
+        result = result + lambert_spot_light(spot_lights.spot_lights[i], position, normal);
+    }
+
     return result;
 }
 
@@ -204,8 +981,53 @@ fn sphere_sdf(sphere: Sphere, position: vec3<f32>) -> f32 {
     return distance(sphere.position, position) - sphere.radius;
 }
 
+fn plane_sdf(plane: Plane, position: vec3<f32>) -> f32 {
+    return abs(dot(position - plane.position, plane.normal));
+}
+
+fn disc_sdf(disc: Disc, position: vec3<f32>) -> f32 {
+    let offset = position - disc.position;
+    let height = dot(offset, disc.normal);
+
+    // the distance to the rim is measured inside the plane of the disc
+    let rim = max(length(offset - disc.normal * height) - disc.radius, 0.0);
+
+    return sqrt(height * height + rim * rim);
+}
+
+fn capsule_nearest_segment_point(capsule: Capsule, position: vec3<f32>) -> vec3<f32> {
+    let ba = capsule.b - capsule.a;
+    let h = clamp(dot(position - capsule.a, ba) / dot(ba, ba), 0.0, 1.0);
+
+    return capsule.a + ba * h;
+}
+
+fn capsule_sdf(capsule: Capsule, position: vec3<f32>) -> f32 {
+    return distance(position, capsule_nearest_segment_point(capsule, position)) - capsule.radius;
+}
+
+// mirrors Instance::distance of the rust implementation, only the unit sphere
+// has a distance
+fn instance_sdf(instance: Instance, position: vec3<f32>) -> f32 {
+    if(instance.shape == 1u) {
+        return 1000.0;
+    }
+
+    let local = (instance.transform * vec4<f32>(position, 1.0)).xyz;
+    let scale = max(
+        length(instance.transform[0].xyz),
+        max(length(instance.transform[1].xyz), length(instance.transform[2].xyz))
+    );
+
+    return (length(local) - 1.0) / scale;
+}
+
 fn sdf(position: vec3<f32>) -> f32 {
-    let spheres_count = arrayLength(&spheres.spheres);
+    let spheres_count = min(arrayLength(&spheres.spheres), args.scene_args.sphere_count);
+    let plane_count = min(arrayLength(&planes.planes), args.scene_args.plane_count);
+    let disc_count = min(arrayLength(&discs.discs), args.scene_args.disc_count);
+    let capsule_count = min(arrayLength(&capsules.capsules), args.scene_args.capsule_count);
+    let instance_count = min(arrayLength(&instances.instances), args.scene_args.instance_count);
 
     var min_distance = 1000.0;
 
@@ -213,6 +1035,22 @@ fn sdf(position: vec3<f32>) -> f32 {
         min_distance = min(min_distance, sphere_sdf(spheres.spheres[i], position));
     }
 
+    for(var i: u32 = 0u; i < plane_count; i = i + 1u) {
+        min_distance = min(min_distance, plane_sdf(planes.planes[i], position));
+    }
+
+    for(var i: u32 = 0u; i < disc_count; i = i + 1u) {
+        min_distance = min(min_distance, disc_sdf(discs.discs[i], position));
+    }
+
+    for(var i: u32 = 0u; i < capsule_count; i = i + 1u) {
+        min_distance = min(min_distance, capsule_sdf(capsules.capsules[i], position));
+    }
+
+    for(var i: u32 = 0u; i < instance_count; i = i + 1u) {
+        min_distance = min(min_distance, instance_sdf(instances.instances[i], position));
+    }
+
     return min_distance;
 }
 
@@ -225,31 +1063,138 @@ fn ambient_occlusion(position: vec3<f32>, normal: vec3<f32>) -> f32 {
     return occlusion;
 }
 
-fn shade_sphere(sphere: Sphere, ray: Ray, t: f32) -> ShadingResult {
+// the ray is either reflected or refracted, chosen randomly by the fresnel
+// term, so the average over many paths contains both
+fn shade_transmissive(material: Material, ray: Ray, position: vec3<f32>, normal: vec3<f32>, transmission: f32) -> ShadingResult {
     var shading_result: ShadingResult;
 
-    var reflection_ray: Ray;
+    // rays inside of the material hit the surface from behind and leave into
+    // the air
+    var normal = normal;
+    var n1 = 1.0;
+    var n2 = material.ior;
 
-    let position = point_at(ray, t);
-    let normal = normalize(position - sphere.position);
+    if(ray.inside) {
+        normal = -normal;
+        n1 = material.ior;
+        n2 = 1.0;
+    }
+
+    let eta = n1 / n2;
+    let cos_i = -dot(ray.direction, normal);
+    let k = 1.0 - eta * eta * (1.0 - cos_i * cos_i);
+
+    // beyond the critical angle all light is reflected, otherwise the fresnel
+    // term uses the angle on the optically thinner side
+    var fresnel = 1.0;
+
+    if(k >= 0.0) {
+        if(n1 > n2) {
+            let r = (n1 - n2) / (n1 + n2);
+            let r2 = r * r;
+            fresnel = r2 + (1.0 - r2) * pow(1.0 - sqrt(k), 5.0);
+        } else {
+            fresnel = shlick(ray.direction, normal, n1, n2);
+        }
+    }
+
+    let gloss = 1.0 - material.roughness;
+    let diffuse = material.albedo * (1.0 - material.metallic) * (1.0 - fresnel) * (1.0 - transmission);
+
+    shading_result.emissive_color = material.emission;
+
+    // the inside of a surface is not lit
+    if(!ray.inside && max(diffuse.x, max(diffuse.y, diffuse.z)) > 0.0) {
+        shading_result.emissive_color = shading_result.emissive_color + diffuse * (background_intensity(normal) * (1.0 + args.raytracer_args.loudness) * ambient_occlusion(position, normal) + lambert(position, normal));
+    }
+
+    var next_ray: Ray;
+
+    next_ray.origin = position;
+    next_ray.t_min = 0.001;
+    next_ray.t_max = 1000.0;
+    next_ray.seed = hash(ray.seed);
+    next_ray.inside = ray.inside;
+
+    // the colors are divided by the probability of their branch
+    if(unit(ray.seed) < fresnel) {
+        next_ray.direction = reflect(ray.direction, normal);
+        shading_result.reflective_color = mix(vec3<f32>(fresnel * gloss), material.albedo * gloss, material.metallic) / fresnel;
+    } else {
+        next_ray.direction = ray.direction * eta + normal * (eta * cos_i - sqrt(max(k, 0.0)));
+        next_ray.inside = !ray.inside;
+        shading_result.reflective_color = material.albedo * transmission;
+    }
+
+    shading_result.reflection = true;
+    shading_result.reflection_ray = next_ray;
+
+    return shading_result;
+}
+
+fn shade_material(material: Material, ray: Ray, t: f32, position: vec3<f32>, normal: vec3<f32>) -> ShadingResult {
+    let transmission = material.transmission * (1.0 - material.metallic);
+
+    if(transmission > 0.0) {
+        return shade_transmissive(material, ray, position, normal, transmission);
+    }
+
+    var shading_result: ShadingResult;
+
+    var reflection_ray: Ray;
 
     reflection_ray.origin = position;
     reflection_ray.direction = reflect(ray.direction, normal);
     reflection_ray.t_min = 0.001;
     reflection_ray.t_max = 1000.0;
+    reflection_ray.seed = hash(ray.seed);
 
-    let shlick = shlick(ray.direction, normal, 1.0, sphere.n);
+    let gloss = 1.0 - material.roughness;
+    let fresnel = shlick(ray.direction, normal, 1.0, material.ior) * gloss;
 
-    //shading_result.emissive_color = vec3<f32>(0.0);
-    shading_result.emissive_color = (1.0 - shlick) * sphere.color * (ambient_occlusion(position, normal) + lambert(position, normal));
-    shading_result.reflection = true;
+    // dielectrics reflect white by the fresnel term, metals in their color
+    let reflectance = mix(vec3<f32>(fresnel), material.albedo * gloss, material.metallic);
+    let diffuse = material.albedo * (1.0 - material.metallic) * (1.0 - fresnel);
+
+    shading_result.emissive_color = material.emission;
+
+    // black surfaces are not lit, which saves the shadow rays
+    if(max(diffuse.x, max(diffuse.y, diffuse.z)) > 0.0) {
+        shading_result.emissive_color = shading_result.emissive_color + diffuse * (background_intensity(normal) * (1.0 + args.raytracer_args.loudness) * ambient_occlusion(position, normal) + lambert(position, normal));
+    }
+
+    shading_result.reflection = gloss > 0.0;
     shading_result.reflection_ray = reflection_ray;
-    shading_result.reflective_color = vec3<f32>(shlick);
-    //shading_result.reflective_color = vec3<f32>(0.5);
+    shading_result.reflective_color = reflectance;
 
     return shading_result;
 }
 
+fn shade_sphere(sphere: Sphere, ray: Ray, t: f32) -> ShadingResult {
+    let pi = 3.14159265358979;
+    let position = point_at(ray, t);
+    var normal = normalize(position - sphere_center(sphere));
+
+    if(sphere.normal_strength > 0.0) {
+        let u = atan2(normal.x, normal.z) / (2.0 * pi);
+        let v = acos(clamp(normal.y, -1.0, 1.0)) / pi;
+
+        // the tangent follows the texture coordinate around the vertical
+        // axis, which is undefined at the poles
+        let tangent = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(normal.z, 0.0, -normal.x), abs(normal.x) + abs(normal.z) > 0.0001);
+
+        normal = map_normal(normal, tangent, vec2<f32>(u - floor(u), v), sphere.normal_strength);
+    }
+
+    let material = materials.materials[sphere.material];
+    var result = shade_material(material, ray, t, position, normal);
+
+    // glowing spheres add their color to the emission of the material
+    result.emissive_color = result.emissive_color + material.albedo * sphere.emission;
+
+    return result;
+}
+
 fn rect_intersect(ray: Ray, rect: Rect, intersection: ptr<function, f32>) -> bool {
     let ray = transform_ray(ray, rect.transform);
     
@@ -276,6 +1221,8 @@ fn intersect_rects(ray: Ray, rects_intersection: ptr<function, RectsIntersection
     var nearest_intersection_result: f32 = ray.t_max; 
     var nearest_intersected_rect: u32 = rect_count;
 
+    count_shape_tests(rect_count);
+
     for(var i: u32 = 0u; i < rect_count; i = i + 1u) {
         var t: f32;
 
@@ -293,23 +1240,191 @@ fn intersect_rects(ray: Ray, rects_intersection: ptr<function, RectsIntersection
     return nearest_intersected_rect != rect_count;
 }
 
-fn rect_shade(rect: Rect) -> ShadingResult {
-    var shading_result: ShadingResult;
+fn rect_shade(rect: Rect, ray: Ray, t: f32) -> ShadingResult {
+    let local_ray = transform_ray(ray, rect.transform);
+    let position = point_at(local_ray, t);
+    let view = normalize(-local_ray.direction);
+
+    let radius = max(length(position.xz), 0.0001);
+    let slope = rect.ripple_amplitude * rect.ripple_frequency * cos(rect.ripple_frequency * radius - rect.ripple_phase);
+    var normal = normalize(vec3<f32>(-slope * position.x / radius, 1.0, -slope * position.z / radius));
+    let uv = (position.xz + 0.5) * rect.texture_scale;
+
+    if(rect.normal_strength > 0.0) {
+        normal = map_normal(normal, vec3<f32>(1.0, 0.0, 0.0), uv, rect.normal_strength);
+    }
+
+    // the brightness follows the tilt of the rippled and normal mapped
+    // surface towards the viewer relative to the flat surface
+    let brightness = min(abs(dot(normal, view)) / max(abs(view.y), 0.001), 4.0);
 
-    shading_result.emissive_color = rect.color;
-    shading_result.reflection = false;
+    // normals are transformed back by the transpose of the inverse
+    let normal = normalize((transpose(rect.transform) * vec4<f32>(normal, 0.0)).xyz);
+
+    // the normal always faces the incoming ray
+    let normal = select(normal, -normal, dot(normal, ray.direction) > 0.0);
+
+    var material = materials.materials[rect.material];
+
+    if(rect.texture_scale > 0.0) {
+        let color = textureSampleLevel(surface_texture, surface_sampler, uv, 0.0).rgb;
+
+        material.albedo = material.albedo * color;
+        material.emission = material.emission * color;
+    }
+
+    var shading_result = shade_material(material, ray, t, point_at(ray, t), normal);
+
+    shading_result.emissive_color = shading_result.emissive_color * brightness;
 
     return shading_result;
 }
 
-let RAY_BOUNCES: u32 = 5u;
+fn shade_triangle(triangle: Triangle, ray: Ray, t: f32) -> ShadingResult {
+    let position = point_at(ray, t);
+    let normal = normalize(cross(triangle.b - triangle.a, triangle.c - triangle.a));
+
+    // the normal always faces the incoming ray
+    let normal = select(normal, -normal, dot(normal, ray.direction) > 0.0);
+
+    return shade_material(materials.materials[triangle.material], ray, t, position, normal);
+}
+
+fn shade_plane(plane: Plane, ray: Ray, t: f32) -> ShadingResult {
+    let position = point_at(ray, t);
+
+    // the normal always faces the incoming ray
+    let normal = select(plane.normal, -plane.normal, dot(plane.normal, ray.direction) > 0.0);
+
+    return shade_material(materials.materials[plane.material], ray, t, position, normal);
+}
+
+fn shade_disc(disc: Disc, ray: Ray, t: f32) -> ShadingResult {
+    let position = point_at(ray, t);
+
+    // the normal always faces the incoming ray
+    let normal = select(disc.normal, -disc.normal, dot(disc.normal, ray.direction) > 0.0);
+
+    return shade_material(materials.materials[disc.material], ray, t, position, normal);
+}
+
+fn shade_capsule(capsule: Capsule, ray: Ray, t: f32) -> ShadingResult {
+    let position = point_at(ray, t);
+    let normal = normalize(position - capsule_nearest_segment_point(capsule, position));
+
+    return shade_material(materials.materials[capsule.material], ray, t, position, normal);
+}
+
+fn shade_instance(instance: Instance, ray: Ray, t: f32) -> ShadingResult {
+    var normal = vec3<f32>(0.0, 1.0, 0.0);
+
+    if(instance.shape != 1u) {
+        normal = point_at(transform_ray(ray, instance.transform), t);
+    }
+
+    // normals are transformed back by the transpose of the inverse
+    normal = normalize((transpose(instance.transform) * vec4<f32>(normal, 0.0)).xyz);
+
+    // rects have two sides, their normal always faces the incoming ray
+    if(instance.shape == 1u && dot(normal, ray.direction) > 0.0) {
+        normal = -normal;
+    }
+
+    return shade_material(materials.materials[instance.material], ray, t, point_at(ray, t), normal);
+}
+
+// mirrors Light::in_scattering of the rust implementation, the fog has no
+// normal, so only the position is used
+fn fog_light(position: vec3<f32>) -> vec3<f32> {
+    let point_light_count = arrayLength(&point_lights.point_lights);
+    let directional_light_count = arrayLength(&directional_lights.directional_lights);
+    let spot_light_count = arrayLength(&spot_lights.spot_lights);
+
+    var result = vec3<f32>(0.0);
+    var ray: Ray;
+
+    ray.origin = position;
+    ray.t_min = 0.001;
+
+    for(var i: u32 = 0u; i < point_light_count; i = i + 1u) {
+        let point_light = point_lights.point_lights[i];
+
+        ray.direction = point_light.position - position;
+        ray.t_max = 1.0;
+
+        if(!shadow(ray)) {
+            result = result + point_light.color / dot(ray.direction, ray.direction);
+        }
+    }
+
+    for(var i: u32 = 0u; i < directional_light_count; i = i + 1u) {
+        let directional_light = directional_lights.directional_lights[i];
+
+        ray.direction = directional_light.direction;
+        ray.t_max = 1000.0;
+
+        if(!shadow(ray)) {
+            result = result + directional_light.color;
+        }
+    }
+
+    for(var i: u32 = 0u; i < spot_light_count; i = i + 1u) {
+        let spot_light = spot_lights.spot_lights[i];
+        let light_dir = spot_light.position - position;
+
+        let cos_angle = -dot(normalize(light_dir), spot_light.direction);
+        let falloff = select(
+            smoothStep(spot_light.cos_outer_angle, spot_light.cos_inner_angle, cos_angle),
+            select(0.0, 1.0, cos_angle >= spot_light.cos_outer_angle),
+            spot_light.cos_inner_angle <= spot_light.cos_outer_angle
+        );
+
+        // points outside of the cone are not lit, which saves the shadow ray
+        if(falloff <= 0.0) {
+            continue;
+        }
+
+        ray.direction = light_dir;
+        ray.t_max = 1.0;
+
+        if(!shadow(ray)) {
+            result = result + falloff / dot(light_dir, light_dir) * spot_light.color;
+        }
+    }
+
+    return result;
+}
+
+// mirrors Raytracer::in_scattering of the rust implementation
+fn in_scattering(ray: Ray, distance: f32) -> vec3<f32> {
+    let fog = args.raytracer_args.fog;
+    let phase = 1.0 / (4.0 * 3.14159265358979);
+
+    let step = max(distance - ray.t_min, 0.0) / f32(fog.steps);
+    let offset = unit(hash(ray.seed ^ 0x85ebca6bu));
+
+    var result = vec3<f32>(0.0);
+
+    for(var i: u32 = 0u; i < fog.steps; i = i + 1u) {
+        let t = (f32(i) + offset) * step;
+        let intensity = fog_light(point_at(ray, ray.t_min + t));
+
+        result = result + intensity * fog.color * fog.density * phase * exp(-fog.density * t) * step;
+    }
+
+    return result;
+}
 
 fn radiance(ray: Ray) -> vec3<f32> {
     var ray = ray;
     var reflective_color = vec3<f32>(1.0);
     var radiance = vec3<f32>(0.0);
 
-    for(var i: u32 = 0u; i < RAY_BOUNCES; i = i + 1u) {
+    count_samples();
+
+    for(var i: u32 = 0u; i < args.raytracer_args.bounces; i = i + 1u) {
+        count_rays_cast();
+
         var spheres_intersection: SpheresIntersection;
 
         let is_sphere_intersected = intersect_spheres(ray, &spheres_intersection);
@@ -318,23 +1433,99 @@ fn radiance(ray: Ray) -> vec3<f32> {
 
         let is_rect_intersected = intersect_rects(ray, &rects_intersection);
 
+        var triangles_intersection: TrianglesIntersection;
+
+        let is_triangle_intersected = intersect_triangles(ray, &triangles_intersection);
+
+        var planes_intersection: PlanesIntersection;
+
+        let is_plane_intersected = intersect_planes(ray, &planes_intersection);
+
+        var discs_intersection: DiscsIntersection;
+
+        let is_disc_intersected = intersect_discs(ray, &discs_intersection);
+
+        var capsules_intersection: CapsulesIntersection;
+
+        let is_capsule_intersected = intersect_capsules(ray, &capsules_intersection);
+
+        var instances_intersection: InstancesIntersection;
+
+        let is_instance_intersected = intersect_instances(ray, &instances_intersection);
+
+        let sphere_t = spheres_intersection.nearest_intersection_result;
+        let rect_t = rects_intersection.nearest_intersection_result;
+        let triangle_t = triangles_intersection.nearest_intersection_result;
+        let plane_t = planes_intersection.nearest_intersection_result;
+        let disc_t = discs_intersection.nearest_intersection_result;
+        let capsule_t = capsules_intersection.nearest_intersection_result;
+        let instance_t = instances_intersection.nearest_intersection_result;
+
+        // only the nearest hit is shaded
+        let nearest_t = min(min(min(sphere_t, rect_t), triangle_t), min(min(plane_t, disc_t), min(capsule_t, instance_t)));
+
         var shading_result: ShadingResult;
 
-        shading_result.emissive_color = args.raytracer_args.background.color;
+        shading_result.emissive_color = background_radiance(ray.direction) * (1.0 + args.raytracer_args.loudness);
         shading_result.reflection = false;
 
-        if(is_sphere_intersected && spheres_intersection.nearest_intersection_result < rects_intersection.nearest_intersection_result) {
-            shading_result = shade_sphere(spheres.spheres[spheres_intersection.nearest_intersected_sphere], ray, spheres_intersection.nearest_intersection_result);
+        if(is_sphere_intersected && sphere_t == nearest_t) {
+            shading_result = shade_sphere(spheres.spheres[spheres_intersection.nearest_intersected_sphere], ray, sphere_t);
+        }
+
+        if(is_rect_intersected && rect_t == nearest_t) {
+            shading_result = rect_shade(rects.rects[rects_intersection.nearest_intersected_rect], ray, rect_t);
+        }
+
+        if(is_triangle_intersected && triangle_t == nearest_t) {
+            shading_result = shade_triangle(triangles.triangles[triangles_intersection.nearest_intersected_triangle], ray, triangle_t);
+        }
+
+        if(is_plane_intersected && plane_t == nearest_t) {
+            shading_result = shade_plane(planes.planes[planes_intersection.nearest_intersected_plane], ray, plane_t);
+        }
+
+        if(is_disc_intersected && disc_t == nearest_t) {
+            shading_result = shade_disc(discs.discs[discs_intersection.nearest_intersected_disc], ray, disc_t);
+        }
+
+        if(is_capsule_intersected && capsule_t == nearest_t) {
+            shading_result = shade_capsule(capsules.capsules[capsules_intersection.nearest_intersected_capsule], ray, capsule_t);
+        }
+
+        if(is_instance_intersected && instance_t == nearest_t) {
+            shading_result = shade_instance(instances.instances[instances_intersection.nearest_intersected_instance], ray, instance_t);
         }
 
-        if(is_rect_intersected && rects_intersection.nearest_intersection_result < spheres_intersection.nearest_intersection_result) {
-            shading_result = rect_shade(rects.rects[rects_intersection.nearest_intersected_rect]);
+        // only the primary ray is marched through the fog, the light of the
+        // hit surface is dimmed by the fog in front of it
+        let fog = args.raytracer_args.fog;
+
+        if(i == 0u && fog.density > 0.0 && fog.steps > 0u) {
+            let is_intersected = is_sphere_intersected || is_rect_intersected || is_triangle_intersected
+                || is_plane_intersected || is_disc_intersected || is_capsule_intersected
+                || is_instance_intersected;
+            let distance = min(select(ray.t_max, nearest_t, is_intersected), fog.distance);
+
+            radiance = radiance + in_scattering(ray, distance);
+            reflective_color = reflective_color * exp(-fog.density * (distance - ray.t_min));
         }
         
         if(shading_result.reflection) { 
             ray = shading_result.reflection_ray;
             radiance = radiance + reflective_color * shading_result.emissive_color;
             reflective_color = reflective_color * shading_result.reflective_color;
+
+            // mirrors the russian roulette of the rust implementation
+            if (args.raytracer_args.roulette_depth != 0u && i + 1u >= args.raytracer_args.roulette_depth) {
+                let survival = min(max(max(reflective_color.x, reflective_color.y), reflective_color.z), 1.0);
+
+                if (unit(hash(~ray.seed)) >= survival) {
+                    break;
+                }
+
+                reflective_color = reflective_color / survival;
+            }
         } else {
             radiance = radiance + reflective_color * shading_result.emissive_color;
             break;
@@ -347,12 +1538,27 @@ fn radiance(ray: Ray) -> vec3<f32> {
 fn prime_ray(camera: Camera, sample: vec2<f32>) -> Ray {
     var ray: Ray;
 
-    let sensor = (sample / camera.screen_size * 2.0 - vec2<f32>(1.0))
-            * camera.tan_fov
-            * vec2<f32>(1.0, -(camera.screen_size.y / camera.screen_size.x));
+    if(camera.panoramic != 0u) {
+        let uv = (sample - camera.screen_offset) / camera.screen_size;
+
+        // the center of the image looks along the positive z axis
+        let longitude = (uv.x - 0.5) * 6.28318530717959;
+        let latitude = (0.5 - uv.y) * 3.14159265358979;
+
+        ray.direction = vec3<f32>(
+            cos(latitude) * sin(longitude),
+            sin(latitude),
+            cos(latitude) * cos(longitude)
+        );
+    } else {
+        let sensor = ((sample - camera.screen_offset) / camera.screen_size * 2.0 - vec2<f32>(1.0))
+                * camera.tan_fov
+                * vec2<f32>(1.0, -(camera.screen_size.y / camera.screen_size.x));
+
+        ray.direction = normalize(vec3<f32>(sensor, 1.0));
+    }
 
     ray.origin = vec3<f32>(0.0);
-    ray.direction = normalize(vec3<f32>(sensor, 1.0));
     ray.t_min = camera.t_min;
     ray.t_max = camera.t_max;
 
@@ -369,6 +1575,48 @@ fn tonemapFilmic(x: vec3<f32>) -> vec3<f32> {
     return pow(result, vec3<f32>(2.2));
 }
 
+fn tonemapAces(x: vec3<f32>) -> vec3<f32> {
+    let X = max(vec3<f32>(0.0), x);
+    let result = (X * (2.51 * X + 0.03)) / (X * (2.43 * X + 0.59) + 0.14);
+    return clamp(result, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn tonemapReinhard(x: vec3<f32>) -> vec3<f32> {
+    let X = max(vec3<f32>(0.0), x);
+    return X / (X + 1.0);
+}
+
+fn grade(x: vec3<f32>) -> vec3<f32> {
+    let grading = args.raytracer_args.color_grading;
+    let middle_grey = 0.18;
+
+    var color = max(x, vec3<f32>(0.0));
+    color = middle_grey * pow(color / middle_grey, vec3<f32>(grading.contrast));
+
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    color = max(luminance + (color - luminance) * grading.saturation, vec3<f32>(0.0));
+
+    return pow(color, vec3<f32>(1.0 / grading.gamma));
+}
+
+// mirrors the order of the variants of the rust tonemapper
+fn tonemap(x: vec3<f32>) -> vec3<f32> {
+    switch (args.raytracer_args.tonemapper) {
+        case 1u: {
+            return tonemapAces(x);
+        }
+        case 2u: {
+            return tonemapReinhard(x);
+        }
+        case 3u: {
+            return x;
+        }
+        default: {
+            return tonemapFilmic(x);
+        }
+    }
+}
+
 [[stage(vertex)]]
 fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
     let x = f32(vertex_index & 1u) * 2.0 - 1.0;
@@ -381,7 +1629,38 @@ fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]]
 
 [[stage(fragment)]]
 fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
-    let prime_ray = prime_ray(args.raytracer_args.camera, position.xy);
+    let supersampling = max(args.raytracer_args.supersampling, 1u);
+    var color = vec3<f32>(0.0);
+
+    // the pixel is split into a grid and the cell centers are averaged, with
+    // jitter the cells are sampled at random positions instead
+    for(var y = 0u; y < supersampling; y = y + 1u) {
+        for(var x = 0u; x < supersampling; x = x + 1u) {
+            var cell = vec2<f32>(0.5);
+
+            if (args.raytracer_args.jitter != 0u) {
+                let dimension = (y * supersampling + x) * 2u;
+
+                cell = vec2<f32>(random(position.xy, dimension), random(position.xy, dimension + 1u));
+            }
+
+            let offset = (vec2<f32>(f32(x), f32(y)) + cell) / f32(supersampling) - 0.5;
+
+            // the dimensions following the jitter seed the path
+            var prime_ray = prime_ray(args.raytracer_args.camera, position.xy + offset);
+            prime_ray.seed = random_state(position.xy, supersampling * supersampling * 2u + y * supersampling + x);
+            shutter_time = unit(hash(prime_ray.seed ^ 0xc2b2ae35u)) - 0.5;
+
+            // HDR outputs roll off the highlights themselves
+            let sample_radiance = radiance(prime_ray) * exp2(args.raytracer_args.color_grading.exposure);
+
+            if (args.raytracer_args.hdr != 0u) {
+                color = color + sample_radiance;
+            } else {
+                color = color + tonemap(sample_radiance);
+            }
+        }
+    }
 
-    return vec4<f32>(tonemapFilmic(radiance(prime_ray)), 1.0);
+    return vec4<f32>(grade(color / f32(supersampling * supersampling)), 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,398 @@
+use sphere_audio_visualizer_core::{
+    glam::Vec3A,
+    raytracing::{
+        light::{DirectionalLight, PointLight, SpotLight},
+        shape::{Material, Plane, Sphere},
+    },
+    sdf::SdfTracerArgs,
+};
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BufferUsages, ColorTargetState, ColorWrites, Device,
+    FragmentState, PolygonMode, PrimitiveState, PrimitiveTopology, RenderPipeline,
+    RenderPipelineDescriptor, TextureFormat, TextureView, VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::{
+        scene::{BasicRaytracerScene, ShapeCollection, Viewport},
+        wgpu::{
+            utils::{
+                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+            },
+            Pipeline, HDR_FORMAT,
+        },
+    },
+};
+
+use super::draw_fullscreen;
+
+/// Defines the default distance over which the spheres are blended
+pub const SDF_TRACER_SMOOTHNESS: f32 = 0.5;
+
+/// Defines the default maximum number of marching steps per pixel
+pub const SDF_TRACER_STEPS: u32 = 128;
+
+/// Defines the default distance to a surface, relative to the distance
+/// travelled, below which a ray hits the surface
+pub const SDF_TRACER_EPSILON: f32 = 0.001;
+
+/// Defines the default strength with which the loudness raises the
+/// brightness of the background
+pub const SDF_TRACER_PULSE: f32 = 0.5;
+
+struct SdfTracerPipeline(RenderPipeline, TextureFormat);
+
+impl SdfTracerPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("sdf_tracer.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-sdf-tracer-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+/// The pipeline module for rendering raytracer scenes by sphere tracing their
+/// signed distance function. The spheres are blended into each other like
+/// metaballs. Only spheres and planes are drawn and lit without reflections.
+pub struct SdfTracer {
+    smoothness: f32,
+    steps: u32,
+    epsilon: f32,
+    pulse: f32,
+    loudness: f32,
+    pipeline: Option<SdfTracerPipeline>,
+}
+
+impl SdfTracer {
+    /// Returns the distance over which the spheres are blended
+    pub fn smoothness(&self) -> f32 {
+        self.smoothness
+    }
+
+    /// Sets the distance over which the spheres are blended. Zero keeps the
+    /// spheres apart.
+    pub fn set_smoothness(&mut self, smoothness: f32) -> &mut Self {
+        self.smoothness = smoothness.max(0.0);
+        self
+    }
+
+    /// Sets the distance over which the spheres are blended
+    pub fn with_smoothness(mut self, smoothness: f32) -> Self {
+        self.set_smoothness(smoothness);
+        self
+    }
+
+    /// Returns the maximum number of marching steps per pixel
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Sets the maximum number of marching steps per pixel. Rays running out
+    /// of steps show the background.
+    pub fn set_steps(&mut self, steps: u32) -> &mut Self {
+        self.steps = steps.max(1);
+        self
+    }
+
+    /// Sets the maximum number of marching steps per pixel
+    pub fn with_steps(mut self, steps: u32) -> Self {
+        self.set_steps(steps);
+        self
+    }
+
+    /// Returns the distance to a surface, relative to the distance
+    /// travelled, below which a ray hits the surface
+    pub fn epsilon(&self) -> f32 {
+        self.epsilon
+    }
+
+    /// Sets the distance to a surface, relative to the distance travelled,
+    /// below which a ray hits the surface. Larger values need fewer steps,
+    /// but round off the edges.
+    pub fn set_epsilon(&mut self, epsilon: f32) -> &mut Self {
+        self.epsilon = epsilon.max(f32::EPSILON);
+        self
+    }
+
+    /// Sets the distance to a surface below which a ray hits the surface
+    pub fn with_epsilon(mut self, epsilon: f32) -> Self {
+        self.set_epsilon(epsilon);
+        self
+    }
+
+    /// Returns the strength with which the loudness raises the brightness
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse;
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+}
+
+impl Default for SdfTracer {
+    fn default() -> Self {
+        Self {
+            smoothness: SDF_TRACER_SMOOTHNESS,
+            steps: SDF_TRACER_STEPS,
+            epsilon: SDF_TRACER_EPSILON,
+            pulse: SDF_TRACER_PULSE,
+            loudness: 0.0,
+            pipeline: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`SdfTracer`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SdfTracerSettings {
+    /// The distance over which the spheres are blended
+    pub smoothness: f32,
+    /// The maximum number of marching steps per pixel
+    pub steps: u32,
+    /// The distance to a surface below which a ray hits the surface
+    pub epsilon: f32,
+    /// The strength with which the loudness raises the brightness
+    pub pulse: f32,
+}
+
+impl Default for SdfTracerSettings {
+    fn default() -> Self {
+        Self {
+            smoothness: SDF_TRACER_SMOOTHNESS,
+            steps: SDF_TRACER_STEPS,
+            epsilon: SDF_TRACER_EPSILON,
+            pulse: SDF_TRACER_PULSE,
+        }
+    }
+}
+
+impl Module for SdfTracer {
+    type Settings = SdfTracerSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_smoothness(settings.smoothness)
+            .set_steps(settings.steps)
+            .set_epsilon(settings.epsilon)
+            .set_pulse(settings.pulse)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        SdfTracerSettings {
+            smoothness: self.smoothness(),
+            steps: self.steps(),
+            epsilon: self.epsilon(),
+            pulse: self.pulse(),
+        }
+    }
+}
+
+impl Pipeline<BasicRaytracerScene> for SdfTracer {
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
+    fn render(
+        &mut self,
+        scene: BasicRaytracerScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| SdfTracerPipeline::new(device, output_format));
+
+        if pipeline.1 != output_format {
+            *pipeline = SdfTracerPipeline::new(device, output_format);
+        }
+
+        let memory_tracker = command_queue.memory_tracker();
+
+        let spheres = memory_tracker.fit(
+            "Spheres",
+            scene
+                .shapes::<Sphere>()
+                .map(ShapeCollection::shapes)
+                .unwrap_or(&[]),
+        );
+        let planes = memory_tracker.fit(
+            "Planes",
+            scene
+                .shapes::<Plane>()
+                .map(ShapeCollection::shapes)
+                .unwrap_or(&[]),
+        );
+        // the materials are not fitted, as every shape relies on its material
+        let materials = scene.materials();
+
+        let point_lights = memory_tracker.fit(
+            "Point Lights",
+            scene
+                .lights::<PointLight>()
+                .map(Vec::as_slice)
+                .unwrap_or(&[]),
+        );
+        let directional_lights = memory_tracker.fit(
+            "Directional Lights",
+            scene
+                .lights::<DirectionalLight>()
+                .map(Vec::as_slice)
+                .unwrap_or(&[]),
+        );
+        let spot_lights = memory_tracker.fit(
+            "Spot Lights",
+            scene
+                .lights::<SpotLight>()
+                .map(Vec::as_slice)
+                .unwrap_or(&[]),
+        );
+
+        let args = SdfTracerArgs {
+            camera: scene.camera.clone(),
+            background: scene.background.color,
+            smoothness: self.smoothness,
+            epsilon: self.epsilon,
+            loudness: self.loudness * self.pulse,
+            steps: self.steps,
+            sphere_count: spheres.len() as u32,
+            plane_count: planes.len() as u32,
+            hdr: (output_format == HDR_FORMAT) as u32,
+        };
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &args,
+        });
+        memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+        // empty buffers can't be bound, the placeholder shapes are never
+        // sampled as their counts are zero and the placeholder lights light
+        // nothing as their intensity is zero
+        let placeholder_sphere = [Sphere::new(Vec3A::ZERO, 0.0, 0)];
+        let placeholder_plane = [Plane::new(Vec3A::ZERO, Vec3A::Y, 0)];
+        let placeholder_material = [Material::new(Vec3A::ZERO)];
+        let placeholder_point_light = [PointLight::new(Vec3A::ZERO, Vec3A::ZERO)];
+        let placeholder_directional_light = [DirectionalLight::new(Vec3A::Y, Vec3A::ZERO)];
+        let placeholder_spot_light = [SpotLight::new(Vec3A::ZERO, Vec3A::Y, 0.0, 0.0, Vec3A::ZERO)];
+
+        let spheres_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: match spheres.is_empty() {
+                true => &placeholder_sphere[..],
+                false => spheres,
+            },
+        });
+        let planes_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: match planes.is_empty() {
+                true => &placeholder_plane[..],
+                false => planes,
+            },
+        });
+        let materials_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: match materials.is_empty() {
+                true => &placeholder_material[..],
+                false => materials,
+            },
+        });
+        let point_lights_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: match point_lights.is_empty() {
+                true => &placeholder_point_light[..],
+                false => point_lights,
+            },
+        });
+        let directional_lights_buffer =
+            device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+                label: None,
+                usage: BufferUsages::STORAGE,
+                value: match directional_lights.is_empty() {
+                    true => &placeholder_directional_light[..],
+                    false => directional_lights,
+                },
+            });
+        let spot_lights_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: match spot_lights.is_empty() {
+                true => &placeholder_spot_light[..],
+                false => spot_lights,
+            },
+        });
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[
+                args_buffer.bind_group_entry(0).unwrap(),
+                spheres_buffer.bind_group_entry(1).unwrap(),
+                planes_buffer.bind_group_entry(2).unwrap(),
+                materials_buffer.bind_group_entry(3).unwrap(),
+                point_lights_buffer.bind_group_entry(4).unwrap(),
+                directional_lights_buffer.bind_group_entry(5).unwrap(),
+                spot_lights_buffer.bind_group_entry(6).unwrap(),
+            ],
+            layout: &pipeline.0.get_bind_group_layout(0),
+        });
+
+        let viewport = Viewport {
+            offset: scene.camera.screen_offset(),
+            size: scene.camera.screen_size(),
+        };
+
+        draw_fullscreen(
+            command_queue.command_encoder(device),
+            &pipeline.0,
+            &bind_group,
+            target_texture,
+            &viewport,
+        );
+    }
+}
@@ -0,0 +1,398 @@
+// mirrors sphere_audio_visualizer_core::sdf::SdfTracer
+
+struct Camera {
+    transform: mat4x4<f32>;
+    screen_size: vec2<f32>;
+    screen_offset: vec2<f32>;
+    tan_fov: f32;
+    t_min: f32;
+    t_max: f32;
+    panoramic: u32;
+};
+
+struct Args {
+    camera: Camera;
+    background: vec3<f32>;
+    _pad0: f32;
+    smoothness: f32;
+    epsilon: f32;
+    loudness: f32;
+    steps: u32;
+    sphere_count: u32;
+    plane_count: u32;
+    hdr: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+struct Sphere {
+    position: vec3<f32>;
+    _pad0: f32;
+    radius: f32;
+    material: u32;
+    normal_strength: f32;
+    emission: f32;
+    motion: vec3<f32>;
+};
+
+struct Spheres {
+    spheres: array<Sphere>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> spheres: Spheres;
+
+struct Plane {
+    position: vec3<f32>;
+    _pad0: f32;
+    normal: vec3<f32>;
+    _pad1: f32;
+    material: u32;
+};
+
+struct Planes {
+    planes: array<Plane>;
+};
+
+[[group(0), binding(2)]]
+var<storage, read> planes: Planes;
+
+struct Material {
+    albedo: vec3<f32>;
+    _pad0: f32;
+    emission: vec3<f32>;
+    _pad1: f32;
+    roughness: f32;
+    metallic: f32;
+    ior: f32;
+    transmission: f32;
+};
+
+struct Materials {
+    materials: array<Material>;
+};
+
+[[group(0), binding(3)]]
+var<storage, read> materials: Materials;
+
+struct PointLight {
+    position: vec3<f32>;
+    color: vec3<f32>;
+};
+
+struct PointLights {
+    point_lights: array<PointLight>;
+};
+
+[[group(0), binding(4)]]
+var<storage, read> point_lights: PointLights;
+
+struct DirectionalLight {
+    direction: vec3<f32>;
+    color: vec3<f32>;
+};
+
+struct DirectionalLights {
+    directional_lights: array<DirectionalLight>;
+};
+
+[[group(0), binding(5)]]
+var<storage, read> directional_lights: DirectionalLights;
+
+struct SpotLight {
+    position: vec3<f32>;
+    direction: vec3<f32>;
+    color: vec3<f32>;
+    _pad0: f32;
+    cos_inner_angle: f32;
+    cos_outer_angle: f32;
+};
+
+struct SpotLights {
+    spot_lights: array<SpotLight>;
+};
+
+[[group(0), binding(6)]]
+var<storage, read> spot_lights: SpotLights;
+
+struct Ray {
+    origin: vec3<f32>;
+    t_min: f32;
+    direction: vec3<f32>;
+    t_max: f32;
+};
+
+fn point_at(ray: Ray, t: f32) -> vec3<f32> {
+    return ray.origin + ray.direction * t;
+}
+
+fn prime_ray(camera: Camera, sample: vec2<f32>) -> Ray {
+    var ray: Ray;
+
+    if(camera.panoramic != 0u) {
+        let uv = (sample - camera.screen_offset) / camera.screen_size;
+
+        // the center of the image looks along the positive z axis
+        let longitude = (uv.x - 0.5) * 6.28318530717959;
+        let latitude = (0.5 - uv.y) * 3.14159265358979;
+
+        ray.direction = vec3<f32>(
+            cos(latitude) * sin(longitude),
+            sin(latitude),
+            cos(latitude) * cos(longitude)
+        );
+    } else {
+        let sensor = ((sample - camera.screen_offset) / camera.screen_size * 2.0 - vec2<f32>(1.0))
+                * camera.tan_fov
+                * vec2<f32>(1.0, -(camera.screen_size.y / camera.screen_size.x));
+
+        ray.direction = normalize(vec3<f32>(sensor, 1.0));
+    }
+
+    ray.origin = (camera.transform * vec4<f32>(0.0, 0.0, 0.0, 1.0)).xyz;
+    ray.direction = normalize((camera.transform * vec4<f32>(ray.direction, 0.0)).xyz);
+    ray.t_min = camera.t_min;
+    ray.t_max = camera.t_max;
+
+    return ray;
+}
+
+// mirrors sphere_audio_visualizer_core::utils::math::smooth_min, the weight
+// of a is returned in the y component
+fn smooth_min(a: f32, b: f32, k: f32) -> vec2<f32> {
+    if (k <= 0.0) {
+        return select(vec2<f32>(b, 0.0), vec2<f32>(a, 1.0), a < b);
+    }
+
+    let h = clamp(0.5 + 0.5 * (b - a) / k, 0.0, 1.0);
+
+    return vec2<f32>(mix(b, a, h) - k * h * (1.0 - h), h);
+}
+
+struct SdfSample {
+    distance: f32;
+    albedo: vec3<f32>;
+    emission: vec3<f32>;
+};
+
+fn nearest(position: vec3<f32>) -> SdfSample {
+    let sphere_count = min(arrayLength(&spheres.spheres), args.sphere_count);
+    let plane_count = min(arrayLength(&planes.planes), args.plane_count);
+
+    var nearest: SdfSample;
+    nearest.distance = 1000000.0;
+    nearest.albedo = vec3<f32>(0.0);
+    nearest.emission = vec3<f32>(0.0);
+
+    for(var i: u32 = 0u; i < sphere_count; i = i + 1u) {
+        let sphere = spheres.spheres[i];
+        let material = materials.materials[sphere.material];
+        let distance = distance(sphere.position, position) - sphere.radius;
+        let emission = material.emission + material.albedo * sphere.emission;
+
+        if (i == 0u) {
+            nearest.distance = distance;
+            nearest.albedo = material.albedo;
+            nearest.emission = emission;
+        } else {
+            // the materials are blended like the distances
+            let blend = smooth_min(distance, nearest.distance, args.smoothness);
+
+            nearest.distance = blend.x;
+            nearest.albedo = mix(nearest.albedo, material.albedo, blend.y);
+            nearest.emission = mix(nearest.emission, emission, blend.y);
+        }
+    }
+
+    for(var i: u32 = 0u; i < plane_count; i = i + 1u) {
+        let plane = planes.planes[i];
+        let distance = abs(dot(position - plane.position, plane.normal));
+
+        if (distance < nearest.distance) {
+            let material = materials.materials[plane.material];
+
+            nearest.distance = distance;
+            nearest.albedo = material.albedo;
+            nearest.emission = material.emission;
+        }
+    }
+
+    return nearest;
+}
+
+fn sdf(position: vec3<f32>) -> f32 {
+    return nearest(position).distance;
+}
+
+// returns the distance along the ray to the hit or a negative value if the
+// ray misses
+fn march(ray: Ray) -> f32 {
+    var t = ray.t_min;
+
+    for(var i: u32 = 0u; i < args.steps; i = i + 1u) {
+        let distance = sdf(point_at(ray, t));
+
+        // the precision is relaxed with the distance, which saves steps
+        // where a pixel covers a large area
+        if (distance < args.epsilon * max(t, 1.0)) {
+            return t;
+        }
+
+        t = t + distance;
+
+        if (t > ray.t_max) {
+            break;
+        }
+    }
+
+    return -1.0;
+}
+
+fn normal(position: vec3<f32>) -> vec3<f32> {
+    let offset = 0.001;
+    let a = vec3<f32>(1.0, -1.0, -1.0);
+    let b = vec3<f32>(-1.0, -1.0, 1.0);
+    let c = vec3<f32>(-1.0, 1.0, -1.0);
+    let d = vec3<f32>(1.0, 1.0, 1.0);
+
+    return normalize(
+        a * sdf(position + a * offset)
+        + b * sdf(position + b * offset)
+        + c * sdf(position + c * offset)
+        + d * sdf(position + d * offset)
+    );
+}
+
+fn shadow(origin: vec3<f32>, direction: vec3<f32>, distance: f32) -> bool {
+    var ray: Ray;
+
+    ray.origin = origin;
+    ray.direction = normalize(direction);
+    ray.t_min = 0.0001;
+    ray.t_max = distance * 0.9999;
+
+    return march(ray) >= 0.0;
+}
+
+fn ambient_occlusion(position: vec3<f32>, normal: vec3<f32>) -> f32 {
+    var occlusion = 1.0;
+    for(var i: u32 = 1u; i < 6u; i = i + 1u) {
+        let sample = f32(i);
+        occlusion = occlusion - ((sample * 0.35 - sdf(position + normal * (sample * 0.35))) / pow(2.0, sample));
+    }
+    return max(occlusion, 0.0);
+}
+
+fn lambert_point_light(point_light: PointLight, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let light_dir = point_light.position - position;
+    let color = max(dot(normalize(light_dir), normal), 0.0) / dot(light_dir, light_dir) * point_light.color;
+
+    // surfaces facing away from the light are not lit, which saves the
+    // shadow ray
+    if(max(color.x, max(color.y, color.z)) <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    return select(color, vec3<f32>(0.0), shadow(position, light_dir, length(light_dir)));
+}
+
+fn lambert_directional_light(directional_light: DirectionalLight, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let color = max(dot(directional_light.direction, normal), 0.0) * directional_light.color;
+
+    if(max(color.x, max(color.y, color.z)) <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    return select(color, vec3<f32>(0.0), shadow(position, directional_light.direction, 1000.0));
+}
+
+fn lambert_spot_light(spot_light: SpotLight, position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let light_dir = spot_light.position - position;
+    let light_dir_normalized = normalize(light_dir);
+
+    // a hard edged cone has no range to fade out in
+    let cos_angle = -dot(light_dir_normalized, spot_light.direction);
+    let falloff = select(
+        smoothStep(spot_light.cos_outer_angle, spot_light.cos_inner_angle, cos_angle),
+        select(0.0, 1.0, cos_angle >= spot_light.cos_outer_angle),
+        spot_light.cos_inner_angle <= spot_light.cos_outer_angle
+    );
+
+    let color = max(dot(light_dir_normalized, normal), 0.0) * falloff / dot(light_dir, light_dir) * spot_light.color;
+
+    if(max(color.x, max(color.y, color.z)) <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    return select(color, vec3<f32>(0.0), shadow(position, light_dir, length(light_dir)));
+}
+
+fn lambert(position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let point_light_count = arrayLength(&point_lights.point_lights);
+    let directional_light_count = arrayLength(&directional_lights.directional_lights);
+    let spot_light_count = arrayLength(&spot_lights.spot_lights);
+
+    var result = vec3<f32>(0.0);
+
+    for(var i: u32 = 0u; i < point_light_count; i = i + 1u) {
+        result = result + lambert_point_light(point_lights.point_lights[i], position, normal);
+    }
+
+    for(var i: u32 = 0u; i < directional_light_count; i = i + 1u) {
+        result = result + lambert_directional_light(directional_lights.directional_lights[i], position, normal);
+    }
+
+    for(var i: u32 = 0u; i < spot_light_count; i = i + 1u) {
+        result = result + lambert_spot_light(spot_lights.spot_lights[i], position, normal);
+    }
+
+    return result;
+}
+
+fn radiance(ray: Ray) -> vec3<f32> {
+    let background = args.background * (1.0 + args.loudness);
+    let t = march(ray);
+
+    if (t < 0.0) {
+        return background;
+    }
+
+    let position = point_at(ray, t);
+    let nearest = nearest(position);
+    let normal = normal(position);
+
+    // the lighting starts above the surface, so the shadow rays don't hit
+    // the surface they start on
+    let surface = position + normal * 0.01;
+
+    let intensity = background * ambient_occlusion(surface, normal) + lambert(surface, normal);
+
+    return nearest.albedo * intensity + nearest.emission;
+}
+
+fn tonemapFilmic(x: vec3<f32>) -> vec3<f32> {
+    let X: vec3<f32> = max(vec3<f32>(0.0), x - 0.004);
+    let result: vec3<f32> = (X * (6.2 * X + 0.5)) / (X * (6.2 * X + 1.7) + 0.06);
+    return pow(result, vec3<f32>(2.2));
+}
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    let radiance = radiance(prime_ray(args.camera, position.xy));
+
+    // HDR outputs roll off the highlights themselves
+    if (args.hdr != 0u) {
+        return vec4<f32>(radiance, 1.0);
+    }
+
+    return vec4<f32>(tonemapFilmic(radiance), 1.0);
+}
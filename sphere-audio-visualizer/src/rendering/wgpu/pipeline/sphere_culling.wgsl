@@ -0,0 +1,91 @@
+struct Sphere {
+    position: vec3<f32>;
+    _pad0: f32;
+    radius: f32;
+    material: u32;
+    normal_strength: f32;
+    emission: f32;
+    motion: vec3<f32>;
+};
+
+struct CullingArgs {
+    view: mat4x4<f32>;
+    slopes: vec2<f32>;
+    pixel_scale: f32;
+    cull_threshold: f32;
+    t_min: f32;
+    t_max: f32;
+    sphere_count: atomic<u32>;
+    panoramic: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read_write> culling_args: CullingArgs;
+
+struct Spheres {
+    spheres: array<Sphere>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> input: Spheres;
+
+[[group(0), binding(2)]]
+var<storage, read_write> output: Spheres;
+
+fn visible(sphere: Sphere) -> bool {
+    let position = (culling_args.view * vec4<f32>(sphere.position, 1.0)).xyz;
+
+    // moving spheres are kept while any point of their path is visible
+    let radius = sphere.radius + length(sphere.motion) * 0.5;
+
+    // the panoramic camera sees every direction, so only the distance matters
+    if(culling_args.panoramic != 0u) {
+        let distance = length(position);
+
+        if(distance + radius < culling_args.t_min || distance - radius > culling_args.t_max) {
+            return false;
+        }
+
+        if(distance <= radius) {
+            return true;
+        }
+
+        return radius * culling_args.pixel_scale / distance >= culling_args.cull_threshold;
+    }
+
+    if(position.z + radius < culling_args.t_min || position.z - radius > culling_args.t_max) {
+        return false;
+    }
+
+    // distance of the center to the side planes of the frustum
+    let slopes = culling_args.slopes;
+    let margin = radius * sqrt(1.0 + slopes * slopes);
+    let outside = abs(position.xy) - slopes * position.z > margin;
+
+    if(any(outside)) {
+        return false;
+    }
+
+    // spheres intersecting the near plane are always kept
+    if(position.z <= radius) {
+        return true;
+    }
+
+    return radius * culling_args.pixel_scale / position.z >= culling_args.cull_threshold;
+}
+
+[[stage(compute), workgroup_size(64)]]
+fn cull([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    let index = global_id.x;
+
+    if(index >= arrayLength(&input.spheres)) {
+        return;
+    }
+
+    let sphere = input.spheres[index];
+
+    if(visible(sphere)) {
+        let slot = atomicAdd(&culling_args.sphere_count, 1u);
+        output.spheres[slot] = sphere;
+    }
+}
@@ -0,0 +1,252 @@
+use sphere_audio_visualizer_core::tunnel::TunnelArgs;
+use wgpu::{
+    include_wgsl, BindGroupDescriptor, BufferUsages, Color, ColorTargetState, ColorWrites, Device,
+    FragmentState, LoadOp, Operations, PolygonMode, PrimitiveState, PrimitiveTopology,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    TextureFormat, TextureView, VertexState,
+};
+
+use crate::{
+    module::Module,
+    rendering::{
+        scene::TunnelScene,
+        wgpu::{
+            utils::{
+                CommandQueue, {TypedBufferDeviceExt, TypedBufferInitDescriptor},
+            },
+            Pipeline,
+        },
+    },
+};
+
+use super::set_viewport;
+
+/// Defines the default maximum number of raymarching steps per pixel
+pub const TUNNEL_STEPS: u32 = 64;
+
+/// Defines the default strength with which the loudness raises the
+/// brightness of the tunnel
+pub const TUNNEL_PULSE: f32 = 0.5;
+
+struct TunnelPipeline(RenderPipeline, TextureFormat);
+
+impl TunnelPipeline {
+    fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("tunnel.wgsl"));
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("sphere-visualizer-tunnel-pipeline"),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: target_format,
+                    blend: None,
+                    write_mask: ColorWrites::COLOR,
+                }],
+            }),
+            depth_stencil: None,
+            multiview: None,
+            layout: None,
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleStrip,
+                polygon_mode: PolygonMode::Fill,
+                ..Default::default()
+            },
+            multisample: Default::default(),
+        });
+
+        Self(pipeline, target_format)
+    }
+}
+
+/// The pipeline module for rendering the tunnel scene by raymarching its
+/// signed distance function in a fragment shader
+pub struct Tunnel {
+    steps: u32,
+    pulse: f32,
+    loudness: f32,
+    pipeline: Option<TunnelPipeline>,
+}
+
+impl Tunnel {
+    /// Returns the maximum number of raymarching steps per pixel
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Sets the maximum number of raymarching steps per pixel. Fewer steps
+    /// are cheaper, but blur the far end of the tunnel.
+    pub fn set_steps(&mut self, steps: u32) -> &mut Self {
+        self.steps = steps.max(1);
+        self
+    }
+
+    /// Sets the maximum number of raymarching steps per pixel
+    pub fn with_steps(mut self, steps: u32) -> Self {
+        self.set_steps(steps);
+        self
+    }
+
+    /// Returns the strength with which the loudness raises the brightness
+    pub fn pulse(&self) -> f32 {
+        self.pulse
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn set_pulse(&mut self, pulse: f32) -> &mut Self {
+        self.pulse = pulse;
+        self
+    }
+
+    /// Sets the strength with which the loudness raises the brightness
+    pub fn with_pulse(mut self, pulse: f32) -> Self {
+        self.set_pulse(pulse);
+        self
+    }
+}
+
+impl Default for Tunnel {
+    fn default() -> Self {
+        Self {
+            steps: TUNNEL_STEPS,
+            pulse: TUNNEL_PULSE,
+            loudness: 0.0,
+            pipeline: None,
+        }
+    }
+}
+
+/// Stores the settings of the [`Tunnel`] pipeline module
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TunnelSettings {
+    /// The maximum number of raymarching steps per pixel
+    pub steps: u32,
+    /// The strength with which the loudness raises the brightness
+    pub pulse: f32,
+}
+
+impl Default for TunnelSettings {
+    fn default() -> Self {
+        Self {
+            steps: TUNNEL_STEPS,
+            pulse: TUNNEL_PULSE,
+        }
+    }
+}
+
+impl Module for Tunnel {
+    type Settings = TunnelSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_steps(settings.steps).set_pulse(settings.pulse)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        TunnelSettings {
+            steps: self.steps(),
+            pulse: self.pulse(),
+        }
+    }
+}
+
+impl Pipeline<TunnelScene> for Tunnel {
+    fn set_loudness(&mut self, loudness: f32) {
+        self.loudness = loudness;
+    }
+
+    fn render(
+        &mut self,
+        scene: TunnelScene,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        output_format: TextureFormat,
+        target_texture: &TextureView,
+    ) {
+        let pipeline = self
+            .pipeline
+            .get_or_insert_with(|| TunnelPipeline::new(device, output_format));
+
+        if pipeline.1 != output_format {
+            *pipeline = TunnelPipeline::new(device, output_format);
+        }
+
+        let memory_tracker = command_queue.memory_tracker();
+
+        let args = TunnelArgs {
+            color: scene.color,
+            size: scene.viewport.size,
+            offset: scene.viewport.offset,
+            depth: scene.depth,
+            radius: scene.radius,
+            displacement: scene.displacement,
+            ring_spacing: scene.ring_spacing,
+            twist: scene.twist,
+            fog: scene.fog,
+            loudness: self.loudness * self.pulse,
+            steps: self.steps,
+        };
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &args,
+        });
+        memory_tracker.track_buffer(std::mem::size_of_val(&args) as u64);
+
+        // the shader expects at least one band
+        let levels = match scene.levels.is_empty() {
+            true => &[0.0],
+            false => memory_tracker.fit("Tunnel Levels", scene.levels.as_slice()),
+        };
+
+        let levels_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: levels,
+        });
+
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            entries: &[
+                args_buffer.bind_group_entry(0).unwrap(),
+                levels_buffer.bind_group_entry(1).unwrap(),
+            ],
+            layout: &pipeline.0.get_bind_group_layout(0),
+        });
+
+        let command_encoder = command_queue.command_encoder(device);
+
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[RenderPassColorAttachment {
+                    view: target_texture,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            set_viewport(&mut render_pass, &scene.viewport);
+
+            render_pass.set_pipeline(&pipeline.0);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+
+            render_pass.draw(0..4, 0..1);
+        }
+    }
+}
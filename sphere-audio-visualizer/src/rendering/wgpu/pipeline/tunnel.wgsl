@@ -0,0 +1,93 @@
+let PI: f32 = 3.14159265;
+let TAU: f32 = 6.28318531;
+
+let FOCAL_LENGTH: f32 = 1.0;
+let MAX_DISTANCE: f32 = 100.0;
+let STEP_FACTOR: f32 = 0.5;
+
+struct Args {
+    color: vec3<f32>;
+    size: vec2<f32>;
+    offset: vec2<f32>;
+    depth: f32;
+    radius: f32;
+    displacement: f32;
+    ring_spacing: f32;
+    twist: f32;
+    fog: f32;
+    loudness: f32;
+    steps: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+struct Levels {
+    levels: array<f32>;
+};
+
+[[group(0), binding(1)]]
+var<storage, read> levels: Levels;
+
+fn level(angle: f32) -> f32 {
+    let count = arrayLength(&levels.levels);
+    let band = u32(abs(angle) / PI * f32(count));
+
+    return levels.levels[min(band, count - 1u)];
+}
+
+fn ring(depth: f32) -> f32 {
+    let x = depth / args.ring_spacing;
+    let x = x - floor(x) - 0.5;
+
+    return exp(-x * x * 32.0);
+}
+
+fn angle(point: vec3<f32>) -> f32 {
+    let angle = atan2(point.y, point.x) + point.z * args.twist;
+
+    return angle - floor(angle / TAU + 0.5) * TAU;
+}
+
+fn distance_to_wall(point: vec3<f32>) -> f32 {
+    let wall = args.radius - args.displacement * level(angle(point)) * ring(point.z);
+
+    return wall - length(point.xy);
+}
+
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(vertex_index & 1u) * 2.0 - 1.0;
+    let y = f32(vertex_index & 2u) - 1.0;
+
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fragment([[builtin(position)]] position: vec4<f32>) -> [[location(0)]] vec4<f32> {
+    let uv = (position.xy - args.offset) / args.size * 2.0 - 1.0;
+    let aspect = args.size.x / args.size.y;
+
+    // the shorter axis spans the field of view, so vertical viewports don't
+    // crop the sides of the tunnel
+    let scale = vec2<f32>(max(aspect, 1.0), max(1.0 / aspect, 1.0));
+
+    let origin = vec3<f32>(0.0, 0.0, args.depth);
+    let direction = normalize(vec3<f32>(uv.x * scale.x, -uv.y * scale.y, FOCAL_LENGTH));
+
+    var t = 0.0;
+    for(var i: u32 = 0u; i < args.steps; i = i + 1u) {
+        let distance = distance_to_wall(origin + direction * t);
+
+        if (distance < 0.001 * t || t > MAX_DISTANCE) {
+            break;
+        }
+
+        t = t + distance * STEP_FACTOR;
+    }
+
+    let point = origin + direction * t;
+    let brightness = (0.15 + ring(point.z) * (0.5 + level(angle(point)))) * exp(-t * args.fog);
+
+    return vec4<f32>(args.color * brightness * (1.0 + args.loudness), 1.0);
+}
@@ -0,0 +1,124 @@
+struct Args {
+    y: vec4<f32>;
+    u: vec4<f32>;
+    v: vec4<f32>;
+    size: vec2<u32>;
+    y_offset: u32;
+    y_stride: u32;
+    u_offset: u32;
+    u_stride: u32;
+    v_offset: u32;
+    v_stride: u32;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> args: Args;
+
+[[group(0), binding(1)]]
+var input: texture_2d<f32>;
+
+struct Output {
+    data: array<u32>;
+};
+
+[[group(0), binding(2)]]
+var<storage, read_write> output: Output;
+
+// the input texture is sRGB so the loaded values have to be encoded again
+fn encode_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn load(x: u32, y: u32) -> vec3<f32> {
+    let position = vec2<i32>(min(vec2<u32>(x, y), args.size - 1u));
+
+    return encode_srgb(clamp(textureLoad(input, position, 0).rgb, vec3<f32>(0.0), vec3<f32>(1.0)));
+}
+
+fn load_block(x: u32, y: u32) -> vec3<f32> {
+    let sum = load(x * 2u, y * 2u)
+        + load(x * 2u + 1u, y * 2u)
+        + load(x * 2u, y * 2u + 1u)
+        + load(x * 2u + 1u, y * 2u + 1u);
+
+    return sum * 0.25;
+}
+
+fn apply(coefficients: vec4<f32>, color: vec3<f32>) -> u32 {
+    let value = dot(coefficients.rgb, color) + coefficients.a;
+
+    return u32(clamp(value, 0.0, 255.0) + 0.5);
+}
+
+fn chroma_size() -> vec2<u32> {
+    return (args.size + 1u) / 2u;
+}
+
+[[stage(compute), workgroup_size(8, 8)]]
+fn luma([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    if (global_id.x >= args.y_stride || global_id.y >= args.size.y) {
+        return;
+    }
+
+    var word = 0u;
+    for(var i: u32 = 0u; i < 4u; i = i + 1u) {
+        let x = global_id.x * 4u + i;
+
+        if (x < args.size.x) {
+            word = word | (apply(args.y, load(x, global_id.y)) << (i * 8u));
+        }
+    }
+
+    output.data[args.y_offset + global_id.y * args.y_stride + global_id.x] = word;
+}
+
+[[stage(compute), workgroup_size(8, 8)]]
+fn chroma_planar([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    let size = chroma_size();
+
+    if (global_id.x >= args.u_stride || global_id.y >= size.y) {
+        return;
+    }
+
+    var u_word = 0u;
+    var v_word = 0u;
+    for(var i: u32 = 0u; i < 4u; i = i + 1u) {
+        let x = global_id.x * 4u + i;
+
+        if (x < size.x) {
+            let color = load_block(x, global_id.y);
+
+            u_word = u_word | (apply(args.u, color) << (i * 8u));
+            v_word = v_word | (apply(args.v, color) << (i * 8u));
+        }
+    }
+
+    output.data[args.u_offset + global_id.y * args.u_stride + global_id.x] = u_word;
+    output.data[args.v_offset + global_id.y * args.v_stride + global_id.x] = v_word;
+}
+
+[[stage(compute), workgroup_size(8, 8)]]
+fn chroma_interleaved([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    let size = chroma_size();
+
+    if (global_id.x >= args.u_stride || global_id.y >= size.y) {
+        return;
+    }
+
+    var word = 0u;
+    for(var i: u32 = 0u; i < 2u; i = i + 1u) {
+        let x = global_id.x * 2u + i;
+
+        if (x < size.x) {
+            let color = load_block(x, global_id.y);
+
+            word = word | (apply(args.u, color) << (i * 16u));
+            word = word | (apply(args.v, color) << (i * 16u + 8u));
+        }
+    }
+
+    output.data[args.u_offset + global_id.y * args.u_stride + global_id.x] = word;
+}
@@ -0,0 +1,642 @@
+use std::{num::NonZeroU32, ops::Range, sync::Arc};
+
+use sphere_audio_visualizer_core::glam::{uvec2, vec4, UVec2, Vec4};
+use wgpu::{
+    include_wgsl, BindGroup, BindGroupDescriptor, BindGroupEntry, BindingResource, Buffer,
+    BufferDescriptor, BufferUsages, ComputePassDescriptor, ComputePipeline,
+    ComputePipelineDescriptor, Device, Extent3d, ImageCopyBuffer, ImageDataLayout, Maintain,
+    Texture, TextureAspect, TextureDescriptor, TextureFormat, TextureUsages, TextureView,
+    TextureViewDescriptor, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+use crate::rendering::wgpu::utils::{
+    CommandQueue, TypedBufferDeviceExt, TypedBufferInitDescriptor,
+};
+
+use super::{RenderTarget, RenderTargetTexture};
+
+const CONVERSION_WORKGROUP_SIZE: u32 = 8;
+
+#[repr(C, align(16))]
+struct ColorConversionArgs {
+    y: Vec4,
+    u: Vec4,
+    v: Vec4,
+    size: UVec2,
+    y_offset: u32,
+    y_stride: u32,
+    u_offset: u32,
+    u_stride: u32,
+    v_offset: u32,
+    v_stride: u32,
+}
+
+/// Converts the rendered texture into a YUV layout on the GPU.
+struct ColorConversion {
+    luma_pipeline: ComputePipeline,
+    chroma_pipeline: ComputePipeline,
+    luma_bind_group: BindGroup,
+    chroma_bind_group: BindGroup,
+    output_buffer: Buffer,
+    luma_dispatch: (u32, u32),
+    chroma_dispatch: (u32, u32),
+}
+
+impl ColorConversion {
+    fn new(
+        device: &Device,
+        texture: &Texture,
+        format: OutputFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let shader_module = device.create_shader_module(&include_wgsl!("color_conversion.wgsl"));
+
+        let chroma_entry_point = match format {
+            OutputFormat::NV12 => "chroma_interleaved",
+            _ => "chroma_planar",
+        };
+
+        let luma_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("sphere-visualizer-luma-conversion-pipeline"),
+            layout: None,
+            module: &shader_module,
+            entry_point: "luma",
+        });
+
+        let chroma_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("sphere-visualizer-chroma-conversion-pipeline"),
+            layout: None,
+            module: &shader_module,
+            entry_point: chroma_entry_point,
+        });
+
+        let planes = format.planes(width, height);
+        let [y, u, v] = ColorMatrix::for_height(height).coefficients();
+
+        // offsets and strides are passed in words since the shader writes
+        // four bytes at once
+        let word = |plane: Option<&OutputPlane>| {
+            plane.map_or((0, 0), |plane| {
+                (plane.offset as u32 / 4, plane.stride as u32 / 4)
+            })
+        };
+
+        let (y_offset, y_stride) = word(planes.get(0));
+        let (u_offset, u_stride) = word(planes.get(1));
+        let (v_offset, v_stride) = word(planes.get(2));
+
+        let args_buffer = device.create_typed_buffer_init(&TypedBufferInitDescriptor {
+            label: None,
+            usage: BufferUsages::STORAGE,
+            value: &ColorConversionArgs {
+                y,
+                u,
+                v,
+                size: uvec2(width, height),
+                y_offset,
+                y_stride,
+                u_offset,
+                u_stride,
+                v_offset,
+                v_stride,
+            },
+        });
+
+        let output_buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            mapped_at_creation: false,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            size: format.size(width, height) as u64,
+        });
+
+        let texture_view = texture.create_view(&TextureViewDescriptor::default());
+
+        let bind_group = |pipeline: &ComputePipeline| {
+            device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                entries: &[
+                    args_buffer.bind_group_entry(0).unwrap(),
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::TextureView(&texture_view),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: output_buffer.as_entire_binding(),
+                    },
+                ],
+                layout: &pipeline.get_bind_group_layout(0),
+            })
+        };
+
+        let luma_bind_group = bind_group(&luma_pipeline);
+        let chroma_bind_group = bind_group(&chroma_pipeline);
+
+        let chroma_height = (height + 1) / 2;
+
+        Self {
+            luma_dispatch: (
+                y_stride.div_ceil(CONVERSION_WORKGROUP_SIZE),
+                height.div_ceil(CONVERSION_WORKGROUP_SIZE),
+            ),
+            chroma_dispatch: (
+                u_stride.div_ceil(CONVERSION_WORKGROUP_SIZE),
+                chroma_height.div_ceil(CONVERSION_WORKGROUP_SIZE),
+            ),
+            luma_pipeline,
+            chroma_pipeline,
+            luma_bind_group,
+            chroma_bind_group,
+            output_buffer,
+        }
+    }
+}
+
+struct TextureBufferBundle {
+    texture: Texture,
+    buffer: Buffer,
+    color_conversion: Option<ColorConversion>,
+}
+
+/// A [`RenderTarget`] used for offscreen rendering
+pub struct OffscreenTarget {
+    texture_buffer_bundle: Option<Arc<TextureBufferBundle>>,
+    texture_descriptor: TextureDescriptor<'static>,
+    image_data_layout: ImageDataLayout,
+    bytes_per_row: u32,
+    format: OutputFormat,
+}
+
+impl OffscreenTarget {
+    /// Creates a new instance using the specified [`OutputFormat`]
+    pub fn new(format: OutputFormat) -> Self {
+        let texture_descriptor = TextureDescriptor {
+            label: None,
+            dimension: wgpu::TextureDimension::D2,
+            format: format.into(),
+            mip_level_count: 1,
+            sample_count: 1,
+            size: Extent3d {
+                width: 0,
+                height: 0,
+                depth_or_array_layers: 1,
+            },
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::COPY_SRC
+                | TextureUsages::TEXTURE_BINDING,
+        };
+
+        Self {
+            texture_buffer_bundle: None,
+            texture_descriptor,
+            image_data_layout: ImageDataLayout::default(),
+            bytes_per_row: 0,
+            format,
+        }
+    }
+
+    /// Returns the [`OutputFormat`] of target texture
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+}
+
+impl RenderTarget for OffscreenTarget {
+    type Texture = OffscreenTargetTexture;
+
+    fn target_format(&self) -> TextureFormat {
+        self.texture_descriptor.format
+    }
+
+    fn target_texture(&mut self, width: u32, height: u32, device: &Device) -> Self::Texture {
+        if self.texture_buffer_bundle.is_none()
+            || self.texture_descriptor.size.width != width
+            || self.texture_descriptor.size.height != height
+        {
+            self.texture_descriptor = TextureDescriptor {
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                ..self.texture_descriptor
+            };
+
+            let texture = device.create_texture(&self.texture_descriptor);
+
+            let size = if self.format.is_yuv() {
+                self.format.size(width, height) as u32
+            } else {
+                self.bytes_per_row = (width * self.format.size_per_pixel() as u32)
+                    .div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT)
+                    * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+                self.bytes_per_row * height
+            };
+
+            let buffer = device.create_buffer(&BufferDescriptor {
+                label: None,
+                mapped_at_creation: false,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                size: size as u64,
+            });
+
+            self.image_data_layout = ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(self.bytes_per_row),
+                rows_per_image: NonZeroU32::new(height),
+            };
+
+            let color_conversion = self
+                .format
+                .is_yuv()
+                .then(|| ColorConversion::new(device, &texture, self.format, width, height));
+
+            self.texture_buffer_bundle = Some(Arc::new(TextureBufferBundle {
+                texture,
+                buffer,
+                color_conversion,
+            }));
+        }
+
+        let texture_buffer_bundle = self.texture_buffer_bundle.clone().unwrap();
+
+        let texture_view = texture_buffer_bundle
+            .texture
+            .create_view(&TextureViewDescriptor {
+                label: None,
+                format: None,
+                dimension: None,
+                aspect: TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: None,
+                base_array_layer: 0,
+                array_layer_count: None,
+            });
+
+        OffscreenTargetTexture {
+            texture_buffer_bundle,
+            texture_view,
+            image_data_layout: self.image_data_layout.clone(),
+            subpixels_per_row: self.bytes_per_row,
+            copy_size: self.texture_descriptor.size.clone(),
+            format: self.format,
+        }
+    }
+}
+
+/// The [`RenderTargetTexture`] of the [`OffscreenTarget`]
+pub struct OffscreenTargetTexture {
+    texture_view: TextureView,
+    texture_buffer_bundle: Arc<TextureBufferBundle>,
+    image_data_layout: ImageDataLayout,
+    subpixels_per_row: u32,
+    copy_size: Extent3d,
+    format: OutputFormat,
+}
+
+impl OffscreenTargetTexture {
+    fn convert(
+        &self,
+        color_conversion: &ColorConversion,
+        device: &Device,
+        queue: &mut CommandQueue,
+    ) {
+        let command_encoder = queue.command_encoder(device);
+
+        {
+            let mut compute_pass =
+                command_encoder.begin_compute_pass(&ComputePassDescriptor { label: None });
+
+            compute_pass.set_pipeline(&color_conversion.luma_pipeline);
+            compute_pass.set_bind_group(0, &color_conversion.luma_bind_group, &[]);
+            compute_pass.dispatch(
+                color_conversion.luma_dispatch.0,
+                color_conversion.luma_dispatch.1,
+                1,
+            );
+
+            compute_pass.set_pipeline(&color_conversion.chroma_pipeline);
+            compute_pass.set_bind_group(0, &color_conversion.chroma_bind_group, &[]);
+            compute_pass.dispatch(
+                color_conversion.chroma_dispatch.0,
+                color_conversion.chroma_dispatch.1,
+                1,
+            );
+        }
+
+        command_encoder.copy_buffer_to_buffer(
+            &color_conversion.output_buffer,
+            0,
+            &self.texture_buffer_bundle.buffer,
+            0,
+            self.format
+                .size(self.copy_size.width, self.copy_size.height) as u64,
+        );
+    }
+}
+
+impl RenderTargetTexture for OffscreenTargetTexture {
+    type Output = OffscreenTargetOutput;
+
+    fn texture_view(&self) -> &TextureView {
+        &self.texture_view
+    }
+
+    fn present(self, device: &Device, queue: &mut CommandQueue) -> Self::Output {
+        if let Some(color_conversion) = &self.texture_buffer_bundle.color_conversion {
+            self.convert(color_conversion, device, queue);
+        } else {
+            let command_encoder = queue.command_encoder(device);
+
+            command_encoder.copy_texture_to_buffer(
+                self.texture_buffer_bundle.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &self.texture_buffer_bundle.buffer,
+                    layout: self.image_data_layout,
+                },
+                self.copy_size,
+            );
+        }
+
+        queue.flush();
+
+        let planes = self
+            .format
+            .planes(self.copy_size.width, self.copy_size.height);
+
+        let image = {
+            let slice = self.texture_buffer_bundle.buffer.slice(..);
+
+            let future = slice.map_async(wgpu::MapMode::Read);
+            device.poll(Maintain::Wait);
+            pollster::block_on(future).unwrap();
+
+            let view = slice.get_mapped_range();
+
+            let data = if self.format.is_yuv() {
+                view.to_vec()
+            } else {
+                let size_per_pixel = self.format.size_per_pixel();
+
+                let mut data = Vec::with_capacity(
+                    self.copy_size.width as usize * self.copy_size.height as usize * size_per_pixel,
+                );
+
+                for y in 0..self.copy_size.height {
+                    let offset = y * self.subpixels_per_row;
+                    let end = offset + self.copy_size.width * size_per_pixel as u32;
+                    data.extend(&view[offset as usize..end as usize])
+                }
+
+                data
+            };
+
+            OffscreenTargetOutput {
+                data,
+                planes,
+                format: self.format,
+                width: self.copy_size.width,
+                height: self.copy_size.height,
+            }
+        };
+
+        self.texture_buffer_bundle.buffer.unmap();
+
+        image
+    }
+}
+
+/// Specifies the Supported output formats for offscreen rendering
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum OutputFormat {
+    /// 8-Bit Red Green Blue Alpha Color
+    RGBA8,
+    /// 8-Bit Blue Green Red Alpha Color
+    BGRA8,
+    /// Planar 8-Bit YUV 4:2:0 with separate U and V planes
+    I420,
+    /// 8-Bit YUV 4:2:0 with one Y plane and one interleaved UV plane
+    NV12,
+}
+
+impl From<OutputFormat> for TextureFormat {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::RGBA8 | OutputFormat::I420 | OutputFormat::NV12 => {
+                TextureFormat::Rgba8UnormSrgb
+            }
+            OutputFormat::BGRA8 => TextureFormat::Bgra8UnormSrgb,
+        }
+    }
+}
+
+impl OutputFormat {
+    fn size_per_pixel(&self) -> usize {
+        match self {
+            OutputFormat::RGBA8 | OutputFormat::BGRA8 => 4,
+            OutputFormat::I420 | OutputFormat::NV12 => 1,
+        }
+    }
+
+    /// Returns if the format is a YUV format which needs color conversion
+    pub fn is_yuv(&self) -> bool {
+        matches!(self, OutputFormat::I420 | OutputFormat::NV12)
+    }
+
+    /// Returns the layout of the planes of a frame with the given size. The
+    /// layout of the YUV formats matches the default layout used by GStreamer.
+    pub fn planes(&self, width: u32, height: u32) -> Vec<OutputPlane> {
+        let (width, height) = (width as usize, height as usize);
+
+        let round_up_4 = |value: usize| (value + 3) & !3;
+        let chroma_width = (width + 1) / 2;
+        let chroma_height = (height + 1) / 2;
+
+        let luma = OutputPlane {
+            offset: 0,
+            stride: round_up_4(width * self.size_per_pixel()),
+            rows: height,
+        };
+
+        match self {
+            OutputFormat::RGBA8 | OutputFormat::BGRA8 => vec![luma],
+            OutputFormat::I420 => {
+                let u = OutputPlane {
+                    offset: luma.end(),
+                    stride: round_up_4(chroma_width),
+                    rows: chroma_height,
+                };
+
+                let v = OutputPlane {
+                    offset: u.end(),
+                    ..u
+                };
+
+                vec![luma, u, v]
+            }
+            OutputFormat::NV12 => {
+                let uv = OutputPlane {
+                    offset: luma.end(),
+                    stride: round_up_4(chroma_width * 2),
+                    rows: chroma_height,
+                };
+
+                vec![luma, uv]
+            }
+        }
+    }
+
+    /// Returns the size in bytes of a frame with the given size
+    pub fn size(&self, width: u32, height: u32) -> usize {
+        self.planes(width, height)
+            .last()
+            .map_or(0, OutputPlane::end)
+    }
+}
+
+/// Specifies the color matrix used for converting RGB to YUV
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ColorMatrix {
+    /// ITU-R BT.601 with limited range
+    BT601,
+    /// ITU-R BT.709 with limited range
+    BT709,
+}
+
+impl ColorMatrix {
+    /// Returns the color matrix GStreamer assumes by default for a frame with
+    /// the given height
+    pub fn for_height(height: u32) -> Self {
+        if height > 576 {
+            ColorMatrix::BT709
+        } else {
+            ColorMatrix::BT601
+        }
+    }
+
+    /// Returns the rows of the conversion matrix. Each row contains the
+    /// weights of the red, green and blue channel followed by an offset. The
+    /// result is in the range 0-255.
+    pub fn coefficients(&self) -> [Vec4; 3] {
+        match self {
+            ColorMatrix::BT601 => [
+                vec4(65.481, 128.553, 24.966, 16.0),
+                vec4(-37.797, -74.203, 112.0, 128.0),
+                vec4(112.0, -93.786, -18.214, 128.0),
+            ],
+            ColorMatrix::BT709 => [
+                vec4(46.559, 156.629, 15.812, 16.0),
+                vec4(-25.664, -86.336, 112.0, 128.0),
+                vec4(112.0, -101.730, -10.270, 128.0),
+            ],
+        }
+    }
+}
+
+/// Describes the location of a plane inside the [`OffscreenTargetOutput`]
+#[derive(Clone, Copy, Debug)]
+pub struct OutputPlane {
+    /// The offset of the first row in bytes
+    pub offset: usize,
+    /// The distance between two rows in bytes
+    pub stride: usize,
+    /// The amount of rows
+    pub rows: usize,
+}
+
+impl OutputPlane {
+    /// Returns the offset after the last row of the plane
+    pub fn end(&self) -> usize {
+        self.offset + self.stride * self.rows
+    }
+
+    /// Returns the byte range of the plane
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.end()
+    }
+}
+
+/// Stores the resulting data after offscreen rendering.
+pub struct OffscreenTargetOutput {
+    /// The raw texture data
+    pub data: Vec<u8>,
+    /// The layout of the planes inside the data
+    pub planes: Vec<OutputPlane>,
+    /// The format of the data
+    pub format: OutputFormat,
+    /// The width of the frame in pixels
+    pub width: u32,
+    /// The height of the frame in pixels
+    pub height: u32,
+}
+
+impl OffscreenTargetOutput {
+    /// Returns an iterator over the rows of a plane
+    pub fn rows(&self, plane: usize) -> impl Iterator<Item = &[u8]> {
+        let plane = self.planes[plane];
+
+        self.data[plane.range()].chunks(plane.stride.max(1))
+    }
+
+    /// Returns the luma of the pixel at the given position in the range 0-255
+    fn luma(&self, x: usize, y: usize) -> f32 {
+        let plane = self.planes[0];
+        let offset = plane.offset + plane.stride * y;
+
+        match self.format {
+            OutputFormat::RGBA8 => {
+                let pixel = &self.data[offset + x * 4..];
+                0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32
+            }
+            OutputFormat::BGRA8 => {
+                let pixel = &self.data[offset + x * 4..];
+                0.2126 * pixel[2] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[0] as f32
+            }
+            OutputFormat::I420 | OutputFormat::NV12 => self.data[offset + x] as f32,
+        }
+    }
+
+    /// Returns a perceptual hash of the frame. The luma is averaged in a grid
+    /// of 9x8 cells and every bit tells if a cell is darker than its right
+    /// neighbour. Similar frames have hashes with a small Hamming distance,
+    /// so small differences in precision or color conversion hardly change
+    /// the hash.
+    pub fn perceptual_hash(&self) -> u64 {
+        let (width, height) = (self.width as usize, self.height as usize);
+
+        if width == 0 || height == 0 {
+            return 0;
+        }
+
+        let mut sums = [[0.0f32; 9]; 8];
+        let mut counts = [[0u32; 9]; 8];
+
+        for y in 0..height {
+            let cell_y = y * 8 / height;
+
+            for x in 0..width {
+                let cell_x = x * 9 / width;
+
+                sums[cell_y][cell_x] += self.luma(x, y);
+                counts[cell_y][cell_x] += 1;
+            }
+        }
+
+        let mut hash = 0;
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let left = sums[y][x] / counts[y][x].max(1) as f32;
+                let right = sums[y][x + 1] / counts[y][x + 1].max(1) as f32;
+
+                if left < right {
+                    hash |= 1 << (y * 8 + x);
+                }
+            }
+        }
+
+        hash
+    }
+}
@@ -0,0 +1,135 @@
+use wgpu::{
+    Adapter, Backend, Device, PresentMode, Surface, SurfaceConfiguration, SurfaceTexture,
+    TextureAspect, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
+    TextureViewDimension,
+};
+
+use crate::rendering::wgpu::utils::CommandQueue;
+
+use super::{RenderTarget, RenderTargetTexture, HDR_FORMAT};
+
+/// A [`RenderTarget`] used for rendering on a surface
+pub struct SurfaceTarget {
+    surface: Surface,
+    surface_configuration: SurfaceConfiguration,
+    sdr_format: TextureFormat,
+    hdr_supported: bool,
+}
+
+impl SurfaceTarget {
+    /// Creates a new instance
+    pub fn new(surface: Surface, adapter: &Adapter) -> Self {
+        let sdr_format = surface
+            .get_preferred_format(adapter)
+            .unwrap_or(wgpu::TextureFormat::Rgba8UnormSrgb);
+
+        let surface_configuration = SurfaceConfiguration {
+            format: sdr_format,
+            width: 0,
+            height: 0,
+            present_mode: PresentMode::Mailbox,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+        };
+
+        // wgpu can't set the color space of a surface, so only DXGI is
+        // supported, which presents half float swapchains as scRGB
+        let hdr_supported = adapter.get_info().backend == Backend::Dx12;
+
+        SurfaceTarget {
+            surface: surface,
+            surface_configuration,
+            sdr_format,
+            hdr_supported,
+        }
+    }
+
+    /// Returns if the surface can present HDR colors on the active backend
+    pub fn hdr_supported(&self) -> bool {
+        self.hdr_supported
+    }
+
+    /// Returns if the surface presents HDR colors
+    pub fn hdr(&self) -> bool {
+        self.surface_configuration.format == HDR_FORMAT
+    }
+
+    /// Sets if the surface presents HDR colors. The surface is configured
+    /// with [`HDR_FORMAT`] then, which holds linear scRGB colors. This is
+    /// ignored if HDR is not supported.
+    pub fn set_hdr(&mut self, hdr: bool) -> &mut Self {
+        let format = match hdr && self.hdr_supported {
+            true => HDR_FORMAT,
+            false => self.sdr_format,
+        };
+
+        // the surface is configured again with the next texture
+        if self.surface_configuration.format != format {
+            self.surface_configuration = SurfaceConfiguration {
+                format,
+                width: 0,
+                height: 0,
+                ..self.surface_configuration
+            };
+        }
+
+        self
+    }
+}
+
+impl RenderTarget for SurfaceTarget {
+    type Texture = SurfaceTargetTexture;
+
+    fn target_format(&self) -> TextureFormat {
+        self.surface_configuration.format
+    }
+
+    fn target_texture(&mut self, width: u32, height: u32, device: &Device) -> Self::Texture {
+        if self.surface_configuration.width != width || self.surface_configuration.height != height
+        {
+            self.surface_configuration = SurfaceConfiguration {
+                width,
+                height,
+                ..self.surface_configuration
+            };
+
+            self.surface.configure(device, &self.surface_configuration);
+        }
+
+        let texture = self.surface.get_current_texture().unwrap();
+        let texture_view = texture.texture.create_view(&TextureViewDescriptor {
+            label: None,
+            format: None,
+            dimension: Some(TextureViewDimension::D2),
+            aspect: TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: None,
+            base_array_layer: 0,
+            array_layer_count: None,
+        });
+
+        SurfaceTargetTexture {
+            texture,
+            texture_view,
+        }
+    }
+}
+
+/// The [`RenderTargetTexture`] of the [`SurfaceTarget`]
+pub struct SurfaceTargetTexture {
+    texture: SurfaceTexture,
+    texture_view: TextureView,
+}
+
+impl RenderTargetTexture for SurfaceTargetTexture {
+    type Output = ();
+
+    fn texture_view(&self) -> &TextureView {
+        &self.texture_view
+    }
+
+    fn present(self, _device: &Device, queue: &mut CommandQueue) -> Self::Output {
+        queue.flush();
+
+        self.texture.present()
+    }
+}
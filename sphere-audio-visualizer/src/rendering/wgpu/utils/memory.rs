@@ -0,0 +1,124 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+
+use wgpu::{Limits, TextureFormat};
+
+/// Defines the default amount of GPU memory in bytes a frame is allowed to
+/// allocate
+pub const MEMORY_BUDGET: u64 = 256 << 20;
+
+/// Tracks the GPU memory allocated while rendering and reduces the size of
+/// uploaded scene data if it would exceed the memory budget or the binding
+/// limits of the device.
+pub struct MemoryTracker {
+    budget: AtomicU64,
+    max_binding_size: u64,
+    buffers: AtomicU64,
+    textures: AtomicU64,
+    warnings: Mutex<Vec<String>>,
+}
+
+impl MemoryTracker {
+    /// Creates a new instance for a device with the given [`Limits`]
+    pub fn new(limits: &Limits) -> Self {
+        Self {
+            budget: AtomicU64::new(MEMORY_BUDGET),
+            max_binding_size: limits.max_storage_buffer_binding_size as u64,
+            buffers: AtomicU64::new(0),
+            textures: AtomicU64::new(0),
+            warnings: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the memory budget in bytes
+    pub fn budget(&self) -> u64 {
+        self.budget.load(Ordering::Relaxed)
+    }
+
+    /// Sets the memory budget in bytes
+    pub fn set_budget(&self, budget: u64) {
+        self.budget.store(budget, Ordering::Relaxed);
+    }
+
+    /// Resets the allocations and warnings of the previous frame
+    pub fn begin_frame(&self) {
+        self.buffers.store(0, Ordering::Relaxed);
+        self.textures.store(0, Ordering::Relaxed);
+        self.warnings.lock().unwrap().clear();
+    }
+
+    /// Tracks a buffer allocation of the given size in bytes
+    pub fn track_buffer(&self, size: u64) {
+        self.buffers.fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// Tracks a texture allocation with the given dimensions and format
+    pub fn track_texture(&self, width: u32, height: u32, format: TextureFormat) {
+        let size = width as u64 * height as u64 * format.describe().block_size as u64;
+
+        self.textures.fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// Adds a warning which is shown to the user
+    pub fn warn(&self, warning: impl ToString) {
+        self.warnings.lock().unwrap().push(warning.to_string());
+    }
+
+    /// Returns the part of `values` that fits into a single storage buffer
+    /// binding and the remaining memory budget and tracks its allocation. A
+    /// warning is emitted if the values had to be reduced.
+    pub fn fit<'a, T>(&self, name: &str, values: &'a [T]) -> &'a [T] {
+        let element_size = std::mem::size_of::<T>().max(1) as u64;
+
+        let remaining = self.budget().saturating_sub(self.usage().total());
+        let max_len = ((self.max_binding_size.min(remaining) / element_size) as usize).max(1);
+
+        let values = if values.len() > max_len {
+            self.warn(format!(
+                "{} reduced from {} to {} to fit into GPU memory",
+                name,
+                values.len(),
+                max_len
+            ));
+
+            &values[..max_len]
+        } else {
+            values
+        };
+
+        self.track_buffer(std::mem::size_of_val(values) as u64);
+
+        values
+    }
+
+    /// Returns the memory allocated in the current frame
+    pub fn usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            buffers: self.buffers.load(Ordering::Relaxed),
+            textures: self.textures.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the warnings emitted in the current frame
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.lock().unwrap().clone()
+    }
+}
+
+/// Stores the amount of GPU memory in bytes allocated while rendering a frame
+#[derive(Clone, Copy, Default, Debug)]
+pub struct MemoryUsage {
+    /// Memory allocated by buffers
+    pub buffers: u64,
+    /// Memory allocated by textures
+    pub textures: u64,
+}
+
+impl MemoryUsage {
+    /// Returns the total amount of allocated memory
+    pub fn total(&self) -> u64 {
+        self.buffers + self.textures
+    }
+}
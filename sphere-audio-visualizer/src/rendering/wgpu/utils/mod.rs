@@ -1,8 +1,14 @@
 //! Contains Utility functions used for rendering
 
+mod memory;
 mod queue;
 mod raw_window_handle;
+mod seed;
 mod shader_cache;
 mod typed_buffer;
+mod uploader;
 
-pub use self::{queue::*, raw_window_handle::*, shader_cache::*, typed_buffer::*};
+pub use self::{
+    memory::*, queue::*, raw_window_handle::*, seed::*, shader_cache::*, typed_buffer::*,
+    uploader::*,
+};
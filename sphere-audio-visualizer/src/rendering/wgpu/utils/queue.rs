@@ -1,41 +1,110 @@
 use std::ops::Deref;
 
-use wgpu::{CommandEncoder, CommandEncoderDescriptor, Device, Queue};
+use wgpu::{CommandBuffer, CommandEncoder, CommandEncoderDescriptor, Device, Queue};
+
+use super::MemoryTracker;
+
+/// Defines the default maximum amount of [`CommandEncoder`]s submitted at once
+/// by the [`CommandQueue`]
+pub const COMMAND_QUEUE_MAX_BATCH: usize = 16;
 
 /// Combines the WGPU [`Queue`] und [`CommandEncoder`]. Records all commands to
-/// its internal [`CommandEncoder`] untill submit is called and a new
-/// [`CommandEncoder`]. This is done to reduce the amount of [`CommandEncoder`]
-/// submited to a [`Queue`]. This seams to be inefficient.
+/// its internal [`CommandEncoder`]. Finished [`CommandEncoder`]s are batched
+/// and submitted together when the [`CommandQueue`] is flushed, the batch is
+/// full or the [`CommandQueue`] is dropped. This is done to reduce the amount
+/// of submissions to the [`Queue`], as each of them adds overhead.
 pub struct CommandQueue<'a> {
     queue: &'a Queue,
+    memory_tracker: &'a MemoryTracker,
+    label: Option<&'a str>,
+    max_batch: usize,
     command_encoder: Option<CommandEncoder>,
+    command_buffers: Vec<CommandBuffer>,
 }
 
 impl<'a> CommandQueue<'a> {
-    /// Creates a new instance
-    pub fn new(queue: &'a Queue) -> Self {
+    /// Creates a new instance. Allocations made while recording are tracked by
+    /// the passed [`MemoryTracker`].
+    pub fn new(queue: &'a Queue, memory_tracker: &'a MemoryTracker) -> Self {
         Self {
             queue,
+            memory_tracker,
+            label: None,
+            max_batch: COMMAND_QUEUE_MAX_BATCH,
             command_encoder: None,
+            command_buffers: Vec::new(),
         }
     }
 
+    /// Builder method for setting the label of the recorded
+    /// [`CommandEncoder`]s
+    pub fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Builder method for setting the maximum amount of [`CommandEncoder`]s
+    /// submitted at once
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch.max(1);
+        self
+    }
+
     /// Gets the internal [`Queue`]
     pub fn queue(&self) -> &'a Queue {
         self.queue
     }
 
+    /// Gets the [`MemoryTracker`]
+    pub fn memory_tracker(&self) -> &'a MemoryTracker {
+        self.memory_tracker
+    }
+
+    /// Gets the label of the recorded [`CommandEncoder`]s
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+
+    /// Gets the maximum amount of [`CommandEncoder`]s submitted at once
+    pub fn max_batch(&self) -> usize {
+        self.max_batch
+    }
+
+    /// Gets the amount of [`CommandEncoder`]s waiting to be submitted
+    pub fn pending(&self) -> usize {
+        self.command_buffers.len() + self.command_encoder.is_some() as usize
+    }
+
     /// Gets a [`CommandEncoder`]
     pub fn command_encoder(&mut self, device: &Device) -> &mut CommandEncoder {
+        let label = self.label;
+
         self.command_encoder.get_or_insert_with(|| {
-            device.create_command_encoder(&CommandEncoderDescriptor { label: None })
+            device.create_command_encoder(&CommandEncoderDescriptor { label })
         })
     }
 
-    /// Submits the internal [`CommandEncoder`]
-    pub fn submit(&mut self) {
+    /// Finishes the internal [`CommandEncoder`] and adds it to the batch
+    /// without submitting it. The batch is submitted once it is full.
+    pub fn finish_encoder(&mut self) {
         if let Some(command_encoder) = self.command_encoder.take() {
-            self.queue.submit([command_encoder.finish()]);
+            self.command_buffers.push(command_encoder.finish());
+        }
+
+        if self.command_buffers.len() >= self.max_batch {
+            self.queue.submit(self.command_buffers.drain(..));
+        }
+    }
+
+    /// Submits all recorded commands in a single submission. Has to be called
+    /// before the results are read back or presented.
+    pub fn flush(&mut self) {
+        if let Some(command_encoder) = self.command_encoder.take() {
+            self.command_buffers.push(command_encoder.finish());
+        }
+
+        if !self.command_buffers.is_empty() {
+            self.queue.submit(self.command_buffers.drain(..));
         }
     }
 }
@@ -50,6 +119,6 @@ impl<'a> Deref for CommandQueue<'a> {
 
 impl<'a> Drop for CommandQueue<'a> {
     fn drop(&mut self) {
-        self.submit()
+        self.flush()
     }
 }
@@ -0,0 +1,47 @@
+use sphere_audio_visualizer_core::utils::random::frame_seed;
+
+/// Defines the default base seed of the [`SeedStream`]
+pub const SEED: u32 = 0;
+
+/// A deterministic stream of frame indices and seeds for stochastic shader
+/// effects. The same base seed always yields the same sequence, so exports
+/// are reproducible.
+#[derive(Clone)]
+pub struct SeedStream {
+    seed: u32,
+    frame: u32,
+}
+
+impl SeedStream {
+    /// Creates a new instance starting at the first frame
+    pub fn new(seed: u32) -> Self {
+        Self { seed, frame: 0 }
+    }
+
+    /// Returns the base seed
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Sets the base seed. Restarts the stream if the seed changed.
+    pub fn set_seed(&mut self, seed: u32) -> &mut Self {
+        if self.seed != seed {
+            *self = Self::new(seed);
+        }
+        self
+    }
+
+    /// Returns the index and the seed of the next frame
+    pub fn next_frame(&mut self) -> (u32, u32) {
+        let frame = self.frame;
+        self.frame = self.frame.wrapping_add(1);
+
+        (frame, frame_seed(self.seed, frame))
+    }
+}
+
+impl Default for SeedStream {
+    fn default() -> Self {
+        Self::new(SEED)
+    }
+}
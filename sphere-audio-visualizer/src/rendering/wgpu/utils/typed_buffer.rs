@@ -1,7 +1,7 @@
 use std::{
     borrow::Borrow,
     num::NonZeroU64,
-    ops::{Deref, DerefMut},
+    ops::{Bound, Deref, DerefMut, RangeBounds},
     ptr::Pointee,
 };
 
@@ -22,6 +22,56 @@ pub fn align_of_metadata<T: ?Sized>(metadata: <T as Pointee>::Metadata) -> usize
     unsafe { std::mem::align_of_val_raw(std::ptr::from_raw_parts::<T>(std::ptr::null(), metadata)) }
 }
 
+/// Represents the errors which could happen when creating a [`TypedBuffer`] or
+/// a view into one.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum TypedBufferError {
+    /// This error happens when the value does not fit into the buffer at the
+    /// passed offset.
+    #[error("value of {size} bytes at offset {offset} exceeds the buffer size of {capacity}")]
+    OutOfBounds {
+        /// The offset of the value in bytes
+        offset: usize,
+        /// The size of the value in bytes
+        size: usize,
+        /// The size of the available memory in bytes
+        capacity: usize,
+    },
+    /// This error happens when the offset is not a multiple of the alignment of
+    /// the value.
+    #[error("offset {offset} is not aligned to {align}")]
+    Misaligned {
+        /// The offset of the value in bytes
+        offset: usize,
+        /// The alignment of the value in bytes
+        align: usize,
+    },
+}
+
+/// Checks if a value described by the metadata fits at the offset into memory
+/// of the given capacity.
+fn check_bounds<T: ?Sized>(
+    offset: usize,
+    metadata: <T as Pointee>::Metadata,
+    capacity: usize,
+) -> Result<(), TypedBufferError> {
+    let size = size_of_metadata::<T>(metadata);
+    let align = align_of_metadata::<T>(metadata);
+
+    if offset % align != 0 {
+        return Err(TypedBufferError::Misaligned { offset, align });
+    }
+
+    match offset.checked_add(size) {
+        Some(end) if end <= capacity => Ok(()),
+        _ => Err(TypedBufferError::OutOfBounds {
+            offset,
+            size,
+            capacity,
+        }),
+    }
+}
+
 /// A wrapper for WGPU buffer containing a Value. The size of the value might
 /// not be known at compile time!
 pub struct TypedBuffer<B: Borrow<Buffer>, T: ?Sized> {
@@ -31,15 +81,25 @@ pub struct TypedBuffer<B: Borrow<Buffer>, T: ?Sized> {
 }
 
 impl<B: Borrow<Buffer>, T: ?Sized> TypedBuffer<B, T> {
+    /// Crates a new instance from a WGPU Buffer of `buffer_size` bytes with
+    /// offset and metadata. Fails if the value does not fit into the buffer at
+    /// the passed offset.
+    pub fn new(
+        buffer: B,
+        buffer_size: u64,
+        offset: usize,
+        metadata: <T as Pointee>::Metadata,
+    ) -> Result<Self, TypedBufferError> {
+        check_bounds::<T>(offset, metadata, buffer_size as usize)?;
+
+        Ok(unsafe { Self::from_buffer(buffer, offset, metadata) })
+    }
+
     /// Crates a new instance from a WGPU Buffer with offset and metadata
     ///
     /// Safety: it should be garanteed by the caller that the passed offset and
     /// metadata are valid.
-    pub unsafe fn from_buffer(
-        buffer: B,
-        offset: usize,
-        metadata: <T as Pointee>::Metadata,
-    ) -> Self {
+    unsafe fn from_buffer(buffer: B, offset: usize, metadata: <T as Pointee>::Metadata) -> Self {
         Self {
             buffer,
             offset,
@@ -47,13 +107,18 @@ impl<B: Borrow<Buffer>, T: ?Sized> TypedBuffer<B, T> {
         }
     }
 
+    /// The size of the data inside the [`TypedBuffer`] in bytes
+    pub fn size(&self) -> usize {
+        size_of_metadata::<T>(self.metadata)
+    }
+
     /// Tries to creates a [`BufferBinding`] for the data inside the
     /// [`TypedBuffer`]
     pub fn buffer_binding(&self) -> Option<BufferBinding> {
         Some(BufferBinding {
             buffer: self.buffer.borrow(),
             offset: self.offset as u64,
-            size: Some(NonZeroU64::new(size_of_metadata::<T>(self.metadata) as u64)?),
+            size: Some(NonZeroU64::new(self.size() as u64)?),
         })
     }
 
@@ -72,27 +137,19 @@ impl<B: Borrow<Buffer>, T: ?Sized> TypedBuffer<B, T> {
         })
     }
 
-    /// Creates a view of the data in the buffer matching the rederence retuned
-    /// by the passed function.
-    ///
-    /// Safety: The memory behind reference passed to the function is not valid
-    /// and shoud never under any circumstances be accessed.  
-    pub fn view<'a, U: ?Sized, F: FnOnce(&T) -> &U>(
-        &'a self,
-        mapper: F,
-    ) -> TypedBuffer<&'a Buffer, U> {
-        let mapped_reference = (mapper)(unsafe {
-            &*std::ptr::from_raw_parts::<T>(
-                align_of_metadata::<T>(self.metadata) as *const _,
-                self.metadata,
-            )
-        });
+    /// Creates a view of a value of type `U` located at `offset` bytes into
+    /// the data of the [`TypedBuffer`]. Fails if the value is not fully
+    /// contained in the data.
+    pub fn view<U: ?Sized>(
+        &self,
+        offset: usize,
+        metadata: <U as Pointee>::Metadata,
+    ) -> Result<TypedBuffer<&Buffer, U>, TypedBufferError> {
+        check_bounds::<U>(offset, metadata, self.size())?;
 
-        TypedBuffer {
-            buffer: &self,
-            offset: mapped_reference as *const U as *const () as usize,
-            metadata: std::ptr::metadata(mapped_reference),
-        }
+        Ok(unsafe {
+            TypedBuffer::from_buffer(self.buffer.borrow(), self.offset + offset, metadata)
+        })
     }
 
     /// The offset of the [`TypedBuffer`] related to the underlying WGPU
@@ -106,9 +163,17 @@ impl<B: Borrow<Buffer>, T: ?Sized> TypedBuffer<B, T> {
         self.metadata
     }
 
-    /// Creates a slice of the buffer ready for mapping into main memory
-    pub fn slice<'a>(&'a self) -> TypedBufferSlice<'a, T> {
-        TypedBufferSlice(self.buffer.borrow().slice(..), self.metadata)
+    /// Tries to create a slice of the buffer covering only the data of the
+    /// [`TypedBuffer`] ready for mapping into main memory. Returns `None` if
+    /// the data is empty.
+    pub fn slice<'a>(&'a self) -> Option<TypedBufferSlice<'a, T>> {
+        let start = self.offset as u64;
+        let size = NonZeroU64::new(self.size() as u64)?;
+
+        Some(TypedBufferSlice(
+            self.buffer.borrow().slice(start..start + size.get()),
+            self.metadata,
+        ))
     }
 
     /// Unmaps the underlying Buffer
@@ -122,6 +187,48 @@ impl<B: Borrow<Buffer>, T: ?Sized> TypedBuffer<B, T> {
     }
 }
 
+impl<B: Borrow<Buffer>, T> TypedBuffer<B, [T]> {
+    /// The amount of elements inside the [`TypedBuffer`]
+    pub fn len(&self) -> usize {
+        self.metadata
+    }
+
+    /// Returns `true` if the [`TypedBuffer`] contains no elements
+    pub fn is_empty(&self) -> bool {
+        self.metadata == 0
+    }
+
+    /// Creates a view of the elements in the passed range. Fails if the range
+    /// is out of bounds.
+    pub fn subslice<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> Result<TypedBuffer<&Buffer, [T]>, TypedBufferError> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+
+        let element_size = std::mem::size_of::<T>();
+
+        if start > end || end > self.len() {
+            return Err(TypedBufferError::OutOfBounds {
+                offset: start * element_size,
+                size: end.saturating_sub(start) * element_size,
+                capacity: self.size(),
+            });
+        }
+
+        self.view::<[T]>(start * element_size, end - start)
+    }
+}
+
 impl<B: Borrow<Buffer>, T: ?Sized> Deref for TypedBuffer<B, T> {
     type Target = Buffer;
 
@@ -323,15 +430,37 @@ impl TypedBufferCommandEncoderExt for CommandEncoder {
 
 ///Extension trait for the WGPU [`Queue`] to write data to a [`TypedBuffer`]
 pub trait TypedBufferQueueExt {
-    /// Writes data to a [`TypedBuffer`]
-    fn write_typed_buffer<T: ?Sized, B: Borrow<Buffer>>(&self, dst: &TypedBuffer<B, T>, value: &T);
+    /// Writes data to a [`TypedBuffer`]. Fails if the value is larger than the
+    /// value of the [`TypedBuffer`], e.g. a longer slice.
+    fn write_typed_buffer<T: ?Sized, B: Borrow<Buffer>>(
+        &self,
+        dst: &TypedBuffer<B, T>,
+        value: &T,
+    ) -> Result<(), TypedBufferError>;
 }
 
 impl TypedBufferQueueExt for Queue {
-    fn write_typed_buffer<T: ?Sized, B: Borrow<Buffer>>(&self, dst: &TypedBuffer<B, T>, value: &T) {
+    fn write_typed_buffer<T: ?Sized, B: Borrow<Buffer>>(
+        &self,
+        dst: &TypedBuffer<B, T>,
+        value: &T,
+    ) -> Result<(), TypedBufferError> {
+        let size = std::mem::size_of_val(value);
+        let dst_size = size_of_metadata::<T>(dst.metadata());
+
+        if size > dst_size {
+            return Err(TypedBufferError::OutOfBounds {
+                offset: dst.offset(),
+                size,
+                capacity: dst.offset() + dst_size,
+            });
+        }
+
         self.write_buffer(&dst, dst.offset() as u64, unsafe {
-            std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of_val(value))
-        })
+            std::slice::from_raw_parts(value as *const T as *const u8, size)
+        });
+
+        Ok(())
     }
 }
 
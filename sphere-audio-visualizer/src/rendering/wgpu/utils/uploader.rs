@@ -0,0 +1,182 @@
+use std::{
+    future::Future,
+    num::NonZeroU64,
+    pin::Pin,
+    ptr::Pointee,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use wgpu::{
+    util::StagingBelt, Buffer, BufferDescriptor, BufferUsages, Device, Maintain,
+    COPY_BUFFER_ALIGNMENT,
+};
+
+use super::{size_of_metadata, CommandQueue, TypedBuffer};
+
+/// Defines the default size of the staging chunks allocated by the
+/// [`Uploader`]
+pub const UPLOADER_CHUNK_SIZE: u64 = 1 << 16;
+
+type Recall = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A GPU buffer which is kept across frames. It is only reallocated when the
+/// uploaded data does not fit into it anymore.
+pub struct DynamicBuffer {
+    label: Option<&'static str>,
+    usage: BufferUsages,
+    buffer: Option<Arc<Buffer>>,
+    capacity: u64,
+}
+
+impl DynamicBuffer {
+    /// Creates a new instance. The buffer is allocated on the first upload.
+    pub fn new(label: Option<&'static str>, usage: BufferUsages) -> Self {
+        Self {
+            label,
+            usage: usage | BufferUsages::COPY_DST,
+            buffer: None,
+            capacity: 0,
+        }
+    }
+
+    /// Gets the size of the allocated buffer in bytes
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Reserves memory for a value described by the metadata without uploading
+    /// anything, e.g. for the output of a compute pass.
+    pub fn allocate<T: ?Sized>(
+        &mut self,
+        device: &Device,
+        metadata: <T as Pointee>::Metadata,
+    ) -> TypedBuffer<Arc<Buffer>, T> {
+        let size = aligned_size(size_of_metadata::<T>(metadata));
+
+        let buffer = self.reserve(device, size);
+
+        TypedBuffer::new(buffer, self.capacity, 0, metadata)
+            .expect("the buffer is reserved for the value")
+    }
+
+    fn reserve(&mut self, device: &Device, size: u64) -> Arc<Buffer> {
+        if self.buffer.is_none() || self.capacity < size {
+            self.capacity = size.max(COPY_BUFFER_ALIGNMENT).next_power_of_two();
+            self.buffer = Some(Arc::new(device.create_buffer(&BufferDescriptor {
+                label: self.label,
+                size: self.capacity,
+                usage: self.usage,
+                mapped_at_creation: false,
+            })));
+        }
+
+        self.buffer.clone().unwrap()
+    }
+}
+
+/// Uploads data into [`DynamicBuffer`]s through a [`StagingBelt`]. The staging
+/// memory is reused across frames instead of being allocated for every upload
+/// like it is done by `create_buffer_init`.
+pub struct Uploader {
+    // the staging belt is not `Sync`, but only ever accessed mutably
+    staging_belt: Mutex<StagingBelt>,
+    recalls: Mutex<Vec<Recall>>,
+}
+
+impl Uploader {
+    /// Creates a new instance allocating staging chunks of the given size
+    pub fn new(chunk_size: u64) -> Self {
+        Self {
+            staging_belt: Mutex::new(StagingBelt::new(chunk_size)),
+            recalls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Records the upload of `value` into `buffer` and returns a
+    /// [`TypedBuffer`] covering the uploaded data. [`Uploader::finish`] has to
+    /// be called before the [`CommandQueue`] is flushed.
+    pub fn upload<T: ?Sized>(
+        &mut self,
+        device: &Device,
+        command_queue: &mut CommandQueue,
+        buffer: &mut DynamicBuffer,
+        value: &T,
+    ) -> TypedBuffer<Arc<Buffer>, T> {
+        let data = unsafe {
+            std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of_val(value))
+        };
+
+        let size = aligned_size(data.len());
+
+        let target = buffer.reserve(device, size);
+
+        if let Some(size) = NonZeroU64::new(size) {
+            let mut view = self.staging_belt.get_mut().unwrap().write_buffer(
+                command_queue.command_encoder(device),
+                &target,
+                0,
+                size,
+                device,
+            );
+
+            view[..data.len()].copy_from_slice(data);
+            view[data.len()..].fill(0);
+        }
+
+        TypedBuffer::new(
+            target,
+            buffer.capacity(),
+            0,
+            std::ptr::metadata(value as *const T),
+        )
+        .expect("the buffer is reserved for the value")
+    }
+
+    /// Closes the staging memory written in the current frame. Has to be
+    /// called after the last upload and before the [`CommandQueue`] is
+    /// flushed.
+    pub fn finish(&mut self) {
+        self.staging_belt.get_mut().unwrap().finish();
+    }
+
+    /// Returns the staging memory of previous frames, which the GPU is done
+    /// with, to the [`Uploader`]. Has to be called after the [`CommandQueue`]
+    /// of the previous frame was flushed.
+    pub fn recall(&mut self, device: &Device) {
+        device.poll(Maintain::Poll);
+
+        let recalls = self.recalls.get_mut().unwrap();
+
+        recalls.push(Box::pin(self.staging_belt.get_mut().unwrap().recall()));
+
+        let waker = noop_waker();
+        let mut context = Context::from_waker(&waker);
+
+        recalls.retain_mut(|recall| recall.as_mut().poll(&mut context) == Poll::Pending);
+    }
+}
+
+// copies have to be a multiple of the copy alignment
+fn aligned_size(size: usize) -> u64 {
+    (size as u64).div_ceil(COPY_BUFFER_ALIGNMENT) * COPY_BUFFER_ALIGNMENT
+}
+
+impl Default for Uploader {
+    fn default() -> Self {
+        Self::new(UPLOADER_CHUNK_SIZE)
+    }
+}
+
+// the recalls are polled once per frame, so nothing has to be woken up
+pub(crate) fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+
+    fn noop(_: *const ()) {}
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+}
@@ -0,0 +1,144 @@
+use crate::Module;
+
+/// Defines the default number of groups the frequency bands are split into
+const BAND_GROUPS: usize = 8;
+
+/// Stores the settings of the [`BandMask`]
+#[derive(Clone, PartialEq)]
+pub struct BandMaskSettings {
+    /// The number of groups the frequency bands are split into
+    pub groups: usize,
+    /// Whether a group is soloed, indexed from bass to treble
+    pub solo: Vec<bool>,
+    /// Whether a group is muted, indexed from bass to treble
+    pub mute: Vec<bool>,
+}
+
+impl Default for BandMaskSettings {
+    fn default() -> Self {
+        Self {
+            groups: BAND_GROUPS,
+            solo: vec![false; BAND_GROUPS],
+            mute: vec![false; BAND_GROUPS],
+        }
+    }
+}
+
+/// Solos or mutes groups of frequency bands before their levels reach the
+/// simulation, which helps to find out which bands drive which spheres. The
+/// bands are spread evenly over the groups. If any group is soloed, every
+/// other group is silenced, otherwise the muted groups are silenced.
+pub struct BandMask {
+    settings: BandMaskSettings,
+    levels: Vec<f32>,
+}
+
+impl BandMask {
+    /// Returns the number of groups the frequency bands are split into
+    pub fn groups(&self) -> usize {
+        self.settings.groups
+    }
+
+    /// Sets the number of groups the frequency bands are split into. The
+    /// solo and mute toggles of removed groups are dropped.
+    pub fn set_groups(&mut self, groups: usize) -> &mut Self {
+        self.settings.groups = groups.max(1);
+        self.settings.solo.resize(self.settings.groups, false);
+        self.settings.mute.resize(self.settings.groups, false);
+        self
+    }
+
+    /// Sets the number of groups the frequency bands are split into
+    pub fn with_groups(mut self, groups: usize) -> Self {
+        self.set_groups(groups);
+        self
+    }
+
+    /// Returns if the group is soloed
+    pub fn solo(&self, group: usize) -> bool {
+        self.settings.solo.get(group).copied().unwrap_or_default()
+    }
+
+    /// Sets if the group is soloed
+    pub fn set_solo(&mut self, group: usize, solo: bool) -> &mut Self {
+        if let Some(value) = self.settings.solo.get_mut(group) {
+            *value = solo;
+        }
+        self
+    }
+
+    /// Returns if the group is muted
+    pub fn mute(&self, group: usize) -> bool {
+        self.settings.mute.get(group).copied().unwrap_or_default()
+    }
+
+    /// Sets if the group is muted
+    pub fn set_mute(&mut self, group: usize, mute: bool) -> &mut Self {
+        if let Some(value) = self.settings.mute.get_mut(group) {
+            *value = mute;
+        }
+        self
+    }
+
+    /// Returns if any group is soloed or muted
+    pub fn is_active(&self) -> bool {
+        self.settings
+            .solo
+            .iter()
+            .chain(&self.settings.mute)
+            .any(|value| *value)
+    }
+
+    /// Returns the levels with the silenced groups set to zero
+    pub fn apply<'a>(&'a mut self, levels: &'a [f32]) -> &'a [f32] {
+        if !self.is_active() {
+            return levels;
+        }
+
+        let soloed = self.settings.solo.iter().any(|solo| *solo);
+        let groups = self.settings.groups;
+
+        self.levels.clear();
+        self.levels
+            .extend(levels.iter().enumerate().map(|(band, level)| {
+                let group = band * groups / levels.len();
+
+                let audible = match soloed {
+                    true => self.settings.solo[group],
+                    false => !self.settings.mute[group],
+                };
+
+                if audible {
+                    *level
+                } else {
+                    0.0
+                }
+            }));
+
+        &self.levels
+    }
+}
+
+impl Default for BandMask {
+    fn default() -> Self {
+        Self {
+            settings: BandMaskSettings::default(),
+            levels: Vec::new(),
+        }
+    }
+}
+
+impl Module for BandMask {
+    type Settings = BandMaskSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.settings = settings;
+
+        // keeps the toggles in sync with the number of groups
+        self.set_groups(self.settings.groups)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        self.settings.clone()
+    }
+}
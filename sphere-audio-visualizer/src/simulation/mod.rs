@@ -2,38 +2,170 @@
 
 use std::time::Duration;
 
-pub use self::{resampler::*, scene_2d::*, scene_3d::*};
+use crate::audio_analysis::Samples;
 
+pub use self::{
+    band_mask::*, passthrough::*, resampler::*, scene_2d::*, scene_3d::*, smoother::*, waveform::*,
+};
+
+mod band_mask;
+mod noise;
+mod passthrough;
 mod resampler;
 mod scene_2d;
 mod scene_3d;
+mod smoother;
+mod waveform;
 
 const SPHERE_MIN_RADIUS: f32 = 0.1;
 
+/// Defines the default amplitude of the noise field perturbing the origin of
+/// the spheres
+const WOBBLE_AMPLITUDE: f32 = 0.0;
+
+/// Defines the default frequency in Hz of the noise field perturbing the
+/// origin of the spheres
+const WOBBLE_FREQUENCY: f32 = 0.25;
+
+/// Defines the default strength of the turbulence force
+const TURBULENCE_STRENGTH: f32 = 0.0;
+
+/// Defines the default spatial scale of the turbulence noise field
+const TURBULENCE_SCALE: f32 = 0.5;
+
+/// Defines the default speed at which the turbulence noise field changes
+const TURBULENCE_SPEED: f32 = 0.5;
+
+/// Defines the default speed in radians per second of the spheres in the
+/// [`Layout::Orbit`] layout
+const ORBIT_SPEED: f32 = 0.25;
+
+/// Specifies how the spheres are arranged
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum Layout {
+    /// The frequency bands are lined up from left to right
+    Line,
+    /// The frequency bands orbit a central emissive sphere at radii
+    /// proportional to the band index, faster the louder they are. Only
+    /// supported by [`Simulation3D`], [`Simulation2D`] uses [`Layout::Line`]
+    /// instead.
+    Orbit,
+}
+
+/// Specifies the order of the frequency bands in the [`Layout::Line`] layout
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum BandOrder {
+    /// The bands go from bass on the left to treble on the right
+    LeftToRight,
+    /// The bands are mirrored around the center with the bass in the middle
+    /// and the treble at the edges
+    BassCenter,
+    /// The bands are mirrored around the center with the treble in the
+    /// middle and the bass at the edges
+    TrebleCenter,
+}
+
 /// Stores the settings of the [`Simulation2D`] [`Simulation3D`] physics simulations
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct SimulationSettings {
     /// The minimum radius for the spheres in the simulation.
     pub min_radius: f32,
+    /// The amplitude of the noise field perturbing the origin of the spheres
+    pub wobble_amplitude: f32,
+    /// The frequency in Hz of the noise field perturbing the origin of the
+    /// spheres
+    pub wobble_frequency: f32,
+    /// The strength of the turbulence force, scaled by the energy of the mid
+    /// and high frequency bands
+    pub turbulence_strength: f32,
+    /// The spatial scale of the turbulence noise field
+    pub turbulence_scale: f32,
+    /// The speed at which the turbulence noise field changes over time
+    pub turbulence_speed: f32,
+    /// The arrangement of the spheres
+    pub layout: Layout,
+    /// The order of the frequency bands in the [`Layout::Line`] layout
+    pub band_order: BandOrder,
+    /// The speed in radians per second of the spheres in the
+    /// [`Layout::Orbit`] layout
+    pub orbit_speed: f32,
 }
 
 impl Default for SimulationSettings {
     fn default() -> Self {
         Self {
             min_radius: SPHERE_MIN_RADIUS,
+            wobble_amplitude: WOBBLE_AMPLITUDE,
+            wobble_frequency: WOBBLE_FREQUENCY,
+            turbulence_strength: TURBULENCE_STRENGTH,
+            turbulence_scale: TURBULENCE_SCALE,
+            turbulence_speed: TURBULENCE_SPEED,
+            layout: Layout::Line,
+            band_order: BandOrder::LeftToRight,
+            orbit_speed: ORBIT_SPEED,
         }
     }
 }
 
+/// Returns the horizontal position of a frequency band in the
+/// [`Layout::Line`] layout
+fn line_position(band: usize, band_count: usize, band_order: BandOrder) -> f32 {
+    let factor = 16.0 / band_count as f32;
+
+    // mirrored bands alternate between the right and the left side while
+    // moving outwards
+    let mirrored = |band: usize| {
+        let side = if band % 2 == 0 { 1.0 } else { -1.0 };
+        side * ((band + 1) / 2) as f32 * factor
+    };
+
+    match band_order {
+        BandOrder::LeftToRight => (band as f32 - (band_count - 1) as f32 * 0.5) * factor,
+        BandOrder::BassCenter => mirrored(band),
+        BandOrder::TrebleCenter => mirrored(band_count - 1 - band),
+    }
+}
+
+/// Returns the mean level of the mid and high frequency bands, which drives
+/// the turbulence force
+fn mid_high_energy(levels: &[f32]) -> f32 {
+    let mid_high = &levels[levels.len() / 3..];
+
+    if mid_high.is_empty() {
+        0.0
+    } else {
+        mid_high.iter().sum::<f32>() / mid_high.len() as f32
+    }
+}
+
 /// A [`Simulator`] is used to turn the level output from the audio analysis
 /// into a scene using physics simulation.
 pub trait Simulator {
     /// The Output Scene Type used.
     type Scene;
 
+    /// Is invoked with the raw samples of the audio signal before the
+    /// simulation is advanced over them. Most simulators only need the
+    /// levels, so this does nothing by default.
+    fn feed(&mut self, _samples: &Samples) {}
+
     /// Advances the simulation
     fn step(&mut self, delta_time: Duration, levels: &[f32]);
 
     /// Creates as snapshot of the current scene
     fn scene(&self) -> Self::Scene;
+
+    /// Returns the position and radius of every sphere as `[x, y, z, radius]`
+    /// for external tools. Simulators without spheres return nothing by
+    /// default.
+    fn spheres(&self) -> Vec<[f32; 4]> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,79 @@
+/// Defines the distance used to approximate the derivatives of the noise
+const EPSILON: f32 = 0.01;
+
+/// Samples the time varying noise field used to perturb the origin of a
+/// sphere. The result is in the range -1.0 to 1.0 and changes smoothly with
+/// the time. Neighbouring spheres move similar but not equal, and every axis
+/// samples an independent part of the field.
+pub(super) fn wobble(sphere: usize, axis: usize, time: f32) -> f32 {
+    value_noise(sphere as f32 * 0.5 + axis as f32 * 101.0, time, 0.0)
+}
+
+/// Samples a time varying 2D curl noise flow field. As the field is
+/// divergence free, bodies following it swirl around instead of clumping
+/// together.
+pub(super) fn curl_2d(x: f32, y: f32, time: f32) -> [f32; 2] {
+    let potential = |x: f32, y: f32| value_noise(x, y, time);
+
+    let dx = (potential(x + EPSILON, y) - potential(x - EPSILON, y)) / (2.0 * EPSILON);
+    let dy = (potential(x, y + EPSILON) - potential(x, y - EPSILON)) / (2.0 * EPSILON);
+
+    [dy, -dx]
+}
+
+/// Samples a time varying 3D curl noise flow field. As the field is
+/// divergence free, bodies following it swirl around instead of clumping
+/// together.
+pub(super) fn curl_3d(x: f32, y: f32, z: f32, time: f32) -> [f32; 3] {
+    let potential = |axis: usize, x: f32, y: f32, z: f32| {
+        value_noise(x + axis as f32 * 101.0, y + time, z + time)
+    };
+
+    let derivative = |axis: usize, [dx, dy, dz]: [f32; 3]| {
+        (potential(axis, x + dx, y + dy, z + dz) - potential(axis, x - dx, y - dy, z - dz))
+            / (2.0 * EPSILON)
+    };
+
+    let x_axis = [EPSILON, 0.0, 0.0];
+    let y_axis = [0.0, EPSILON, 0.0];
+    let z_axis = [0.0, 0.0, EPSILON];
+
+    [
+        derivative(2, y_axis) - derivative(1, z_axis),
+        derivative(0, z_axis) - derivative(2, x_axis),
+        derivative(1, x_axis) - derivative(0, y_axis),
+    ]
+}
+
+fn value_noise(x: f32, y: f32, z: f32) -> f32 {
+    let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+    let (tx, ty, tz) = (fade(x - x0), fade(y - y0), fade(z - z0));
+    let (x0, y0, z0) = (x0 as i32, y0 as i32, z0 as i32);
+
+    let plane = |z: i32| {
+        let a = lerp(hash(x0, y0, z), hash(x0 + 1, y0, z), tx);
+        let b = lerp(hash(x0, y0 + 1, z), hash(x0 + 1, y0 + 1, z), tx);
+
+        lerp(a, b, ty)
+    };
+
+    lerp(plane(z0), plane(z0 + 1), tz)
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn hash(x: i32, y: i32, z: i32) -> f32 {
+    let mut hash = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+    hash = (hash ^ (hash >> 13)).wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 16;
+
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
@@ -0,0 +1,45 @@
+//! Contains implementation of the passthrough of the spectrum levels
+
+use std::time::Duration;
+
+use crate::Module;
+
+use super::Simulator;
+
+/// A [`Simulator`] without any simulation, which hands the levels of the
+/// spectrum straight to the scene converter. This is used by visualizers
+/// which are driven directly by the spectrum, like the bars and the tunnel.
+#[derive(Default)]
+pub struct Passthrough {
+    levels: Vec<f32>,
+}
+
+impl Simulator for Passthrough {
+    type Scene = Vec<f32>;
+
+    fn step(&mut self, _delta_time: Duration, levels: &[f32]) {
+        self.levels.clear();
+        self.levels.extend_from_slice(levels);
+    }
+
+    fn scene(&self) -> Self::Scene {
+        self.levels.clone()
+    }
+}
+
+impl Module for Passthrough {
+    type Settings = PassthroughSettings;
+
+    fn set_settings(&mut self, _settings: Self::Settings) -> &mut Self {
+        self
+    }
+
+    fn settings(&self) -> Self::Settings {
+        PassthroughSettings
+    }
+}
+
+/// Stores the settings of the [`Passthrough`], which has none
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub struct PassthroughSettings;
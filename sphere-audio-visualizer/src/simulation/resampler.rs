@@ -1,18 +1,57 @@
+use std::time::Duration;
+
 use crate::{audio_analysis::Samples, Module};
 
 const SIMULATION_FRAMERATE: f64 = 240.0;
+const MAX_FRAME_TIME: f64 = 0.25;
+const MAX_STEPS_PER_FRAME: usize = 60;
+const MAX_BATCH_TIME: f64 = 1.0;
+const FRAME_TIME_SMOOTHING: f64 = 0.8;
+
+/// Defines the range of simulator framerates. Slower simulations become
+/// unstable, while faster ones cost more than they change the motion.
+const SIMULATION_FRAMERATE_RANGE: (f64, f64) = (30.0, 1000.0);
+
+/// Specifies how the [`SimulationResampler`] ingests sample batches which are
+/// longer than the maximum batch time, e.g. after the audio source stalled
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IngestionPolicy {
+    /// Every sample is analyzed, however long the batch is
+    ProcessAll,
+    /// Only the latest samples up to the maximum batch time are analyzed, the
+    /// older ones are dropped with a warning
+    DropOldest,
+}
 
 /// Stores the settings of the [`SimulationResampler`]
 #[derive(Clone)]
 pub struct SimulationResamplerSettings {
     /// The simulator framerate used
     pub simulator_framerate: f64,
+    /// The maximum amount of time in seconds simulated in a single frame
+    pub max_frame_time: f64,
+    /// The maximum amount of simulation steps executed in a single frame
+    pub max_steps_per_frame: usize,
+    /// The weight of the previous frame times in the smoothed frame time. 0.0
+    /// disables the smoothing.
+    pub frame_time_smoothing: f64,
+    /// Specifies how sample batches longer than the maximum batch time are
+    /// ingested
+    pub ingestion_policy: IngestionPolicy,
+    /// The maximum duration in seconds of a sample batch, which is analyzed
+    /// in a single frame
+    pub max_batch_time: f64,
 }
 
 impl Default for SimulationResamplerSettings {
     fn default() -> Self {
         Self {
             simulator_framerate: SIMULATION_FRAMERATE,
+            max_frame_time: MAX_FRAME_TIME,
+            max_steps_per_frame: MAX_STEPS_PER_FRAME,
+            frame_time_smoothing: FRAME_TIME_SMOOTHING,
+            ingestion_policy: IngestionPolicy::DropOldest,
+            max_batch_time: MAX_BATCH_TIME,
         }
     }
 }
@@ -30,7 +69,9 @@ impl<'a> SimulationResamplerIterator<'a> {
         Self {
             first: true,
             sample_pos: 0.0,
-            samples_per_step: samples.sample_rate / simulation_framerate,
+            // every step takes at least one sample, so the iteration ends
+            // for any sample rate
+            samples_per_step: (samples.sample_rate / simulation_framerate).max(1.0),
             samples_len: samples.samples.len() as f64,
             samples,
         }
@@ -65,9 +106,26 @@ impl<'a> Iterator for SimulationResamplerIterator<'a> {
 }
 
 /// Resamples the audio samples of one frame to a given framerate to archive consistent frame rate indipendent
-/// simulation
+/// simulation.
+///
+/// The elapsed time is fed into an accumulator which is drained in fixed simulation steps. The time
+/// simulated per frame as well as the amount of steps per frame are clamped, so long stalls (e.g. while
+/// the window is dragged) don't destabilize the simulation or lead to an ever growing backlog of steps.
+/// The clamped frame times are smoothed exponentially, so uneven sample batches don't make the motion jitter.
+/// The samples are [`ingest`](Self::ingest)ed first, which limits the samples analyzed per frame.
 pub struct SimulationResampler {
     simulation_framerate: f64,
+    max_frame_time: f64,
+    max_steps_per_frame: usize,
+    frame_time_smoothing: f64,
+    ingestion_policy: IngestionPolicy,
+    max_batch_time: f64,
+    invalid_sample_rate: bool,
+    accumulator: f64,
+    smoothed_frame_time: Option<f64>,
+    frame_time_scale: f64,
+    frame_time: f64,
+    frame_steps: usize,
 }
 
 impl SimulationResampler {
@@ -75,6 +133,17 @@ impl SimulationResampler {
     pub fn new(simulator_framerate: f64) -> Self {
         Self {
             simulation_framerate: simulator_framerate,
+            max_frame_time: MAX_FRAME_TIME,
+            max_steps_per_frame: MAX_STEPS_PER_FRAME,
+            frame_time_smoothing: FRAME_TIME_SMOOTHING,
+            ingestion_policy: IngestionPolicy::DropOldest,
+            max_batch_time: MAX_BATCH_TIME,
+            invalid_sample_rate: false,
+            accumulator: 0.0,
+            smoothed_frame_time: None,
+            frame_time_scale: 1.0,
+            frame_time: 0.0,
+            frame_steps: 0,
         }
     }
 
@@ -83,18 +152,220 @@ impl SimulationResampler {
         self.simulation_framerate
     }
 
-    /// Sets the simulator framerate
+    /// Sets the simulator framerate. The framerate is clamped to a range the
+    /// simulators stay stable in.
     pub fn set_simulator_framerate(&mut self, simulator_framerate: f64) -> &mut Self {
-        self.simulation_framerate = simulator_framerate;
+        let (min, max) = SIMULATION_FRAMERATE_RANGE;
+
+        self.simulation_framerate = simulator_framerate.clamp(min, max);
         self
     }
 
-    /// Sets the simulator framerate
+    /// Sets the simulator framerate. The framerate is clamped to a range the
+    /// simulators stay stable in.
     pub fn with_simulator_framerate(mut self, simulator_framerate: f64) -> Self {
         self.set_simulator_framerate(simulator_framerate);
         self
     }
 
+    /// Returns the maximum amount of time in seconds simulated in a single frame
+    pub fn max_frame_time(&self) -> f64 {
+        self.max_frame_time
+    }
+
+    /// Sets the maximum amount of time in seconds simulated in a single frame
+    pub fn set_max_frame_time(&mut self, max_frame_time: f64) -> &mut Self {
+        self.max_frame_time = max_frame_time.max(0.0);
+        self
+    }
+
+    /// Sets the maximum amount of time in seconds simulated in a single frame
+    pub fn with_max_frame_time(mut self, max_frame_time: f64) -> Self {
+        self.set_max_frame_time(max_frame_time);
+        self
+    }
+
+    /// Returns the maximum amount of simulation steps executed in a single frame
+    pub fn max_steps_per_frame(&self) -> usize {
+        self.max_steps_per_frame
+    }
+
+    /// Sets the maximum amount of simulation steps executed in a single frame
+    pub fn set_max_steps_per_frame(&mut self, max_steps_per_frame: usize) -> &mut Self {
+        self.max_steps_per_frame = max_steps_per_frame.max(1);
+        self
+    }
+
+    /// Sets the maximum amount of simulation steps executed in a single frame
+    pub fn with_max_steps_per_frame(mut self, max_steps_per_frame: usize) -> Self {
+        self.set_max_steps_per_frame(max_steps_per_frame);
+        self
+    }
+
+    /// Returns the weight of the previous frame times in the smoothed frame
+    /// time
+    pub fn frame_time_smoothing(&self) -> f64 {
+        self.frame_time_smoothing
+    }
+
+    /// Sets the weight of the previous frame times in the smoothed frame time.
+    /// 0.0 disables the smoothing.
+    pub fn set_frame_time_smoothing(&mut self, frame_time_smoothing: f64) -> &mut Self {
+        self.frame_time_smoothing = frame_time_smoothing.clamp(0.0, 0.99);
+        self
+    }
+
+    /// Sets the weight of the previous frame times in the smoothed frame time
+    pub fn with_frame_time_smoothing(mut self, frame_time_smoothing: f64) -> Self {
+        self.set_frame_time_smoothing(frame_time_smoothing);
+        self
+    }
+
+    /// Returns how sample batches longer than the maximum batch time are
+    /// ingested
+    pub fn ingestion_policy(&self) -> IngestionPolicy {
+        self.ingestion_policy
+    }
+
+    /// Sets how sample batches longer than the maximum batch time are ingested
+    pub fn set_ingestion_policy(&mut self, ingestion_policy: IngestionPolicy) -> &mut Self {
+        self.ingestion_policy = ingestion_policy;
+        self
+    }
+
+    /// Sets how sample batches longer than the maximum batch time are ingested
+    pub fn with_ingestion_policy(mut self, ingestion_policy: IngestionPolicy) -> Self {
+        self.set_ingestion_policy(ingestion_policy);
+        self
+    }
+
+    /// Returns the maximum duration in seconds of a sample batch
+    pub fn max_batch_time(&self) -> f64 {
+        self.max_batch_time
+    }
+
+    /// Sets the maximum duration in seconds of a sample batch. The batch of a
+    /// frame keeps at least one simulation step worth of samples.
+    pub fn set_max_batch_time(&mut self, max_batch_time: f64) -> &mut Self {
+        self.max_batch_time = max_batch_time.max(0.0);
+        self
+    }
+
+    /// Sets the maximum duration in seconds of a sample batch
+    pub fn with_max_batch_time(mut self, max_batch_time: f64) -> Self {
+        self.set_max_batch_time(max_batch_time);
+        self
+    }
+
+    /// Applies the ingestion policy to the samples of one frame. Batches
+    /// without a valid sample rate are dropped, as they can't be analyzed.
+    pub fn ingest<'a>(&mut self, samples: Samples<'a>) -> Samples<'a> {
+        if !samples.has_valid_sample_rate() {
+            // a source with a broken sample rate would warn every frame
+            if !self.invalid_sample_rate && !samples.samples.is_empty() {
+                eprintln!(
+                    "Dropped samples with the invalid sample rate {}",
+                    samples.sample_rate
+                );
+                self.invalid_sample_rate = true;
+            }
+
+            return Samples {
+                sample_rate: samples.sample_rate,
+                samples: &[],
+            };
+        }
+
+        self.invalid_sample_rate = false;
+
+        let max_batch_time = self.max_batch_time.max(self.step_time());
+
+        if self.ingestion_policy == IngestionPolicy::ProcessAll
+            || samples.duration() <= max_batch_time
+        {
+            return samples;
+        }
+
+        let kept = (max_batch_time * samples.sample_rate).ceil() as usize;
+        let dropped = samples.samples.len().saturating_sub(kept);
+
+        eprintln!(
+            "Dropped {:.2} s of samples, which arrived at once",
+            dropped as f64 / samples.sample_rate
+        );
+
+        Samples {
+            sample_rate: samples.sample_rate,
+            samples: &samples.samples[dropped..],
+        }
+    }
+
+    /// Returns the duration of a single fixed simulation step
+    pub fn step_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.step_time())
+    }
+
+    fn step_time(&self) -> f64 {
+        if self.simulation_framerate > 0.0 {
+            1.0 / self.simulation_framerate
+        } else {
+            1.0 / SIMULATION_FRAMERATE
+        }
+    }
+
+    /// Starts a new frame, which lasts the given time. Has to be called once before the samples of a frame
+    /// are [`advance`](Self::advance)d. The time of the frame is clamped and smoothed with the previous
+    /// frames, the steps of the frame are scaled accordingly.
+    pub fn begin_frame(&mut self, frame_time: Duration) {
+        self.frame_time = 0.0;
+        self.frame_steps = 0;
+
+        let frame_time = frame_time.as_secs_f64().min(self.max_frame_time);
+
+        // frames without samples don't advance the simulation and would drag
+        // the smoothed time towards zero
+        if frame_time <= 0.0 {
+            return;
+        }
+
+        let smoothed_frame_time = match self.smoothed_frame_time {
+            Some(smoothed_frame_time) => {
+                smoothed_frame_time * self.frame_time_smoothing
+                    + frame_time * (1.0 - self.frame_time_smoothing)
+            }
+            None => frame_time,
+        };
+
+        self.smoothed_frame_time = Some(smoothed_frame_time);
+        self.frame_time_scale = smoothed_frame_time / frame_time;
+    }
+
+    /// Advances the accumulator by the given elapsed time and returns the amount of fixed simulation steps
+    /// of [`step_duration`](Self::step_duration) that should be executed.
+    pub fn advance(&mut self, delta_time: Duration) -> usize {
+        let step_time = self.step_time();
+
+        let delta_time = (delta_time.as_secs_f64() * self.frame_time_scale)
+            .min(self.max_frame_time - self.frame_time)
+            .max(0.0);
+
+        self.frame_time += delta_time;
+        self.accumulator += delta_time;
+
+        let available_steps = self.max_steps_per_frame.saturating_sub(self.frame_steps);
+        let steps = ((self.accumulator / step_time) as usize).min(available_steps);
+
+        self.accumulator -= steps as f64 * step_time;
+        self.frame_steps += steps;
+
+        if self.frame_steps >= self.max_steps_per_frame {
+            // drop the backlog instead of catching up in the following frames
+            self.accumulator = self.accumulator.min(step_time);
+        }
+
+        steps
+    }
+
     /// Resamples the audio samples of one frame to a given framerate to archive consistent frame rate indipendent
     /// simulation
     pub fn resample<'a>(&self, samples: Samples<'a>) -> impl Iterator<Item = Samples<'a>> {
@@ -113,11 +384,21 @@ impl Module for SimulationResampler {
 
     fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
         self.set_simulator_framerate(settings.simulator_framerate)
+            .set_max_frame_time(settings.max_frame_time)
+            .set_max_steps_per_frame(settings.max_steps_per_frame)
+            .set_frame_time_smoothing(settings.frame_time_smoothing)
+            .set_ingestion_policy(settings.ingestion_policy)
+            .set_max_batch_time(settings.max_batch_time)
     }
 
     fn settings(&self) -> Self::Settings {
         SimulationResamplerSettings {
             simulator_framerate: self.simulator_framerate(),
+            max_frame_time: self.max_frame_time(),
+            max_steps_per_frame: self.max_steps_per_frame(),
+            frame_time_smoothing: self.frame_time_smoothing(),
+            ingestion_policy: self.ingestion_policy(),
+            max_batch_time: self.max_batch_time(),
         }
     }
 }
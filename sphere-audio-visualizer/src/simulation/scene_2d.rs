@@ -12,7 +12,12 @@ use rapier2d::prelude::{
 
 use crate::module::Module;
 
-use super::{SimulationSettings, Simulator, SPHERE_MIN_RADIUS};
+use super::{
+    line_position, mid_high_energy,
+    noise::{curl_2d, wobble},
+    BandOrder, Layout, SimulationSettings, Simulator, ORBIT_SPEED, SPHERE_MIN_RADIUS,
+    TURBULENCE_SCALE, TURBULENCE_SPEED, TURBULENCE_STRENGTH, WOBBLE_AMPLITUDE, WOBBLE_FREQUENCY,
+};
 
 /// Stores data from a 2D sphere
 pub struct Sphere2D {
@@ -40,6 +45,15 @@ pub struct Simulation2D {
     ccd_solver: CCDSolver,
     spheres: Vec<SphereData2D>,
     min_radius: f32,
+    wobble_amplitude: f32,
+    wobble_frequency: f32,
+    turbulence_strength: f32,
+    turbulence_scale: f32,
+    turbulence_speed: f32,
+    layout: Layout,
+    band_order: BandOrder,
+    orbit_speed: f32,
+    time: f32,
 }
 
 impl Simulation2D {
@@ -74,6 +88,15 @@ impl Simulation2D {
             ccd_solver,
             spheres,
             min_radius,
+            wobble_amplitude: WOBBLE_AMPLITUDE,
+            wobble_frequency: WOBBLE_FREQUENCY,
+            turbulence_strength: TURBULENCE_STRENGTH,
+            turbulence_scale: TURBULENCE_SCALE,
+            turbulence_speed: TURBULENCE_SPEED,
+            layout: Layout::Line,
+            band_order: BandOrder::LeftToRight,
+            orbit_speed: ORBIT_SPEED,
+            time: 0.0,
         }
     }
 
@@ -93,6 +116,154 @@ impl Simulation2D {
         self.set_min_radius(min_radius);
         self
     }
+
+    /// Gets the amplitude of the noise field perturbing the origin of the
+    /// spheres
+    pub fn wobble_amplitude(&self) -> f32 {
+        self.wobble_amplitude
+    }
+
+    /// Sets the amplitude of the noise field perturbing the origin of the
+    /// spheres
+    pub fn set_wobble_amplitude(&mut self, wobble_amplitude: f32) -> &mut Self {
+        self.wobble_amplitude = wobble_amplitude;
+        self
+    }
+
+    /// Sets the amplitude of the noise field perturbing the origin of the
+    /// spheres
+    pub fn with_wobble_amplitude(mut self, wobble_amplitude: f32) -> Self {
+        self.set_wobble_amplitude(wobble_amplitude);
+        self
+    }
+
+    /// Gets the frequency in Hz of the noise field perturbing the origin of
+    /// the spheres
+    pub fn wobble_frequency(&self) -> f32 {
+        self.wobble_frequency
+    }
+
+    /// Sets the frequency in Hz of the noise field perturbing the origin of
+    /// the spheres
+    pub fn set_wobble_frequency(&mut self, wobble_frequency: f32) -> &mut Self {
+        self.wobble_frequency = wobble_frequency;
+        self
+    }
+
+    /// Sets the frequency in Hz of the noise field perturbing the origin of
+    /// the spheres
+    pub fn with_wobble_frequency(mut self, wobble_frequency: f32) -> Self {
+        self.set_wobble_frequency(wobble_frequency);
+        self
+    }
+
+    /// Gets the strength of the turbulence force, which is scaled by the
+    /// energy of the mid and high frequency bands
+    pub fn turbulence_strength(&self) -> f32 {
+        self.turbulence_strength
+    }
+
+    /// Sets the strength of the turbulence force, which is scaled by the
+    /// energy of the mid and high frequency bands
+    pub fn set_turbulence_strength(&mut self, turbulence_strength: f32) -> &mut Self {
+        self.turbulence_strength = turbulence_strength;
+        self
+    }
+
+    /// Sets the strength of the turbulence force, which is scaled by the
+    /// energy of the mid and high frequency bands
+    pub fn with_turbulence_strength(mut self, turbulence_strength: f32) -> Self {
+        self.set_turbulence_strength(turbulence_strength);
+        self
+    }
+
+    /// Gets the spatial scale of the turbulence noise field
+    pub fn turbulence_scale(&self) -> f32 {
+        self.turbulence_scale
+    }
+
+    /// Sets the spatial scale of the turbulence noise field
+    pub fn set_turbulence_scale(&mut self, turbulence_scale: f32) -> &mut Self {
+        self.turbulence_scale = turbulence_scale;
+        self
+    }
+
+    /// Sets the spatial scale of the turbulence noise field
+    pub fn with_turbulence_scale(mut self, turbulence_scale: f32) -> Self {
+        self.set_turbulence_scale(turbulence_scale);
+        self
+    }
+
+    /// Gets the speed at which the turbulence noise field changes over time
+    pub fn turbulence_speed(&self) -> f32 {
+        self.turbulence_speed
+    }
+
+    /// Sets the speed at which the turbulence noise field changes over time
+    pub fn set_turbulence_speed(&mut self, turbulence_speed: f32) -> &mut Self {
+        self.turbulence_speed = turbulence_speed;
+        self
+    }
+
+    /// Sets the speed at which the turbulence noise field changes over time
+    pub fn with_turbulence_speed(mut self, turbulence_speed: f32) -> Self {
+        self.set_turbulence_speed(turbulence_speed);
+        self
+    }
+
+    /// Gets the arrangement of the spheres
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Sets the arrangement of the spheres
+    pub fn set_layout(&mut self, layout: Layout) -> &mut Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the arrangement of the spheres
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.set_layout(layout);
+        self
+    }
+
+    /// Gets the order of the frequency bands in the [`Layout::Line`] layout
+    pub fn band_order(&self) -> BandOrder {
+        self.band_order
+    }
+
+    /// Sets the order of the frequency bands in the [`Layout::Line`] layout
+    pub fn set_band_order(&mut self, band_order: BandOrder) -> &mut Self {
+        self.band_order = band_order;
+        self
+    }
+
+    /// Sets the order of the frequency bands in the [`Layout::Line`] layout
+    pub fn with_band_order(mut self, band_order: BandOrder) -> Self {
+        self.set_band_order(band_order);
+        self
+    }
+
+    /// Gets the speed in radians per second of the spheres in the
+    /// [`Layout::Orbit`] layout
+    pub fn orbit_speed(&self) -> f32 {
+        self.orbit_speed
+    }
+
+    /// Sets the speed in radians per second of the spheres in the
+    /// [`Layout::Orbit`] layout
+    pub fn set_orbit_speed(&mut self, orbit_speed: f32) -> &mut Self {
+        self.orbit_speed = orbit_speed;
+        self
+    }
+
+    /// Sets the speed in radians per second of the spheres in the
+    /// [`Layout::Orbit`] layout
+    pub fn with_orbit_speed(mut self, orbit_speed: f32) -> Self {
+        self.set_orbit_speed(orbit_speed);
+        self
+    }
 }
 
 impl Simulator for Simulation2D {
@@ -102,10 +273,9 @@ impl Simulator for Simulation2D {
         let gravity = vec2(0.0f32, 0.0f32);
         let delta_time_seconds = delta_time.as_secs_f32();
 
-        let sphere_count = levels.len();
+        let turbulence = self.turbulence_strength * mid_high_energy(levels);
 
-        let offset = (sphere_count - 1) as f32 * 0.5;
-        let factor = 16.0 / sphere_count as f32;
+        let sphere_count = levels.len();
 
         if sphere_count < self.spheres.len() {
             unsafe { self.spheres.set_len(sphere_count) }
@@ -113,12 +283,15 @@ impl Simulator for Simulation2D {
 
         let mut rng = thread_rng();
 
+        self.time += delta_time_seconds;
+        let wobble_time = self.time * self.wobble_frequency;
+
         for (i, level) in levels.iter().enumerate() {
             let radius = self.min_radius.max(*level * 2.0);
 
             match self.spheres.get_mut(i) {
                 Some(sphere) => {
-                    sphere.origin.x = (i as f32 - offset) * factor;
+                    sphere.origin.x = line_position(i, sphere_count, self.band_order);
 
                     if let Some(collider) = self.collider_set.get_mut(sphere.collider) {
                         if let Some(sphere) = collider.shape_mut().downcast_mut::<Ball>() {
@@ -126,19 +299,26 @@ impl Simulator for Simulation2D {
                         }
                     }
 
+                    let origin = sphere.origin
+                        + vec2(wobble(i, 0, wobble_time), wobble(i, 1, wobble_time))
+                            * self.wobble_amplitude;
+
                     if let Some(rigid_body) = self.rigid_body_set.get_mut(sphere.rigid_body) {
                         let current_position = rigid_body.translation().clone();
 
                         rigid_body.set_translation(
-                            sphere.origin
-                                + (current_position - sphere.origin)
+                            origin
+                                + (current_position - origin)
                                     * (1.0 - f32::powf(0.99, delta_time_seconds)),
                             true,
                         );
                     }
                 }
                 None => {
-                    let origin = vec2((i as f32 - offset) * factor, rng.gen_range(-0.05..0.05));
+                    let origin = vec2(
+                        line_position(i, sphere_count, self.band_order),
+                        rng.gen_range(-0.05..0.05),
+                    );
 
                     let rigid_body = RigidBodyBuilder::new_dynamic().translation(origin).build();
 
@@ -164,6 +344,20 @@ impl Simulator for Simulation2D {
             }
         }
 
+        if turbulence != 0.0 {
+            let turbulence_time = self.time * self.turbulence_speed;
+
+            for sphere in &self.spheres {
+                if let Some(rigid_body) = self.rigid_body_set.get_mut(sphere.rigid_body) {
+                    let position = rigid_body.translation() * self.turbulence_scale;
+                    let [x, y] = curl_2d(position.x, position.y, turbulence_time);
+                    let mass = rigid_body.mass();
+
+                    rigid_body.apply_force(vec2(x, y) * turbulence * mass, true);
+                }
+            }
+        }
+
         let integration_parameters = IntegrationParameters {
             dt: delta_time_seconds,
             ..Default::default()
@@ -200,6 +394,13 @@ impl Simulator for Simulation2D {
             })
             .collect()
     }
+
+    fn spheres(&self) -> Vec<[f32; 4]> {
+        self.scene()
+            .iter()
+            .map(|sphere| [sphere.position.x, sphere.position.y, 0.0, sphere.radius])
+            .collect()
+    }
 }
 
 impl Default for Simulation2D {
@@ -213,11 +414,27 @@ impl Module for Simulation2D {
 
     fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
         self.set_min_radius(settings.min_radius)
+            .set_wobble_amplitude(settings.wobble_amplitude)
+            .set_wobble_frequency(settings.wobble_frequency)
+            .set_turbulence_strength(settings.turbulence_strength)
+            .set_turbulence_scale(settings.turbulence_scale)
+            .set_turbulence_speed(settings.turbulence_speed)
+            .set_layout(settings.layout)
+            .set_band_order(settings.band_order)
+            .set_orbit_speed(settings.orbit_speed)
     }
 
     fn settings(&self) -> Self::Settings {
         SimulationSettings {
             min_radius: self.min_radius(),
+            wobble_amplitude: self.wobble_amplitude(),
+            wobble_frequency: self.wobble_frequency(),
+            turbulence_strength: self.turbulence_strength(),
+            turbulence_scale: self.turbulence_scale(),
+            turbulence_speed: self.turbulence_speed(),
+            layout: self.layout(),
+            band_order: self.band_order(),
+            orbit_speed: self.orbit_speed(),
         }
     }
 }
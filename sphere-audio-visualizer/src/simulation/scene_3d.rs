@@ -12,7 +12,31 @@ use rapier3d::prelude::{
 
 use crate::module::Module;
 
-use super::{SimulationSettings, Simulator, SPHERE_MIN_RADIUS};
+use super::{
+    line_position, mid_high_energy,
+    noise::{curl_3d, wobble},
+    BandOrder, Layout, SimulationSettings, Simulator, ORBIT_SPEED, SPHERE_MIN_RADIUS,
+    TURBULENCE_SCALE, TURBULENCE_SPEED, TURBULENCE_STRENGTH, WOBBLE_AMPLITUDE, WOBBLE_FREQUENCY,
+};
+
+/// Defines the fraction of the recent peak level which is kept per second
+const PEAK_RELEASE: f32 = 0.5;
+
+/// The radius of the central sphere of the orbit layout
+const CENTER_RADIUS: f32 = 1.0;
+
+/// The orbit radius of the lowest frequency band
+const ORBIT_INNER_RADIUS: f32 = 2.0;
+
+/// The orbit radius of the highest frequency band
+const ORBIT_OUTER_RADIUS: f32 = 8.0;
+
+/// The angle in radians the orbit plane is tilted away from the camera
+const ORBIT_TILT: f32 = 1.0;
+
+/// The angle between the start positions of neighbouring orbits, which
+/// spreads the spheres evenly
+const GOLDEN_ANGLE: f32 = 2.399_963;
 
 /// Stores data from a 3D sphere
 pub struct Sphere3D {
@@ -20,10 +44,29 @@ pub struct Sphere3D {
     pub radius: f32,
     /// The position of the sphere
     pub position: Vec3,
+    /// The velocity of the sphere in units per second during the last step
+    pub velocity: Vec3,
+    /// The index of the frequency band the sphere represents
+    pub band: usize,
+    /// The current level of the frequency band
+    pub level: f32,
+    /// The recent peak level of the frequency band
+    pub peak: f32,
+    /// The time in seconds since the last peak of the frequency band
+    pub peak_age: f32,
+    /// Whether the sphere is a light source, like the central sphere of the
+    /// orbit layout
+    pub emissive: bool,
 }
 
 struct SphereData3D {
     origin: Vec3,
+    position: Vec3,
+    velocity: Vec3,
+    angle: f32,
+    level: f32,
+    peak: f32,
+    peak_age: f32,
     rigid_body: RigidBodyHandle,
     collider: ColliderHandle,
 }
@@ -39,7 +82,17 @@ pub struct Simulation3D {
     joint_set: JointSet,
     ccd_solver: CCDSolver,
     spheres: Vec<SphereData3D>,
+    center: Option<RigidBodyHandle>,
     min_radius: f32,
+    wobble_amplitude: f32,
+    wobble_frequency: f32,
+    turbulence_strength: f32,
+    turbulence_scale: f32,
+    turbulence_speed: f32,
+    layout: Layout,
+    band_order: BandOrder,
+    orbit_speed: f32,
+    time: f32,
 }
 
 impl Simulation3D {
@@ -73,7 +126,17 @@ impl Simulation3D {
             joint_set,
             ccd_solver,
             spheres,
+            center: None,
             min_radius,
+            wobble_amplitude: WOBBLE_AMPLITUDE,
+            wobble_frequency: WOBBLE_FREQUENCY,
+            turbulence_strength: TURBULENCE_STRENGTH,
+            turbulence_scale: TURBULENCE_SCALE,
+            turbulence_speed: TURBULENCE_SPEED,
+            layout: Layout::Line,
+            band_order: BandOrder::LeftToRight,
+            orbit_speed: ORBIT_SPEED,
+            time: 0.0,
         }
     }
 
@@ -93,6 +156,184 @@ impl Simulation3D {
         self.set_min_radius(min_radius);
         self
     }
+
+    /// Gets the amplitude of the noise field perturbing the origin of the
+    /// spheres
+    pub fn wobble_amplitude(&self) -> f32 {
+        self.wobble_amplitude
+    }
+
+    /// Sets the amplitude of the noise field perturbing the origin of the
+    /// spheres
+    pub fn set_wobble_amplitude(&mut self, wobble_amplitude: f32) -> &mut Self {
+        self.wobble_amplitude = wobble_amplitude;
+        self
+    }
+
+    /// Sets the amplitude of the noise field perturbing the origin of the
+    /// spheres
+    pub fn with_wobble_amplitude(mut self, wobble_amplitude: f32) -> Self {
+        self.set_wobble_amplitude(wobble_amplitude);
+        self
+    }
+
+    /// Gets the frequency in Hz of the noise field perturbing the origin of
+    /// the spheres
+    pub fn wobble_frequency(&self) -> f32 {
+        self.wobble_frequency
+    }
+
+    /// Sets the frequency in Hz of the noise field perturbing the origin of
+    /// the spheres
+    pub fn set_wobble_frequency(&mut self, wobble_frequency: f32) -> &mut Self {
+        self.wobble_frequency = wobble_frequency;
+        self
+    }
+
+    /// Sets the frequency in Hz of the noise field perturbing the origin of
+    /// the spheres
+    pub fn with_wobble_frequency(mut self, wobble_frequency: f32) -> Self {
+        self.set_wobble_frequency(wobble_frequency);
+        self
+    }
+
+    /// Gets the strength of the turbulence force, which is scaled by the
+    /// energy of the mid and high frequency bands
+    pub fn turbulence_strength(&self) -> f32 {
+        self.turbulence_strength
+    }
+
+    /// Sets the strength of the turbulence force, which is scaled by the
+    /// energy of the mid and high frequency bands
+    pub fn set_turbulence_strength(&mut self, turbulence_strength: f32) -> &mut Self {
+        self.turbulence_strength = turbulence_strength;
+        self
+    }
+
+    /// Sets the strength of the turbulence force, which is scaled by the
+    /// energy of the mid and high frequency bands
+    pub fn with_turbulence_strength(mut self, turbulence_strength: f32) -> Self {
+        self.set_turbulence_strength(turbulence_strength);
+        self
+    }
+
+    /// Gets the spatial scale of the turbulence noise field
+    pub fn turbulence_scale(&self) -> f32 {
+        self.turbulence_scale
+    }
+
+    /// Sets the spatial scale of the turbulence noise field
+    pub fn set_turbulence_scale(&mut self, turbulence_scale: f32) -> &mut Self {
+        self.turbulence_scale = turbulence_scale;
+        self
+    }
+
+    /// Sets the spatial scale of the turbulence noise field
+    pub fn with_turbulence_scale(mut self, turbulence_scale: f32) -> Self {
+        self.set_turbulence_scale(turbulence_scale);
+        self
+    }
+
+    /// Gets the speed at which the turbulence noise field changes over time
+    pub fn turbulence_speed(&self) -> f32 {
+        self.turbulence_speed
+    }
+
+    /// Sets the speed at which the turbulence noise field changes over time
+    pub fn set_turbulence_speed(&mut self, turbulence_speed: f32) -> &mut Self {
+        self.turbulence_speed = turbulence_speed;
+        self
+    }
+
+    /// Sets the speed at which the turbulence noise field changes over time
+    pub fn with_turbulence_speed(mut self, turbulence_speed: f32) -> Self {
+        self.set_turbulence_speed(turbulence_speed);
+        self
+    }
+
+    /// Gets the arrangement of the spheres
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Sets the arrangement of the spheres
+    pub fn set_layout(&mut self, layout: Layout) -> &mut Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the arrangement of the spheres
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.set_layout(layout);
+        self
+    }
+
+    /// Gets the order of the frequency bands in the [`Layout::Line`] layout
+    pub fn band_order(&self) -> BandOrder {
+        self.band_order
+    }
+
+    /// Sets the order of the frequency bands in the [`Layout::Line`] layout
+    pub fn set_band_order(&mut self, band_order: BandOrder) -> &mut Self {
+        self.band_order = band_order;
+        self
+    }
+
+    /// Sets the order of the frequency bands in the [`Layout::Line`] layout
+    pub fn with_band_order(mut self, band_order: BandOrder) -> Self {
+        self.set_band_order(band_order);
+        self
+    }
+
+    /// Gets the speed in radians per second of the spheres in the
+    /// [`Layout::Orbit`] layout
+    pub fn orbit_speed(&self) -> f32 {
+        self.orbit_speed
+    }
+
+    /// Sets the speed in radians per second of the spheres in the
+    /// [`Layout::Orbit`] layout
+    pub fn set_orbit_speed(&mut self, orbit_speed: f32) -> &mut Self {
+        self.orbit_speed = orbit_speed;
+        self
+    }
+
+    /// Sets the speed in radians per second of the spheres in the
+    /// [`Layout::Orbit`] layout
+    pub fn with_orbit_speed(mut self, orbit_speed: f32) -> Self {
+        self.set_orbit_speed(orbit_speed);
+        self
+    }
+
+    /// Adds or removes the central sphere of the orbit layout
+    fn update_center(&mut self) {
+        match (self.layout, self.center) {
+            (Layout::Orbit, None) => {
+                let rigid_body = self
+                    .rigid_body_set
+                    .insert(RigidBodyBuilder::new_static().build());
+
+                self.collider_set.insert_with_parent(
+                    ColliderBuilder::ball(CENTER_RADIUS).friction(0.0).build(),
+                    rigid_body,
+                    &mut self.rigid_body_set,
+                );
+
+                self.center = Some(rigid_body);
+            }
+            (Layout::Line, Some(rigid_body)) => {
+                self.rigid_body_set.remove(
+                    rigid_body,
+                    &mut self.island_manager,
+                    &mut self.collider_set,
+                    &mut self.joint_set,
+                );
+
+                self.center = None;
+            }
+            _ => {}
+        }
+    }
 }
 
 impl Simulator for Simulation3D {
@@ -102,10 +343,11 @@ impl Simulator for Simulation3D {
         let gravity = vec3(0.0f32, 0.0f32, 0.0f32);
         let delta_time_seconds = delta_time.as_secs_f32();
 
+        let turbulence = self.turbulence_strength * mid_high_energy(levels);
+
         let levels = levels.into_iter();
         let sphere_count = levels.len();
 
-        let offset = (sphere_count - 1) as f32 * 0.5;
         let factor = 16.0 / sphere_count as f32;
 
         if sphere_count < self.spheres.len() {
@@ -114,12 +356,32 @@ impl Simulator for Simulation3D {
 
         let mut rng = thread_rng();
 
+        self.update_center();
+
+        self.time += delta_time_seconds;
+        let wobble_time = self.time * self.wobble_frequency;
+
         for (i, level) in levels.enumerate() {
             let radius = self.min_radius.max(*level);
 
             match self.spheres.get_mut(i) {
                 Some(sphere) => {
-                    sphere.origin.x = (i as f32 - offset) * factor;
+                    sphere.origin.x = line_position(i, sphere_count, self.band_order);
+                    sphere.level = *level;
+                    sphere.angle += self.orbit_speed * (1.0 + level) * delta_time_seconds;
+
+                    let anchor = match self.layout {
+                        Layout::Line => sphere.origin,
+                        Layout::Orbit => orbit_position(i, sphere_count, sphere.angle),
+                    };
+
+                    if *level >= sphere.peak {
+                        sphere.peak = *level;
+                        sphere.peak_age = 0.0;
+                    } else {
+                        sphere.peak *= PEAK_RELEASE.powf(delta_time_seconds);
+                        sphere.peak_age += delta_time_seconds;
+                    }
 
                     if let Some(collider) = self.collider_set.get_mut(sphere.collider) {
                         if let Some(sphere) = collider.shape_mut().downcast_mut::<Ball>() {
@@ -127,20 +389,25 @@ impl Simulator for Simulation3D {
                         }
                     }
 
+                    let origin = anchor
+                        + vec3(
+                            wobble(i, 0, wobble_time),
+                            wobble(i, 1, wobble_time),
+                            wobble(i, 2, wobble_time),
+                        ) * self.wobble_amplitude;
+
                     if let Some(rigid_body) = self.rigid_body_set.get_mut(sphere.rigid_body) {
                         let current_position = rigid_body.translation().clone();
 
                         rigid_body.set_translation(
-                            sphere.origin
-                                + (current_position - sphere.origin)
-                                    * 0.01f32.powf(delta_time_seconds),
+                            origin + (current_position - origin) * 0.01f32.powf(delta_time_seconds),
                             true,
                         );
                     }
                 }
                 None => {
                     let origin = vec3(
-                        (i as f32 - offset) * factor,
+                        line_position(i, sphere_count, self.band_order),
                         rng.gen_range(-0.05..0.05),
                         rng.gen_range(-0.05..0.05),
                     );
@@ -165,6 +432,12 @@ impl Simulator for Simulation3D {
 
                     self.spheres.push(SphereData3D {
                         origin,
+                        position: origin,
+                        velocity: Vec3::zeros(),
+                        angle: i as f32 * GOLDEN_ANGLE,
+                        level: *level,
+                        peak: *level,
+                        peak_age: 0.0,
                         rigid_body,
                         collider,
                     });
@@ -172,6 +445,20 @@ impl Simulator for Simulation3D {
             }
         }
 
+        if turbulence != 0.0 {
+            let turbulence_time = self.time * self.turbulence_speed;
+
+            for sphere in &self.spheres {
+                if let Some(rigid_body) = self.rigid_body_set.get_mut(sphere.rigid_body) {
+                    let position = rigid_body.translation() * self.turbulence_scale;
+                    let [x, y, z] = curl_3d(position.x, position.y, position.z, turbulence_time);
+                    let mass = rigid_body.mass();
+
+                    rigid_body.apply_force(vec3(x, y, z) * turbulence * mass, true);
+                }
+            }
+        }
+
         let integration_parameters = IntegrationParameters {
             dt: delta_time_seconds,
             ..Default::default()
@@ -190,26 +477,79 @@ impl Simulator for Simulation3D {
             &() as &dyn PhysicsHooks<RigidBodySet, ColliderSet>,
             &() as &dyn EventHandler,
         );
+
+        // the spheres are moved towards their origins directly, so the
+        // velocity of the rigid bodies misses most of their movement
+        if delta_time_seconds > 0.0 {
+            for sphere in &mut self.spheres {
+                if let Some(rigid_body) = self.rigid_body_set.get(sphere.rigid_body) {
+                    let position = rigid_body.translation().clone();
+
+                    sphere.velocity = (position - sphere.position) / delta_time_seconds;
+                    sphere.position = position;
+                }
+            }
+        }
     }
 
     fn scene(&self) -> Self::Scene {
         self.spheres
             .iter()
-            .filter_map(|sphere| {
-                let rigid_body = self.rigid_body_set.get(sphere.rigid_body)?;
-                let collider = self.collider_set.get(sphere.collider)?;
+            .enumerate()
+            .filter_map(|(band, sphere_data)| {
+                let rigid_body = self.rigid_body_set.get(sphere_data.rigid_body)?;
+                let collider = self.collider_set.get(sphere_data.collider)?;
 
                 let sphere = collider.shape().downcast_ref::<Ball>()?;
 
                 Some(Sphere3D {
                     radius: sphere.radius,
                     position: rigid_body.translation().clone(),
+                    velocity: sphere_data.velocity,
+                    band,
+                    level: sphere_data.level,
+                    peak: sphere_data.peak,
+                    peak_age: sphere_data.peak_age,
+                    emissive: false,
                 })
             })
+            .chain(self.center.map(|_| Sphere3D {
+                radius: CENTER_RADIUS,
+                position: Vec3::zeros(),
+                velocity: Vec3::zeros(),
+                band: 0,
+                level: 0.0,
+                peak: 0.0,
+                peak_age: 0.0,
+                emissive: true,
+            }))
+            .collect()
+    }
+
+    fn spheres(&self) -> Vec<[f32; 4]> {
+        self.scene()
+            .iter()
+            .map(|sphere| {
+                [
+                    sphere.position.x,
+                    sphere.position.y,
+                    sphere.position.z,
+                    sphere.radius,
+                ]
+            })
             .collect()
     }
 }
 
+/// Returns the position on the orbit of a frequency band at the given angle
+fn orbit_position(band: usize, band_count: usize, angle: f32) -> Vec3 {
+    let radius = ORBIT_INNER_RADIUS
+        + (ORBIT_OUTER_RADIUS - ORBIT_INNER_RADIUS) * (band as f32 + 0.5) / band_count as f32;
+    let (sin, cos) = angle.sin_cos();
+
+    vec3(cos, sin * ORBIT_TILT.cos(), sin * ORBIT_TILT.sin()) * radius
+}
+
 impl Default for Simulation3D {
     fn default() -> Self {
         Self::new(SPHERE_MIN_RADIUS)
@@ -221,11 +561,27 @@ impl Module for Simulation3D {
 
     fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
         self.set_min_radius(settings.min_radius)
+            .set_wobble_amplitude(settings.wobble_amplitude)
+            .set_wobble_frequency(settings.wobble_frequency)
+            .set_turbulence_strength(settings.turbulence_strength)
+            .set_turbulence_scale(settings.turbulence_scale)
+            .set_turbulence_speed(settings.turbulence_speed)
+            .set_layout(settings.layout)
+            .set_band_order(settings.band_order)
+            .set_orbit_speed(settings.orbit_speed)
     }
 
     fn settings(&self) -> Self::Settings {
         SimulationSettings {
             min_radius: self.min_radius(),
+            wobble_amplitude: self.wobble_amplitude(),
+            wobble_frequency: self.wobble_frequency(),
+            turbulence_strength: self.turbulence_strength(),
+            turbulence_scale: self.turbulence_scale(),
+            turbulence_speed: self.turbulence_speed(),
+            layout: self.layout(),
+            band_order: self.band_order(),
+            orbit_speed: self.orbit_speed(),
         }
     }
 }
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use crate::Module;
+
+/// Defines the default time in seconds the radius smoothing takes to follow
+/// rising levels
+const RADIUS_ATTACK: f32 = 0.0;
+
+/// Defines the default time in seconds the radius smoothing takes to follow
+/// falling levels
+const RADIUS_DECAY: f32 = 0.0;
+
+/// Stores the settings of the [`RadiusSmoother`]
+#[derive(Clone)]
+pub struct RadiusSmootherSettings {
+    /// The time in seconds to follow rising levels
+    pub attack: f32,
+    /// The time in seconds to follow falling levels
+    pub decay: f32,
+}
+
+impl Default for RadiusSmootherSettings {
+    fn default() -> Self {
+        Self {
+            attack: RADIUS_ATTACK,
+            decay: RADIUS_DECAY,
+        }
+    }
+}
+
+/// Smoothes the levels in the simulation domain before they are used as the
+/// radii of the spheres. This is independent of the envelope of the
+/// spectrum, so the response of the spheres can be tuned without changing
+/// the audio analysis.
+pub struct RadiusSmoother {
+    attack: f32,
+    decay: f32,
+    levels: Vec<f32>,
+}
+
+impl RadiusSmoother {
+    /// Creates a new instance with the given attack and decay times in
+    /// seconds
+    pub fn new(attack: f32, decay: f32) -> Self {
+        Self {
+            attack,
+            decay,
+            levels: Vec::new(),
+        }
+    }
+
+    /// Returns the time in seconds to follow rising levels
+    pub fn attack(&self) -> f32 {
+        self.attack
+    }
+
+    /// Sets the time in seconds to follow rising levels
+    pub fn set_attack(&mut self, attack: f32) -> &mut Self {
+        self.attack = attack.max(0.0);
+        self
+    }
+
+    /// Sets the time in seconds to follow rising levels
+    pub fn with_attack(mut self, attack: f32) -> Self {
+        self.set_attack(attack);
+        self
+    }
+
+    /// Returns the time in seconds to follow falling levels
+    pub fn decay(&self) -> f32 {
+        self.decay
+    }
+
+    /// Sets the time in seconds to follow falling levels
+    pub fn set_decay(&mut self, decay: f32) -> &mut Self {
+        self.decay = decay.max(0.0);
+        self
+    }
+
+    /// Sets the time in seconds to follow falling levels
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.set_decay(decay);
+        self
+    }
+
+    /// Advances the smoothing by one simulation step and returns the
+    /// smoothed levels
+    pub fn smooth(&mut self, delta_time: Duration, levels: &[f32]) -> &[f32] {
+        let delta_time = delta_time.as_secs_f32();
+
+        let attack = smoothing_factor(delta_time, self.attack);
+        let decay = smoothing_factor(delta_time, self.decay);
+
+        self.levels.resize(levels.len(), 0.0);
+
+        for (smoothed, level) in self.levels.iter_mut().zip(levels) {
+            let factor = if *smoothed < *level { attack } else { decay };
+
+            *smoothed = factor * (*smoothed - level) + level;
+        }
+
+        &self.levels
+    }
+}
+
+fn smoothing_factor(delta_time: f32, time: f32) -> f32 {
+    if time > 0.0 {
+        (-delta_time / time).exp()
+    } else {
+        0.0
+    }
+}
+
+impl Default for RadiusSmoother {
+    fn default() -> Self {
+        Self::new(RADIUS_ATTACK, RADIUS_DECAY)
+    }
+}
+
+impl Module for RadiusSmoother {
+    type Settings = RadiusSmootherSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_attack(settings.attack).set_decay(settings.decay)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        RadiusSmootherSettings {
+            attack: self.attack(),
+            decay: self.decay(),
+        }
+    }
+}
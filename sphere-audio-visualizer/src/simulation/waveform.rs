@@ -0,0 +1,198 @@
+//! Contains implementation of the waveform history of the oscilloscope
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{audio_analysis::Samples, Module};
+
+use super::Simulator;
+
+/// Defines the default duration in seconds of the waveform shown per trace
+const WAVEFORM_WINDOW: f32 = 0.02;
+
+/// Defines the default time in seconds an old trace takes to fade out
+const WAVEFORM_PERSISTENCE: f32 = 0.1;
+
+/// Defines the intensity below which old traces are dropped
+const TRACE_CUTOFF: f32 = 0.01;
+
+/// Defines the maximum number of traces kept for the persistence
+const MAX_TRACES: usize = 32;
+
+/// Represents a snapshot of the waveform
+#[derive(Clone)]
+pub struct Trace {
+    /// The samples of the waveform
+    pub samples: Vec<f32>,
+    /// The sample rate of the samples
+    pub sample_rate: f64,
+    /// The brightness of the trace, which fades out with its age
+    pub intensity: f32,
+}
+
+/// A [`Simulator`] without any physics, which keeps the latest raw samples
+/// of the audio signal and a history of previous traces, which fade out
+/// like the phosphor of an analog oscilloscope. The traces start at a rising
+/// zero crossing if possible to keep periodic waveforms steady.
+pub struct WaveformSimulation {
+    window: f32,
+    persistence: f32,
+    sample_rate: f64,
+    samples: VecDeque<f32>,
+    traces: VecDeque<(Vec<f32>, f32)>,
+}
+
+impl WaveformSimulation {
+    /// Creates a new instance with the given window and persistence in
+    /// seconds
+    pub fn new(window: f32, persistence: f32) -> Self {
+        Self {
+            window,
+            persistence,
+            sample_rate: 0.0,
+            samples: VecDeque::new(),
+            traces: VecDeque::new(),
+        }
+    }
+
+    /// Returns the duration in seconds of the waveform shown per trace
+    pub fn window(&self) -> f32 {
+        self.window
+    }
+
+    /// Sets the duration in seconds of the waveform shown per trace
+    pub fn set_window(&mut self, window: f32) -> &mut Self {
+        self.window = window.max(0.001);
+        self
+    }
+
+    /// Sets the duration in seconds of the waveform shown per trace
+    pub fn with_window(mut self, window: f32) -> Self {
+        self.set_window(window);
+        self
+    }
+
+    /// Returns the time in seconds an old trace takes to fade out
+    pub fn persistence(&self) -> f32 {
+        self.persistence
+    }
+
+    /// Sets the time in seconds an old trace takes to fade out. Zero only
+    /// shows the latest trace.
+    pub fn set_persistence(&mut self, persistence: f32) -> &mut Self {
+        self.persistence = persistence.max(0.0);
+        self
+    }
+
+    /// Sets the time in seconds an old trace takes to fade out
+    pub fn with_persistence(mut self, persistence: f32) -> Self {
+        self.set_persistence(persistence);
+        self
+    }
+
+    fn window_len(&self) -> usize {
+        (self.window as f64 * self.sample_rate).ceil() as usize
+    }
+}
+
+impl Simulator for WaveformSimulation {
+    /// The traces ordered from the latest to the oldest
+    type Scene = Vec<Trace>;
+
+    fn feed(&mut self, samples: &Samples) {
+        self.sample_rate = samples.sample_rate;
+        self.samples.extend(samples.samples);
+
+        // keeps a second window to search for the trigger in
+        let buffer_len = self.window_len() * 2;
+        if self.samples.len() > buffer_len {
+            self.samples.drain(..self.samples.len() - buffer_len);
+        }
+    }
+
+    fn step(&mut self, delta_time: Duration, _levels: &[f32]) {
+        let delta_time = delta_time.as_secs_f32();
+
+        for (_, age) in self.traces.iter_mut() {
+            *age += delta_time;
+        }
+
+        let persistence = self.persistence;
+        self.traces
+            .retain(|(_, age)| persistence > 0.0 && (-age / persistence).exp() > TRACE_CUTOFF);
+
+        let window_len = self.window_len().min(self.samples.len());
+        let latest = self.samples.len() - window_len;
+        let start = (1..=latest)
+            .rev()
+            .find(|&i| self.samples[i - 1] < 0.0 && self.samples[i] >= 0.0)
+            .unwrap_or(latest);
+
+        self.traces.push_front((
+            self.samples
+                .range(start..start + window_len)
+                .copied()
+                .collect(),
+            0.0,
+        ));
+        self.traces.truncate(MAX_TRACES);
+    }
+
+    fn scene(&self) -> Self::Scene {
+        self.traces
+            .iter()
+            .map(|(samples, age)| Trace {
+                samples: samples.clone(),
+                sample_rate: self.sample_rate,
+                intensity: match self.persistence > 0.0 {
+                    true => (-age / self.persistence).exp(),
+                    false => 1.0,
+                },
+            })
+            .collect()
+    }
+}
+
+impl Default for WaveformSimulation {
+    fn default() -> Self {
+        Self::new(WAVEFORM_WINDOW, WAVEFORM_PERSISTENCE)
+    }
+}
+
+impl Module for WaveformSimulation {
+    type Settings = WaveformSimulationSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_window(settings.window)
+            .set_persistence(settings.persistence)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        WaveformSimulationSettings {
+            window: self.window(),
+            persistence: self.persistence(),
+        }
+    }
+}
+
+/// Stores the settings of the [`WaveformSimulation`]
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct WaveformSimulationSettings {
+    /// The duration in seconds of the waveform shown per trace
+    pub window: f32,
+    /// The time in seconds an old trace takes to fade out
+    pub persistence: f32,
+}
+
+impl Default for WaveformSimulationSettings {
+    fn default() -> Self {
+        Self {
+            window: WAVEFORM_WINDOW,
+            persistence: WAVEFORM_PERSISTENCE,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use sphere_audio_visualizer_core::glam::{Mat3, Vec3};
+
+/// Specifies a color vision, used to simulate how people with color vision
+/// deficiencies see colors. The simulation follows Machado et al. 2009 with
+/// full severity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorVision {
+    /// Colors are seen without deficiency
+    Normal,
+    /// Red cones are missing, red and green are hard to tell apart and red
+    /// looks darker
+    Protanopia,
+    /// Green cones are missing, red and green are hard to tell apart
+    Deuteranopia,
+    /// Blue cones are missing, blue and green as well as yellow and violet
+    /// are hard to tell apart
+    Tritanopia,
+}
+
+impl ColorVision {
+    /// Every color vision, starting with [`ColorVision::Normal`]
+    pub const ALL: [ColorVision; 4] = [
+        ColorVision::Normal,
+        ColorVision::Protanopia,
+        ColorVision::Deuteranopia,
+        ColorVision::Tritanopia,
+    ];
+
+    /// Returns the matrix which transforms a linear RGB color into the color
+    /// seen with this color vision
+    pub fn matrix(&self) -> Mat3 {
+        let rows = match self {
+            ColorVision::Normal => return Mat3::IDENTITY,
+            ColorVision::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorVision::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorVision::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        };
+
+        Mat3::from_cols_array_2d(&rows).transpose()
+    }
+
+    /// Simulates how a linear RGB color is seen with this color vision
+    pub fn simulate(&self, color: Vec3) -> Vec3 {
+        (self.matrix() * color).clamp(Vec3::ZERO, Vec3::ONE)
+    }
+}
+
+impl Default for ColorVision {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// Returns the relative luminance of a linear RGB color as defined by WCAG
+pub fn relative_luminance(color: Vec3) -> f32 {
+    color.dot(Vec3::new(0.2126, 0.7152, 0.0722))
+}
+
+/// Returns the WCAG contrast ratio between two linear RGB colors, which
+/// ranges from 1.0 for equally bright colors to 21.0 for black on white
+pub fn contrast_ratio(a: Vec3, b: Vec3) -> f32 {
+    let a = relative_luminance(a);
+    let b = relative_luminance(b);
+
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
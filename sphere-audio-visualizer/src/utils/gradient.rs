@@ -1,4 +1,10 @@
-use sphere_audio_visualizer_core::glam::Vec3;
+use sphere_audio_visualizer_core::glam::{vec3, Vec3};
+
+use super::{contrast_ratio, relative_luminance, ColorVision};
+
+/// Defines the minimal contrast ratio between neighbouring stops of a
+/// gradient that is considered color-blind safe
+pub const MIN_STOP_CONTRAST: f32 = 1.25;
 
 /// Implements a simple gradient with equal distant stops
 #[derive(Debug)]
@@ -12,6 +18,31 @@ impl Gradient {
         Gradient { colors }
     }
 
+    /// Creates a new instance from stops in 8 bit sRGB, which are converted
+    /// to linear colors
+    pub fn from_srgb(colors: &[[u8; 3]]) -> Self {
+        let linear = |value: u8| {
+            let value = value as f32 / 255.0;
+
+            match value <= 0.04045 {
+                true => value / 12.92,
+                false => ((value + 0.055) / 1.055).powf(2.4),
+            }
+        };
+
+        Self::new(
+            colors
+                .iter()
+                .map(|[r, g, b]| vec3(linear(*r), linear(*g), linear(*b)))
+                .collect(),
+        )
+    }
+
+    /// Returns the linear colors of the stops
+    pub fn colors(&self) -> &[Vec3] {
+        &self.colors
+    }
+
     /// Retrives one color on the gradient. `t` should be between 0.0-1.0. if
     /// `t` is bigger or smaller the color of the first or last stop are used
     /// respectively.
@@ -25,4 +56,135 @@ impl Gradient {
 
         return (a * (1.0 - fract)) + (b * fract);
     }
+
+    /// Returns the lowest contrast ratio between neighbouring stops as seen
+    /// with the color vision. Returns `None` if the luminance does not rise
+    /// or fall steadily, because then different parts of the gradient can
+    /// look alike.
+    pub fn min_contrast(&self, color_vision: ColorVision) -> Option<f32> {
+        let colors = self
+            .colors
+            .iter()
+            .map(|color| color_vision.simulate(*color))
+            .collect::<Vec<_>>();
+
+        let rising = colors
+            .windows(2)
+            .all(|pair| relative_luminance(pair[0]) < relative_luminance(pair[1]));
+        let falling = colors
+            .windows(2)
+            .all(|pair| relative_luminance(pair[0]) > relative_luminance(pair[1]));
+
+        if !rising && !falling {
+            return None;
+        }
+
+        Some(
+            colors
+                .windows(2)
+                .map(|pair| contrast_ratio(pair[0], pair[1]))
+                .fold(f32::INFINITY, f32::min),
+        )
+    }
+
+    /// Returns if the gradient can be read with every [`ColorVision`], which
+    /// is the case if the luminance changes steadily with at least
+    /// [`MIN_STOP_CONTRAST`] between neighbouring stops
+    pub fn is_color_blind_safe(&self) -> bool {
+        ColorVision::ALL.iter().all(|color_vision| {
+            self.min_contrast(*color_vision)
+                .map_or(false, |contrast| contrast >= MIN_STOP_CONTRAST)
+        })
+    }
+}
+
+/// Specifies a preset of colors for the gradients of a visualizer
+///
+/// Every preset except [`Palette::Classic`] is color-blind safe:
+/// ```
+/// use sphere_audio_visualizer::utils::Palette;
+///
+/// for palette in Palette::ALL {
+///     assert_eq!(
+///         palette.gradient().is_color_blind_safe(),
+///         palette != Palette::Classic
+///     );
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum Palette {
+    /// The original blue and violet colors of the visualizer
+    Classic,
+    /// Goes from dark violet over blue and green to yellow
+    Viridis,
+    /// Goes from dark blue over gray to yellow. Optimized for deuteranopia
+    /// and protanopia.
+    Cividis,
+    /// Goes from black over violet and pink to light yellow
+    Magma,
+    /// Goes from black over red and orange to light yellow
+    Inferno,
+}
+
+impl Palette {
+    /// Every palette, starting with [`Palette::Classic`]
+    pub const ALL: [Palette; 5] = [
+        Palette::Classic,
+        Palette::Viridis,
+        Palette::Cividis,
+        Palette::Magma,
+        Palette::Inferno,
+    ];
+
+    /// Returns the gradient of the palette with linear colors
+    pub fn gradient(&self) -> Gradient {
+        match self {
+            Palette::Classic => Gradient::new(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.5, 0.0, 1.0),
+                vec3(0.0, 0.0, 1.0),
+                vec3(0.0, 0.5, 1.0),
+                vec3(0.0, 0.1, 1.0),
+            ]),
+            Palette::Viridis => Gradient::from_srgb(&[
+                [68, 1, 84],
+                [65, 68, 135],
+                [42, 120, 142],
+                [34, 168, 132],
+                [122, 209, 81],
+                [253, 231, 37],
+            ]),
+            Palette::Cividis => Gradient::from_srgb(&[
+                [0, 32, 77],
+                [49, 68, 107],
+                [102, 105, 112],
+                [149, 143, 120],
+                [203, 186, 105],
+                [255, 234, 70],
+            ]),
+            Palette::Magma => Gradient::from_srgb(&[
+                [0, 0, 4],
+                [59, 15, 112],
+                [140, 41, 129],
+                [222, 73, 104],
+                [254, 159, 109],
+                [252, 253, 191],
+            ]),
+            Palette::Inferno => Gradient::from_srgb(&[
+                [0, 0, 4],
+                [87, 16, 110],
+                [188, 55, 84],
+                [249, 142, 9],
+                [252, 255, 164],
+            ]),
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::Classic
+    }
 }
@@ -1,6 +1,7 @@
 //! Contains general purpose utility functions
 
+mod color_vision;
 mod gradient;
 mod type_map;
 
-pub use self::{gradient::*, type_map::*};
+pub use self::{color_vision::*, gradient::*, type_map::*};
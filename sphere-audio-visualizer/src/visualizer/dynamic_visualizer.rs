@@ -1,23 +1,32 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    any::TypeId,
+    ops::{Deref, DerefMut},
+};
 
 use winit::window::Window;
 
 use crate::{
     audio_analysis::Samples,
     module::ModuleManager,
-    rendering::wgpu::{EGUIScene, OutputFormat},
+    rendering::{
+        wgpu::{AdapterSelection, EGUIScene, OutputFormat},
+        CameraControl,
+    },
     utils::TypeMap,
 };
 
-use super::{OfflineVisualizer, OnlineVisualizer, Visualizer, VisualizerFactory};
+use super::{OfflineVisualizer, OnlineVisualizer, Visualizer, VisualizerFactory, VisualizerPreset};
 
 /// This Visualizer forwards all Visualizer calls to the internal Visualizer.
 /// This internal Viusualizer can dynamically swaped at runtime.
 /// Also the settings of previous visualizers are store and passed to the
 /// creation of new visualizers.
 /// Modules are recycled from the previous visualizer.
+/// Settings of scoped modules, like the audio analysis, are additionally
+/// kept per visualizer factory, so every visualizer keeps its own copy.
 pub struct DynamicVisualizer {
     settings_bin: TypeMap,
+    scope: Option<TypeId>,
     online_visualizer: Option<Box<dyn OnlineVisualizer>>,
     offline_visualizer_factory:
         Option<fn(OutputFormat, &mut TypeMap) -> Box<dyn OfflineVisualizer>>,
@@ -28,6 +37,7 @@ impl DynamicVisualizer {
     pub fn new() -> Self {
         Self {
             settings_bin: TypeMap::new(),
+            scope: None,
             online_visualizer: None,
             offline_visualizer_factory: None,
         }
@@ -38,6 +48,12 @@ impl DynamicVisualizer {
         &self.settings_bin
     }
 
+    /// Get the settings of the previous and current visualizers. Changed
+    /// settings are used by the next visualizer created.
+    pub fn settings_bin_mut(&mut self) -> &mut TypeMap {
+        &mut self.settings_bin
+    }
+
     /// Tries to retrive the current internal visualizer. Fails when the type
     /// does not match.
     pub fn online_visualizer<V: OnlineVisualizer>(&self) -> Option<&V> {
@@ -74,21 +90,68 @@ impl DynamicVisualizer {
         ))
     }
 
+    /// Captures the current settings of the current inner visualizer
+    pub fn current_settings(&self) -> Option<TypeMap> {
+        let mut settings_bin = TypeMap::new();
+
+        self.online_visualizer
+            .as_ref()?
+            .store_settings(&mut ModuleManager::new(&mut settings_bin).with_scope(self.scope));
+
+        Some(settings_bin)
+    }
+
+    /// Changes the settings inside the settings bin and applies them to the
+    /// current inner visualizer
+    pub fn update_settings<R>(&mut self, update: impl FnOnce(&mut TypeMap) -> R) -> R {
+        let result = update(&mut self.settings_bin);
+
+        if let Some(visualizer) = &mut self.online_visualizer {
+            visualizer.load_settings(&ModuleManager::new(&mut self.settings_bin));
+        }
+
+        result
+    }
+
+    /// Captures the settings of the current inner visualizer as a preset,
+    /// from which offline visualizers can be created later on.
+    pub fn preset(&self, name: impl ToString) -> Option<VisualizerPreset> {
+        let mut settings_bin = self.current_settings()?;
+
+        // presets are exported with the adapter of the visualizer
+        if let Some(adapter_selection) = self.settings_bin.get::<AdapterSelection>() {
+            settings_bin.insert(*adapter_selection);
+        }
+
+        Some(VisualizerPreset::new(
+            name,
+            settings_bin,
+            self.offline_visualizer_factory?,
+        ))
+    }
+
     /// Changes the internal Visualizer. Modules from the previous visualizer
     /// are recycled. Also module settings from previous visualizers are
-    /// reused.
-    pub fn change_visualizer<F: VisualizerFactory>(&mut self, window: &Window) {
-        let mut module_manager = ModuleManager::new(&mut self.settings_bin);
+    /// reused. Scoped settings are restored from the last time the new
+    /// visualizer was active.
+    pub fn change_visualizer<F: VisualizerFactory + 'static>(&mut self, window: &Window) {
+        let mut module_manager = ModuleManager::new(&mut self.settings_bin).with_scope(self.scope);
 
         if let Some(visualizer) = self.online_visualizer.take() {
             visualizer.module_bin(&mut module_manager);
         }
 
+        self.scope = Some(TypeId::of::<F>());
+        module_manager.set_scope(self.scope);
+
         self.online_visualizer = Some(Box::new(F::new_online(window, module_manager)));
 
         self.offline_visualizer_factory =
             Some(|format, settings_bin| -> Box<dyn OfflineVisualizer> {
-                Box::new(F::new_offline(format, ModuleManager::new(settings_bin)))
+                Box::new(F::new_offline(
+                    format,
+                    ModuleManager::new(settings_bin).with_scope(Some(TypeId::of::<F>())),
+                ))
             });
     }
 }
@@ -99,6 +162,24 @@ impl Visualizer for DynamicVisualizer {
             visualizer.module_bin(module_manager);
         }
     }
+
+    fn store_settings(&self, module_manager: &mut ModuleManager) {
+        if let Some(visualizer) = &self.online_visualizer {
+            visualizer.store_settings(module_manager);
+        }
+    }
+
+    fn load_settings(&mut self, module_manager: &ModuleManager) {
+        if let Some(visualizer) = &mut self.online_visualizer {
+            visualizer.load_settings(module_manager);
+        }
+    }
+
+    fn set_bpm(&mut self, bpm: Option<f32>) {
+        if let Some(visualizer) = &mut self.online_visualizer {
+            visualizer.set_bpm(bpm);
+        }
+    }
 }
 
 impl OnlineVisualizer for DynamicVisualizer {
@@ -107,4 +188,16 @@ impl OnlineVisualizer for DynamicVisualizer {
             online_visualizer.visualize(samples, width, height, egui_scene);
         }
     }
+
+    fn set_camera_control(&mut self, camera_control: CameraControl) {
+        if let Some(online_visualizer) = &mut self.online_visualizer {
+            online_visualizer.set_camera_control(camera_control);
+        }
+    }
+
+    fn set_monitor(&mut self, monitor: Option<String>) {
+        if let Some(online_visualizer) = &mut self.online_visualizer {
+            online_visualizer.set_monitor(monitor);
+        }
+    }
 }
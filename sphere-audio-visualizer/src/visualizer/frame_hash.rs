@@ -0,0 +1,227 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+/// Defines the file extension of frame hash logs
+pub const FRAME_HASH_EXTENSION: &str = "savh";
+
+/// Defines the default Hamming distance up to which two frame hashes are
+/// treated as the same frame
+pub const FRAME_HASH_TOLERANCE: u32 = 4;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Summarizes the frame hashes of a track
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameHashSummary {
+    /// Represents the amount of frames
+    pub frames: usize,
+    /// Represents a digest of every hash in order. Unlike the hashes of the
+    /// frames, the digest changes with the smallest difference.
+    pub digest: u64,
+}
+
+impl FrameHashSummary {
+    /// Creates the summary of the hashes
+    pub fn new(hashes: &[u64]) -> Self {
+        // FNV-1a is used, as it is stable between versions of the standard
+        // library
+        let digest = hashes
+            .iter()
+            .flat_map(|hash| hash.to_le_bytes())
+            .fold(FNV_OFFSET_BASIS, |digest, byte| {
+                (digest ^ byte as u64).wrapping_mul(FNV_PRIME)
+            });
+
+        Self {
+            frames: hashes.len(),
+            digest,
+        }
+    }
+}
+
+/// Describes how far the frame hashes of a track differ from a reference
+#[derive(Clone, Debug)]
+pub struct FrameHashComparison {
+    /// Represents the frames whose hashes differ by more than the tolerance
+    pub differing_frames: Vec<usize>,
+    /// Represents the largest Hamming distance between two hashes of a frame
+    pub max_distance: u32,
+    /// Represents if both tracks have the same amount of frames
+    pub same_length: bool,
+}
+
+impl FrameHashComparison {
+    /// Compares the hashes frame by frame. Hashes with a Hamming distance of
+    /// at most `tolerance` are treated as the same frame, as different GPUs
+    /// and drivers may render slightly different images.
+    ///
+    /// ```
+    /// use sphere_audio_visualizer::FrameHashComparison;
+    ///
+    /// let comparison = FrameHashComparison::new(&[0b0000, 0b0000], &[0b0001, 0b1111], 1);
+    ///
+    /// assert_eq!(comparison.differing_frames, vec![1]);
+    /// assert_eq!(comparison.max_distance, 4);
+    /// assert!(!comparison.is_match());
+    /// ```
+    pub fn new(reference: &[u64], hashes: &[u64], tolerance: u32) -> Self {
+        let mut differing_frames = Vec::new();
+        let mut max_distance = 0;
+
+        for (frame, (reference, hash)) in reference.iter().zip(hashes).enumerate() {
+            let distance = (reference ^ hash).count_ones();
+
+            if distance > tolerance {
+                differing_frames.push(frame);
+            }
+
+            max_distance = max_distance.max(distance);
+        }
+
+        Self {
+            differing_frames,
+            max_distance,
+            same_length: reference.len() == hashes.len(),
+        }
+    }
+
+    /// Returns if every frame matches the reference
+    pub fn is_match(&self) -> bool {
+        self.same_length && self.differing_frames.is_empty()
+    }
+}
+
+/// Writes the perceptual hash of every exported frame into a text file, one
+/// frame per line, followed by a summary once the track ended. A log that
+/// already exists at the path is used as the reference, so exporting a track
+/// again with a new version reports the frames that render differently.
+pub struct FrameHashLog {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    reference: Option<Vec<u64>>,
+    hashes: Vec<u64>,
+}
+
+impl FrameHashLog {
+    /// Creates the log at the path, which replaces an existing log
+    pub fn create(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+
+        let reference = match Self::read(&path) {
+            Ok(reference) => Some(reference),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => {
+                eprintln!("Failed to read frame hashes {:?}: {}", path, error);
+                None
+            }
+        };
+
+        let writer = match File::create(&path) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(error) => {
+                eprintln!("Failed to create frame hashes {:?}: {}", path, error);
+                None
+            }
+        };
+
+        Self {
+            path,
+            writer,
+            reference,
+            hashes: Vec::new(),
+        }
+    }
+
+    /// Gets the path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends the hash of the next frame
+    pub fn record(&mut self, hash: u64) {
+        let frame = self.hashes.len();
+        self.hashes.push(hash);
+
+        self.write(|writer| writeln!(writer, "{} {:016x}", frame, hash));
+    }
+
+    /// Appends the summary and the comparison with the reference. Frames
+    /// differing from the reference are reported on the standard error.
+    /// Returns the comparison, if there is a reference.
+    pub fn complete(&mut self) -> Option<FrameHashComparison> {
+        let summary = FrameHashSummary::new(&self.hashes);
+        let comparison = self.reference.as_ref().map(|reference| {
+            FrameHashComparison::new(reference, &self.hashes, FRAME_HASH_TOLERANCE)
+        });
+
+        self.write(|writer| {
+            writeln!(writer, "# frames {}", summary.frames)?;
+            writeln!(writer, "# digest {:016x}", summary.digest)?;
+
+            if let Some(comparison) = &comparison {
+                writeln!(
+                    writer,
+                    "# differing frames {}",
+                    comparison.differing_frames.len()
+                )?;
+                writeln!(writer, "# max distance {}", comparison.max_distance)?;
+                writeln!(writer, "# same length {}", comparison.same_length)?;
+            }
+
+            writer.flush()
+        });
+
+        if let (Some(reference), Some(comparison)) = (&self.reference, &comparison) {
+            if !comparison.is_match() {
+                eprintln!(
+                    "{} of {} frames differ from the reference of {:?}, which has {} frames",
+                    comparison.differing_frames.len(),
+                    summary.frames,
+                    self.path,
+                    reference.len()
+                );
+            }
+        }
+
+        comparison
+    }
+
+    /// Reads the frame hashes of a log. The summary is skipped.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u64>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut hashes = Vec::new();
+
+        for line in reader.lines() {
+            let line = line?;
+
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+
+            let hash = line
+                .split_whitespace()
+                .nth(1)
+                .and_then(|hash| u64::from_str_radix(hash, 16).ok())
+                .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "not a frame hash log"))?;
+
+            hashes.push(hash);
+        }
+
+        Ok(hashes)
+    }
+
+    fn write(&mut self, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) {
+        if let Some(writer) = &mut self.writer {
+            if let Err(error) = write(writer) {
+                eprintln!("Failed to write frame hashes {:?}: {}", self.path, error);
+
+                // the following lines would be missing anyway
+                self.writer = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::module::Module;
+
+/// Defines the default intensity, which leaves the visualizer unchanged
+pub const INTENSITY: f32 = 1.0;
+
+/// Stores the settings of the intensity module
+#[derive(Clone, PartialEq)]
+pub struct IntensitySettings {
+    /// The factor the reaction of the visualizer to the music is scaled with
+    pub intensity: f32,
+}
+
+impl Default for IntensitySettings {
+    fn default() -> Self {
+        Self {
+            intensity: INTENSITY,
+        }
+    }
+}
+
+/// A macro control scaling everything the visualizer derives from the music
+/// at once, so a performer can raise or calm down the visuals with a single
+/// value. The band levels driving the spheres and the loudness driving the
+/// pulse of the background and lights are multiplied by the intensity. The
+/// published data stays unscaled.
+pub struct Intensity {
+    intensity: f32,
+    levels: Vec<f32>,
+}
+
+impl Intensity {
+    /// Returns the factor the reaction to the music is scaled with
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Sets the factor the reaction to the music is scaled with
+    pub fn set_intensity(&mut self, intensity: f32) -> &mut Self {
+        self.intensity = intensity.max(0.0);
+        self
+    }
+
+    /// Sets the factor the reaction to the music is scaled with
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.set_intensity(intensity);
+        self
+    }
+
+    /// Scales the band levels
+    pub fn scale_levels(&mut self, levels: &[f32]) -> &[f32] {
+        self.levels.clear();
+        self.levels
+            .extend(levels.iter().map(|level| level * self.intensity));
+
+        &self.levels
+    }
+
+    /// Scales the loudness
+    pub fn scale_loudness(&self, loudness: f32) -> f32 {
+        loudness * self.intensity
+    }
+}
+
+impl Default for Intensity {
+    fn default() -> Self {
+        Self {
+            intensity: INTENSITY,
+            levels: Vec::new(),
+        }
+    }
+}
+
+impl Module for Intensity {
+    type Settings = IntensitySettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_intensity(settings.intensity)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        IntensitySettings {
+            intensity: self.intensity(),
+        }
+    }
+}
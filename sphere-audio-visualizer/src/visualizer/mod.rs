@@ -1,17 +1,31 @@
-use std::any::Any;
+use std::{any::Any, path::Path};
 
 use winit::window::Window;
 
-pub use self::{dynamic_visualizer::*, wgpu::*};
+pub use self::{
+    dynamic_visualizer::*, frame_hash::*, intensity::*, preset::*, profiler::*, publisher::*,
+    sectioned::*, sphere_labels::*, transport::*, wgpu::*,
+};
 use crate::{
     audio_analysis::Samples,
     module::ModuleManager,
-    rendering::wgpu::{
-        EGUIScene, {OffscreenTargetOutput, OutputFormat},
+    rendering::{
+        wgpu::{
+            EGUIScene, {OffscreenTargetOutput, OutputFormat},
+        },
+        CameraControl,
     },
 };
 
 mod dynamic_visualizer;
+mod frame_hash;
+mod intensity;
+mod preset;
+mod profiler;
+mod publisher;
+mod sectioned;
+mod sphere_labels;
+mod transport;
 mod wgpu;
 
 /// Base trait for the [`OnlineVisualizer`] and [`OfflineVisualizer`]
@@ -19,6 +33,19 @@ pub trait Visualizer: Any + Send + Sync {
     /// Deconstructs the visualizer into modules which are stored inside the
     /// module manager.
     fn module_bin(self: Box<Self>, module_manager: &mut ModuleManager);
+
+    /// Stores the current module settings inside the module manager, while
+    /// the visualizer keeps its modules.
+    fn store_settings(&self, module_manager: &mut ModuleManager);
+
+    /// Sets the settings of the simulator, scene converter and pipeline
+    /// stored inside the module manager, while the visualizer keeps its
+    /// modules.
+    fn load_settings(&mut self, module_manager: &ModuleManager);
+
+    /// Tells the visualizer the tempo of the audio in beats per minute if it
+    /// is known, e.g. from the beat grid of the played track
+    fn set_bpm(&mut self, bpm: Option<f32>);
 }
 
 /// An online visualizer is used to draw onto a window. It also support drawing
@@ -26,12 +53,40 @@ pub trait Visualizer: Any + Send + Sync {
 pub trait OnlineVisualizer: Visualizer {
     /// Visualizes onto a window. Supports drawing of UI.
     fn visualize(&mut self, samples: Samples, width: u32, height: u32, egui_scene: EGUIScene);
+
+    /// Overrides the camera of the visualizer with a manually controlled
+    /// camera, e.g. flown with a gamepad
+    fn set_camera_control(&mut self, camera_control: CameraControl);
+
+    /// Tells the visualizer the name of the monitor the window is on, so
+    /// per-monitor settings like HDR can follow the window
+    fn set_monitor(&mut self, monitor: Option<String>);
 }
 
 /// An offline visualizer is used to draw offscreen.
 pub trait OfflineVisualizer: Visualizer {
     /// Visualizes offscreen
     fn visualize(&mut self, samples: Samples, width: u32, height: u32) -> OffscreenTargetOutput;
+
+    /// Advances the analysis and simulation like [`OfflineVisualizer::visualize`]
+    /// without rendering a frame. Used to pre-roll the visualizer before the
+    /// first exported frame.
+    fn warm_up(&mut self, samples: Samples);
+
+    /// Caches the analysis of the track identified by `track` in the
+    /// directory. An existing cache is replayed instead of analyzing the
+    /// samples, otherwise the analysis is recorded and stored once
+    /// [`OfflineVisualizer::end_of_track`] is called.
+    fn cache_analysis(&mut self, directory: &Path, track: u64);
+
+    /// Writes a perceptual hash of every rendered frame to the
+    /// [`FrameHashLog`] at the path, followed by a summary once
+    /// [`OfflineVisualizer::end_of_track`] is called. Used to detect
+    /// rendering regressions between versions.
+    fn hash_frames(&mut self, path: &Path);
+
+    /// Signals that every sample of the track was passed to the visualizer
+    fn end_of_track(&mut self);
 }
 
 /// A Factory for creating
@@ -0,0 +1,46 @@
+use crate::{rendering::wgpu::OutputFormat, utils::TypeMap};
+
+use super::OfflineVisualizer;
+
+/// A named snapshot of the settings of a visualizer. Offline visualizers
+/// created from the preset look like the visualizer at the time the preset
+/// was captured, even if the settings were changed afterwards.
+pub struct VisualizerPreset {
+    name: String,
+    settings_bin: TypeMap,
+    offline_visualizer_factory: fn(OutputFormat, &mut TypeMap) -> Box<dyn OfflineVisualizer>,
+}
+
+impl VisualizerPreset {
+    /// Creates a new instance
+    /// - `settings_bin` Represents the captured module settings
+    /// - `offline_visualizer_factory` Represents the factory of the visualizer
+    /// the settings were captured from
+    pub fn new(
+        name: impl ToString,
+        settings_bin: TypeMap,
+        offline_visualizer_factory: fn(OutputFormat, &mut TypeMap) -> Box<dyn OfflineVisualizer>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            settings_bin,
+            offline_visualizer_factory,
+        }
+    }
+
+    /// Returns the name of the preset
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets the name of the preset
+    pub fn set_name(&mut self, name: impl ToString) -> &mut Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Creates a new offline visualizer using the settings of the preset
+    pub fn offline_visualizer(&mut self, format: OutputFormat) -> Box<dyn OfflineVisualizer> {
+        (self.offline_visualizer_factory)(format, &mut self.settings_bin)
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::Instant;
+
+/// Defines the weight of the latest frame in the smoothed timings
+const PROFILER_SMOOTHING: f64 = 0.05;
+
+/// Identifies a stage of a frame timed by the [`FrameProfiler`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileStage {
+    /// The loudness and spectrum analysis
+    Spectrum,
+    /// The resampling of the audio to the simulation framerate
+    Resampler,
+    /// The simulation steps including the smoothing of the levels
+    Simulator,
+    /// The conversion of the simulation into a scene
+    Converter,
+    /// The recording of the render passes of the pipeline, filters and UI
+    Render,
+    /// The acquisition and presentation of the target texture, which
+    /// includes waiting for the GPU and reading back exported frames
+    Present,
+}
+
+impl ProfileStage {
+    /// Contains every stage in the order of a frame
+    pub const ALL: [ProfileStage; 6] = [
+        ProfileStage::Spectrum,
+        ProfileStage::Resampler,
+        ProfileStage::Simulator,
+        ProfileStage::Converter,
+        ProfileStage::Render,
+        ProfileStage::Present,
+    ];
+}
+
+/// Measures the CPU time spent in the stages of a frame, so users know which
+/// setting to reduce when the frame rate drops. The timings are smoothed
+/// over several frames to keep them readable.
+#[derive(Default)]
+pub struct FrameProfiler {
+    frame: [f64; ProfileStage::ALL.len()],
+    smoothed: [f64; ProfileStage::ALL.len()],
+    measured: bool,
+}
+
+impl FrameProfiler {
+    /// Starts a new frame
+    pub fn begin_frame(&mut self) {
+        self.frame = Default::default();
+    }
+
+    /// Adds the time elapsed since `start` to a stage of the current frame.
+    /// Returns the current time, so consecutive stages can be chained.
+    pub fn record(&mut self, stage: ProfileStage, start: Instant) -> Instant {
+        let now = Instant::now();
+        self.frame[stage as usize] += (now - start).as_secs_f64();
+        now
+    }
+
+    /// Moves the time of a stage of the current frame to another stage. This
+    /// separates stages which are nested into each other.
+    pub fn subtract(&mut self, stage: ProfileStage, nested: ProfileStage) {
+        self.frame[stage as usize] =
+            (self.frame[stage as usize] - self.frame[nested as usize]).max(0.0);
+    }
+
+    /// Ends the current frame and adds it to the smoothed timings
+    pub fn end_frame(&mut self) {
+        for (smoothed, frame) in self.smoothed.iter_mut().zip(self.frame) {
+            *smoothed = match self.measured {
+                true => *smoothed + (frame - *smoothed) * PROFILER_SMOOTHING,
+                false => frame,
+            };
+        }
+
+        self.measured = true;
+    }
+
+    /// Returns the smoothed time of a stage in seconds
+    pub fn time(&self, stage: ProfileStage) -> f64 {
+        self.smoothed[stage as usize]
+    }
+
+    /// Returns the smoothed time of all stages in seconds
+    pub fn total_time(&self) -> f64 {
+        self.smoothed.iter().sum()
+    }
+}
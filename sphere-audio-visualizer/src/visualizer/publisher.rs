@@ -0,0 +1,219 @@
+use std::{
+    fmt::Write,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use crate::module::Module;
+
+/// Defines the default address the [`DataPublisher`] sends to
+pub const DATA_PUBLISHER_ADDRESS: &str = "127.0.0.1:7728";
+
+/// Defines the default ratio between the bass level and its moving average
+/// above which a beat is detected
+const BEAT_THRESHOLD: f32 = 1.5;
+
+/// Defines the time in seconds the moving average of the bass level follows
+/// the bass level
+const BEAT_AVERAGE_TIME: f64 = 1.0;
+
+/// Defines the minimal time in seconds between two beats
+const BEAT_MIN_INTERVAL: f64 = 0.25;
+
+/// Defines the minimal bass level of a beat, so noise in silent passages is
+/// not detected as beats
+const BEAT_MIN_LEVEL: f32 = 0.05;
+
+/// Stores the settings of the data publisher module
+#[derive(Clone, PartialEq)]
+pub struct DataPublisherSettings {
+    /// Represents if the data is published
+    pub enabled: bool,
+    /// The address the data is sent to
+    pub address: String,
+    /// The ratio between the bass level and its moving average above which a
+    /// beat is detected
+    pub beat_threshold: f32,
+}
+
+impl Default for DataPublisherSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: DATA_PUBLISHER_ADDRESS.to_string(),
+            beat_threshold: BEAT_THRESHOLD,
+        }
+    }
+}
+
+/// Publishes the data driving the visuals of every frame, so external tools
+/// like lighting controllers can follow the music. Every frame is sent as a
+/// single UDP datagram containing a JSON object like
+/// `{"time":1.5,"loudness":0.4,"beat":true,"levels":[0.2,0.7],"spheres":[[0,1,0,0.5]]}`.
+/// `time` is in seconds, `levels` contains the band levels from bass to
+/// treble and `spheres` the position and radius of every sphere as
+/// `[x, y, z, radius]`. Beats are detected from sudden rises of the bass.
+pub struct DataPublisher {
+    settings: DataPublisherSettings,
+    socket: Option<UdpSocket>,
+    target: Option<SocketAddr>,
+    time: f64,
+    bass_average: f32,
+    last_beat: f64,
+}
+
+impl DataPublisher {
+    /// Returns if the data is published, which allows to skip collecting it
+    pub fn is_enabled(&self) -> bool {
+        self.socket.is_some() && self.target.is_some()
+    }
+
+    /// Publishes the data of one frame, which lasted `delta_time` seconds
+    pub fn publish(
+        &mut self,
+        delta_time: f64,
+        loudness: f32,
+        levels: &[f32],
+        spheres: &[[f32; 4]],
+    ) {
+        self.time += delta_time;
+
+        let beat = self.detect_beat(delta_time, levels);
+
+        let (socket, target) = match (&self.socket, self.target) {
+            (Some(socket), Some(target)) => (socket, target),
+            _ => return,
+        };
+
+        let mut message = format!(
+            "{{\"time\":{},\"loudness\":{},\"beat\":{},\"levels\":[",
+            self.time,
+            number(loudness),
+            beat
+        );
+
+        for (i, level) in levels.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(message, "{}{}", separator, number(*level)).unwrap();
+        }
+
+        message.push_str("],\"spheres\":[");
+
+        for (i, [x, y, z, radius]) in spheres.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            write!(
+                message,
+                "{}[{},{},{},{}]",
+                separator,
+                number(*x),
+                number(*y),
+                number(*z),
+                number(*radius)
+            )
+            .unwrap();
+        }
+
+        message.push_str("]}");
+
+        // nobody listening on the other side is not an error worth reporting
+        // every frame
+        let _ = socket.send_to(message.as_bytes(), target);
+    }
+
+    fn detect_beat(&mut self, delta_time: f64, levels: &[f32]) -> bool {
+        let bass_levels = &levels[..(levels.len() / 4).max(1).min(levels.len())];
+
+        if bass_levels.is_empty() {
+            return false;
+        }
+
+        let bass = bass_levels.iter().sum::<f32>() / bass_levels.len() as f32;
+
+        let beat = bass > BEAT_MIN_LEVEL
+            && bass > self.bass_average * self.settings.beat_threshold
+            && self.time - self.last_beat >= BEAT_MIN_INTERVAL;
+
+        if beat {
+            self.last_beat = self.time;
+        }
+
+        let factor = (delta_time / BEAT_AVERAGE_TIME).min(1.0) as f32;
+        self.bass_average += (bass - self.bass_average) * factor;
+
+        beat
+    }
+
+    fn update_socket(&mut self) {
+        self.socket = None;
+        self.target = None;
+
+        if !self.settings.enabled {
+            return;
+        }
+
+        match self.settings.address.to_socket_addrs() {
+            Ok(mut addresses) => self.target = addresses.next(),
+            Err(error) => {
+                eprintln!(
+                    "Failed to resolve data publisher address {}: {}",
+                    self.settings.address, error
+                );
+                return;
+            }
+        }
+
+        let bind_address = match self.target {
+            Some(SocketAddr::V6(_)) => "[::]:0",
+            _ => "0.0.0.0:0",
+        };
+
+        match UdpSocket::bind(bind_address) {
+            Ok(socket) => self.socket = Some(socket),
+            Err(error) => eprintln!("Failed to open data publisher socket: {}", error),
+        }
+    }
+}
+
+impl Default for DataPublisher {
+    fn default() -> Self {
+        Self {
+            settings: DataPublisherSettings::default(),
+            socket: None,
+            target: None,
+            time: 0.0,
+            bass_average: 0.0,
+            last_beat: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl Module for DataPublisher {
+    type Settings = DataPublisherSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        if self.settings != settings {
+            let reconnect = self.settings.enabled != settings.enabled
+                || self.settings.address != settings.address;
+
+            self.settings = settings;
+
+            if reconnect {
+                self.update_socket();
+            }
+        }
+
+        self
+    }
+
+    fn settings(&self) -> Self::Settings {
+        self.settings.clone()
+    }
+}
+
+/// JSON has no representation for NaN and infinity, so they are sent as 0
+fn number(value: f32) -> f32 {
+    if value.is_finite() {
+        value
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use crate::{
+    audio_analysis::Samples,
+    module::ModuleManager,
+    rendering::wgpu::{OffscreenTargetOutput, OutputFormat},
+};
+
+use super::{FrameHashLog, OfflineVisualizer, Visualizer, VisualizerPreset};
+
+/// A section of the track, e.g. the intro, drop or outro
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Section {
+    /// The start of the section in seconds
+    pub start: f64,
+    /// The index of the preset the section is rendered with
+    pub preset: usize,
+}
+
+/// An offline visualizer, which switches between the visualizers of presets at
+/// the boundaries of sections. The time is measured by the samples passed to
+/// [`OfflineVisualizer::visualize`]. Before the first section starts, the
+/// first section is rendered. Every visualizer is fed with all samples, so
+/// the analysis and simulation continue seamlessly across the boundaries.
+pub struct SectionedVisualizer {
+    visualizers: Vec<Box<dyn OfflineVisualizer>>,
+    sections: Vec<(f64, usize)>,
+    time: f64,
+    frame_hashes: Option<FrameHashLog>,
+}
+
+impl SectionedVisualizer {
+    /// Creates a new instance. Only the presets used by a section get an
+    /// offline visualizer. Sections referencing a preset which does not exist
+    /// are ignored. Returns None if no section is left.
+    pub fn from_presets(
+        presets: &mut [VisualizerPreset],
+        sections: &[Section],
+        format: OutputFormat,
+    ) -> Option<Self> {
+        let mut visualizers = Vec::new();
+        let mut preset_ids = Vec::new();
+
+        let mut sections = sections
+            .iter()
+            .filter_map(|section| {
+                let visualizer_id = match preset_ids.iter().position(|id| *id == section.preset) {
+                    Some(visualizer_id) => visualizer_id,
+                    None => {
+                        visualizers
+                            .push(presets.get_mut(section.preset)?.offline_visualizer(format));
+                        preset_ids.push(section.preset);
+                        visualizers.len() - 1
+                    }
+                };
+
+                Some((section.start, visualizer_id))
+            })
+            .collect::<Vec<_>>();
+
+        if sections.is_empty() {
+            return None;
+        }
+
+        sections.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Some(Self {
+            visualizers,
+            sections,
+            time: 0.0,
+            frame_hashes: None,
+        })
+    }
+
+    /// Returns the index of the visualizer of the section at the current time
+    fn active_visualizer_id(&self) -> Option<usize> {
+        let (_, visualizer_id) = self
+            .sections
+            .iter()
+            .take_while(|(start, _)| *start <= self.time)
+            .last()
+            .or_else(|| self.sections.first())?;
+
+        Some(*visualizer_id)
+    }
+}
+
+impl Visualizer for SectionedVisualizer {
+    fn module_bin(self: Box<Self>, module_manager: &mut ModuleManager) {
+        for visualizer in self.visualizers {
+            visualizer.module_bin(module_manager);
+        }
+    }
+
+    fn store_settings(&self, module_manager: &mut ModuleManager) {
+        if let Some(visualizer_id) = self.active_visualizer_id() {
+            self.visualizers[visualizer_id].store_settings(module_manager);
+        }
+    }
+
+    fn load_settings(&mut self, module_manager: &ModuleManager) {
+        if let Some(visualizer_id) = self.active_visualizer_id() {
+            self.visualizers[visualizer_id].load_settings(module_manager);
+        }
+    }
+
+    fn set_bpm(&mut self, bpm: Option<f32>) {
+        for visualizer in &mut self.visualizers {
+            visualizer.set_bpm(bpm);
+        }
+    }
+}
+
+impl OfflineVisualizer for SectionedVisualizer {
+    fn visualize(&mut self, samples: Samples, width: u32, height: u32) -> OffscreenTargetOutput {
+        let active_visualizer_id = self.active_visualizer_id().unwrap_or_default();
+
+        self.time += samples.duration();
+
+        for (visualizer_id, visualizer) in self.visualizers.iter_mut().enumerate() {
+            if visualizer_id != active_visualizer_id {
+                visualizer.warm_up(samples.clone());
+            }
+        }
+
+        let output = self.visualizers[active_visualizer_id].visualize(samples, width, height);
+
+        if let Some(frame_hashes) = &mut self.frame_hashes {
+            frame_hashes.record(output.perceptual_hash());
+        }
+
+        output
+    }
+
+    fn warm_up(&mut self, samples: Samples) {
+        for visualizer in &mut self.visualizers {
+            visualizer.warm_up(samples.clone());
+        }
+    }
+
+    fn cache_analysis(&mut self, directory: &Path, track: u64) {
+        for visualizer in &mut self.visualizers {
+            visualizer.cache_analysis(directory, track);
+        }
+    }
+
+    // the frames of every section are hashed into a single log
+    fn hash_frames(&mut self, path: &Path) {
+        self.frame_hashes = Some(FrameHashLog::create(path));
+    }
+
+    fn end_of_track(&mut self) {
+        for visualizer in &mut self.visualizers {
+            visualizer.end_of_track();
+        }
+
+        if let Some(frame_hashes) = &mut self.frame_hashes {
+            frame_hashes.complete();
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use sphere_audio_visualizer_core::glam::Vec2;
+
+use crate::{audio_analysis::SpectrumSettings, module::Module, rendering::ProjectedSphere};
+
+/// Stores the settings of the sphere labels module
+#[derive(Clone, Default, PartialEq)]
+pub struct SphereLabelsSettings {
+    /// If the labels are shown
+    pub enabled: bool,
+}
+
+/// A label of a sphere on the screen
+#[derive(Clone, Debug)]
+pub struct SphereLabel {
+    /// The position of the sphere center on the screen in pixels
+    pub position: Vec2,
+    /// The center frequency of the band the sphere represents
+    pub frequency: f32,
+    /// The current level of the band
+    pub level: f32,
+}
+
+/// A debug overlay showing the center frequency and current level of the band
+/// next to every sphere, which helps tuning the frequency range of the
+/// spectrum. The spheres are projected with the camera of the scene
+/// converter, so only converters with a 3D camera produce labels.
+#[derive(Default)]
+pub struct SphereLabels {
+    enabled: bool,
+    labels: Vec<SphereLabel>,
+}
+
+impl SphereLabels {
+    /// Returns if the labels are shown, which allows to skip projecting the
+    /// spheres
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Sets if the labels are shown
+    pub fn set_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.enabled = enabled;
+        if !enabled {
+            self.labels.clear();
+        }
+        self
+    }
+
+    /// Sets if the labels are shown
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.set_enabled(enabled);
+        self
+    }
+
+    /// Returns the labels of the last frame
+    pub fn labels(&self) -> &[SphereLabel] {
+        &self.labels
+    }
+
+    /// Replaces the labels with the projected spheres of the current frame
+    pub fn update(&mut self, spectrum: &SpectrumSettings, spheres: Vec<ProjectedSphere>) {
+        self.labels.clear();
+        self.labels
+            .extend(spheres.into_iter().map(|sphere| SphereLabel {
+                position: sphere.position,
+                frequency: spectrum.center_frequency(sphere.band),
+                level: sphere.level,
+            }));
+    }
+}
+
+impl Module for SphereLabels {
+    type Settings = SphereLabelsSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_enabled(settings.enabled)
+    }
+
+    fn settings(&self) -> Self::Settings {
+        SphereLabelsSettings {
+            enabled: self.is_enabled(),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use crate::module::Module;
+
+/// Defines the default speed of the visualization, which runs in real time
+pub const TRANSPORT_SPEED: f32 = 1.0;
+
+/// Stores the settings of the transport module
+#[derive(Clone, PartialEq)]
+pub struct TransportSettings {
+    /// If the simulation is frozen
+    pub paused: bool,
+    /// The factor the simulation is slowed down or sped up by
+    pub speed: f32,
+    /// The number of single steps still to be simulated while paused
+    pub steps: u32,
+}
+
+impl Default for TransportSettings {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: TRANSPORT_SPEED,
+            steps: 0,
+        }
+    }
+}
+
+/// Controls the clock of the simulation independent of the audio playback,
+/// which helps debugging scene converters and capturing still frames. The
+/// simulation can be frozen, advanced by single steps or slowed down, while
+/// the audio keeps being analyzed. The transport only affects the window, so
+/// its settings are not stored with presets and exports.
+pub struct Transport {
+    paused: bool,
+    speed: f32,
+    steps: u32,
+}
+
+impl Transport {
+    /// Returns if the simulation is frozen
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Sets if the simulation is frozen. Resuming drops pending steps.
+    pub fn set_paused(&mut self, paused: bool) -> &mut Self {
+        self.paused = paused;
+        if !paused {
+            self.steps = 0;
+        }
+        self
+    }
+
+    /// Sets if the simulation is frozen
+    pub fn with_paused(mut self, paused: bool) -> Self {
+        self.set_paused(paused);
+        self
+    }
+
+    /// Returns the factor the simulation is slowed down or sped up by
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the factor the simulation is slowed down or sped up by
+    pub fn set_speed(&mut self, speed: f32) -> &mut Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Sets the factor the simulation is slowed down or sped up by
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.set_speed(speed);
+        self
+    }
+
+    /// Returns the number of single steps still to be simulated
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Requests a single simulation step, which is only taken while paused
+    pub fn step(&mut self) -> &mut Self {
+        self.steps += 1;
+        self
+    }
+
+    /// Scales the elapsed time of the audio to the elapsed time of the
+    /// simulation. No time elapses while paused.
+    pub fn scale(&self, delta_time: Duration) -> Duration {
+        match self.paused {
+            true => Duration::ZERO,
+            false => delta_time.mul_f32(self.speed),
+        }
+    }
+
+    /// Takes a pending single step. Returns if a step has to be simulated.
+    pub fn take_step(&mut self) -> bool {
+        match self.paused && self.steps > 0 {
+            true => {
+                self.steps -= 1;
+                true
+            }
+            false => false,
+        }
+    }
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: TRANSPORT_SPEED,
+            steps: 0,
+        }
+    }
+}
+
+impl Module for Transport {
+    type Settings = TransportSettings;
+
+    fn set_settings(&mut self, settings: Self::Settings) -> &mut Self {
+        self.set_paused(settings.paused).set_speed(settings.speed);
+        self.steps = settings.steps;
+        self
+    }
+
+    fn settings(&self) -> Self::Settings {
+        TransportSettings {
+            paused: self.is_paused(),
+            speed: self.speed(),
+            steps: self.steps(),
+        }
+    }
+}
@@ -1,25 +1,35 @@
-use std::{marker::PhantomData, time::Duration};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::Path,
+    time::{Duration, Instant},
+};
 
 use winit::window::Window;
 
 use crate::{
-    audio_analysis::{Samples, Spectrum},
+    audio_analysis::{AnalysisCache, Loudness, Samples, Spectrum},
     module::{Module, ModuleManager},
     rendering::{
         wgpu::{
             utils::CommandQueue,
-            Pipeline, WGPURenderer, {EGUIRenderer, EGUIScene},
+            AdapterSelection, ColorVisionFilter, HdrOutput, Pipeline, WGPURenderer, HDR_FORMAT,
+            {EGUIRenderer, EGUIScene},
             {
                 RenderTarget, RenderTargetTexture, SurfaceTarget,
                 {OffscreenTarget, OffscreenTargetOutput, OutputFormat},
             },
         },
-        SceneConverter,
+        CameraControl, SceneConverter,
     },
-    simulation::{SimulationResampler, Simulator},
+    simulation::{BandMask, RadiusSmoother, SimulationResampler, Simulator},
 };
 
-use super::{OfflineVisualizer, OnlineVisualizer, Visualizer, VisualizerFactory};
+use super::{
+    DataPublisher, FrameHashLog, FrameProfiler, Intensity, OfflineVisualizer, OnlineVisualizer,
+    ProfileStage, SphereLabels, Transport, Visualizer, VisualizerFactory,
+};
 
 /// A Visualizer Implementation for WGPU based visualizers.
 pub struct WGPUVisualizer<S, SC, P, T>
@@ -30,14 +40,26 @@ where
     T: RenderTarget,
 {
     pub(crate) spectrum: Spectrum,
+    pub(crate) loudness: Loudness,
+    pub(crate) intensity: Intensity,
+    pub(crate) data_publisher: DataPublisher,
     pub(crate) simulation_resampler: SimulationResampler,
+    pub(crate) band_mask: BandMask,
+    pub(crate) radius_smoother: RadiusSmoother,
     pub(crate) simulator: S,
     pub(crate) scene_converter: SC,
     pub(crate) pipeline: P,
-    renderer: WGPURenderer,
+    pub(crate) renderer: WGPURenderer,
+    pub(crate) color_vision_filter: ColorVisionFilter,
+    pub(crate) hdr_output: HdrOutput,
+    pub(crate) sphere_labels: SphereLabels,
+    pub(crate) transport: Transport,
+    pub(crate) profiler: FrameProfiler,
     target: T,
     egui_renderer: EGUIRenderer,
     levels: Vec<f32>,
+    analysis_cache: Option<AnalysisCache>,
+    frame_hashes: Option<FrameHashLog>,
 }
 
 impl<S, SC, P, T> WGPUVisualizer<S, SC, P, T>
@@ -48,12 +70,110 @@ where
     T: RenderTarget,
 {
     fn simulate(&mut self, samples: Samples) {
-        let delta_time =
-            Duration::from_secs_f64(samples.samples.len() as f64 / samples.sample_rate);
+        // empty batches carry no time, but would reset the envelopes of the
+        // analysis to their sample rate
+        if samples.samples.is_empty() {
+            return;
+        }
+
+        // the audio is analyzed in real time, only the simulation follows
+        // the transport
+        let delta_time = self
+            .transport
+            .scale(Duration::from_secs_f64(samples.duration()));
+
+        let start = Instant::now();
+        if !self.transport.is_paused() {
+            self.simulator.feed(&samples);
+        }
+        let start = self.profiler.record(ProfileStage::Simulator, start);
+
+        self.loudness.tick(samples.clone());
+        self.levels = match &mut self.analysis_cache {
+            Some(analysis_cache) => analysis_cache.levels(|| self.spectrum.analyze(samples)),
+            None => self.spectrum.analyze(samples),
+        };
+        self.profiler.record(ProfileStage::Spectrum, start);
+
+        for _ in 0..self.simulation_resampler.advance(delta_time) {
+            self.step();
+        }
+    }
+
+    /// Advances the simulation by one fixed step with the current levels
+    fn step(&mut self) {
+        let start = Instant::now();
+        let step_duration = self.simulation_resampler.step_duration();
+
+        let levels = self.band_mask.apply(&self.levels);
+        let levels = self.intensity.scale_levels(levels);
+        let levels = self.radius_smoother.smooth(step_duration, levels);
+        self.simulator.step(step_duration, levels);
+        self.scene_converter.advance(step_duration);
+
+        self.profiler.record(ProfileStage::Simulator, start);
+    }
+
+    /// Feeds the samples of one frame to the analysis and simulation
+    fn advance(&mut self, samples: Samples) {
+        let start = Instant::now();
+        let samples = self.simulation_resampler.ingest(samples);
+
+        self.simulation_resampler.begin_frame(
+            self.transport
+                .scale(Duration::from_secs_f64(samples.duration())),
+        );
+
+        for samples in self.simulation_resampler.resample(samples) {
+            self.simulate(samples);
+        }
+
+        if self.transport.take_step() {
+            self.step();
+        }
 
-        self.levels = self.spectrum.tick_par(samples).collect();
+        // the resampler is left after removing the stages it drives
+        self.profiler.record(ProfileStage::Resampler, start);
+        self.profiler
+            .subtract(ProfileStage::Resampler, ProfileStage::Spectrum);
+        self.profiler
+            .subtract(ProfileStage::Resampler, ProfileStage::Simulator);
+    }
+
+    /// Combines the key of the track with every setting the band levels
+    /// depend on. The hash is not stable across Rust versions, which only
+    /// causes the analysis to be recorded again.
+    fn analysis_key(&self, track: u64) -> u64 {
+        let spectrum = self.spectrum.settings();
+        let simulation_resampler = self.simulation_resampler.settings();
+
+        let mut hasher = DefaultHasher::new();
+
+        track.hash(&mut hasher);
+        spectrum.count.hash(&mut hasher);
+        simulation_resampler.max_steps_per_frame.hash(&mut hasher);
+        (simulation_resampler.ingestion_policy as u8).hash(&mut hasher);
+
+        for value in [
+            spectrum.low,
+            spectrum.high,
+            spectrum.threshold,
+            spectrum.attack,
+            spectrum.release,
+        ] {
+            value.to_bits().hash(&mut hasher);
+        }
 
-        self.simulator.step(delta_time, &self.levels);
+        for value in [
+            simulation_resampler.simulator_framerate,
+            simulation_resampler.max_frame_time,
+            simulation_resampler.frame_time_smoothing,
+            simulation_resampler.max_batch_time,
+        ] {
+            value.to_bits().hash(&mut hasher);
+        }
+
+        hasher.finish()
     }
 }
 
@@ -71,8 +191,29 @@ where
         height: u32,
         egui_scene: Option<EGUIScene>,
     ) -> <T::Texture as RenderTargetTexture>::Output {
-        for samples in self.simulation_resampler.resample(samples) {
-            self.simulate(samples);
+        let frame_time = samples.duration();
+
+        self.profiler.begin_frame();
+        self.advance(samples);
+
+        if self.data_publisher.is_enabled() {
+            self.data_publisher.publish(
+                frame_time,
+                self.loudness.level(),
+                &self.levels,
+                &self.simulator.spheres(),
+            );
+        }
+
+        let start = Instant::now();
+
+        if self.sphere_labels.is_enabled() {
+            let spheres =
+                self.scene_converter
+                    .project(self.simulator.scene(), width as f32, height as f32);
+
+            self.sphere_labels
+                .update(&self.spectrum.settings(), spheres);
         }
 
         let simulator_scene = self.simulator.scene();
@@ -80,37 +221,111 @@ where
         let renderer_scene =
             self.scene_converter
                 .convert(simulator_scene, width as f32, height as f32);
+        let start = self.profiler.record(ProfileStage::Converter, start);
 
+        // acquiring the texture waits for the previous frame to be presented
         let output_texture = self
             .target
             .target_texture(width, height, &self.renderer.device());
+        let start = self.profiler.record(ProfileStage::Present, start);
+
+        self.pipeline
+            .set_loudness(self.intensity.scale_loudness(self.loudness.level()));
 
-        let mut command_queue = CommandQueue::new(self.renderer.queue());
+        let memory_tracker = self.renderer.memory_tracker();
+        memory_tracker.begin_frame();
+        memory_tracker.track_texture(width, height, self.target.target_format());
+
+        let mut command_queue =
+            CommandQueue::new(self.renderer.queue(), memory_tracker).with_label("visualizer frame");
 
         {
             let output_texture_view = output_texture.texture_view();
+            let target_format = self.target.target_format();
+
+            // HDR targets get the scene and the UI in separate textures, which
+            // are combined by the output transform
+            let hdr = target_format == HDR_FORMAT;
+
+            let hdr_texture_view = match hdr {
+                true => self.hdr_output.scene_texture(
+                    self.renderer.device(),
+                    &command_queue,
+                    width,
+                    height,
+                ),
+                false => output_texture_view,
+            };
+
+            // only the scene in the window is filtered, so the UI stays
+            // readable and exports are not affected by the preview
+            let filter_color_vision = egui_scene.is_some() && self.color_vision_filter.is_enabled();
+
+            let scene_texture_view = match filter_color_vision {
+                true => self.color_vision_filter.scene_texture(
+                    self.renderer.device(),
+                    &command_queue,
+                    width,
+                    height,
+                    target_format,
+                ),
+                false => hdr_texture_view,
+            };
 
             self.pipeline.render(
                 renderer_scene,
                 self.renderer.device(),
                 &mut command_queue,
-                self.target.target_format(),
-                &output_texture_view,
+                target_format,
+                scene_texture_view,
             );
 
+            if filter_color_vision {
+                self.color_vision_filter.render(
+                    self.renderer.device(),
+                    &mut command_queue,
+                    target_format,
+                    hdr_texture_view,
+                );
+            }
+
             if let Some(egui_scene) = egui_scene {
+                let (overlay_texture_view, overlay_format) = match hdr {
+                    true => self.hdr_output.overlay_texture(
+                        self.renderer.device(),
+                        &mut command_queue,
+                        width,
+                        height,
+                    ),
+                    false => (output_texture_view, target_format),
+                };
+
                 self.egui_renderer.render(
                     egui_scene,
                     self.renderer.device(),
                     &mut command_queue,
-                    self.target.target_format(),
-                    &output_texture_view,
+                    overlay_format,
+                    overlay_texture_view,
+                );
+            }
+
+            if hdr {
+                self.hdr_output.render(
+                    self.renderer.device(),
+                    &mut command_queue,
+                    target_format,
+                    output_texture_view,
                 );
             }
         }
 
+        let start = self.profiler.record(ProfileStage::Render, start);
+
         let output = output_texture.present(self.renderer.device(), &mut command_queue);
 
+        self.profiler.record(ProfileStage::Present, start);
+        self.profiler.end_frame();
+
         output
     }
 }
@@ -123,14 +338,50 @@ where
     T: RenderTarget + 'static,
 {
     fn module_bin(self: Box<Self>, module_manager: &mut ModuleManager) {
-        module_manager.insert(self.spectrum);
+        module_manager.insert_scoped(self.spectrum);
+        module_manager.insert_scoped(self.simulation_resampler);
+        module_manager.insert(self.loudness);
+        module_manager.insert(self.intensity);
+        module_manager.insert(self.data_publisher);
+        module_manager.insert(self.band_mask);
+        module_manager.insert(self.radius_smoother);
         module_manager.insert(self.simulator);
         module_manager.insert(self.scene_converter);
         module_manager.insert(self.pipeline);
+        module_manager.insert(self.color_vision_filter);
+        module_manager.insert(self.hdr_output);
+        module_manager.insert(self.sphere_labels);
+        module_manager.insert(self.transport);
         module_manager.insert_lossy(self.renderer);
         module_manager.insert_lossy(self.target);
         module_manager.insert_lossy(self.egui_renderer);
     }
+
+    fn store_settings(&self, module_manager: &mut ModuleManager) {
+        module_manager.insert_settings_scoped(&self.spectrum);
+        module_manager.insert_settings_scoped(&self.simulation_resampler);
+        module_manager.insert_settings(&self.loudness);
+        module_manager.insert_settings(&self.intensity);
+        module_manager.insert_settings(&self.data_publisher);
+        module_manager.insert_settings(&self.band_mask);
+        module_manager.insert_settings(&self.radius_smoother);
+        module_manager.insert_settings(&self.simulator);
+        module_manager.insert_settings(&self.scene_converter);
+        module_manager.insert_settings(&self.pipeline);
+        module_manager.insert_settings(&self.color_vision_filter);
+        module_manager.insert_settings(&self.hdr_output);
+        module_manager.insert_settings(&self.sphere_labels);
+    }
+
+    fn load_settings(&mut self, module_manager: &ModuleManager) {
+        module_manager.load_settings(&mut self.simulator);
+        module_manager.load_settings(&mut self.scene_converter);
+        module_manager.load_settings(&mut self.pipeline);
+    }
+
+    fn set_bpm(&mut self, bpm: Option<f32>) {
+        self.scene_converter.set_bpm(bpm);
+    }
 }
 
 impl<S, SC, P> OnlineVisualizer for WGPUVisualizer<S, SC, P, SurfaceTarget>
@@ -140,8 +391,18 @@ where
     P: Pipeline<SC::Scene> + Module + 'static,
 {
     fn visualize(&mut self, samples: Samples, width: u32, height: u32, egui_scene: EGUIScene) {
+        self.hdr_output.configure(&mut self.target);
+
         self.visualize(samples, width, height, Some(egui_scene))
     }
+
+    fn set_monitor(&mut self, monitor: Option<String>) {
+        self.hdr_output.set_monitor(monitor);
+    }
+
+    fn set_camera_control(&mut self, camera_control: CameraControl) {
+        self.scene_converter.set_camera_control(camera_control);
+    }
 }
 
 impl<S, SC, P> OfflineVisualizer for WGPUVisualizer<S, SC, P, OffscreenTarget>
@@ -151,7 +412,35 @@ where
     P: Pipeline<SC::Scene> + Module + 'static,
 {
     fn visualize(&mut self, samples: Samples, width: u32, height: u32) -> OffscreenTargetOutput {
-        self.visualize(samples, width, height, None)
+        let output = self.visualize(samples, width, height, None);
+
+        if let Some(frame_hashes) = &mut self.frame_hashes {
+            frame_hashes.record(output.perceptual_hash());
+        }
+
+        output
+    }
+
+    fn warm_up(&mut self, samples: Samples) {
+        self.advance(samples)
+    }
+
+    fn cache_analysis(&mut self, directory: &Path, track: u64) {
+        self.analysis_cache = Some(AnalysisCache::open(directory, self.analysis_key(track)));
+    }
+
+    fn hash_frames(&mut self, path: &Path) {
+        self.frame_hashes = Some(FrameHashLog::create(path));
+    }
+
+    fn end_of_track(&mut self) {
+        if let Some(analysis_cache) = &mut self.analysis_cache {
+            analysis_cache.complete();
+        }
+
+        if let Some(frame_hashes) = &mut self.frame_hashes {
+            frame_hashes.complete();
+        }
     }
 }
 
@@ -168,32 +457,64 @@ where
     type OfflineVisualizer = WGPUVisualizer<S, SC, P, OffscreenTarget>;
 
     fn new_online(window: &Window, mut module_manager: ModuleManager) -> Self::OnlineVisualizer {
-        let spectrum = module_manager.extract::<Spectrum>();
-        let simulation_resampler = module_manager.extract::<SimulationResampler>();
+        let spectrum = module_manager.extract_scoped::<Spectrum>();
+        let loudness = module_manager.extract::<Loudness>();
+        let intensity = module_manager.extract::<Intensity>();
+        let data_publisher = module_manager.extract::<DataPublisher>();
+        let simulation_resampler = module_manager.extract_scoped::<SimulationResampler>();
+        let band_mask = module_manager.extract::<BandMask>();
+        let radius_smoother = module_manager.extract::<RadiusSmoother>();
         let simulator = module_manager.extract::<S>();
         let scene_converter = module_manager.extract::<SC>();
-        let pipeline = module_manager.extract::<P>();
+        let mut pipeline = module_manager.extract::<P>();
+        let color_vision_filter = module_manager.extract::<ColorVisionFilter>();
+        let hdr_output = module_manager.extract::<HdrOutput>();
+        let sphere_labels = module_manager.extract::<SphereLabels>();
+        let transport = module_manager.extract::<Transport>();
+
+        let adapter_selection = module_manager
+            .stored::<AdapterSelection>()
+            .unwrap_or_default();
 
         let (renderer, target) = match (
             module_manager.extract_optional::<WGPURenderer>(),
             module_manager.extract_optional::<SurfaceTarget>(),
         ) {
             (Some(renderer), Some(surface_target)) => (renderer, surface_target),
-            _ => pollster::block_on(WGPURenderer::onscreen(window, None)).unwrap(),
+            _ => pollster::block_on(WGPURenderer::onscreen_with_adapter(
+                window,
+                None,
+                adapter_selection,
+            ))
+            .unwrap(),
         };
 
+        pipeline.prepare(&renderer);
+
         let egui_renderer = module_manager.extract_or_default::<EGUIRenderer>();
 
         Self::OnlineVisualizer {
             spectrum,
+            loudness,
+            intensity,
+            data_publisher,
             simulation_resampler,
+            band_mask,
+            radius_smoother,
             simulator,
             scene_converter,
             pipeline,
             renderer,
+            color_vision_filter,
+            hdr_output,
+            sphere_labels,
+            transport,
+            profiler: FrameProfiler::default(),
             target,
             egui_renderer,
             levels: vec![],
+            analysis_cache: None,
+            frame_hashes: None,
         }
     }
 
@@ -201,32 +522,65 @@ where
         format: OutputFormat,
         mut module_manager: ModuleManager,
     ) -> Self::OfflineVisualizer {
-        let spectrum = module_manager.extract::<Spectrum>();
-        let simulation_resampler = module_manager.extract::<SimulationResampler>();
+        let spectrum = module_manager.extract_scoped::<Spectrum>();
+        let loudness = module_manager.extract::<Loudness>();
+        let intensity = module_manager.extract::<Intensity>();
+        let data_publisher = module_manager.extract::<DataPublisher>();
+        let simulation_resampler = module_manager.extract_scoped::<SimulationResampler>();
+        let band_mask = module_manager.extract::<BandMask>();
+        let radius_smoother = module_manager.extract::<RadiusSmoother>();
         let simulator = module_manager.extract::<S>();
         let scene_converter = module_manager.extract::<SC>();
-        let pipeline = module_manager.extract::<P>();
+        let mut pipeline = module_manager.extract::<P>();
+        let color_vision_filter = module_manager.extract::<ColorVisionFilter>();
+        let hdr_output = module_manager.extract::<HdrOutput>();
+        let sphere_labels = module_manager.extract::<SphereLabels>();
+        // exports always run in real time
+        let transport = Transport::default();
 
-        let renderer = module_manager
-            .extract_or_else(|| pollster::block_on(WGPURenderer::offscreen(None)).unwrap());
+        let adapter_selection = module_manager
+            .stored::<AdapterSelection>()
+            .unwrap_or_default();
+
+        let renderer = module_manager.extract_or_else(|| {
+            pollster::block_on(WGPURenderer::offscreen_with_adapter(
+                None,
+                adapter_selection,
+            ))
+            .unwrap()
+        });
 
         let target = module_manager
             .extract_optional::<OffscreenTarget>()
             .filter(|target| target.format() == format)
             .unwrap_or_else(|| OffscreenTarget::new(format));
 
+        pipeline.prepare(&renderer);
+
         let egui_renderer = module_manager.extract_or_default::<EGUIRenderer>();
 
         Self::OfflineVisualizer {
             spectrum,
+            loudness,
+            intensity,
+            data_publisher,
             simulation_resampler,
+            band_mask,
+            radius_smoother,
             simulator,
             scene_converter,
             pipeline,
             renderer,
+            color_vision_filter,
+            hdr_output,
+            sphere_labels,
+            transport,
+            profiler: FrameProfiler::default(),
             target,
             egui_renderer,
             levels: vec![],
+            analysis_cache: None,
+            frame_hashes: None,
         }
     }
 }